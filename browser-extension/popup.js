@@ -151,6 +151,18 @@ async function handleSignup() {
 
 // Handle logout
 async function handleLogout() {
+  // Revoke the token server-side; clear local state even if this fails
+  if (authToken) {
+    try {
+      await fetch(`${API_BASE}/auth/logout`, {
+        method: 'POST',
+        headers: { 'Authorization': `Bearer ${authToken}` }
+      });
+    } catch (error) {
+      console.error('Logout request failed:', error);
+    }
+  }
+  
   await chrome.storage.local.remove(['authToken', 'userEmail']);
   authToken = null;
   currentUser = null;
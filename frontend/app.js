@@ -106,15 +106,54 @@ async function handleCheckPrices() {
         showToast('⏳ Checking prices...', 'info');
         const response = await fetch(`${API_BASE}/alerts/check`, {
             method: 'POST',
-            headers: { 'Authorization': `Bearer ${authToken}` }
+            headers: {
+                'Authorization': `Bearer ${authToken}`,
+                'Accept': 'text/event-stream'
+            }
         });
         
         if (!response.ok) {
             throw new Error('Failed to check prices');
         }
         
-        const result = await response.json();
-        showToast('✅ Price check completed!', 'success');
+        // Progress arrives as server-sent events: "event: <name>\ndata: <json>\n\n"
+        const reader = response.body.getReader();
+        const decoder = new TextDecoder();
+        let buffered = '';
+        let finished = null;
+        
+        while (true) {
+            const { done, value } = await reader.read();
+            if (done) break;
+            
+            buffered += decoder.decode(value, { stream: true });
+            const messages = buffered.split('\n\n');
+            buffered = messages.pop();
+            
+            for (const message of messages) {
+                const data = message.split('\n').find(line => line.startsWith('data:'));
+                if (!data) continue;
+                
+                const progress = JSON.parse(data.slice(5));
+                if (progress.event === 'checking') {
+                    showToast(`⏳ Checking ${progress.index}/${progress.total}...`, 'info');
+                } else if (progress.event === 'price') {
+                    showToast(`💰 Found ₹${progress.price.toLocaleString('en-IN')}`, progress.dropped ? 'success' : 'info');
+                } else if (progress.event === 'failed') {
+                    showToast(`⚠️ Failed: ${progress.reason.replace(/_/g, ' ')}`, 'error');
+                } else if (progress.event === 'finished') {
+                    finished = progress;
+                } else if (progress.event === 'aborted') {
+                    throw new Error(progress.error);
+                }
+            }
+        }
+        
+        if (finished) {
+            showToast(`✅ Checked ${finished.checked} alert(s), ${finished.drops} drop(s)`, 'success');
+        } else {
+            showToast('✅ Price check completed!', 'success');
+        }
         
         // Reload alerts after check
         setTimeout(loadAlerts, 1000);
@@ -150,7 +189,7 @@ function renderAlerts() {
 
 function createAlertCard(alert) {
     const currentPrice = alert.last_price || 'Not checked yet';
-    const isPriceDrop = alert.last_price && alert.last_price <= alert.target_price;
+    const isPriceDrop = alert.mode !== 'back_in_stock' && alert.last_price && alert.last_price <= alert.target_price;
     const priceClass = isPriceDrop ? 'price-drop' : 'price-current';
     
     return `
@@ -162,6 +201,16 @@ function createAlertCard(alert) {
             
             <div class="alert-url">${truncateUrl(alert.url)}</div>
             
+            ${alert.mode === 'back_in_stock' ? `
+            <div class="alert-prices">
+                <div class="price-info">
+                    <div class="price-label">Back-in-stock alert</div>
+                    <div class="price-value">
+                        ${alert.in_stock === undefined ? 'Not checked yet' : alert.in_stock ? '✅ In stock' : '⏳ Out of stock'}
+                    </div>
+                </div>
+            </div>
+            ` : `
             <div class="alert-prices">
                 <div class="price-info">
                     <div class="price-label">Target Price</div>
@@ -187,6 +236,7 @@ function createAlertCard(alert) {
                     </div>
                 ` : ''}
             </div>
+            `}
             
             <!-- Price History Chart -->
             <div class="price-history-section">
@@ -358,9 +408,10 @@ function renderPriceChart(alertId, history) {
 
 function updateStats() {
     const totalAlerts = alerts.length;
-    const priceDrops = alerts.filter(a => a.last_price && a.last_price <= a.target_price).length;
-    const avgTargetPrice = alerts.length > 0 
-        ? alerts.reduce((sum, a) => sum + a.target_price, 0) / alerts.length
+    const priceAlerts = alerts.filter(a => a.mode !== 'back_in_stock');
+    const priceDrops = priceAlerts.filter(a => a.last_price && a.last_price <= a.target_price).length;
+    const avgTargetPrice = priceAlerts.length > 0 
+        ? priceAlerts.reduce((sum, a) => sum + a.target_price, 0) / priceAlerts.length
         : 0;
     
     document.getElementById('totalAlerts').textContent = totalAlerts;
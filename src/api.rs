@@ -1,82 +1,345 @@
 use axum::{
-    extract::{Path, State},
-    http::{StatusCode, header, Method},
-    response::Json,
-    routing::{get, post, delete},
+    body::Body,
+    extract::{ConnectInfo, FromRef, Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    middleware,
+    response::{sse::{Event, KeepAlive, Sse}, Html, IntoResponse, Json, Response},
+    routing::{get, post, put, delete},
     Router,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use serde_json::json;
-use tower_http::cors::{CorsLayer, Any};
-use tower_http::services::ServeDir;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::compression::CompressionLayer;
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::TraceLayer;
 use uuid::Uuid;
 
+use crate::config::{CorsConfig, DiscoveryConfig, PlatformPolicy};
+use crate::chart;
 use crate::db::Database;
+use crate::discovery;
+use crate::frontend;
+use crate::maintenance::{reject_writes, Maintenance};
+use crate::legal::{flag_stale_consent, Terms};
 use crate::models::{
-    CreateAlertRequest, PriceAlert, AlertResponse,
-    SignupRequest, LoginRequest, AuthResponse, UserResponse
+    AlertMode, RenotifyPolicy, CreateAlertRequest, ReplaceAlertRequest, RelinkAlertRequest, PriceAlert, AlertResponse, AlertThreshold, SetThresholdsRequest,
+    SignupRequest, LoginRequest, AcceptTermsRequest, AuthResponse, LookupRequest, Role, User, UserResponse, SetPlanRequest, SetRoleRequest, SetTimezoneRequest, SetNotificationsPausedRequest, SetDefaultDiscountRequest, SetPrivacyRequest, SetWeeklySummaryRequest, SetMaintenanceRequest,
+    CreateChannelRequest, NotificationChannel,
+    CreateListingAlertRequest, ListingAlert,
+    CreateKeywordWatchRequest, KeywordWatch,
+    AlertTemplate, AlertTemplateRequest, Annotation, AnnotationKind, CreateAnnotationRequest, HistoryCursor,
+    MarkPurchasedRequest, Purchase, SavingsReport,
+    CreateProductRequest, PlatformComparison, Product,
+    ApiKey, CreateApiKeyRequest, CreateTokenRequest, DropFeedItem,
+    AccountExport, ExportedAlert, ExportedChannel, ExportedListingAlert, ExportedPreferences, ExportedProduct, ExportedTemplate, ExportedWatch, ImportSummary,
+    ACCOUNT_EXPORT_FORMAT, ACCOUNT_EXPORT_VERSION,
+    ScraperConfig, UpsertScraperConfigRequest, SitemapSource, CreateSitemapSourceRequest
 };
-use crate::email::EmailService;
-use crate::scraper_trait::detect_platform;
-use crate::worker::trigger_manual_check;
-use crate::auth::{AuthUser, generate_token, hash_password, verify_password};
+use crate::email::{app_base_url, EmailQueue, EmailService};
+use crate::metrics::{delivery_metrics, scrape_metrics};
+use crate::event_schema::{self, Versioned};
+use crate::etag::{etag_for, is_fresh, json_tagged, json_with_etag, not_modified, tagged};
+use crate::request_id::{annotate_errors, make_span, REQUEST_ID_HEADER};
+use crate::notifiers::NotifierRegistry;
+use crate::rate_limit::{client_ip, trusted_proxies, RateLimiter};
+use crate::storage::{local::LocalStorage, Storage};
+use crate::scraper_config::{validate_config, ScraperConfigStore};
+use crate::scraper_trait::{canonical_product_url, desktop_url, detect_platform, is_platform_host, PageKind};
+use crate::live_price::LivePrices;
+use crate::matcher::required_terms;
+use crate::plans::{Plan, PlanLimits};
+use crate::scrapers::{create_scraper, platform_site, MARKETPLACES, PLATFORMS};
+use crate::timezone;
+use crate::inbound_email::{self, InboundEmail};
+use crate::telegram_bot;
+use crate::unsubscribe::{self, UnsubscribeScope};
+use crate::url_resolver::UrlResolver;
+use crate::worker::{check_alert, trigger_manual_check, CheckProgress};
+use crate::auth::{enforce_scopes, AdminUser, ApiKeyUser, AuthUser, VerifiedUser, JwtKeys, LockoutPolicy, RouteGroup, Scope, SessionCookies, api_key_display_prefix, email_verification_required, generate_api_key, hash_api_key, hash_password, needs_rehash, verify_password};
 
 #[derive(Clone)]
 pub struct AppState {
     pub db: Database,
+    pub notifiers: NotifierRegistry,
+    /// Transactional account emails (lockouts etc.); None when SMTP isn't configured
+    pub email_queue: Option<EmailQueue>,
+    /// Per-IP budget for login attempts
+    pub login_limiter: RateLimiter,
+    /// Cached `scraper_configs` rows, reloaded after admin edits
+    pub scraper_configs: ScraperConfigStore,
+    /// Chart images and account exports (local disk or S3)
+    pub storage: Arc<dyn Storage>,
+    /// Unwraps shared short links / app deep links on alert creation
+    pub url_resolver: Arc<UrlResolver>,
+    /// On-demand scrapes behind `GET /alerts/:id/price/live`
+    pub live_prices: LivePrices,
+    /// Per-user budget for on-demand scrapes
+    pub live_price_limiter: RateLimiter,
+    /// Per-IP budget for the anonymous `POST /lookup`
+    pub lookup_limiter: RateLimiter,
+    /// Per-user budget for `POST /email/test`
+    pub email_test_limiter: RateLimiter,
+    /// Platforms new alerts may be created for
+    pub platforms: PlatformPolicy,
+    /// Runtime switch that turns away writes, shared with the worker
+    pub maintenance: Maintenance,
+    /// Signing and verification keys, loaded once at startup
+    pub jwt_keys: Arc<JwtKeys>,
 }
 
-pub fn create_router(db: Database) -> Router {
-    let state = AppState { db };
+impl AppState {
+    pub fn new(db: Database) -> Self {
+        AppState {
+            db,
+            notifiers: NotifierRegistry::new(),
+            email_queue: None,
+            login_limiter: Self::login_limiter_from_env(),
+            scraper_configs: ScraperConfigStore::new(),
+            storage: Arc::new(LocalStorage::from_env()),
+            url_resolver: Arc::new(UrlResolver::new()),
+            live_prices: LivePrices::from_env(),
+            live_price_limiter: Self::live_price_limiter_from_env(),
+            lookup_limiter: Self::lookup_limiter_from_env(),
+            email_test_limiter: Self::email_test_limiter_from_env(),
+            platforms: PlatformPolicy::from_env(),
+            maintenance: Maintenance::new(),
+            jwt_keys: Arc::new(JwtKeys::from_env().expect("Invalid JWT key configuration")),
+        }
+    }
     
-    // CORS configuration
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods([Method::GET, Method::POST, Method::DELETE])
-        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION]);
+    pub fn lookup_limiter_from_env() -> RateLimiter {
+        let per_hour = std::env::var("LOOKUP_MAX_PER_HOUR")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        RateLimiter::new(per_hour, Duration::from_secs(60 * 60))
+    }
     
-    // API routes
-    let api_routes = Router::new()
+    pub fn email_test_limiter_from_env() -> RateLimiter {
+        let per_hour = std::env::var("EMAIL_TEST_MAX_PER_HOUR")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(3);
+        RateLimiter::new(per_hour, Duration::from_secs(60 * 60))
+    }
+    
+    pub fn live_price_limiter_from_env() -> RateLimiter {
+        let per_hour = std::env::var("LIVE_PRICE_MAX_PER_HOUR")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(30);
+        RateLimiter::new(per_hour, Duration::from_secs(60 * 60))
+    }
+    
+    pub fn login_limiter_from_env() -> RateLimiter {
+        let per_window = std::env::var("LOGIN_IP_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(20);
+        RateLimiter::new(per_window, Duration::from_secs(15 * 60))
+    }
+}
+
+impl FromRef<AppState> for Database {
+    fn from_ref(state: &AppState) -> Self {
+        state.db.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<JwtKeys> {
+    fn from_ref(state: &AppState) -> Self {
+        state.jwt_keys.clone()
+    }
+}
+
+/// Cap on request bodies when `MAX_REQUEST_BODY_BYTES` isn't set; every endpoint takes small JSON
+const DEFAULT_MAX_REQUEST_BODY_BYTES: usize = 1024 * 1024;
+
+pub fn create_router(state: AppState) -> Router {
+    let max_body_bytes = std::env::var("MAX_REQUEST_BODY_BYTES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MAX_REQUEST_BODY_BYTES);
+
+    // CORS: only the deployed frontend's origin unless configured otherwise
+    let cors = CorsConfig::from_env().layer();
+    
+    // Scoped tokens and API keys only reach route groups their scopes cover
+    let scoped = |group: RouteGroup| middleware::from_fn_with_state((state.db.clone(), state.jwt_keys.clone(), group), enforce_scopes);
+    
+    // Public routes, and ones whose handlers do their own checks
+    let public_routes = Router::new()
         .route("/", get(health_check))
-        // Auth routes (public)
         .route("/auth/signup", post(signup))
         .route("/auth/login", post(login))
+        // Any token, scoped or not, can revoke itself
+        .route("/auth/logout", post(logout))
+        .route("/auth/unlock", get(unlock_account))
+        .route("/auth/verify", get(verify_email))
+        // One-click links from notification emails (public, signed token). GET only
+        // shows a confirmation page; the page's button and RFC 8058 one-click POST here.
+        .route("/unsubscribe", get(unsubscribe_page))
+        .route("/unsubscribe", post(unsubscribe))
+        .route("/auth/keys", get(list_signing_keys))
+        // Anonymous one-off price check (public, rate limited)
+        .route("/lookup", post(lookup_price))
+        .route("/validate-url", post(validate_url))
+        .route("/public/chart", get(public_chart))
+        .route("/stats/public", get(public_stats))
+        .route("/legal/current", get(current_terms))
+        .route("/meta", get(meta))
+        // Mail provider webhook for forwarded product links (shared secret)
+        .route("/inbound/email", post(inbound_email))
+        // Telegram bot webhook (secret token header)
+        .route("/telegram/webhook", post(telegram_webhook))
+        .route("/metrics", get(prometheus_metrics));
+    
+    // Account settings, channels and credentials (session tokens only)
+    let account_routes = Router::new()
         .route("/auth/me", get(get_current_user))
-        // Alert routes (protected)
+        .route("/auth/me/timezone", put(set_timezone))
+        .route("/auth/me/default-discount", put(set_default_discount))
+        .route("/auth/me/notifications", put(set_notifications_paused))
+        .route("/auth/me/privacy", put(set_privacy))
+        .route("/auth/me/weekly-summary", put(set_weekly_summary))
+        .route("/legal/accept", post(accept_terms))
+        .route("/auth/verify/resend", post(resend_verification))
+        .route("/auth/tokens", post(create_scoped_token))
+        .route("/telegram/link", post(create_telegram_link))
+        .route("/channels", get(list_channels))
+        .route("/channels", post(create_channel))
+        .route("/channels/:id", delete(delete_channel))
+        .route("/api-keys", post(create_api_key))
+        .route("/api-keys", get(list_api_keys))
+        .route("/api-keys/:id", delete(delete_api_key))
+        .route("/export", get(export_account))
+        .route("/import", post(import_account))
+        .route("/email/test", post(test_email))
+        .route_layer(scoped(RouteGroup::Account));
+    
+    // Alert routes (protected; alerts:read / alerts:write)
+    let alert_routes = Router::new()
         .route("/alerts", post(create_alert))
         .route("/alerts", get(list_alerts))
+        .route("/alerts/:id", put(replace_alert))
         .route("/alerts/:id", delete(delete_alert))
+        .route("/alerts/:id/notifications", put(set_alert_notifications_paused))
+        .route("/alerts/:id/relink", post(relink_alert))
         .route("/alerts/:id/history", get(get_price_history))
         .route("/alerts/:id/stats", get(get_price_stats))
-        .route("/email/test", post(test_email))
+        .route("/alerts/:id/chart.png", get(get_alert_chart))
+        .route("/alerts/:id/drops", get(get_alert_drops))
+        .route("/alerts/:id/thresholds", get(list_thresholds))
+        .route("/alerts/:id/thresholds", put(set_thresholds))
+        .route("/alerts/:id/timeline", get(get_alert_timeline))
+        .route("/alerts/:id/annotations", get(list_annotations))
+        .route("/alerts/:id/annotations", post(create_annotation))
+        .route("/alerts/:id/annotations/:annotation_id", delete(delete_annotation))
+        .route("/alerts/:id/purchase", post(mark_purchased))
+        .route("/alerts/:id/purchase", delete(unmark_purchased))
+        .route("/reports/savings", get(get_savings_report))
+        .route("/alerts/:id/price/live", get(get_live_price))
+        .route("/alerts/:id/check", post(check_alert_now))
         .route("/alerts/check", post(manual_price_check))
+        .route("/drops", get(list_drops))
+        .route("/products", post(create_product))
+        .route("/products", get(list_products))
+        .route("/products/:id/compare", get(compare_product))
+        .route("/history/by-url", get(get_history_by_url))
+        .route("/listing-alerts", post(create_listing_alert))
+        .route("/listing-alerts", get(list_listing_alerts))
+        .route("/listing-alerts/:id", delete(delete_listing_alert))
+        .route("/listing-alerts/:id/history", get(get_listing_price_history))
+        .route("/watches", post(create_keyword_watch))
+        .route("/watches", get(list_keyword_watches))
+        .route("/watches/:id", delete(delete_keyword_watch))
+        .route("/templates", post(create_alert_template))
+        .route("/templates", get(list_alert_templates))
+        .route("/templates/:id", put(update_alert_template))
+        .route("/templates/:id", delete(delete_alert_template))
+        .route("/integrations/new-drops", get(integration_new_drops))
+        .route("/events", get(event_stream))
+        .route("/events/schema", get(event_schemas))
+        .route_layer(scoped(RouteGroup::Alerts));
+    
+    // Admin routes
+    let admin_routes = Router::new()
+        .route("/admin/scraper-configs", get(list_scraper_configs))
+        .route("/admin/scraper-configs/:platform", put(upsert_scraper_config))
+        .route("/admin/scraper-configs/:platform", delete(delete_scraper_config))
+        .route("/admin/scrape-stats", get(get_scrape_stats))
+        .route("/admin/delivery-stats", get(get_delivery_stats))
+        .route("/admin/email/preview", get(preview_email))
+        .route("/admin/email/test", post(admin_test_email))
+        .route("/admin/users/:id/plan", put(set_user_plan))
+        .route("/admin/users/:id/role", put(set_user_role))
+        .route("/admin/maintenance", get(get_maintenance))
+        .route("/admin/maintenance", put(set_maintenance))
+        .route("/admin/worker/status", get(get_worker_status))
+        .route("/admin/alerts/check", post(admin_price_check))
+        .route("/admin/sitemaps", get(list_sitemap_sources))
+        .route("/admin/sitemaps", post(create_sitemap_source))
+        .route("/admin/sitemaps/:id", delete(delete_sitemap_source))
+        .route("/admin/sitemaps/:id/run", post(run_sitemap_source))
+        .route_layer(scoped(RouteGroup::Admin));
+    
+    // API routes
+    let api_routes = public_routes
+        .merge(account_routes)
+        .merge(alert_routes)
+        .merge(admin_routes)
+        .layer(middleware::from_fn_with_state((state.db.clone(), state.jwt_keys.clone()), flag_stale_consent))
+        .layer(middleware::from_fn_with_state(state.maintenance.clone(), reject_writes))
         .with_state(state)
         .layer(cors);
     
-    // Serve static frontend files
-    let frontend_service = ServeDir::new("frontend")
-        .append_index_html_on_directories(true);
-    
-    // Combine routes
+    // Combine routes; every response carries an x-request-id (generated unless the client sent one)
     Router::new()
-        .nest_service("/app", frontend_service)
+        .nest_service("/app", frontend::router())
         .merge(api_routes)
+        .layer(RequestBodyLimitLayer::new(max_body_bytes))
+        .layer(middleware::from_fn(annotate_errors))
+        // gzip/br by Accept-Encoding; SSE and tiny responses are left alone
+        .layer(CompressionLayer::new())
+        .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER))
+        .layer(TraceLayer::new_for_http().make_span_with(make_span))
+        .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
 }
 
-async fn health_check() -> Json<serde_json::Value> {
-    Json(json!({
-        "status": "healthy",
+// 503 while the database is unreachable, so orchestrators hold traffic until it's back
+async fn health_check(State(state): State<AppState>) -> (StatusCode, Json<serde_json::Value>) {
+    let database_ok = tokio::time::timeout(Duration::from_secs(3), state.db.ping())
+        .await
+        .is_ok_and(|result| result.is_ok());
+    
+    let maintenance = state.maintenance.status();
+    
+    let status = if database_ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    let health = match (database_ok, &maintenance) {
+        (false, _) => "unhealthy",
+        (true, Some(_)) => "maintenance",
+        (true, None) => "healthy",
+    };
+    (status, Json(json!({
+        "status": health,
         "service": "clothing-price-tracker",
         "version": "0.1.0",
-        "database": "supabase"
-    }))
+        "database": if database_ok { "ok" } else { "unreachable" },
+        "maintenance": maintenance
+    })))
 }
 
 // Authentication Handlers
 async fn signup(
     State(state): State<AppState>,
     Json(payload): Json<SignupRequest>,
-) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+) -> Result<(HeaderMap, Json<AuthResponse>), (StatusCode, String)> {
     // Validate email
     if !payload.email.contains('@') {
         return Err((StatusCode::BAD_REQUEST, "Invalid email address".to_string()));
@@ -88,11 +351,21 @@ async fn signup(
     }
     
     // Check if user already exists
-    if let Some(_) = state.db.get_user_by_email(&payload.email).await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))? {
+    if state.db.get_user_by_email(&payload.email).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .is_some() {
         return Err((StatusCode::CONFLICT, "Email already registered".to_string()));
     }
     
+    // Signing up accepts the live terms; refuse if the client showed an older version
+    let terms = Terms::from_env();
+    if payload.terms_version.as_deref().is_some_and(|version| version != terms.version) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("The terms have changed (now version {}); review them and sign up again", terms.version),
+        ));
+    }
+    
     // Hash password
     let password_hash = hash_password(&payload.password)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to hash password: {}", e)))?;
@@ -100,201 +373,2615 @@ async fn signup(
     // Create user
     let user = state.db.create_user(&payload.email, &password_hash).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let user = state.db.accept_terms(user.id, &terms.version).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .unwrap_or(user);
+    
+    send_verification(&state, &user).await?;
     
     // Generate JWT token
-    let token = generate_token(user.id, user.email.clone())
+    let token = state.jwt_keys.issue(user.id, user.email.clone())
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to generate token: {}", e)))?;
     
-    Ok(Json(AuthResponse {
+    Ok((session_cookies(&state.jwt_keys, &token), Json(AuthResponse {
         token,
-        user: UserResponse {
-            id: user.id.to_string(),
-            email: user.email,
-            created_at: user.created_at,
-        },
-    }))
+        user: user.into(),
+    })))
+}
+
+/// Longest lifetime a scoped token can be given
+const MAX_SCOPED_TOKEN_TTL_DAYS: i64 = 365;
+
+// Scoped tokens are plain Bearer tokens (no cookies); revoke one by logging out with it
+async fn create_scoped_token(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateTokenRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
+    if payload.scopes.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "scopes can't be empty".to_string()));
+    }
+    
+    let keys = &state.jwt_keys;
+    let ttl = match payload.ttl_minutes {
+        Some(minutes) if minutes <= 0 || minutes > MAX_SCOPED_TOKEN_TTL_DAYS * 24 * 60 => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("ttl_minutes must be between 1 and {}", MAX_SCOPED_TOKEN_TTL_DAYS * 24 * 60),
+            ));
+        }
+        Some(minutes) => chrono::Duration::minutes(minutes),
+        None => keys.ttl(),
+    };
+    
+    let token = keys
+        .issue_scoped(auth_user.user_id, auth_user.email, &payload.scopes, ttl)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to generate token: {}", e)))?;
+    
+    Ok((StatusCode::CREATED, Json(json!({
+        "token": token,
+        "scopes": payload.scopes,
+        "expires_at": Utc::now() + ttl,
+    }))))
+}
+
+/// Session cookies to go with a newly issued token, unless cookie sessions are turned off
+fn session_cookies(keys: &JwtKeys, token: &str) -> HeaderMap {
+    SessionCookies::from_env()
+        .map(|cookies| cookies.issue(token, keys.ttl()))
+        .unwrap_or_default()
 }
 
 async fn login(
     State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+) -> Result<(HeaderMap, Json<AuthResponse>), (StatusCode, String)> {
+    // Throttle per client IP before touching the database
+    let ip = client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr), trusted_proxies());
+    if let Err(retry_after) = state.login_limiter.check(&ip).await {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many login attempts. Try again in {} seconds", retry_after.as_secs().max(1)),
+        ));
+    }
+    
     // Find user by email
     let user = state.db.get_user_by_email(&payload.email).await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid email or password".to_string()))?;
     
+    // Answered like an unknown email, so lockouts don't reveal which addresses have
+    // accounts; the owner hears about it (and gets an unlock link) by email
+    if user.locked_until.is_some_and(|until| until > Utc::now()) {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid email or password".to_string()));
+    }
+    
     // Verify password
     let valid = verify_password(&payload.password, &user.password_hash)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Password verification failed: {}", e)))?;
     
     if !valid {
+        record_failed_login(&state, &user).await?;
         return Err((StatusCode::UNAUTHORIZED, "Invalid email or password".to_string()));
     }
     
+    if user.failed_login_attempts > 0 || user.locked_until.is_some() {
+        state.db.reset_failed_logins(user.id).await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    }
+    
+    // Upgrade legacy bcrypt (or outdated Argon2) hashes while we have the plaintext
+    if needs_rehash(&user.password_hash) {
+        match hash_password(&payload.password) {
+            Ok(new_hash) => {
+                if let Err(e) = state.db.update_password_hash(user.id, &new_hash).await {
+                    tracing::error!("Failed to upgrade password hash for {}: {}", user.email, e);
+                }
+            }
+            Err(e) => tracing::error!("Failed to re-hash password for {}: {}", user.email, e),
+        }
+    }
+    
     // Generate JWT token
-    let token = generate_token(user.id, user.email.clone())
+    let token = state.jwt_keys.issue(user.id, user.email.clone())
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to generate token: {}", e)))?;
     
-    Ok(Json(AuthResponse {
+    Ok((session_cookies(&state.jwt_keys, &token), Json(AuthResponse {
         token,
-        user: UserResponse {
-            id: user.id.to_string(),
-            email: user.email,
-            created_at: user.created_at,
-        },
-    }))
+        user: user.into(),
+    })))
 }
 
-async fn get_current_user(
+async fn record_failed_login(state: &AppState, user: &User) -> Result<(), (StatusCode, String)> {
+    let policy = LockoutPolicy::from_env();
+    let locked_until = Utc::now() + policy.lockout;
+    let unlock_token = Uuid::new_v4().simple().to_string();
+    
+    let locked = state.db
+        .record_failed_login(user.id, policy.max_attempts, locked_until, &unlock_token)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !locked {
+        return Ok(());
+    }
+    
+    tracing::warn!("🔒 Locked account {} after {} failed logins", user.email, policy.max_attempts);
+    
+    match &state.email_queue {
+        Some(queue) => {
+            let unlock_url = format!("{}/auth/unlock?token={}", app_base_url(), unlock_token);
+            let until = timezone::format_local(locked_until, timezone::user_zone(&user.timezone));
+            if let Err(e) = queue.enqueue(EmailService::account_locked(&user.email, &unlock_url, &until)).await {
+                tracing::error!("Failed to queue unlock email: {}", e);
+            }
+        }
+        None => tracing::warn!("Email service not configured - no unlock email sent"),
+    }
+    
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct UnlockQuery {
+    token: String,
+}
+
+async fn unlock_account(
+    State(state): State<AppState>,
+    Query(query): Query<UnlockQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let unlocked = state.db
+        .unlock_user(&query.token)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !unlocked {
+        return Err((StatusCode::BAD_REQUEST, "Invalid or expired unlock link".to_string()));
+    }
+    
+    Ok(Json(json!({ "message": "Account unlocked. You can sign in again." })))
+}
+
+/// Issue a fresh verification link for `user` and email it, if email is set up
+async fn send_verification(state: &AppState, user: &User) -> Result<(), (StatusCode, String)> {
+    let token = Uuid::new_v4().simple().to_string();
+    state.db
+        .set_verification_token(user.id, &token)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    match &state.email_queue {
+        Some(queue) => {
+            let verify_url = format!("{}/auth/verify?token={}", app_base_url(), token);
+            if let Err(e) = queue.enqueue(EmailService::verify_email(&user.email, &verify_url)).await {
+                tracing::error!("Failed to queue verification email: {}", e);
+            }
+        }
+        None => tracing::warn!("Email service not configured - no verification email sent"),
+    }
+    
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyQuery {
+    token: String,
+}
+
+async fn verify_email(
+    State(state): State<AppState>,
+    Query(query): Query<VerifyQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user = state.db
+        .verify_email(&query.token)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Invalid or already used verification link".to_string()))?;
+    tracing::info!("✉️  Verified {}", user.email);
+    
+    Ok(Json(json!({ "message": "Email address confirmed." })))
+}
+
+async fn resend_verification(
     auth_user: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<UserResponse>, (StatusCode, String)> {
-    let user = state.db.get_user_by_id(auth_user.user_id).await
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user = state.db
+        .get_user_by_id(auth_user.user_id)
+        .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
         .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+    if user.email_verified_at.is_some() {
+        return Err((StatusCode::CONFLICT, "Email address is already confirmed".to_string()));
+    }
     
-    Ok(Json(UserResponse {
-        id: user.id.to_string(),
-        email: user.email,
-        created_at: user.created_at,
-    }))
+    send_verification(&state, &user).await?;
+    
+    Ok(Json(json!({ "message": format!("Verification link sent to {}", user.email) })))
 }
 
-async fn create_alert(
-    auth_user: AuthUser,
+#[derive(Deserialize)]
+struct UnsubscribeQuery {
+    token: String,
+}
+
+// Where an email's unsubscribe link lands. Opening it changes nothing, so link scanners
+// and prefetchers can't unsubscribe anyone; its button POSTs back to apply it.
+async fn unsubscribe_page(Query(query): Query<UnsubscribeQuery>) -> Result<Html<String>, (StatusCode, String)> {
+    let scope = unsubscribe::verify(&query.token)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Invalid unsubscribe link".to_string()))?;
+    
+    let (question, details, button) = match scope {
+        UnsubscribeScope::Alert(_) => (
+            "Pause this alert?",
+            "You won't get any more notifications about this product. It keeps being checked, and you can turn them back on from the app.",
+            "Pause alert",
+        ),
+        UnsubscribeScope::Account(_) => (
+            "Pause all notifications?",
+            "Nothing will be sent to you until you sign in and turn notifications back on.",
+            "Pause all notifications",
+        ),
+        UnsubscribeScope::WeeklySummary(_) => (
+            "Stop the weekly summary?",
+            "You won't get the weekly summary email any more. Price alerts are unaffected.",
+            "Stop weekly summary",
+        ),
+    };
+    // Re-signed from the verified scope, so only URL-safe characters end up in the page
+    let form = format!(
+        r#"<form method="post" action="/unsubscribe?token={}"><button type="submit">{}</button></form>"#,
+        unsubscribe::sign(scope),
+        button
+    );
+    
+    Ok(Html(unsubscribe_html(question, details, &form)))
+}
+
+fn unsubscribe_html(title: &str, details: &str, form: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1"><title>{title}</title></head>
+<body style="font-family: sans-serif; max-width: 32rem; margin: 3rem auto; padding: 0 1rem;">
+<h1>{title}</h1>
+<p>{details}</p>
+{form}
+</body>
+</html>
+"#
+    )
+}
+
+// Applies an unsubscribe link: the confirmation page's button, or a mail client's
+// RFC 8058 one-click request. Browsers get a page back, everything else JSON.
+async fn unsubscribe(
     State(state): State<AppState>,
-    Json(payload): Json<CreateAlertRequest>,
-) -> Result<(StatusCode, Json<AlertResponse>), (StatusCode, String)> {
-    // Detect platform from URL
-    let platform = detect_platform(&payload.url)
-        .ok_or_else(|| {
-            (
-                StatusCode::BAD_REQUEST,
-                "Unsupported platform. Supported: Myntra, Flipkart, Ajio, Tata Cliq".to_string(),
-            )
-        })?;
+    Query(query): Query<UnsubscribeQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let scope = unsubscribe::verify(&query.token)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Invalid unsubscribe link".to_string()))?;
     
-    // Validate target price
-    if payload.target_price <= 0.0 {
+    let message = match scope {
+        UnsubscribeScope::Alert(alert_id) => {
+            let paused = state.db
+                .pause_alert_notifications(alert_id)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            
+            if paused {
+                "Notifications for this alert are paused. Sign in to turn them back on."
+            } else {
+                "Notifications for this alert were already paused."
+            }
+        }
+        UnsubscribeScope::Account(user_id) => {
+            state.db
+                .set_notifications_paused(user_id, true)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .ok_or_else(|| (StatusCode::NOT_FOUND, "Account not found".to_string()))?;
+            
+            "All notifications are paused. Sign in to turn them back on."
+        }
+        UnsubscribeScope::WeeklySummary(user_id) => {
+            state.db
+                .set_weekly_summary(user_id, false)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .ok_or_else(|| (StatusCode::NOT_FOUND, "Account not found".to_string()))?;
+            
+            "You won't get the weekly summary any more. Price alerts are unaffected."
+        }
+    };
+    
+    let wants_html = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    if wants_html {
+        return Ok(Html(unsubscribe_html("Done", message, "")).into_response());
+    }
+    Ok(Json(json!({ "message": message })).into_response())
+}
+
+async fn logout(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<(StatusCode, HeaderMap), (StatusCode, String)> {
+    if auth_user.token_id.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            "Target price must be greater than 0".to_string(),
+            "This token predates logout support; it will expire on its own".to_string(),
         ));
     }
     
-    // Create alert document
-    let alert = PriceAlert {
-        id: None,
-        url: payload.url,
-        target_price: payload.target_price,
-        last_price: None,
-        user_email: payload.user_email,
-        user_id: Some(auth_user.user_id),
-        platform: platform.to_string(),
-        created_at: Utc::now(),
-        last_checked: Utc::now(),
-        is_active: true,
-    };
+    let expires_at = DateTime::from_timestamp(auth_user.token_expires_at, 0)
+        .unwrap_or_else(Utc::now);
     
-    // Insert into database
-    let created_alert = state.db
-        .create_alert(&alert)
+    state.db
+        .revoke_token(&auth_user.token_id, expires_at)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
-    Ok((StatusCode::CREATED, Json(created_alert.into())))
+    let cleared = SessionCookies::from_env().map(|cookies| cookies.clear()).unwrap_or_default();
+    Ok((StatusCode::NO_CONTENT, cleared))
 }
 
-async fn list_alerts(
+// RS256 public keys, so other services can verify our access tokens
+async fn list_signing_keys(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(json!({ "keys": state.jwt_keys.public_keys() }))
+}
+
+async fn get_current_user(
     auth_user: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<Vec<AlertResponse>>, (StatusCode, String)> {
-    let alerts = state.db
-        .get_alerts_by_user(auth_user.user_id)
-        .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
-    
-    let responses: Vec<AlertResponse> = alerts.into_iter().map(|a| a.into()).collect();
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let user = state.db.get_user_by_id(auth_user.user_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
     
-    Ok(Json(responses))
+    Ok(Json(UserResponse::from(user)))
 }
 
-async fn delete_alert(
+// Zone that timestamps in this user's notifications are shown in
+async fn set_timezone(
+    auth_user: AuthUser,
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    let uuid = Uuid::parse_str(&id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    Json(payload): Json<SetTimezoneRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let zone = timezone::parse(&payload.timezone).ok_or_else(|| {
+        (StatusCode::BAD_REQUEST, format!("Unknown timezone '{}'. Use an IANA name such as Asia/Kolkata", payload.timezone))
+    })?;
     
-    state.db
-        .delete_alert(uuid)
+    let user = state.db
+        .set_user_timezone(auth_user.user_id, zone.name())
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
     
-    Ok(StatusCode::NO_CONTENT)
+    Ok(Json(user.into()))
 }
 
-async fn manual_price_check(
+// "Alert me at N% off": used for price alerts created without a target
+async fn set_default_discount(
+    auth_user: AuthUser,
     State(state): State<AppState>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    trigger_manual_check(state.db)
+    Json(payload): Json<SetDefaultDiscountRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    if payload.percent.is_some_and(|percent| !(percent > 0.0 && percent < 100.0)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "percent must be between 0 and 100".to_string(),
+        ));
+    }
+    
+    let user = state.db
+        .set_default_discount(auth_user.user_id, payload.percent)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
     
-    Ok(Json(json!({ "message": "Price check triggered successfully" })))
+    Ok(Json(user.into()))
 }
 
-async fn test_email(
-    State(_state): State<AppState>,
-    Json(payload): Json<serde_json::Value>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let to_email = payload["email"]
-        .as_str()
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, "email field required".to_string()))?;
-    
-    let email_service = EmailService::from_env()
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Email not configured: {}", e)))?;
-    
-    email_service.send_test_email(to_email)
+async fn set_notifications_paused(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<SetNotificationsPausedRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let user = state.db
+        .set_notifications_paused(auth_user.user_id, payload.paused)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to send email: {}", e)))?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
     
-    Ok(Json(json!({ 
-        "message": format!("Test email sent to {}", to_email),
-        "status": "success"
-    })))
+    Ok(Json(user.into()))
 }
 
-async fn get_price_history(
+// The live terms of service, for signup forms and re-accept prompts
+async fn current_terms() -> Json<Terms> {
+    Json(Terms::from_env())
+}
+
+// Accept the live terms again after a bump; the version must match, so a client
+// can't accept text it never showed
+async fn accept_terms(
+    auth_user: AuthUser,
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let alert_id = Uuid::parse_str(&id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    Json(payload): Json<AcceptTermsRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let terms = Terms::from_env();
+    if payload.version != terms.version {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Version {} is not the current terms (now version {})", payload.version, terms.version),
+        ));
+    }
     
-    // Get last 30 price checks (default)
-    let history = state.db.get_price_history(alert_id, 30)
+    let user = state.db
+        .accept_terms(auth_user.user_id, &terms.version)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
     
-    Ok(Json(json!({
-        "alert_id": id,
-        "history": history,
-        "count": history.len()
-    })))
+    Ok(Json(user.into()))
 }
 
-async fn get_price_stats(
+async fn set_privacy(
+    auth_user: AuthUser,
     State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    let alert_id = Uuid::parse_str(&id)
-        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    Json(payload): Json<SetPrivacyRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let user = state.db
+        .set_exclude_from_public(auth_user.user_id, payload.exclude_from_public)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
     
-    let stats = state.db.get_price_stats(alert_id)
+    Ok(Json(user.into()))
+}
+
+async fn set_weekly_summary(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<SetWeeklySummaryRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let user = state.db
+        .set_weekly_summary(auth_user.user_id, payload.enabled)
         .await
-        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+    
+    Ok(Json(user.into()))
+}
+
+async fn create_alert(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateAlertRequest>,
+) -> Result<(StatusCode, Json<AlertResponse>), (StatusCode, String)> {
+    let alert = build_alert(&state, auth_user.user_id, &auth_user.email, payload).await?;
+    
+    ensure_alert_capacity(&state, auth_user.user_id).await?;
+    
+    // Insert into database
+    let created_alert = state.db
+        .create_alert(&alert)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(created_alert.into())))
+}
+
+// Forwarding a product link to the inbound address creates an alert for the
+// account whose email matches the sender. Disabled unless INBOUND_EMAIL_SECRET
+// is set; the provider passes it in an `X-Inbound-Secret` header, never the URL,
+// which ends up in access logs.
+async fn inbound_email(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(email): Json<InboundEmail>,
+) -> Result<(StatusCode, Json<AlertResponse>), (StatusCode, String)> {
+    let expected = std::env::var("INBOUND_EMAIL_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Inbound email is not enabled".to_string()))?;
+    let provided = headers.get("x-inbound-secret").and_then(|v| v.to_str().ok());
+    if !provided.is_some_and(|provided| inbound_email::secret_matches(&expected, provided)) {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid inbound email secret".to_string()));
+    }
+    
+    // Forged senders get no reply either, so they can't aim confirmations at someone else
+    if !email.sender_authenticated() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "Sender failed SPF and DKIM checks".to_string()));
+    }
+    
+    let sender = email
+        .sender_address()
+        .ok_or_else(|| (StatusCode::UNPROCESSABLE_ENTITY, "Unreadable sender address".to_string()))?;
+    // Unknown senders get no reply, so the address can't be used to probe for accounts. Nor
+    // do unconfirmed accounts: whoever signed up with an address may not own it.
+    let user = state.db
+        .get_user_by_email(&sender)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .filter(|user| user.email_verified_at.is_some())
+        .ok_or_else(|| (StatusCode::UNPROCESSABLE_ENTITY, "Sender has no verified account".to_string()))?;
+    
+    let result = alert_from_email(&state, &user, &email).await;
+    
+    if let Some(queue) = &state.email_queue {
+        let reply = match &result {
+            Ok(alert) => EmailService::alert_created_by_email(&user.email, &alert.url, alert.target_price),
+            Err((_, reason)) => EmailService::inbound_email_rejected(&user.email, reason),
+        };
+        if let Err(e) = queue.enqueue(reply).await {
+            tracing::error!("Failed to queue inbound email reply for {}: {}", user.email, e);
+        }
+    }
+    
+    let alert = result?;
+    tracing::info!("📨 Created alert {} for {} from a forwarded email", alert.url, user.email);
+    Ok((StatusCode::CREATED, Json(alert.into())))
+}
+
+/// Create a price alert from the first supported product link in a forwarded email
+async fn alert_from_email(state: &AppState, user: &User, email: &InboundEmail) -> Result<PriceAlert, (StatusCode, String)> {
+    let mut product = None;
+    for link in email.links() {
+        if let Ok(url) = state.url_resolver.resolve(link).await
+            && let Some(platform) = detect_platform(&url)
+        {
+            product = Some((url, platform));
+            break;
+        }
+    }
+    let (url, platform) = product.ok_or_else(|| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "No supported product link (Myntra, Flipkart, Ajio, Tata Cliq, Shoppers Stop, Lifestyle) found in the email".to_string(),
+        )
+    })?;
+    
+    // No target in the email: aim a little below today's price
+    let target_price = match email.requested_target() {
+        Some(target) => target,
+        None => {
+            let (live, _) = state.live_prices
+                .get(&state.scraper_configs, platform, &url)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!("Couldn't read the current price ({}); send the link again with a target like \"under 999\"", e),
+                    )
+                })?;
+            inbound_email::default_target(live.price, inbound_email::default_discount_percent())
+        }
+    };
+    
+    let payload = CreateAlertRequest {
+        url: Some(url),
+        platform: None,
+        product_id: None,
+        target_price: Some(target_price),
+        mode: AlertMode::Price,
+        user_email: None,
+        notify_channels: None,
+        check_interval_minutes: None,
+        expires_at: None,
+        renotify: None,
+        min_price: None,
+        max_price: None,
+        template_id: None,
+    };
+    let alert = build_alert(state, user.id, &user.email, payload).await?;
+    ensure_alert_capacity(state, user.id).await?;
+    
+    state.db
+        .create_alert(&alert)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+// A one-time code to send the bot (`/link CODE`), plus a t.me deep link that
+// sends it automatically when TELEGRAM_BOT_USERNAME is set
+async fn create_telegram_link(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
+    let code = telegram_bot::generate_link_code();
+    let expires_at = Utc::now() + chrono::Duration::minutes(telegram_bot::LINK_CODE_TTL_MINUTES);
+    
+    state.db
+        .create_telegram_link_code(auth_user.user_id, &code, expires_at)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let deep_link = std::env::var("TELEGRAM_BOT_USERNAME")
+        .ok()
+        .map(|bot| format!("https://t.me/{}?start={}", bot.trim_start_matches('@'), code));
+    
+    Ok((StatusCode::CREATED, Json(json!({
+        "code": code,
+        "expires_at": expires_at,
+        "link": deep_link,
+    }))))
+}
+
+// Telegram delivers every message sent to the bot here. Disabled unless
+// TELEGRAM_WEBHOOK_SECRET is set and passed as `secret_token` to setWebhook.
+// Replies go back in the response body, so no Bot API call is needed.
+async fn telegram_webhook(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(update): Json<telegram_bot::Update>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let expected = std::env::var("TELEGRAM_WEBHOOK_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Telegram bot is not enabled".to_string()))?;
+    let provided = headers
+        .get("x-telegram-bot-api-secret-token")
+        .and_then(|v| v.to_str().ok());
+    if !provided.is_some_and(|provided| inbound_email::secret_matches(&expected, provided)) {
+        return Err((StatusCode::UNAUTHORIZED, "Invalid Telegram secret token".to_string()));
+    }
+    
+    // Edits, joins, stickers... need no answer
+    let Some((chat_id, text)) = update.message.and_then(|m| m.text.map(|text| (m.chat.id, text))) else {
+        return Ok(Json(json!({})));
+    };
+    
+    let answer = match telegram_command(&state, chat_id, telegram_bot::Command::parse(&text)).await {
+        Ok(answer) => answer,
+        Err((StatusCode::INTERNAL_SERVER_ERROR, message)) => {
+            tracing::error!("Telegram command '{}' failed: {}", text, message);
+            "Something went wrong, please try again later.".to_string()
+        }
+        Err((_, message)) => message,
+    };
+    
+    Ok(Json(telegram_bot::reply(chat_id, &answer)))
+}
+
+/// Run a bot command for whoever linked `chat_id`; errors are replied as-is
+async fn telegram_command(
+    state: &AppState,
+    chat_id: i64,
+    command: telegram_bot::Command,
+) -> Result<String, (StatusCode, String)> {
+    use telegram_bot::Command;
+    
+    let internal = |e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    
+    if let Command::Link(code) = command {
+        let Some(code) = code else {
+            return Ok(format!(
+                "Get a code from your account (POST /telegram/link) and send /link <code> here.\n\n{}",
+                telegram_bot::HELP
+            ));
+        };
+        let user_id = state.db
+            .link_telegram_chat(&code, chat_id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, "That code is invalid or has expired. Create a new one and try again.".to_string()))?;
+        state.db
+            .create_channel(user_id, "telegram", &chat_id.to_string())
+            .await
+            .map_err(internal)?;
+        
+        return Ok(format!("✅ Linked! Price drops will be sent here too.\n\n{}", telegram_bot::HELP));
+    }
+    
+    let user = state.db
+        .get_telegram_chat_user(chat_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "This chat isn't linked yet. Send /link <code> with a code from your account.".to_string()))?;
+    
+    // /price and /delete refer to positions in /list, which is newest first
+    let nth_alert = |n: usize| async move {
+        state.db
+            .get_alerts_by_user(user.id)
+            .await
+            .map_err(internal)?
+            .into_iter()
+            .nth(n - 1)
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("There's no alert {}; send /list to see yours.", n)))
+    };
+    
+    match command {
+        Command::Track { url, target_price } => {
+            let payload = CreateAlertRequest {
+                url: Some(url),
+                platform: None,
+                product_id: None,
+                target_price: Some(target_price),
+                mode: AlertMode::Price,
+                user_email: None,
+                notify_channels: None,
+                check_interval_minutes: None,
+                expires_at: None,
+                renotify: None,
+                min_price: None,
+                max_price: None,
+                template_id: None,
+            };
+            let alert = build_alert(state, user.id, &user.email, payload).await?;
+            ensure_alert_capacity(state, user.id).await?;
+            let created = state.db.create_alert(&alert).await.map_err(internal)?;
+            
+            Ok(format!(
+                "👀 Tracking on {} - you'll hear from me at ₹{:.0} or less.\n{}",
+                created.platform.to_uppercase(),
+                created.target_price,
+                created.url
+            ))
+        }
+        Command::List => {
+            let alerts = state.db.get_alerts_by_user(user.id).await.map_err(internal)?;
+            if alerts.is_empty() {
+                return Ok("No active alerts. Add one with /track <url> <price>.".to_string());
+            }
+            
+            let lines: Vec<String> = alerts
+                .iter()
+                .enumerate()
+                .map(|(i, alert)| {
+                    let last = alert.last_price.map(|p| format!("₹{:.0}", p)).unwrap_or_else(|| "not checked yet".to_string());
+                    format!("{}. {} · {} · target ₹{:.0}\n{}", i + 1, alert.platform.to_uppercase(), last, alert.target_price, alert.url)
+                })
+                .collect();
+            Ok(lines.join("\n\n"))
+        }
+        Command::Price(n) => {
+            let alert = nth_alert(n).await?;
+            if let Err(retry_after) = state.live_price_limiter.check(&user.id.to_string()).await {
+                return Ok(format!("Too many live price checks. Try again in {} seconds.", retry_after.as_secs().max(1)));
+            }
+            
+            let (live, _) = state.live_prices
+                .get(&state.scraper_configs, &alert.platform, &alert.url)
+                .await
+                .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Couldn't fetch the current price: {}", e)))?;
+            let verdict = if live.price <= alert.target_price { "✅ at or below" } else { "above" };
+            
+            Ok(format!("₹{:.0} right now, {} your target of ₹{:.0}.\n{}", live.price, verdict, alert.target_price, alert.url))
+        }
+        Command::Delete(n) => {
+            let alert = nth_alert(n).await?;
+            if let Some(id) = alert.id {
+                state.db.delete_alert(id).await.map_err(internal)?;
+            }
+            
+            Ok(format!("🗑️ Stopped tracking {}", alert.url))
+        }
+        Command::Unlink => {
+            state.db.unlink_telegram_chat(chat_id).await.map_err(internal)?;
+            Ok("Unlinked. This chat won't get notifications any more.".to_string())
+        }
+        Command::Help | Command::Link(_) => Ok(telegram_bot::HELP.to_string()),
+        Command::Invalid(usage) => Ok(usage.to_string()),
+    }
+}
+
+/// 403 if the user's plan has no room for another alert
+async fn ensure_alert_capacity(state: &AppState, user_id: Uuid) -> Result<(), (StatusCode, String)> {
+    let (plan, limits) = user_plan(state, user_id).await?;
+    let active = state.db
+        .get_alerts_by_user(user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .len() as i64;
+    if active >= limits.max_alerts {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!(
+                "The {} plan allows up to {} alerts; delete one or upgrade to add more",
+                plan.as_str(),
+                limits.max_alerts
+            ),
+        ));
+    }
+    
+    Ok(())
+}
+
+/// The caller's plan and its limits
+async fn user_plan(state: &AppState, user_id: Uuid) -> Result<(Plan, PlanLimits), (StatusCode, String)> {
+    let user = state.db.get_user_by_id(user_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+    let plan = Plan::parse(&user.plan);
+    Ok((plan, plan.limits()))
+}
+
+// Replace every editable field. Fails with 409 if the alert changed since the
+// client read `version`, so two tabs can't silently overwrite each other.
+async fn replace_alert(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<ReplaceAlertRequest>,
+) -> Result<Json<AlertResponse>, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    let alert = build_alert(&state, auth_user.user_id, &auth_user.email, payload.alert).await?;
+    let before = state.db
+        .get_alert_for_user(uuid, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let replaced = state.db
+        .replace_alert(uuid, auth_user.user_id, payload.version, &alert)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if let Some(replaced) = replaced {
+        if let Some(before) = &before {
+            note_resumed(&state, before, &replaced).await;
+        }
+        return Ok(Json(replaced.into()));
+    }
+    
+    let current = state.db
+        .get_alert_for_user(uuid, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match current {
+        Some(current) => Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Alert was changed elsewhere (version {} -> {}); reload it and try again",
+                payload.version, current.version
+            ),
+        )),
+        None => Err((StatusCode::NOT_FOUND, "Alert not found".to_string())),
+    }
+}
+
+// Move an alert to a new URL for the same product (the retailer changed its slugs, or the
+// old page is gone), keeping its price history, target and settings
+async fn relink_alert(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<RelinkAlertRequest>,
+) -> Result<Json<AlertResponse>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    let alert = state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let url = state.url_resolver.resolve(&payload.url).await.map_err(|e| {
+        (StatusCode::BAD_REQUEST, format!("Could not resolve product link: {}", e))
+    })?;
+    if detect_platform(&url) != Some(alert.platform.as_str()) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("The new link must be a {} product page, like the alert's current one", alert.platform),
+        ));
+    }
+    let product_id = create_scraper(&alert.platform).and_then(|s| s.product_id(&url));
+    
+    let relinked = state.db
+        .relink_alert(alert_id, auth_user.user_id, &url, product_id.as_deref())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let note = format!("Moved from {}", alert.url);
+    if let Err(e) = state.db.create_annotation(alert_id, None, AnnotationKind::Relinked, Some(&note), None, Utc::now()).await {
+        tracing::error!("Failed to annotate relink: {}", e);
+    }
+    note_resumed(&state, &alert, &relinked).await;
+    Ok(Json(relinked.into()))
+}
+
+/// Mark the timeline when a new URL brings back an alert that was paused for a missing product
+async fn note_resumed(state: &AppState, before: &PriceAlert, after: &PriceAlert) {
+    if before.product_removed_at.is_none() || after.product_removed_at.is_some() {
+        return;
+    }
+    let Some(alert_id) = after.id else {
+        return;
+    };
+    let note = format!("Checking {} again", after.url);
+    if let Err(e) = state.db.create_annotation(alert_id, None, AnnotationKind::Resumed, Some(&note), None, Utc::now()).await {
+        tracing::error!("Failed to annotate resumed alert: {}", e);
+    }
+}
+
+/// 400 for platforms the operator has turned off for new alerts
+fn ensure_platform_enabled(state: &AppState, platform: &str) -> Result<(), (StatusCode, String)> {
+    if state.platforms.allows(platform) {
+        return Ok(());
+    }
+    Err((
+        StatusCode::BAD_REQUEST,
+        format!("New alerts for '{}' are disabled on this server", platform),
+    ))
+}
+
+/// Validate a create/replace payload into the alert to store
+async fn build_alert(
+    state: &AppState,
+    user_id: Uuid,
+    account_email: &str,
+    mut payload: CreateAlertRequest,
+) -> Result<PriceAlert, (StatusCode, String)> {
+    // Resolve the product page from a URL or a platform product ID
+    let (url, platform) = resolve_product(&state.url_resolver, &payload).await?;
+    ensure_platform_enabled(state, platform)?;
+    let product_id = create_scraper(platform).and_then(|s| s.product_id(&url));
+    
+    // Settings given explicitly win over the template's
+    let mut template_discount = None;
+    if let Some(template_id) = payload.template_id {
+        let template = state.db
+            .get_alert_template(template_id, user_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, "Unknown alert template".to_string()))?;
+        template_discount = template.discount_percent;
+        payload.notify_channels = payload.notify_channels.or(template.notify_channels);
+        payload.check_interval_minutes = payload.check_interval_minutes.or(template.check_interval_minutes);
+        payload.expires_at = payload.expires_at.or_else(|| {
+            template.expires_after_days.map(|days| Utc::now() + chrono::Duration::days(days.into()))
+        });
+    }
+    
+    if payload.check_interval_minutes.is_some_and(|minutes| minutes < 1) {
+        return Err((StatusCode::BAD_REQUEST, "check_interval_minutes must be at least 1".to_string()));
+    }
+    if payload.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err((StatusCode::BAD_REQUEST, "expires_at must be in the future".to_string()));
+    }
+    let renotify = payload.renotify.unwrap_or_default();
+    renotify.validate().map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if [payload.min_price, payload.max_price].into_iter().flatten().any(|bound| !bound.is_finite() || bound < 0.0) {
+        return Err((StatusCode::BAD_REQUEST, "min_price and max_price must be non-negative numbers".to_string()));
+    }
+    if payload.min_price.zip(payload.max_price).is_some_and(|(min, max)| min >= max) {
+        return Err((StatusCode::BAD_REQUEST, "min_price must be below max_price".to_string()));
+    }
+    
+    // Price alerts need a target; back-in-stock alerts only watch availability.
+    // Without one, the template's or the user's default discount off today's
+    // price is used, and that scrape becomes the alert's baseline price.
+    let mut last_price = None;
+    let target_price = match payload.mode {
+        AlertMode::Price => match payload.target_price {
+            Some(price) if price > 0.0 => price,
+            Some(_) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "Target price must be greater than 0".to_string(),
+                ));
+            }
+            None => {
+                let (baseline, target) = default_target_price(state, user_id, template_discount, platform, &url).await?;
+                last_price = Some(baseline);
+                target
+            }
+        },
+        AlertMode::BackInStock => 0.0,
+    };
+    if matches!(payload.mode, AlertMode::Price) && payload.min_price.is_some_and(|min| target_price < min) {
+        return Err((StatusCode::BAD_REQUEST, "target_price is below min_price, so it could never be reached".to_string()));
+    }
+    
+    // Per-alert channels must belong to the caller
+    if let Some(ids) = payload.notify_channels.as_deref().filter(|ids| !ids.is_empty()) {
+        let owned = state.db
+            .channels_belong_to_user(ids, user_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if !owned {
+            return Err((StatusCode::BAD_REQUEST, "Unknown notification channel".to_string()));
+        }
+    }
+    
+    // Create alert document
+    Ok(PriceAlert {
+        id: None,
+        url,
+        target_price,
+        last_price,
+        user_email: payload.user_email.unwrap_or_else(|| account_email.to_string()),
+        user_id: Some(user_id),
+        platform: platform.to_string(),
+        created_at: Utc::now(),
+        last_checked: Utc::now(),
+        is_active: true,
+        notify_channels: payload.notify_channels,
+        product_id,
+        alert_mode: payload.mode.as_str().to_string(),
+        in_stock: None,
+        offers: sqlx::types::Json(Vec::new()),
+        version: 1,
+        check_interval_minutes: payload.check_interval_minutes,
+        expires_at: payload.expires_at,
+        renotify: sqlx::types::Json(renotify),
+        min_price: payload.min_price,
+        max_price: payload.max_price,
+        product_removed_at: None,
+        product_name: None,
+        image_url: None,
+        notifications_paused: false,
+    })
+}
+
+/// `(current price, target)` for an alert created without a target, from the
+/// template's discount or else the user's default discount
+async fn default_target_price(
+    state: &AppState,
+    user_id: Uuid,
+    template_discount: Option<f64>,
+    platform: &str,
+    url: &str,
+) -> Result<(f64, f64), (StatusCode, String)> {
+    let user_default = match template_discount {
+        Some(_) => None,
+        None => state.db
+            .get_user_by_id(user_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .and_then(|user| user.default_discount_percent),
+    };
+    let percent = template_discount
+        .or(user_default)
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                "Target price is required (or set a default discount with PUT /auth/me/default-discount)".to_string(),
+            )
+        })?;
+    
+    let (live, _) = state.live_prices
+        .get(&state.scraper_configs, platform, url)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Couldn't fetch the current price to compute a target: {}", e)))?;
+    
+    Ok((live.price, inbound_email::default_target(live.price, percent)))
+}
+
+async fn resolve_product(
+    resolver: &UrlResolver,
+    payload: &CreateAlertRequest,
+) -> Result<(String, &'static str), (StatusCode, String)> {
+    let unsupported = || {
+        (
+            StatusCode::BAD_REQUEST,
+            "Unsupported platform. Supported: Myntra, Flipkart, Ajio, Tata Cliq, Shoppers Stop, Lifestyle".to_string(),
+        )
+    };
+    
+    if let Some(input) = &payload.url {
+        // Links shared from the apps are often short links or wrapped in share text
+        let url = resolver.resolve(input).await.map_err(|e| {
+            (StatusCode::BAD_REQUEST, format!("Could not resolve product link: {}", e))
+        })?;
+        let platform = detect_platform(&url).ok_or_else(unsupported)?;
+        return Ok((url, platform));
+    }
+    
+    let (Some(platform), Some(product_id)) = (&payload.platform, &payload.product_id) else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Provide either a url or a platform and product_id".to_string(),
+        ));
+    };
+    
+    let scraper = create_scraper(platform).ok_or_else(unsupported)?;
+    let url = scraper.product_url(product_id).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("'{}' doesn't look like a {} product ID", product_id, scraper.platform_name()),
+        )
+    })?;
+    
+    Ok((url, scraper.platform_name()))
+}
+
+#[derive(Debug, Deserialize)]
+struct ListAlertsQuery {
+    sort: Option<String>,
+}
+
+async fn list_alerts(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<ListAlertsQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let alerts = state.db
+        .get_alerts_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let mut responses: Vec<AlertResponse> = alerts.into_iter().map(|a| a.into()).collect();
+    
+    match query.sort.as_deref() {
+        None => {}
+        // Smallest percentage above target first (already-reached alerts lead),
+        // alerts without a price yet last
+        Some("closest") => responses.sort_by(|a, b| {
+            match (a.distance_to_target, b.distance_to_target) {
+                (Some(a), Some(b)) => a.percent.total_cmp(&b.percent),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            }
+        }),
+        Some(other) => return Err((StatusCode::BAD_REQUEST, format!("Unknown sort '{}'", other))),
+    }
+    
+    // The frontend polls this; unchanged lists go back as a bodyless 304
+    Ok(json_with_etag(&headers, &responses))
+}
+
+async fn delete_alert(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    state.db
+        .delete_alert(uuid)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Upper bound on `max_results`; listing pages rarely show more per page anyway
+const MAX_LISTING_RESULTS: i32 = 50;
+
+async fn create_listing_alert(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(mut payload): Json<CreateListingAlertRequest>,
+) -> Result<(StatusCode, Json<ListingAlert>), (StatusCode, String)> {
+    payload.url = desktop_url(&payload.url);
+    let scraper = detect_platform(&payload.url)
+        .and_then(create_scraper)
+        .filter(|scraper| scraper.is_listing_url(&payload.url))
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                "Expected a Myntra, Ajio, OLX or Quikr search/category page URL".to_string(),
+            )
+        })?;
+    ensure_platform_enabled(&state, scraper.platform_name())?;
+    
+    if payload.target_price <= 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Target price must be greater than 0".to_string(),
+        ));
+    }
+    
+    let max_results = payload.max_results.unwrap_or(20);
+    if !(1..=MAX_LISTING_RESULTS).contains(&max_results) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("max_results must be between 1 and {}", MAX_LISTING_RESULTS),
+        ));
+    }
+    
+    let created = state.db
+        .create_listing_alert(auth_user.user_id, &auth_user.email, scraper.platform_name(), &payload, max_results)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+async fn list_listing_alerts(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ListingAlert>>, (StatusCode, String)> {
+    let alerts = state.db
+        .get_listing_alerts_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(alerts))
+}
+
+/// Lowest asking price among the matching results at each check, newest first
+async fn get_listing_price_history(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let history = state.db
+        .get_listing_price_history(uuid, auth_user.user_id, 90)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Listing alert not found".to_string()))?;
+    
+    Ok(Json(json!({
+        "listing_alert_id": id,
+        "lowest_price": history.iter().map(|point| point.min_price).reduce(f64::min),
+        "history": history,
+        "count": history.len()
+    })))
+}
+
+async fn delete_listing_alert(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let deleted = state.db
+        .delete_listing_alert(uuid, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Listing alert not found".to_string()));
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn create_keyword_watch(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateKeywordWatchRequest>,
+) -> Result<(StatusCode, Json<KeywordWatch>), (StatusCode, String)> {
+    if required_terms(&payload.query).is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Query must name the product, e.g. \"Nike Pegasus 40\"".to_string(),
+        ));
+    }
+    
+    if payload.target_price <= 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Target price must be greater than 0".to_string(),
+        ));
+    }
+    
+    // Only platforms whose scraper can run a search are worth watching
+    for platform in &payload.platforms {
+        if create_scraper(platform).and_then(|s| s.search_url(&payload.query)).is_none() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Search is not supported on '{}'", platform),
+            ));
+        }
+        ensure_platform_enabled(&state, platform)?;
+    }
+    
+    let created = state.db
+        .create_keyword_watch(auth_user.user_id, &auth_user.email, &payload)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+async fn list_keyword_watches(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<KeywordWatch>>, (StatusCode, String)> {
+    let watches = state.db
+        .get_keyword_watches_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(watches))
+}
+
+async fn delete_keyword_watch(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid watch ID".to_string()))?;
+    
+    let deleted = state.db
+        .delete_keyword_watch(uuid, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Watch not found".to_string()));
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn validate_alert_template(
+    state: &AppState,
+    user_id: Uuid,
+    payload: &AlertTemplateRequest,
+) -> Result<(), (StatusCode, String)> {
+    if payload.name.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Template name is required".to_string()));
+    }
+    if payload.discount_percent.is_some_and(|percent| !(percent > 0.0 && percent < 100.0)) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "discount_percent must be between 0 and 100".to_string(),
+        ));
+    }
+    if payload.check_interval_minutes.is_some_and(|minutes| minutes < 1) {
+        return Err((StatusCode::BAD_REQUEST, "check_interval_minutes must be at least 1".to_string()));
+    }
+    if payload.expires_after_days.is_some_and(|days| days < 1) {
+        return Err((StatusCode::BAD_REQUEST, "expires_after_days must be at least 1".to_string()));
+    }
+    
+    if let Some(ids) = payload.notify_channels.as_deref().filter(|ids| !ids.is_empty()) {
+        let owned = state.db
+            .channels_belong_to_user(ids, user_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if !owned {
+            return Err((StatusCode::BAD_REQUEST, "Unknown notification channel".to_string()));
+        }
+    }
+    
+    Ok(())
+}
+
+async fn create_alert_template(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<AlertTemplateRequest>,
+) -> Result<(StatusCode, Json<AlertTemplate>), (StatusCode, String)> {
+    validate_alert_template(&state, auth_user.user_id, &payload).await?;
+    
+    let created = state.db
+        .create_alert_template(auth_user.user_id, &payload)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+async fn list_alert_templates(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AlertTemplate>>, (StatusCode, String)> {
+    let templates = state.db
+        .get_alert_templates_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(templates))
+}
+
+async fn update_alert_template(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<AlertTemplateRequest>,
+) -> Result<Json<AlertTemplate>, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid template ID".to_string()))?;
+    validate_alert_template(&state, auth_user.user_id, &payload).await?;
+    
+    let updated = state.db
+        .update_alert_template(uuid, auth_user.user_id, &payload)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert template not found".to_string()))?;
+    
+    Ok(Json(updated))
+}
+
+async fn delete_alert_template(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid template ID".to_string()))?;
+    
+    let deleted = state.db
+        .delete_alert_template(uuid, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Alert template not found".to_string()));
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_channels(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let channels = state.db
+        .get_channels_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({
+        "channels": channels,
+        "available": state.notifiers.channels()
+    })))
+}
+
+async fn create_channel(
+    VerifiedUser(auth_user): VerifiedUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateChannelRequest>,
+) -> Result<(StatusCode, Json<NotificationChannel>), (StatusCode, String)> {
+    let notifier = state.notifiers.get(&payload.channel).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Unsupported channel. Available: {}",
+                state.notifiers.channels().join(", ")
+            ),
+        )
+    })?;
+    
+    let destination = payload.destination.trim();
+    notifier
+        .validate_destination(destination)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    let channel = state.db
+        .create_channel(auth_user.user_id, &payload.channel, destination)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(channel)))
+}
+
+async fn delete_channel(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid channel ID".to_string()))?;
+    
+    let deleted = state.db
+        .delete_channel(uuid, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Channel not found".to_string()));
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// The key is only ever returned here; afterwards listings show its prefix
+async fn create_api_key(
+    VerifiedUser(auth_user): VerifiedUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, String)> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "name is required".to_string()));
+    }
+    
+    let scopes = payload.scopes.unwrap_or_else(|| vec![Scope::AlertsRead]);
+    if scopes.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "scopes can't be empty".to_string()));
+    }
+    let scopes: Vec<&str> = scopes.iter().map(Scope::as_str).collect();
+    
+    let key = generate_api_key();
+    let api_key = state.db
+        .create_api_key(auth_user.user_id, name, &api_key_display_prefix(&key), &hash_api_key(&key), &scopes)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(json!({
+        "id": api_key.id,
+        "name": api_key.name,
+        "prefix": api_key.prefix,
+        "scopes": api_key.scopes,
+        "created_at": api_key.created_at,
+        "key": key
+    }))))
+}
+
+async fn list_api_keys(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ApiKey>>, (StatusCode, String)> {
+    let keys = state.db.get_api_keys_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(keys))
+}
+
+async fn delete_api_key(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let uuid = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid API key ID".to_string()))?;
+    
+    let deleted = state.db
+        .delete_api_key(uuid, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "API key not found".to_string()));
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct NewDropsQuery {
+    /// ID of the newest drop already seen
+    since: Option<String>,
+}
+
+// Polling trigger for Zapier/IFTTT: a bare array, newest first, with stable `id`s
+// the platform deduplicates on
+async fn integration_new_drops(
+    api_user: ApiKeyUser,
+    State(state): State<AppState>,
+    Query(query): Query<NewDropsQuery>,
+) -> Result<Json<Vec<DropFeedItem>>, (StatusCode, String)> {
+    let after = match query.since.as_deref().filter(|since| !since.is_empty()) {
+        Some(since) => {
+            let id = Uuid::parse_str(since)
+                .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))?;
+            let event = state.db.get_price_drop_for_user(id, api_user.user_id)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .ok_or_else(|| (StatusCode::BAD_REQUEST, "Unknown cursor".to_string()))?;
+            Some(event)
+        }
+        None => None,
+    };
+    
+    let drops = state.db.get_drop_feed(api_user.user_id, after.as_ref(), 100)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(drops))
+}
+
+// Checks the caller's own alerts. With `Accept: text/event-stream` the check runs in the
+// background and each alert's progress is streamed as server-sent events; otherwise it
+// blocks until done.
+async fn manual_price_check(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    run_price_check(state, Some(auth_user.user_id), &headers).await
+}
+
+// Same as `POST /alerts/check`, over every user's alerts
+async fn admin_price_check(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    run_price_check(state, None, &headers).await
+}
+
+async fn run_price_check(
+    state: AppState,
+    user_id: Option<Uuid>,
+    headers: &HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let wants_stream = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+    if wants_stream {
+        return Ok(stream_price_check(state, user_id).into_response());
+    }
+    
+    trigger_manual_check(state.db, &state.notifiers, &state.scraper_configs, user_id, None)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({ "message": "Price check triggered successfully" })).into_response())
+}
+
+fn stream_price_check(
+    state: AppState,
+    user_id: Option<Uuid>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, axum::Error>>> {
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel::<CheckProgress>();
+    
+    tokio::spawn(async move {
+        if let Err(e) = trigger_manual_check(state.db, &state.notifiers, &state.scraper_configs, user_id, Some(&sender)).await {
+            tracing::error!("Manual price check failed: {}", e);
+        }
+    });
+    
+    // The stream ends when the check finishes and drops the sender
+    let events = tokio_stream::StreamExt::map(
+        tokio_stream::wrappers::UnboundedReceiverStream::new(receiver),
+        |progress| Event::default().event(progress.name()).json_data(&progress),
+    );
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// Live notifications for the signed-in user, whichever replica's worker found them
+async fn event_stream(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Sse<impl tokio_stream::Stream<Item = Result<Event, axum::Error>>> {
+    let receiver = state.notifiers.events().subscribe();
+    
+    // A lagging client skips what it missed rather than being disconnected
+    let events = tokio_stream::StreamExt::filter_map(
+        tokio_stream::wrappers::BroadcastStream::new(receiver),
+        move |event| {
+            let event = event.ok().filter(|event| event.user_id() == Some(auth_user.user_id))?;
+            Some(Event::default().event(event.name()).json_data(Versioned::new(&event)))
+        },
+    );
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+/// JSON Schemas for the payloads on `/events`, keyed `<type>.v<schema_version>`
+async fn event_schemas() -> Json<serde_json::Value> {
+    Json(event_schema::schemas())
+}
+
+// Platforms, channels, alert modes and limits, built from the registries so clients don't
+// keep their own copies. `enabled` is false for platforms new alerts can't be created for.
+async fn meta(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let platform = |name: &&str, kind: &str| {
+        let (display_name, site_url) = platform_site(name).unwrap_or((name, ""));
+        json!({
+            "id": name,
+            "name": display_name,
+            "kind": kind,
+            "site_url": site_url,
+            "logo_url": (!site_url.is_empty()).then(|| format!("{}/favicon.ico", site_url)),
+            "enabled": state.platforms.allows(name)
+        })
+    };
+    let platforms: Vec<_> = PLATFORMS.iter().map(|name| platform(name, "store"))
+        .chain(MARKETPLACES.iter().map(|name| platform(name, "marketplace")))
+        .collect();
+    let plans: serde_json::Map<_, _> = Plan::ALL.iter()
+        .map(|plan| (plan.as_str().to_string(), json!(plan.limits())))
+        .collect();
+    
+    json_with_etag(&headers, &json!({
+        "platforms": platforms,
+        "channels": state.notifiers.channels(),
+        "alert_modes": AlertMode::ALL.map(|mode| mode.as_str()),
+        "renotify_policies": RenotifyPolicy::NAMES,
+        "annotation_kinds": AnnotationKind::ALL.iter().filter(|kind| !kind.is_system()).map(|kind| kind.as_str()).collect::<Vec<_>>(),
+        "limits": {
+            "plans": plans,
+            "max_thresholds": MAX_THRESHOLDS,
+            "max_annotation_note_chars": MAX_ANNOTATION_NOTE_CHARS,
+            "max_listing_results": MAX_LISTING_RESULTS,
+            "max_history_page": MAX_HISTORY_PAGE
+        }
+    }))
+}
+
+// Send a test email to the caller's own confirmed address, a few times an hour.
+// Admins can test any recipient through `POST /admin/email/test`.
+async fn test_email(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    payload: Option<Json<serde_json::Value>>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let user = state.db.get_user_by_id(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+    if user.email_verified_at.is_none() {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Confirm your email address first; POST /auth/verify/resend sends a new link".to_string(),
+        ));
+    }
+    let requested = payload.as_ref().and_then(|Json(body)| body["email"].as_str());
+    if requested.is_some_and(|email| !email.trim().eq_ignore_ascii_case(&user.email)) {
+        return Err((StatusCode::FORBIDDEN, "Test emails only go to your own address".to_string()));
+    }
+    
+    if let Err(retry_after) = state.email_test_limiter.check(&user.id.to_string()).await {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many test emails. Try again in {} seconds", retry_after.as_secs().max(1)),
+        ));
+    }
+    
+    send_test_email(&user.email).await
+}
+
+// Admin: send a test email to any address, to check the mail setup
+async fn admin_test_email(
+    _admin: AdminUser,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let to_email = payload["email"]
+        .as_str()
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "email field required".to_string()))?;
+    
+    send_test_email(to_email).await
+}
+
+async fn send_test_email(to_email: &str) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let email_service = EmailService::from_env()
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Email not configured: {}", e)))?;
+    
+    email_service.send_test_email(to_email)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to send email: {}", e)))?;
+    
+    Ok(Json(json!({ 
+        "message": format!("Test email sent to {}", to_email),
+        "status": "success"
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryQuery {
+    /// `json` (a page of points with annotations), or `ndjson`/`csv` for every point, streamed
+    format: Option<String>,
+    /// JSON page size, 30 by default
+    limit: Option<i64>,
+    /// `next_cursor` from the previous JSON page
+    cursor: Option<String>,
+}
+
+/// Largest JSON page of history
+const MAX_HISTORY_PAGE: i64 = 500;
+
+async fn get_price_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<HistoryQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    let format = query.format.as_deref().unwrap_or("json");
+    if !matches!(format, "json" | "ndjson" | "csv") {
+        return Err((StatusCode::BAD_REQUEST, "format must be json, ndjson or csv".to_string()));
+    }
+    let limit = query.limit.unwrap_or(30).clamp(1, MAX_HISTORY_PAGE);
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => Some(
+            HistoryCursor::decode(cursor).ok_or_else(|| (StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+    
+    // Answer polling from the (index-only) version queries before loading any rows
+    let (count, latest) = state.db.get_price_history_version(alert_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let (annotation_count, annotated) = state.db.get_annotations_version(alert_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let page = format!("{}:{}:{}", format, limit, query.cursor.as_deref().unwrap_or_default());
+    let etag = etag_for(format!("{}:{}:{}:{:?}:{}:{:?}", page, alert_id, count, latest, annotation_count, annotated).as_bytes());
+    if is_fresh(&headers, &etag) {
+        return Ok(not_modified(&etag));
+    }
+    
+    // The full history, a line per point, written as rows come off the cursor
+    if format != "json" {
+        let csv = format == "csv";
+        let header_row = tokio_stream::iter(csv.then(|| Ok("checked_at,price,mrp\n".to_string())));
+        let rows = tokio_stream::StreamExt::map(state.db.stream_price_history(alert_id), move |row| {
+            let point = row?;
+            Ok::<_, anyhow::Error>(if csv {
+                let mrp = point.mrp.map(|mrp| mrp.to_string()).unwrap_or_default();
+                format!("{},{},{}\n", point.checked_at.to_rfc3339(), point.price, mrp)
+            } else {
+                format!("{}\n", serde_json::to_string(&point)?)
+            })
+        });
+        
+        let content_type = if csv { "text/csv; charset=utf-8" } else { "application/x-ndjson" };
+        let disposition = format!("attachment; filename=\"history-{}.{}\"", alert_id, format);
+        let headers = [(header::CONTENT_TYPE, content_type.to_string()), (header::CONTENT_DISPOSITION, disposition)];
+        return Ok(tagged(&etag, (headers, Body::from_stream(tokio_stream::StreamExt::chain(header_row, rows)))));
+    }
+    
+    // One row past the page tells whether there's another
+    let mut history = state.db.get_price_history_page(alert_id, cursor.as_ref(), limit + 1)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let more = history.len() as i64 > limit;
+    history.truncate(limit as usize);
+    let next_cursor = history.last().filter(|_| more).map(|point| HistoryCursor::after(point).encode());
+    
+    // Markers within the returned window, for the chart
+    let annotations = state.db
+        .get_annotations(alert_id, history.last().map(|point| point.checked_at), cursor.map(|cursor| cursor.checked_at))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(json_tagged(&etag, json!({
+        "alert_id": id,
+        "history": history,
+        "count": history.len(),
+        "annotations": annotations,
+        "next_cursor": next_cursor
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct DropsQuery {
+    /// Only drops recorded after this instant
+    since: Option<DateTime<Utc>>,
+    limit: Option<i64>,
+}
+
+impl DropsQuery {
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(100).clamp(1, 1000)
+    }
+}
+
+// Public one-off price check for a pasted link, e.g. the landing page widget.
+// Nothing is stored; shares the live price cache and politeness with logged-in lookups.
+async fn lookup_price(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(payload): Json<LookupRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let ip = client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr), trusted_proxies());
+    if let Err(retry_after) = state.lookup_limiter.check(&ip).await {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many lookups. Sign up to track prices, or try again in {} seconds", retry_after.as_secs().max(1)),
+        ));
+    }
+    
+    let url = state.url_resolver.resolve(&payload.url).await.map_err(|e| {
+        (StatusCode::BAD_REQUEST, format!("Could not resolve product link: {}", e))
+    })?;
+    // Anonymous callers choose this URL, so it's only fetched from the platform's own hosts
+    let platform = detect_platform(&url).filter(|platform| is_platform_host(&url, platform)).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            "Unsupported platform. Supported: Myntra, Flipkart, Ajio, Tata Cliq, Shoppers Stop, Lifestyle".to_string(),
+        )
+    })?;
+    
+    let (live, cached) = state.live_prices
+        .get(&state.scraper_configs, platform, &url)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Couldn't fetch the current price: {}", e)))?;
+    
+    Ok(Json(json!({
+        "url": live.url,
+        "platform": live.platform,
+        "price": live.price,
+        "mrp": live.mrp,
+        "discount_percent": live.discount_percent,
+        "offers": live.offers,
+        "fetched_at": live.fetched_at,
+        "cached": cached,
+    })))
+}
+
+// Say whether a pasted link can be tracked, and if not why: an unsupported site, a
+// disabled platform, or a search/category/home page instead of a product. Only the
+// URL's shape is checked; nothing is fetched except a short link's redirect, which
+// counts against the same per-IP budget as `POST /lookup`.
+async fn validate_url(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Json(payload): Json<LookupRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    if state.url_resolver.needs_fetch(&payload.url) {
+        let ip = client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr), trusted_proxies());
+        if let Err(retry_after) = state.lookup_limiter.check(&ip).await {
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Too many short links checked. Paste the full product link, or try again in {} seconds", retry_after.as_secs().max(1)),
+            ));
+        }
+    }
+    
+    let url = match state.url_resolver.resolve(&payload.url).await {
+        Ok(url) => url,
+        Err(e) => {
+            return Ok(Json(json!({
+                "valid": false,
+                "url": null,
+                "platform": null,
+                "page": null,
+                "message": format!("Could not read a link: {}", e)
+            })));
+        }
+    };
+    let Some((platform, scraper)) = detect_platform(&url).and_then(|p| create_scraper(p).map(|scraper| (p, scraper))) else {
+        let supported: Vec<_> = PLATFORMS.iter().filter_map(|p| platform_site(p)).map(|(name, _)| name).collect();
+        return Ok(Json(json!({
+            "valid": false,
+            "url": url,
+            "platform": null,
+            "page": null,
+            "message": format!("Not a supported store. Supported: {}", supported.join(", "))
+        })));
+    };
+    
+    let name = platform_site(platform).map_or(platform, |(name, _)| name);
+    let page = PageKind::of(scraper.as_ref(), &url);
+    let enabled = state.platforms.allows(platform);
+    let message = match page {
+        _ if !enabled => Some(format!("New alerts for {} are disabled on this server", name)),
+        PageKind::Product => None,
+        PageKind::Listing => Some(
+            "This is a search or category page, not a product. Open a product and copy its link, \
+             or track the whole page with a listing alert (POST /listing-alerts)".to_string(),
+        ),
+        PageKind::Home => Some(format!("This is {}'s home page. Open a product and copy its link", name)),
+        PageKind::Other => Some(format!("This doesn't look like a {} product page. Open a product and copy its link", name)),
+    };
+    
+    Ok(Json(json!({
+        "valid": message.is_none(),
+        "url": url,
+        "platform": platform,
+        "platform_name": name,
+        "enabled": enabled,
+        "page": page,
+        "product_id": scraper.product_id(&url),
+        "message": message
+    })))
+}
+
+// Scrape an alert's page now instead of waiting for the next scheduled check.
+// Briefly cached and rate limited per user; nothing is saved to the history.
+async fn get_live_price(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    if let Err(retry_after) = state.live_price_limiter.check(&auth_user.user_id.to_string()).await {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many live price checks. Try again in {} seconds", retry_after.as_secs().max(1)),
+        ));
+    }
+    
+    let alert = state.db
+        .get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let (live, cached) = state.live_prices
+        .get(&state.scraper_configs, &alert.platform, &alert.url)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Couldn't fetch the current price: {}", e)))?;
+    
+    Ok(Json(json!({
+        "alert_id": alert_id,
+        "url": live.url,
+        "platform": live.platform,
+        "price": live.price,
+        "target_price": alert.target_price,
+        "below_target": live.price <= alert.target_price,
+        "mrp": live.mrp,
+        "discount_percent": live.discount_percent,
+        "offers": live.offers,
+        "fetched_at": live.fetched_at,
+        "cached": cached,
+    })))
+}
+
+// Re-check one alert right away: scrape, save to history and notify on a drop, as the
+// scheduled run would. Shares the per-user budget and per-site spacing of live prices
+async fn check_alert_now(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let alert = state.db
+        .get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    if !alert.is_active {
+        return Err((StatusCode::CONFLICT, "Alert is inactive; reactivate it before checking".to_string()));
+    }
+    
+    if let Err(retry_after) = state.live_price_limiter.check(&auth_user.user_id.to_string()).await {
+        return Err((
+            StatusCode::TOO_MANY_REQUESTS,
+            format!("Too many on-demand checks. Try again in {} seconds", retry_after.as_secs().max(1)),
+        ));
+    }
+    
+    state.live_prices.wait_turn(&alert.platform, state.scraper_configs.request_delay()).await;
+    let check = check_alert(&state.db, &state.notifiers, &state.scraper_configs, &alert, None)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let alert = state.db
+        .get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    Ok(Json(json!({
+        "check": check,
+        "checked_at": Utc::now(),
+        "alert": AlertResponse::from(alert)
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct TimelineQuery {
+    /// Page size, 50 by default
+    limit: Option<i64>,
+    /// `next_cursor` from the previous page
+    cursor: Option<String>,
+}
+
+// Everything that happened to an alert, newest first, for its detail page
+async fn get_alert_timeline(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<TimelineQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_HISTORY_PAGE);
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => Some(
+            HistoryCursor::decode(cursor).ok_or_else(|| (StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+    
+    state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    // One entry past the page tells whether there's another
+    let mut timeline = state.db.get_alert_timeline(alert_id, cursor.as_ref(), limit + 1)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let more = timeline.len() as i64 > limit;
+    timeline.truncate(limit as usize);
+    let next_cursor = timeline
+        .last()
+        .filter(|_| more)
+        .map(|entry| HistoryCursor { checked_at: entry.at, id: entry.id }.encode());
+    
+    Ok(Json(json!({
+        "alert_id": alert_id,
+        "timeline": timeline,
+        "count": timeline.len(),
+        "next_cursor": next_cursor
+    })))
+}
+
+/// Longest note accepted on an annotation
+const MAX_ANNOTATION_NOTE_CHARS: usize = 500;
+
+async fn list_annotations(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let annotations = state.db.get_annotations(alert_id, None, None)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({
+        "alert_id": alert_id,
+        "annotations": annotations,
+        "count": annotations.len()
+    })))
+}
+
+async fn create_annotation(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<CreateAnnotationRequest>,
+) -> Result<(StatusCode, Json<Annotation>), (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    if payload.kind == AnnotationKind::TargetReached {
+        return Err((StatusCode::BAD_REQUEST, "target_reached annotations are added by the price check".to_string()));
+    }
+    if payload.kind == AnnotationKind::Relinked {
+        return Err((StatusCode::BAD_REQUEST, "relinked annotations are added by POST /alerts/:id/relink".to_string()));
+    }
+    if payload.kind.is_system() {
+        return Err((StatusCode::BAD_REQUEST, format!("{} annotations are added when checking stops or starts again", payload.kind.as_str())));
+    }
+    let note = payload.note.as_deref().map(str::trim).filter(|note| !note.is_empty());
+    if note.is_some_and(|note| note.chars().count() > MAX_ANNOTATION_NOTE_CHARS) {
+        return Err((StatusCode::BAD_REQUEST, format!("note must be at most {} characters", MAX_ANNOTATION_NOTE_CHARS)));
+    }
+    
+    let annotated_at = match payload.history_id {
+        Some(history_id) => state.db.get_history_point_time(alert_id, history_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, "history_id is not in this alert's history".to_string()))?,
+        None => payload.at.unwrap_or_else(Utc::now),
+    };
+    if annotated_at > Utc::now() {
+        return Err((StatusCode::BAD_REQUEST, "at can't be in the future".to_string()));
+    }
+    
+    let annotation = state.db
+        .create_annotation(alert_id, Some(auth_user.user_id), payload.kind, note, payload.history_id, annotated_at)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(annotation)))
+}
+
+async fn delete_annotation(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path((id, annotation_id)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    let annotation_id = Uuid::parse_str(&annotation_id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid annotation ID".to_string()))?;
+    
+    state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let deleted = state.db.delete_annotation(alert_id, annotation_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Annotation not found".to_string()));
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Mark an alert as bought: records the price paid, marks the history and stops tracking
+// the alert, which leaves the alert list like a deleted one
+// Undo an unsubscribe link, or mute an alert without stopping its checks
+async fn set_alert_notifications_paused(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<SetNotificationsPausedRequest>,
+) -> Result<Json<PriceAlert>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let alert = state.db.set_alert_notifications_paused(alert_id, auth_user.user_id, payload.paused)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    Ok(Json(alert))
+}
+
+async fn mark_purchased(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<MarkPurchasedRequest>,
+) -> Result<(StatusCode, Json<Purchase>), (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let alert = state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let price = payload.price
+        .or(alert.last_price)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "price is required until the alert has been checked".to_string()))?;
+    if !price.is_finite() || price <= 0.0 {
+        return Err((StatusCode::BAD_REQUEST, "price must be a positive number".to_string()));
+    }
+    let purchased_at = payload.purchased_at.unwrap_or_else(Utc::now);
+    if purchased_at > Utc::now() {
+        return Err((StatusCode::BAD_REQUEST, "purchased_at can't be in the future".to_string()));
+    }
+    
+    let purchase = state.db.record_purchase(&alert, price, purchased_at)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    state.db.deactivate_alert(alert_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let note = format!("Bought for ₹{}", price);
+    if let Err(e) = state.db.create_annotation(alert_id, Some(auth_user.user_id), AnnotationKind::Bought, Some(&note), None, purchased_at).await {
+        tracing::error!("Failed to annotate purchase: {}", e);
+    }
+    
+    Ok((StatusCode::CREATED, Json(purchase)))
+}
+
+// Drop a purchase from the savings report; the alert stays stopped
+async fn unmark_purchased(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let deleted = state.db.delete_purchase(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Purchase not found".to_string()));
+    }
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+struct SavingsQuery {
+    since: Option<DateTime<Utc>>,
+}
+
+// What the user saved by buying at tracked prices, against the first price seen and the target
+async fn get_savings_report(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<SavingsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let purchases = state.db.get_purchases(auth_user.user_id, query.since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let items: Vec<serde_json::Value> = purchases
+        .iter()
+        .map(|purchase| json!({
+            "alert_id": purchase.alert_id,
+            "url": purchase.url,
+            "platform": purchase.platform,
+            "price": purchase.price,
+            "purchased_at": purchase.purchased_at,
+            "initial_price": purchase.initial_price,
+            "target_price": purchase.target_price,
+            "saved_vs_initial": purchase.saved_vs_initial(),
+            "saved_vs_target": purchase.saved_vs_target()
+        }))
+        .collect();
+    
+    Ok(Json(json!({
+        "since": query.since,
+        "summary": SavingsReport::from_purchases(&purchases),
+        "items": items
+    })))
+}
+
+async fn get_alert_drops(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<DropsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    
+    state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let drops = state.db.get_price_drops_for_alert(alert_id, query.since, query.limit())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({
+        "alert_id": alert_id,
+        "drops": drops,
+        "count": drops.len()
+    })))
+}
+
+/// Most rungs a price ladder can have
+const MAX_THRESHOLDS: usize = 10;
+
+async fn list_thresholds(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let alert = state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    let thresholds = state.db.get_alert_thresholds(alert_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({
+        "alert_id": alert_id,
+        "target_price": alert.target_price,
+        "thresholds": thresholds,
+    })))
+}
+
+// Replace the alert's price ladder. Rungs sit above the target, which stays the last one.
+async fn set_thresholds(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<SetThresholdsRequest>,
+) -> Result<Json<Vec<AlertThreshold>>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()))?;
+    
+    let alert = state.db.get_alert_for_user(alert_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Alert not found".to_string()))?;
+    
+    if alert.alert_mode != AlertMode::Price.as_str() {
+        return Err((StatusCode::BAD_REQUEST, "Only price alerts have thresholds".to_string()));
+    }
+    if payload.prices.len() > MAX_THRESHOLDS {
+        return Err((StatusCode::BAD_REQUEST, format!("At most {} thresholds per alert", MAX_THRESHOLDS)));
+    }
+    if let Some(price) = payload.prices.iter().find(|price| !price.is_finite() || **price <= alert.target_price) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Threshold {} must be above the target price of {}", price, alert.target_price),
+        ));
+    }
+    
+    let thresholds = state.db
+        .set_alert_thresholds(alert_id, &payload.prices)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(thresholds))
+}
+
+// Drops across all of the user's alerts; poll with `since` set to the newest `created_at` seen
+async fn list_drops(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<DropsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let drops = state.db.get_price_drops_for_user(auth_user.user_id, query.since, query.limit())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({
+        "drops": drops,
+        "count": drops.len()
+    })))
+}
+
+// Group alerts for the same item on different platforms
+async fn create_product(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateProductRequest>,
+) -> Result<(StatusCode, Json<Product>), (StatusCode, String)> {
+    let name = payload.name.trim();
+    if name.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "name is required".to_string()));
+    }
+    if payload.alert_ids.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "alert_ids must not be empty".to_string()));
+    }
+    
+    let product = state.db.create_product(auth_user.user_id, name, &payload.alert_ids)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok((StatusCode::CREATED, Json(product)))
+}
+
+async fn list_products(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<Product>>, (StatusCode, String)> {
+    let products = state.db.get_products_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(products))
+}
+
+// Which platform has historically been cheaper for a product, from its alerts' history
+async fn compare_product(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let product_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    
+    let product = state.db.get_product_for_user(product_id, auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Product not found".to_string()))?;
+    
+    let prices = state.db.get_product_daily_prices(product_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({
+        "product_id": product.id,
+        "name": product.name,
+        "comparison": PlatformComparison::from_daily(&prices)
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryByUrlQuery {
+    url: String,
+    limit: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct PublicChartQuery {
+    url: String,
+}
+
+// A product's price history for anyone, no account needed: every tracker's history
+// pooled into daily low/high/average, without emails, user or alert IDs. Users who
+// opted out of public aggregates don't contribute, and days with fewer than
+// PUBLIC_CHART_MIN_USERS contributors are left out.
+async fn public_chart(
+    State(state): State<AppState>,
+    Query(query): Query<PublicChartQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let url = canonical_product_url(&query.url);
+    let platform = detect_platform(&url).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            "Unsupported platform. Supported: Myntra, Flipkart, Ajio, Tata Cliq, Shoppers Stop, Lifestyle".to_string(),
+        )
+    })?;
+    let product_id = create_scraper(platform).and_then(|s| s.product_id(&url));
+    let min_users = std::env::var("PUBLIC_CHART_MIN_USERS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(3);
+    
+    let days = state.db.get_public_daily_prices(platform, product_id.as_deref(), &url, min_users)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let body = Json(json!({
+        "url": url,
+        "platform": platform,
+        "days": days,
+    }));
+    Ok(([(header::CACHE_CONTROL, "public, max-age=900")], body).into_response())
+}
+
+// Site-wide totals for the landing page, as of the last periodic refresh (computed on
+// the spot if none has run yet, e.g. right after the first deploy)
+async fn public_stats(State(state): State<AppState>) -> Result<Response, (StatusCode, String)> {
+    let stats = match state.db.get_public_stats().await {
+        Ok(Some(stats)) => stats,
+        Ok(None) => state.db.refresh_public_stats()
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?,
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+    
+    Ok(([(header::CACHE_CONTROL, "public, max-age=900")], Json(stats)).into_response())
+}
+
+// Merged history for a product across the user's current and deleted alerts,
+// so re-creating an alert doesn't lose the chart
+async fn get_history_by_url(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Query(query): Query<HistoryByUrlQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let canonical = canonical_product_url(&query.url);
+    let limit = query.limit.unwrap_or(500).clamp(1, 5000);
+    
+    let alert_ids: Vec<Uuid> = state.db.get_all_alerts_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .filter(|alert| canonical_product_url(&alert.url) == canonical)
+        .filter_map(|alert| alert.id)
+        .collect();
+    
+    let history = if alert_ids.is_empty() {
+        Vec::new()
+    } else {
+        state.db.get_price_history_for_alerts(&alert_ids, limit)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    };
+    
+    Ok(Json(json!({
+        "url": canonical,
+        "alert_ids": alert_ids,
+        "history": history,
+        "count": history.len()
+    })))
+}
+
+async fn get_price_stats(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    
+    let stats = state.db.get_price_stats(alert_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
     
     match stats {
         Some(stats) => Ok(Json(json!({
@@ -310,3 +2997,771 @@ async fn get_price_stats(
         })))
     }
 }
+
+#[derive(Debug, Deserialize)]
+struct ChartQuery {
+    /// Pixels, `chart::DEFAULT_SIZE` by default
+    width: Option<u32>,
+    height: Option<u32>,
+    /// `7d`, `30d`, `90d` (the default), `1y` or `all`
+    range: Option<String>,
+}
+
+// The price history drawn as a PNG with the target line, for `<img>` tags in emails,
+// Telegram messages and READMEs. Like the history itself it needs no login: the alert
+// ID is the key. Cached by browsers and proxies for a while, and revalidated by ETag.
+async fn get_alert_chart(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(query): Query<ChartQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let alert_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    let width = query.width.unwrap_or(chart::DEFAULT_SIZE.0);
+    let height = query.height.unwrap_or(chart::DEFAULT_SIZE.1);
+    if !chart::WIDTHS.contains(&width) || !chart::HEIGHTS.contains(&height) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "width must be {}-{} and height {}-{}",
+                chart::WIDTHS.start(),
+                chart::WIDTHS.end(),
+                chart::HEIGHTS.start(),
+                chart::HEIGHTS.end()
+            ),
+        ));
+    }
+    let range = query.range.as_deref().unwrap_or("90d");
+    let days = chart::range_days(range)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "range must be 7d, 30d, 90d, 1y or all".to_string()))?;
+    
+    // A relative range moves with the calendar, so its image changes daily even without new checks
+    let now = Utc::now();
+    let since = days.map(|days| now - chrono::Duration::days(days));
+    let (count, latest) = state.db.get_price_history_version(alert_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let Some(((target, mode), points)) = state.db.get_chart_data(alert_id, since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    else {
+        return Err((StatusCode::NOT_FOUND, "Alert not found".to_string()));
+    };
+    let day = days.map(|_| now.date_naive());
+    let etag = etag_for(
+        format!("chart:{}:{}:{:?}:{}:{}:{}x{}:{}:{:?}", alert_id, count, latest, target, mode, width, height, range, day).as_bytes(),
+    );
+    if is_fresh(&headers, &etag) {
+        return Ok(not_modified(&etag));
+    }
+    
+    // The image is drawn once per version and kept in storage, shared by every replica.
+    // Each alert has a single slot, so a new version replaces the previous one.
+    let key = format!("charts/{}", alert_id);
+    let cached = state.storage.get(&key).await;
+    let png = match cached.as_ref().ok().and_then(|object| cached_chart(object.as_deref()?, &etag)) {
+        Some(png) => png,
+        None => {
+            if let Err(e) = cached {
+                tracing::warn!("Failed to read cached chart {}: {}", key, e);
+            }
+            // Back-in-stock alerts have no target worth drawing
+            let target = (mode == AlertMode::Price.as_str()).then_some(target);
+            let from = since.or(points.first().map(|(at, _)| *at)).unwrap_or(now);
+            let png = tokio::task::spawn_blocking(move || chart::render_png(&points, target, from, now, (width, height)))
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            let object = [etag.as_bytes(), b"\n", &png].concat();
+            if let Err(e) = state.storage.put(&key, object, "application/octet-stream").await {
+                tracing::warn!("Failed to cache chart {}: {}", key, e);
+            }
+            png
+        }
+    };
+    
+    let headers = [
+        (header::CONTENT_TYPE, "image/png".to_string()),
+        (header::CACHE_CONTROL, "public, max-age=900".to_string()),
+        (header::ETAG, etag),
+    ];
+    Ok((headers, png).into_response())
+}
+
+/// The PNG in a cached chart (`<etag>\n<png>`), if it's the version for `etag`
+fn cached_chart(object: &[u8], etag: &str) -> Option<Vec<u8>> {
+    let newline = object.iter().position(|byte| *byte == b'\n')?;
+    (object[..newline] == *etag.as_bytes()).then(|| object[newline + 1..].to_vec())
+}
+
+// The whole account as a JSON download, for `POST /import` here or on another instance
+async fn export_account(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let internal = |e: anyhow::Error| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string());
+    let user_id = auth_user.user_id;
+    
+    let user = state.db.get_user_by_id(user_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+    let channels = state.db.get_channels_by_user(user_id).await.map_err(internal)?;
+    let templates = state.db.get_alert_templates_by_user(user_id).await.map_err(internal)?;
+    let alerts = state.db.get_alerts_by_user(user_id).await.map_err(internal)?;
+    let products = state.db.get_products_by_user(user_id).await.map_err(internal)?;
+    let memberships = state.db.get_product_memberships(user_id).await.map_err(internal)?;
+    let listing_alerts = state.db.get_listing_alerts_by_user(user_id).await.map_err(internal)?;
+    let watches = state.db.get_keyword_watches_by_user(user_id).await.map_err(internal)?;
+    
+    let mut exported_alerts = Vec::with_capacity(alerts.len());
+    for alert in alerts {
+        let Some(id) = alert.id else { continue };
+        let history = state.db.get_full_price_history(id).await.map_err(internal)?;
+        let thresholds = state.db.get_alert_thresholds(id).await.map_err(internal)?;
+        exported_alerts.push(ExportedAlert {
+            id,
+            url: alert.url,
+            platform: alert.platform,
+            product_id: alert.product_id,
+            alert_mode: alert.alert_mode,
+            target_price: alert.target_price,
+            user_email: alert.user_email,
+            last_price: alert.last_price,
+            notify_channels: alert.notify_channels,
+            created_at: alert.created_at,
+            check_interval_minutes: alert.check_interval_minutes,
+            expires_at: alert.expires_at,
+            renotify: alert.renotify.0,
+            min_price: alert.min_price,
+            max_price: alert.max_price,
+            thresholds: thresholds.into_iter().map(|threshold| threshold.price).collect(),
+            notifications_paused: alert.notifications_paused,
+            history,
+        });
+    }
+    
+    let export = AccountExport {
+        format: ACCOUNT_EXPORT_FORMAT.to_string(),
+        version: ACCOUNT_EXPORT_VERSION,
+        exported_at: Utc::now(),
+        preferences: Some(ExportedPreferences {
+            timezone: user.timezone,
+            default_discount_percent: user.default_discount_percent,
+            weekly_summary: user.weekly_summary,
+            exclude_from_public: user.exclude_from_public,
+            notifications_paused: user.notifications_paused,
+        }),
+        channels: channels
+            .into_iter()
+            .map(|channel| ExportedChannel {
+                id: channel.id,
+                channel: channel.channel,
+                destination: channel.destination,
+                enabled: channel.enabled,
+            })
+            .collect(),
+        templates: templates
+            .into_iter()
+            .map(|template| ExportedTemplate {
+                name: template.name,
+                discount_percent: template.discount_percent,
+                notify_channels: template.notify_channels,
+                check_interval_minutes: template.check_interval_minutes,
+                expires_after_days: template.expires_after_days,
+            })
+            .collect(),
+        alerts: exported_alerts,
+        products: products
+            .into_iter()
+            .map(|product| ExportedProduct {
+                alert_ids: memberships
+                    .iter()
+                    .filter(|(product_id, _)| *product_id == product.id)
+                    .map(|(_, alert_id)| *alert_id)
+                    .collect(),
+                name: product.name,
+            })
+            .collect(),
+        listing_alerts: listing_alerts
+            .into_iter()
+            .map(|listing| ExportedListingAlert {
+                url: listing.url,
+                platform: listing.platform,
+                target_price: listing.target_price,
+                max_results: listing.max_results,
+                include_terms: listing.include_terms,
+                exclude_terms: listing.exclude_terms,
+            })
+            .collect(),
+        watches: watches
+            .into_iter()
+            .map(|watch| ExportedWatch {
+                query: watch.query,
+                target_price: watch.target_price,
+                platforms: watch.platforms,
+            })
+            .collect(),
+    };
+    
+    // The latest export is kept in storage too, replacing the previous one
+    let body = serde_json::to_vec(&export).map_err(|e| internal(e.into()))?;
+    state.storage
+        .put(&format!("exports/{}.json", user_id), body.clone(), "application/json")
+        .await
+        .map_err(internal)?;
+    
+    let disposition = format!(
+        "attachment; filename=\"price-tracker-export-{}.json\"",
+        export.exported_at.format("%Y-%m-%d")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        body,
+    ))
+}
+
+// Add the contents of an export to the caller's account. Entries this instance can't
+// take (unsupported platform or channel, invalid values) are skipped and listed in the
+// response rather than failing the whole import.
+async fn import_account(
+    auth_user: AuthUser,
+    State(state): State<AppState>,
+    Json(mut export): Json<AccountExport>,
+) -> Result<Json<ImportSummary>, (StatusCode, String)> {
+    if export.format != ACCOUNT_EXPORT_FORMAT {
+        return Err((StatusCode::BAD_REQUEST, "Not a price tracker account export".to_string()));
+    }
+    if export.version > ACCOUNT_EXPORT_VERSION {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Export version {} is newer than this server supports ({})",
+                export.version, ACCOUNT_EXPORT_VERSION
+            ),
+        ));
+    }
+    
+    let mut skipped = Vec::new();
+    if let Some(reason) = export.preferences.as_mut().and_then(invalid_exported_preferences) {
+        skipped.push(format!("preferences: {}", reason));
+        export.preferences = None;
+    }
+    // Channels send to outside destinations, which takes a confirmed address like POST /channels
+    if !export.channels.is_empty() && email_verification_required() {
+        let verified = state.db.get_user_by_id(auth_user.user_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .is_some_and(|user| user.email_verified_at.is_some());
+        if !verified {
+            for channel in export.channels.drain(..) {
+                skipped.push(format!("channel {}: confirm your email address first", channel.channel));
+            }
+        }
+    }
+    export.channels.retain(|channel| {
+        let valid = match state.notifiers.get(&channel.channel) {
+            Some(notifier) => notifier.validate_destination(channel.destination.trim()).map_err(|e| e.to_string()),
+            None => Err("not supported on this server".to_string()),
+        };
+        valid.map_err(|reason| skipped.push(format!("channel {}: {}", channel.channel, reason))).is_ok()
+    });
+    export.templates.retain(|template| {
+        invalid_exported_template(template)
+            .map(|reason| skipped.push(format!("template {}: {}", template.name, reason)))
+            .is_none()
+    });
+    export.alerts.retain(|alert| {
+        invalid_exported_alert(alert)
+            .or_else(|| (!state.platforms.allows(&alert.platform)).then_some("platform disabled on this server"))
+            .map(|reason| skipped.push(format!("alert {}: {}", alert.url, reason)))
+            .is_none()
+    });
+    
+    // Alerts past the plan's limit are skipped like any other unimportable entry
+    let (plan, limits) = user_plan(&state, auth_user.user_id).await?;
+    let active = state.db
+        .get_alerts_by_user(auth_user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .len() as i64;
+    let room = (limits.max_alerts - active).max(0) as usize;
+    if export.alerts.len() > room {
+        for alert in export.alerts.drain(room..) {
+            skipped.push(format!("alert {}: over the {} plan's limit of {} alerts", alert.url, plan.as_str(), limits.max_alerts));
+        }
+    }
+    export.listing_alerts.retain(|listing| {
+        invalid_exported_listing(listing)
+            .or_else(|| (!state.platforms.allows(&listing.platform)).then_some("platform disabled on this server"))
+            .map(|reason| skipped.push(format!("listing alert {}: {}", listing.url, reason)))
+            .is_none()
+    });
+    export.watches.retain(|watch| {
+        invalid_exported_watch(watch)
+            .map(|reason| skipped.push(format!("watch \"{}\": {}", watch.query, reason)))
+            .is_none()
+    });
+    
+    let mut summary = state.db
+        .import_account(auth_user.user_id, &auth_user.email, &export)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    skipped.append(&mut summary.skipped);
+    summary.skipped = skipped;
+    
+    Ok(Json(summary))
+}
+
+/// Normalizes the timezone to its canonical name on success
+fn invalid_exported_preferences(preferences: &mut ExportedPreferences) -> Option<&'static str> {
+    let Some(zone) = timezone::parse(&preferences.timezone) else {
+        return Some("unknown timezone");
+    };
+    preferences.timezone = zone.name().to_string();
+    preferences.default_discount_percent
+        .is_some_and(|percent| !(percent > 0.0 && percent < 100.0))
+        .then_some("default discount must be between 0 and 100")
+}
+
+fn invalid_exported_template(template: &ExportedTemplate) -> Option<&'static str> {
+    if template.name.trim().is_empty() {
+        Some("name is required")
+    } else if template.discount_percent.is_some_and(|percent| !(percent > 0.0 && percent < 100.0)) {
+        Some("discount must be between 0 and 100")
+    } else if template.check_interval_minutes.is_some_and(|minutes| minutes < 1)
+        || template.expires_after_days.is_some_and(|days| days < 1)
+    {
+        Some("check interval and expiry must be at least 1")
+    } else {
+        None
+    }
+}
+
+fn invalid_exported_alert(alert: &ExportedAlert) -> Option<&'static str> {
+    if detect_platform(&alert.url) != Some(alert.platform.as_str()) {
+        return Some("platform not supported on this server");
+    }
+    let mode = match alert.alert_mode.as_str() {
+        "price" if alert.target_price <= 0.0 => return Some("target price must be greater than 0"),
+        "price" => AlertMode::Price,
+        "back_in_stock" => AlertMode::BackInStock,
+        _ => return Some("unknown alert mode"),
+    };
+    
+    if alert.check_interval_minutes.is_some_and(|minutes| minutes < 1) {
+        Some("check interval must be at least 1 minute")
+    } else if alert.renotify.validate().is_err() {
+        Some("invalid renotify policy")
+    } else if [alert.min_price, alert.max_price].into_iter().flatten().any(|bound| !bound.is_finite() || bound < 0.0)
+        || alert.min_price.zip(alert.max_price).is_some_and(|(min, max)| min >= max)
+    {
+        Some("invalid min/max price")
+    } else if !alert.thresholds.is_empty() && !matches!(mode, AlertMode::Price) {
+        Some("only price alerts have thresholds")
+    } else if alert.thresholds.len() > MAX_THRESHOLDS
+        || alert.thresholds.iter().any(|price| !price.is_finite() || *price <= alert.target_price)
+    {
+        Some("invalid thresholds")
+    } else {
+        None
+    }
+}
+
+fn invalid_exported_listing(listing: &ExportedListingAlert) -> Option<&'static str> {
+    let supported = detect_platform(&listing.url)
+        .filter(|platform| *platform == listing.platform)
+        .and_then(create_scraper)
+        .is_some_and(|scraper| scraper.is_listing_url(&listing.url));
+    if !supported {
+        Some("not a supported search/category page")
+    } else if listing.target_price <= 0.0 {
+        Some("target price must be greater than 0")
+    } else if !(1..=MAX_LISTING_RESULTS).contains(&listing.max_results) {
+        Some("max_results out of range")
+    } else {
+        None
+    }
+}
+
+fn invalid_exported_watch(watch: &ExportedWatch) -> Option<&'static str> {
+    if required_terms(&watch.query).is_empty() {
+        Some("query doesn't name a product")
+    } else if watch.target_price <= 0.0 {
+        Some("target price must be greater than 0")
+    } else if watch.platforms.iter().any(|p| create_scraper(p).and_then(|s| s.search_url(&watch.query)).is_none()) {
+        Some("search not supported on one of its platforms")
+    } else {
+        None
+    }
+}
+
+// Admin: scraper configuration overrides
+async fn list_scraper_configs(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ScraperConfig>>, (StatusCode, String)> {
+    let configs = state.db.get_scraper_configs()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(configs))
+}
+
+async fn upsert_scraper_config(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(platform): Path<String>,
+    Json(payload): Json<UpsertScraperConfigRequest>,
+) -> Result<Json<ScraperConfig>, (StatusCode, String)> {
+    validate_config(&platform, &payload).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    
+    let saved = state.db.upsert_scraper_config(&platform, &payload)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    reload_scraper_configs(&state).await;
+    tracing::info!("🧩 {} updated scraper config for {}", admin.email, platform);
+    
+    Ok(Json(saved))
+}
+
+async fn delete_scraper_config(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(platform): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let deleted = state.db.delete_scraper_config(&platform)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "No config for this platform".to_string()));
+    }
+    
+    reload_scraper_configs(&state).await;
+    tracing::info!("🧩 {} removed scraper config for {}", admin.email, platform);
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn reload_scraper_configs(state: &AppState) {
+    if let Err(e) = state.scraper_configs.reload(&state.db).await {
+        tracing::error!("Failed to reload scraper configs: {}", e);
+    }
+}
+
+// Admin: sitemaps the discovery job reads products from, with how many it's checking
+async fn list_sitemap_sources(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SitemapSource>>, (StatusCode, String)> {
+    let sources = state.db.get_sitemap_sources()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(sources))
+}
+
+// Admin: add a platform sitemap; it's read on the next discovery round
+async fn create_sitemap_source(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateSitemapSourceRequest>,
+) -> Result<(StatusCode, Json<SitemapSource>), (StatusCode, String)> {
+    let url = payload.url.trim();
+    if !reqwest::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https")) {
+        return Err((StatusCode::BAD_REQUEST, "url must be an http(s) link".to_string()));
+    }
+    let platform = detect_platform(url)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, "The sitemap must be on a supported platform's site".to_string()))?;
+    let max_products = payload.max_products.unwrap_or(discovery::DEFAULT_MAX_PRODUCTS);
+    if !(1..=discovery::MAX_PRODUCTS).contains(&max_products) {
+        return Err((StatusCode::BAD_REQUEST, format!("max_products must be 1-{}", discovery::MAX_PRODUCTS)));
+    }
+    
+    let source = state.db.create_sitemap_source(platform, url, max_products)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::CONFLICT, "This sitemap is already a source".to_string()))?;
+    tracing::info!("🗺️ {} added sitemap {} for {}", admin.email, url, platform);
+    
+    Ok((StatusCode::CREATED, Json(source)))
+}
+
+// Admin: stop reading a sitemap. Its products stop being checked; their history stays.
+async fn delete_sitemap_source(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    let deleted = state.db.delete_sitemap_source(id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, "Sitemap not found".to_string()));
+    }
+    tracing::info!("🗺️ {} removed sitemap {}", admin.email, id);
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Admin: read a sitemap now instead of waiting for its next round. The products found
+// are checked by the scheduled rounds.
+async fn run_sitemap_source(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<SitemapSource>, (StatusCode, String)> {
+    let id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid UUID".to_string()))?;
+    let source = state.db.get_sitemap_source(id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Sitemap not found".to_string()))?;
+    
+    discovery::run_source(&state.db, &DiscoveryConfig::from_env(), &source)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, format!("Couldn't read the sitemap: {}", e)))?;
+    
+    let source = state.db.get_sitemap_source(id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Sitemap not found".to_string()))?;
+    Ok(Json(source))
+}
+
+#[derive(Debug, Deserialize)]
+struct ScrapeStatsQuery {
+    hours: Option<i64>,
+}
+
+// Admin: move a user to another plan
+async fn set_user_plan(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<SetPlanRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let user_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+    
+    let user = state.db
+        .set_user_plan(user_id, payload.plan)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+    tracing::info!("Moved user {} to the {} plan", user.email, payload.plan.as_str());
+    
+    Ok(Json(user.into()))
+}
+
+async fn set_user_role(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(payload): Json<SetRoleRequest>,
+) -> Result<Json<UserResponse>, (StatusCode, String)> {
+    let user_id = Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid user ID".to_string()))?;
+    
+    // Keep at least the caller able to undo this
+    if user_id == admin.user_id && payload.role != Role::Admin {
+        return Err((StatusCode::BAD_REQUEST, "You can't remove your own admin role".to_string()));
+    }
+    
+    let user = state.db
+        .set_user_role(user_id, payload.role)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "User not found".to_string()))?;
+    tracing::info!("{} gave {} the {} role", admin.email, user.email, payload.role.as_str());
+    
+    Ok(Json(user.into()))
+}
+
+// Admin: maintenance mode, which turns away writes and pauses scheduled checks
+async fn get_maintenance(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+) -> Json<serde_json::Value> {
+    Json(json!({ "maintenance": state.maintenance.status() }))
+}
+
+async fn set_maintenance(
+    AdminUser(admin): AdminUser,
+    State(state): State<AppState>,
+    Json(payload): Json<SetMaintenanceRequest>,
+) -> Json<serde_json::Value> {
+    if payload.enabled {
+        state.maintenance.enable(payload.message);
+        tracing::warn!("🚧 {} switched maintenance mode on", admin.email);
+    } else {
+        state.maintenance.disable();
+        tracing::info!("🚧 {} switched maintenance mode off", admin.email);
+    }
+    
+    Json(json!({ "maintenance": state.maintenance.status() }))
+}
+
+// Admin: what's holding the scheduled checks back: maintenance mode, and platforms
+// that rate-limited us and are being left alone until their backoff runs out. Also the
+// notifications still waiting in the outbox.
+async fn get_worker_status(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let (pending, oldest) = state.db.get_outbox_backlog()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    // Platforms checked less often than every round, and why
+    let throttles: Vec<_> = state.db.get_platform_throttles()
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .into_iter()
+        .filter(|throttle| throttle.factor > 1)
+        .collect();
+    let adjustments = state.db.get_throttle_adjustments(20)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    Ok(Json(json!({
+        "maintenance": state.maintenance.status(),
+        "backoff": state.scraper_configs.backoff().active(),
+        "outbox": { "pending": pending, "oldest": oldest },
+        "throttles": throttles,
+        "throttle_adjustments": adjustments
+    })))
+}
+
+// Admin: per-platform scrape success rate, latency and error breakdown
+async fn get_scrape_stats(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    Query(query): Query<ScrapeStatsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let hours = query.hours.unwrap_or(24).clamp(1, 24 * 90);
+    let since = Utc::now() - chrono::Duration::hours(hours);
+    
+    let stats = state.db.get_scrape_stats(since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let error_counts = state.db.get_scrape_error_counts(since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let platforms: Vec<_> = stats
+        .into_iter()
+        .map(|s| {
+            let errors: serde_json::Map<String, serde_json::Value> = error_counts
+                .iter()
+                .filter(|(platform, _, _)| *platform == s.platform)
+                .map(|(_, kind, count)| (kind.clone(), json!(count)))
+                .collect();
+            
+            json!({
+                "platform": s.platform,
+                "attempts": s.attempts,
+                "successes": s.successes,
+                "failures": s.failures,
+                "success_rate": s.successes as f64 / s.attempts.max(1) as f64,
+                "avg_duration_ms": s.avg_duration_ms,
+                "p95_duration_ms": s.p95_duration_ms,
+                "errors": errors,
+            })
+        })
+        .collect();
+    
+    Ok(Json(json!({
+        "window_hours": hours,
+        "platforms": platforms
+    })))
+}
+
+// Admin: notification outcomes per channel, with the reasons for failures
+async fn get_delivery_stats(
+    _admin: AdminUser,
+    State(state): State<AppState>,
+    Query(query): Query<ScrapeStatsQuery>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let hours = query.hours.unwrap_or(24).clamp(1, 24 * 90);
+    let since = Utc::now() - chrono::Duration::hours(hours);
+    
+    let counts = state.db.get_delivery_counts(since)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    
+    let mut channels: Vec<serde_json::Value> = Vec::new();
+    for (channel, outcome, error_kind, count) in counts {
+        if channels.last().is_none_or(|c| c["channel"] != channel.as_str()) {
+            channels.push(json!({
+                "channel": channel,
+                "sent": 0,
+                "bounced": 0,
+                "failed": 0,
+                "retried": 0,
+                "errors": {},
+            }));
+        }
+        let entry = channels.last_mut().expect("pushed above");
+        entry[outcome.as_str()] = json!(entry[outcome.as_str()].as_i64().unwrap_or(0) + count);
+        if !error_kind.is_empty() {
+            let errors = &mut entry["errors"][error_kind.as_str()];
+            *errors = json!(errors.as_i64().unwrap_or(0) + count);
+        }
+    }
+    
+    Ok(Json(json!({
+        "window_hours": hours,
+        "channels": channels
+    })))
+}
+
+// Admin: render an email template with sample data, to check it without sending anything.
+// Returns the HTML as-is, or the subject and HTML as JSON with `format=json`
+async fn preview_email(
+    _admin: AdminUser,
+    Query(mut params): Query<HashMap<String, String>>,
+) -> Result<Response, (StatusCode, String)> {
+    let template = params.remove("template")
+        .ok_or((StatusCode::BAD_REQUEST, "template is required".to_string()))?;
+    let as_json = params.remove("format").is_some_and(|format| format == "json");
+    
+    let email = EmailService::preview(&template, &params)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    
+    if as_json {
+        return Ok(Json(json!({
+            "template": template,
+            "subject": email.subject,
+            "unsubscribe_url": email.unsubscribe_url,
+            "html": email.html_body
+        })).into_response());
+    }
+    Ok(Html(email.html_body).into_response())
+}
+
+// Prometheus scrape endpoint; protected by METRICS_TOKEN when it is set
+async fn prometheus_metrics(headers: HeaderMap) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if let Ok(expected) = std::env::var("METRICS_TOKEN") {
+        let provided = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        if provided != Some(expected.as_str()) {
+            return Err((StatusCode::UNAUTHORIZED, "Invalid metrics token".to_string()));
+        }
+    }
+    
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        format!("{}{}", scrape_metrics().render(), delivery_metrics().render()),
+    ))
+}
@@ -1,8 +1,12 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Argon2, Params};
 use axum::{
     async_trait,
-    extract::FromRequestParts,
-    http::{request::Parts, StatusCode},
+    extract::{FromRef, FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
     RequestPartsExt,
 };
 use axum_extra::{
@@ -10,10 +14,15 @@ use axum_extra::{
     TypedHeader,
 };
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use std::collections::HashMap;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::db::Database;
+use crate::models::{Role, User};
+
 // JWT Claims structure
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -21,83 +30,376 @@ pub struct Claims {
     pub email: String,
     pub exp: i64,     // Expiration timestamp
     pub iat: i64,     // Issued at
+    #[serde(default)]
+    pub jti: String,  // Token ID, used for revocation (empty on pre-logout tokens)
+    /// What the token may be used for; absent on full-access session tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<Scope>>,
+}
+
+/// Permission carried by a scoped token or an API key. Session tokens from signup
+/// and login carry none and may do anything the account can.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Scope {
+    #[serde(rename = "alerts:read")]
+    AlertsRead,
+    #[serde(rename = "alerts:write")]
+    AlertsWrite,
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::AlertsRead => "alerts:read",
+            Scope::AlertsWrite => "alerts:write",
+            Scope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "alerts:read" => Some(Scope::AlertsRead),
+            "alerts:write" => Some(Scope::AlertsWrite),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+
+    /// Whether holding `granted` is enough for this scope; write access includes reading
+    pub fn granted_by(&self, granted: &[Scope]) -> bool {
+        granted.contains(self) || (*self == Scope::AlertsRead && granted.contains(&Scope::AlertsWrite))
+    }
 }
 
+/// Default access-token lifetime when `JWT_ACCESS_TTL_MINUTES` is unset
+pub const DEFAULT_TOKEN_TTL_MINUTES: i64 = 24 * 60;
+
+/// `kid` given to the `JWT_SECRET` key, and assumed for tokens issued before key IDs existed
+const LEGACY_KID: &str = "default";
+
 impl Claims {
     pub fn new(user_id: Uuid, email: String) -> Self {
+        Self::with_ttl(user_id, email, Duration::minutes(DEFAULT_TOKEN_TTL_MINUTES))
+    }
+
+    pub fn with_ttl(user_id: Uuid, email: String, ttl: Duration) -> Self {
         let now = Utc::now();
-        let expiry = now + Duration::hours(24); // Token valid for 24 hours
+        let expiry = now + ttl;
         
         Claims {
             sub: user_id.to_string(),
             email,
             exp: expiry.timestamp(),
             iat: now.timestamp(),
+            jti: Uuid::new_v4().to_string(),
+            scopes: None,
         }
     }
 }
 
-// JWT token generator
-pub fn generate_token(user_id: Uuid, email: String) -> Result<String> {
-    let claims = Claims::new(user_id, email);
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "dev_secret_key_change_in_production".to_string());
-    
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )?;
-    
-    Ok(token)
+/// Public half of an RS256 key, for services that verify our tokens themselves
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicKey {
+    pub kid: String,
+    pub alg: &'static str,
+    pub pem: String,
 }
 
-// JWT token validator
-pub fn verify_token(token: &str) -> Result<Claims> {
-    let secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "dev_secret_key_change_in_production".to_string());
-    
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
-    )?;
+/// Keyring for access tokens: one signing key plus every key still accepted
+/// for verification, looked up by the token's `kid` header.
+///
+/// Rotating a key is a two-step deploy: add the new key and make it active,
+/// then drop the old one once its tokens have expired.
+pub struct JwtKeys {
+    active_kid: String,
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    verification_keys: HashMap<String, (Algorithm, DecodingKey)>,
+    public_keys: Vec<PublicKey>,
+    ttl: Duration,
+}
+
+impl JwtKeys {
+    /// HS256 keyring from `(kid, secret)` pairs, signing with `active_kid`
+    pub fn hs256(keys: &[(&str, &str)], active_kid: &str, ttl: Duration) -> Result<Self> {
+        let secret = keys
+            .iter()
+            .find(|(kid, _)| *kid == active_kid)
+            .map(|(_, secret)| *secret)
+            .ok_or_else(|| anyhow!("Active JWT key '{}' is not configured", active_kid))?;
+
+        Ok(JwtKeys {
+            active_kid: active_kid.to_string(),
+            algorithm: Algorithm::HS256,
+            encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+            verification_keys: keys
+                .iter()
+                .map(|(kid, secret)| {
+                    (kid.to_string(), (Algorithm::HS256, DecodingKey::from_secret(secret.as_bytes())))
+                })
+                .collect(),
+            public_keys: Vec::new(),
+            ttl,
+        })
+    }
+
+    /// Switch signing to an RS256 key pair; existing keys stay valid for verification
+    pub fn with_rs256_signer(mut self, kid: &str, private_pem: &[u8], public_pem: &[u8]) -> Result<Self> {
+        self.encoding_key = EncodingKey::from_rsa_pem(private_pem)?;
+        self.algorithm = Algorithm::RS256;
+        self.active_kid = kid.to_string();
+        self.add_rs256_public_key(kid, public_pem)
+    }
+
+    /// Accept tokens signed by a (usually retired) RS256 key
+    pub fn add_rs256_public_key(mut self, kid: &str, public_pem: &[u8]) -> Result<Self> {
+        let key = DecodingKey::from_rsa_pem(public_pem)?;
+        self.verification_keys.insert(kid.to_string(), (Algorithm::RS256, key));
+        self.public_keys.push(PublicKey {
+            kid: kid.to_string(),
+            alg: "RS256",
+            pem: String::from_utf8_lossy(public_pem).into_owned(),
+        });
+        Ok(self)
+    }
+
+    /// Load keys from the environment:
+    /// - `JWT_SECRET`: HS256 secret with kid `default` (used when `JWT_KEYS` is unset)
+    /// - `JWT_KEYS`: comma-separated `kid:secret` HS256 keys
+    /// - `JWT_ACTIVE_KID`: which HS256 key signs new tokens (first in `JWT_KEYS` by default)
+    /// - `JWT_RS256_PRIVATE_KEY_FILE` / `JWT_RS256_PUBLIC_KEY_FILE` / `JWT_RS256_KID`: sign with RS256 instead
+    /// - `JWT_RS256_VERIFY_KEYS`: comma-separated `kid:/path/to/public.pem` keys still accepted
+    /// - `JWT_ACCESS_TTL_MINUTES`: access-token lifetime (24h by default)
+    pub fn from_env() -> Result<Self> {
+        let ttl_minutes = std::env::var("JWT_ACCESS_TTL_MINUTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|m: &i64| *m > 0)
+            .unwrap_or(DEFAULT_TOKEN_TTL_MINUTES);
+        let ttl = Duration::minutes(ttl_minutes);
+
+        let legacy_secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "dev_secret_key_change_in_production".to_string());
+        let configured = std::env::var("JWT_KEYS").unwrap_or_default();
+        let mut hs_keys = parse_key_list(&configured)?;
+        if hs_keys.is_empty() {
+            hs_keys.push((LEGACY_KID, legacy_secret.as_str()));
+        }
+
+        let active_kid = std::env::var("JWT_ACTIVE_KID").unwrap_or_else(|_| hs_keys[0].0.to_string());
+        let mut keys = Self::hs256(&hs_keys, &active_kid, ttl)?;
+
+        let verify_only = std::env::var("JWT_RS256_VERIFY_KEYS").unwrap_or_default();
+        for (kid, path) in parse_key_list(&verify_only)? {
+            keys = keys.add_rs256_public_key(kid, &std::fs::read(path)?)?;
+        }
+
+        if let Ok(private_path) = std::env::var("JWT_RS256_PRIVATE_KEY_FILE") {
+            let public_path = std::env::var("JWT_RS256_PUBLIC_KEY_FILE")
+                .map_err(|_| anyhow!("JWT_RS256_PUBLIC_KEY_FILE must be set alongside JWT_RS256_PRIVATE_KEY_FILE"))?;
+            let kid = std::env::var("JWT_RS256_KID").unwrap_or_else(|_| "rs256".to_string());
+            keys = keys.with_rs256_signer(&kid, &std::fs::read(private_path)?, &std::fs::read(public_path)?)?;
+        }
+
+        Ok(keys)
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    pub fn public_keys(&self) -> &[PublicKey] {
+        &self.public_keys
+    }
+
+    pub fn issue(&self, user_id: Uuid, email: String) -> Result<String> {
+        self.sign(&Claims::with_ttl(user_id, email, self.ttl))
+    }
+
+    /// A token limited to `scopes`, e.g. a read-only one for a dashboard
+    pub fn issue_scoped(&self, user_id: Uuid, email: String, scopes: &[Scope], ttl: Duration) -> Result<String> {
+        let mut claims = Claims::with_ttl(user_id, email, ttl);
+        claims.scopes = Some(scopes.to_vec());
+        self.sign(&claims)
+    }
+
+    fn sign(&self, claims: &Claims) -> Result<String> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.active_kid.clone());
+
+        Ok(encode(&header, claims, &self.encoding_key)?)
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Claims> {
+        let header = decode_header(token)?;
+        let kid = header.kid.as_deref().unwrap_or(LEGACY_KID);
+        let (algorithm, key) = self
+            .verification_keys
+            .get(kid)
+            .ok_or_else(|| anyhow!("Unknown signing key '{}'", kid))?;
+
+        let token_data = decode::<Claims>(token, key, &Validation::new(*algorithm))?;
+        Ok(token_data.claims)
+    }
+}
+
+/// Parse `kid:value,kid:value` lists used by the key env vars
+fn parse_key_list(raw: &str) -> Result<Vec<(&str, &str)>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .split_once(':')
+                .map(|(kid, value)| (kid.trim(), value.trim()))
+                .filter(|(kid, value)| !kid.is_empty() && !value.is_empty())
+                .ok_or_else(|| anyhow!("Invalid JWT key entry '{}', expected kid:value", entry))
+        })
+        .collect()
+}
+
+/// Cookie holding the access token for browser sessions; httpOnly, so page scripts can't read it
+pub const SESSION_COOKIE: &str = "pt_session";
+
+/// Readable cookie whose value the frontend echoes in `X-CSRF-Token` on state-changing requests
+pub const CSRF_COOKIE: &str = "pt_csrf";
+
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Cookie sessions handed out at login alongside the Bearer token, so a browser
+/// frontend never has to keep the token in localStorage
+#[derive(Debug, Clone, Copy)]
+pub struct SessionCookies {
+    /// Leave off only for plain-http development on a non-localhost host
+    pub secure: bool,
+}
+
+impl SessionCookies {
+    /// None when `AUTH_COOKIES=false`; `AUTH_COOKIE_SECURE=false` drops the Secure flag
+    pub fn from_env() -> Option<Self> {
+        let flag = |key: &str| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.trim().parse::<bool>().ok())
+                .unwrap_or(true)
+        };
+        
+        flag("AUTH_COOKIES").then(|| SessionCookies { secure: flag("AUTH_COOKIE_SECURE") })
+    }
+
+    /// `Set-Cookie` headers for a new session: the token plus a fresh CSRF token
+    pub fn issue(&self, token: &str, ttl: Duration) -> HeaderMap {
+        let csrf = Uuid::new_v4().simple().to_string();
+        let max_age = ttl.num_seconds();
+        self.headers(&[
+            format!("{}={}; HttpOnly; Max-Age={}", SESSION_COOKIE, token, max_age),
+            format!("{}={}; Max-Age={}", CSRF_COOKIE, csrf, max_age),
+        ])
+    }
+
+    /// `Set-Cookie` headers that end the session in the browser
+    pub fn clear(&self) -> HeaderMap {
+        self.headers(&[
+            format!("{}=; HttpOnly; Max-Age=0", SESSION_COOKIE),
+            format!("{}=; Max-Age=0", CSRF_COOKIE),
+        ])
+    }
+
+    fn headers(&self, cookies: &[String]) -> HeaderMap {
+        let secure = if self.secure { "; Secure" } else { "" };
+        let mut headers = HeaderMap::new();
+        for cookie in cookies {
+            let value = format!("{}; Path=/; SameSite=Lax{}", cookie, secure);
+            if let Ok(value) = HeaderValue::from_str(&value) {
+                headers.append(header::SET_COOKIE, value);
+            }
+        }
+        headers
+    }
+}
+
+/// A cookie's value from the request's `Cookie` headers
+pub fn cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+/// Double-submit check for cookie-authenticated requests: another site can make the browser
+/// send our cookies, but can't read the CSRF cookie to copy it into the header
+fn check_csrf(parts: &Parts) -> Result<(), (StatusCode, String)> {
+    if parts.method.is_safe() {
+        return Ok(());
+    }
     
-    Ok(token_data.claims)
+    let expected = cookie(&parts.headers, CSRF_COOKIE);
+    let sent = parts.headers.get(CSRF_HEADER).and_then(|v| v.to_str().ok());
+    match (expected, sent) {
+        (Some(expected), Some(sent)) if expected == sent => Ok(()),
+        _ => Err((StatusCode::FORBIDDEN, "Missing or invalid X-CSRF-Token header".to_string())),
+    }
 }
 
-// Axum extractor for authenticated requests
+// Axum extractor for authenticated requests: a Bearer token, or the session cookie
 #[derive(Debug, Clone)]
 pub struct AuthUser {
     pub user_id: Uuid,
     pub email: String,
+    pub token_id: String,
+    pub token_expires_at: i64,
 }
 
 #[async_trait]
 impl<S> FromRequestParts<S> for AuthUser
 where
+    Database: FromRef<S>,
+    Arc<JwtKeys>: FromRef<S>,
     S: Send + Sync,
 {
     type Rejection = (StatusCode, String);
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        // Extract Authorization header
-        let TypedHeader(Authorization(bearer)) = parts
-            .extract::<TypedHeader<Authorization<Bearer>>>()
-            .await
-            .map_err(|_| {
-                (
-                    StatusCode::UNAUTHORIZED,
-                    "Missing or invalid Authorization header".to_string(),
-                )
-            })?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // An Authorization header wins; otherwise fall back to a browser session
+        let token = match parts.extract::<TypedHeader<Authorization<Bearer>>>().await {
+            Ok(TypedHeader(Authorization(bearer))) => bearer.token().to_string(),
+            Err(_) => {
+                let token = cookie(&parts.headers, SESSION_COOKIE).ok_or_else(|| {
+                    (
+                        StatusCode::UNAUTHORIZED,
+                        "Missing or invalid Authorization header".to_string(),
+                    )
+                })?;
+                check_csrf(parts)?;
+                token.to_string()
+            }
+        };
 
         // Verify token
-        let claims = verify_token(bearer.token()).map_err(|e| {
+        let claims = Arc::<JwtKeys>::from_ref(state).verify(&token).map_err(|e| {
             (
                 StatusCode::UNAUTHORIZED,
                 format!("Invalid token: {}", e),
             )
         })?;
 
+        // Reject tokens revoked by logout
+        if !claims.jti.is_empty() {
+            let revoked = Database::from_ref(state)
+                .is_token_revoked(&claims.jti)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            if revoked {
+                return Err((StatusCode::UNAUTHORIZED, "Token has been revoked".to_string()));
+            }
+        }
+
         // Parse user_id
         let user_id = Uuid::parse_str(&claims.sub).map_err(|_| {
             (
@@ -109,19 +411,325 @@ where
         Ok(AuthUser {
             user_id,
             email: claims.email,
+            token_id: claims.jti,
+            token_expires_at: claims.exp,
         })
     }
 }
 
+/// The signed-in user's account row, for guards that check more than the token
+async fn account<S>(user: &AuthUser, state: &S) -> Result<User, (StatusCode, String)>
+where
+    Database: FromRef<S>,
+    S: Send + Sync,
+{
+    Database::from_ref(state)
+        .get_user_by_id(user.user_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Account no longer exists".to_string()))
+}
+
+// Extractor for admin-only routes: an authenticated user with the admin role, or listed in
+// ADMIN_EMAILS once they've confirmed that address
+#[derive(Debug, Clone)]
+pub struct AdminUser(pub AuthUser);
+
+pub fn is_admin_email(email: &str) -> bool {
+    std::env::var("ADMIN_EMAILS")
+        .unwrap_or_default()
+        .split(',')
+        .any(|admin| admin.trim().eq_ignore_ascii_case(email))
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminUser
+where
+    Database: FromRef<S>,
+    Arc<JwtKeys>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        
+        // ADMIN_EMAILS bootstraps the first admin, but anyone can sign up with a listed
+        // address, so it only counts once the account has proven it owns it
+        let account = account(&user, state).await?;
+        let listed = is_admin_email(&account.email) && account.email_verified_at.is_some();
+        if !listed && Role::parse(&account.role) != Role::Admin {
+            return Err((StatusCode::FORBIDDEN, "Admin access required".to_string()));
+        }
+        
+        Ok(AdminUser(user))
+    }
+}
+
+/// Whether routes guarded by `VerifiedUser` turn away unconfirmed addresses (`REQUIRE_EMAIL_VERIFICATION`, off by default)
+pub fn email_verification_required() -> bool {
+    std::env::var("REQUIRE_EMAIL_VERIFICATION")
+        .ok()
+        .and_then(|v| v.trim().parse::<bool>().ok())
+        .unwrap_or(false)
+}
+
+// Extractor for routes that reach beyond the account (outside destinations, integrations):
+// an authenticated user who has confirmed their email address, when verification is required
+#[derive(Debug, Clone)]
+pub struct VerifiedUser(pub AuthUser);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for VerifiedUser
+where
+    Database: FromRef<S>,
+    Arc<JwtKeys>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let user = AuthUser::from_request_parts(parts, state).await?;
+        
+        if email_verification_required() && account(&user, state).await?.email_verified_at.is_none() {
+            return Err((
+                StatusCode::FORBIDDEN,
+                "Confirm your email address first; POST /auth/verify/resend sends a new link".to_string(),
+            ));
+        }
+        
+        Ok(VerifiedUser(user))
+    }
+}
+
+/// Header integrations send their API key in
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Keys are recognisable by this prefix (e.g. in leaked-secret scanners)
+const API_KEY_PREFIX: &str = "pt_";
+
+/// A new random API key: the prefix plus 244 random bits from two v4 UUIDs
+pub fn generate_api_key() -> String {
+    format!("{}{}{}", API_KEY_PREFIX, Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// What's stored for a key. Keys are random, so a plain SHA-256 (no salt or stretching) suffices.
+pub fn hash_api_key(key: &str) -> String {
+    ring::digest::digest(&ring::digest::SHA256, key.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The part of a key shown in listings
+pub fn api_key_display_prefix(key: &str) -> String {
+    key.chars().take(API_KEY_PREFIX.len() + 6).collect()
+}
+
+// Extractor for integration routes authenticated by an `X-API-Key` header
+#[derive(Debug, Clone)]
+pub struct ApiKeyUser {
+    pub user_id: Uuid,
+    pub key_id: Uuid,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ApiKeyUser
+where
+    Database: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, String);
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let key = parts
+            .headers
+            .get(API_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|key| key.starts_with(API_KEY_PREFIX))
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Missing or invalid X-API-Key header".to_string()))?;
+
+        let api_key = Database::from_ref(state)
+            .use_api_key(&hash_api_key(key))
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Unknown or revoked API key".to_string()))?;
+
+        Ok(ApiKeyUser { user_id: api_key.user_id, key_id: api_key.id })
+    }
+}
+
+/// Which scope a group of routes asks of scoped credentials
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteGroup {
+    /// Alerts and everything hanging off them: `alerts:read` to read, `alerts:write` to change
+    Alerts,
+    /// `/admin/*`: the `admin` scope, on top of the caller being an admin
+    Admin,
+    /// Account settings, channels and credentials: session tokens only, so a scoped
+    /// token can't mint itself broader access
+    Account,
+}
+
+impl RouteGroup {
+    /// None when no scope is enough
+    pub fn required_scope(&self, method: &Method) -> Option<Scope> {
+        match self {
+            RouteGroup::Alerts if method.is_safe() => Some(Scope::AlertsRead),
+            RouteGroup::Alerts => Some(Scope::AlertsWrite),
+            RouteGroup::Admin => Some(Scope::Admin),
+            RouteGroup::Account => None,
+        }
+    }
+}
+
+/// The request's JWT, unverified: a Bearer token (scheme matched case-insensitively,
+/// as the `AuthUser` extractor does) or the session cookie
+pub fn request_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split_once(' '))
+        .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+        .map(|(_, token)| token.trim())
+        .or_else(|| cookie(headers, SESSION_COOKIE))
+}
+
+/// Scopes of the request's credentials: None for a session token, or when there are no
+/// usable credentials (the handler's extractor then rejects the request as usual)
+async fn granted_scopes(db: &Database, keys: &JwtKeys, headers: &HeaderMap) -> Result<Option<Vec<Scope>>, (StatusCode, String)> {
+    if let Some(token) = request_token(headers) {
+        return Ok(keys.verify(token).ok().and_then(|claims| claims.scopes));
+    }
+
+    let Some(key) = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()) else {
+        return Ok(None);
+    };
+    let scopes = db
+        .get_api_key_scopes(&hash_api_key(key.trim()))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(scopes.map(|scopes| scopes.iter().filter_map(|scope| Scope::parse(scope)).collect()))
+}
+
+/// Route-group layer that turns away scoped tokens and API keys lacking the group's scope
+pub async fn enforce_scopes(
+    State((db, keys, group)): State<(Database, Arc<JwtKeys>, RouteGroup)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let granted = match granted_scopes(&db, &keys, request.headers()).await {
+        Ok(Some(granted)) => granted,
+        Ok(None) => return next.run(request).await,
+        Err(rejection) => return rejection.into_response(),
+    };
+
+    match group.required_scope(request.method()) {
+        Some(required) if required.granted_by(&granted) => next.run(request).await,
+        Some(required) => {
+            (StatusCode::FORBIDDEN, format!("This token lacks the {} scope", required.as_str())).into_response()
+        }
+        None => (StatusCode::FORBIDDEN, "This route needs a full session token, not a scoped one".to_string())
+            .into_response(),
+    }
+}
+
+// Brute-force protection settings for login
+#[derive(Debug, Clone, Copy)]
+pub struct LockoutPolicy {
+    pub max_attempts: i32,
+    pub lockout: Duration,
+}
+
+impl LockoutPolicy {
+    pub fn from_env() -> Self {
+        let max_attempts = std::env::var("LOGIN_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(5);
+        let lockout_minutes = std::env::var("LOGIN_LOCKOUT_MINUTES")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(15);
+        
+        LockoutPolicy {
+            max_attempts,
+            lockout: Duration::minutes(lockout_minutes),
+        }
+    }
+}
+
 // Password hashing utilities
+//
+// New hashes are Argon2id. bcrypt hashes from before the switch still verify,
+// and login re-hashes them (see `needs_rehash`).
+
+/// Argon2id cost parameters, defaulting to the OWASP baseline (19 MiB, 2 passes, 1 lane)
+fn argon2_params() -> Params {
+    let env_u32 = |key: &str, default: u32| {
+        std::env::var(key)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    };
+    
+    Params::new(
+        env_u32("ARGON2_MEMORY_KIB", 19 * 1024),
+        env_u32("ARGON2_ITERATIONS", 2),
+        env_u32("ARGON2_PARALLELISM", 1),
+        None,
+    )
+    .unwrap_or_else(|e| {
+        tracing::warn!("Invalid Argon2 parameters ({}), using defaults", e);
+        Params::default()
+    })
+}
+
+fn argon2() -> Argon2<'static> {
+    Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, argon2_params())
+}
+
 pub fn hash_password(password: &str) -> Result<String> {
-    let hashed = bcrypt::hash(password, bcrypt::DEFAULT_COST)?;
-    Ok(hashed)
+    // A v4 UUID is 122 bits from the OS RNG, plenty for a salt
+    let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes())
+        .map_err(|e| anyhow!("Failed to encode salt: {}", e))?;
+    let hashed = argon2()
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| anyhow!("Failed to hash password: {}", e))?;
+    Ok(hashed.to_string())
 }
 
 pub fn verify_password(password: &str, hash: &str) -> Result<bool> {
-    let valid = bcrypt::verify(password, hash)?;
-    Ok(valid)
+    if !hash.starts_with("$argon2") {
+        return Ok(bcrypt::verify(password, hash)?);
+    }
+    
+    let parsed = PasswordHash::new(hash).map_err(|e| anyhow!("Invalid password hash: {}", e))?;
+    match argon2().verify_password(password.as_bytes(), &parsed) {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(anyhow!("Password verification failed: {}", e)),
+    }
+}
+
+/// Whether a stored hash should be replaced after a successful login:
+/// anything that isn't Argon2id with the currently configured parameters.
+pub fn needs_rehash(hash: &str) -> bool {
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return true;
+    };
+    if parsed.algorithm != argon2::Algorithm::Argon2id.ident() {
+        return true;
+    }
+    
+    let current = argon2_params();
+    Params::try_from(&parsed).map_or(true, |params| {
+        params.m_cost() != current.m_cost()
+            || params.t_cost() != current.t_cost()
+            || params.p_cost() != current.p_cost()
+    })
 }
 
 #[cfg(test)]
@@ -139,6 +747,16 @@ mod tests {
         assert_eq!(claims.email, email);
         assert!(claims.exp > claims.iat);
         assert_eq!(claims.exp - claims.iat, 24 * 3600); // 24 hours in seconds
+        assert!(Uuid::parse_str(&claims.jti).is_ok());
+    }
+
+    #[test]
+    fn test_claims_have_unique_token_ids() {
+        let user_id = Uuid::new_v4();
+        let first = Claims::new(user_id, "test@example.com".to_string());
+        let second = Claims::new(user_id, "test@example.com".to_string());
+
+        assert_ne!(first.jti, second.jti);
     }
 
     #[test]
@@ -148,12 +766,14 @@ mod tests {
         let user_id = Uuid::new_v4();
         let email = "test@example.com".to_string();
         
+        let keys = JwtKeys::from_env().unwrap();
+        
         // Generate token
-        let token = generate_token(user_id, email.clone()).unwrap();
+        let token = keys.issue(user_id, email.clone()).unwrap();
         assert!(!token.is_empty());
         
         // Verify token
-        let claims = verify_token(&token).unwrap();
+        let claims = keys.verify(&token).unwrap();
         assert_eq!(claims.sub, user_id.to_string());
         assert_eq!(claims.email, email);
     }
@@ -163,7 +783,7 @@ mod tests {
         unsafe { std::env::set_var("JWT_SECRET", "test_secret_key_12345"); }
         
         let invalid_token = "invalid.jwt.token";
-        let result = verify_token(invalid_token);
+        let result = JwtKeys::from_env().unwrap().verify(invalid_token);
         
         assert!(result.is_err());
     }
@@ -172,22 +792,110 @@ mod tests {
     fn test_token_with_wrong_secret() {
         unsafe { std::env::set_var("JWT_SECRET", "secret1"); }
         let user_id = Uuid::new_v4();
-        let token = generate_token(user_id, "test@example.com".to_string()).unwrap();
+        let token = JwtKeys::from_env().unwrap().issue(user_id, "test@example.com".to_string()).unwrap();
         
         // Change secret
         unsafe { std::env::set_var("JWT_SECRET", "secret2"); }
-        let result = verify_token(&token);
+        let result = JwtKeys::from_env().unwrap().verify(&token);
         
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_rotated_keys_verify_by_kid() {
+        let ttl = Duration::minutes(30);
+        let old = JwtKeys::hs256(&[("k1", "old-secret")], "k1", ttl).unwrap();
+        let rotated = JwtKeys::hs256(&[("k1", "old-secret"), ("k2", "new-secret")], "k2", ttl).unwrap();
+        let user_id = Uuid::new_v4();
+
+        // Tokens signed before the rotation keep working
+        let old_token = old.issue(user_id, "test@example.com".to_string()).unwrap();
+        assert_eq!(rotated.verify(&old_token).unwrap().sub, user_id.to_string());
+
+        let new_token = rotated.issue(user_id, "test@example.com".to_string()).unwrap();
+        assert_eq!(decode_header(&new_token).unwrap().kid.as_deref(), Some("k2"));
+        assert!(old.verify(&new_token).is_err());
+
+        let claims = rotated.verify(&new_token).unwrap();
+        assert_eq!(claims.exp - claims.iat, 30 * 60);
+    }
+
+    #[test]
+    fn test_token_without_kid_uses_legacy_key() {
+        let keys = JwtKeys::hs256(&[(LEGACY_KID, "legacy")], LEGACY_KID, Duration::hours(1)).unwrap();
+        let claims = Claims::new(Uuid::new_v4(), "test@example.com".to_string());
+        let token = encode(&Header::default(), &claims, &EncodingKey::from_secret(b"legacy")).unwrap();
+
+        assert_eq!(keys.verify(&token).unwrap().jti, claims.jti);
+    }
+
+    #[test]
+    fn test_rs256_signing() {
+        let private_pem = include_bytes!("../tests/fixtures/jwt_rs256_private.pem");
+        let public_pem = include_bytes!("../tests/fixtures/jwt_rs256_public.pem");
+        let keys = JwtKeys::hs256(&[("k1", "secret")], "k1", Duration::hours(1))
+            .unwrap()
+            .with_rs256_signer("rsa1", private_pem, public_pem)
+            .unwrap();
+
+        let token = keys.issue(Uuid::new_v4(), "test@example.com".to_string()).unwrap();
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::RS256);
+        assert_eq!(header.kid.as_deref(), Some("rsa1"));
+        assert!(keys.verify(&token).is_ok());
+
+        // Another service only needs the published public key
+        let published = &keys.public_keys()[0];
+        let verifier = DecodingKey::from_rsa_pem(published.pem.as_bytes()).unwrap();
+        assert!(decode::<Claims>(&token, &verifier, &Validation::new(Algorithm::RS256)).is_ok());
+    }
+
+    #[test]
+    fn test_scoped_tokens() {
+        let keys = JwtKeys::hs256(&[("k1", "secret")], "k1", Duration::hours(1)).unwrap();
+        let session = keys.verify(&keys.issue(Uuid::new_v4(), "test@example.com".to_string()).unwrap()).unwrap();
+        assert_eq!(session.scopes, None);
+
+        let token = keys
+            .issue_scoped(Uuid::new_v4(), "test@example.com".to_string(), &[Scope::AlertsWrite], Duration::minutes(5))
+            .unwrap();
+        let claims = keys.verify(&token).unwrap();
+        assert_eq!(claims.scopes.as_deref(), Some(&[Scope::AlertsWrite][..]));
+        assert!(claims.exp - claims.iat <= 300);
+
+        // Writing includes reading, but nothing else
+        let granted = claims.scopes.unwrap();
+        assert!(Scope::AlertsRead.granted_by(&granted));
+        assert!(!Scope::Admin.granted_by(&granted));
+        assert_eq!(RouteGroup::Alerts.required_scope(&Method::GET), Some(Scope::AlertsRead));
+        assert_eq!(RouteGroup::Alerts.required_scope(&Method::DELETE), Some(Scope::AlertsWrite));
+        assert_eq!(RouteGroup::Account.required_scope(&Method::GET), None);
+    }
+
+    #[test]
+    fn test_unknown_active_kid_is_rejected() {
+        assert!(JwtKeys::hs256(&[("k1", "secret")], "k2", Duration::hours(1)).is_err());
+        assert_eq!(parse_key_list("k1:a, k2:b").unwrap(), vec![("k1", "a"), ("k2", "b")]);
+        assert!(parse_key_list("missing-separator").is_err());
+    }
+
     #[test]
     fn test_password_hashing() {
         let password = "SecurePassword123!";
         
         let hashed = hash_password(password).unwrap();
         assert_ne!(hashed, password);
-        assert!(hashed.starts_with("$2b$")); // bcrypt hash format
+        assert!(hashed.starts_with("$argon2id$"));
+        assert!(!needs_rehash(&hashed));
+    }
+
+    #[test]
+    fn test_legacy_bcrypt_hashes_still_verify() {
+        let legacy = bcrypt::hash("OldPassword1", 4).unwrap();
+
+        assert!(verify_password("OldPassword1", &legacy).unwrap());
+        assert!(!verify_password("WrongPassword", &legacy).unwrap());
+        assert!(needs_rehash(&legacy));
     }
 
     #[test]
@@ -217,7 +925,7 @@ mod tests {
 
     #[test]
     fn test_same_password_produces_different_hashes() {
-        // bcrypt uses random salt, so same password should produce different hashes
+        // Salts are random, so the same password should produce different hashes
         let password = "SamePassword123";
         
         let hash1 = hash_password(password).unwrap();
@@ -229,4 +937,34 @@ mod tests {
         assert!(verify_password(password, &hash1).unwrap());
         assert!(verify_password(password, &hash2).unwrap());
     }
+
+    #[test]
+    fn test_api_keys() {
+        let key = generate_api_key();
+        assert!(key.starts_with("pt_"));
+        assert_eq!(key.len(), 3 + 64);
+        assert_ne!(key, generate_api_key());
+
+        let hash = hash_api_key(&key);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, hash_api_key(&key));
+        assert_ne!(hash, hash_api_key(&generate_api_key()));
+
+        assert_eq!(api_key_display_prefix(&key), key[..9]);
+    }
+
+    #[test]
+    fn test_session_cookies() {
+        let issued = SessionCookies { secure: true }.issue("header.claims.sig", Duration::minutes(60));
+        let cookies: Vec<&str> = issued.get_all(header::SET_COOKIE).iter().map(|v| v.to_str().unwrap()).collect();
+        assert_eq!(cookies[0], "pt_session=header.claims.sig; HttpOnly; Max-Age=3600; Path=/; SameSite=Lax; Secure");
+        assert!(cookies[1].starts_with("pt_csrf="));
+        assert!(!cookies[1].contains("HttpOnly"));
+
+        let mut request = HeaderMap::new();
+        request.insert(header::COOKIE, HeaderValue::from_static("theme=dark; pt_session=abc.def; pt_csrf="));
+        assert_eq!(cookie(&request, SESSION_COOKIE), Some("abc.def"));
+        assert_eq!(cookie(&request, CSRF_COOKIE), None);
+        assert_eq!(cookie(&request, "missing"), None);
+    }
 }
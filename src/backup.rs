@@ -0,0 +1,318 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Read;
+use std::path::PathBuf;
+use crate::db::Database;
+use crate::storage::Storage;
+
+pub const BACKUP_FORMAT: &str = "price-tracker-backup";
+pub const BACKUP_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+
+/// Tables saved in a backup, parents before children so a restore satisfies
+/// foreign keys as it goes. Scrape stats, revoked tokens and pending Telegram
+/// link codes are left out; they're either operational or short-lived.
+pub const TABLES: &[&str] = &[
+    "users",
+    "notification_channels",
+    "price_alerts",
+    "alert_thresholds",
+    "alert_templates",
+    "price_history",
+    "price_drop_events",
+    "annotations",
+    "purchases",
+    "listing_alerts",
+    "listing_price_history",
+    "keyword_watches",
+    "products",
+    "product_alerts",
+    "api_keys",
+    "telegram_chats",
+    "scraper_configs",
+];
+
+/// Rows inserted per statement on restore
+const RESTORE_BATCH: usize = 1000;
+
+/// `manifest.json` at the end of the archive: what was saved, and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    /// Row count per table
+    pub tables: BTreeMap<String, usize>,
+}
+
+/// Where an archive is written to or read from: a local file, or `storage:<key>` in the
+/// configured storage backend, which outlives the machine on hosts with ephemeral disks
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    File(PathBuf),
+    Storage(String),
+}
+
+impl Location {
+    fn parse(arg: &str) -> Location {
+        match arg.strip_prefix("storage:") {
+            Some(key) => Location::Storage(key.to_string()),
+            None => Location::File(arg.into()),
+        }
+    }
+
+    async fn write(&self, storage: &dyn Storage, archive: Vec<u8>) -> Result<()> {
+        match self {
+            Location::File(path) => {
+                std::fs::write(path, archive).with_context(|| format!("Can't write {}", path.display()))
+            }
+            Location::Storage(key) => storage.put(key, archive, "application/zstd").await,
+        }
+    }
+
+    async fn read(&self, storage: &dyn Storage) -> Result<Vec<u8>> {
+        match self {
+            Location::File(path) => std::fs::read(path).with_context(|| format!("Can't open {}", path.display())),
+            Location::Storage(key) => storage
+                .get(key)
+                .await?
+                .ok_or_else(|| anyhow!("No {} in {} storage", key, storage.backend_name())),
+        }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::File(path) => write!(f, "{}", path.display()),
+            Location::Storage(key) => write!(f, "storage:{}", key),
+        }
+    }
+}
+
+/// `backup --out <file>` / `restore <file> [--replace]` on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Backup { out: Location },
+    Restore { input: Location, replace: bool },
+}
+
+impl Command {
+    /// None when the first argument isn't a backup subcommand, so the server starts as usual
+    pub fn from_args(args: &[String]) -> Result<Option<Command>> {
+        let usage = "Usage: backup --out <file.tar.zst | storage:key> | restore <file.tar.zst | storage:key> [--replace]";
+        match args.first().map(String::as_str) {
+            Some("backup") => match &args[1..] {
+                [flag, out] if flag == "--out" => Ok(Some(Command::Backup { out: Location::parse(out) })),
+                _ => bail!(usage),
+            },
+            Some("restore") => {
+                let replace = args[1..].iter().any(|arg| arg == "--replace");
+                let mut files = args[1..].iter().filter(|arg| !arg.starts_with("--"));
+                match (files.next(), files.next()) {
+                    (Some(input), None) => Ok(Some(Command::Restore { input: Location::parse(input), replace })),
+                    _ => bail!(usage),
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub async fn run(&self, db: &Database, storage: &dyn Storage) -> Result<Manifest> {
+        match self {
+            Command::Backup { out } => backup(db, storage, out).await,
+            Command::Restore { input, replace } => restore(db, storage, input, *replace).await,
+        }
+    }
+}
+
+/// Dump every table in `TABLES` from one consistent snapshot into a zstd-compressed
+/// tar of JSON lines (`<table>.jsonl`), without needing `pg_dump`
+pub async fn backup(db: &Database, storage: &dyn Storage, out: &Location) -> Result<Manifest> {
+    let mut tx = db.pool.begin().await?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *tx)
+        .await?;
+
+    let mut tables = Vec::with_capacity(TABLES.len());
+    for table in TABLES {
+        let rows: Vec<String> = sqlx::query_scalar(&format!("SELECT row_to_json(t)::text FROM {} t", table))
+            .fetch_all(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to read {}", table))?;
+        tracing::info!("💾 {}: {} row(s)", table, rows.len());
+        tables.push((table.to_string(), rows));
+    }
+    tx.commit().await?;
+
+    let manifest = Manifest {
+        format: BACKUP_FORMAT.to_string(),
+        version: BACKUP_VERSION,
+        created_at: Utc::now(),
+        tables: tables.iter().map(|(table, rows)| (table.clone(), rows.len())).collect(),
+    };
+    out.write(storage, write_archive(&manifest, &tables)?).await?;
+    Ok(manifest)
+}
+
+/// Load a backup in one transaction. Refuses a database that already has users
+/// unless `replace` is set, in which case the backed-up tables are emptied first.
+/// Columns the current schema no longer has are dropped, and ones added since the
+/// backup was taken get their defaults, so a backup restores into a newer version.
+pub async fn restore(db: &Database, storage: &dyn Storage, input: &Location, replace: bool) -> Result<Manifest> {
+    let (manifest, files) = read_archive(&input.read(storage).await?)
+        .with_context(|| format!("Can't restore from {}", input))?;
+
+    let mut tx = db.pool.begin().await?;
+    if replace {
+        sqlx::query(&format!("TRUNCATE {} CASCADE", TABLES.join(", ")))
+            .execute(&mut *tx)
+            .await?;
+    } else {
+        let has_users: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users)")
+            .fetch_one(&mut *tx)
+            .await?;
+        if has_users {
+            bail!("The database already has users; restore into an empty database or pass --replace");
+        }
+    }
+
+    for table in TABLES {
+        let Some(lines) = files.get(*table) else {
+            continue;
+        };
+        let rows: Vec<serde_json::Map<String, serde_json::Value>> = lines
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()
+            .with_context(|| format!("{}.jsonl is not valid JSON lines", table))?;
+        let Some(first) = rows.first() else {
+            continue;
+        };
+
+        let current: Vec<String> = sqlx::query_scalar(
+            "SELECT column_name::text FROM information_schema.columns WHERE table_schema = current_schema() AND table_name = $1 ORDER BY ordinal_position"
+        )
+        .bind(table)
+        .fetch_all(&mut *tx)
+        .await?;
+        let columns = current
+            .iter()
+            .filter(|column| first.contains_key(*column))
+            .map(|column| format!("\"{}\"", column.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if columns.is_empty() {
+            bail!("{}.jsonl has none of the table's columns", table);
+        }
+
+        let insert = format!(
+            "INSERT INTO {table} ({columns}) SELECT {columns} FROM jsonb_populate_recordset(NULL::{table}, $1)"
+        );
+        for batch in rows.chunks(RESTORE_BATCH) {
+            sqlx::query(&insert)
+                .bind(sqlx::types::Json(batch))
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("Failed to restore {}", table))?;
+        }
+        tracing::info!("♻️  {}: {} row(s)", table, rows.len());
+    }
+
+    tx.commit().await?;
+    Ok(manifest)
+}
+
+fn write_archive(manifest: &Manifest, tables: &[(String, Vec<String>)]) -> Result<Vec<u8>> {
+    let mut archive = tar::Builder::new(zstd::Encoder::new(Vec::new(), 0)?);
+
+    let mut append = |name: &str, data: &[u8]| -> Result<()> {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(manifest.created_at.timestamp().max(0) as u64);
+        header.set_cksum();
+        archive.append_data(&mut header, name, data)?;
+        Ok(())
+    };
+    for (table, rows) in tables {
+        append(&format!("{}.jsonl", table), rows.join("\n").as_bytes())?;
+    }
+    append(MANIFEST, &serde_json::to_vec_pretty(manifest)?)?;
+
+    Ok(archive.into_inner()?.finish()?)
+}
+
+/// The manifest and each `<table>.jsonl`, keyed by table name
+fn read_archive(archive: &[u8]) -> Result<(Manifest, HashMap<String, String>)> {
+    let mut archive = tar::Archive::new(zstd::Decoder::new(archive)?);
+
+    let mut manifest = None;
+    let mut files = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents)?;
+
+        if name == MANIFEST {
+            manifest = Some(serde_json::from_str::<Manifest>(&contents)?);
+        } else if let Some(table) = name.strip_suffix(".jsonl") {
+            files.insert(table.to_string(), contents);
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| anyhow!("Not a price tracker backup (no manifest)"))?;
+    if manifest.format != BACKUP_FORMAT || manifest.version > BACKUP_VERSION {
+        bail!(
+            "Unsupported backup: {} v{} (this build reads {} up to v{})",
+            manifest.format,
+            manifest.version,
+            BACKUP_FORMAT,
+            BACKUP_VERSION
+        );
+    }
+    Ok((manifest, files))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_archive_round_trip() {
+        let tables = vec![
+            ("users".to_string(), vec![r#"{"id": "a", "email": "buyer@example.com"}"#.to_string()]),
+            ("price_alerts".to_string(), vec![]),
+        ];
+        let manifest = Manifest {
+            format: BACKUP_FORMAT.to_string(),
+            version: BACKUP_VERSION,
+            created_at: Utc::now(),
+            tables: tables.iter().map(|(table, rows)| (table.clone(), rows.len())).collect(),
+        };
+
+        let (read, files) = read_archive(&write_archive(&manifest, &tables).unwrap()).unwrap();
+
+        assert_eq!(read, manifest);
+        assert_eq!(files["users"], tables[0].1[0]);
+        assert_eq!(files["price_alerts"], "");
+
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            Command::from_args(&args("restore --replace dump.tar.zst")).unwrap(),
+            Some(Command::Restore { input: Location::File("dump.tar.zst".into()), replace: true })
+        );
+        assert_eq!(
+            Command::from_args(&args("backup --out storage:backups/dump.tar.zst")).unwrap(),
+            Some(Command::Backup { out: Location::Storage("backups/dump.tar.zst".to_string()) })
+        );
+        assert!(Command::from_args(&args("backup dump.tar.zst")).is_err());
+        assert_eq!(Command::from_args(&args("--demo")).unwrap(), None);
+    }
+}
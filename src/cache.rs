@@ -0,0 +1,206 @@
+use moka::future::Cache;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+use crate::models::PriceAlert;
+use crate::redis_store::RedisStore;
+
+/// Per-user cache of active alerts, the read behind the frontend's polling of `GET /alerts`.
+///
+/// `Database` invalidates a user's entry on every write to their alerts (including the
+/// worker's price updates). In process by default; with `REDIS_URL` set the entries live
+/// in Redis instead, so an invalidation on one replica is seen by all of them.
+#[derive(Clone)]
+pub struct AlertCache {
+    backend: Option<Backend>,
+}
+
+#[derive(Clone)]
+enum Backend {
+    Local(Cache<Uuid, Arc<Vec<PriceAlert>>>),
+    Shared { store: Box<RedisStore>, ttl: Duration },
+}
+
+impl AlertCache {
+    pub fn new(ttl: Duration, max_users: u64) -> Self {
+        AlertCache {
+            backend: Some(Backend::Local(Cache::builder().max_capacity(max_users).time_to_live(ttl).build())),
+        }
+    }
+
+    pub fn shared(store: RedisStore, ttl: Duration) -> Self {
+        AlertCache { backend: Some(Backend::Shared { store: Box::new(store), ttl }) }
+    }
+
+    pub fn disabled() -> Self {
+        AlertCache { backend: None }
+    }
+
+    pub fn from_env() -> Self {
+        match Self::ttl_from_env() {
+            Some(ttl) => Self::new(ttl, 10_000),
+            None => Self::disabled(),
+        }
+    }
+
+    /// `from_env`, but backed by Redis
+    pub fn shared_from_env(store: RedisStore) -> Self {
+        match Self::ttl_from_env() {
+            Some(ttl) => Self::shared(store, ttl),
+            None => Self::disabled(),
+        }
+    }
+
+    /// `None` when `ALERT_CACHE_ENABLED` turns the cache off
+    fn ttl_from_env() -> Option<Duration> {
+        let enabled = std::env::var("ALERT_CACHE_ENABLED")
+            .map(|v| !matches!(v.trim().to_lowercase().as_str(), "false" | "0" | "no" | "off"))
+            .unwrap_or(true);
+        if !enabled {
+            return None;
+        }
+
+        // The TTL only bounds staleness if an invalidation is ever missed
+        let ttl_secs = std::env::var("ALERT_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(300);
+        Some(Duration::from_secs(ttl_secs))
+    }
+
+    /// Cached alerts for `user_id`, running `load` on a miss.
+    /// Redis errors are logged and treated as a miss rather than failing the read.
+    pub async fn get_or_load<F>(&self, user_id: Uuid, load: F) -> anyhow::Result<Vec<PriceAlert>>
+    where
+        F: Future<Output = anyhow::Result<Vec<PriceAlert>>>,
+    {
+        match &self.backend {
+            None => load.await,
+            Some(Backend::Local(cache)) => {
+                if let Some(alerts) = cache.get(&user_id).await {
+                    return Ok(alerts.as_ref().clone());
+                }
+
+                let alerts = load.await?;
+                cache.insert(user_id, Arc::new(alerts.clone())).await;
+                Ok(alerts)
+            }
+            Some(Backend::Shared { store, ttl }) => {
+                let key = shared_key(user_id);
+                match store.get_json(&key).await {
+                    Ok(Some(alerts)) => return Ok(alerts),
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!("Alert cache read failed: {}", e),
+                }
+
+                let alerts = load.await?;
+                if let Err(e) = store.set_json(&key, &alerts, *ttl).await {
+                    tracing::warn!("Alert cache write failed: {}", e);
+                }
+                Ok(alerts)
+            }
+        }
+    }
+
+    pub async fn invalidate(&self, user_id: Uuid) {
+        match &self.backend {
+            None => {}
+            Some(Backend::Local(cache)) => cache.invalidate(&user_id).await,
+            Some(Backend::Shared { store, .. }) => {
+                if let Err(e) = store.delete(&shared_key(user_id)).await {
+                    tracing::warn!("Alert cache invalidation failed for user {}: {}", user_id, e);
+                }
+            }
+        }
+    }
+}
+
+fn shared_key(user_id: Uuid) -> String {
+    format!("alerts:{}", user_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn alert(url: &str) -> PriceAlert {
+        PriceAlert {
+            id: Some(Uuid::new_v4()),
+            url: url.to_string(),
+            target_price: 999.0,
+            last_price: None,
+            user_email: "buyer@example.com".to_string(),
+            user_id: None,
+            platform: "myntra".to_string(),
+            created_at: Utc::now(),
+            last_checked: Utc::now(),
+            is_active: true,
+            notify_channels: None,
+            product_id: None,
+            alert_mode: "price".to_string(),
+            in_stock: None,
+            offers: sqlx::types::Json(vec![]),
+            version: 1,
+            check_interval_minutes: None,
+            expires_at: None,
+            renotify: Default::default(),
+            min_price: None,
+            max_price: None,
+            product_removed_at: None,
+            product_name: None,
+            image_url: None,
+            notifications_paused: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_hit_and_invalidate() {
+        let cache = AlertCache::new(Duration::from_secs(60), 100);
+        let user = Uuid::new_v4();
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![alert("https://www.myntra.com/12345678")])
+        };
+
+        assert_eq!(cache.get_or_load(user, load()).await.unwrap().len(), 1);
+        assert_eq!(cache.get_or_load(user, load()).await.unwrap().len(), 1);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        cache.invalidate(user).await;
+        cache.get_or_load(user, load()).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+
+        // Other users have their own entries
+        cache.get_or_load(Uuid::new_v4(), load()).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_cache_always_loads() {
+        let cache = AlertCache::disabled();
+        let user = Uuid::new_v4();
+        let loads = AtomicUsize::new(0);
+        let load = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![])
+        };
+
+        cache.get_or_load(user, load()).await.unwrap();
+        cache.get_or_load(user, load()).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_errors_are_not_cached() {
+        let cache = AlertCache::new(Duration::from_secs(60), 100);
+        let user = Uuid::new_v4();
+
+        assert!(cache.get_or_load(user, async { Err(anyhow::anyhow!("db down")) }).await.is_err());
+        let alerts = cache.get_or_load(user, async { Ok(vec![alert("https://www.ajio.com/p/469581234")]) }).await.unwrap();
+        assert_eq!(alerts.len(), 1);
+    }
+}
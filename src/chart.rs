@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, Utc};
+use image::{codecs::png::PngEncoder, ColorType, ImageEncoder};
+use plotters::prelude::*;
+
+/// Default and allowed image sizes, in pixels
+pub const DEFAULT_SIZE: (u32, u32) = (800, 400);
+pub const WIDTHS: std::ops::RangeInclusive<u32> = 200..=2000;
+pub const HEIGHTS: std::ops::RangeInclusive<u32> = 100..=1200;
+
+const PRICE_COLOR: RGBColor = RGBColor(37, 99, 235);
+const TARGET_COLOR: RGBColor = RGBColor(220, 38, 38);
+const GRID_COLOR: RGBColor = RGBColor(229, 231, 235);
+
+/// Days covered by a `range` of `7d`, `30d`, `90d` or `1y`; `None` for `all`.
+/// The outer `None` means the range isn't one of those.
+pub fn range_days(range: &str) -> Option<Option<i64>> {
+    match range {
+        "7d" => Some(Some(7)),
+        "30d" => Some(Some(30)),
+        "90d" => Some(Some(90)),
+        "1y" => Some(Some(365)),
+        "all" => Some(None),
+        _ => None,
+    }
+}
+
+/// Prices over time from `from` to `to`, with the alert's target as a dashed line, as a PNG.
+/// Axis labels need a system font; without one the chart is drawn without them.
+pub fn render_png(
+    points: &[(DateTime<Utc>, f64)],
+    target: Option<f64>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    (width, height): (u32, u32),
+) -> Result<Vec<u8>> {
+    let mut pixels = vec![0u8; width as usize * height as usize * 3];
+    if let Err(e) = draw(&mut pixels, points, target, from, to, (width, height), true) {
+        tracing::debug!("Drawing the chart without labels: {}", e);
+        draw(&mut pixels, points, target, from, to, (width, height), false)?;
+    }
+
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png).write_image(&pixels, width, height, ColorType::Rgb8)?;
+    Ok(png)
+}
+
+fn draw(
+    pixels: &mut [u8],
+    points: &[(DateTime<Utc>, f64)],
+    target: Option<f64>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    size: (u32, u32),
+    labels: bool,
+) -> Result<()> {
+    let root = BitMapBackend::with_buffer(pixels, size).into_drawing_area();
+    root.fill(&WHITE).map_err(|e| anyhow!("{}", e))?;
+
+    // Room for the target and a little air above and below the line
+    let prices = points.iter().map(|(_, price)| *price).chain(target);
+    let (low, high) = prices.fold((f64::MAX, f64::MIN), |(low, high), price| (low.min(price), high.max(price)));
+    let (low, high) = if low > high { (0.0, 1.0) } else { (low, high) };
+    let pad = ((high - low) * 0.1).max(high.abs() * 0.02).max(1.0);
+    let to = if to > from { to } else { from + Duration::days(1) };
+
+    let mut builder = ChartBuilder::on(&root);
+    builder.margin(12);
+    if labels {
+        builder.x_label_area_size(28).y_label_area_size(64);
+    }
+    let mut chart = builder
+        .build_cartesian_2d(from..to, (low - pad).max(0.0)..high + pad)
+        .map_err(|e| anyhow!("{}", e))?;
+
+    let date_format = if to - from > Duration::days(300) { "%b %Y" } else { "%d %b" };
+    let format_date = |at: &DateTime<Utc>| at.format(date_format).to_string();
+    let format_price = |price: &f64| format!("{:.0}", price);
+    let mut mesh = chart.configure_mesh();
+    mesh.light_line_style(GRID_COLOR).bold_line_style(GRID_COLOR);
+    if labels {
+        mesh.x_labels(6)
+            .y_labels(6)
+            .x_label_formatter(&format_date)
+            .y_label_formatter(&format_price)
+            .label_style(("sans-serif", 13));
+    } else {
+        mesh.disable_x_axis().disable_y_axis();
+    }
+    mesh.draw().map_err(|e| anyhow!("{}", e))?;
+
+    if let Some(target) = target {
+        chart
+            .draw_series(DashedLineSeries::new([(from, target), (to, target)], 8, 6, TARGET_COLOR.stroke_width(2)))
+            .map_err(|e| anyhow!("{}", e))?;
+    }
+    chart
+        .draw_series(LineSeries::new(points.iter().copied(), PRICE_COLOR.stroke_width(2)))
+        .map_err(|e| anyhow!("{}", e))?;
+    // A lone check is a line of length zero, so mark every point
+    chart
+        .draw_series(points.iter().map(|&point| Circle::new(point, 3, PRICE_COLOR.filled())))
+        .map_err(|e| anyhow!("{}", e))?;
+
+    root.present().map_err(|e| anyhow!("{}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_range_days() {
+        assert_eq!(range_days("30d"), Some(Some(30)));
+        assert_eq!(range_days("all"), Some(None));
+        assert_eq!(range_days("2w"), None);
+    }
+
+    #[test]
+    fn test_render_png() {
+        let start = Utc.with_ymd_and_hms(2026, 9, 1, 0, 0, 0).unwrap();
+        let points: Vec<_> = [1499.0, 1399.0, 1299.0, 999.0]
+            .iter()
+            .enumerate()
+            .map(|(day, price)| (start + Duration::days(day as i64 * 7), *price))
+            .collect();
+
+        let png = render_png(&points, Some(1099.0), start, start + Duration::days(30), (400, 200)).unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+
+        // Nothing to plot yet still makes an image
+        assert!(render_png(&[], None, start, start, (200, 100)).is_ok());
+    }
+}
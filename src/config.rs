@@ -0,0 +1,570 @@
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use std::time::Duration;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use crate::auth::CSRF_HEADER;
+use crate::legal::TERMS_HEADER;
+use crate::request_id::REQUEST_ID_HEADER;
+
+/// Parsed env var, or `default` when unset or unparsable
+fn var_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+}
+
+/// Comma-separated env var as trimmed, non-empty items; `None` when unset or blank
+fn list_var(key: &str) -> Option<Vec<String>> {
+    let items: Vec<String> = std::env::var(key)
+        .ok()?
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect();
+    (!items.is_empty()).then_some(items)
+}
+
+/// Which origins may call the API from a browser
+#[derive(Debug, Clone, PartialEq)]
+pub enum AllowedOrigins {
+    /// `CORS_ALLOWED_ORIGINS=*`, for local development against another port
+    Any,
+    List(Vec<HeaderValue>),
+}
+
+/// Cross-origin settings, from `CORS_ALLOWED_ORIGINS` / `CORS_ALLOWED_METHODS` / `CORS_ALLOWED_HEADERS`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorsConfig {
+    pub origins: AllowedOrigins,
+    pub methods: Vec<Method>,
+    pub headers: Vec<HeaderName>,
+}
+
+impl CorsConfig {
+    /// Defaults to the deployed frontend's origin (from `APP_BASE_URL`) only
+    pub fn from_env() -> Self {
+        let origins = match list_var("CORS_ALLOWED_ORIGINS") {
+            Some(origins) if origins.iter().any(|o| o == "*") => AllowedOrigins::Any,
+            Some(origins) => AllowedOrigins::List(
+                origins
+                    .iter()
+                    .filter_map(|origin| match HeaderValue::from_str(origin.trim_end_matches('/')) {
+                        Ok(value) => Some(value),
+                        Err(_) => {
+                            tracing::warn!("Ignoring invalid CORS origin '{}'", origin);
+                            None
+                        }
+                    })
+                    .collect(),
+            ),
+            None => AllowedOrigins::List(frontend_origin().into_iter().collect()),
+        };
+
+        let methods = list_var("CORS_ALLOWED_METHODS")
+            .map(|methods| {
+                methods
+                    .iter()
+                    .filter_map(|m| Method::from_bytes(m.to_uppercase().as_bytes()).ok())
+                    .collect()
+            })
+            .unwrap_or_else(|| vec![Method::GET, Method::POST, Method::PUT, Method::DELETE]);
+
+        let headers = list_var("CORS_ALLOWED_HEADERS")
+            .map(|headers| {
+                headers
+                    .iter()
+                    .filter_map(|h| HeaderName::from_bytes(h.to_lowercase().as_bytes()).ok())
+                    .collect()
+            })
+            .unwrap_or_else(|| {
+                vec![
+                    header::CONTENT_TYPE,
+                    header::AUTHORIZATION,
+                    header::IF_NONE_MATCH,
+                    HeaderName::from_static(CSRF_HEADER),
+                ]
+            });
+
+        CorsConfig { origins, methods, headers }
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        let origin = match &self.origins {
+            AllowedOrigins::Any => AllowOrigin::from(Any),
+            AllowedOrigins::List(origins) => AllowOrigin::list(origins.clone()),
+        };
+
+        // Session cookies only cross origins to a listed frontend; never with a wildcard
+        CorsLayer::new()
+            .allow_origin(origin)
+            .allow_credentials(matches!(self.origins, AllowedOrigins::List(_)))
+            .allow_methods(self.methods.clone())
+            .allow_headers(self.headers.clone())
+            .expose_headers([REQUEST_ID_HEADER, header::ETAG, TERMS_HEADER])
+    }
+}
+
+/// Postgres pool sizing, timeouts and startup retry, from `DB_*` env vars
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseConfig {
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout: Duration,
+    pub idle_timeout: Duration,
+    pub max_lifetime: Duration,
+    /// Connection attempts at startup before giving up (Postgres may still be booting)
+    pub connect_attempts: u32,
+    /// Delay after the first failed attempt; doubles each retry up to `MAX_RETRY_DELAY`
+    pub retry_delay: Duration,
+}
+
+impl DatabaseConfig {
+    pub const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+    pub fn from_env() -> Self {
+        DatabaseConfig {
+            max_connections: var_or("DB_MAX_CONNECTIONS", 10),
+            min_connections: var_or("DB_MIN_CONNECTIONS", 1),
+            acquire_timeout: Duration::from_secs(var_or("DB_ACQUIRE_TIMEOUT_SECS", 10)),
+            idle_timeout: Duration::from_secs(var_or("DB_IDLE_TIMEOUT_SECS", 10 * 60)),
+            max_lifetime: Duration::from_secs(var_or("DB_MAX_LIFETIME_SECS", 30 * 60)),
+            connect_attempts: var_or("DB_CONNECT_ATTEMPTS", 10).max(1),
+            retry_delay: Duration::from_millis(var_or("DB_CONNECT_RETRY_MS", 500)),
+        }
+    }
+
+    /// Wait before retry number `attempt` (1-based)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.retry_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(Self::MAX_RETRY_DELAY)
+    }
+}
+
+/// Redis shared by every replica, from `REDIS_URL` / `REDIS_KEY_PREFIX`.
+/// Unset means single-instance mode: caches, rate limits and locks stay in process.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedisConfig {
+    pub url: String,
+    /// Namespace for every key and channel, so deployments can share one Redis
+    pub key_prefix: String,
+}
+
+impl RedisConfig {
+    pub fn from_env() -> Option<Self> {
+        let url = std::env::var("REDIS_URL").ok().filter(|url| !url.trim().is_empty())?;
+        Some(RedisConfig {
+            url: url.trim().to_string(),
+            key_prefix: var_or("REDIS_KEY_PREFIX", "price_tracker".to_string()),
+        })
+    }
+}
+
+/// MQTT broker for publishing price checks and drops, from `MQTT_*` env vars.
+/// Unset `MQTT_HOST` disables publishing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub client_id: String,
+    pub tls: bool,
+    /// Topic templates; `{alert_id}` and `{platform}` are filled in per message
+    pub price_topic: String,
+    pub drop_topic: String,
+}
+
+impl MqttConfig {
+    pub fn from_env() -> Option<Self> {
+        let host = std::env::var("MQTT_HOST").ok().filter(|host| !host.trim().is_empty())?;
+        let tls = var_or("MQTT_TLS", false);
+        Some(MqttConfig {
+            host: host.trim().to_string(),
+            port: var_or("MQTT_PORT", if tls { 8883 } else { 1883 }),
+            username: std::env::var("MQTT_USERNAME").ok().filter(|v| !v.is_empty()),
+            password: std::env::var("MQTT_PASSWORD").ok().filter(|v| !v.is_empty()),
+            client_id: var_or("MQTT_CLIENT_ID", "price-tracker".to_string()),
+            tls,
+            price_topic: var_or("MQTT_PRICE_TOPIC", "price_tracker/{alert_id}/price".to_string()),
+            drop_topic: var_or("MQTT_DROP_TOPIC", "price_tracker/{alert_id}/drop".to_string()),
+        })
+    }
+}
+
+/// Seeding new alerts' history from Internet Archive snapshots, from `WAYBACK_*` env vars.
+/// Off unless `WAYBACK_BACKFILL` is true.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaybackConfig {
+    /// Archive to query; its CDX API is at `/cdx/search/cdx`, snapshots under `/web/`
+    pub base_url: String,
+    /// Snapshots read per alert, at most one per month, newest first
+    pub max_snapshots: usize,
+    /// How far back snapshots are looked for
+    pub lookback_days: i64,
+    /// Pause between requests to the archive, which throttles heavy clients
+    pub request_gap: Duration,
+}
+
+impl WaybackConfig {
+    pub fn from_env() -> Option<Self> {
+        if !var_or("WAYBACK_BACKFILL", false) {
+            return None;
+        }
+        Some(WaybackConfig {
+            base_url: var_or("WAYBACK_URL", "https://web.archive.org".to_string()).trim_end_matches('/').to_string(),
+            max_snapshots: var_or("WAYBACK_MAX_SNAPSHOTS", 12),
+            lookback_days: var_or("WAYBACK_LOOKBACK_DAYS", 365),
+            request_gap: Duration::from_millis(var_or("WAYBACK_REQUEST_GAP_MS", 4000)),
+        })
+    }
+}
+
+/// Pre-scraping products found in admin-added sitemaps, from `DISCOVERY_*` env vars.
+/// Nothing is read until a sitemap is added through `/admin/sitemaps`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryConfig {
+    /// How often each sitemap is read again for new products
+    pub sitemap_refresh: Duration,
+    /// How often each discovered product is checked
+    pub check_interval: Duration,
+    /// Products checked per round
+    pub batch: i64,
+    /// Sitemaps read from a sitemap index
+    pub max_sitemaps: usize,
+    /// Pause between requests, so discovery stays well behind the alert checks
+    pub request_gap: Duration,
+}
+
+impl DiscoveryConfig {
+    pub fn from_env() -> Self {
+        DiscoveryConfig {
+            sitemap_refresh: Duration::from_secs(var_or("DISCOVERY_SITEMAP_REFRESH_HOURS", 24) * 3600),
+            check_interval: Duration::from_secs(var_or("DISCOVERY_CHECK_HOURS", 12) * 3600),
+            batch: var_or("DISCOVERY_BATCH", 50),
+            max_sitemaps: var_or("DISCOVERY_MAX_SITEMAPS", 10),
+            request_gap: Duration::from_millis(var_or("DISCOVERY_REQUEST_GAP_MS", 3000)),
+        }
+    }
+}
+
+/// When a platform's failure rate spreads its checks out or brings them back, from
+/// `THROTTLE_*` env vars. On unless `THROTTLE_ENABLED` is false.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrottleConfig {
+    /// Share of failed checks at or above which a platform is checked half as often
+    pub failure_rate: f64,
+    /// Share at or below which a throttled platform is checked twice as often again
+    pub recovery_rate: f64,
+    /// Checks since the last adjustment needed before judging a platform
+    pub min_attempts: i64,
+    /// How far back checks are counted
+    pub window: chrono::Duration,
+}
+
+impl ThrottleConfig {
+    pub fn from_env() -> Option<Self> {
+        if !var_or("THROTTLE_ENABLED", true) {
+            return None;
+        }
+        Some(ThrottleConfig {
+            failure_rate: var_or("THROTTLE_FAILURE_RATE", 0.5),
+            recovery_rate: var_or("THROTTLE_RECOVERY_RATE", 0.2),
+            min_attempts: var_or("THROTTLE_MIN_ATTEMPTS", 20),
+            window: chrono::Duration::hours(var_or("THROTTLE_WINDOW_HOURS", 24)),
+        })
+    }
+}
+
+/// The HTTP client every scraper shares, from `SCRAPER_HTTP_*` env vars
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScraperHttpConfig {
+    pub user_agent: String,
+    pub connect_timeout: Duration,
+    /// Whole request, including reading the body; a platform's `timeout_secs` override wins
+    pub timeout: Duration,
+    /// Redirects followed before giving up; 0 disables following
+    pub max_redirects: usize,
+    /// Idle keep-alive connections kept per site
+    pub pool_max_idle_per_host: usize,
+    pub pool_idle_timeout: Duration,
+    /// PEM file with an extra root certificate, e.g. for an intercepting proxy
+    pub ca_cert: Option<String>,
+    /// Skip certificate verification entirely (debugging only)
+    pub accept_invalid_certs: bool,
+}
+
+impl ScraperHttpConfig {
+    pub const DEFAULT_USER_AGENT: &'static str =
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36";
+
+    pub fn from_env() -> Self {
+        ScraperHttpConfig {
+            user_agent: var_or("SCRAPER_HTTP_USER_AGENT", Self::DEFAULT_USER_AGENT.to_string()),
+            connect_timeout: Duration::from_secs(var_or("SCRAPER_HTTP_CONNECT_TIMEOUT_SECS", 10)),
+            timeout: Duration::from_secs(var_or("SCRAPER_HTTP_TIMEOUT_SECS", 30)),
+            max_redirects: var_or("SCRAPER_HTTP_MAX_REDIRECTS", 10),
+            pool_max_idle_per_host: var_or("SCRAPER_HTTP_POOL_MAX_IDLE", 8),
+            pool_idle_timeout: Duration::from_secs(var_or("SCRAPER_HTTP_POOL_IDLE_SECS", 90)),
+            ca_cert: std::env::var("SCRAPER_HTTP_CA_CERT").ok().filter(|path| !path.trim().is_empty()),
+            accept_invalid_certs: var_or("SCRAPER_HTTP_INSECURE", false),
+        }
+    }
+}
+
+/// Which platforms new alerts may target, from `ENABLED_PLATFORMS` / `DISABLED_PLATFORMS`
+/// (comma-separated platform names), so a deployment can stick to scrapers that
+/// currently work. Existing alerts keep being checked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlatformPolicy {
+    /// Only these platforms, when set
+    pub enabled: Option<Vec<String>>,
+    /// Never these, even if listed in `enabled`
+    pub disabled: Vec<String>,
+}
+
+impl PlatformPolicy {
+    pub fn from_env() -> Self {
+        let names = |key: &str| {
+            list_var(key).map(|names| {
+                names
+                    .into_iter()
+                    .map(|name| name.to_lowercase())
+                    .inspect(|name| {
+                        if crate::scrapers::create_scraper(name).is_none() {
+                            tracing::warn!("{} names unknown platform '{}'", key, name);
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        PlatformPolicy {
+            enabled: names("ENABLED_PLATFORMS"),
+            disabled: names("DISABLED_PLATFORMS").unwrap_or_default(),
+        }
+    }
+
+    pub fn allows(&self, platform: &str) -> bool {
+        self.enabled.as_ref().is_none_or(|enabled| enabled.iter().any(|p| p == platform))
+            && !self.disabled.iter().any(|p| p == platform)
+    }
+}
+
+/// Scheme, host and port of `APP_BASE_URL`, as browsers send it in `Origin`
+fn frontend_origin() -> Option<HeaderValue> {
+    let url = reqwest::Url::parse(&crate::email::app_base_url()).ok()?;
+    HeaderValue::from_str(&url.origin().ascii_serialization()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+
+    fn clear_env() {
+        for key in ["CORS_ALLOWED_ORIGINS", "CORS_ALLOWED_METHODS", "CORS_ALLOWED_HEADERS", "APP_BASE_URL"] {
+            unsafe { std::env::remove_var(key); }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_cors_defaults_to_frontend_origin() {
+        clear_env();
+        unsafe { std::env::set_var("APP_BASE_URL", "https://prices.example.com/app/"); }
+
+        let config = CorsConfig::from_env();
+        assert_eq!(config.origins, AllowedOrigins::List(vec![HeaderValue::from_static("https://prices.example.com")]));
+        assert_eq!(config.methods, vec![Method::GET, Method::POST, Method::PUT, Method::DELETE]);
+        assert!(config.headers.contains(&header::AUTHORIZATION));
+        assert!(config.headers.contains(&HeaderName::from_static(CSRF_HEADER)));
+
+        clear_env();
+    }
+
+    #[test]
+    #[serial]
+    fn test_cors_from_env() {
+        clear_env();
+        unsafe {
+            std::env::set_var("CORS_ALLOWED_ORIGINS", "https://a.example.com/, https://b.example.com");
+            std::env::set_var("CORS_ALLOWED_METHODS", "get,post");
+            std::env::set_var("CORS_ALLOWED_HEADERS", "Content-Type");
+        }
+
+        let config = CorsConfig::from_env();
+        assert_eq!(
+            config.origins,
+            AllowedOrigins::List(vec![
+                HeaderValue::from_static("https://a.example.com"),
+                HeaderValue::from_static("https://b.example.com"),
+            ])
+        );
+        assert_eq!(config.methods, vec![Method::GET, Method::POST]);
+        assert_eq!(config.headers, vec![header::CONTENT_TYPE]);
+
+        unsafe { std::env::set_var("CORS_ALLOWED_ORIGINS", "*"); }
+        assert_eq!(CorsConfig::from_env().origins, AllowedOrigins::Any);
+
+        clear_env();
+    }
+
+    #[test]
+    #[serial]
+    fn test_database_config() {
+        unsafe {
+            std::env::set_var("DB_MAX_CONNECTIONS", "25");
+            std::env::set_var("DB_CONNECT_ATTEMPTS", "0");
+            std::env::set_var("DB_ACQUIRE_TIMEOUT_SECS", "soon");
+        }
+
+        let config = DatabaseConfig::from_env();
+        assert_eq!(config.max_connections, 25);
+        assert_eq!(config.connect_attempts, 1);
+        assert_eq!(config.acquire_timeout, Duration::from_secs(10));
+
+        assert_eq!(config.backoff(1), Duration::from_millis(500));
+        assert_eq!(config.backoff(3), Duration::from_secs(2));
+        assert_eq!(config.backoff(20), DatabaseConfig::MAX_RETRY_DELAY);
+
+        unsafe {
+            std::env::remove_var("DB_MAX_CONNECTIONS");
+            std::env::remove_var("DB_CONNECT_ATTEMPTS");
+            std::env::remove_var("DB_ACQUIRE_TIMEOUT_SECS");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_mqtt_config() {
+        for key in ["MQTT_HOST", "MQTT_PORT", "MQTT_TLS", "MQTT_PRICE_TOPIC"] {
+            unsafe { std::env::remove_var(key); }
+        }
+        assert_eq!(MqttConfig::from_env(), None);
+
+        unsafe {
+            std::env::set_var("MQTT_HOST", "broker.local");
+            std::env::set_var("MQTT_TLS", "true");
+            std::env::set_var("MQTT_PRICE_TOPIC", "home/prices/{platform}/{alert_id}");
+        }
+        let config = MqttConfig::from_env().unwrap();
+        assert_eq!(config.port, 8883);
+        assert_eq!(config.price_topic, "home/prices/{platform}/{alert_id}");
+        assert_eq!(config.drop_topic, "price_tracker/{alert_id}/drop");
+
+        for key in ["MQTT_HOST", "MQTT_TLS", "MQTT_PRICE_TOPIC"] {
+            unsafe { std::env::remove_var(key); }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_wayback_config() {
+        unsafe { std::env::remove_var("WAYBACK_BACKFILL"); }
+        assert_eq!(WaybackConfig::from_env(), None);
+
+        unsafe {
+            std::env::set_var("WAYBACK_BACKFILL", "true");
+            std::env::set_var("WAYBACK_URL", "http://archive.local/");
+        }
+        let config = WaybackConfig::from_env().unwrap();
+        assert_eq!(config.base_url, "http://archive.local");
+        assert_eq!((config.max_snapshots, config.lookback_days), (12, 365));
+
+        for key in ["WAYBACK_BACKFILL", "WAYBACK_URL"] {
+            unsafe { std::env::remove_var(key); }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_discovery_config() {
+        unsafe { std::env::set_var("DISCOVERY_CHECK_HOURS", "6"); }
+        let config = DiscoveryConfig::from_env();
+        assert_eq!(config.check_interval, Duration::from_secs(6 * 3600));
+        assert_eq!(config.sitemap_refresh, Duration::from_secs(24 * 3600));
+        assert_eq!((config.batch, config.max_sitemaps), (50, 10));
+
+        unsafe { std::env::remove_var("DISCOVERY_CHECK_HOURS"); }
+    }
+
+    #[test]
+    #[serial]
+    fn test_throttle_config() {
+        unsafe { std::env::set_var("THROTTLE_FAILURE_RATE", "0.6"); }
+        let config = ThrottleConfig::from_env().unwrap();
+        assert_eq!((config.failure_rate, config.recovery_rate, config.min_attempts), (0.6, 0.2, 20));
+
+        unsafe { std::env::set_var("THROTTLE_ENABLED", "false"); }
+        assert_eq!(ThrottleConfig::from_env(), None);
+
+        for key in ["THROTTLE_FAILURE_RATE", "THROTTLE_ENABLED"] {
+            unsafe { std::env::remove_var(key); }
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_scraper_http_config() {
+        unsafe {
+            std::env::set_var("SCRAPER_HTTP_TIMEOUT_SECS", "45");
+            std::env::set_var("SCRAPER_HTTP_MAX_REDIRECTS", "0");
+            std::env::set_var("SCRAPER_HTTP_CA_CERT", "");
+        }
+
+        let config = ScraperHttpConfig::from_env();
+        assert_eq!(config.timeout, Duration::from_secs(45));
+        assert_eq!(config.connect_timeout, Duration::from_secs(10));
+        assert_eq!(config.max_redirects, 0);
+        assert_eq!(config.ca_cert, None);
+        assert!(!config.accept_invalid_certs);
+        assert_eq!(config.user_agent, ScraperHttpConfig::DEFAULT_USER_AGENT);
+
+        unsafe {
+            std::env::remove_var("SCRAPER_HTTP_TIMEOUT_SECS");
+            std::env::remove_var("SCRAPER_HTTP_MAX_REDIRECTS");
+            std::env::remove_var("SCRAPER_HTTP_CA_CERT");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_redis_config() {
+        unsafe {
+            std::env::remove_var("REDIS_KEY_PREFIX");
+            std::env::set_var("REDIS_URL", " ");
+        }
+        assert_eq!(RedisConfig::from_env(), None);
+
+        unsafe { std::env::set_var("REDIS_URL", "redis://cache:6379/0"); }
+        let config = RedisConfig::from_env().unwrap();
+        assert_eq!(config.url, "redis://cache:6379/0");
+        assert_eq!(config.key_prefix, "price_tracker");
+
+        unsafe { std::env::remove_var("REDIS_URL"); }
+    }
+
+    #[test]
+    #[serial]
+    fn test_platform_policy() {
+        unsafe {
+            std::env::remove_var("ENABLED_PLATFORMS");
+            std::env::remove_var("DISABLED_PLATFORMS");
+        }
+        assert!(PlatformPolicy::from_env().allows("flipkart"));
+
+        unsafe { std::env::set_var("DISABLED_PLATFORMS", "Flipkart"); }
+        let policy = PlatformPolicy::from_env();
+        assert!(!policy.allows("flipkart"));
+        assert!(policy.allows("myntra"));
+
+        unsafe { std::env::set_var("ENABLED_PLATFORMS", "myntra, flipkart"); }
+        let policy = PlatformPolicy::from_env();
+        assert!(policy.allows("myntra"));
+        assert!(!policy.allows("flipkart"));
+        assert!(!policy.allows("ajio"));
+
+        unsafe {
+            std::env::remove_var("ENABLED_PLATFORMS");
+            std::env::remove_var("DISABLED_PLATFORMS");
+        }
+    }
+}
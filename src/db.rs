@@ -1,27 +1,151 @@
 use anyhow::Result;
+use crate::cache::AlertCache;
+use crate::config::DatabaseConfig;
 use sqlx::{PgPool, postgres::PgPoolOptions};
-use crate::models::{PriceAlert, PriceHistory, PriceStats, User};
-use chrono::Utc;
+use crate::models::{AccountExport, AlertTemplate, AlertTemplateRequest, AlertThreshold, Annotation, AnnotationKind, ApiKey, CatalogProduct, CheckCheckpoint, CreateKeywordWatchRequest, CreateListingAlertRequest, DailyPlatformPrice, DropFeedItem, ExportedPrice, HistoryCursor, ImportSummary, KeywordWatch, ListingAlert, ListingPricePoint, NotificationChannel, Offer, OutboxEntry, PlatformThrottle, PriceAlert, PriceBadge, PriceDropEvent, PriceHistory, PriceLows, PriceStats, PriceUpdate, Product, PublicDailyPrice, PublicStats, Purchase, Role, ScrapeStats, ScraperConfig, SitemapSource, SummaryDrop, SummaryFailingAlert, SummaryNearTarget, ThrottleAdjustment, TimelineEntry, UpsertScraperConfigRequest, User, WeeklySummary};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use uuid::Uuid;
+use crate::notifier_trait::{NotificationEvent, PriceDrop};
+use crate::plans::Plan;
+use tokio_stream::StreamExt;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Rows read ahead of a streamed response
+const HISTORY_STREAM_BUFFER: usize = 256;
+
+/// Indexes the hot queries rely on, checked at startup by `missing_indexes`.
+/// Keep in step with the `CREATE INDEX` statements in `create_tables`.
+pub const EXPECTED_INDEXES: &[&str] = &[
+    "idx_users_email",
+    "idx_alert_templates_user_id",
+    "idx_is_active",
+    "idx_user_id",
+    "idx_price_alerts_user_active",
+    "idx_alert_id",
+    "idx_price_history_alert_price",
+    "idx_price_history_alert_checked",
+    "idx_price_history_alert_recent",
+    "idx_price_drop_events_alert_time",
+    "idx_price_drop_events_user_time",
+    "idx_annotations_alert_time",
+    "idx_purchases_user_time",
+    "idx_scrape_attempts_platform_time",
+    "idx_notification_deliveries_time",
+    "idx_deferred_notifications_time",
+    "idx_listing_alerts_user_id",
+    "idx_listing_alerts_user_active",
+    "idx_listing_price_history_alert",
+    "idx_keyword_watches_user_id",
+    "idx_keyword_watches_user_active",
+    "idx_product_alerts_product_id",
+    "idx_api_keys_user_id",
+    "idx_catalog_price_history_product",
+];
 
 #[derive(Clone)]
 pub struct Database {
     pub pool: PgPool,
+    /// Active alerts per user; every write below to `price_alerts` invalidates the owner's entry
+    alert_cache: AlertCache,
 }
 
 impl Database {
     pub async fn new(database_url: &str) -> Result<Self> {
-        let pool = PgPoolOptions::new()
-            .max_connections(5)
-            .connect(database_url)
-            .await?;
+        Self::connect(database_url, &DatabaseConfig::from_env()).await
+    }
+    
+    /// Connect, retrying with backoff while Postgres isn't accepting connections yet
+    /// (e.g. when both containers start together)
+    pub async fn connect(database_url: &str, config: &DatabaseConfig) -> Result<Self> {
+        // Connections are checked before being handed out and recycled periodically,
+        // so the pool recovers on its own after a database restart
+        let options = PgPoolOptions::new()
+            .max_connections(config.max_connections)
+            .min_connections(config.min_connections)
+            .acquire_timeout(config.acquire_timeout)
+            .idle_timeout(config.idle_timeout)
+            .max_lifetime(config.max_lifetime)
+            .test_before_acquire(true);
         
-        // Run migrations to create tables if they don't exist
-        Self::create_tables(&pool).await?;
+        let mut attempt = 1;
+        let pool = loop {
+            match options.clone().connect(database_url).await {
+                Ok(pool) => break pool,
+                Err(e) if attempt < config.connect_attempts => {
+                    let delay = config.backoff(attempt);
+                    tracing::warn!(
+                        "Database not reachable (attempt {}/{}): {}. Retrying in {:?}",
+                        attempt,
+                        config.connect_attempts,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
+        
+        let db = Self::from_pool(pool).await?;
         
         tracing::info!("Successfully connected to Supabase PostgreSQL");
         
-        Ok(Database { pool })
+        Ok(db)
+    }
+    
+    /// Swap the alert cache, e.g. for the Redis-backed one shared between replicas
+    pub fn with_alert_cache(mut self, alert_cache: AlertCache) -> Self {
+        self.alert_cache = alert_cache;
+        self
+    }
+    
+    /// Cheap round trip for health checks
+    pub async fn ping(&self) -> Result<()> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+    
+    /// Wrap an existing pool, creating tables if they don't exist
+    pub async fn from_pool(pool: PgPool) -> Result<Self> {
+        Self::create_tables(&pool).await?;
+        
+        let db = Database { pool, alert_cache: AlertCache::from_env() };
+        match db.missing_indexes().await {
+            Ok(missing) if !missing.is_empty() => tracing::warn!(
+                "⚠️  Expected indexes missing or invalid: {} (queries on these tables will fall back to scans)",
+                missing.join(", ")
+            ),
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Couldn't check indexes: {}", e),
+        }
+        
+        Ok(db)
+    }
+    
+    /// Indexes from `EXPECTED_INDEXES` that don't exist in the current schema, or that
+    /// exist but are invalid (e.g. a `CREATE INDEX CONCURRENTLY` that was interrupted)
+    pub async fn missing_indexes(&self) -> Result<Vec<String>> {
+        let missing = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT expected.name
+            FROM UNNEST($1::text[]) WITH ORDINALITY AS expected(name, position)
+            WHERE NOT EXISTS (
+                SELECT 1 FROM pg_class c
+                JOIN pg_index i ON i.indexrelid = c.oid
+                WHERE c.relname = expected.name
+                  AND c.relnamespace = current_schema()::regnamespace
+                  AND i.indisvalid
+            )
+            ORDER BY expected.position
+            "#
+        )
+        .bind(EXPECTED_INDEXES)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(missing)
     }
     
     async fn create_tables(pool: &PgPool) -> Result<()> {
@@ -40,6 +164,76 @@ impl Database {
         .execute(pool)
         .await?;
         
+        // Login throttling state
+        sqlx::query(
+            r#"
+            ALTER TABLE users
+                ADD COLUMN IF NOT EXISTS failed_login_attempts INTEGER NOT NULL DEFAULT 0,
+                ADD COLUMN IF NOT EXISTS locked_until TIMESTAMPTZ,
+                ADD COLUMN IF NOT EXISTS unlock_token TEXT
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Billing tier; limits live in code (plans.rs), keyed by this name
+        sqlx::query("ALTER TABLE users ADD COLUMN IF NOT EXISTS plan TEXT NOT NULL DEFAULT 'free'")
+            .execute(pool)
+            .await?;
+        
+        // IANA zone that notification timestamps are shown in
+        sqlx::query("ALTER TABLE users ADD COLUMN IF NOT EXISTS timezone TEXT NOT NULL DEFAULT 'UTC'")
+            .execute(pool)
+            .await?;
+        
+        // Set by the account-wide unsubscribe link; nothing is delivered until it's cleared
+        sqlx::query("ALTER TABLE users ADD COLUMN IF NOT EXISTS notifications_paused BOOLEAN NOT NULL DEFAULT FALSE")
+            .execute(pool)
+            .await?;
+        
+        sqlx::query("ALTER TABLE users ADD COLUMN IF NOT EXISTS default_discount_percent DOUBLE PRECISION")
+            .execute(pool)
+            .await?;
+        
+        // Access: `role` is 'user' or 'admin'; `verification_token` is cleared once the address is confirmed
+        sqlx::query(
+            r#"
+            ALTER TABLE users
+                ADD COLUMN IF NOT EXISTS role TEXT NOT NULL DEFAULT 'user',
+                ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMPTZ,
+                ADD COLUMN IF NOT EXISTS verification_token TEXT
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Set from account settings; keeps the user's alerts out of public charts and stats
+        sqlx::query("ALTER TABLE users ADD COLUMN IF NOT EXISTS exclude_from_public BOOLEAN NOT NULL DEFAULT FALSE")
+            .execute(pool)
+            .await?;
+        
+        // Terms of service: the version last accepted, and when (see legal::Terms)
+        sqlx::query(
+            r#"
+            ALTER TABLE users
+                ADD COLUMN IF NOT EXISTS terms_version TEXT,
+                ADD COLUMN IF NOT EXISTS terms_accepted_at TIMESTAMPTZ
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Opt-in weekly summary email, and when the last one went out (see summary.rs)
+        sqlx::query(
+            r#"
+            ALTER TABLE users
+                ADD COLUMN IF NOT EXISTS weekly_summary BOOLEAN NOT NULL DEFAULT FALSE,
+                ADD COLUMN IF NOT EXISTS weekly_summary_sent_at TIMESTAMPTZ
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
         // Create index on email for faster lookups
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_users_email ON users(email)")
             .execute(pool)
@@ -64,6 +258,106 @@ impl Database {
         .execute(pool)
         .await?;
         
+        // Per-alert notification channel selection
+        sqlx::query("ALTER TABLE price_alerts ADD COLUMN IF NOT EXISTS notify_channels UUID[]")
+            .execute(pool)
+            .await?;
+        
+        // Platform product ID, so alerts survive URL format changes
+        sqlx::query("ALTER TABLE price_alerts ADD COLUMN IF NOT EXISTS product_id TEXT")
+            .execute(pool)
+            .await?;
+        
+        // Back-in-stock alerts
+        sqlx::query(
+            r#"
+            ALTER TABLE price_alerts
+                ADD COLUMN IF NOT EXISTS alert_mode TEXT NOT NULL DEFAULT 'price',
+                ADD COLUMN IF NOT EXISTS in_stock BOOLEAN
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Latest coupons/bank offers per alert
+        sqlx::query("ALTER TABLE price_alerts ADD COLUMN IF NOT EXISTS offers JSONB NOT NULL DEFAULT '[]'")
+            .execute(pool)
+            .await?;
+        
+        // Bumped on every user edit, so concurrent PUTs can't overwrite each other
+        sqlx::query("ALTER TABLE price_alerts ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1")
+            .execute(pool)
+            .await?;
+        
+        // Per-alert check interval and expiry (usually from an alert template)
+        sqlx::query(
+            r#"
+            ALTER TABLE price_alerts
+                ADD COLUMN IF NOT EXISTS check_interval_minutes INTEGER,
+                ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // When to notify again after the first drop (see `RenotifyPolicy`)
+        sqlx::query(r#"ALTER TABLE price_alerts ADD COLUMN IF NOT EXISTS renotify JSONB NOT NULL DEFAULT '{"policy": "always"}'"#)
+            .execute(pool)
+            .await?;
+        
+        // Plausible price range; checks outside it are saved as suspect
+        sqlx::query(
+            r#"
+            ALTER TABLE price_alerts
+                ADD COLUMN IF NOT EXISTS min_price DOUBLE PRECISION,
+                ADD COLUMN IF NOT EXISTS max_price DOUBLE PRECISION
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Dead-link detection: 404/410s in a row, and when they marked the product removed
+        sqlx::query(
+            r#"
+            ALTER TABLE price_alerts
+                ADD COLUMN IF NOT EXISTS gone_checks INTEGER NOT NULL DEFAULT 0,
+                ADD COLUMN IF NOT EXISTS product_removed_at TIMESTAMPTZ
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // What the product page last showed, for notifications
+        sqlx::query("ALTER TABLE price_alerts ADD COLUMN IF NOT EXISTS product_name TEXT, ADD COLUMN IF NOT EXISTS image_url TEXT")
+            .execute(pool)
+            .await?;
+        
+        // Per-alert counterpart of users.notifications_paused, set by the email's unsubscribe link
+        sqlx::query("ALTER TABLE price_alerts ADD COLUMN IF NOT EXISTS notifications_paused BOOLEAN NOT NULL DEFAULT FALSE")
+            .execute(pool)
+            .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS alert_templates (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                discount_percent DOUBLE PRECISION,
+                notify_channels UUID[],
+                check_interval_minutes INTEGER,
+                expires_after_days INTEGER,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_alert_templates_user_id ON alert_templates(user_id)")
+            .execute(pool)
+            .await?;
+        
         // Create index on is_active for faster queries
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_is_active ON price_alerts(is_active)")
             .execute(pool)
@@ -74,6 +368,11 @@ impl Database {
             .execute(pool)
             .await?;
         
+        // A user's active alerts, newest first (the dashboard list)
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_price_alerts_user_active ON price_alerts(user_id, is_active, created_at)")
+            .execute(pool)
+            .await?;
+        
         // Create price_history table for tracking price changes over time
         sqlx::query(
             r#"
@@ -89,166 +388,3567 @@ impl Database {
         .await?;
         
         // Create index on alert_id for faster lookups
+        // List price alongside the selling price, when the page showed one
+        sqlx::query("ALTER TABLE price_history ADD COLUMN IF NOT EXISTS mrp DOUBLE PRECISION")
+            .execute(pool)
+            .await?;
+        
+        // Prices far off the recent median, kept for the record but left out of stats and alerts
+        sqlx::query("ALTER TABLE price_history ADD COLUMN IF NOT EXISTS suspect BOOLEAN NOT NULL DEFAULT FALSE")
+            .execute(pool)
+            .await?;
+        
         sqlx::query("CREATE INDEX IF NOT EXISTS idx_alert_id ON price_history(alert_id)")
             .execute(pool)
             .await?;
         
-        tracing::info!("Database tables verified/created");
-        Ok(())
-    }
-    
-    pub async fn create_alert(&self, alert: &PriceAlert) -> Result<PriceAlert> {
-        let result = sqlx::query_as::<_, PriceAlert>(
+        // Index-only lookups of an alert's lowest price, overall and within a window
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_price_history_alert_price ON price_history(alert_id, price)")
+            .execute(pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_price_history_alert_checked ON price_history(alert_id, checked_at) INCLUDE (price)")
+            .execute(pool)
+            .await?;
+        // Keyset paging through an alert's history, newest first; stored in that order so
+        // the chart and export queries read it forwards
+        sqlx::query("DROP INDEX IF EXISTS idx_price_history_alert_checked_id")
+            .execute(pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_price_history_alert_recent ON price_history(alert_id, checked_at DESC, id DESC)")
+            .execute(pool)
+            .await?;
+        
+        // Create price_drop_events table: one row each time a check meets an alert's target,
+        // pointing at the history snapshot that triggered it
+        sqlx::query(
             r#"
-            INSERT INTO price_alerts (url, target_price, last_price, user_email, platform, created_at, last_checked, is_active)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING *
+            CREATE TABLE IF NOT EXISTS price_drop_events (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                alert_id UUID NOT NULL REFERENCES price_alerts(id) ON DELETE CASCADE,
+                user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+                history_id UUID REFERENCES price_history(id) ON DELETE SET NULL,
+                price DOUBLE PRECISION NOT NULL,
+                target_price DOUBLE PRECISION NOT NULL,
+                previous_price DOUBLE PRECISION,
+                badge TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
             "#
         )
-        .bind(&alert.url)
-        .bind(alert.target_price)
-        .bind(alert.last_price)
-        .bind(&alert.user_email)
-        .bind(&alert.platform)
-        .bind(alert.created_at)
-        .bind(alert.last_checked)
-        .bind(alert.is_active)
-        .fetch_one(&self.pool)
+        .execute(pool)
         .await?;
         
-        Ok(result)
-    }
-    
-    pub async fn get_all_active_alerts(&self) -> Result<Vec<PriceAlert>> {
-        let alerts = sqlx::query_as::<_, PriceAlert>(
-            "SELECT * FROM price_alerts WHERE is_active = TRUE ORDER BY created_at DESC"
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_price_drop_events_alert_time ON price_drop_events(alert_id, created_at)")
+            .execute(pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_price_drop_events_user_time ON price_drop_events(user_id, created_at)")
+            .execute(pool)
+            .await?;
+        
+        // Price ladder: extra thresholds above the target, each notified once
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS alert_thresholds (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                alert_id UUID NOT NULL REFERENCES price_alerts(id) ON DELETE CASCADE,
+                price DOUBLE PRECISION NOT NULL,
+                notified_at TIMESTAMPTZ,
+                notified_price DOUBLE PRECISION,
+                UNIQUE (alert_id, price)
+            )
+            "#
         )
-        .fetch_all(&self.pool)
+        .execute(pool)
         .await?;
         
-        Ok(alerts)
-    }
-    
-    pub async fn update_alert_price(&self, id: Uuid, last_price: f64) -> Result<()> {
+        // Create annotations table: markers on an alert's price history, from the user or the worker
         sqlx::query(
-            "UPDATE price_alerts SET last_price = $1, last_checked = $2 WHERE id = $3"
+            r#"
+            CREATE TABLE IF NOT EXISTS annotations (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                alert_id UUID NOT NULL REFERENCES price_alerts(id) ON DELETE CASCADE,
+                user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+                history_id UUID REFERENCES price_history(id) ON DELETE SET NULL,
+                kind TEXT NOT NULL,
+                note TEXT,
+                source TEXT NOT NULL DEFAULT 'user',
+                annotated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
         )
-        .bind(last_price)
-        .bind(Utc::now())
-        .bind(id)
-        .execute(&self.pool)
+        .execute(pool)
         .await?;
         
-        Ok(())
-    }
-    
-    pub async fn delete_alert(&self, id: Uuid) -> Result<()> {
-        sqlx::query("UPDATE price_alerts SET is_active = FALSE WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_annotations_alert_time ON annotations(alert_id, annotated_at)")
+            .execute(pool)
             .await?;
         
-        Ok(())
-    }
-    
-    // Save price snapshot to history
-    pub async fn save_price_snapshot(&self, alert_id: Uuid, price: f64) -> Result<()> {
+        // Create purchases table: alerts the user bought, with the prices savings are measured against
         sqlx::query(
-            "INSERT INTO price_history (alert_id, price, checked_at) VALUES ($1, $2, $3)"
+            r#"
+            CREATE TABLE IF NOT EXISTS purchases (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                alert_id UUID NOT NULL UNIQUE REFERENCES price_alerts(id) ON DELETE CASCADE,
+                user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+                price DOUBLE PRECISION NOT NULL,
+                purchased_at TIMESTAMPTZ NOT NULL,
+                initial_price DOUBLE PRECISION,
+                target_price DOUBLE PRECISION NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
         )
-        .bind(alert_id)
-        .bind(price)
-        .bind(Utc::now())
-        .execute(&self.pool)
+        .execute(pool)
         .await?;
         
-        Ok(())
-    }
-    
-    // Get price history for an alert
-    pub async fn get_price_history(&self, alert_id: Uuid, limit: i64) -> Result<Vec<PriceHistory>> {
-        let history = sqlx::query_as::<_, PriceHistory>(
-            "SELECT * FROM price_history WHERE alert_id = $1 ORDER BY checked_at DESC LIMIT $2"
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_purchases_user_time ON purchases(user_id, purchased_at)")
+            .execute(pool)
+            .await?;
+        
+        // Create scrape_attempts table: one row per scrape, feeding health stats
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS scrape_attempts (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                alert_id UUID REFERENCES price_alerts(id) ON DELETE CASCADE,
+                platform TEXT NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                outcome TEXT NOT NULL,
+                error_kind TEXT,
+                attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
         )
-        .bind(alert_id)
-        .bind(limit)
-        .fetch_all(&self.pool)
+        .execute(pool)
         .await?;
         
-        Ok(history)
-    }
-    
-    // Get price statistics for an alert
-    pub async fn get_price_stats(&self, alert_id: Uuid) -> Result<Option<PriceStats>> {
-        let stats = sqlx::query_as::<_, PriceStats>(
-            r#"
-            SELECT 
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_scrape_attempts_platform_time ON scrape_attempts(platform, attempted_at)")
+            .execute(pool)
+            .await?;
+        
+        // Create notification_deliveries table: one row per notification attempt, feeding delivery stats
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS notification_deliveries (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                channel TEXT NOT NULL,
+                outcome TEXT NOT NULL,
+                error_kind TEXT,
+                attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_notification_deliveries_time ON notification_deliveries(attempted_at)")
+            .execute(pool)
+            .await?;
+        
+        // Emails sent per user per UTC day, against the daily send budget
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS email_sends (
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                day DATE NOT NULL,
+                sent INTEGER NOT NULL DEFAULT 0,
+                PRIMARY KEY (user_id, day)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Notifications over the budget, held for the next day's digest
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS deferred_notifications (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                destination TEXT NOT NULL,
+                event JSONB NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_deferred_notifications_time ON deferred_notifications(created_at)")
+            .execute(pool)
+            .await?;
+        
+        // Notifications written in the same transaction as the change behind them, sent
+        // (and retried) by `outbox` until they're marked sent
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS notification_outbox (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+                alert_id UUID REFERENCES price_alerts(id) ON DELETE CASCADE,
+                event JSONB NOT NULL,
+                channels UUID[],
+                attempts INT NOT NULL DEFAULT 0,
+                claimed_until TIMESTAMPTZ,
+                sent_at TIMESTAMPTZ,
+                last_error TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_notification_outbox_pending ON notification_outbox(created_at) WHERE sent_at IS NULL")
+            .execute(pool)
+            .await?;
+        
+        // Create notification_channels table for per-user delivery preferences
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS notification_channels (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                channel TEXT NOT NULL,
+                destination TEXT NOT NULL,
+                enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                UNIQUE (user_id, channel, destination)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Create scraper_configs table for selector/header overrides without a redeploy
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS scraper_configs (
+                platform TEXT PRIMARY KEY,
+                selectors TEXT[] NOT NULL DEFAULT '{}',
+                price_patterns TEXT[] NOT NULL DEFAULT '{}',
+                headers JSONB NOT NULL DEFAULT '{}',
+                timeout_secs INTEGER,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("ALTER TABLE scraper_configs ADD COLUMN IF NOT EXISTS connect_timeout_secs INTEGER")
+            .execute(pool)
+            .await?;
+        
+        // Create listing_alerts table for search/category page watches
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS listing_alerts (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                user_email TEXT NOT NULL,
+                platform TEXT NOT NULL,
+                url TEXT NOT NULL,
+                target_price DOUBLE PRECISION NOT NULL,
+                max_results INTEGER NOT NULL DEFAULT 20,
+                include_terms TEXT[] NOT NULL DEFAULT '{}',
+                exclude_terms TEXT[] NOT NULL DEFAULT '{}',
+                notified_urls TEXT[] NOT NULL DEFAULT '{}',
+                is_active BOOLEAN NOT NULL DEFAULT TRUE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                last_checked TIMESTAMPTZ
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_listing_alerts_user_id ON listing_alerts(user_id)")
+            .execute(pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_listing_alerts_user_active ON listing_alerts(user_id, is_active, created_at)")
+            .execute(pool)
+            .await?;
+        
+        // Lowest matching asking price per listing check, for marketplaces where it moves daily
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS listing_price_history (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                listing_alert_id UUID NOT NULL REFERENCES listing_alerts(id) ON DELETE CASCADE,
+                min_price DOUBLE PRECISION NOT NULL,
+                results INTEGER NOT NULL,
+                checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_listing_price_history_alert ON listing_price_history(listing_alert_id, checked_at)")
+            .execute(pool)
+            .await?;
+        
+        // Create keyword_watches table for cross-platform searches
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS keyword_watches (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                user_email TEXT NOT NULL,
+                query TEXT NOT NULL,
+                target_price DOUBLE PRECISION NOT NULL,
+                platforms TEXT[] NOT NULL DEFAULT '{}',
+                notified_urls TEXT[] NOT NULL DEFAULT '{}',
+                is_active BOOLEAN NOT NULL DEFAULT TRUE,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                last_checked TIMESTAMPTZ
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_keyword_watches_user_id ON keyword_watches(user_id)")
+            .execute(pool)
+            .await?;
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_keyword_watches_user_active ON keyword_watches(user_id, is_active, created_at)")
+            .execute(pool)
+            .await?;
+        
+        // Create products table: a user's grouping of alerts that track the same item on
+        // different platforms, for cross-platform comparison
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS products (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // An alert belongs to at most one product
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS product_alerts (
+                alert_id UUID PRIMARY KEY REFERENCES price_alerts(id) ON DELETE CASCADE,
+                product_id UUID NOT NULL REFERENCES products(id) ON DELETE CASCADE
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_product_alerts_product_id ON product_alerts(product_id)")
+            .execute(pool)
+            .await?;
+        
+        // Create revoked_tokens table as the logout denylist
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS revoked_tokens (
+                jti TEXT PRIMARY KEY,
+                expires_at TIMESTAMPTZ NOT NULL
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Create api_keys table: long-lived credentials for integrations (Zapier, IFTTT...).
+        // Only a SHA-256 of each key is stored; `prefix` lets users tell them apart.
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS api_keys (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                name TEXT NOT NULL,
+                prefix TEXT NOT NULL,
+                key_hash TEXT UNIQUE NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                last_used_at TIMESTAMPTZ
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_api_keys_user_id ON api_keys(user_id)")
+            .execute(pool)
+            .await?;
+        
+        // What each key may do (see auth::Scope); keys made before scopes existed only fed the drops feed
+        sqlx::query("ALTER TABLE api_keys ADD COLUMN IF NOT EXISTS scopes TEXT[] NOT NULL DEFAULT ARRAY['alerts:read']")
+            .execute(pool)
+            .await?;
+        
+        // Telegram chats linked to an account, and the one-time codes that link them
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS telegram_chats (
+                chat_id BIGINT PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                linked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS telegram_link_codes (
+                code TEXT PRIMARY KEY,
+                user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+                expires_at TIMESTAMPTZ NOT NULL
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS check_checkpoints (
+                run TEXT PRIMARY KEY,
+                run_started_at TIMESTAMPTZ NOT NULL,
+                after_created_at TIMESTAMPTZ NOT NULL,
+                after_id UUID NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Alerts whose history was seeded from the Internet Archive, or is being
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS wayback_backfills (
+                alert_id UUID PRIMARY KEY REFERENCES price_alerts(id) ON DELETE CASCADE,
+                started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                finished_at TIMESTAMPTZ,
+                snapshots INTEGER,
+                saved INTEGER,
+                error TEXT
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // A single row, rewritten by `refresh_public_stats`
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS public_stats (
+                id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+                products_tracked BIGINT NOT NULL,
+                drops_this_week BIGINT NOT NULL,
+                average_saving DOUBLE PRECISION,
+                average_saving_percent DOUBLE PRECISION,
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Platform sitemaps the discovery job reads product links from (admin-managed)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS sitemap_sources (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                platform TEXT NOT NULL,
+                url TEXT NOT NULL UNIQUE,
+                max_products INTEGER NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                last_run_at TIMESTAMPTZ,
+                last_discovered INTEGER,
+                last_error TEXT
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        // Products found in those sitemaps, checked on a schedule whether or not anyone
+        // tracks them; shared by every user. Products dropped from their sitemap stop being
+        // checked but keep their history.
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS catalog_products (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                platform TEXT NOT NULL,
+                product_id TEXT NOT NULL,
+                url TEXT NOT NULL,
+                source_id UUID REFERENCES sitemap_sources(id) ON DELETE SET NULL,
+                priority DOUBLE PRECISION NOT NULL,
+                active BOOLEAN NOT NULL DEFAULT TRUE,
+                discovered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                last_checked TIMESTAMPTZ,
+                last_price DOUBLE PRECISION,
+                last_error TEXT,
+                UNIQUE (platform, product_id)
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_catalog_products_source_id ON catalog_products(source_id)")
+            .execute(pool)
+            .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS catalog_price_history (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                catalog_product_id UUID NOT NULL REFERENCES catalog_products(id) ON DELETE CASCADE,
+                price DOUBLE PRECISION NOT NULL,
+                mrp DOUBLE PRECISION,
+                checked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query("CREATE INDEX IF NOT EXISTS idx_catalog_price_history_product ON catalog_price_history(catalog_product_id, checked_at)")
+            .execute(pool)
+            .await?;
+        
+        // Platforms whose checks are spread out after too many failures, and the history of
+        // those adjustments for the admin dashboard
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS platform_throttles (
+                platform TEXT PRIMARY KEY,
+                factor INTEGER NOT NULL,
+                adjusted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                next_check_at TIMESTAMPTZ
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS throttle_adjustments (
+                id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+                platform TEXT NOT NULL,
+                from_factor INTEGER NOT NULL,
+                to_factor INTEGER NOT NULL,
+                attempts BIGINT NOT NULL,
+                failures BIGINT NOT NULL,
+                reason TEXT NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(pool)
+        .await?;
+        
+        tracing::info!("Database tables verified/created");
+        Ok(())
+    }
+    
+    pub async fn create_alert(&self, alert: &PriceAlert) -> Result<PriceAlert> {
+        let result = sqlx::query_as::<_, PriceAlert>(
+            r#"
+            INSERT INTO price_alerts (url, target_price, last_price, user_email, user_id, platform, created_at, last_checked, is_active, notify_channels, product_id, alert_mode, check_interval_minutes, expires_at, renotify, min_price, max_price)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            RETURNING *
+            "#
+        )
+        .bind(&alert.url)
+        .bind(alert.target_price)
+        .bind(alert.last_price)
+        .bind(&alert.user_email)
+        .bind(alert.user_id)
+        .bind(&alert.platform)
+        .bind(alert.created_at)
+        .bind(alert.last_checked)
+        .bind(alert.is_active)
+        .bind(&alert.notify_channels)
+        .bind(&alert.product_id)
+        .bind(&alert.alert_mode)
+        .bind(alert.check_interval_minutes)
+        .bind(alert.expires_at)
+        .bind(alert.renotify)
+        .bind(alert.min_price)
+        .bind(alert.max_price)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        // Failing to seed leaves the alert without a head start, nothing worse
+        if let Err(e) = self.seed_history_from_catalog(&result).await {
+            tracing::warn!("Failed to seed the history of {} from the catalog: {}", result.url, e);
+        }
+        
+        self.invalidate_alerts(result.user_id).await;
+        Ok(result)
+    }
+    
+    async fn invalidate_alerts(&self, user_id: Option<Uuid>) {
+        if let Some(user_id) = user_id {
+            self.alert_cache.invalidate(user_id).await;
+        }
+    }
+    
+    pub async fn get_all_active_alerts(&self) -> Result<Vec<PriceAlert>> {
+        let alerts = sqlx::query_as::<_, PriceAlert>(
+            "SELECT * FROM price_alerts WHERE is_active = TRUE AND product_removed_at IS NULL ORDER BY created_at DESC, id DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(alerts)
+    }
+    
+    pub async fn update_alert_price(&self, id: Uuid, last_price: f64) -> Result<()> {
+        let owner = sqlx::query_scalar::<_, Option<Uuid>>(
+            "UPDATE price_alerts SET last_price = $1, last_checked = $2, gone_checks = 0 WHERE id = $3 RETURNING user_id"
+        )
+        .bind(last_price)
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        self.invalidate_alerts(owner.flatten()).await;
+        Ok(())
+    }
+    
+    /// Replace the stored offers with the set seen on the latest check
+    pub async fn update_alert_offers(&self, id: Uuid, offers: &[Offer]) -> Result<()> {
+        let owner = sqlx::query_scalar::<_, Option<Uuid>>(
+            "UPDATE price_alerts SET offers = $1 WHERE id = $2 RETURNING user_id"
+        )
+        .bind(sqlx::types::Json(offers))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        self.invalidate_alerts(owner.flatten()).await;
+        Ok(())
+    }
+    
+    pub async fn update_alert_stock(&self, id: Uuid, in_stock: bool) -> Result<()> {
+        let owner = sqlx::query_scalar::<_, Option<Uuid>>(
+            "UPDATE price_alerts SET in_stock = $1, last_checked = $2, gone_checks = 0 WHERE id = $3 RETURNING user_id"
+        )
+        .bind(in_stock)
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        self.invalidate_alerts(owner.flatten()).await;
+        Ok(())
+    }
+    
+    /// Point an alert at a new URL for the same product, keeping its history, prices and
+    /// settings. Clears a `product_removed` mark, since the new page hasn't been checked yet.
+    pub async fn relink_alert(&self, id: Uuid, user_id: Uuid, url: &str, product_id: Option<&str>) -> Result<Option<PriceAlert>> {
+        let alert = sqlx::query_as::<_, PriceAlert>(
+            r#"
+            UPDATE price_alerts SET
+                url = $3,
+                product_id = $4,
+                gone_checks = 0,
+                product_removed_at = NULL,
+                product_name = NULL,
+                image_url = NULL,
+                version = version + 1
+            WHERE id = $1 AND user_id = $2 AND is_active = TRUE
+            RETURNING *
+            "#
+        )
+        .bind(id)
+        .bind(user_id)
+        .bind(url)
+        .bind(product_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        if alert.is_some() {
+            self.alert_cache.invalidate(user_id).await;
+        }
+        Ok(alert)
+    }
+    
+    /// Count another 404/410 for an alert's page. The `limit`th in a row marks the product
+    /// removed, which leaves the alert out of checks; true only for the call that does.
+    pub async fn record_page_gone(&self, id: Uuid, limit: i32) -> Result<bool> {
+        let marked = sqlx::query_as::<_, (Option<Uuid>, bool)>(
+            r#"
+            UPDATE price_alerts SET
+                gone_checks = gone_checks + 1,
+                product_removed_at = CASE WHEN gone_checks + 1 >= $2 THEN NOW() END
+            WHERE id = $1 AND product_removed_at IS NULL
+            RETURNING user_id, product_removed_at IS NOT NULL
+            "#
+        )
+        .bind(id)
+        .bind(limit)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let Some((owner, removed)) = marked else {
+            return Ok(false);
+        };
+        if removed {
+            self.invalidate_alerts(owner).await;
+        }
+        Ok(removed)
+    }
+    
+    pub async fn delete_alert(&self, id: Uuid) -> Result<()> {
+        self.deactivate_alert(id).await.map(|_| ())
+    }
+    
+    /// Deactivate alerts past their `expires_at`, returning how many there were
+    pub async fn expire_alerts(&self) -> Result<usize> {
+        let owners = sqlx::query_scalar::<_, Option<Uuid>>(
+            "UPDATE price_alerts SET is_active = FALSE WHERE is_active = TRUE AND expires_at <= NOW() RETURNING user_id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        for owner in &owners {
+            self.invalidate_alerts(*owner).await;
+        }
+        Ok(owners.len())
+    }
+    
+    /// Stop tracking an alert; false if it doesn't exist or was already stopped
+    pub async fn deactivate_alert(&self, id: Uuid) -> Result<bool> {
+        let owner = sqlx::query_scalar::<_, Option<Uuid>>(
+            "UPDATE price_alerts SET is_active = FALSE WHERE id = $1 AND is_active = TRUE RETURNING user_id"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let Some(owner) = owner else {
+            return Ok(false);
+        };
+        
+        self.invalidate_alerts(owner).await;
+        Ok(true)
+    }
+    
+    /// Pause notifications about an alert from its unsubscribe link; false if it doesn't
+    /// exist or was already paused. The alert keeps being checked.
+    pub async fn pause_alert_notifications(&self, id: Uuid) -> Result<bool> {
+        let owner = sqlx::query_scalar::<_, Option<Uuid>>(
+            "UPDATE price_alerts SET notifications_paused = TRUE WHERE id = $1 AND NOT notifications_paused RETURNING user_id"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let Some(owner) = owner else {
+            return Ok(false);
+        };
+        
+        self.invalidate_alerts(owner).await;
+        Ok(true)
+    }
+    
+    /// Pause or resume notifications about one of the user's active alerts
+    pub async fn set_alert_notifications_paused(&self, id: Uuid, user_id: Uuid, paused: bool) -> Result<Option<PriceAlert>> {
+        let alert = sqlx::query_as::<_, PriceAlert>(
+            "UPDATE price_alerts SET notifications_paused = $1 WHERE id = $2 AND user_id = $3 AND is_active = TRUE RETURNING *"
+        )
+        .bind(paused)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        self.invalidate_alerts(Some(user_id)).await;
+        Ok(alert)
+    }
+    
+    pub async fn alert_notifications_paused(&self, id: Uuid) -> Result<bool> {
+        let paused = sqlx::query_scalar::<_, bool>("SELECT notifications_paused FROM price_alerts WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(paused.unwrap_or(false))
+    }
+    
+    // Save price snapshot to history: the selling price, plus the MRP if the page showed one
+    pub async fn save_price_snapshot(&self, alert_id: Uuid, price: f64, mrp: Option<f64>) -> Result<Uuid> {
+        let id = sqlx::query_scalar(
+            "INSERT INTO price_history (alert_id, price, mrp, checked_at) VALUES ($1, $2, $3, $4) RETURNING id"
+        )
+        .bind(alert_id)
+        .bind(price)
+        .bind(mrp)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(id)
+    }
+    
+    // Save a price the worker doesn't trust (see `is_price_anomaly`), flagged as suspect
+    pub async fn save_suspect_price(&self, alert_id: Uuid, price: f64, mrp: Option<f64>) -> Result<Uuid> {
+        let id = sqlx::query_scalar(
+            "INSERT INTO price_history (alert_id, price, mrp, checked_at, suspect) VALUES ($1, $2, $3, $4, TRUE) RETURNING id"
+        )
+        .bind(alert_id)
+        .bind(price)
+        .bind(mrp)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(id)
+    }
+    
+    /// Median of the alert's last `window` prices; None with fewer than `min_points`. Suspect
+    /// ones count too, so a lasting change of level is accepted once it fills half the window.
+    pub async fn get_recent_median_price(&self, alert_id: Uuid, window: i64, min_points: i64) -> Result<Option<f64>> {
+        let median: Option<Option<f64>> = sqlx::query_scalar(
+            r#"
+            SELECT PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY price)
+            FROM (
+                SELECT price FROM price_history
+                WHERE alert_id = $1
+                ORDER BY checked_at DESC
+                LIMIT $2
+            ) recent
+            HAVING COUNT(*) >= $3
+            "#
+        )
+        .bind(alert_id)
+        .bind(window)
+        .bind(min_points)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(median.flatten())
+    }
+    
+    /// Write a batch of check results in one transaction: a single UNNEST-based UPDATE of
+    /// the alerts and a single multi-row INSERT of their snapshots, instead of three round
+    /// trips per alert. Alerts deleted since they were checked are skipped.
+    pub async fn apply_price_updates(&self, updates: &[PriceUpdate]) -> Result<()> {
+        if updates.is_empty() {
+            return Ok(());
+        }
+        
+        let ids: Vec<Uuid> = updates.iter().map(|u| u.alert_id).collect();
+        let prices: Vec<f64> = updates.iter().map(|u| u.price).collect();
+        let mrps: Vec<Option<f64>> = updates.iter().map(|u| u.mrp).collect();
+        let offers: Vec<sqlx::types::Json<&[Offer]>> = updates.iter().map(|u| sqlx::types::Json(u.offers.as_slice())).collect();
+        let names: Vec<Option<&str>> = updates.iter().map(|u| u.product_name.as_deref()).collect();
+        let images: Vec<Option<&str>> = updates.iter().map(|u| u.image_url.as_deref()).collect();
+        let checked_at: Vec<DateTime<Utc>> = updates.iter().map(|u| u.checked_at).collect();
+        
+        let mut tx = self.pool.begin().await?;
+        let owners: Vec<Option<Uuid>> = sqlx::query_scalar(
+            r#"
+            UPDATE price_alerts a
+            SET last_price = u.price, last_checked = u.checked_at, offers = u.offers, gone_checks = 0,
+                product_name = COALESCE(u.product_name, a.product_name), image_url = COALESCE(u.image_url, a.image_url)
+            FROM UNNEST($1::uuid[], $2::float8[], $3::jsonb[], $4::timestamptz[], $5::text[], $6::text[])
+                AS u(id, price, offers, checked_at, product_name, image_url)
+            WHERE a.id = u.id
+            RETURNING a.user_id
+            "#
+        )
+        .bind(&ids)
+        .bind(&prices)
+        .bind(&offers)
+        .bind(&checked_at)
+        .bind(&names)
+        .bind(&images)
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO price_history (alert_id, price, mrp, checked_at)
+            SELECT h.alert_id, h.price, h.mrp, h.checked_at
+            FROM UNNEST($1::uuid[], $2::float8[], $3::float8[], $4::timestamptz[]) AS h(alert_id, price, mrp, checked_at)
+            WHERE EXISTS (SELECT 1 FROM price_alerts a WHERE a.id = h.alert_id)
+            "#
+        )
+        .bind(&ids)
+        .bind(&prices)
+        .bind(&mrps)
+        .bind(&checked_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        let mut owners: Vec<Uuid> = owners.into_iter().flatten().collect();
+        owners.sort();
+        owners.dedup();
+        for owner in owners {
+            self.invalidate_alerts(Some(owner)).await;
+        }
+        Ok(())
+    }
+    
+    // Record that a check met the alert's target. Target and previous price are taken
+    // from the alert as it was before this check updated it.
+    pub async fn record_price_drop(
+        &self,
+        alert: &PriceAlert,
+        price: f64,
+        badge: Option<PriceBadge>,
+        history_id: Option<Uuid>,
+    ) -> Result<PriceDropEvent> {
+        let event = sqlx::query_as::<_, PriceDropEvent>(
+            r#"
+            INSERT INTO price_drop_events (alert_id, user_id, history_id, price, target_price, previous_price, badge)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#
+        )
+        .bind(alert.id)
+        .bind(alert.user_id)
+        .bind(history_id)
+        .bind(price)
+        .bind(alert.target_price)
+        .bind(alert.last_price)
+        .bind(badge.map(|badge| badge.as_str()))
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(event)
+    }
+    
+    /// Everything a check that met the target writes, in one transaction: the alert's new
+    /// price and offers, the history snapshot, the drop event, and the notification for it
+    /// in the outbox. The entry comes back claimed for `claim`, so the caller sends it
+    /// and the outbox dispatcher only picks it up if that never happens.
+    pub async fn save_price_drop(
+        &self,
+        alert: &PriceAlert,
+        price: f64,
+        mrp: Option<f64>,
+        offers: &[Offer],
+        badge: Option<PriceBadge>,
+        claim: chrono::Duration,
+    ) -> Result<(PriceDropEvent, OutboxEntry)> {
+        let alert_id = alert.id.ok_or_else(|| anyhow::anyhow!("Alert has no id"))?;
+        let now = Utc::now();
+        let mut tx = self.pool.begin().await?;
+        
+        sqlx::query(
+            r#"
+            UPDATE price_alerts
+            SET last_price = $1, last_checked = $2, gone_checks = 0, offers = $3,
+                product_name = COALESCE($5, product_name), image_url = COALESCE($6, image_url)
+            WHERE id = $4
+            "#
+        )
+        .bind(price)
+        .bind(now)
+        .bind(sqlx::types::Json(offers))
+        .bind(alert_id)
+        .bind(&alert.product_name)
+        .bind(&alert.image_url)
+        .execute(&mut *tx)
+        .await?;
+        
+        let history_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO price_history (alert_id, price, mrp, checked_at) VALUES ($1, $2, $3, $4) RETURNING id"
+        )
+        .bind(alert_id)
+        .bind(price)
+        .bind(mrp)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        let event = sqlx::query_as::<_, PriceDropEvent>(
+            r#"
+            INSERT INTO price_drop_events (alert_id, user_id, history_id, price, target_price, previous_price, badge)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#
+        )
+        .bind(alert_id)
+        .bind(alert.user_id)
+        .bind(history_id)
+        .bind(price)
+        .bind(alert.target_price)
+        .bind(alert.last_price)
+        .bind(badge.map(|badge| badge.as_str()))
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        let notification = NotificationEvent::PriceDrop(PriceDrop::recorded(alert, &event, offers.to_vec()));
+        let entry = sqlx::query_as::<_, OutboxEntry>(
+            r#"
+            INSERT INTO notification_outbox (user_id, alert_id, event, channels, attempts, claimed_until)
+            VALUES ($1, $2, $3, $4, 1, $5)
+            RETURNING id, event, channels, attempts, created_at
+            "#
+        )
+        .bind(alert.user_id)
+        .bind(alert_id)
+        .bind(sqlx::types::Json(&notification))
+        .bind(&alert.notify_channels)
+        .bind(now + claim)
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        tx.commit().await?;
+        self.invalidate_alerts(alert.user_id).await;
+        Ok((event, entry))
+    }
+    
+    // An alert's price ladder, highest rung first
+    pub async fn get_alert_thresholds(&self, alert_id: Uuid) -> Result<Vec<AlertThreshold>> {
+        let thresholds = sqlx::query_as::<_, AlertThreshold>(
+            "SELECT * FROM alert_thresholds WHERE alert_id = $1 ORDER BY price DESC"
+        )
+        .bind(alert_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(thresholds)
+    }
+    
+    // Replace an alert's price ladder; every rung starts out un-notified
+    pub async fn set_alert_thresholds(&self, alert_id: Uuid, prices: &[f64]) -> Result<Vec<AlertThreshold>> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM alert_thresholds WHERE alert_id = $1")
+            .bind(alert_id)
+            .execute(&mut *tx)
+            .await?;
+        let mut thresholds = sqlx::query_as::<_, AlertThreshold>(
+            r#"
+            INSERT INTO alert_thresholds (alert_id, price)
+            SELECT $1, price FROM UNNEST($2::float8[]) AS price
+            ON CONFLICT (alert_id, price) DO NOTHING
+            RETURNING *
+            "#
+        )
+        .bind(alert_id)
+        .bind(prices)
+        .fetch_all(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        
+        thresholds.sort_by(|a, b| b.price.total_cmp(&a.price));
+        Ok(thresholds)
+    }
+    
+    // Mark the not-yet-notified rungs at or above `price` as notified, returning their prices
+    pub async fn take_reached_thresholds(&self, alert_id: Uuid, price: f64) -> Result<Vec<f64>> {
+        let prices = sqlx::query_scalar(
+            r#"
+            UPDATE alert_thresholds SET notified_at = NOW(), notified_price = $2
+            WHERE alert_id = $1 AND notified_at IS NULL AND price >= $2
+            RETURNING price
+            "#
+        )
+        .bind(alert_id)
+        .bind(price)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(prices)
+    }
+    
+    // Add a marker to an alert's history; `user_id` is None for the worker's own annotations
+    pub async fn create_annotation(
+        &self,
+        alert_id: Uuid,
+        user_id: Option<Uuid>,
+        kind: AnnotationKind,
+        note: Option<&str>,
+        history_id: Option<Uuid>,
+        annotated_at: DateTime<Utc>,
+    ) -> Result<Annotation> {
+        let annotation = sqlx::query_as::<_, Annotation>(
+            r#"
+            INSERT INTO annotations (alert_id, user_id, history_id, kind, note, source, annotated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING *
+            "#
+        )
+        .bind(alert_id)
+        .bind(user_id)
+        .bind(history_id)
+        .bind(kind.as_str())
+        .bind(note)
+        .bind(if user_id.is_some() { "user" } else { "system" })
+        .bind(annotated_at)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(annotation)
+    }
+    
+    // An alert's annotations from `since` on, oldest first
+    /// Annotations from `since` up to (not including) `until`, oldest first
+    pub async fn get_annotations(
+        &self,
+        alert_id: Uuid,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Annotation>> {
+        let annotations = sqlx::query_as::<_, Annotation>(
+            r#"
+            SELECT * FROM annotations
+            WHERE alert_id = $1
+                AND ($2::timestamptz IS NULL OR annotated_at >= $2)
+                AND ($3::timestamptz IS NULL OR annotated_at < $3)
+            ORDER BY annotated_at
+            "#
+        )
+        .bind(alert_id)
+        .bind(since)
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(annotations)
+    }
+    
+    // Change marker for an alert's annotations, folded into the history ETag
+    pub async fn get_annotations_version(&self, alert_id: Uuid) -> Result<(i64, Option<DateTime<Utc>>)> {
+        let version = sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+            "SELECT COUNT(*), MAX(created_at) FROM annotations WHERE alert_id = $1"
+        )
+        .bind(alert_id)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(version)
+    }
+    
+    pub async fn delete_annotation(&self, alert_id: Uuid, annotation_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM annotations WHERE id = $1 AND alert_id = $2")
+            .bind(annotation_id)
+            .bind(alert_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    // The time of one of an alert's history points, to pin an annotation to it
+    pub async fn get_history_point_time(&self, alert_id: Uuid, history_id: Uuid) -> Result<Option<DateTime<Utc>>> {
+        let checked_at = sqlx::query_scalar::<_, DateTime<Utc>>(
+            "SELECT checked_at FROM price_history WHERE id = $1 AND alert_id = $2"
+        )
+        .bind(history_id)
+        .bind(alert_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(checked_at)
+    }
+    
+    // Mark an alert purchased, copying in its first recorded price and current target.
+    // A repeat replaces the price and date
+    pub async fn record_purchase(&self, alert: &PriceAlert, price: f64, purchased_at: DateTime<Utc>) -> Result<Purchase> {
+        let purchase = sqlx::query_as::<_, Purchase>(
+            r#"
+            WITH purchase AS (
+                INSERT INTO purchases (alert_id, user_id, price, purchased_at, initial_price, target_price)
+                VALUES ($1, $2, $3, $4, (SELECT price FROM price_history WHERE alert_id = $1 AND NOT suspect ORDER BY checked_at LIMIT 1), $5)
+                ON CONFLICT (alert_id) DO UPDATE SET price = EXCLUDED.price, purchased_at = EXCLUDED.purchased_at
+                RETURNING *
+            )
+            SELECT purchase.*, a.url, a.platform FROM purchase JOIN price_alerts a ON a.id = purchase.alert_id
+            "#
+        )
+        .bind(alert.id)
+        .bind(alert.user_id)
+        .bind(price)
+        .bind(purchased_at)
+        .bind(alert.target_price)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(purchase)
+    }
+    
+    // The user's purchases from `since` on, newest first
+    pub async fn get_purchases(&self, user_id: Uuid, since: Option<DateTime<Utc>>) -> Result<Vec<Purchase>> {
+        let purchases = sqlx::query_as::<_, Purchase>(
+            r#"
+            SELECT p.*, a.url, a.platform
+            FROM purchases p
+            JOIN price_alerts a ON a.id = p.alert_id
+            WHERE p.user_id = $1 AND ($2::timestamptz IS NULL OR p.purchased_at >= $2)
+            ORDER BY p.purchased_at DESC
+            "#
+        )
+        .bind(user_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(purchases)
+    }
+    
+    pub async fn delete_purchase(&self, alert_id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM purchases WHERE alert_id = $1 AND user_id = $2")
+            .bind(alert_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    pub async fn create_api_key(&self, user_id: Uuid, name: &str, prefix: &str, key_hash: &str, scopes: &[&str]) -> Result<ApiKey> {
+        let key = sqlx::query_as::<_, ApiKey>(
+            "INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes) VALUES ($1, $2, $3, $4, $5) RETURNING *"
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(prefix)
+        .bind(key_hash)
+        .bind(scopes)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(key)
+    }
+    
+    pub async fn get_api_keys_by_user(&self, user_id: Uuid) -> Result<Vec<ApiKey>> {
+        let keys = sqlx::query_as::<_, ApiKey>(
+            "SELECT * FROM api_keys WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(keys)
+    }
+    
+    pub async fn delete_api_key(&self, id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM api_keys WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// A key's scopes, without counting the lookup as a use
+    pub async fn get_api_key_scopes(&self, key_hash: &str) -> Result<Option<Vec<String>>> {
+        let scopes = sqlx::query_scalar("SELECT scopes FROM api_keys WHERE key_hash = $1")
+            .bind(key_hash)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(scopes)
+    }
+    
+    // Look up a key by hash, recording that it was used
+    pub async fn use_api_key(&self, key_hash: &str) -> Result<Option<ApiKey>> {
+        let key = sqlx::query_as::<_, ApiKey>(
+            "UPDATE api_keys SET last_used_at = NOW() WHERE key_hash = $1 RETURNING *"
+        )
+        .bind(key_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(key)
+    }
+    
+    // Group some of the user's alerts under a new product. Alerts that aren't the user's
+    // are ignored; alerts already in another product move to this one.
+    pub async fn create_product(&self, user_id: Uuid, name: &str, alert_ids: &[Uuid]) -> Result<Product> {
+        let mut tx = self.pool.begin().await?;
+        
+        let product = sqlx::query_as::<_, Product>(
+            "INSERT INTO products (user_id, name) VALUES ($1, $2) RETURNING *"
+        )
+        .bind(user_id)
+        .bind(name)
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO product_alerts (alert_id, product_id)
+            SELECT id, $1 FROM price_alerts WHERE id = ANY($2) AND user_id = $3
+            ON CONFLICT (alert_id) DO UPDATE SET product_id = EXCLUDED.product_id
+            "#
+        )
+        .bind(product.id)
+        .bind(alert_ids)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        
+        tx.commit().await?;
+        
+        Ok(product)
+    }
+    
+    pub async fn get_products_by_user(&self, user_id: Uuid) -> Result<Vec<Product>> {
+        let products = sqlx::query_as::<_, Product>(
+            "SELECT * FROM products WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(products)
+    }
+    
+    pub async fn get_product_for_user(&self, id: Uuid, user_id: Uuid) -> Result<Option<Product>> {
+        let product = sqlx::query_as::<_, Product>(
+            "SELECT * FROM products WHERE id = $1 AND user_id = $2"
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(product)
+    }
+    
+    // Each platform's lowest price per day across the product's alerts (deleted ones included)
+    pub async fn get_product_daily_prices(&self, product_id: Uuid) -> Result<Vec<DailyPlatformPrice>> {
+        let prices = sqlx::query_as::<_, DailyPlatformPrice>(
+            r#"
+            SELECT a.platform, date_trunc('day', h.checked_at) AS day, MIN(h.price) AS price
+            FROM product_alerts pa
+            JOIN price_alerts a ON a.id = pa.alert_id
+            JOIN price_history h ON h.alert_id = a.id AND NOT h.suspect
+            WHERE pa.product_id = $1
+            GROUP BY a.platform, day
+            ORDER BY day
+            "#
+        )
+        .bind(product_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(prices)
+    }
+    
+    /// Daily low/high/average of a product across every user's alerts on it (deleted ones
+    /// included), matched by platform product ID when known and by URL otherwise. Alerts of
+    /// users who opted out of public aggregates are left out, and so is any day fewer than
+    /// `min_users` distinct users contributed to, so no one's own history shows through.
+    pub async fn get_public_daily_prices(&self, platform: &str, product_id: Option<&str>, url: &str, min_users: i64) -> Result<Vec<PublicDailyPrice>> {
+        let days = sqlx::query_as::<_, PublicDailyPrice>(
+            r#"
+            SELECT date_trunc('day', h.checked_at) AS day, MIN(h.price) AS low, MAX(h.price) AS high, AVG(h.price) AS average
+            FROM price_alerts a
+            JOIN users u ON u.id = a.user_id
+            JOIN price_history h ON h.alert_id = a.id AND NOT h.suspect
+            WHERE a.platform = $1
+              AND (CASE WHEN $2::text IS NULL THEN a.url = $3 ELSE a.product_id = $2 END)
+              AND NOT u.exclude_from_public
+            GROUP BY day
+            HAVING COUNT(DISTINCT a.user_id) >= $4
+            ORDER BY day
+            "#
+        )
+        .bind(platform)
+        .bind(product_id)
+        .bind(url)
+        .bind(min_users)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(days)
+    }
+    
+    /// Take up to `limit` active alerts that were never backfilled, newest first. Each
+    /// alert is only ever handed out once, even to concurrent callers.
+    pub async fn claim_backfills(&self, limit: i64) -> Result<Vec<PriceAlert>> {
+        let alerts = sqlx::query_as::<_, PriceAlert>(
+            r#"
+            WITH claimed AS (
+                INSERT INTO wayback_backfills (alert_id)
+                SELECT a.id FROM price_alerts a
+                WHERE a.is_active AND a.product_removed_at IS NULL
+                  AND NOT EXISTS (SELECT 1 FROM wayback_backfills b WHERE b.alert_id = a.id)
+                ORDER BY a.created_at DESC
+                LIMIT $1
+                ON CONFLICT (alert_id) DO NOTHING
+                RETURNING alert_id
+            )
+            SELECT a.* FROM price_alerts a JOIN claimed c ON c.alert_id = a.id
+            ORDER BY a.created_at DESC
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(alerts)
+    }
+    
+    /// Add archived prices (`checked_at`, price, MRP) to an alert's history. Only points
+    /// older than everything already recorded are kept, so the latest price stays the
+    /// alert's own. Returns how many were added.
+    pub async fn save_backfilled_prices(&self, alert_id: Uuid, points: &[(DateTime<Utc>, f64, Option<f64>)]) -> Result<u64> {
+        let checked_at: Vec<DateTime<Utc>> = points.iter().map(|(at, ..)| *at).collect();
+        let prices: Vec<f64> = points.iter().map(|(_, price, _)| *price).collect();
+        let mrps: Vec<Option<f64>> = points.iter().map(|(.., mrp)| *mrp).collect();
+        
+        let result = sqlx::query(
+            r#"
+            INSERT INTO price_history (alert_id, price, mrp, checked_at)
+            SELECT $1, p.price, p.mrp, p.checked_at
+            FROM UNNEST($2::timestamptz[], $3::float8[], $4::float8[]) AS p(checked_at, price, mrp)
+            WHERE p.checked_at < COALESCE((SELECT MIN(checked_at) FROM price_history WHERE alert_id = $1), 'infinity')
+            "#
+        )
+        .bind(alert_id)
+        .bind(&checked_at)
+        .bind(&prices)
+        .bind(&mrps)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.rows_affected())
+    }
+    
+    pub async fn finish_backfill(&self, alert_id: Uuid, snapshots: usize, saved: u64, error: Option<&str>) -> Result<()> {
+        sqlx::query(
+            "UPDATE wayback_backfills SET finished_at = NOW(), snapshots = $2, saved = $3, error = $4 WHERE alert_id = $1"
+        )
+        .bind(alert_id)
+        .bind(snapshots as i32)
+        .bind(saved as i32)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn get_sitemap_sources(&self) -> Result<Vec<SitemapSource>> {
+        let sources = sqlx::query_as::<_, SitemapSource>(
+            r#"
+            SELECT s.*, (SELECT COUNT(*) FROM catalog_products c WHERE c.source_id = s.id AND c.active) AS products
+            FROM sitemap_sources s
+            ORDER BY s.platform, s.created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(sources)
+    }
+    
+    pub async fn get_sitemap_source(&self, id: Uuid) -> Result<Option<SitemapSource>> {
+        let source = sqlx::query_as::<_, SitemapSource>(
+            r#"
+            SELECT s.*, (SELECT COUNT(*) FROM catalog_products c WHERE c.source_id = s.id AND c.active) AS products
+            FROM sitemap_sources s
+            WHERE s.id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(source)
+    }
+    
+    /// `None` if the sitemap is already a source
+    pub async fn create_sitemap_source(&self, platform: &str, url: &str, max_products: i32) -> Result<Option<SitemapSource>> {
+        let source = sqlx::query_as::<_, SitemapSource>(
+            r#"
+            WITH created AS (
+                INSERT INTO sitemap_sources (platform, url, max_products)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (url) DO NOTHING
+                RETURNING *
+            )
+            SELECT created.*, 0::BIGINT AS products FROM created
+            "#
+        )
+        .bind(platform)
+        .bind(url)
+        .bind(max_products)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(source)
+    }
+    
+    /// Remove a source; its products stop being checked but keep their history
+    pub async fn delete_sitemap_source(&self, id: Uuid) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("UPDATE catalog_products SET active = FALSE WHERE source_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM sitemap_sources WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Take the sources not read since `before` (or never), marking them read now so
+    /// concurrent callers don't read them too
+    pub async fn claim_due_sitemaps(&self, before: DateTime<Utc>) -> Result<Vec<SitemapSource>> {
+        let sources = sqlx::query_as::<_, SitemapSource>(
+            r#"
+            WITH claimed AS (
+                UPDATE sitemap_sources SET last_run_at = NOW()
+                WHERE id IN (
+                    SELECT id FROM sitemap_sources
+                    WHERE last_run_at IS NULL OR last_run_at < $1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING *
+            )
+            SELECT claimed.*, 0::BIGINT AS products FROM claimed
+            "#
+        )
+        .bind(before)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(sources)
+    }
+    
+    pub async fn finish_sitemap_run(&self, id: Uuid, discovered: Option<usize>, error: Option<&str>) -> Result<()> {
+        sqlx::query("UPDATE sitemap_sources SET last_run_at = NOW(), last_discovered = $2, last_error = $3 WHERE id = $1")
+            .bind(id)
+            .bind(discovered.map(|count| count as i32))
+            .bind(error)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Make `(product ID, URL, priority)` the products checked for a source: new ones are
+    /// added, known ones (from any source) updated, and the source's others deactivated
+    pub async fn save_catalog_products(&self, source_id: Uuid, platform: &str, products: &[(String, String, f64)]) -> Result<()> {
+        let product_ids: Vec<&str> = products.iter().map(|(product_id, _, _)| product_id.as_str()).collect();
+        let urls: Vec<&str> = products.iter().map(|(_, url, _)| url.as_str()).collect();
+        let priorities: Vec<f64> = products.iter().map(|(_, _, priority)| *priority).collect();
+        
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO catalog_products (platform, product_id, url, priority, source_id)
+            SELECT $1, product_id, url, priority, $5
+            FROM UNNEST($2::text[], $3::text[], $4::float8[]) AS p(product_id, url, priority)
+            ON CONFLICT (platform, product_id) DO UPDATE
+            SET url = EXCLUDED.url, priority = EXCLUDED.priority, source_id = EXCLUDED.source_id, active = TRUE
+            "#
+        )
+        .bind(platform)
+        .bind(&product_ids)
+        .bind(&urls)
+        .bind(&priorities)
+        .bind(source_id)
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query("UPDATE catalog_products SET active = FALSE WHERE source_id = $1 AND NOT (product_id = ANY($2))")
+            .bind(source_id)
+            .bind(&product_ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    /// Take up to `limit` active catalog products not checked since `before`, never-checked
+    /// and higher-priority ones first, skipping `platforms`. Claimed products count as
+    /// checked, so a crash delays them rather than having them read twice.
+    pub async fn claim_catalog_checks(&self, limit: i64, before: DateTime<Utc>, platforms: &[String]) -> Result<Vec<CatalogProduct>> {
+        let products = sqlx::query_as::<_, CatalogProduct>(
+            r#"
+            UPDATE catalog_products SET last_checked = NOW()
+            WHERE id IN (
+                SELECT id FROM catalog_products
+                WHERE active AND (last_checked IS NULL OR last_checked < $2) AND NOT (platform = ANY($3))
+                ORDER BY last_checked NULLS FIRST, priority DESC
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING *
+            "#
+        )
+        .bind(limit)
+        .bind(before)
+        .bind(platforms)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(products)
+    }
+    
+    pub async fn save_catalog_price(&self, id: Uuid, price: f64, mrp: Option<f64>) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("INSERT INTO catalog_price_history (catalog_product_id, price, mrp) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(price)
+            .bind(mrp)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE catalog_products SET last_price = $2, last_error = NULL WHERE id = $1")
+            .bind(id)
+            .bind(price)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        
+        Ok(())
+    }
+    
+    pub async fn save_catalog_error(&self, id: Uuid, error: &str) -> Result<()> {
+        sqlx::query("UPDATE catalog_products SET last_error = $2 WHERE id = $1")
+            .bind(id)
+            .bind(error)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Copy the catalog's history of the alert's product (within the alert's price range)
+    /// into the alert's own, so a new alert has a chart and stats from the start
+    pub async fn seed_history_from_catalog(&self, alert: &PriceAlert) -> Result<u64> {
+        let (Some(alert_id), Some(product_id)) = (alert.id, &alert.product_id) else {
+            return Ok(0);
+        };
+        let result = sqlx::query(
+            r#"
+            INSERT INTO price_history (alert_id, price, mrp, checked_at)
+            SELECT $1, h.price, h.mrp, h.checked_at
+            FROM catalog_price_history h
+            JOIN catalog_products c ON c.id = h.catalog_product_id
+            WHERE c.platform = $2 AND c.product_id = $3
+              AND ($4::float8 IS NULL OR h.price >= $4)
+              AND ($5::float8 IS NULL OR h.price <= $5)
+              AND h.checked_at < COALESCE((SELECT MIN(checked_at) FROM price_history WHERE alert_id = $1), 'infinity')
+            "#
+        )
+        .bind(alert_id)
+        .bind(&alert.platform)
+        .bind(product_id)
+        .bind(alert.min_price)
+        .bind(alert.max_price)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.rows_affected())
+    }
+    
+    /// Recompute the landing page totals. Drops are counted unless the price that
+    /// triggered them was saved as suspect.
+    pub async fn refresh_public_stats(&self) -> Result<PublicStats> {
+        let stats = sqlx::query_as::<_, PublicStats>(
+            r#"
+            INSERT INTO public_stats (id, products_tracked, drops_this_week, average_saving, average_saving_percent, updated_at)
+            SELECT
+                TRUE,
+                (
+                    SELECT COUNT(DISTINCT a.platform || ':' || COALESCE(a.product_id, a.url))
+                    FROM price_alerts a
+                    JOIN users u ON u.id = a.user_id
+                    WHERE a.is_active AND a.product_removed_at IS NULL AND NOT u.exclude_from_public
+                ),
+                COUNT(*),
+                ROUND(AVG(e.previous_price - e.price) FILTER (WHERE e.previous_price > e.price)::numeric, 2)::float8,
+                ROUND(AVG((e.previous_price - e.price) / e.previous_price * 100) FILTER (WHERE e.previous_price > e.price)::numeric, 1)::float8,
+                NOW()
+            FROM price_drop_events e
+            JOIN price_alerts a ON a.id = e.alert_id
+            JOIN users u ON u.id = a.user_id
+            LEFT JOIN price_history h ON h.id = e.history_id
+            WHERE e.created_at > NOW() - INTERVAL '7 days'
+              AND NOT u.exclude_from_public
+              AND h.suspect IS NOT TRUE
+            ON CONFLICT (id) DO UPDATE SET
+                products_tracked = EXCLUDED.products_tracked,
+                drops_this_week = EXCLUDED.drops_this_week,
+                average_saving = EXCLUDED.average_saving,
+                average_saving_percent = EXCLUDED.average_saving_percent,
+                updated_at = EXCLUDED.updated_at
+            RETURNING products_tracked, drops_this_week, average_saving, average_saving_percent, updated_at
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(stats)
+    }
+    
+    /// The totals from the last `refresh_public_stats`, if it ever ran
+    pub async fn get_public_stats(&self) -> Result<Option<PublicStats>> {
+        let stats = sqlx::query_as::<_, PublicStats>(
+            "SELECT products_tracked, drops_this_week, average_saving, average_saving_percent, updated_at FROM public_stats"
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(stats)
+    }
+    
+    pub async fn get_price_drop_for_user(&self, id: Uuid, user_id: Uuid) -> Result<Option<PriceDropEvent>> {
+        let event = sqlx::query_as::<_, PriceDropEvent>(
+            "SELECT * FROM price_drop_events WHERE id = $1 AND user_id = $2"
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(event)
+    }
+    
+    // Drops with their alert's product, newest first, strictly after `after` when given.
+    // Ordered by (created_at, id) so drops recorded in the same instant still page stably.
+    pub async fn get_drop_feed(&self, user_id: Uuid, after: Option<&PriceDropEvent>, limit: i64) -> Result<Vec<DropFeedItem>> {
+        let items = sqlx::query_as::<_, DropFeedItem>(
+            r#"
+            SELECT e.id, e.alert_id, a.url, a.platform, e.price, e.target_price, e.previous_price, e.badge, e.created_at
+            FROM price_drop_events e
+            JOIN price_alerts a ON a.id = e.alert_id
+            WHERE e.user_id = $1 AND ($2::timestamptz IS NULL OR (e.created_at, e.id) > ($2, $3))
+            ORDER BY e.created_at DESC, e.id DESC
+            LIMIT $4
+            "#
+        )
+        .bind(user_id)
+        .bind(after.map(|event| event.created_at))
+        .bind(after.map(|event| event.id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(items)
+    }
+    
+    // Drops for one alert, newest first
+    pub async fn get_price_drops_for_alert(&self, alert_id: Uuid, since: Option<DateTime<Utc>>, limit: i64) -> Result<Vec<PriceDropEvent>> {
+        let events = sqlx::query_as::<_, PriceDropEvent>(
+            r#"
+            SELECT * FROM price_drop_events
+            WHERE alert_id = $1 AND ($2::timestamptz IS NULL OR created_at > $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#
+        )
+        .bind(alert_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(events)
+    }
+    
+    // Drops across all of a user's alerts (including deleted ones), newest first
+    pub async fn get_price_drops_for_user(&self, user_id: Uuid, since: Option<DateTime<Utc>>, limit: i64) -> Result<Vec<PriceDropEvent>> {
+        let events = sqlx::query_as::<_, PriceDropEvent>(
+            r#"
+            SELECT * FROM price_drop_events
+            WHERE user_id = $1 AND ($2::timestamptz IS NULL OR created_at > $2)
+            ORDER BY created_at DESC
+            LIMIT $3
+            "#
+        )
+        .bind(user_id)
+        .bind(since)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(events)
+    }
+    
+    // Get price history for an alert
+    pub async fn get_price_history(&self, alert_id: Uuid, limit: i64) -> Result<Vec<PriceHistory>> {
+        self.get_price_history_page(alert_id, None, limit).await
+    }
+    
+    /// Up to `limit` points older than `before` (or the newest ones), newest first. Seeks
+    /// straight to the cursor in the index, so deep pages cost the same as the first.
+    pub async fn get_price_history_page(
+        &self,
+        alert_id: Uuid,
+        before: Option<&HistoryCursor>,
+        limit: i64,
+    ) -> Result<Vec<PriceHistory>> {
+        let history = sqlx::query_as::<_, PriceHistory>(
+            r#"
+            SELECT * FROM price_history
+            WHERE alert_id = $1 AND ($2::timestamptz IS NULL OR (checked_at, id) < ($2, $3))
+            ORDER BY checked_at DESC, id DESC
+            LIMIT $4
+            "#
+        )
+        .bind(alert_id)
+        .bind(before.map(|cursor| cursor.checked_at))
+        .bind(before.map(|cursor| cursor.id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(history)
+    }
+    
+    /// An alert's price checks, drops, notifications sent, pauses, resumes and relinks in one
+    /// feed, newest first, starting before `before`
+    pub async fn get_alert_timeline(
+        &self,
+        alert_id: Uuid,
+        before: Option<&HistoryCursor>,
+        limit: i64,
+    ) -> Result<Vec<TimelineEntry>> {
+        let entries = sqlx::query_as::<_, TimelineEntry>(
+            r#"
+            SELECT id, at, kind, details FROM (
+                SELECT id, checked_at AS at, 'price' AS kind,
+                    jsonb_build_object('price', price, 'mrp', mrp, 'suspect', suspect) AS details
+                FROM price_history WHERE alert_id = $1
+                UNION ALL
+                SELECT id, created_at, 'drop',
+                    jsonb_build_object('price', price, 'target_price', target_price, 'previous_price', previous_price, 'badge', badge, 'history_id', history_id)
+                FROM price_drop_events WHERE alert_id = $1
+                UNION ALL
+                SELECT id, sent_at, 'notification',
+                    jsonb_build_object('event', event->>'type', 'price', (event->>'current_price')::float8, 'attempts', attempts)
+                FROM notification_outbox WHERE alert_id = $1 AND sent_at IS NOT NULL
+                UNION ALL
+                SELECT id, notified_at, 'notification',
+                    jsonb_build_object('event', 'price_threshold', 'price', notified_price, 'threshold', price)
+                FROM alert_thresholds WHERE alert_id = $1 AND notified_at IS NOT NULL
+                UNION ALL
+                SELECT id, annotated_at, kind, jsonb_build_object('note', note)
+                FROM annotations WHERE alert_id = $1 AND kind IN ('paused', 'resumed', 'relinked')
+            ) timeline
+            WHERE $2::timestamptz IS NULL OR (at, id) < ($2, $3)
+            ORDER BY at DESC, id DESC
+            LIMIT $4
+            "#
+        )
+        .bind(alert_id)
+        .bind(before.map(|cursor| cursor.checked_at))
+        .bind(before.map(|cursor| cursor.id))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(entries)
+    }
+    
+    /// Every snapshot of an alert, newest first, without holding them all in memory. A
+    /// background task feeds rows from the cursor through a small channel and stops
+    /// when the receiver is dropped (e.g. the client disconnects).
+    pub fn stream_price_history(&self, alert_id: Uuid) -> ReceiverStream<Result<PriceHistory>> {
+        let (sender, receiver) = tokio::sync::mpsc::channel(HISTORY_STREAM_BUFFER);
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            let mut rows = sqlx::query_as::<_, PriceHistory>(
+                "SELECT * FROM price_history WHERE alert_id = $1 ORDER BY checked_at DESC, id DESC"
+            )
+            .bind(alert_id)
+            .fetch(&pool);
+            
+            while let Some(row) = rows.next().await {
+                if sender.send(row.map_err(Into::into)).await.is_err() {
+                    break;
+                }
+            }
+        });
+        
+        ReceiverStream::new(receiver)
+    }
+    
+    // Every alert the user has ever created, including deleted ones
+    pub async fn get_all_alerts_by_user(&self, user_id: Uuid) -> Result<Vec<PriceAlert>> {
+        let alerts = sqlx::query_as::<_, PriceAlert>(
+            "SELECT * FROM price_alerts WHERE user_id = $1 ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(alerts)
+    }
+    
+    // Cheap change marker for an alert's history: every snapshot bumps both values
+    pub async fn get_price_history_version(&self, alert_id: Uuid) -> Result<(i64, Option<DateTime<Utc>>)> {
+        let version = sqlx::query_as::<_, (i64, Option<DateTime<Utc>>)>(
+            "SELECT COUNT(*), MAX(checked_at) FROM price_history WHERE alert_id = $1"
+        )
+        .bind(alert_id)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(version)
+    }
+    
+    /// What an alert's chart image shows: its target and mode, `None` if there's no such
+    /// alert, and its trusted prices since `since` (all of them when `None`), oldest first
+    pub async fn get_chart_data(
+        &self,
+        alert_id: Uuid,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<Option<((f64, String), Vec<(DateTime<Utc>, f64)>)>> {
+        let alert = sqlx::query_as::<_, (f64, String)>("SELECT target_price, alert_mode FROM price_alerts WHERE id = $1")
+            .bind(alert_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let Some(alert) = alert else {
+            return Ok(None);
+        };
+    
+        let points = sqlx::query_as::<_, (DateTime<Utc>, f64)>(
+            r#"
+            SELECT checked_at, price FROM price_history
+            WHERE alert_id = $1 AND NOT suspect AND ($2::timestamptz IS NULL OR checked_at >= $2)
+            ORDER BY checked_at
+            "#
+        )
+        .bind(alert_id)
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+    
+        Ok(Some((alert, points)))
+    }
+    
+    // Get merged price history across several alerts
+    pub async fn get_price_history_for_alerts(&self, alert_ids: &[Uuid], limit: i64) -> Result<Vec<PriceHistory>> {
+        let history = sqlx::query_as::<_, PriceHistory>(
+            "SELECT * FROM price_history WHERE alert_id = ANY($1) ORDER BY checked_at DESC LIMIT $2"
+        )
+        .bind(alert_ids)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(history)
+    }
+    
+    // Get price statistics for an alert
+    pub async fn get_price_stats(&self, alert_id: Uuid) -> Result<Option<PriceStats>> {
+        let stats = sqlx::query_as::<_, PriceStats>(
+            r#"
+            SELECT 
                 MIN(price) as lowest_price,
                 MAX(price) as highest_price,
                 AVG(price) as average_price,
                 COUNT(*) as data_points
             FROM price_history 
-            WHERE alert_id = $1
+            WHERE alert_id = $1 AND NOT suspect
+            "#
+        )
+        .bind(alert_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(stats)
+    }
+    
+    // Lowest recorded price for an alert, all-time and since `since`
+    pub async fn get_price_lows(&self, alert_id: Uuid, since: DateTime<Utc>) -> Result<PriceLows> {
+        let lows = sqlx::query_as::<_, PriceLows>(
+            r#"
+            SELECT
+                (SELECT MIN(price) FROM price_history WHERE alert_id = $1 AND NOT suspect) AS all_time_low,
+                (SELECT MIN(price) FROM price_history WHERE alert_id = $1 AND NOT suspect AND checked_at >= $2) AS recent_low
+            "#
+        )
+        .bind(alert_id)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(lows)
+    }
+    
+    // User authentication methods
+    pub async fn create_user(&self, email: &str, password_hash: &str) -> Result<User> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            INSERT INTO users (email, password_hash, created_at, updated_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
+            "#
+        )
+        .bind(email)
+        .bind(password_hash)
+        .bind(Utc::now())
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE email = $1"
+        )
+        .bind(email)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    pub async fn get_user_by_id(&self, user_id: Uuid) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE id = $1"
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// `timezone` must already be a valid IANA name (see `timezone::parse`)
+    pub async fn set_user_timezone(&self, user_id: Uuid, timezone: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET timezone = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(timezone)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// The user's timezone and whether they've paused all notifications
+    pub async fn get_notification_settings(&self, user_id: Uuid) -> Result<Option<(String, bool)>> {
+        let settings = sqlx::query_as("SELECT timezone, notifications_paused FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(settings)
+    }
+    
+    pub async fn set_notifications_paused(&self, user_id: Uuid, paused: bool) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET notifications_paused = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(paused)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// `percent` must be between 0 and 100 (exclusive), or None to clear it
+    pub async fn set_default_discount(&self, user_id: Uuid, percent: Option<f64>) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET default_discount_percent = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(percent)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    pub async fn set_user_plan(&self, user_id: Uuid, plan: Plan) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET plan = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(plan.as_str())
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// Record that the user accepted terms `version` just now
+    pub async fn accept_terms(&self, user_id: Uuid, version: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET terms_version = $1, terms_accepted_at = NOW(), updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(version)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// None for an unknown user; Some(None) for one who never accepted any terms
+    pub async fn get_accepted_terms_version(&self, user_id: Uuid) -> Result<Option<Option<String>>> {
+        let version = sqlx::query_scalar("SELECT terms_version FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        Ok(version)
+    }
+    
+    pub async fn set_exclude_from_public(&self, user_id: Uuid, exclude: bool) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET exclude_from_public = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(exclude)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    pub async fn set_weekly_summary(&self, user_id: Uuid, enabled: bool) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET weekly_summary = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(enabled)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// Opted-in users with notifications on
+    pub async fn get_weekly_summary_recipients(&self) -> Result<Vec<User>> {
+        let users = sqlx::query_as::<_, User>(
+            "SELECT * FROM users WHERE weekly_summary = TRUE AND notifications_paused = FALSE ORDER BY created_at"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(users)
+    }
+    
+    /// Mark this week's summary as sent unless one went out after `not_since`; false
+    /// means another replica (or an earlier run) already took it
+    pub async fn claim_weekly_summary(&self, user_id: Uuid, not_since: DateTime<Utc>) -> Result<bool> {
+        let claimed = sqlx::query(
+            r#"
+            UPDATE users SET weekly_summary_sent_at = NOW()
+            WHERE id = $1 AND (weekly_summary_sent_at IS NULL OR weekly_summary_sent_at < $2)
+            "#
+        )
+        .bind(user_id)
+        .bind(not_since)
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        
+        Ok(claimed > 0)
+    }
+    
+    /// Highlights of a user's active price alerts since `since`: the biggest price falls,
+    /// the alerts closest to their target, and the ones whose checks keep failing
+    pub async fn get_weekly_summary(&self, user_id: Uuid, since: DateTime<Utc>) -> Result<WeeklySummary> {
+        // Start price is the last one seen before the week, or else the week's first
+        let biggest_drops = sqlx::query_as::<_, SummaryDrop>(
+            r#"
+            WITH moves AS (
+                SELECT a.id AS alert_id, a.url, a.platform, a.target_price,
+                    COALESCE(
+                        (SELECT h.price FROM price_history h WHERE h.alert_id = a.id AND NOT h.suspect AND h.checked_at < $2 ORDER BY h.checked_at DESC LIMIT 1),
+                        (SELECT h.price FROM price_history h WHERE h.alert_id = a.id AND NOT h.suspect AND h.checked_at >= $2 ORDER BY h.checked_at LIMIT 1)
+                    ) AS start_price,
+                    (SELECT h.price FROM price_history h WHERE h.alert_id = a.id AND NOT h.suspect AND h.checked_at >= $2 ORDER BY h.checked_at DESC LIMIT 1) AS end_price,
+                    (SELECT COUNT(*) FROM price_drop_events d WHERE d.alert_id = a.id AND d.created_at >= $2) AS drop_events
+                FROM price_alerts a
+                WHERE a.user_id = $1 AND a.is_active = TRUE AND a.alert_mode = 'price'
+            )
+            SELECT * FROM moves
+            WHERE end_price < start_price
+            ORDER BY (start_price - end_price) / start_price DESC
+            LIMIT $3
+            "#
+        )
+        .bind(user_id)
+        .bind(since)
+        .bind(WeeklySummary::MAX_ROWS)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let nearest_target = sqlx::query_as::<_, SummaryNearTarget>(
+            r#"
+            SELECT id AS alert_id, url, platform, last_price, target_price
+            FROM price_alerts
+            WHERE user_id = $1 AND is_active = TRUE AND alert_mode = 'price'
+              AND last_price > target_price AND target_price > 0
+            ORDER BY (last_price - target_price) / target_price
+            LIMIT $2
+            "#
+        )
+        .bind(user_id)
+        .bind(WeeklySummary::MAX_ROWS)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        // Alerts with no successful check this week, but at least one attempt
+        let needs_attention = sqlx::query_as::<_, SummaryFailingAlert>(
+            r#"
+            SELECT a.id AS alert_id, a.url, a.platform,
+                COUNT(*) AS failures,
+                (ARRAY_AGG(s.error_kind ORDER BY s.attempted_at DESC))[1] AS last_error
+            FROM price_alerts a
+            JOIN scrape_attempts s ON s.alert_id = a.id AND s.attempted_at >= $2
+            WHERE a.user_id = $1 AND a.is_active = TRUE
+            GROUP BY a.id
+            HAVING COUNT(*) FILTER (WHERE s.outcome = 'success') = 0
+            ORDER BY COUNT(*) DESC
+            LIMIT $3
+            "#
+        )
+        .bind(user_id)
+        .bind(since)
+        .bind(WeeklySummary::MAX_ROWS)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(WeeklySummary { since, biggest_drops, nearest_target, needs_attention })
+    }
+    
+    /// Plan of every user with an active alert, for the worker's check interval limits
+    pub async fn get_plans_of_alert_owners(&self) -> Result<HashMap<Uuid, Plan>> {
+        let rows: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, plan FROM users
+            WHERE id IN (SELECT user_id FROM price_alerts WHERE is_active = TRUE)
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(|(id, plan)| (id, Plan::parse(&plan))).collect())
+    }
+    
+    /// Count a failed login. Once `max_attempts` is reached the account is locked
+    /// until `locked_until` and the counter restarts; returns true when this call locked it.
+    pub async fn record_failed_login(
+        &self,
+        user_id: Uuid,
+        max_attempts: i32,
+        locked_until: DateTime<Utc>,
+        unlock_token: &str,
+    ) -> Result<bool> {
+        let (attempts, locked): (i32, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            UPDATE users SET
+                failed_login_attempts = CASE WHEN failed_login_attempts + 1 >= $2 THEN 0 ELSE failed_login_attempts + 1 END,
+                locked_until = CASE WHEN failed_login_attempts + 1 >= $2 THEN $3 ELSE locked_until END,
+                unlock_token = CASE WHEN failed_login_attempts + 1 >= $2 THEN $4 ELSE unlock_token END
+            WHERE id = $1
+            RETURNING failed_login_attempts, locked_until
+            "#
+        )
+        .bind(user_id)
+        .bind(max_attempts)
+        .bind(locked_until)
+        .bind(unlock_token)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(attempts == 0 && locked == Some(locked_until))
+    }
+    
+    pub async fn reset_failed_logins(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query(
+            "UPDATE users SET failed_login_attempts = 0, locked_until = NULL, unlock_token = NULL WHERE id = $1"
+        )
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn update_password_hash(&self, user_id: Uuid, password_hash: &str) -> Result<()> {
+        sqlx::query("UPDATE users SET password_hash = $1, updated_at = NOW() WHERE id = $2")
+            .bind(password_hash)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn set_user_role(&self, user_id: Uuid, role: Role) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "UPDATE users SET role = $1, updated_at = NOW() WHERE id = $2 RETURNING *"
+        )
+        .bind(role.as_str())
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// Replace the token a verification link must carry
+    pub async fn set_verification_token(&self, user_id: Uuid, token: &str) -> Result<()> {
+        sqlx::query("UPDATE users SET verification_token = $1 WHERE id = $2")
+            .bind(token)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Mark the address behind a verification link as confirmed; None for an unknown or used token
+    pub async fn verify_email(&self, token: &str) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            r#"
+            UPDATE users SET email_verified_at = NOW(), verification_token = NULL, updated_at = NOW()
+            WHERE verification_token = $1
+            RETURNING *
+            "#
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// Clear a lockout using the token from the unlock email
+    pub async fn unlock_user(&self, unlock_token: &str) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE users SET failed_login_attempts = 0, locked_until = NULL, unlock_token = NULL WHERE unlock_token = $1"
+        )
+        .bind(unlock_token)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    /// Deny a token until it would have expired anyway
+    pub async fn revoke_token(&self, jti: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("INSERT INTO revoked_tokens (jti, expires_at) VALUES ($1, $2) ON CONFLICT (jti) DO NOTHING")
+            .bind(jti)
+            .bind(expires_at)
+            .execute(&self.pool)
+            .await?;
+        
+        // Expired tokens fail signature validation anyway, so their entries can go
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn is_token_revoked(&self, jti: &str) -> Result<bool> {
+        let (revoked,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = $1)"
+        )
+        .bind(jti)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(revoked)
+    }
+    
+    // Update alerts to be user-scoped (served from the alert cache when enabled)
+    pub async fn get_alerts_by_user(&self, user_id: Uuid) -> Result<Vec<PriceAlert>> {
+        self.alert_cache
+            .get_or_load(user_id, async {
+                let alerts = sqlx::query_as::<_, PriceAlert>(
+                    "SELECT * FROM price_alerts WHERE user_id = $1 AND is_active = TRUE ORDER BY created_at DESC"
+                )
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await?;
+                Ok(alerts)
+            })
+            .await
+    }
+    
+    pub async fn get_alert_for_user(&self, id: Uuid, user_id: Uuid) -> Result<Option<PriceAlert>> {
+        let alert = sqlx::query_as::<_, PriceAlert>(
+            "SELECT * FROM price_alerts WHERE id = $1 AND user_id = $2 AND is_active = TRUE"
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(alert)
+    }
+    
+    // Replace the user-editable fields of an alert if it is still at `expected_version`.
+    // `None` means the alert is gone or someone else saved first. Scraped state is
+    // reset when the product changes.
+    pub async fn replace_alert(&self, id: Uuid, user_id: Uuid, expected_version: i32, alert: &PriceAlert) -> Result<Option<PriceAlert>> {
+        let result = sqlx::query_as::<_, PriceAlert>(
+            r#"
+            UPDATE price_alerts SET
+                last_price = CASE WHEN url = $1 THEN last_price END,
+                in_stock = CASE WHEN url = $1 THEN in_stock END,
+                offers = CASE WHEN url = $1 THEN offers ELSE '[]' END,
+                gone_checks = CASE WHEN url = $1 THEN gone_checks ELSE 0 END,
+                product_removed_at = CASE WHEN url = $1 THEN product_removed_at END,
+                product_name = CASE WHEN url = $1 THEN product_name END,
+                image_url = CASE WHEN url = $1 THEN image_url END,
+                url = $1,
+                target_price = $2,
+                user_email = $3,
+                platform = $4,
+                notify_channels = $5,
+                product_id = $6,
+                alert_mode = $7,
+                check_interval_minutes = $11,
+                expires_at = $12,
+                renotify = $13,
+                min_price = $14,
+                max_price = $15,
+                version = version + 1
+            WHERE id = $8 AND user_id = $9 AND version = $10 AND is_active = TRUE
+            RETURNING *
+            "#
+        )
+        .bind(&alert.url)
+        .bind(alert.target_price)
+        .bind(&alert.user_email)
+        .bind(&alert.platform)
+        .bind(&alert.notify_channels)
+        .bind(&alert.product_id)
+        .bind(&alert.alert_mode)
+        .bind(id)
+        .bind(user_id)
+        .bind(expected_version)
+        .bind(alert.check_interval_minutes)
+        .bind(alert.expires_at)
+        .bind(alert.renotify)
+        .bind(alert.min_price)
+        .bind(alert.max_price)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        if result.is_some() {
+            self.alert_cache.invalidate(user_id).await;
+        }
+        Ok(result)
+    }
+    
+    // Alert template methods
+    pub async fn create_alert_template(&self, user_id: Uuid, request: &AlertTemplateRequest) -> Result<AlertTemplate> {
+        let template = sqlx::query_as::<_, AlertTemplate>(
+            r#"
+            INSERT INTO alert_templates (user_id, name, discount_percent, notify_channels, check_interval_minutes, expires_after_days)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(request.name.trim())
+        .bind(request.discount_percent)
+        .bind(&request.notify_channels)
+        .bind(request.check_interval_minutes)
+        .bind(request.expires_after_days)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(template)
+    }
+    
+    pub async fn get_alert_templates_by_user(&self, user_id: Uuid) -> Result<Vec<AlertTemplate>> {
+        let templates = sqlx::query_as::<_, AlertTemplate>(
+            "SELECT * FROM alert_templates WHERE user_id = $1 ORDER BY name"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(templates)
+    }
+    
+    pub async fn get_alert_template(&self, id: Uuid, user_id: Uuid) -> Result<Option<AlertTemplate>> {
+        let template = sqlx::query_as::<_, AlertTemplate>(
+            "SELECT * FROM alert_templates WHERE id = $1 AND user_id = $2"
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(template)
+    }
+    
+    /// None when the template doesn't exist or belongs to another user
+    pub async fn update_alert_template(&self, id: Uuid, user_id: Uuid, request: &AlertTemplateRequest) -> Result<Option<AlertTemplate>> {
+        let template = sqlx::query_as::<_, AlertTemplate>(
+            r#"
+            UPDATE alert_templates SET
+                name = $1,
+                discount_percent = $2,
+                notify_channels = $3,
+                check_interval_minutes = $4,
+                expires_after_days = $5
+            WHERE id = $6 AND user_id = $7
+            RETURNING *
+            "#
+        )
+        .bind(request.name.trim())
+        .bind(request.discount_percent)
+        .bind(&request.notify_channels)
+        .bind(request.check_interval_minutes)
+        .bind(request.expires_after_days)
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(template)
+    }
+    
+    /// Alerts already made from the template are unaffected
+    pub async fn delete_alert_template(&self, id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM alert_templates WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    // Listing alert methods
+    pub async fn create_listing_alert(
+        &self,
+        user_id: Uuid,
+        user_email: &str,
+        platform: &str,
+        request: &CreateListingAlertRequest,
+        max_results: i32,
+    ) -> Result<ListingAlert> {
+        let created = sqlx::query_as::<_, ListingAlert>(
+            r#"
+            INSERT INTO listing_alerts (user_id, user_email, platform, url, target_price, max_results, include_terms, exclude_terms)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(user_email)
+        .bind(platform)
+        .bind(&request.url)
+        .bind(request.target_price)
+        .bind(max_results)
+        .bind(&request.include_terms)
+        .bind(&request.exclude_terms)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(created)
+    }
+    
+    pub async fn get_listing_alerts_by_user(&self, user_id: Uuid) -> Result<Vec<ListingAlert>> {
+        let alerts = sqlx::query_as::<_, ListingAlert>(
+            "SELECT * FROM listing_alerts WHERE user_id = $1 AND is_active = TRUE ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(alerts)
+    }
+    
+    pub async fn get_all_active_listing_alerts(&self) -> Result<Vec<ListingAlert>> {
+        let alerts = sqlx::query_as::<_, ListingAlert>(
+            "SELECT * FROM listing_alerts WHERE is_active = TRUE ORDER BY created_at"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(alerts)
+    }
+    
+    /// Stamp the check time and remember the result URLs just notified
+    pub async fn mark_listing_alert_checked(&self, id: Uuid, notified_urls: &[String]) -> Result<()> {
+        sqlx::query(
+            "UPDATE listing_alerts SET last_checked = NOW(), notified_urls = notified_urls || $1 WHERE id = $2"
+        )
+        .bind(notified_urls)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn save_listing_price(&self, listing_alert_id: Uuid, min_price: f64, results: i32) -> Result<()> {
+        sqlx::query("INSERT INTO listing_price_history (listing_alert_id, min_price, results) VALUES ($1, $2, $3)")
+            .bind(listing_alert_id)
+            .bind(min_price)
+            .bind(results)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Newest first; None when the alert doesn't exist or belongs to another user
+    pub async fn get_listing_price_history(
+        &self,
+        listing_alert_id: Uuid,
+        user_id: Uuid,
+        limit: i64,
+    ) -> Result<Option<Vec<ListingPricePoint>>> {
+        let owned: bool = sqlx::query_scalar(
+            "SELECT EXISTS (SELECT 1 FROM listing_alerts WHERE id = $1 AND user_id = $2)"
+        )
+        .bind(listing_alert_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        if !owned {
+            return Ok(None);
+        }
+        
+        let history = sqlx::query_as::<_, ListingPricePoint>(
+            r#"
+            SELECT min_price, results, checked_at FROM listing_price_history
+            WHERE listing_alert_id = $1 ORDER BY checked_at DESC LIMIT $2
+            "#
+        )
+        .bind(listing_alert_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(Some(history))
+    }
+    
+    /// Returns false when the alert doesn't exist or belongs to another user
+    pub async fn delete_listing_alert(&self, id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE listing_alerts SET is_active = FALSE WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    // Keyword watch methods
+    pub async fn create_keyword_watch(
+        &self,
+        user_id: Uuid,
+        user_email: &str,
+        request: &CreateKeywordWatchRequest,
+    ) -> Result<KeywordWatch> {
+        let created = sqlx::query_as::<_, KeywordWatch>(
+            r#"
+            INSERT INTO keyword_watches (user_id, user_email, query, target_price, platforms)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING *
+            "#
+        )
+        .bind(user_id)
+        .bind(user_email)
+        .bind(request.query.trim())
+        .bind(request.target_price)
+        .bind(&request.platforms)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(created)
+    }
+    
+    pub async fn get_keyword_watches_by_user(&self, user_id: Uuid) -> Result<Vec<KeywordWatch>> {
+        let watches = sqlx::query_as::<_, KeywordWatch>(
+            "SELECT * FROM keyword_watches WHERE user_id = $1 AND is_active = TRUE ORDER BY created_at DESC"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(watches)
+    }
+    
+    pub async fn get_all_active_keyword_watches(&self) -> Result<Vec<KeywordWatch>> {
+        let watches = sqlx::query_as::<_, KeywordWatch>(
+            "SELECT * FROM keyword_watches WHERE is_active = TRUE ORDER BY created_at"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(watches)
+    }
+    
+    /// Stamp the check time and remember the result URLs just notified
+    pub async fn mark_keyword_watch_checked(&self, id: Uuid, notified_urls: &[String]) -> Result<()> {
+        sqlx::query(
+            "UPDATE keyword_watches SET last_checked = NOW(), notified_urls = notified_urls || $1 WHERE id = $2"
+        )
+        .bind(notified_urls)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Returns false when the watch doesn't exist or belongs to another user
+    pub async fn delete_keyword_watch(&self, id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("UPDATE keyword_watches SET is_active = FALSE WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    // Notification channel methods
+    pub async fn create_channel(&self, user_id: Uuid, channel: &str, destination: &str) -> Result<NotificationChannel> {
+        let created = sqlx::query_as::<_, NotificationChannel>(
+            r#"
+            INSERT INTO notification_channels (user_id, channel, destination)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, channel, destination) DO UPDATE SET enabled = TRUE
+            RETURNING *
             "#
         )
+        .bind(user_id)
+        .bind(channel)
+        .bind(destination)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(created)
+    }
+    
+    /// A one-time code the user sends to the bot to link a chat
+    pub async fn create_telegram_link_code(&self, user_id: Uuid, code: &str, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query("INSERT INTO telegram_link_codes (code, user_id, expires_at) VALUES ($1, $2, $3)")
+            .bind(code)
+            .bind(user_id)
+            .bind(expires_at)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Consume a link code and attach the chat to its user. None if the code is unknown or expired.
+    pub async fn link_telegram_chat(&self, code: &str, chat_id: i64) -> Result<Option<Uuid>> {
+        let mut tx = self.pool.begin().await?;
+        
+        let user_id: Option<Uuid> = sqlx::query_scalar(
+            "DELETE FROM telegram_link_codes WHERE code = $1 AND expires_at > NOW() RETURNING user_id"
+        )
+        .bind(code)
+        .fetch_optional(&mut *tx)
+        .await?;
+        
+        if let Some(user_id) = user_id {
+            sqlx::query(
+                r#"
+                INSERT INTO telegram_chats (chat_id, user_id) VALUES ($1, $2)
+                ON CONFLICT (chat_id) DO UPDATE SET user_id = EXCLUDED.user_id, linked_at = NOW()
+                "#
+            )
+            .bind(chat_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(user_id)
+    }
+    
+    pub async fn get_telegram_chat_user(&self, chat_id: i64) -> Result<Option<User>> {
+        let user = sqlx::query_as::<_, User>(
+            "SELECT users.* FROM telegram_chats JOIN users ON users.id = telegram_chats.user_id WHERE telegram_chats.chat_id = $1"
+        )
+        .bind(chat_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(user)
+    }
+    
+    /// Forget the chat and stop sending it notifications
+    pub async fn unlink_telegram_chat(&self, chat_id: i64) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        
+        let user_id: Option<Uuid> = sqlx::query_scalar("DELETE FROM telegram_chats WHERE chat_id = $1 RETURNING user_id")
+            .bind(chat_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        
+        if let Some(user_id) = user_id {
+            sqlx::query("DELETE FROM notification_channels WHERE user_id = $1 AND channel = 'telegram' AND destination = $2")
+                .bind(user_id)
+                .bind(chat_id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        tx.commit().await?;
+        Ok(user_id.is_some())
+    }
+    
+    pub async fn get_channels_by_user(&self, user_id: Uuid) -> Result<Vec<NotificationChannel>> {
+        let channels = sqlx::query_as::<_, NotificationChannel>(
+            "SELECT * FROM notification_channels WHERE user_id = $1 ORDER BY created_at"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(channels)
+    }
+    
+    pub async fn get_enabled_channels(&self, user_id: Uuid) -> Result<Vec<NotificationChannel>> {
+        let channels = sqlx::query_as::<_, NotificationChannel>(
+            "SELECT * FROM notification_channels WHERE user_id = $1 AND enabled = TRUE ORDER BY created_at"
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(channels)
+    }
+    
+    /// True when every ID refers to a channel owned by the user
+    pub async fn channels_belong_to_user(&self, ids: &[Uuid], user_id: Uuid) -> Result<bool> {
+        // A channel listed twice is still one row
+        let mut ids = ids.to_vec();
+        ids.sort();
+        ids.dedup();
+        let (owned,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM notification_channels WHERE id = ANY($1) AND user_id = $2"
+        )
+        .bind(&ids)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(owned as usize == ids.len())
+    }
+    
+    /// Returns false when the channel doesn't exist or belongs to another user
+    pub async fn delete_channel(&self, id: Uuid, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM notification_channels WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    // Account export/import methods
+    pub async fn get_full_price_history(&self, alert_id: Uuid) -> Result<Vec<ExportedPrice>> {
+        let history = sqlx::query_as::<_, ExportedPrice>(
+            // Suspect prices are scraping glitches, not worth carrying to another account
+            "SELECT price, mrp, checked_at FROM price_history WHERE alert_id = $1 AND NOT suspect ORDER BY checked_at"
+        )
         .bind(alert_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(history)
+    }
+    
+    /// (product, alert) pairs for every product the user has
+    pub async fn get_product_memberships(&self, user_id: Uuid) -> Result<Vec<(Uuid, Uuid)>> {
+        let memberships = sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            SELECT pa.product_id, pa.alert_id
+            FROM product_alerts pa
+            JOIN products p ON p.id = pa.product_id
+            WHERE p.user_id = $1
+            "#
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(memberships)
+    }
+    
+    // Add an exported account's contents to this user in one transaction. Entries are
+    // expected to be validated already; ones the user already has are skipped, so
+    // importing the same file twice doesn't duplicate anything.
+    pub async fn import_account(&self, user_id: Uuid, user_email: &str, export: &AccountExport) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        let mut tx = self.pool.begin().await?;
+        
+        if let Some(preferences) = &export.preferences {
+            sqlx::query(
+                r#"
+                UPDATE users SET timezone = $2, default_discount_percent = $3, weekly_summary = $4,
+                    exclude_from_public = $5, notifications_paused = $6, updated_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(user_id)
+            .bind(&preferences.timezone)
+            .bind(preferences.default_discount_percent)
+            .bind(preferences.weekly_summary)
+            .bind(preferences.exclude_from_public)
+            .bind(preferences.notifications_paused)
+            .execute(&mut *tx)
+            .await?;
+            summary.preferences = true;
+        }
+        
+        // IDs in the file -> IDs here
+        let mut channel_ids = HashMap::new();
+        for channel in &export.channels {
+            let id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO notification_channels (user_id, channel, destination, enabled)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (user_id, channel, destination)
+                DO UPDATE SET enabled = notification_channels.enabled OR EXCLUDED.enabled
+                RETURNING id
+                "#
+            )
+            .bind(user_id)
+            .bind(&channel.channel)
+            .bind(&channel.destination)
+            .bind(channel.enabled)
+            .fetch_one(&mut *tx)
+            .await?;
+            channel_ids.insert(channel.id, id);
+            summary.channels += 1;
+        }
+        let map_channels = |ids: &Option<Vec<Uuid>>| {
+            ids.as_ref()
+                .map(|ids| ids.iter().filter_map(|id| channel_ids.get(id).copied()).collect::<Vec<Uuid>>())
+                .filter(|ids| !ids.is_empty())
+        };
+        
+        for template in &export.templates {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM alert_templates WHERE user_id = $1 AND name = $2)"
+            )
+            .bind(user_id)
+            .bind(template.name.trim())
+            .fetch_one(&mut *tx)
+            .await?;
+            if exists {
+                summary.skipped.push(format!("template {}: already exists", template.name));
+                continue;
+            }
+            
+            sqlx::query(
+                r#"
+                INSERT INTO alert_templates (user_id, name, discount_percent, notify_channels, check_interval_minutes, expires_after_days)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(user_id)
+            .bind(template.name.trim())
+            .bind(template.discount_percent)
+            .bind(map_channels(&template.notify_channels))
+            .bind(template.check_interval_minutes)
+            .bind(template.expires_after_days)
+            .execute(&mut *tx)
+            .await?;
+            summary.templates += 1;
+        }
+        
+        let mut alert_ids = HashMap::new();
+        for alert in &export.alerts {
+            let existing: Option<Uuid> = sqlx::query_scalar(
+                "SELECT id FROM price_alerts WHERE user_id = $1 AND url = $2 AND is_active = TRUE LIMIT 1"
+            )
+            .bind(user_id)
+            .bind(&alert.url)
+            .fetch_optional(&mut *tx)
+            .await?;
+            if let Some(existing) = existing {
+                alert_ids.insert(alert.id, existing);
+                summary.skipped.push(format!("alert {}: already tracked", alert.url));
+                continue;
+            }
+            
+            let last_checked = alert.history.last().map_or(alert.created_at, |point| point.checked_at);
+            let id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO price_alerts (url, target_price, last_price, user_email, user_id, platform, created_at, last_checked, is_active, notify_channels, product_id, alert_mode,
+                    check_interval_minutes, expires_at, renotify, min_price, max_price, notifications_paused)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, TRUE, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+                RETURNING id
+                "#
+            )
+            .bind(&alert.url)
+            .bind(alert.target_price)
+            .bind(alert.last_price)
+            .bind(&alert.user_email)
+            .bind(user_id)
+            .bind(&alert.platform)
+            .bind(alert.created_at)
+            .bind(last_checked)
+            .bind(map_channels(&alert.notify_channels))
+            .bind(&alert.product_id)
+            .bind(&alert.alert_mode)
+            .bind(alert.check_interval_minutes)
+            .bind(alert.expires_at)
+            .bind(sqlx::types::Json(alert.renotify))
+            .bind(alert.min_price)
+            .bind(alert.max_price)
+            .bind(alert.notifications_paused)
+            .fetch_one(&mut *tx)
+            .await?;
+            
+            if !alert.thresholds.is_empty() {
+                sqlx::query(
+                    r#"
+                    INSERT INTO alert_thresholds (alert_id, price)
+                    SELECT $1, price FROM UNNEST($2::float8[]) AS price
+                    ON CONFLICT (alert_id, price) DO NOTHING
+                    "#
+                )
+                .bind(id)
+                .bind(&alert.thresholds)
+                .execute(&mut *tx)
+                .await?;
+            }
+            
+            if !alert.history.is_empty() {
+                let prices: Vec<f64> = alert.history.iter().map(|point| point.price).collect();
+                let mrps: Vec<Option<f64>> = alert.history.iter().map(|point| point.mrp).collect();
+                let checked_at: Vec<DateTime<Utc>> = alert.history.iter().map(|point| point.checked_at).collect();
+                sqlx::query(
+                    r#"
+                    INSERT INTO price_history (alert_id, price, mrp, checked_at)
+                    SELECT $1, price, mrp, checked_at
+                    FROM UNNEST($2::float8[], $3::float8[], $4::timestamptz[]) AS h(price, mrp, checked_at)
+                    "#
+                )
+                .bind(id)
+                .bind(&prices)
+                .bind(&mrps)
+                .bind(&checked_at)
+                .execute(&mut *tx)
+                .await?;
+                summary.price_points += prices.len();
+            }
+            
+            alert_ids.insert(alert.id, id);
+            summary.alerts += 1;
+        }
+        
+        for product in &export.products {
+            let members: Vec<Uuid> = product.alert_ids.iter().filter_map(|id| alert_ids.get(id).copied()).collect();
+            if members.is_empty() {
+                summary.skipped.push(format!("product {}: none of its alerts were imported", product.name));
+                continue;
+            }
+            
+            let product_id: Uuid = sqlx::query_scalar(
+                "INSERT INTO products (user_id, name) VALUES ($1, $2) RETURNING id"
+            )
+            .bind(user_id)
+            .bind(&product.name)
+            .fetch_one(&mut *tx)
+            .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO product_alerts (alert_id, product_id)
+                SELECT UNNEST($1::uuid[]), $2
+                ON CONFLICT (alert_id) DO NOTHING
+                "#
+            )
+            .bind(&members)
+            .bind(product_id)
+            .execute(&mut *tx)
+            .await?;
+            summary.products += 1;
+        }
+        
+        for listing in &export.listing_alerts {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM listing_alerts WHERE user_id = $1 AND url = $2 AND is_active = TRUE)"
+            )
+            .bind(user_id)
+            .bind(&listing.url)
+            .fetch_one(&mut *tx)
+            .await?;
+            if exists {
+                summary.skipped.push(format!("listing alert {}: already watched", listing.url));
+                continue;
+            }
+            
+            sqlx::query(
+                r#"
+                INSERT INTO listing_alerts (user_id, user_email, platform, url, target_price, max_results, include_terms, exclude_terms)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                "#
+            )
+            .bind(user_id)
+            .bind(user_email)
+            .bind(&listing.platform)
+            .bind(&listing.url)
+            .bind(listing.target_price)
+            .bind(listing.max_results)
+            .bind(&listing.include_terms)
+            .bind(&listing.exclude_terms)
+            .execute(&mut *tx)
+            .await?;
+            summary.listing_alerts += 1;
+        }
+        
+        for watch in &export.watches {
+            let exists: bool = sqlx::query_scalar(
+                "SELECT EXISTS (SELECT 1 FROM keyword_watches WHERE user_id = $1 AND LOWER(query) = LOWER($2) AND is_active = TRUE)"
+            )
+            .bind(user_id)
+            .bind(watch.query.trim())
+            .fetch_one(&mut *tx)
+            .await?;
+            if exists {
+                summary.skipped.push(format!("watch \"{}\": already watched", watch.query));
+                continue;
+            }
+            
+            sqlx::query(
+                "INSERT INTO keyword_watches (user_id, user_email, query, target_price, platforms) VALUES ($1, $2, $3, $4, $5)"
+            )
+            .bind(user_id)
+            .bind(user_email)
+            .bind(watch.query.trim())
+            .bind(watch.target_price)
+            .bind(&watch.platforms)
+            .execute(&mut *tx)
+            .await?;
+            summary.watches += 1;
+        }
+        
+        tx.commit().await?;
+        
+        self.alert_cache.invalidate(user_id).await;
+        Ok(summary)
+    }
+    
+    // Scraper config methods
+    pub async fn get_scraper_configs(&self) -> Result<Vec<ScraperConfig>> {
+        let configs = sqlx::query_as::<_, ScraperConfig>(
+            "SELECT * FROM scraper_configs ORDER BY platform"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(configs)
+    }
+    
+    pub async fn upsert_scraper_config(&self, platform: &str, config: &UpsertScraperConfigRequest) -> Result<ScraperConfig> {
+        let saved = sqlx::query_as::<_, ScraperConfig>(
+            r#"
+            INSERT INTO scraper_configs (platform, selectors, price_patterns, headers, timeout_secs, connect_timeout_secs, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, NOW())
+            ON CONFLICT (platform) DO UPDATE SET
+                selectors = EXCLUDED.selectors,
+                price_patterns = EXCLUDED.price_patterns,
+                headers = EXCLUDED.headers,
+                timeout_secs = EXCLUDED.timeout_secs,
+                connect_timeout_secs = EXCLUDED.connect_timeout_secs,
+                updated_at = NOW()
+            RETURNING *
+            "#
+        )
+        .bind(platform)
+        .bind(&config.selectors)
+        .bind(&config.price_patterns)
+        .bind(sqlx::types::Json(&config.headers))
+        .bind(config.timeout_secs)
+        .bind(config.connect_timeout_secs)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(saved)
+    }
+    
+    pub async fn delete_scraper_config(&self, platform: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM scraper_configs WHERE platform = $1")
+            .bind(platform)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+    
+    // Check run checkpoints, so an interrupted run resumes instead of starting over
+    pub async fn get_check_checkpoint(&self, run: &str) -> Result<Option<CheckCheckpoint>> {
+        let checkpoint = sqlx::query_as::<_, CheckCheckpoint>(
+            "SELECT run_started_at, after_created_at, after_id, updated_at FROM check_checkpoints WHERE run = $1"
+        )
+        .bind(run)
         .fetch_optional(&self.pool)
         .await?;
         
+        Ok(checkpoint)
+    }
+    
+    pub async fn save_check_checkpoint(
+        &self,
+        run: &str,
+        run_started_at: DateTime<Utc>,
+        after_created_at: DateTime<Utc>,
+        after_id: Uuid,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO check_checkpoints (run, run_started_at, after_created_at, after_id, updated_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            ON CONFLICT (run) DO UPDATE SET
+                run_started_at = EXCLUDED.run_started_at,
+                after_created_at = EXCLUDED.after_created_at,
+                after_id = EXCLUDED.after_id,
+                updated_at = NOW()
+            "#
+        )
+        .bind(run)
+        .bind(run_started_at)
+        .bind(after_created_at)
+        .bind(after_id)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn clear_check_checkpoint(&self, run: &str) -> Result<()> {
+        sqlx::query("DELETE FROM check_checkpoints WHERE run = $1")
+            .bind(run)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    // Scrape attempt tracking
+    pub async fn record_scrape_attempt(
+        &self,
+        alert_id: Option<Uuid>,
+        platform: &str,
+        duration_ms: i32,
+        outcome: &str,
+        error_kind: Option<&str>,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO scrape_attempts (alert_id, platform, duration_ms, outcome, error_kind) VALUES ($1, $2, $3, $4, $5)"
+        )
+        .bind(alert_id)
+        .bind(platform)
+        .bind(duration_ms)
+        .bind(outcome)
+        .bind(error_kind)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    pub async fn get_scrape_stats(&self, since: DateTime<Utc>) -> Result<Vec<ScrapeStats>> {
+        let stats = sqlx::query_as::<_, ScrapeStats>(
+            r#"
+            SELECT
+                platform,
+                COUNT(*) AS attempts,
+                COUNT(*) FILTER (WHERE outcome = 'success') AS successes,
+                COUNT(*) FILTER (WHERE outcome <> 'success') AS failures,
+                AVG(duration_ms)::DOUBLE PRECISION AS avg_duration_ms,
+                PERCENTILE_CONT(0.95) WITHIN GROUP (ORDER BY duration_ms) AS p95_duration_ms
+            FROM scrape_attempts
+            WHERE attempted_at >= $1
+            GROUP BY platform
+            ORDER BY platform
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
         Ok(stats)
     }
     
-    // User authentication methods
-    pub async fn create_user(&self, email: &str, password_hash: &str) -> Result<User> {
-        let user = sqlx::query_as::<_, User>(
+    pub async fn get_platform_throttles(&self) -> Result<Vec<PlatformThrottle>> {
+        let throttles = sqlx::query_as::<_, PlatformThrottle>("SELECT * FROM platform_throttles ORDER BY platform")
+            .fetch_all(&self.pool)
+            .await?;
+        
+        Ok(throttles)
+    }
+    
+    /// (platform, attempts, failures) since `since` or the platform's last throttle
+    /// adjustment, whichever is later. A missing page is the product's doing, not the
+    /// platform's, so it counts as an attempt but not a failure.
+    pub async fn get_platform_failure_counts(&self, since: DateTime<Utc>) -> Result<Vec<(String, i64, i64)>> {
+        let counts = sqlx::query_as(
             r#"
-            INSERT INTO users (email, password_hash, created_at, updated_at)
+            SELECT a.platform, COUNT(*), COUNT(*) FILTER (WHERE a.outcome <> 'success' AND a.error_kind IS DISTINCT FROM 'page_gone')
+            FROM scrape_attempts a
+            LEFT JOIN platform_throttles t ON t.platform = a.platform
+            WHERE a.attempted_at >= GREATEST($1, t.adjusted_at)
+            GROUP BY a.platform
+            ORDER BY a.platform
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(counts)
+    }
+    
+    /// Set a platform's throttle factor and record why
+    pub async fn save_throttle_adjustment(
+        &self,
+        adjustment: &ThrottleAdjustment,
+        next_check_at: Option<DateTime<Utc>>,
+    ) -> Result<PlatformThrottle> {
+        let mut tx = self.pool.begin().await?;
+        let throttle = sqlx::query_as::<_, PlatformThrottle>(
+            r#"
+            INSERT INTO platform_throttles (platform, factor, adjusted_at, next_check_at)
             VALUES ($1, $2, $3, $4)
+            ON CONFLICT (platform) DO UPDATE
+            SET factor = EXCLUDED.factor, adjusted_at = EXCLUDED.adjusted_at, next_check_at = EXCLUDED.next_check_at
             RETURNING *
             "#
         )
-        .bind(email)
-        .bind(password_hash)
-        .bind(Utc::now())
-        .bind(Utc::now())
-        .fetch_one(&self.pool)
+        .bind(&adjustment.platform)
+        .bind(adjustment.to_factor)
+        .bind(adjustment.created_at)
+        .bind(next_check_at)
+        .fetch_one(&mut *tx)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT INTO throttle_adjustments (platform, from_factor, to_factor, attempts, failures, reason, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#
+        )
+        .bind(&adjustment.platform)
+        .bind(adjustment.from_factor)
+        .bind(adjustment.to_factor)
+        .bind(adjustment.attempts)
+        .bind(adjustment.failures)
+        .bind(&adjustment.reason)
+        .bind(adjustment.created_at)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
         
-        Ok(user)
+        Ok(throttle)
     }
     
-    pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
-        let user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE email = $1"
+    /// When a throttled platform's alerts are next checked
+    pub async fn set_throttle_next_check(&self, platform: &str, next_check_at: Option<DateTime<Utc>>) -> Result<()> {
+        sqlx::query("UPDATE platform_throttles SET next_check_at = $2 WHERE platform = $1")
+            .bind(platform)
+            .bind(next_check_at)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// The latest throttle adjustments, newest first
+    pub async fn get_throttle_adjustments(&self, limit: i64) -> Result<Vec<ThrottleAdjustment>> {
+        let adjustments = sqlx::query_as::<_, ThrottleAdjustment>(
+            r#"
+            SELECT platform, from_factor, to_factor, attempts, failures, reason, created_at
+            FROM throttle_adjustments
+            ORDER BY created_at DESC
+            LIMIT $1
+            "#
         )
-        .bind(email)
-        .fetch_optional(&self.pool)
+        .bind(limit)
+        .fetch_all(&self.pool)
         .await?;
         
-        Ok(user)
+        Ok(adjustments)
     }
     
-    pub async fn get_user_by_id(&self, user_id: Uuid) -> Result<Option<User>> {
-        let user = sqlx::query_as::<_, User>(
-            "SELECT * FROM users WHERE id = $1"
+    /// (platform, error_kind, count) for failed attempts since `since`
+    pub async fn get_scrape_error_counts(&self, since: DateTime<Utc>) -> Result<Vec<(String, String, i64)>> {
+        let counts = sqlx::query_as(
+            r#"
+            SELECT platform, COALESCE(error_kind, 'unknown'), COUNT(*)
+            FROM scrape_attempts
+            WHERE attempted_at >= $1 AND outcome <> 'success'
+            GROUP BY 1, 2
+            ORDER BY 1, 3 DESC
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(counts)
+    }
+    
+    pub async fn record_notification_delivery(&self, channel: &str, outcome: &str, error_kind: Option<&str>) -> Result<()> {
+        sqlx::query("INSERT INTO notification_deliveries (channel, outcome, error_kind) VALUES ($1, $2, $3)")
+            .bind(channel)
+            .bind(outcome)
+            .bind(error_kind)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Count one email against the user's budget for today (UTC). False, and nothing
+    /// counted, once `cap` emails have gone out today.
+    pub async fn take_email_budget(&self, user_id: Uuid, cap: i32) -> Result<bool> {
+        let sent = sqlx::query_scalar::<_, i32>(
+            r#"
+            INSERT INTO email_sends (user_id, day, sent) VALUES ($1, (NOW() AT TIME ZONE 'UTC')::date, 1)
+            ON CONFLICT (user_id, day) DO UPDATE SET sent = email_sends.sent + 1
+            WHERE email_sends.sent < $2
+            RETURNING sent
+            "#
         )
         .bind(user_id)
+        .bind(cap)
         .fetch_optional(&self.pool)
         .await?;
         
-        Ok(user)
+        Ok(sent.is_some())
     }
     
-    // Update alerts to be user-scoped
-    pub async fn get_alerts_by_user(&self, user_id: Uuid) -> Result<Vec<PriceAlert>> {
-        let alerts = sqlx::query_as::<_, PriceAlert>(
-            "SELECT * FROM price_alerts WHERE user_id = $1 AND is_active = TRUE ORDER BY created_at DESC"
+    /// Claim up to `limit` unsent outbox entries nobody is working on and with attempts
+    /// left, oldest first, for `claim`; each claim counts as an attempt
+    pub async fn claim_outbox(&self, limit: i64, max_attempts: i32, claim: chrono::Duration) -> Result<Vec<OutboxEntry>> {
+        let entries = sqlx::query_as::<_, OutboxEntry>(
+            r#"
+            UPDATE notification_outbox SET claimed_until = $2, attempts = attempts + 1
+            WHERE id IN (
+                SELECT id FROM notification_outbox
+                WHERE sent_at IS NULL AND attempts < $3 AND (claimed_until IS NULL OR claimed_until <= NOW())
+                ORDER BY created_at
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, event, channels, attempts, created_at
+            "#
         )
-        .bind(user_id)
+        .bind(limit)
+        .bind(Utc::now() + claim)
+        .bind(max_attempts)
         .fetch_all(&self.pool)
         .await?;
         
-        Ok(alerts)
+        Ok(entries)
+    }
+    
+    pub async fn mark_outbox_sent(&self, id: Uuid) -> Result<()> {
+        sqlx::query("UPDATE notification_outbox SET sent_at = NOW(), claimed_until = NULL, last_error = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Leave an entry for another attempt once `retry_at` comes
+    pub async fn release_outbox(&self, id: Uuid, retry_at: DateTime<Utc>, error: &str) -> Result<()> {
+        sqlx::query("UPDATE notification_outbox SET claimed_until = $2, last_error = $3 WHERE id = $1")
+            .bind(id)
+            .bind(retry_at)
+            .bind(error)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Unsent outbox entries, and the age of the oldest
+    pub async fn get_outbox_backlog(&self) -> Result<(i64, Option<DateTime<Utc>>)> {
+        let backlog = sqlx::query_as("SELECT COUNT(*), MIN(created_at) FROM notification_outbox WHERE sent_at IS NULL")
+            .fetch_one(&self.pool)
+            .await?;
+        
+        Ok(backlog)
+    }
+    
+    /// Hold a notification for the next digest
+    pub async fn defer_notification(&self, user_id: Uuid, destination: &str, event: &NotificationEvent) -> Result<()> {
+        sqlx::query("INSERT INTO deferred_notifications (user_id, destination, event) VALUES ($1, $2, $3)")
+            .bind(user_id)
+            .bind(destination)
+            .bind(sqlx::types::Json(event))
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Remove and return notifications deferred before today (UTC), grouped by user and
+    /// destination in the order they were held back
+    pub async fn take_due_digests(&self) -> Result<Vec<(Uuid, String, Vec<NotificationEvent>)>> {
+        let rows: Vec<(Uuid, String, sqlx::types::Json<NotificationEvent>)> = sqlx::query_as(
+            r#"
+            WITH due AS (
+                DELETE FROM deferred_notifications
+                WHERE created_at < date_trunc('day', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+                RETURNING user_id, destination, event, created_at
+            )
+            SELECT user_id, destination, event FROM due ORDER BY user_id, destination, created_at
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let mut digests: Vec<(Uuid, String, Vec<NotificationEvent>)> = Vec::new();
+        for (user_id, destination, event) in rows {
+            match digests.last_mut() {
+                Some((user, dest, events)) if *user == user_id && *dest == destination => events.push(event.0),
+                _ => digests.push((user_id, destination, vec![event.0])),
+            }
+        }
+        Ok(digests)
+    }
+    
+    /// (channel, outcome, error_kind or "", count) for attempts since `since`
+    pub async fn get_delivery_counts(&self, since: DateTime<Utc>) -> Result<Vec<(String, String, String, i64)>> {
+        let counts = sqlx::query_as(
+            r#"
+            SELECT channel, outcome, COALESCE(error_kind, ''), COUNT(*)
+            FROM notification_deliveries
+            WHERE attempted_at >= $1
+            GROUP BY 1, 2, 3
+            ORDER BY 1, 4 DESC
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(counts)
     }
 }
-
@@ -0,0 +1,101 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::auth::hash_password;
+use crate::db::Database;
+use crate::models::{
+    AccountExport, ExportedAlert, ExportedPrice, ExportedProduct, RenotifyPolicy, ACCOUNT_EXPORT_FORMAT, ACCOUNT_EXPORT_VERSION,
+};
+use crate::scrapers::demo::{demo_price, today};
+
+pub const DEMO_EMAIL: &str = "demo@example.com";
+pub const DEMO_PASSWORD: &str = "demo-password";
+
+/// Days of synthetic history behind each seeded alert
+const HISTORY_DAYS: i64 = 60;
+
+/// Demo mode: `--demo` on the command line or `DEMO_MODE=true`
+pub fn enabled() -> bool {
+    std::env::args().any(|arg| arg == "--demo")
+        || std::env::var("DEMO_MODE")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes" | "on"))
+            .unwrap_or(false)
+}
+
+/// Sample alerts: URL, mode, and target as a fraction of today's demo price
+const SAMPLE_ALERTS: &[(&str, &str, f64)] = &[
+    ("https://www.myntra.com/jeans/levis/levis-men-511-slim-fit-jeans/11842204/buy", "price", 1.05),
+    ("https://www.flipkart.com/levi-s-511-slim-men-blue-jeans/p/itmf3c8d9a2b7e41", "price", 0.92),
+    ("https://www.ajio.com/nike-pegasus-40-running-shoes/p/469581234", "price", 0.95),
+    ("https://www.tatacliq.com/puma-men-regular-fit-polo-t-shirt/p-mp000000017654321", "back_in_stock", 0.0),
+];
+
+/// Create the demo account with sample alerts and history, unless it already exists.
+/// Goes through the account import, so the seed is exactly what an export would restore.
+pub async fn seed(db: &Database) -> Result<()> {
+    if db.get_user_by_email(DEMO_EMAIL).await?.is_some() {
+        return Ok(());
+    }
+    let user = db.create_user(DEMO_EMAIL, &hash_password(DEMO_PASSWORD)?).await?;
+
+    let today = today();
+    let alerts: Vec<ExportedAlert> = SAMPLE_ALERTS
+        .iter()
+        .map(|(url, mode, target)| {
+            let history: Vec<ExportedPrice> = (today - HISTORY_DAYS..today)
+                .map(|day| ExportedPrice { price: demo_price(url, day), mrp: None, checked_at: noon(day) })
+                .collect();
+            ExportedAlert {
+                id: Uuid::new_v4(),
+                url: url.to_string(),
+                platform: crate::scraper_trait::detect_platform(url).unwrap_or_default().to_string(),
+                product_id: None,
+                alert_mode: mode.to_string(),
+                target_price: (demo_price(url, today) * target).round(),
+                user_email: DEMO_EMAIL.to_string(),
+                last_price: history.last().map(|point| point.price),
+                notify_channels: None,
+                created_at: noon(today - HISTORY_DAYS),
+                check_interval_minutes: None,
+                expires_at: None,
+                renotify: RenotifyPolicy::default(),
+                min_price: None,
+                max_price: None,
+                thresholds: Vec::new(),
+                notifications_paused: false,
+                history,
+            }
+        })
+        .collect();
+
+    // The two Levi's listings are the same jeans, for the platform comparison
+    let products = vec![ExportedProduct {
+        name: "Levi's 511 Slim Fit Jeans".to_string(),
+        alert_ids: alerts.iter().take(2).map(|alert| alert.id).collect(),
+    }];
+
+    let summary = db
+        .import_account(user.id, DEMO_EMAIL, &AccountExport {
+            format: ACCOUNT_EXPORT_FORMAT.to_string(),
+            version: ACCOUNT_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            preferences: None,
+            channels: Vec::new(),
+            templates: Vec::new(),
+            alerts,
+            products,
+            listing_alerts: Vec::new(),
+            watches: Vec::new(),
+        })
+        .await?;
+    tracing::info!(
+        "🎭 Seeded demo account with {} alerts and {} price points",
+        summary.alerts,
+        summary.price_points
+    );
+    Ok(())
+}
+
+fn noon(day: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(day * 86_400 + 12 * 3_600, 0).unwrap_or_else(Utc::now)
+}
@@ -0,0 +1,246 @@
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+use crate::config::DiscoveryConfig;
+use crate::db::Database;
+use crate::models::SitemapSource;
+use crate::scraper_config::ScraperConfigStore;
+use crate::scraper_trait::{desktop_url, detect_platform, PageKind, PriceScraper};
+use crate::scrapers::{body, create_scraper};
+
+/// How often due sitemaps and products are looked for
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Products kept from a sitemap unless the admin says otherwise, and the most allowed
+pub const DEFAULT_MAX_PRODUCTS: i32 = 200;
+pub const MAX_PRODUCTS: i32 = 2000;
+
+/// Weight of an entry without a `<priority>`, as the sitemap protocol defines it
+const DEFAULT_PRIORITY: f64 = 0.5;
+
+static URL_ENTRY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<url\b[^>]*>(.*?)</url>").unwrap());
+static SITEMAP_ENTRY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<sitemap\b[^>]*>(.*?)</sitemap>").unwrap());
+static LOC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<loc>(.*?)</loc>").unwrap());
+static PRIORITY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<priority>(.*?)</priority>").unwrap());
+static LASTMOD: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<lastmod>(.*?)</lastmod>").unwrap());
+
+/// A `<url>` of a sitemap
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapEntry {
+    pub loc: String,
+    pub priority: f64,
+    /// As written (W3C datetime), which sorts chronologically
+    pub lastmod: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sitemap {
+    /// A `<sitemapindex>`: the sitemaps it lists
+    Index(Vec<String>),
+    Urls(Vec<SitemapEntry>),
+}
+
+fn tag(pattern: &Regex, xml: &str) -> Option<String> {
+    let text = pattern.captures(xml)?.get(1)?.as_str().trim();
+    let text = text.strip_prefix("<![CDATA[").and_then(|t| t.strip_suffix("]]>")).unwrap_or(text);
+    let text = text.replace("&amp;", "&").replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'");
+    Some(text.trim().to_string()).filter(|text| !text.is_empty())
+}
+
+/// Read a sitemap or sitemap index. Entries without a `<loc>` are skipped.
+pub fn parse_sitemap(xml: &str) -> Sitemap {
+    if xml.contains("<sitemapindex") {
+        let sitemaps = SITEMAP_ENTRY
+            .captures_iter(xml)
+            .filter_map(|entry| tag(&LOC, &entry[1]))
+            .collect();
+        return Sitemap::Index(sitemaps);
+    }
+    let entries = URL_ENTRY
+        .captures_iter(xml)
+        .filter_map(|entry| {
+            let entry = &entry[1];
+            Some(SitemapEntry {
+                loc: tag(&LOC, entry)?,
+                priority: tag(&PRIORITY, entry).and_then(|p| p.parse().ok()).unwrap_or(DEFAULT_PRIORITY),
+                lastmod: tag(&LASTMOD, entry),
+            })
+        })
+        .collect();
+    Sitemap::Urls(entries)
+}
+
+/// The `platform` product pages among `entries` as `(product ID, URL, priority)`: the
+/// sitemap's own ranking stands in for popularity, so the highest priority and most
+/// recently modified come first. One entry per product, at most `max`.
+pub fn select_products(
+    entries: &[SitemapEntry],
+    scraper: &dyn PriceScraper,
+    platform: &str,
+    max: usize,
+) -> Vec<(String, String, f64)> {
+    let mut entries: Vec<&SitemapEntry> = entries
+        .iter()
+        .filter(|entry| detect_platform(&entry.loc) == Some(platform))
+        .filter(|entry| PageKind::of(scraper, &entry.loc) == PageKind::Product)
+        .collect();
+    entries.sort_by(|a, b| b.priority.total_cmp(&a.priority).then_with(|| b.lastmod.cmp(&a.lastmod)));
+
+    let mut products: Vec<(String, String, f64)> = Vec::new();
+    for entry in entries {
+        if products.len() >= max {
+            break;
+        }
+        let Some(product_id) = scraper.product_id(&entry.loc) else { continue };
+        if products.iter().any(|(known, _, _)| *known == product_id) {
+            continue;
+        }
+        products.push((product_id, desktop_url(&entry.loc), entry.priority));
+    }
+    products
+}
+
+/// A sitemap's XML; `.xml.gz` sitemaps are unpacked
+async fn fetch_sitemap(url: &str) -> Result<String> {
+    let response = crate::http_client::shared().get(url).send().await?.error_for_status()?;
+    let encoding = response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let bytes = response.bytes().await?;
+    let xml = body::decompress(encoding.as_deref(), &bytes)?;
+    Ok(String::from_utf8_lossy(&xml).into_owned())
+}
+
+/// Read a source's sitemap (and, for an index, its first `max_sitemaps` sitemaps) and make
+/// the products found the ones checked for it. Returns how many were kept.
+pub async fn discover(db: &Database, config: &DiscoveryConfig, source: &SitemapSource) -> Result<usize> {
+    // Links are told apart by the platform's URL patterns, whichever backend fetches them
+    let scraper = create_scraper(&source.platform)
+        .ok_or_else(|| anyhow!("No scraper for platform '{}'", source.platform))?;
+
+    let entries = match parse_sitemap(&fetch_sitemap(&source.url).await?) {
+        Sitemap::Urls(entries) => entries,
+        Sitemap::Index(sitemaps) => {
+            let mut entries = Vec::new();
+            for sitemap in sitemaps.iter().take(config.max_sitemaps) {
+                tokio::time::sleep(config.request_gap).await;
+                // Nested indexes aren't followed
+                match fetch_sitemap(sitemap).await.map(|xml| parse_sitemap(&xml)) {
+                    Ok(Sitemap::Urls(found)) => entries.extend(found),
+                    Ok(Sitemap::Index(_)) => tracing::debug!("Skipping nested sitemap index {}", sitemap),
+                    Err(e) => tracing::warn!("Failed to read sitemap {}: {}", sitemap, e),
+                }
+            }
+            entries
+        }
+    };
+
+    let products = select_products(&entries, scraper.as_ref(), &source.platform, source.max_products.max(0) as usize);
+    db.save_catalog_products(source.id, &source.platform, &products).await?;
+    Ok(products.len())
+}
+
+/// `discover` with the outcome recorded on the source
+pub async fn run_source(db: &Database, config: &DiscoveryConfig, source: &SitemapSource) -> Result<usize> {
+    let outcome = discover(db, config, source).await;
+    let error = outcome.as_ref().err().map(|e| e.to_string());
+    db.finish_sitemap_run(source.id, outcome.as_ref().ok().copied(), error.as_deref()).await?;
+    outcome
+}
+
+/// Check a batch of catalog products that are due, skipping platforms that are backing
+/// off after rate limiting us. Returns how many prices were saved.
+pub async fn check_due(db: &Database, scraper_configs: &ScraperConfigStore, config: &DiscoveryConfig) -> Result<usize> {
+    let before = chrono::Utc::now() - chrono::Duration::from_std(config.check_interval).unwrap_or_default();
+    let backing_off: Vec<String> = scraper_configs.backoff().active().into_iter().map(|b| b.platform).collect();
+    let products = db.claim_catalog_checks(config.batch, before, &backing_off).await?;
+
+    let mut saved = 0;
+    for product in &products {
+        let Some(scraper) = scraper_configs.scraper(&product.platform) else { continue };
+        match scraper.get_product(&product.url).await {
+            Ok(snapshot) => {
+                db.save_catalog_price(product.id, snapshot.selling_price, snapshot.mrp).await?;
+                saved += 1;
+            }
+            Err(e) => {
+                tracing::debug!("Failed to check catalog product {}: {}", product.url, e);
+                db.save_catalog_error(product.id, &e.to_string()).await?;
+            }
+        }
+        tokio::time::sleep(config.request_gap).await;
+    }
+    Ok(saved)
+}
+
+/// Read due sitemaps, then check a batch of products, every few minutes for as long as
+/// the process runs
+pub fn spawn(db: Database, scraper_configs: ScraperConfigStore, config: DiscoveryConfig) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            let before = chrono::Utc::now() - chrono::Duration::from_std(config.sitemap_refresh).unwrap_or_default();
+            match db.claim_due_sitemaps(before).await {
+                Ok(sources) => {
+                    for source in &sources {
+                        match run_source(&db, &config, source).await {
+                            Ok(count) => tracing::info!("🗺️ Found {} product(s) in {}", count, source.url),
+                            Err(e) => tracing::warn!("Failed to read sitemap {}: {}", source.url, e),
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("Failed to claim sitemaps: {}", e),
+            }
+            match check_due(&db, &scraper_configs, &config).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("🗺️ Checked {} catalog product(s)", count),
+                Err(e) => tracing::error!("Failed to check catalog products: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sitemap() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+              <url><loc>https://www.myntra.com/tshirts/roadster/12345678/buy</loc><priority>0.8</priority><lastmod>2026-10-01</lastmod></url>
+              <url><loc><![CDATA[https://www.myntra.com/men-tshirts?p=2&amp;sort=new]]></loc></url>
+              <url><priority>0.9</priority></url>
+            </urlset>"#;
+        let Sitemap::Urls(entries) = parse_sitemap(xml) else { panic!("not a urlset") };
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].priority, entries[0].lastmod.as_deref()), (0.8, Some("2026-10-01")));
+        assert_eq!((entries[1].loc.as_str(), entries[1].priority), ("https://www.myntra.com/men-tshirts?p=2&sort=new", 0.5));
+
+        let index = r#"<sitemapindex><sitemap><loc>https://www.myntra.com/sitemap-men.xml.gz</loc></sitemap></sitemapindex>"#;
+        assert_eq!(parse_sitemap(index), Sitemap::Index(vec!["https://www.myntra.com/sitemap-men.xml.gz".to_string()]));
+    }
+
+    #[test]
+    fn test_select_products() {
+        let entry = |loc: &str, priority: f64| SitemapEntry { loc: loc.to_string(), priority, lastmod: None };
+        let entries = [
+            entry("https://www.myntra.com/tshirts/roadster/11111111/buy", 0.4),
+            entry("https://www.myntra.com/men-tshirts", 1.0),
+            entry("https://www.myntra.com/22222222", 0.9),
+            entry("https://www.myntra.com/shirts/hrx/22222222/buy", 0.8),
+            entry("https://www.flipkart.com/shirt/p/itm0123456789abc", 1.0),
+            entry("https://www.myntra.com/33333333", 0.6),
+        ];
+        let scraper = create_scraper("myntra").unwrap();
+
+        let products = select_products(&entries, scraper.as_ref(), "myntra", 2);
+        let ids: Vec<&str> = products.iter().map(|(id, _, _)| id.as_str()).collect();
+        assert_eq!(ids, ["22222222", "33333333"]);
+        assert_eq!(select_products(&entries, scraper.as_ref(), "myntra", 10).len(), 3);
+    }
+}
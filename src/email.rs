@@ -1,32 +1,100 @@
-use anyhow::{Result, Context};
+use anyhow::{Result, Context, anyhow, bail};
+use chrono::Utc;
+use chrono_tz::Tz;
 use lettre::{
-    Message, SmtpTransport, Transport,
-    message::{header::ContentType, Mailbox},
-    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{header::{ContentType, HeaderName, HeaderValue}, Mailbox},
+    transport::smtp::{authentication::Credentials, PoolConfig},
 };
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use crate::db::Database;
+use crate::email_api::EmailApi;
+use crate::metrics::{classify_delivery_error, is_bounce, is_retryable_delivery_error, DeliveryOutcome};
+use crate::models::{effective_price, ListingItem, Offer, PriceBadge, SummaryDrop, SummaryFailingAlert, SummaryNearTarget, WeeklySummary};
+use crate::notifiers::record_delivery;
+use crate::notifier_trait::{BackInStock, KeywordMatch, NotificationEvent, PriceDrop};
+use crate::timezone::format_local;
 
+/// A rendered email, ready to be sent directly or queued
+#[derive(Debug, Clone)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub html_body: String,
+    /// Sent as `List-Unsubscribe` so mail clients can offer one-click unsubscribe
+    pub unsubscribe_url: Option<String>,
+}
+
+impl OutgoingEmail {
+    /// Add unsubscribe links to the footer and headers. `alert_url` pauses
+    /// just the alert the email is about; `account_url` pauses everything.
+    pub fn with_unsubscribe(mut self, alert_url: Option<String>, account_url: Option<String>) -> Self {
+        let mut links = Vec::new();
+        if let Some(url) = &alert_url {
+            links.push(format!(r#"<a href="{}" style="color: #6b7280;">Pause this alert</a>"#, escape_html(url)));
+        }
+        if let Some(url) = &account_url {
+            links.push(format!(r#"<a href="{}" style="color: #6b7280;">Pause all notifications</a>"#, escape_html(url)));
+        }
+        if links.is_empty() {
+            return self;
+        }
+
+        let footer = format!(
+            r#"<p style="text-align: center; color: #6b7280; font-size: 12px;">{}</p>
+</body>"#,
+            links.join(" · ")
+        );
+        self.html_body = self.html_body.replacen("</body>", &footer, 1);
+        self.unsubscribe_url = alert_url.or(account_url);
+        self
+    }
+}
+
+/// Templates `EmailService::preview` can render
+pub const PREVIEW_TEMPLATES: &[&str] = &[
+    "price_drop",
+    "back_in_stock",
+    "product_removed",
+    "listing_match",
+    "keyword_match",
+    "account_locked",
+    "verify_email",
+    "alert_created",
+    "inbound_rejected",
+    "digest",
+    "weekly_summary",
+];
+
+#[derive(Clone)]
 pub struct EmailService {
-    smtp_username: String,
-    smtp_password: String,
-    smtp_server: String,
-    smtp_port: u16,
+    transport: Transport,
     from_email: String,
     from_name: String,
 }
 
+/// How messages leave the server, chosen with `EMAIL_PROVIDER`
+#[derive(Clone)]
+enum Transport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    Api(EmailApi),
+}
+
 impl EmailService {
+    /// SMTP by default; `EMAIL_PROVIDER=sendgrid|resend|ses` sends through that provider's HTTP API
     pub fn from_env() -> Result<Self> {
+        let provider = std::env::var("EMAIL_PROVIDER")
+            .map(|p| p.trim().to_lowercase())
+            .unwrap_or_default();
+        let transport = match provider.as_str() {
+            "" | "smtp" => Transport::Smtp(Self::smtp_from_env()?),
+            api => Transport::Api(EmailApi::from_env(api)?),
+        };
+        
         Ok(EmailService {
-            smtp_username: std::env::var("SMTP_USERNAME")
-                .context("SMTP_USERNAME not set in environment")?,
-            smtp_password: std::env::var("SMTP_PASSWORD")
-                .context("SMTP_PASSWORD not set in environment")?,
-            smtp_server: std::env::var("SMTP_SERVER")
-                .unwrap_or_else(|_| "smtp.gmail.com".to_string()),
-            smtp_port: std::env::var("SMTP_PORT")
-                .unwrap_or_else(|_| "587".to_string())
-                .parse()
-                .unwrap_or(587),
+            transport,
             from_email: std::env::var("FROM_EMAIL")
                 .context("FROM_EMAIL not set in environment")?,
             from_name: std::env::var("FROM_NAME")
@@ -34,14 +102,45 @@ impl EmailService {
         })
     }
 
-    pub async fn send_price_drop_alert(
-        &self,
-        to_email: &str,
-        product_url: &str,
-        current_price: f64,
-        target_price: f64,
-        platform: &str,
-    ) -> Result<()> {
+    /// Name of the transport in use, for the startup log
+    pub fn transport_name(&self) -> &'static str {
+        match &self.transport {
+            Transport::Smtp(_) => "smtp",
+            Transport::Api(api) => api.name(),
+        }
+    }
+
+    fn smtp_from_env() -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+        let smtp_username = std::env::var("SMTP_USERNAME")
+            .context("SMTP_USERNAME not set in environment")?;
+        let smtp_password = std::env::var("SMTP_PASSWORD")
+            .context("SMTP_PASSWORD not set in environment")?;
+        let smtp_server = std::env::var("SMTP_SERVER")
+            .unwrap_or_else(|_| "smtp.gmail.com".to_string());
+        let smtp_port = std::env::var("SMTP_PORT")
+            .unwrap_or_else(|_| "587".to_string())
+            .parse()
+            .unwrap_or(587);
+        let pool_size = env_or("SMTP_POOL_SIZE", 4);
+        
+        // One pooled transport per service so connections are reused across sends
+        Ok(AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp_server)
+            .context("Failed to create SMTP transport")?
+            .credentials(Credentials::new(smtp_username, smtp_password))
+            .port(smtp_port)
+            .pool_config(PoolConfig::new().max_size(pool_size))
+            .build())
+    }
+
+    pub async fn send_price_drop_alert(&self, to_email: &str, drop: &PriceDrop, timezone: Tz) -> Result<()> {
+        self.send(&Self::price_drop_alert(to_email, drop, &format_local(Utc::now(), timezone))).await
+    }
+
+    /// Render the price drop notification without sending it.
+    /// `checked_at` is the check time, already in the recipient's timezone.
+    pub fn price_drop_alert(to_email: &str, drop: &PriceDrop, checked_at: &str) -> OutgoingEmail {
+        let (current_price, target_price) = (drop.current_price, drop.target_price);
+        let (platform, product_url) = (drop.platform.as_str(), drop.url.as_str());
         let savings = target_price - current_price;
         let discount_percent = ((target_price - current_price) / target_price * 100.0).round();
         
@@ -66,6 +165,7 @@ impl EmailService {
         .savings {{ background: #10b981; color: white; padding: 8px 16px; border-radius: 6px; display: inline-block; margin: 10px 0; }}
         .button {{ background: #6366f1; color: white; padding: 14px 28px; text-decoration: none; border-radius: 8px; display: inline-block; margin: 20px 0; font-weight: 600; }}
         .button:hover {{ background: #4f46e5; }}
+        .badge {{ background: #f59e0b; color: white; padding: 6px 14px; border-radius: 6px; display: inline-block; margin: 0 0 10px 0; font-weight: 600; }}
         .platform {{ background: #ec4899; color: white; padding: 4px 12px; border-radius: 20px; font-size: 12px; font-weight: 600; }}
         .footer {{ text-align: center; padding: 20px; color: #6b7280; font-size: 14px; }}
     </style>
@@ -87,8 +187,9 @@ impl EmailService {
                     <div class="old-price">Was: ₹{:.2}</div>
                     <div class="price">Now: ₹{:.2}</div>
                     <div class="savings">Save ₹{:.0} ({}% OFF)</div>
+                    {}
                 </div>
-                
+                {}
                 <p><strong>Product URL:</strong><br>
                 <a href="{}" style="color: #6366f1; word-break: break-all;">{}</a></p>
                 
@@ -102,6 +203,7 @@ impl EmailService {
         
         <div class="footer">
             <p>This alert was sent because the price dropped to or below your target of ₹{:.2}</p>
+            <p>Price checked {}.</p>
             <p>You're receiving this because you set up a price alert at our service.</p>
             <p style="font-size: 12px; color: #9ca3af;">Clothing Price Tracker • Powered by Rust</p>
         </div>
@@ -113,51 +215,627 @@ impl EmailService {
             current_price,
             savings,
             discount_percent,
+            drop.badge
+                .map(|badge| format!(r#"<div class="badge">🏆 {}</div>"#, badge.label()))
+                .unwrap_or_default(),
+            offers_section(current_price, &drop.offers),
             product_url,
             product_url,
             product_url,
-            target_price
+            target_price,
+            checked_at
         );
 
-        self.send_html_email(to_email, &subject, &body).await
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject,
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+
+    /// Render the restock notification for a `back_in_stock` alert
+    pub fn back_in_stock_alert(
+        to_email: &str,
+        product_url: &str,
+        current_price: Option<f64>,
+        platform: &str,
+        checked_at: &str,
+    ) -> OutgoingEmail {
+        let subject = format!("📦 Back in stock on {}!", platform.to_uppercase());
+        let price_line = current_price
+            .map(|price| format!(r#"<p style="font-size: 24px; font-weight: bold; color: #10b981;">₹{:.0}</p>"#, price))
+            .unwrap_or_default();
+        
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>📦 It's back!</h2>
+        <p>An item you were waiting for on {} is available again:</p>
+        {}
+        <p><a href="{}" style="color: #6366f1; word-break: break-all;">{}</a></p>
+        <p><a href="{}" style="background: #6366f1; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block;">🛍️ View Product Now</a></p>
+        <p style="color: #6b7280; font-size: 14px;">Seen in stock {}. Popular sizes sell out quickly after a restock.</p>
+    </div>
+</body>
+</html>"#,
+            platform.to_uppercase(),
+            price_line,
+            escape_html(product_url),
+            escape_html(product_url),
+            escape_html(product_url),
+            checked_at
+        );
+        
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject,
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+    
+    /// Render the notice that an alert's product page is gone and checks have stopped
+    pub fn product_removed_alert(
+        to_email: &str,
+        product_url: &str,
+        last_price: Option<f64>,
+        platform: &str,
+        checked_at: &str,
+    ) -> OutgoingEmail {
+        let subject = format!("🚫 An item you're tracking was removed from {}", platform.to_uppercase());
+        let price_line = last_price
+            .map(|price| format!("<p>It was last seen at <strong>₹{:.0}</strong>.</p>", price))
+            .unwrap_or_default();
+        
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>🚫 Product page not found</h2>
+        <p>{} keeps answering "page not found" for an item you're tracking:</p>
+        <p><a href="{}" style="color: #6366f1; word-break: break-all;">{}</a></p>
+        {}
+        <p>We've stopped checking it. If the product moved to a new link, update the alert's URL and tracking picks up again; otherwise you can delete the alert.</p>
+        <p style="color: #6b7280; font-size: 14px;">Last checked {}.</p>
+    </div>
+</body>
+</html>"#,
+            platform.to_uppercase(),
+            escape_html(product_url),
+            escape_html(product_url),
+            price_line,
+            checked_at
+        );
+        
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject,
+            html_body: body,
+            unsubscribe_url: None,
+        }
     }
+    
+    /// Render the "new results under target" email for a watched search page
+    pub fn listing_match_alert(
+        to_email: &str,
+        search_url: &str,
+        items: &[ListingItem],
+        target_price: f64,
+        platform: &str,
+        checked_at: &str,
+    ) -> OutgoingEmail {
+        let subject = format!(
+            "🔎 {} new result(s) under ₹{:.0} on {}",
+            items.len(),
+            target_price,
+            platform.to_uppercase()
+        );
+        
+        let intro = format!("These items from your saved {} search are now at or below your price:", platform.to_uppercase());
+        
+        Self::results_email(to_email, subject, &intro, search_url, items, target_price, checked_at)
+    }
+    
+    /// Render the email for new keyword watch results on one platform
+    pub fn keyword_match_alert(
+        to_email: &str,
+        query: &str,
+        search_url: &str,
+        items: &[ListingItem],
+        target_price: f64,
+        platform: &str,
+        checked_at: &str,
+    ) -> OutgoingEmail {
+        let subject = format!(
+            "🔎 \"{}\" is under ₹{:.0} on {}",
+            query,
+            target_price,
+            platform.to_uppercase()
+        );
+        let intro = format!(
+            "We found <strong>{}</strong> on {} at or below your price:",
+            escape_html(query),
+            platform.to_uppercase()
+        );
+        
+        Self::results_email(to_email, subject, &intro, search_url, items, target_price, checked_at)
+    }
+    
+    /// Shared layout for listing and keyword results: a table of items plus a link to the search
+    fn results_email(
+        to_email: &str,
+        subject: String,
+        intro_html: &str,
+        search_url: &str,
+        items: &[ListingItem],
+        target_price: f64,
+        checked_at: &str,
+    ) -> OutgoingEmail {
+        let rows: String = items
+            .iter()
+            .map(|item| {
+                format!(
+                    r#"<tr><td style="padding: 8px 0;"><a href="{}" style="color: #6366f1;">{}</a></td><td style="padding: 8px 0; text-align: right; font-weight: bold; color: #10b981;">₹{:.0}</td></tr>"#,
+                    escape_html(&item.url),
+                    escape_html(&item.title),
+                    item.price
+                )
+            })
+            .collect();
+        
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>🔎 New results under your target of ₹{:.0}</h2>
+        <p>{}</p>
+        <table style="width: 100%; border-collapse: collapse;">{}</table>
+        <p><a href="{}" style="background: #6366f1; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block;">Open the search</a></p>
+        <p style="color: #6b7280; font-size: 14px;">Found {}. You'll only hear about each item once.</p>
+    </div>
+</body>
+</html>"#,
+            target_price,
+            intro_html,
+            rows,
+            escape_html(search_url),
+            checked_at
+        );
+        
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject,
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+
+    /// Notifications held back by the daily email budget, one line each
+    pub fn digest(to_email: &str, events: &[NotificationEvent], sent_at: &str) -> OutgoingEmail {
+        let link = |url: &str, text: &str| format!(r#"<a href="{}" style="color: #6366f1;">{}</a>"#, escape_html(url), escape_html(text));
+        let rows: String = events
+            .iter()
+            .map(|event| {
+                let (what, price) = match event {
+                    NotificationEvent::PriceDrop(drop) => (
+                        format!("{} dropped below ₹{:.0} on {}", link(&drop.url, "An item"), drop.target_price, drop.platform.to_uppercase()),
+                        Some(drop.current_price),
+                    ),
+                    NotificationEvent::BackInStock(restock) => (
+                        format!("{} is back in stock on {}", link(&restock.url, "An item"), restock.platform.to_uppercase()),
+                        restock.current_price,
+                    ),
+                    NotificationEvent::ProductRemoved(removed) => (
+                        format!("{} was removed from {} and is no longer checked", link(&removed.url, "An item"), removed.platform.to_uppercase()),
+                        None,
+                    ),
+                    NotificationEvent::ListingMatch(listing) => (
+                        format!("{} new result(s) under ₹{:.0} on {}", listing.items.len(), listing.target_price, link(&listing.url, &listing.platform.to_uppercase())),
+                        listing.items.iter().map(|item| item.price).reduce(f64::min),
+                    ),
+                    NotificationEvent::KeywordMatch(keyword) => (
+                        format!("{} new result(s) for {} on {}", keyword.items.len(), link(&keyword.search_url, &format!("\"{}\"", keyword.query)), keyword.platform.to_uppercase()),
+                        keyword.items.iter().map(|item| item.price).reduce(f64::min),
+                    ),
+                };
+                format!(
+                    r#"<tr><td style="padding: 8px 0;">{}</td><td style="padding: 8px 0; text-align: right; font-weight: bold; color: #10b981;">{}</td></tr>"#,
+                    what,
+                    price.map(|price| format!("₹{:.0}", price)).unwrap_or_default()
+                )
+            })
+            .collect();
+        
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>📬 Your price tracker digest</h2>
+        <p>You reached today's email limit, so these were saved for one summary instead of separate emails:</p>
+        <table style="width: 100%; border-collapse: collapse;">{}</table>
+        <p style="color: #6b7280; font-size: 14px;">Sent {}. Prices may have changed since.</p>
+    </div>
+</body>
+</html>"#,
+            rows,
+            sent_at
+        );
+        
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject: format!("📬 {} price update(s) you missed", events.len()),
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+    
+    /// The week's highlights for users who opted in: biggest price falls, alerts closest
+    /// to their target, and alerts whose checks keep failing. Empty sections are left out.
+    pub fn weekly_summary(to_email: &str, summary: &WeeklySummary, sent_at: &str) -> OutgoingEmail {
+        let link = |url: &str, platform: &str| {
+            format!(r#"<a href="{}" style="color: #6366f1;">{}</a>"#, escape_html(url), escape_html(&platform.to_uppercase()))
+        };
+        let row = |what: String, detail: String| {
+            format!(
+                r#"<tr><td style="padding: 6px 0;">{}</td><td style="padding: 6px 0; text-align: right;">{}</td></tr>"#,
+                what, detail
+            )
+        };
+        let section = |title: &str, rows: Vec<String>| {
+            if rows.is_empty() {
+                return String::new();
+            }
+            format!(
+                r#"<h3 style="margin-bottom: 4px;">{}</h3><table style="width: 100%; border-collapse: collapse;">{}</table>"#,
+                title,
+                rows.concat()
+            )
+        };
+        
+        let drops = summary.biggest_drops.iter().map(|drop| {
+            let met = if drop.drop_events > 0 { " · target met" } else { "" };
+            row(
+                format!("Item on {}{}", link(&drop.url, &drop.platform), met),
+                format!(
+                    r#"₹{:.0} → <strong style="color: #10b981;">₹{:.0}</strong>"#,
+                    drop.start_price, drop.end_price
+                ),
+            )
+        });
+        let near = summary.nearest_target.iter().map(|alert| {
+            row(
+                format!("Item on {}", link(&alert.url, &alert.platform)),
+                format!("₹{:.0} now, ₹{:.0} to go to ₹{:.0}", alert.last_price, alert.last_price - alert.target_price, alert.target_price),
+            )
+        });
+        let failing = summary.needs_attention.iter().map(|alert| {
+            let reason = alert.last_error.as_deref().map(|kind| kind.replace('_', " ")).unwrap_or_else(|| "unknown error".to_string());
+            row(
+                format!("Item on {}", link(&alert.url, &alert.platform)),
+                format!(r#"<span style="color: #ef4444;">{} failed check(s): {}</span>"#, alert.failures, escape_html(&reason)),
+            )
+        });
+        
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>📰 Your week in prices</h2>
+        {}{}{}
+        <p style="color: #6b7280; font-size: 14px;">Sent {}. Prices may have changed since.</p>
+    </div>
+</body>
+</html>"#,
+            section("📉 Biggest drops", drops.collect()),
+            section("🎯 Closest to target", near.collect()),
+            section("⚠️ Needs attention", failing.collect()),
+            sent_at
+        );
+        
+        let subject = match summary.biggest_drops.first() {
+            Some(drop) => format!("📰 Your week in prices: ₹{:.0} off on {}", drop.start_price - drop.end_price, drop.platform.to_uppercase()),
+            None => "📰 Your week in prices".to_string(),
+        };
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject,
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+    
+    /// Sent when repeated failed logins lock an account
+    pub fn account_locked(to_email: &str, unlock_url: &str, locked_until: &str) -> OutgoingEmail {
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>🔒 Your account has been temporarily locked</h2>
+        <p>We noticed several failed sign-in attempts on your Price Tracker account, so we've locked it until {} as a precaution.</p>
+        <p>If this was you, you can unlock it right away:</p>
+        <p><a href="{}" style="background: #6366f1; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block;">Unlock my account</a></p>
+        <p style="color: #6b7280; font-size: 14px;">If this wasn't you, consider changing your password once you're back in.</p>
+    </div>
+</body>
+</html>"#,
+            locked_until, unlock_url
+        );
 
-    async fn send_html_email(&self, to_email: &str, subject: &str, html_body: &str) -> Result<()> {
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject: "🔒 Price Tracker account locked".to_string(),
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+
+    /// Sent at signup (and on request) to confirm the address belongs to the account holder
+    pub fn verify_email(to_email: &str, verify_url: &str) -> OutgoingEmail {
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>✉️ Confirm your email address</h2>
+        <p>Thanks for signing up to Price Tracker. Confirm this is your address to finish setting up your account:</p>
+        <p><a href="{}" style="background: #6366f1; color: white; padding: 12px 24px; text-decoration: none; border-radius: 8px; display: inline-block;">Confirm my email</a></p>
+        <p style="color: #6b7280; font-size: 14px;">If you didn't create an account, you can ignore this email.</p>
+    </div>
+</body>
+</html>"#,
+            verify_url
+        );
+
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject: "✉️ Confirm your Price Tracker email".to_string(),
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+
+    /// Reply to a forwarded product link that became an alert
+    pub fn alert_created_by_email(to_email: &str, product_url: &str, target_price: f64) -> OutgoingEmail {
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>✅ Tracking started</h2>
+        <p>We'll let you know when this drops to ₹{:.0} or less:</p>
+        <p><a href="{}" style="color: #6366f1; word-break: break-all;">{}</a></p>
+        <p style="color: #6b7280; font-size: 14px;">Change the target any time from your alerts, or forward the link again with "under 999" (your price) in the subject.</p>
+    </div>
+</body>
+</html>"#,
+            target_price,
+            escape_html(product_url),
+            escape_html(product_url)
+        );
+
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject: "✅ Price alert created".to_string(),
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+
+    /// Reply to a forwarded email that couldn't become an alert
+    pub fn inbound_email_rejected(to_email: &str, reason: &str) -> OutgoingEmail {
+        let body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<body style="font-family: Arial, sans-serif; line-height: 1.6; color: #333;">
+    <div style="max-width: 600px; margin: 0 auto; padding: 20px;">
+        <h2>⚠️ We couldn't create that alert</h2>
+        <p>{}</p>
+        <p style="color: #6b7280; font-size: 14px;">Forward a product page from Myntra, Flipkart, Ajio, Tata Cliq, Shoppers Stop or Lifestyle, optionally with "under 999" (your price) in the subject.</p>
+    </div>
+</body>
+</html>"#,
+            escape_html(reason)
+        );
+
+        OutgoingEmail {
+            to: to_email.to_string(),
+            subject: "⚠️ Price alert not created".to_string(),
+            html_body: body,
+            unsubscribe_url: None,
+        }
+    }
+
+    /// Render `template` (one of `PREVIEW_TEMPLATES`) with sample data instead of sending it.
+    /// `params` override the samples: `to`, `url`, `platform`, `current_price`, `target_price`,
+    /// `checked_at`, `badge`, `query`, `reason`, plus `offers=true` and `unsubscribe=true`
+    /// to include those sections.
+    pub fn preview(template: &str, params: &HashMap<String, String>) -> Result<OutgoingEmail> {
+        let param = |key: &str, default: &str| params.get(key).map(String::as_str).unwrap_or(default).to_string();
+        let price = |key: &str, default: f64| -> Result<f64> {
+            match params.get(key) {
+                Some(value) => value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|price| price.is_finite() && *price > 0.0)
+                    .ok_or_else(|| anyhow!("{} must be a positive number", key)),
+                None => Ok(default),
+            }
+        };
+        let flag = |key: &str| params.get(key).is_some_and(|v| matches!(v.as_str(), "true" | "1" | "yes"));
+
+        let to = param("to", "buyer@example.com");
+        let url = param("url", "https://www.myntra.com/shirts/roadster/12345678/buy");
+        let platform = param("platform", "myntra");
+        let checked_at = param("checked_at", &format_local(Utc::now(), Tz::Asia__Kolkata));
+        let (current_price, target_price) = (price("current_price", 799.0)?, price("target_price", 999.0)?);
+        let items = vec![
+            ListingItem { title: "Roadster Men Black Casual Shirt".to_string(), url: url.clone(), price: current_price, location: None },
+            ListingItem {
+                title: "HRX Men Olive Slim Fit Shirt".to_string(),
+                url: "https://www.myntra.com/shirts/hrx/87654321/buy".to_string(),
+                price: (current_price * 0.9).round(),
+                location: Some("Andheri West, Mumbai".to_string()),
+            },
+        ];
+
+        let email = match template {
+            "price_drop" => {
+                let badge = match params.get("badge") {
+                    Some(badge) => Some(PriceBadge::parse(badge).ok_or_else(|| anyhow!("badge must be lowest_ever or lowest_90_days"))?),
+                    None => None,
+                };
+                let offers = if flag("offers") {
+                    vec![
+                        Offer { kind: "coupon".into(), description: "Extra ₹50 off".into(), code: Some("SAVE50".into()), discount: Some(50.0) },
+                        Offer { kind: "bank".into(), description: "10% instant discount on HDFC cards".into(), code: None, discount: None },
+                    ]
+                } else {
+                    Vec::new()
+                };
+                let drop = PriceDrop {
+                    alert_id: None,
+                    user_id: None,
+                    user_email: to.clone(),
+                    url,
+                    platform,
+                    current_price,
+                    target_price,
+                    previous_price: None,
+                    offers,
+                    badge,
+                    product_name: None,
+                    image_url: None,
+                };
+                Self::price_drop_alert(&to, &drop, &checked_at)
+            }
+            "back_in_stock" => Self::back_in_stock_alert(&to, &url, Some(current_price), &platform, &checked_at),
+            "product_removed" => Self::product_removed_alert(&to, &url, Some(current_price), &platform, &checked_at),
+            "listing_match" => Self::listing_match_alert(&to, &url, &items, target_price, &platform, &checked_at),
+            "keyword_match" => {
+                let query = param("query", "black casual shirt");
+                Self::keyword_match_alert(&to, &query, &url, &items, target_price, &platform, &checked_at)
+            }
+            "account_locked" => Self::account_locked(&to, &format!("{}/auth/unlock?token=sample", app_base_url()), &checked_at),
+            "verify_email" => Self::verify_email(&to, &format!("{}/auth/verify?token=sample", app_base_url())),
+            "alert_created" => Self::alert_created_by_email(&to, &url, target_price),
+            "inbound_rejected" => Self::inbound_email_rejected(&to, &param("reason", "We couldn't find a supported product link in your email.")),
+            "digest" => {
+                let restock = BackInStock {
+                    alert_id: None,
+                    user_id: None,
+                    user_email: to.clone(),
+                    url: url.clone(),
+                    platform: platform.clone(),
+                    current_price: Some(current_price),
+                };
+                let keyword = KeywordMatch {
+                    watch_id: uuid::Uuid::nil(),
+                    user_id: uuid::Uuid::nil(),
+                    user_email: to.clone(),
+                    query: param("query", "black casual shirt"),
+                    platform,
+                    search_url: url,
+                    target_price,
+                    items,
+                };
+                Self::digest(&to, &[NotificationEvent::BackInStock(restock), NotificationEvent::KeywordMatch(keyword)], &checked_at)
+            }
+            "weekly_summary" => {
+                let summary = WeeklySummary {
+                    since: Utc::now() - chrono::Duration::days(7),
+                    biggest_drops: vec![SummaryDrop {
+                        alert_id: uuid::Uuid::nil(),
+                        url: url.clone(),
+                        platform: platform.clone(),
+                        target_price,
+                        start_price: (current_price * 1.25).round(),
+                        end_price: current_price,
+                        drop_events: 1,
+                    }],
+                    nearest_target: vec![SummaryNearTarget {
+                        alert_id: uuid::Uuid::nil(),
+                        url: items[1].url.clone(),
+                        platform: platform.clone(),
+                        last_price: (target_price * 1.1).round(),
+                        target_price,
+                    }],
+                    needs_attention: vec![SummaryFailingAlert {
+                        alert_id: uuid::Uuid::nil(),
+                        url: "https://www.flipkart.com/levis-jeans/p/itm0000000000001".to_string(),
+                        platform: "flipkart".to_string(),
+                        failures: 4,
+                        last_error: Some("not_found".to_string()),
+                    }],
+                };
+                Self::weekly_summary(&to, &summary, &checked_at)
+            }
+            other => bail!("Unknown template '{}'; expected one of {}", other, PREVIEW_TEMPLATES.join(", ")),
+        };
+
+        Ok(if flag("unsubscribe") {
+            let link = format!("{}/unsubscribe?token=sample", app_base_url());
+            email.with_unsubscribe(Some(link.clone()), Some(link))
+        } else {
+            email
+        })
+    }
+
+    pub async fn send(&self, email: &OutgoingEmail) -> Result<()> {
         let from_mailbox: Mailbox = format!("{} <{}>", self.from_name, self.from_email)
             .parse()
             .context("Invalid from email address")?;
         
-        let to_mailbox: Mailbox = to_email
+        let to_mailbox: Mailbox = email.to
             .parse()
             .context("Invalid recipient email address")?;
+        
+        match &self.transport {
+            Transport::Smtp(mailer) => Self::send_smtp(mailer, from_mailbox, to_mailbox, email).await?,
+            Transport::Api(api) => api.send(&from_mailbox.to_string(), email).await?,
+        }
+        
+        tracing::info!("📧 Email sent successfully to {} via {}", email.to, self.transport_name());
+        Ok(())
+    }
 
-        let email = Message::builder()
+    async fn send_smtp(
+        mailer: &AsyncSmtpTransport<Tokio1Executor>,
+        from_mailbox: Mailbox,
+        to_mailbox: Mailbox,
+        email: &OutgoingEmail,
+    ) -> Result<()> {
+        let mut builder = Message::builder()
             .from(from_mailbox)
             .to(to_mailbox)
-            .subject(subject)
-            .header(ContentType::TEXT_HTML)
-            .body(html_body.to_string())
+            .subject(&email.subject)
+            .header(ContentType::TEXT_HTML);
+        
+        // RFC 8058 one-click unsubscribe: mail clients POST to the link directly
+        if let Some(url) = &email.unsubscribe_url {
+            builder = builder
+                .raw_header(HeaderValue::new(HeaderName::new_from_ascii_str("List-Unsubscribe"), format!("<{}>", url)))
+                .raw_header(HeaderValue::new(
+                    HeaderName::new_from_ascii_str("List-Unsubscribe-Post"),
+                    "List-Unsubscribe=One-Click".to_string(),
+                ));
+        }
+        
+        let message = builder
+            .body(email.html_body.clone())
             .context("Failed to build email message")?;
 
-        let creds = Credentials::new(
-            self.smtp_username.clone(),
-            self.smtp_password.clone(),
-        );
-
-        let mailer = SmtpTransport::relay(&self.smtp_server)
-            .context("Failed to create SMTP transport")?
-            .credentials(creds)
-            .port(self.smtp_port)
-            .build();
-
-        // Send email in a blocking thread to avoid blocking the async runtime
-        let result = tokio::task::spawn_blocking(move || mailer.send(&email))
+        mailer
+            .send(message)
             .await
-            .context("Failed to spawn email sending task")?;
-
-        result.context("Failed to send email")?;
-
-        tracing::info!("📧 Email sent successfully to {}", to_email);
+            .context("Failed to send email")?;
         Ok(())
     }
 
@@ -200,6 +878,302 @@ impl EmailService {
 </body>
 </html>"#;
 
-        self.send_html_email(to_email, subject, body).await
+        self.send(&OutgoingEmail {
+            to: to_email.to_string(),
+            subject: subject.to_string(),
+            html_body: body.to_string(),
+            unsubscribe_url: None,
+        }).await
+    }
+}
+
+struct QueuedEmail {
+    email: OutgoingEmail,
+    attempt: u32,
+}
+
+/// Handle to the background sender; cheap to clone and share between the API and worker
+#[derive(Clone)]
+pub struct EmailQueue {
+    sender: mpsc::Sender<QueuedEmail>,
+}
+
+impl EmailQueue {
+    /// Start the queue consumer on the current runtime; each attempt's outcome is
+    /// recorded in `db`'s `notification_deliveries`
+    pub fn spawn(service: EmailService, db: Option<Database>) -> Self {
+        let capacity = env_or("EMAIL_QUEUE_CAPACITY", 1000) as usize;
+        let max_retries = env_or("EMAIL_MAX_RETRIES", 3);
+        let per_minute = env_or("EMAIL_RATE_PER_MINUTE", 20).max(1);
+        
+        let (sender, receiver) = mpsc::channel(capacity);
+        tokio::spawn(run_queue(service, db, receiver, sender.downgrade(), max_retries, per_minute));
+        
+        EmailQueue { sender }
+    }
+
+    pub async fn enqueue(&self, email: OutgoingEmail) -> Result<()> {
+        self.sender
+            .send(QueuedEmail { email, attempt: 0 })
+            .await
+            .map_err(|_| anyhow!("Email queue is closed"))
+    }
+}
+
+async fn run_queue(
+    service: EmailService,
+    db: Option<Database>,
+    mut receiver: mpsc::Receiver<QueuedEmail>,
+    retry_sender: mpsc::WeakSender<QueuedEmail>,
+    max_retries: u32,
+    per_minute: u32,
+) {
+    // Space out sends so bursts of drops don't trip provider rate limits
+    let mut throttle = tokio::time::interval(Duration::from_secs(60) / per_minute);
+    throttle.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    
+    while let Some(mut queued) = receiver.recv().await {
+        throttle.tick().await;
+        
+        let Err(e) = service.send(&queued.email).await else {
+            record_delivery(db.as_ref(), "email", DeliveryOutcome::Sent, None).await;
+            continue;
+        };
+        
+        // Refusals and bad credentials fail the same way on every retry
+        let error_kind = classify_delivery_error(&e);
+        if queued.attempt >= max_retries || !is_retryable_delivery_error(error_kind) {
+            tracing::error!(
+                "Giving up on email to {} after {} attempt(s) ({}): {}",
+                queued.email.to,
+                queued.attempt + 1,
+                error_kind,
+                e
+            );
+            let outcome = if is_bounce(error_kind) { DeliveryOutcome::Bounced } else { DeliveryOutcome::Failed };
+            record_delivery(db.as_ref(), "email", outcome, Some(error_kind)).await;
+            continue;
+        }
+        
+        record_delivery(db.as_ref(), "email", DeliveryOutcome::Retried, Some(error_kind)).await;
+        queued.attempt += 1;
+        let delay = retry_delay(queued.attempt);
+        tracing::warn!(
+            "Email to {} failed (attempt {}), retrying in {:?}: {}",
+            queued.email.to,
+            queued.attempt,
+            delay,
+            e
+        );
+        
+        // Re-enqueue after the backoff without holding up the rest of the queue
+        if let Some(sender) = retry_sender.upgrade() {
+            tokio::spawn(async move {
+                tokio::time::sleep(delay).await;
+                let _ = sender.send(queued).await;
+            });
+        }
+    }
+}
+
+/// Exponential backoff: 10s, 20s, 40s, ... capped at 10 minutes
+fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(5 * 2u64.saturating_pow(attempt)).min(Duration::from_secs(600))
+}
+
+/// Public URL of this deployment, used for links in emails
+pub fn app_base_url() -> String {
+    std::env::var("APP_BASE_URL")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| "http://localhost:3000".to_string())
+}
+
+/// Offers block for the drop email, with the price after the best flat discount
+fn offers_section(current_price: f64, offers: &[Offer]) -> String {
+    if offers.is_empty() {
+        return String::new();
+    }
+
+    let items: String = offers
+        .iter()
+        .map(|offer| {
+            let code = offer
+                .code
+                .as_deref()
+                .map(|code| format!(" — use code <strong>{}</strong>", escape_html(code)))
+                .unwrap_or_default();
+            format!("<li>{}{}</li>", escape_html(&offer.description), code)
+        })
+        .collect();
+    let effective = effective_price(current_price, offers)
+        .map(|price| format!("<p><strong>Effective price with offers: ₹{:.2}</strong></p>", price))
+        .unwrap_or_default();
+
+    format!(
+        r#"<div style="margin: 20px 0;">
+                    <p><strong>🏷️ Offers on this product:</strong></p>
+                    <ul>{}</ul>
+                    {}
+                </div>"#,
+        items, effective
+    )
+}
+
+/// Scraped text (product titles, URLs) is interpolated into HTML bodies
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn env_or(key: &str, default: u32) -> u32 {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backoff() {
+        assert_eq!(retry_delay(1), Duration::from_secs(10));
+        assert_eq!(retry_delay(2), Duration::from_secs(20));
+        assert_eq!(retry_delay(3), Duration::from_secs(40));
+        assert_eq!(retry_delay(30), Duration::from_secs(600));
+    }
+
+    const CHECKED_AT: &str = "16 Oct 2026, 9:12 PM IST";
+
+    fn drop(platform: &str, url: &str, offers: Vec<Offer>, badge: Option<PriceBadge>) -> PriceDrop {
+        PriceDrop {
+            alert_id: None,
+            user_id: None,
+            user_email: "buyer@example.com".to_string(),
+            url: url.to_string(),
+            platform: platform.to_string(),
+            current_price: 799.0,
+            target_price: 999.0,
+            previous_price: None,
+            offers,
+            badge,
+            product_name: None,
+            image_url: None,
+        }
+    }
+
+    #[test]
+    fn test_unsubscribe_links() {
+        let email = EmailService::back_in_stock_alert("buyer@example.com", "https://www.ajio.com/p/1", None, "ajio", CHECKED_AT)
+            .with_unsubscribe(Some("https://pt.example/unsubscribe?token=a".to_string()), Some("https://pt.example/unsubscribe?token=b".to_string()));
+
+        assert!(email.html_body.contains(r#"<a href="https://pt.example/unsubscribe?token=a" style="color: #6b7280;">Pause this alert</a>"#));
+        assert!(email.html_body.contains("Pause all notifications"));
+        assert!(email.html_body.trim_end().ends_with("</body>\n</html>"));
+        assert_eq!(email.unsubscribe_url.as_deref(), Some("https://pt.example/unsubscribe?token=a"));
+
+        let plain = EmailService::account_locked("buyer@example.com", "https://pt.example/unlock", "noon").with_unsubscribe(None, None);
+        assert!(!plain.html_body.contains("unsubscribe"));
+        assert!(plain.unsubscribe_url.is_none());
+    }
+
+    #[test]
+    fn test_price_drop_alert_rendering() {
+        let email = EmailService::price_drop_alert("buyer@example.com", &drop("myntra", "https://www.myntra.com/shirts/12345", vec![], Some(PriceBadge::Lowest90Days)), CHECKED_AT);
+
+        assert_eq!(email.to, "buyer@example.com");
+        assert!(email.subject.contains("Save ₹200"));
+        assert!(email.subject.contains("MYNTRA"));
+        assert!(email.html_body.contains("https://www.myntra.com/shirts/12345"));
+        assert!(email.html_body.contains("Now: ₹799.00"));
+        assert!(!email.html_body.contains("Offers on this product"));
+        assert!(email.html_body.contains("🏆 Lowest price in 90 days"));
+        assert!(email.html_body.contains("Price checked 16 Oct 2026, 9:12 PM IST."));
+    }
+
+    #[test]
+    fn test_price_drop_alert_with_offers() {
+        let offers = vec![
+            Offer { kind: "coupon".into(), description: "Extra ₹50 off".into(), code: Some("SAVE50".into()), discount: Some(50.0) },
+            Offer { kind: "bank".into(), description: "10% off on <HDFC> cards".into(), code: None, discount: None },
+        ];
+        let email = EmailService::price_drop_alert("buyer@example.com", &drop("flipkart", "https://www.flipkart.com/shirt/p/itm6a3d8f7c9b2e1", offers, None), CHECKED_AT);
+
+        assert!(email.html_body.contains("use code <strong>SAVE50</strong>"));
+        assert!(email.html_body.contains("10% off on &lt;HDFC&gt; cards"));
+        assert!(email.html_body.contains("Effective price with offers: ₹749.00"));
+    }
+
+    #[test]
+    fn test_back_in_stock_alert_rendering() {
+        let email = EmailService::back_in_stock_alert(
+            "buyer@example.com",
+            "https://www.ajio.com/levis-jeans/p/469581234_blue",
+            Some(1499.0),
+            "ajio",
+            CHECKED_AT,
+        );
+
+        assert_eq!(email.subject, "📦 Back in stock on AJIO!");
+        assert!(email.html_body.contains("₹1499"));
+        assert!(email.html_body.contains("https://www.ajio.com/levis-jeans/p/469581234_blue"));
+    }
+
+    #[test]
+    fn test_listing_match_alert_escapes_titles() {
+        let items = vec![ListingItem {
+            title: "Men <Black> Formal Shirt".to_string(),
+            url: "https://www.myntra.com/shirts/roadster/12345678/buy".to_string(),
+            price: 1299.0,
+            location: None,
+        }];
+        let email = EmailService::listing_match_alert(
+            "buyer@example.com",
+            "https://www.myntra.com/formal-shirts",
+            &items,
+            1500.0,
+            "myntra",
+            CHECKED_AT,
+        );
+
+        assert!(email.subject.contains("1 new result(s) under ₹1500 on MYNTRA"));
+        assert!(email.html_body.contains("Men &lt;Black&gt; Formal Shirt"));
+        assert!(email.html_body.contains("₹1299"));
+    }
+
+    #[test]
+    fn test_preview_templates() {
+        let params = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+
+        for template in PREVIEW_TEMPLATES {
+            let email = EmailService::preview(template, &HashMap::new()).unwrap();
+            assert!(email.html_body.starts_with("<!DOCTYPE html>"), "{}", template);
+        }
+
+        let email = EmailService::preview(
+            "price_drop",
+            &params(&[("platform", "ajio"), ("current_price", "1499"), ("target_price", "1999"), ("badge", "lowest_ever"), ("offers", "true"), ("unsubscribe", "true")]),
+        )
+        .unwrap();
+        assert!(email.subject.contains("Save ₹500 on AJIO"));
+        assert!(email.html_body.contains("🏆 Lowest price ever"));
+        assert!(email.html_body.contains("use code <strong>SAVE50</strong>"));
+        assert!(email.unsubscribe_url.is_some());
+
+        let email = EmailService::preview("digest", &params(&[("query", "linen shirt")])).unwrap();
+        assert!(email.subject.contains("2 price update(s)"));
+        assert!(email.html_body.contains("is back in stock on MYNTRA"));
+        assert!(email.html_body.contains("2 new result(s) for"));
+
+        let email = EmailService::preview("weekly_summary", &params(&[("current_price", "800")])).unwrap();
+        assert!(email.subject.contains("₹200 off on MYNTRA"));
+        assert!(email.html_body.contains("₹1000 → "));
+        assert!(email.html_body.contains("4 failed check(s): not found"));
+
+        assert!(EmailService::preview("welcome", &HashMap::new()).is_err());
+        assert!(EmailService::preview("price_drop", &params(&[("current_price", "cheap")])).is_err());
     }
 }
@@ -0,0 +1,251 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::Utc;
+use reqwest::Client;
+use serde_json::{json, Value};
+use crate::email::OutgoingEmail;
+use crate::storage::s3::{sha256_hex, SigV4};
+
+/// Which HTTP email API sends the mail. Providers sign with the sender's own
+/// domain (DKIM), so messages land in inboxes more reliably than through a shared SMTP relay.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiProvider {
+    SendGrid { api_key: String },
+    Resend { api_key: String },
+    /// Amazon SES v2 `SendEmail`, SigV4-signed
+    Ses { region: String, access_key: String, secret_key: String },
+}
+
+/// A provider's non-2xx reply, kept whole so failures can be categorised like SMTP ones
+#[derive(Debug)]
+pub struct ProviderError {
+    pub provider: &'static str,
+    pub status: u16,
+    pub message: String,
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} returned {}: {}", self.provider, self.status, self.message)
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+/// Sends rendered emails through SendGrid, Resend or SES instead of SMTP
+#[derive(Clone)]
+pub struct EmailApi {
+    client: Client,
+    provider: ApiProvider,
+    /// Scheme + host; the provider's public endpoint unless `EMAIL_API_BASE` overrides it
+    base_url: String,
+}
+
+impl EmailApi {
+    pub fn new(provider: ApiProvider, base_url: Option<&str>) -> Self {
+        let base_url = base_url.map(|url| url.trim_end_matches('/').to_string()).unwrap_or_else(|| match &provider {
+            ApiProvider::SendGrid { .. } => "https://api.sendgrid.com".to_string(),
+            ApiProvider::Resend { .. } => "https://api.resend.com".to_string(),
+            ApiProvider::Ses { region, .. } => format!("https://email.{}.amazonaws.com", region),
+        });
+        EmailApi { client: Client::new(), provider, base_url }
+    }
+
+    /// Provider named by `EMAIL_PROVIDER` (`sendgrid`, `resend` or `ses`) with its credentials
+    pub fn from_env(provider: &str) -> Result<Self> {
+        let env = |key: &str| std::env::var(key).ok().filter(|v| !v.is_empty());
+        let required = |key: &str| env(key).ok_or_else(|| anyhow!("{} not set in environment", key));
+
+        let provider = match provider {
+            "sendgrid" => ApiProvider::SendGrid { api_key: required("SENDGRID_API_KEY")? },
+            "resend" => ApiProvider::Resend { api_key: required("RESEND_API_KEY")? },
+            "ses" => ApiProvider::Ses {
+                region: env("SES_REGION").or_else(|| env("AWS_REGION")).unwrap_or_else(|| "us-east-1".to_string()),
+                access_key: env("SES_ACCESS_KEY_ID")
+                    .or_else(|| env("AWS_ACCESS_KEY_ID"))
+                    .ok_or_else(|| anyhow!("SES_ACCESS_KEY_ID not set in environment"))?,
+                secret_key: env("SES_SECRET_ACCESS_KEY")
+                    .or_else(|| env("AWS_SECRET_ACCESS_KEY"))
+                    .ok_or_else(|| anyhow!("SES_SECRET_ACCESS_KEY not set in environment"))?,
+            },
+            other => bail!("Unknown EMAIL_PROVIDER '{}' (expected smtp, sendgrid, resend or ses)", other),
+        };
+        Ok(Self::new(provider, env("EMAIL_API_BASE").as_deref()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self.provider {
+            ApiProvider::SendGrid { .. } => "sendgrid",
+            ApiProvider::Resend { .. } => "resend",
+            ApiProvider::Ses { .. } => "ses",
+        }
+    }
+
+    /// Send `email` from `from` ("Name <address>")
+    pub async fn send(&self, from: &str, email: &OutgoingEmail) -> Result<()> {
+        let unsubscribe = email.unsubscribe_url.as_ref().map(|url| {
+            [
+                ("List-Unsubscribe", format!("<{}>", url)),
+                ("List-Unsubscribe-Post", "List-Unsubscribe=One-Click".to_string()),
+            ]
+        });
+
+        let request = match &self.provider {
+            ApiProvider::SendGrid { api_key } => {
+                let (from_name, from_email) = split_mailbox(from);
+                let mut body = json!({
+                    "personalizations": [{ "to": [{ "email": email.to }] }],
+                    "from": { "email": from_email, "name": from_name },
+                    "subject": email.subject,
+                    "content": [{ "type": "text/html", "value": email.html_body }],
+                });
+                if let Some(headers) = &unsubscribe {
+                    body["headers"] = headers.iter().map(|(name, value)| (name.to_string(), json!(value))).collect();
+                }
+                self.client.post(format!("{}/v3/mail/send", self.base_url)).bearer_auth(api_key).json(&body)
+            }
+            ApiProvider::Resend { api_key } => {
+                let mut body = json!({
+                    "from": from,
+                    "to": [email.to],
+                    "subject": email.subject,
+                    "html": email.html_body,
+                });
+                if let Some(headers) = &unsubscribe {
+                    body["headers"] = headers.iter().map(|(name, value)| (name.to_string(), json!(value))).collect();
+                }
+                self.client.post(format!("{}/emails", self.base_url)).bearer_auth(api_key).json(&body)
+            }
+            ApiProvider::Ses { region, access_key, secret_key } => {
+                let mut simple = json!({
+                    "Subject": { "Data": email.subject, "Charset": "UTF-8" },
+                    "Body": { "Html": { "Data": email.html_body, "Charset": "UTF-8" } },
+                });
+                if let Some(headers) = &unsubscribe {
+                    simple["Headers"] = headers.iter().map(|(name, value)| json!({ "Name": name, "Value": value })).collect();
+                }
+                let body = serde_json::to_vec(&json!({
+                    "FromEmailAddress": from,
+                    "Destination": { "ToAddresses": [email.to] },
+                    "Content": { "Simple": simple },
+                }))?;
+
+                let path = "/v2/email/outbound-emails";
+                let host = self.base_url.split_once("://").map(|(_, host)| host).unwrap_or(&self.base_url);
+                let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+                let payload_hash = sha256_hex(&body);
+                let headers = [
+                    ("content-type", "application/json"),
+                    ("host", host),
+                    ("x-amz-content-sha256", payload_hash.as_str()),
+                    ("x-amz-date", amz_date.as_str()),
+                ];
+                let authorization = SigV4 { access_key, secret_key, region, service: "ses" }
+                    .authorization("POST", path, &headers, &payload_hash, &amz_date);
+
+                headers
+                    .iter()
+                    .filter(|(name, _)| *name != "host")
+                    .fold(self.client.post(format!("{}{}", self.base_url, path)), |request, (name, value)| {
+                        request.header(*name, *value)
+                    })
+                    .header("authorization", authorization)
+                    .body(body)
+            }
+        };
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let text = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|body| error_message(&body))
+            .unwrap_or(text);
+        Err(ProviderError { provider: self.name(), status: status.as_u16(), message }.into())
+    }
+}
+
+/// ("Price Tracker", "alerts@example.com") from "Price Tracker <alerts@example.com>"
+fn split_mailbox(mailbox: &str) -> (&str, &str) {
+    match mailbox.rsplit_once('<') {
+        Some((name, address)) => (name.trim().trim_matches('"'), address.trim_end_matches('>').trim()),
+        None => ("", mailbox.trim()),
+    }
+}
+
+/// The human-readable part of an error body: SendGrid's `errors[0].message`,
+/// Resend's `message`, SES's `message`/`Message`
+fn error_message(body: &Value) -> Option<String> {
+    body.pointer("/errors/0/message")
+        .or_else(|| body.get("message"))
+        .or_else(|| body.get("Message"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    fn email() -> OutgoingEmail {
+        OutgoingEmail {
+            to: "buyer@example.com".to_string(),
+            subject: "Price drop".to_string(),
+            html_body: "<p>Now ₹799</p>".to_string(),
+            unsubscribe_url: Some("https://pt.example/unsubscribe?token=a".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_provider_requests() {
+        let mut server = Server::new_async().await;
+        let sendgrid = server.mock("POST", "/v3/mail/send")
+            .match_header("authorization", "Bearer SG.key")
+            .match_body(Matcher::PartialJson(json!({
+                "personalizations": [{ "to": [{ "email": "buyer@example.com" }] }],
+                "from": { "email": "alerts@example.com", "name": "Price Tracker" },
+                "headers": { "List-Unsubscribe": "<https://pt.example/unsubscribe?token=a>" },
+            })))
+            .with_status(202)
+            .create_async()
+            .await;
+        let resend = server.mock("POST", "/emails")
+            .match_header("authorization", "Bearer re_key")
+            .match_body(Matcher::PartialJson(json!({ "from": "Price Tracker <alerts@example.com>", "to": ["buyer@example.com"] })))
+            .with_status(422)
+            .with_body(r#"{"name": "validation_error", "message": "The example.com domain is not verified"}"#)
+            .create_async()
+            .await;
+        let ses = server.mock("POST", "/v2/email/outbound-emails")
+            .match_header("authorization", Matcher::Regex(r"^AWS4-HMAC-SHA256 Credential=AKID/\d{8}/eu-west-1/ses/aws4_request".to_string()))
+            .match_body(Matcher::PartialJson(json!({ "Destination": { "ToAddresses": ["buyer@example.com"] } })))
+            .with_status(200)
+            .with_body(r#"{"MessageId": "abc"}"#)
+            .create_async()
+            .await;
+
+        let from = "Price Tracker <alerts@example.com>";
+        let api = EmailApi::new(ApiProvider::SendGrid { api_key: "SG.key".to_string() }, Some(&server.url()));
+        api.send(from, &email()).await.unwrap();
+
+        let api = EmailApi::new(ApiProvider::Resend { api_key: "re_key".to_string() }, Some(&server.url()));
+        let error = api.send(from, &email()).await.unwrap_err();
+        let error = error.downcast_ref::<ProviderError>().unwrap();
+        assert_eq!((error.status, error.message.as_str()), (422, "The example.com domain is not verified"));
+
+        let provider = ApiProvider::Ses {
+            region: "eu-west-1".to_string(),
+            access_key: "AKID".to_string(),
+            secret_key: "secret".to_string(),
+        };
+        EmailApi::new(provider, Some(&server.url())).send(from, &email()).await.unwrap();
+
+        sendgrid.assert_async().await;
+        resend.assert_async().await;
+        ses.assert_async().await;
+    }
+}
@@ -0,0 +1,115 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use ring::digest;
+use serde::Serialize;
+
+/// Strong ETag for a representation: the first 16 bytes of its SHA-256, hex-encoded
+pub fn etag_for(bytes: &[u8]) -> String {
+    let hash = digest::digest(&digest::SHA256, bytes);
+    let hex: String = hash.as_ref()[..16].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("\"{}\"", hex)
+}
+
+/// Whether the client's `If-None-Match` already names `etag` (or is `*`)
+pub fn is_fresh(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+/// 304 with the ETag repeated, as clients expect
+pub fn not_modified(etag: &str) -> Response {
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    set_etag(&mut response, etag);
+    response
+}
+
+/// JSON response tagged with a hash of its body, or a bodyless 304 when the client has it
+pub fn json_with_etag<T: Serialize>(headers: &HeaderMap, body: &T) -> Response {
+    let bytes = match serde_json::to_vec(body) {
+        Ok(bytes) => bytes,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    let etag = etag_for(&bytes);
+    if is_fresh(headers, &etag) {
+        return not_modified(&etag);
+    }
+
+    let mut response = (
+        [(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))],
+        bytes,
+    )
+        .into_response();
+    set_etag(&mut response, &etag);
+    response
+}
+
+/// Like `json_with_etag`, for handlers that computed the ETag up front
+pub fn json_tagged<T: Serialize>(etag: &str, body: T) -> Response {
+    tagged(etag, Json(body))
+}
+
+/// Any response (e.g. a streamed body) with an ETag computed up front
+pub fn tagged(etag: &str, response: impl IntoResponse) -> Response {
+    let mut response = response.into_response();
+    set_etag(&mut response, etag);
+    response
+}
+
+fn set_etag(response: &mut Response, etag: &str) {
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    // Cache, but always revalidate: alerts change whenever the worker runs
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("private, no-cache"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_etag_is_stable_and_quoted() {
+        let etag = etag_for(b"[]");
+        assert_eq!(etag, etag_for(b"[]"));
+        assert_ne!(etag, etag_for(b"[{}]"));
+        assert!(etag.starts_with('"') && etag.ends_with('"'));
+        assert_eq!(etag.len(), 34);
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let etag = etag_for(b"[]");
+
+        assert!(is_fresh(&if_none_match(&etag), &etag));
+        assert!(is_fresh(&if_none_match(&format!("\"other\", W/{}", etag)), &etag));
+        assert!(is_fresh(&if_none_match("*"), &etag));
+        assert!(!is_fresh(&if_none_match("\"other\""), &etag));
+        assert!(!is_fresh(&HeaderMap::new(), &etag));
+    }
+
+    #[test]
+    fn test_json_with_etag() {
+        let response = json_with_etag(&HeaderMap::new(), &vec![1, 2, 3]);
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let response = json_with_etag(&if_none_match(&etag), &vec![1, 2, 3]);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+    }
+}
@@ -0,0 +1,267 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use crate::events::LiveEvent;
+
+/// Bumped only for breaking changes to an event's shape; adding optional fields isn't one.
+/// Consumers should key on `type` + `schema_version`, e.g. `price_drop` v1.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// An event as sent to integrators: its own fields plus `type` and `schema_version`
+#[derive(Debug, Serialize)]
+pub struct Versioned<'a> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub event: &'a LiveEvent,
+}
+
+impl<'a> Versioned<'a> {
+    pub fn new(event: &'a LiveEvent) -> Self {
+        Versioned { schema_version: SCHEMA_VERSION, event }
+    }
+}
+
+/// JSON Schemas for every outbound event, keyed `<type>.v<schema_version>`
+pub fn schemas() -> Value {
+    let offer = json!({
+        "type": "object",
+        "required": ["kind", "description"],
+        "properties": {
+            "kind": { "enum": ["bank", "coupon", "other"] },
+            "description": { "type": "string" },
+            "code": { "type": "string" },
+            "discount": { "type": "number" }
+        }
+    });
+    let listing_item = json!({
+        "type": "object",
+        "required": ["title", "url", "price"],
+        "properties": {
+            "title": { "type": "string" },
+            "url": { "type": "string", "format": "uri" },
+            "price": { "type": "number" },
+            "location": { "type": "string" }
+        }
+    });
+
+    let events = [
+        schema("price_drop", "A checked price met an alert's target", json!({
+            "alert_id": nullable(uuid()),
+            "user_id": nullable(uuid()),
+            "user_email": { "type": "string", "format": "email" },
+            "url": { "type": "string", "format": "uri" },
+            "platform": { "type": "string" },
+            "current_price": { "type": "number" },
+            "target_price": { "type": "number" },
+            "previous_price": nullable(json!({ "type": "number" })),
+            "offers": { "type": "array", "items": offer },
+            "badge": { "enum": ["lowest_ever", "lowest_90_days", null] },
+            "product_name": nullable(json!({ "type": "string" })),
+            "image_url": nullable(json!({ "type": "string", "format": "uri" }))
+        })),
+        schema("listing_match", "New results under target on a watched search/category page", json!({
+            "listing_alert_id": uuid(),
+            "user_id": uuid(),
+            "user_email": { "type": "string", "format": "email" },
+            "url": { "type": "string", "format": "uri" },
+            "platform": { "type": "string" },
+            "target_price": { "type": "number" },
+            "items": { "type": "array", "items": listing_item }
+        })),
+        schema("keyword_match", "New results under target for a keyword watch on one platform", json!({
+            "watch_id": uuid(),
+            "user_id": uuid(),
+            "user_email": { "type": "string", "format": "email" },
+            "query": { "type": "string" },
+            "platform": { "type": "string" },
+            "search_url": { "type": "string", "format": "uri" },
+            "target_price": { "type": "number" },
+            "items": { "type": "array", "items": listing_item }
+        })),
+        schema("back_in_stock", "A back_in_stock alert's product can be bought again", json!({
+            "alert_id": nullable(uuid()),
+            "user_id": nullable(uuid()),
+            "user_email": { "type": "string", "format": "email" },
+            "url": { "type": "string", "format": "uri" },
+            "platform": { "type": "string" },
+            "current_price": nullable(json!({ "type": "number" }))
+        })),
+        schema("product_removed", "An alert's page keeps answering 404/410, so its checks have stopped", json!({
+            "alert_id": nullable(uuid()),
+            "user_id": nullable(uuid()),
+            "user_email": { "type": "string", "format": "email" },
+            "url": { "type": "string", "format": "uri" },
+            "platform": { "type": "string" },
+            "last_price": nullable(json!({ "type": "number" }))
+        })),
+        schema("alert.failed", "A check couldn't scrape an alert's page", json!({
+            "alert_id": uuid(),
+            "user_id": nullable(uuid()),
+            "url": { "type": "string", "format": "uri" },
+            "platform": { "type": "string" },
+            "reason": { "type": "string" },
+            "error": { "type": "string" }
+        })),
+        schema("price.checked", "A check scraped an alert's current price", json!({
+            "alert_id": uuid(),
+            "user_id": nullable(uuid()),
+            "url": { "type": "string", "format": "uri" },
+            "platform": { "type": "string" },
+            "price": { "type": "number" },
+            "target_price": { "type": "number" },
+            "checked_at": { "type": "string", "format": "date-time" }
+        })),
+    ];
+
+    let mut by_name = serde_json::Map::new();
+    for (name, schema) in events {
+        by_name.insert(name, schema);
+    }
+    json!({ "schema_version": SCHEMA_VERSION, "events": by_name })
+}
+
+/// Schema for one event type; every listed top-level property is always present
+fn schema(event_type: &str, description: &str, mut properties: Value) -> (String, Value) {
+    let name = format!("{}.v{}", event_type, SCHEMA_VERSION);
+    let fields = properties.as_object_mut().expect("properties is an object");
+    let mut required: Vec<String> = vec!["type".to_string(), "schema_version".to_string()];
+    required.extend(fields.keys().cloned());
+    fields.insert("type".to_string(), json!({ "const": event_type }));
+    fields.insert("schema_version".to_string(), json!({ "const": SCHEMA_VERSION }));
+
+    let schema = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": name,
+        "title": name,
+        "description": description,
+        "type": "object",
+        "required": required,
+        "properties": properties
+    });
+    (name, schema)
+}
+
+fn uuid() -> Value {
+    json!({ "type": "string", "format": "uuid" })
+}
+
+fn nullable(schema: Value) -> Value {
+    json!({ "anyOf": [schema, { "type": "null" }] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{AlertFailed, PriceChecked};
+    use crate::models::{ListingItem, Offer, PriceBadge};
+    use crate::notifier_trait::{BackInStock, KeywordMatch, ListingMatch, NotificationEvent, PriceDrop, ProductRemoved};
+    use uuid::Uuid;
+
+    fn samples() -> Vec<LiveEvent> {
+        let items = vec![ListingItem {
+            title: "Nike Pegasus 40".to_string(),
+            url: "https://www.myntra.com/12345678".to_string(),
+            price: 6999.0,
+            location: None,
+        }];
+        vec![
+            NotificationEvent::PriceDrop(PriceDrop {
+                alert_id: Some(Uuid::new_v4()),
+                user_id: None,
+                user_email: "buyer@example.com".to_string(),
+                url: "https://www.flipkart.com/p/itm123".to_string(),
+                platform: "flipkart".to_string(),
+                current_price: 899.0,
+                target_price: 999.0,
+                previous_price: None,
+                offers: vec![Offer {
+                    kind: "bank".to_string(),
+                    description: "10% off with HDFC cards".to_string(),
+                    code: None,
+                    discount: Some(100.0),
+                }],
+                badge: Some(PriceBadge::Lowest90Days),
+                product_name: Some("Roadster Men Black Tee".to_string()),
+                image_url: Some("https://cdn.example.com/tee.jpg".to_string()),
+            })
+            .into(),
+            NotificationEvent::ListingMatch(ListingMatch {
+                listing_alert_id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                user_email: "buyer@example.com".to_string(),
+                url: "https://www.myntra.com/shoes".to_string(),
+                platform: "myntra".to_string(),
+                target_price: 7000.0,
+                items: items.clone(),
+            })
+            .into(),
+            NotificationEvent::KeywordMatch(KeywordMatch {
+                watch_id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                user_email: "buyer@example.com".to_string(),
+                query: "Nike Pegasus 40".to_string(),
+                platform: "myntra".to_string(),
+                search_url: "https://www.myntra.com/nike-pegasus-40".to_string(),
+                target_price: 7000.0,
+                items,
+            })
+            .into(),
+            NotificationEvent::BackInStock(BackInStock {
+                alert_id: None,
+                user_id: Some(Uuid::new_v4()),
+                user_email: "buyer@example.com".to_string(),
+                url: "https://www.ajio.com/p/469581234".to_string(),
+                platform: "ajio".to_string(),
+                current_price: Some(1299.0),
+            })
+            .into(),
+            NotificationEvent::ProductRemoved(ProductRemoved {
+                alert_id: Some(Uuid::new_v4()),
+                user_id: Some(Uuid::new_v4()),
+                user_email: "buyer@example.com".to_string(),
+                url: "https://www.myntra.com/12345678".to_string(),
+                platform: "myntra".to_string(),
+                last_price: None,
+            })
+            .into(),
+            LiveEvent::AlertFailed(AlertFailed {
+                alert_id: Uuid::new_v4(),
+                user_id: None,
+                url: "https://www.ajio.com/p/469581234".to_string(),
+                platform: "ajio".to_string(),
+                reason: "timeout".to_string(),
+                error: "operation timed out".to_string(),
+            }),
+            LiveEvent::PriceChecked(PriceChecked {
+                alert_id: Uuid::new_v4(),
+                user_id: Some(Uuid::new_v4()),
+                url: "https://www.myntra.com/12345678".to_string(),
+                platform: "myntra".to_string(),
+                price: 7499.0,
+                target_price: 6999.0,
+                checked_at: chrono::Utc::now(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_payloads_match_their_schema() {
+        let schemas = schemas();
+
+        for event in samples() {
+            let payload = serde_json::to_value(Versioned::new(&event)).unwrap();
+            let name = format!("{}.v{}", event.name(), SCHEMA_VERSION);
+            let schema = &schemas["events"][&name];
+            assert!(schema.is_object(), "no schema for {}", name);
+
+            let fields = payload.as_object().unwrap();
+            for required in schema["required"].as_array().unwrap() {
+                assert!(fields.contains_key(required.as_str().unwrap()), "{} is missing {}", name, required);
+            }
+            for field in fields.keys() {
+                assert!(schema["properties"].get(field).is_some(), "{} has undocumented field {}", name, field);
+            }
+            assert_eq!(payload["type"], event.name());
+            assert_eq!(payload["schema_version"], SCHEMA_VERSION);
+        }
+    }
+}
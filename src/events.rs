@@ -0,0 +1,228 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+use crate::mqtt::MqttPublisher;
+use crate::notifier_trait::NotificationEvent;
+use crate::redis_store::RedisStore;
+
+/// Redis channel (under the key prefix) that carries events between replicas
+const CHANNEL: &str = "events";
+
+/// Anything pushed to live subscribers: every notification, plus check results
+/// (which are not sent through notification channels)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LiveEvent {
+    Notification(NotificationEvent),
+    AlertFailed(AlertFailed),
+    PriceChecked(PriceChecked),
+}
+
+/// A check scraped an alert's current price, whether or not it met the target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "price.checked")]
+pub struct PriceChecked {
+    pub alert_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub url: String,
+    pub platform: String,
+    pub price: f64,
+    pub target_price: f64,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// A scheduled or manual check couldn't scrape an alert's page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "alert.failed")]
+pub struct AlertFailed {
+    pub alert_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub url: String,
+    pub platform: String,
+    /// Error class, e.g. `timeout` or `price_not_found`
+    pub reason: String,
+    pub error: String,
+}
+
+impl LiveEvent {
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            LiveEvent::Notification(event) => event.user_id(),
+            LiveEvent::AlertFailed(failed) => failed.user_id,
+            LiveEvent::PriceChecked(checked) => checked.user_id,
+        }
+    }
+
+    /// Event type, matching the serialized `type` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            LiveEvent::Notification(event) => event.name(),
+            LiveEvent::AlertFailed(_) => "alert.failed",
+            LiveEvent::PriceChecked(_) => "price.checked",
+        }
+    }
+}
+
+impl From<NotificationEvent> for LiveEvent {
+    fn from(event: NotificationEvent) -> Self {
+        LiveEvent::Notification(event)
+    }
+}
+
+/// Live feed of events for `GET /events` subscribers.
+///
+/// Single-instance deployments broadcast in process. With Redis every event is published
+/// to a channel and each replica relays the channel to its own subscribers, so a user
+/// connected to one replica sees drops found by the worker on another.
+/// Price checks and drops are also forwarded to MQTT when a broker is configured.
+#[derive(Clone)]
+pub struct EventBus {
+    local: broadcast::Sender<Arc<LiveEvent>>,
+    shared: Option<RedisStore>,
+    mqtt: Option<MqttPublisher>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    /// Events a slow subscriber can fall behind by before it starts missing them
+    const CAPACITY: usize = 256;
+
+    pub fn new() -> Self {
+        let (local, _) = broadcast::channel(Self::CAPACITY);
+        EventBus { local, shared: None, mqtt: None }
+    }
+
+    /// Fan out through Redis; spawns the task relaying the channel to local subscribers
+    pub fn with_redis(store: RedisStore) -> Self {
+        let (local, _) = broadcast::channel(Self::CAPACITY);
+        tokio::spawn(relay(store.clone(), local.clone()));
+        EventBus { local, shared: Some(store), mqtt: None }
+    }
+
+    pub fn with_mqtt(mut self, publisher: MqttPublisher) -> Self {
+        self.mqtt = Some(publisher);
+        self
+    }
+
+    pub async fn publish(&self, event: &LiveEvent) {
+        // Forwarded by the replica that produced the event, not by every relay
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish(event);
+        }
+
+        if let Some(store) = &self.shared {
+            let published = match serde_json::to_string(event) {
+                Ok(payload) => store.publish(CHANNEL, &payload).await,
+                Err(e) => Err(e.into()),
+            };
+            match published {
+                // Our own relay delivers it locally
+                Ok(()) => return,
+                Err(e) => tracing::warn!("Failed to publish event to Redis, delivering locally only: {}", e),
+            }
+        }
+
+        // Err just means nobody is listening
+        let _ = self.local.send(Arc::new(event.clone()));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<LiveEvent>> {
+        self.local.subscribe()
+    }
+}
+
+/// Forward the Redis channel to this replica's subscribers, resubscribing after disconnects
+async fn relay(store: RedisStore, local: broadcast::Sender<Arc<LiveEvent>>) {
+    loop {
+        match store.subscribe(CHANNEL).await {
+            Ok(mut pubsub) => {
+                let mut messages = pubsub.on_message();
+                while let Some(message) = messages.next().await {
+                    let event = message
+                        .get_payload::<String>()
+                        .map_err(anyhow::Error::from)
+                        .and_then(|payload| Ok(serde_json::from_str::<LiveEvent>(&payload)?));
+                    match event {
+                        Ok(event) => {
+                            let _ = local.send(Arc::new(event));
+                        }
+                        Err(e) => tracing::warn!("Ignoring malformed event from Redis: {}", e),
+                    }
+                }
+                tracing::warn!("Redis event subscription closed, resubscribing");
+            }
+            Err(e) => tracing::warn!("Redis event subscription failed: {}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier_trait::BackInStock;
+
+    #[tokio::test]
+    async fn test_local_bus_delivers_to_subscribers() {
+        let bus = EventBus::new();
+        // Publishing with no subscribers is fine
+        let event = NotificationEvent::BackInStock(BackInStock {
+            alert_id: None,
+            user_id: None,
+            user_email: "buyer@example.com".to_string(),
+            url: "https://www.myntra.com/12345678".to_string(),
+            platform: "myntra".to_string(),
+            current_price: Some(999.0),
+        });
+        let event = LiveEvent::from(event);
+        bus.publish(&event).await;
+
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        bus.publish(&event).await;
+
+        for receiver in [&mut first, &mut second] {
+            let received = receiver.recv().await.unwrap();
+            assert!(matches!(received.as_ref(), LiveEvent::Notification(NotificationEvent::BackInStock(restock)) if restock.platform == "myntra"));
+        }
+    }
+
+    #[test]
+    fn test_events_round_trip_as_json() {
+        let event = NotificationEvent::BackInStock(BackInStock {
+            alert_id: None,
+            user_id: Some(uuid::Uuid::new_v4()),
+            user_email: "buyer@example.com".to_string(),
+            url: "https://www.ajio.com/p/469581234".to_string(),
+            platform: "ajio".to_string(),
+            current_price: None,
+        });
+        let json = serde_json::to_string(&LiveEvent::from(event.clone())).unwrap();
+        assert!(json.contains("\"type\":\"back_in_stock\""));
+
+        let parsed: LiveEvent = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.name(), "back_in_stock");
+        assert_eq!(parsed.user_id(), event.user_id());
+
+        let failed = LiveEvent::AlertFailed(AlertFailed {
+            alert_id: uuid::Uuid::new_v4(),
+            user_id: None,
+            url: "https://www.ajio.com/p/469581234".to_string(),
+            platform: "ajio".to_string(),
+            reason: "timeout".to_string(),
+            error: "operation timed out".to_string(),
+        });
+        let json = serde_json::to_string(&failed).unwrap();
+        assert!(json.contains("\"type\":\"alert.failed\""));
+        assert_eq!(serde_json::from_str::<LiveEvent>(&json).unwrap().name(), "alert.failed");
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{anyhow, bail, Result};
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::future::Future;
+use crate::metrics::classify_error;
+use crate::scraper_trait::{desktop_url, detect_platform, ProductSnapshot};
+use crate::scrapers::create_scraper;
+
+tokio::task_local! {
+    static TRACE: RefCell<Vec<Step>>;
+}
+
+/// One extraction strategy a scraper tried on a page, and what it saw
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    /// "JSON-LD", "__INITIAL_STATE__ product.price.value", a CSS selector...
+    pub strategy: String,
+    /// Regex matched? JSON path present? Selector found? The price, when it worked
+    pub detail: String,
+    pub found: bool,
+}
+
+/// Record a strategy attempt. Only kept while `traced` is collecting; a no-op for the worker.
+pub fn note(strategy: &str, found: bool, detail: impl Into<String>) {
+    let _ = TRACE.try_with(|trace| {
+        trace.borrow_mut().push(Step { strategy: strategy.to_string(), detail: detail.into(), found });
+    });
+}
+
+/// Run `scrape`, collecting every strategy the scrapers note along the way
+pub async fn traced<F: Future>(scrape: F) -> (F::Output, Vec<Step>) {
+    TRACE
+        .scope(RefCell::new(Vec::new()), async move {
+            let output = scrape.await;
+            (output, TRACE.with(|trace| trace.take()))
+        })
+        .await
+}
+
+/// `scrape [--explain] <url>` on the command line: scrape one page with the built-in
+/// scrapers, without a database, and print what was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    pub url: String,
+    /// Also list each strategy tried, in order
+    pub explain: bool,
+}
+
+impl Command {
+    /// None when the first argument isn't `scrape`
+    pub fn from_args(args: &[String]) -> Result<Option<Command>> {
+        if args.first().map(String::as_str) != Some("scrape") {
+            return Ok(None);
+        }
+        let explain = args[1..].iter().any(|arg| arg == "--explain");
+        let mut urls = args[1..].iter().filter(|arg| !arg.starts_with("--"));
+        match (urls.next(), urls.next()) {
+            (Some(url), None) => Ok(Some(Command { url: url.clone(), explain })),
+            _ => bail!("Usage: scrape [--explain] <url>"),
+        }
+    }
+
+    /// Print the report to stdout; fails when the scrape did
+    pub async fn run(&self) -> Result<ProductSnapshot> {
+        let url = desktop_url(&self.url);
+        let platform = detect_platform(&url).ok_or_else(|| anyhow!("No scraper handles {}", self.url))?;
+        let scraper = create_scraper(platform).ok_or_else(|| anyhow!("No scraper for {}", platform))?;
+
+        let (result, steps) = traced(scraper.get_product(&url)).await;
+        print!("{}", report(platform, &url, self.explain.then_some(steps.as_slice()), &result));
+        result
+    }
+}
+
+/// What `scrape` prints: platform, the strategies tried (with `--explain`), then the outcome
+pub fn report(platform: &str, url: &str, steps: Option<&[Step]>, result: &Result<ProductSnapshot>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Platform: {}", platform);
+    let _ = writeln!(out, "URL:      {}", url);
+
+    if let Some(steps) = steps {
+        let _ = writeln!(out, "\nStrategies tried:");
+        if steps.is_empty() {
+            let _ = writeln!(out, "  (none; the page was never parsed)");
+        }
+        for step in steps {
+            let _ = writeln!(out, "  {} {}: {}", if step.found { "✓" } else { "✗" }, step.strategy, step.detail);
+        }
+    }
+
+    let _ = writeln!(out);
+    match result {
+        Ok(product) => {
+            let _ = writeln!(out, "Price:    ₹{}", product.selling_price);
+            if let Some(mrp) = product.mrp {
+                let _ = writeln!(out, "MRP:      ₹{} ({}% off)", mrp, product.discount_percent.unwrap_or_default());
+            }
+            for offer in &product.offers {
+                let _ = writeln!(out, "Offer:    [{}] {}", offer.kind, offer.description);
+            }
+        }
+        Err(e) => {
+            let _ = writeln!(out, "Failed ({}): {}", classify_error(e), e);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper_trait::PriceScraper;
+    use crate::scrapers::flipkart::FlipkartScraper;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_traced_scrape() {
+        let mut server = Server::new_async().await;
+        let _m = server.mock("GET", "/p/itm1")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(r#"<html><body><div class="_30jeq3">₹2,999</div></body></html>"#)
+            .create_async()
+            .await;
+
+        let url = format!("{}/p/itm1", server.url());
+        let (price, steps) = traced(FlipkartScraper::new().get_price(&url)).await;
+        assert_eq!(price.unwrap(), 2999.0);
+        assert!(steps.iter().any(|step| step.strategy == ".Nx9W0j" && !step.found));
+        assert_eq!(steps.last().map(|step| (step.strategy.as_str(), step.found)), Some(("._30jeq3", true)));
+
+        // Outside `traced` nothing is kept
+        note("ignored", true, "");
+        assert_eq!(traced(async {}).await.1, Vec::new());
+
+        let result = Ok(ProductSnapshot::new(2999.0, Some(3999.0), Vec::new()));
+        let text = report("flipkart", &url, Some(&steps), &result);
+        assert!(text.contains("✓ ._30jeq3: ₹2999"));
+        assert!(text.contains("MRP:      ₹3999 (25% off)"));
+
+        let args = |line: &str| line.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            Command::from_args(&args("scrape --explain https://www.myntra.com/123456")).unwrap(),
+            Some(Command { url: "https://www.myntra.com/123456".to_string(), explain: true })
+        );
+        assert!(Command::from_args(&args("scrape")).is_err());
+        assert_eq!(Command::from_args(&args("backup --out x")).unwrap(), None);
+    }
+}
@@ -0,0 +1,219 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use tower_http::services::ServeDir;
+use crate::etag::{etag_for, is_fresh};
+
+/// Fingerprinted files (`app.3f9c2a1b.js`) never change under the same name
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// Everything else is revalidated on each load, which is a bodyless 304 when unchanged
+const REVALIDATE: &str = "no-cache";
+
+/// Web UI mounted at `/app`.
+///
+/// `FRONTEND_DIR` serves files from disk (default `./frontend`). Builds with the
+/// `embed-frontend` feature serve the copy compiled into the binary instead,
+/// unless `FRONTEND_DIR` is set (handy for iterating on the UI).
+///
+/// Either way, a `.br` or `.gz` file next to an asset is sent instead of it to
+/// clients that accept that encoding, and every file gets an ETag and `Cache-Control`.
+pub fn router() -> Router {
+    let router = match std::env::var("FRONTEND_DIR") {
+        Ok(dir) => from_dir(&dir),
+        Err(_) => default_router(),
+    };
+    router.layer(middleware::from_fn(cache_headers))
+}
+
+fn from_dir(dir: &str) -> Router {
+    tracing::info!("Serving frontend from {}", dir);
+    Router::new().fallback_service(
+        ServeDir::new(dir)
+            .append_index_html_on_directories(true)
+            .precompressed_br()
+            .precompressed_gzip(),
+    )
+}
+
+/// Add `Cache-Control` and an ETag to every asset, and answer a matching `If-None-Match` with a 304
+async fn cache_headers(request: Request, next: Next) -> Response {
+    let cache_control = if is_fingerprinted(request.uri().path()) { IMMUTABLE } else { REVALIDATE };
+    let request_headers = request.headers().clone();
+    let mut response = next.run(request).await;
+    if !matches!(response.status(), StatusCode::OK | StatusCode::NOT_MODIFIED) {
+        return response;
+    }
+
+    let headers = response.headers_mut();
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+
+    // Files from disk only carry Last-Modified; derive a validator from it and the size
+    if !headers.contains_key(header::ETAG) && headers.contains_key(header::LAST_MODIFIED) {
+        let validator = [header::LAST_MODIFIED, header::CONTENT_LENGTH, header::CONTENT_ENCODING]
+            .iter()
+            .map(|name| headers.get(name).and_then(|v| v.to_str().ok()).unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join(":");
+        if let Ok(etag) = HeaderValue::from_str(&etag_for(validator.as_bytes())) {
+            headers.insert(header::ETAG, etag);
+        }
+    }
+
+    let fresh = response.status() == StatusCode::OK
+        && response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|etag| is_fresh(&request_headers, etag));
+    if !fresh {
+        return response;
+    }
+
+    let mut not_modified = Response::new(Body::empty());
+    *not_modified.status_mut() = StatusCode::NOT_MODIFIED;
+    for name in [header::ETAG, header::CACHE_CONTROL, header::LAST_MODIFIED, header::VARY] {
+        if let Some(value) = response.headers().get(&name) {
+            not_modified.headers_mut().insert(name, value.clone());
+        }
+    }
+    not_modified
+}
+
+/// Whether the file name carries a content hash, as bundlers emit: `app.3f9c2a1b.js`,
+/// `index-BkX3e9aQ.css`. A hash is a dot- or dash-separated part of at least 8
+/// letters and digits, with at least one digit, after the base name.
+fn is_fingerprinted(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let Some((stem, _extension)) = name.rsplit_once('.') else {
+        return false;
+    };
+    stem.split(['.', '-'])
+        .skip(1)
+        .any(|part| part.len() >= 8 && part.chars().all(|c| c.is_ascii_alphanumeric()) && part.chars().any(|c| c.is_ascii_digit()))
+}
+
+#[cfg(not(feature = "embed-frontend"))]
+fn default_router() -> Router {
+    from_dir("frontend")
+}
+
+#[cfg(feature = "embed-frontend")]
+fn default_router() -> Router {
+    Router::new().fallback(embedded::serve)
+}
+
+#[cfg(feature = "embed-frontend")]
+mod embedded {
+    use axum::{
+        http::{header, HeaderMap, StatusCode, Uri},
+        response::{IntoResponse, Response},
+    };
+    use rust_embed::RustEmbed;
+    use crate::etag::etag_for;
+
+    #[derive(RustEmbed)]
+    #[folder = "frontend/"]
+    #[exclude = "*.md"]
+    struct Assets;
+
+    pub async fn serve(uri: Uri, headers: HeaderMap) -> Response {
+        let path = uri.path().trim_start_matches('/');
+        let path = if path.is_empty() || path.ends_with('/') {
+            format!("{}index.html", path)
+        } else {
+            path.to_string()
+        };
+        
+        let Some(file) = Assets::get(&path) else {
+            return StatusCode::NOT_FOUND.into_response();
+        };
+        let content_type = file.metadata.mimetype().to_string();
+        
+        // A pre-compressed copy, when one was embedded and the client takes it
+        let accepted = headers
+            .get(header::ACCEPT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        let compressed = [("br", "br"), ("gzip", "gz")]
+            .into_iter()
+            .filter(|(encoding, _)| accepted.split(',').any(|a| a.split(';').next().unwrap_or_default().trim() == *encoding))
+            .find_map(|(encoding, extension)| Some((encoding, Assets::get(&format!("{}.{}", path, extension))?)));
+        let has_variants = ["br", "gz"].iter().any(|extension| Assets::get(&format!("{}.{}", path, extension)).is_some());
+        
+        let (file, encoding) = match compressed {
+            Some((encoding, compressed)) => (compressed, Some(encoding)),
+            None => (file, None),
+        };
+        let etag = etag_for(&[file.metadata.sha256_hash().as_slice(), encoding.unwrap_or_default().as_bytes()].concat());
+        
+        let mut response = ([(header::CONTENT_TYPE, content_type), (header::ETAG, etag)], file.data).into_response();
+        if let Some(encoding) = encoding {
+            response.headers_mut().insert(header::CONTENT_ENCODING, header::HeaderValue::from_static(encoding));
+        }
+        if has_variants {
+            response.headers_mut().insert(header::VARY, header::HeaderValue::from_static("accept-encoding"));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_default_frontend_serves_index() {
+        let app = Router::new().nest_service("/app", default_router());
+        
+        let response = app
+            .oneshot(Request::builder().uri("/app/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()["content-type"].to_str().unwrap().starts_with("text/html"));
+    }
+
+    #[tokio::test]
+    async fn test_cache_headers_and_precompressed_assets() {
+        let dir = std::env::temp_dir().join(format!("frontend-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("index.html"), "<!DOCTYPE html>").unwrap();
+        std::fs::write(dir.join("app.3f9c2a1b.js"), "console.log(1)").unwrap();
+        std::fs::write(dir.join("app.3f9c2a1b.js.br"), [0x0b, 0x06, 0x80]).unwrap();
+        let app = Router::new().nest_service("/app", from_dir(dir.to_str().unwrap()).layer(middleware::from_fn(cache_headers)));
+        let get = |uri: &str, header: Option<(header::HeaderName, String)>| {
+            let mut request = Request::builder().uri(uri);
+            if let Some((name, value)) = header {
+                request = request.header(name, value);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = get("/app/app.3f9c2a1b.js", Some((header::ACCEPT_ENCODING, "gzip, br".to_string()))).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+        assert_eq!(response.headers()[header::CACHE_CONTROL], IMMUTABLE);
+
+        let response = get("/app/", None).await.unwrap();
+        assert_eq!(response.headers()[header::CACHE_CONTROL], REVALIDATE);
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let response = get("/app/", Some((header::IF_NONE_MATCH, etag.clone()))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(is_fingerprinted("/assets/index-BkX3e9aQ.css"));
+        assert!(!is_fingerprinted("/app.js"));
+        assert!(!is_fingerprinted("/vendor/jquery-3.7.1.min.js"));
+        assert!(!is_fingerprinted("/icons/logo-512x512.png"));
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+use reqwest::redirect::Policy;
+use reqwest::{Certificate, Client};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use crate::config::ScraperHttpConfig;
+
+/// The startup client plus variants for platforms with their own connect timeout
+struct Shared {
+    config: ScraperHttpConfig,
+    client: Client,
+    by_connect_timeout: Mutex<HashMap<Duration, Client>>,
+}
+
+static SHARED: OnceLock<Shared> = OnceLock::new();
+
+/// Build a scraper client from `config`
+pub fn build(config: &ScraperHttpConfig) -> Result<Client> {
+    let redirects = match config.max_redirects {
+        0 => Policy::none(),
+        max => Policy::limited(max),
+    };
+
+    let mut builder = Client::builder()
+        .user_agent(config.user_agent.as_str())
+        .connect_timeout(config.connect_timeout)
+        .timeout(config.timeout)
+        .redirect(redirects)
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(config.pool_idle_timeout)
+        .danger_accept_invalid_certs(config.accept_invalid_certs);
+    if let Some(path) = &config.ca_cert {
+        let pem = std::fs::read(path).with_context(|| format!("Failed to read CA certificate {}", path))?;
+        builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+    }
+
+    Ok(builder.build()?)
+}
+
+fn shared_state(config: ScraperHttpConfig) -> Result<Shared> {
+    let client = build(&config)?;
+    Ok(Shared { config, client, by_connect_timeout: Mutex::new(HashMap::new()) })
+}
+
+/// Set up the shared client at startup, so a bad config fails fast rather than on the first check
+pub fn init(config: &ScraperHttpConfig) -> Result<()> {
+    let shared = shared_state(config.clone())?;
+    if SHARED.set(shared).is_err() {
+        tracing::warn!("Scraper HTTP client was already initialized; keeping the existing one");
+    }
+    Ok(())
+}
+
+fn get() -> &'static Shared {
+    SHARED.get_or_init(|| shared_state(ScraperHttpConfig::from_env()).expect("Failed to create HTTP client"))
+}
+
+/// The client every scraper uses. Clones share one connection pool, so keep-alive
+/// connections to each site are reused across checks instead of reopened per scraper.
+/// Falls back to `SCRAPER_HTTP_*` from the environment when `init` wasn't called.
+pub fn shared() -> Client {
+    get().client.clone()
+}
+
+/// Like `shared`, but giving up on connecting after `connect_timeout`.
+/// reqwest only sets this per client, so each distinct timeout gets one pooled client of its own.
+pub fn with_connect_timeout(connect_timeout: Duration) -> Client {
+    let shared = get();
+    if connect_timeout == shared.config.connect_timeout {
+        return shared.client.clone();
+    }
+
+    let mut clients = shared.by_connect_timeout.lock().unwrap();
+    if let Some(client) = clients.get(&connect_timeout) {
+        return client.clone();
+    }
+    let config = ScraperHttpConfig { connect_timeout, ..shared.config.clone() };
+    match build(&config) {
+        Ok(client) => {
+            clients.insert(connect_timeout, client.clone());
+            client
+        }
+        // Only the timeout differs from a config that already built
+        Err(e) => {
+            tracing::error!("Failed to build scraper client with {:?} connect timeout: {}", connect_timeout, e);
+            shared.client.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    fn config(max_redirects: usize) -> ScraperHttpConfig {
+        ScraperHttpConfig {
+            user_agent: "price-tracker-test".to_string(),
+            connect_timeout: Duration::from_secs(5),
+            timeout: Duration::from_secs(5),
+            max_redirects,
+            pool_max_idle_per_host: 2,
+            pool_idle_timeout: Duration::from_secs(30),
+            ca_cert: None,
+            accept_invalid_certs: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy_and_user_agent() {
+        let mut server = Server::new_async().await;
+        let _hop = server.mock("GET", "/short")
+            .with_status(302)
+            .with_header("location", &format!("{}/product", server.url()))
+            .create_async()
+            .await;
+        let _page = server.mock("GET", "/product")
+            .match_header("user-agent", "price-tracker-test")
+            .with_body("ok")
+            .create_async()
+            .await;
+
+        let following = build(&config(10)).unwrap();
+        let response = following.get(format!("{}/short", server.url())).send().await.unwrap();
+        assert_eq!(response.text().await.unwrap(), "ok");
+
+        let not_following = build(&config(0)).unwrap();
+        let response = not_following.get(format!("{}/short", server.url())).send().await.unwrap();
+        assert_eq!(response.status(), 302);
+    }
+
+    #[test]
+    fn test_missing_ca_cert_fails_at_build() {
+        let config = ScraperHttpConfig { ca_cert: Some("/nonexistent/ca.pem".to_string()), ..config(10) };
+        assert!(build(&config).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use regex::Regex;
+use ring::hmac;
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+use crate::price_parse::parse_price;
+
+/// Links tried per email, so a long signature full of URLs can't fan out into scrapes
+const MAX_LINKS: usize = 5;
+
+/// A message forwarded by the mail provider's inbound webhook.
+///
+/// Field names follow Postmark's JSON (`From`, `Subject`, `TextBody`, `Headers`); the
+/// lowercase names used by SendGrid/Mailgun-style relays are accepted too, along with
+/// their flat SPF/DKIM verdict fields.
+#[derive(Debug, Clone, Deserialize)]
+pub struct InboundEmail {
+    #[serde(alias = "From", alias = "sender")]
+    pub from: String,
+    #[serde(default, alias = "Subject")]
+    pub subject: String,
+    #[serde(default, alias = "TextBody", alias = "body-plain", alias = "text")]
+    pub body: String,
+    #[serde(default, alias = "SPF", alias = "X-Mailgun-Spf")]
+    pub spf: Option<String>,
+    #[serde(default, alias = "X-Mailgun-Dkim-Check-Result")]
+    pub dkim: Option<String>,
+    #[serde(default, alias = "Headers")]
+    pub headers: Vec<InboundHeader>,
+}
+
+/// One of the original message's headers, as Postmark lists them
+#[derive(Debug, Clone, Deserialize)]
+pub struct InboundHeader {
+    #[serde(alias = "Name")]
+    pub name: String,
+    #[serde(alias = "Value")]
+    pub value: String,
+}
+
+impl InboundEmail {
+    /// Bare address of the sender, e.g. "buyer@example.com" from "Buyer <buyer@example.com>"
+    pub fn sender_address(&self) -> Option<String> {
+        let mailbox: lettre::message::Mailbox = self.from.trim().parse().ok()?;
+        Some(mailbox.email.to_string())
+    }
+
+    /// Whether the provider vouches for the sender: an SPF or DKIM verdict of "pass", either
+    /// in the relay's own fields or in the `Received-SPF`/`Authentication-Results` headers.
+    /// `From` is trivially forged, so mail without one is never matched to an account.
+    pub fn sender_authenticated(&self) -> bool {
+        static PASS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)^\W*pass\b|:\s*pass\b").unwrap());
+        static RESULTS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(?:spf|dkim)\s*=\s*pass\b").unwrap());
+        let header = |name: &'static str| {
+            self.headers
+                .iter()
+                .filter(move |header| header.name.eq_ignore_ascii_case(name))
+                .map(|header| header.value.as_str())
+        };
+        [self.spf.as_deref(), self.dkim.as_deref()]
+            .into_iter()
+            .flatten()
+            .chain(header("Received-SPF"))
+            .any(|verdict| PASS.is_match(verdict))
+            || header("Authentication-Results").any(|results| RESULTS.is_match(results))
+    }
+
+    /// Every http(s) link in the subject and body, in order, capped at `MAX_LINKS`
+    pub fn links(&self) -> Vec<&str> {
+        static URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"https?://[^\s"'<>]+"#).unwrap());
+        [self.subject.as_str(), self.body.as_str()]
+            .into_iter()
+            .flat_map(|text| URL.find_iter(text))
+            .map(|m| m.as_str().trim_end_matches(['.', ',', ')', '!']))
+            .take(MAX_LINKS)
+            .collect()
+    }
+
+    /// A target written as "under 999", "target: ₹1,299" or "below Rs. 850" in the subject or body
+    pub fn requested_target(&self) -> Option<f64> {
+        static TARGET: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)\b(?:target|under|below)\b\s*:?\s*((?:₹|rs\.?|inr)?\s*[\d,]+(?:\.\d+)?)").unwrap()
+        });
+        [self.subject.as_str(), self.body.as_str()]
+            .into_iter()
+            .find_map(|text| TARGET.captures(text))
+            .and_then(|caps| parse_price(&caps[1]))
+    }
+}
+
+/// Whether `provided` is the webhook secret, compared in constant time by way of HMACs
+pub fn secret_matches(expected: &str, provided: &str) -> bool {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, expected.as_bytes());
+    let tag = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, provided.as_bytes()), b"inbound-email");
+    hmac::verify(&key, b"inbound-email", tag.as_ref()).is_ok()
+}
+
+/// How far below the current price the target is set when the email doesn't name one
+pub fn default_discount_percent() -> f64 {
+    std::env::var("INBOUND_EMAIL_DISCOUNT_PERCENT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|percent| (0.0..100.0).contains(percent))
+        .unwrap_or(10.0)
+}
+
+/// `current_price` less `discount_percent`, in whole rupees
+pub fn default_target(current_price: f64, discount_percent: f64) -> f64 {
+    (current_price * (1.0 - discount_percent / 100.0)).round()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(subject: &str, body: &str) -> InboundEmail {
+        InboundEmail {
+            from: "Buyer <buyer@example.com>".to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            spf: None,
+            dkim: None,
+            headers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_forwarded_email() {
+        let forwarded = email(
+            "Fwd: Nike Air Max 270",
+            "track under ₹1,299 please\n\n---------- Forwarded message ---------\nhttps://www.myntra.com/shoes/nike/12345678.\n-- \nhttps://blog.example.com",
+        );
+
+        assert_eq!(forwarded.sender_address().as_deref(), Some("buyer@example.com"));
+        assert_eq!(forwarded.links(), vec!["https://www.myntra.com/shoes/nike/12345678", "https://blog.example.com"]);
+        // The "270" in the product name is not a target
+        assert_eq!(forwarded.requested_target(), Some(1299.0));
+        assert_eq!(email("Target: Rs. 850", "").requested_target(), Some(850.0));
+        assert_eq!(email("Fwd: Nike Air Max 270", "https://www.myntra.com/12345678").requested_target(), None);
+
+        assert_eq!(default_target(1499.0, 10.0), 1349.0);
+    }
+
+    #[test]
+    fn test_sender_authenticated() {
+        let parse = |json: serde_json::Value| serde_json::from_value::<InboundEmail>(json).unwrap();
+        let from = "buyer@example.com";
+
+        assert!(!email("", "").sender_authenticated());
+        // SendGrid
+        assert!(parse(serde_json::json!({ "from": from, "SPF": "pass" })).sender_authenticated());
+        assert!(parse(serde_json::json!({ "from": from, "dkim": "{@example.com : pass}" })).sender_authenticated());
+        assert!(!parse(serde_json::json!({ "from": from, "SPF": "softfail", "dkim": "{@example.com : fail}" })).sender_authenticated());
+        // Mailgun
+        assert!(parse(serde_json::json!({ "sender": from, "X-Mailgun-Dkim-Check-Result": "Pass" })).sender_authenticated());
+        // Postmark
+        let postmark = |name: &str, value: &str| {
+            parse(serde_json::json!({ "From": from, "Headers": [{ "Name": name, "Value": value }] }))
+        };
+        assert!(postmark("Received-SPF", "Pass (sender SPF authorized) identity=mailfrom").sender_authenticated());
+        assert!(!postmark("Received-SPF", "Neutral (mailfrom) identity=mailfrom").sender_authenticated());
+        assert!(postmark("Authentication-Results", "mx.example.net; spf=fail; dkim=pass header.d=example.com").sender_authenticated());
+        assert!(!postmark("Authentication-Results", "mx.example.net; spf=fail; dkim=none").sender_authenticated());
+        // Only the provider's verdicts count, not a look-alike header
+        assert!(!postmark("X-Spf", "pass").sender_authenticated());
+    }
+
+    #[test]
+    fn test_secret_matches() {
+        assert!(secret_matches("inbound-secret", "inbound-secret"));
+        assert!(!secret_matches("inbound-secret", "inbound-secreT"));
+        assert!(!secret_matches("inbound-secret", ""));
+    }
+}
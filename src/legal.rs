@@ -0,0 +1,84 @@
+use axum::{
+    extract::{Request, State},
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+use uuid::Uuid;
+use std::sync::Arc;
+use crate::auth::{request_token, JwtKeys};
+use crate::db::Database;
+
+/// Set on responses to signed-in users who haven't accepted the live terms, carrying
+/// the version to accept with `POST /legal/accept`
+pub const TERMS_HEADER: HeaderName = HeaderName::from_static("x-terms-reaccept");
+
+/// Used when `TERMS_VERSION` isn't set
+pub const DEFAULT_TERMS_VERSION: &str = "1";
+
+/// The terms of service users agree to. Bumping `TERMS_VERSION` asks everyone to accept again.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Terms {
+    pub version: String,
+    /// Where the text lives (`TERMS_URL`), when published
+    pub url: Option<String>,
+}
+
+impl Terms {
+    pub fn from_env() -> Self {
+        let env = |key: &str| std::env::var(key).ok().map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+        Terms {
+            version: env("TERMS_VERSION").unwrap_or_else(|| DEFAULT_TERMS_VERSION.to_string()),
+            url: env("TERMS_URL"),
+        }
+    }
+
+    /// Whether a user who last accepted `accepted` has to accept again
+    pub fn needs_acceptance(&self, accepted: Option<&str>) -> bool {
+        accepted != Some(self.version.as_str())
+    }
+}
+
+/// Flag responses to signed-in users whose accepted terms are out of date. Nothing is
+/// blocked; clients show the new terms when they see the header. API keys aren't
+/// flagged, since integrations have no one to accept.
+pub async fn flag_stale_consent(
+    State((db, keys)): State<(Database, Arc<JwtKeys>)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let user_id = request_token(request.headers())
+        .and_then(|token| keys.verify(token).ok())
+        .and_then(|claims| Uuid::parse_str(&claims.sub).ok());
+
+    let mut response = next.run(request).await;
+    let Some(user_id) = user_id else {
+        return response;
+    };
+
+    let terms = Terms::from_env();
+    match db.get_accepted_terms_version(user_id).await {
+        Ok(Some(accepted)) if terms.needs_acceptance(accepted.as_deref()) => {
+            if let Ok(value) = HeaderValue::from_str(&terms.version) {
+                response.headers_mut().insert(TERMS_HEADER, value);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Couldn't check accepted terms for {}: {}", user_id, e),
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_acceptance() {
+        let terms = Terms { version: "2026-10".to_string(), url: None };
+        assert!(!terms.needs_acceptance(Some("2026-10")));
+        assert!(terms.needs_acceptance(Some("2025-01")));
+        assert!(terms.needs_acceptance(None));
+    }
+}
@@ -1,9 +1,48 @@
 // Library exports for testing and external use
+pub mod backup;
+pub mod cache;
+pub mod chart;
+pub mod config;
 pub mod models;
 pub mod db;
 pub mod scraper_trait;
 pub mod scrapers;
+pub mod scraper_config;
+pub mod scraper_rules;
+pub mod http_client;
+pub mod live_price;
+pub mod matcher;
+pub mod price_parse;
+pub mod url_resolver;
 pub mod worker;
 pub mod api;
 pub mod email;
+pub mod email_api;
+pub mod notifier_trait;
+pub mod notifiers;
+pub mod telegram_bot;
 pub mod auth;
+pub mod plans;
+pub mod timezone;
+pub mod inbound_email;
+pub mod unsubscribe;
+pub mod rate_limit;
+pub mod storage;
+pub mod metrics;
+pub mod request_id;
+pub mod etag;
+pub mod events;
+pub mod event_schema;
+pub mod redis_store;
+pub mod mqtt;
+pub mod demo;
+pub mod explain;
+pub mod maintenance;
+pub mod legal;
+pub mod summary;
+pub mod outbox;
+pub mod public_stats;
+pub mod wayback;
+pub mod discovery;
+pub mod throttle;
+pub mod frontend;
@@ -0,0 +1,145 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use moka::future::Cache;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use crate::models::Offer;
+use crate::scraper_config::ScraperConfigStore;
+
+/// A price scraped on demand rather than by the scheduled check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LivePrice {
+    pub url: String,
+    pub platform: String,
+    /// Selling price
+    pub price: f64,
+    pub mrp: Option<f64>,
+    pub discount_percent: Option<f64>,
+    pub offers: Vec<Offer>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// On-demand scrapes for users who want the current price right now.
+///
+/// Results are cached per URL for a short while, and concurrent requests for the same page
+/// share one scrape. Requests to each platform are spaced out by the same politeness delay
+/// the worker uses, so a burst of clicks can't hammer a site.
+#[derive(Clone)]
+pub struct LivePrices {
+    cache: Cache<String, Arc<LivePrice>>,
+    /// Earliest moment the next request to each platform may go out
+    next_slot: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl LivePrices {
+    pub fn new(ttl: Duration) -> Self {
+        LivePrices {
+            cache: Cache::builder().max_capacity(10_000).time_to_live(ttl).build(),
+            next_slot: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Cache lifetime from `LIVE_PRICE_CACHE_SECS` (default 60)
+    pub fn from_env() -> Self {
+        let ttl_secs = std::env::var("LIVE_PRICE_CACHE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(60);
+        Self::new(Duration::from_secs(ttl_secs))
+    }
+
+    /// The current price of `url`, and whether it came from the cache
+    pub async fn get(&self, scraper_configs: &ScraperConfigStore, platform: &str, url: &str) -> Result<(LivePrice, bool)> {
+        if let Some(cached) = self.cache.get(url).await {
+            return Ok(((*cached).clone(), true));
+        }
+
+        let fetched = self
+            .cache
+            .try_get_with(url.to_string(), self.scrape(scraper_configs, platform, url))
+            .await
+            .map_err(|e| anyhow!("{}", e))?;
+        Ok(((*fetched).clone(), false))
+    }
+
+    async fn scrape(&self, scraper_configs: &ScraperConfigStore, platform: &str, url: &str) -> Result<Arc<LivePrice>> {
+        let scraper = scraper_configs
+            .scraper(platform)
+            .ok_or_else(|| anyhow!("Unknown platform: {}", platform))?;
+
+        self.wait_turn(platform, scraper_configs.request_delay()).await;
+        let deadline = scraper_configs.alert_deadline();
+        let snapshot = tokio::time::timeout(deadline, scraper.get_product(&crate::scraper_trait::desktop_url(url)))
+            .await
+            .map_err(|elapsed| anyhow::Error::new(elapsed).context(format!("Timed out after {:?} scraping {}", deadline, url)))??;
+
+        Ok(Arc::new(LivePrice {
+            url: url.to_string(),
+            platform: platform.to_string(),
+            price: snapshot.selling_price,
+            mrp: snapshot.mrp,
+            discount_percent: snapshot.discount_percent,
+            offers: snapshot.offers,
+            fetched_at: Utc::now(),
+        }))
+    }
+
+    /// Reserve the platform's next request slot and sleep until it comes. Other
+    /// on-demand scrapes (`POST /alerts/:id/check`) take their turn here too.
+    pub async fn wait_turn(&self, platform: &str, gap: Duration) {
+        let slot = {
+            let mut next_slot = self.next_slot.lock().await;
+            let now = Instant::now();
+            let slot = next_slot.get(platform).copied().filter(|slot| *slot > now).unwrap_or(now);
+            next_slot.insert(platform.to_string(), slot + gap);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scrapers::mock::{MockScript, MockStep};
+    use crate::scrapers::ScraperBackend;
+
+    const URL: &str = "https://www.myntra.com/12345678";
+
+    #[tokio::test]
+    async fn test_live_prices_are_cached_briefly() {
+        let script = Arc::new(MockScript::new().with(URL, vec![MockStep::price(1299.0), MockStep::price(999.0)]));
+        let scraper_configs = ScraperConfigStore::new().with_backend(ScraperBackend::Mock(script.clone()));
+        let live = LivePrices::new(Duration::from_millis(100));
+
+        let (first, cached) = live.get(&scraper_configs, "myntra", URL).await.unwrap();
+        assert_eq!((first.price, cached), (1299.0, false));
+        let (again, cached) = live.get(&scraper_configs, "myntra", URL).await.unwrap();
+        assert_eq!((again.price, cached), (1299.0, true));
+        assert_eq!(script.reads(URL), 1);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let (fresh, cached) = live.get(&scraper_configs, "myntra", URL).await.unwrap();
+        assert_eq!((fresh.price, cached), (999.0, false));
+
+        assert!(live.get(&scraper_configs, "amazon", "https://www.amazon.in/dp/B0CX23V2ZK").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_requests_to_a_platform_are_spaced_out() {
+        let live = LivePrices::new(Duration::from_secs(60));
+        let gap = Duration::from_millis(200);
+        let started = Instant::now();
+
+        live.wait_turn("myntra", gap).await;
+        live.wait_turn("ajio", gap).await;
+        assert!(started.elapsed() < gap);
+
+        live.wait_turn("myntra", gap).await;
+        assert!(started.elapsed() >= gap);
+    }
+}
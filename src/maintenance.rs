@@ -0,0 +1,132 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::json;
+use std::sync::Arc;
+use tokio::sync::watch;
+
+/// Shown to clients when maintenance is switched on without a message
+pub const DEFAULT_MESSAGE: &str = "The price tracker is down for maintenance. Please try again in a few minutes.";
+
+/// Seconds clients are told to wait before retrying a rejected write
+const RETRY_AFTER_SECS: u64 = 300;
+
+/// Routes that keep accepting writes, so an admin can sign in and switch maintenance off
+const EXEMPT_PATHS: &[&str] = &["/auth/login", "/admin/maintenance"];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MaintenanceStatus {
+    pub message: String,
+    pub since: DateTime<Utc>,
+}
+
+/// Runtime maintenance switch shared by the API and the background worker.
+/// While it's on, mutating requests get a 503 and scheduled checks wait.
+/// The switch is per process; each replica is toggled on its own.
+#[derive(Clone)]
+pub struct Maintenance {
+    state: Arc<watch::Sender<Option<MaintenanceStatus>>>,
+}
+
+impl Maintenance {
+    pub fn new() -> Self {
+        Maintenance { state: Arc::new(watch::Sender::new(None)) }
+    }
+
+    /// Starts switched on when `MAINTENANCE_MODE` is true, with `MAINTENANCE_MESSAGE` if set
+    pub fn from_env() -> Self {
+        let maintenance = Self::new();
+        let enabled = std::env::var("MAINTENANCE_MODE")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "true" | "1" | "yes" | "on"))
+            .unwrap_or(false);
+        if enabled {
+            maintenance.enable(std::env::var("MAINTENANCE_MESSAGE").ok());
+        }
+        maintenance
+    }
+
+    pub fn enable(&self, message: Option<String>) {
+        let message = message
+            .map(|m| m.trim().to_string())
+            .filter(|m| !m.is_empty())
+            .unwrap_or_else(|| DEFAULT_MESSAGE.to_string());
+        self.state.send_replace(Some(MaintenanceStatus { message, since: Utc::now() }));
+    }
+
+    pub fn disable(&self) {
+        self.state.send_replace(None);
+    }
+
+    pub fn status(&self) -> Option<MaintenanceStatus> {
+        self.state.borrow().clone()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.state.borrow().is_some()
+    }
+
+    /// Resolves once maintenance is off (immediately if it already is)
+    pub async fn wait_until_off(&self) {
+        let mut receiver = self.state.subscribe();
+        let _ = receiver.wait_for(Option::is_none).await;
+    }
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reject mutating requests with a 503 and a JSON explanation while maintenance is on
+pub async fn reject_writes(State(maintenance): State<Maintenance>, request: Request, next: Next) -> Response {
+    let read_only = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+    if read_only || EXEMPT_PATHS.contains(&request.uri().path()) {
+        return next.run(request).await;
+    }
+    let Some(status) = maintenance.status() else {
+        return next.run(request).await;
+    };
+
+    (
+        StatusCode::SERVICE_UNAVAILABLE,
+        [(header::RETRY_AFTER, RETRY_AFTER_SECS.to_string())],
+        Json(json!({
+            "error": "maintenance",
+            "message": status.message,
+            "since": status.since,
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_maintenance_switch() {
+        let maintenance = Maintenance::new();
+        assert!(!maintenance.is_enabled());
+        maintenance.wait_until_off().await;
+
+        maintenance.enable(Some("  ".to_string()));
+        assert_eq!(maintenance.status().unwrap().message, DEFAULT_MESSAGE);
+
+        let waiting = tokio::spawn({
+            let maintenance = maintenance.clone();
+            async move { maintenance.wait_until_off().await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiting.is_finished());
+
+        maintenance.disable();
+        tokio::time::timeout(Duration::from_secs(1), waiting).await.unwrap().unwrap();
+    }
+}
@@ -0,0 +1,59 @@
+/// Words that rarely appear in listing titles and shouldn't block a match
+const IGNORED_WORDS: &[&str] = &["a", "an", "and", "for", "in", "of", "the", "with"];
+
+/// Lowercase alphanumeric words, so "Pegasus-40," and "pegasus 40" tokenize the same
+fn tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The words of a keyword query that a listing title has to contain.
+///
+/// "size 9" / "size XL" are dropped: sizes are picked on the product page,
+/// not shown in search result titles.
+pub fn required_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut words = tokens(query).into_iter();
+
+    while let Some(word) = words.next() {
+        if word == "size" {
+            words.next();
+        } else if !IGNORED_WORDS.contains(&word.as_str()) {
+            terms.push(word);
+        }
+    }
+
+    terms
+}
+
+/// Whether a search result title is the product described by a keyword query
+pub fn title_matches(query: &str, title: &str) -> bool {
+    let title_tokens = tokens(title);
+    let terms = required_terms(query);
+
+    !terms.is_empty() && terms.iter().all(|term| title_tokens.contains(term))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_terms_drop_sizes() {
+        assert_eq!(required_terms("Nike Pegasus 40 size 9"), vec!["nike", "pegasus", "40"]);
+        assert_eq!(required_terms("Shirt for the office"), vec!["shirt", "office"]);
+    }
+
+    #[test]
+    fn test_title_matches() {
+        let query = "Nike Pegasus 40 size 9";
+
+        assert!(title_matches(query, "Nike Men Air Zoom Pegasus 40 Running Shoes"));
+        assert!(title_matches(query, "NIKE Pegasus-40 Road Running Shoes"));
+        assert!(!title_matches(query, "Nike Men Air Zoom Pegasus 39 Running Shoes"));
+        assert!(!title_matches(query, "Nike Pegasus 400"));
+        assert!(!title_matches("size 9", "Nike Pegasus 40"));
+    }
+}
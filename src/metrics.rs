@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the scrape duration histogram buckets
+const DURATION_BUCKETS: [f64; 8] = [0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0];
+
+/// Process-wide scrape counters, exposed in Prometheus text format at `/metrics`.
+/// Persistent per-attempt data lives in the `scrape_attempts` table.
+#[derive(Default)]
+pub struct ScrapeMetrics {
+    inner: Mutex<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// (platform, outcome, error_kind) -> count
+    attempts: BTreeMap<(String, &'static str, &'static str), u64>,
+    durations: BTreeMap<String, Histogram>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; DURATION_BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+static SCRAPE_METRICS: LazyLock<ScrapeMetrics> = LazyLock::new(ScrapeMetrics::default);
+
+pub fn scrape_metrics() -> &'static ScrapeMetrics {
+    &SCRAPE_METRICS
+}
+
+/// How a notification attempt ended, stored as `notification_deliveries.outcome`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DeliveryOutcome {
+    Sent,
+    /// The mail server refused the message for good (5xx); not retried
+    Bounced,
+    /// Gave up: out of retries, or an error retrying can't fix
+    Failed,
+    /// Failed this time, another attempt is scheduled
+    Retried,
+}
+
+impl DeliveryOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryOutcome::Sent => "sent",
+            DeliveryOutcome::Bounced => "bounced",
+            DeliveryOutcome::Failed => "failed",
+            DeliveryOutcome::Retried => "retried",
+        }
+    }
+}
+
+/// Process-wide notification delivery counters, next to the scrape ones at `/metrics`.
+/// Persistent per-attempt data lives in the `notification_deliveries` table.
+#[derive(Default)]
+pub struct DeliveryMetrics {
+    /// (channel, outcome, error_kind) -> count
+    deliveries: Mutex<BTreeMap<(String, &'static str, &'static str), u64>>,
+}
+
+static DELIVERY_METRICS: LazyLock<DeliveryMetrics> = LazyLock::new(DeliveryMetrics::default);
+
+pub fn delivery_metrics() -> &'static DeliveryMetrics {
+    &DELIVERY_METRICS
+}
+
+impl DeliveryMetrics {
+    pub fn record(&self, channel: &str, outcome: DeliveryOutcome, error_kind: Option<&'static str>) {
+        *self
+            .deliveries
+            .lock()
+            .unwrap()
+            .entry((channel.to_string(), outcome.as_str(), error_kind.unwrap_or("")))
+            .or_default() += 1;
+    }
+
+    /// Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP notification_deliveries_total Notification attempts by channel and outcome\n");
+        out.push_str("# TYPE notification_deliveries_total counter\n");
+        for ((channel, outcome, error_kind), count) in self.deliveries.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "notification_deliveries_total{{channel=\"{}\",outcome=\"{}\",error_kind=\"{}\"}} {}",
+                channel, outcome, error_kind, count
+            );
+        }
+        out
+    }
+}
+
+impl ScrapeMetrics {
+    pub fn record(&self, platform: &str, duration: Duration, outcome: &'static str, error_kind: Option<&'static str>) {
+        let mut inner = self.inner.lock().unwrap();
+
+        *inner
+            .attempts
+            .entry((platform.to_string(), outcome, error_kind.unwrap_or("")))
+            .or_default() += 1;
+
+        let secs = duration.as_secs_f64();
+        let histogram = inner.durations.entry(platform.to_string()).or_default();
+        for (bucket, le) in histogram.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if secs <= le {
+                *bucket += 1;
+            }
+        }
+        histogram.count += 1;
+        histogram.sum += secs;
+    }
+
+    /// Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+
+        out.push_str("# HELP scrape_attempts_total Scrape attempts by platform and outcome\n");
+        out.push_str("# TYPE scrape_attempts_total counter\n");
+        for ((platform, outcome, error_kind), count) in &inner.attempts {
+            let _ = writeln!(
+                out,
+                "scrape_attempts_total{{platform=\"{}\",outcome=\"{}\",error_kind=\"{}\"}} {}",
+                platform, outcome, error_kind, count
+            );
+        }
+
+        out.push_str("# HELP scrape_duration_seconds Time spent fetching and parsing a product page\n");
+        out.push_str("# TYPE scrape_duration_seconds histogram\n");
+        for (platform, histogram) in &inner.durations {
+            for (count, le) in histogram.buckets.iter().zip(DURATION_BUCKETS) {
+                let _ = writeln!(out, "scrape_duration_seconds_bucket{{platform=\"{}\",le=\"{}\"}} {}", platform, le, count);
+            }
+            let _ = writeln!(out, "scrape_duration_seconds_bucket{{platform=\"{}\",le=\"+Inf\"}} {}", platform, histogram.count);
+            let _ = writeln!(out, "scrape_duration_seconds_sum{{platform=\"{}\"}} {}", platform, histogram.sum);
+            let _ = writeln!(out, "scrape_duration_seconds_count{{platform=\"{}\"}} {}", platform, histogram.count);
+        }
+
+        out
+    }
+}
+
+/// Coarse failure category for a scrape error, stored as `scrape_attempts.error_kind`
+pub fn classify_error(error: &anyhow::Error) -> &'static str {
+    if let Some(e) = error.downcast_ref::<reqwest::Error>() {
+        return if e.is_timeout() {
+            "timeout"
+        } else if e.is_connect() {
+            "connect"
+        } else if e.is_status() {
+            "http_status"
+        } else {
+            "network"
+        };
+    }
+
+    if error.downcast_ref::<crate::scraper_trait::PageGone>().is_some() {
+        return "page_gone";
+    }
+
+    if error.downcast_ref::<crate::scraper_trait::RateLimited>().is_some() {
+        return "rate_limited";
+    }
+
+    // The worker's per-alert deadline
+    if error.downcast_ref::<tokio::time::error::Elapsed>().is_some() {
+        return "timeout";
+    }
+
+    if error.downcast_ref::<serde_json::Error>().is_some() {
+        return "parse";
+    }
+
+    if error.to_string().contains("Could not find price") {
+        "price_not_found"
+    } else {
+        "other"
+    }
+}
+
+/// Coarse failure category for a notification error, stored as
+/// `notification_deliveries.error_kind`. SMTP replies are split by what they
+/// usually mean with Gmail and similar relays: a bad login, throttling of the
+/// sender, or the message itself being refused.
+pub fn classify_delivery_error(error: &anyhow::Error) -> &'static str {
+    if let Some(e) = error.downcast_ref::<lettre::transport::smtp::Error>() {
+        return match e.status() {
+            Some(code) => smtp_error_kind(code.into()),
+            None if e.is_timeout() => "timeout",
+            None if e.is_tls() => "tls",
+            None => "connect",
+        };
+    }
+    if let Some(e) = error.downcast_ref::<crate::email_api::ProviderError>() {
+        return match e.status {
+            401 | 403 => "provider_auth",
+            429 => "provider_throttled",
+            400..=499 => "provider_rejected",
+            _ => "provider_unavailable",
+        };
+    }
+    if error.to_string().contains("Invalid recipient") {
+        return "invalid_recipient";
+    }
+
+    match classify_error(error) {
+        "price_not_found" => "other",
+        kind => kind,
+    }
+}
+
+fn smtp_error_kind(code: u16) -> &'static str {
+    match code {
+        530 | 534 | 535 => "smtp_auth",
+        // Gmail's "421 4.7.0 try again later" and "450 4.2.1 receiving mail too quickly"
+        421 | 450 | 451 | 452 => "smtp_throttled",
+        500..=599 => "smtp_rejected",
+        _ => "smtp_transient",
+    }
+}
+
+/// Whether another attempt could succeed; refusals and bad credentials won't change on retry
+pub fn is_retryable_delivery_error(error_kind: &str) -> bool {
+    !matches!(error_kind, "smtp_auth" | "smtp_rejected" | "provider_auth" | "provider_rejected" | "invalid_recipient")
+}
+
+/// Whether a final failure means the message was refused (a bounce) rather than lost
+pub fn is_bounce(error_kind: &str) -> bool {
+    matches!(error_kind, "smtp_rejected" | "provider_rejected")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn test_render_counts_and_histogram() {
+        let metrics = ScrapeMetrics::default();
+        metrics.record("flipkart", Duration::from_millis(400), "success", None);
+        metrics.record("flipkart", Duration::from_secs(3), "failure", Some("timeout"));
+
+        let text = metrics.render();
+        assert!(text.contains(r#"scrape_attempts_total{platform="flipkart",outcome="success",error_kind=""} 1"#));
+        assert!(text.contains(r#"scrape_attempts_total{platform="flipkart",outcome="failure",error_kind="timeout"} 1"#));
+        assert!(text.contains(r#"scrape_duration_seconds_bucket{platform="flipkart",le="0.5"} 1"#));
+        assert!(text.contains(r#"scrape_duration_seconds_bucket{platform="flipkart",le="5"} 2"#));
+        assert!(text.contains(r#"scrape_duration_seconds_count{platform="flipkart"} 2"#));
+    }
+
+    #[test]
+    fn test_classify_error() {
+        let not_found = anyhow!("Could not find price in Flipkart HTML. Site structure may have changed.");
+        assert_eq!(classify_error(&not_found), "price_not_found");
+
+        let parse: anyhow::Error = serde_json::from_str::<serde_json::Value>("{").unwrap_err().into();
+        assert_eq!(classify_error(&parse), "parse");
+
+        assert_eq!(classify_error(&anyhow!("boom")), "other");
+        assert_eq!(classify_error(&crate::scraper_trait::PageGone { status: 410 }.into()), "page_gone");
+        assert_eq!(
+            classify_error(&crate::scraper_trait::RateLimited { status: 429, retry_after: None }.into()),
+            "rate_limited"
+        );
+    }
+
+    #[test]
+    fn test_delivery_metrics_and_classification() {
+        assert_eq!(smtp_error_kind(535), "smtp_auth");
+        assert_eq!(smtp_error_kind(550), "smtp_rejected");
+        assert_eq!(smtp_error_kind(421), "smtp_throttled");
+        assert_eq!(smtp_error_kind(454), "smtp_transient");
+        assert!(is_retryable_delivery_error("smtp_throttled"));
+        assert!(!is_retryable_delivery_error("smtp_rejected"));
+        assert_eq!(classify_delivery_error(&anyhow!("Invalid recipient email address")), "invalid_recipient");
+        let unverified = crate::email_api::ProviderError { provider: "resend", status: 422, message: "domain is not verified".into() };
+        assert_eq!(classify_delivery_error(&unverified.into()), "provider_rejected");
+        assert!(is_bounce("provider_rejected"));
+
+        let metrics = DeliveryMetrics::default();
+        metrics.record("email", DeliveryOutcome::Retried, Some("smtp_throttled"));
+        metrics.record("email", DeliveryOutcome::Sent, None);
+        let text = metrics.render();
+        assert!(text.contains(r#"notification_deliveries_total{channel="email",outcome="retried",error_kind="smtp_throttled"} 1"#));
+        assert!(text.contains(r#"notification_deliveries_total{channel="email",outcome="sent",error_kind=""} 1"#));
+    }
+}
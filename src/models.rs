@@ -1,6 +1,11 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use uuid::Uuid;
+use crate::auth::Scope;
+use crate::legal::Terms;
+use crate::plans::{Plan, PlanLimits};
 
 // User model for authentication
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
@@ -11,6 +16,56 @@ pub struct User {
     pub password_hash: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing)]
+    pub failed_login_attempts: i32,
+    #[serde(skip_serializing)]
+    pub locked_until: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub unlock_token: Option<String>,
+    pub plan: String,
+    pub timezone: String,
+    pub notifications_paused: bool,
+    /// Target for new price alerts created without one, as % below the current price
+    pub default_discount_percent: Option<f64>,
+    pub role: String,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub verification_token: Option<String>,
+    /// Keep this user's alerts out of public charts and aggregates
+    pub exclude_from_public: bool,
+    /// Terms of service version last accepted; None for accounts older than consent tracking
+    pub terms_version: Option<String>,
+    pub terms_accepted_at: Option<DateTime<Utc>>,
+    /// Opted in to the weekly summary email
+    pub weekly_summary: bool,
+    #[serde(skip_serializing)]
+    pub weekly_summary_sent_at: Option<DateTime<Utc>>,
+}
+
+/// What an account may do beyond its own data, stored as `users.role`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    #[default]
+    User,
+    Admin,
+}
+
+impl Role {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Admin => "admin",
+        }
+    }
+
+    /// Anything unrecognised gets no extra rights
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "admin" => Role::Admin,
+            _ => Role::User,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
@@ -22,17 +77,206 @@ pub struct PriceAlert {
     pub last_price: Option<f64>,
     pub user_email: String,
     pub user_id: Option<Uuid>,
-    pub platform: String, // myntra, flipkart, ajio, tata_cliq
+    pub platform: String, // myntra, flipkart, ajio, tata_cliq, shoppers_stop, lifestyle
     pub created_at: DateTime<Utc>,
     pub last_checked: DateTime<Utc>,
     pub is_active: bool,
+    /// Restricts notifications to these channel IDs; all enabled channels when empty
+    pub notify_channels: Option<Vec<Uuid>>,
+    /// Platform product ID (Myntra style ID, Flipkart item ID...) when known
+    pub product_id: Option<String>,
+    /// `price` (notify at or below target) or `back_in_stock` (notify on restock)
+    pub alert_mode: String,
+    /// Availability at the last check, for `back_in_stock` alerts
+    pub in_stock: Option<bool>,
+    /// Coupons and bank offers seen at the last check
+    pub offers: sqlx::types::Json<Vec<Offer>>,
+    /// Incremented by every edit; `PUT /alerts/:id` must send the version it read
+    pub version: i32,
+    /// Minutes between checks; the owner's plan minimum applies when unset or lower
+    pub check_interval_minutes: Option<i32>,
+    /// No longer checked (and deactivated) after this
+    pub expires_at: Option<DateTime<Utc>>,
+    /// When to notify again while the price stays at or below target
+    pub renotify: sqlx::types::Json<RenotifyPolicy>,
+    /// Plausible price range set by the user; prices outside it are saved as suspect
+    pub min_price: Option<f64>,
+    pub max_price: Option<f64>,
+    /// Set once the page has kept answering 404/410; the alert isn't checked again
+    /// until its URL is changed
+    pub product_removed_at: Option<DateTime<Utc>>,
+    /// Name and picture the page showed at the last check
+    pub product_name: Option<String>,
+    pub image_url: Option<String>,
+    /// Still checked, but nothing is sent about it (the email's unsubscribe link)
+    pub notifications_paused: bool,
+}
+
+// A coupon or bank offer shown on the product page
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Offer {
+    /// `bank`, `coupon` or `other`
+    pub kind: String,
+    pub description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Flat saving in rupees, when the page states one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discount: Option<f64>,
+}
+
+/// Price after the single biggest flat-discount offer (offers rarely stack)
+pub fn effective_price(price: f64, offers: &[Offer]) -> Option<f64> {
+    offers
+        .iter()
+        .filter_map(|offer| offer.discount)
+        .max_by(f64::total_cmp)
+        .map(|discount| (price - discount).max(0.0))
+}
+
+impl PriceAlert {
+    /// Whether `price` is inside the range the user said is plausible
+    pub fn in_bounds(&self, price: f64) -> bool {
+        self.min_price.is_none_or(|min| price >= min) && self.max_price.is_none_or(|max| price <= max)
+    }
+
+    /// True when the product just went from out of stock to in stock.
+    /// The first check only records the current state.
+    pub fn is_restock(&self, in_stock: bool) -> bool {
+        in_stock && self.in_stock == Some(false)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertMode {
+    #[default]
+    Price,
+    BackInStock,
+}
+
+impl AlertMode {
+    pub const ALL: [AlertMode; 2] = [AlertMode::Price, AlertMode::BackInStock];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertMode::Price => "price",
+            AlertMode::BackInStock => "back_in_stock",
+        }
+    }
+}
+
+/// When a price alert notifies again after its first drop, while the price stays at or
+/// below target. Drops are counted per target, so editing the target starts over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case")]
+pub enum RenotifyPolicy {
+    /// Every check at or below target
+    #[default]
+    Always,
+    /// Only the first time
+    Never,
+    /// When the price falls another `percent` below the last notified price
+    FurtherDrop { percent: f64 },
+    /// When `days` have passed since the last notification
+    AfterDays { days: i64 },
+    /// When the price went back above target since, and has dropped again
+    Rearm,
+}
+
+impl RenotifyPolicy {
+    /// Every `policy` tag, in declaration order
+    pub const NAMES: [&'static str; 5] = ["always", "never", "further_drop", "after_days", "rearm"];
+
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            RenotifyPolicy::FurtherDrop { percent } if !(percent > 0.0 && percent < 100.0) => {
+                Err("renotify percent must be between 0 and 100".to_string())
+            }
+            RenotifyPolicy::AfterDays { days } if days < 1 => Err("renotify days must be at least 1".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether a check that found `price` at or below `target` should notify. `last_drop`
+    /// is the alert's latest recorded drop and `previous_price` the price seen on the
+    /// check before this one.
+    pub fn should_notify(
+        &self,
+        last_drop: Option<&PriceDropEvent>,
+        previous_price: Option<f64>,
+        target: f64,
+        price: f64,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let Some(last) = last_drop.filter(|drop| drop.target_price == target) else {
+            return true;
+        };
+        match *self {
+            RenotifyPolicy::Always => true,
+            RenotifyPolicy::Never => false,
+            RenotifyPolicy::FurtherDrop { percent } => price <= last.price * (1.0 - percent / 100.0),
+            RenotifyPolicy::AfterDays { days } => now - last.created_at >= chrono::Duration::days(days),
+            RenotifyPolicy::Rearm => previous_price.is_none_or(|previous| previous > target),
+        }
+    }
+}
+
+/// Body of the public `POST /lookup` and `POST /validate-url`
+#[derive(Debug, Deserialize)]
+pub struct LookupRequest {
+    /// Product link, share text or short link
+    pub url: String,
 }
 
+/// Either `url`, or `platform` + `product_id` (e.g. a Myntra style ID copied from the app)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateAlertRequest {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub platform: Option<String>,
+    #[serde(default)]
+    pub product_id: Option<String>,
+    /// Required for `price` alerts, ignored for `back_in_stock`
+    #[serde(default)]
+    pub target_price: Option<f64>,
+    #[serde(default)]
+    pub mode: AlertMode,
+    /// Notification address; defaults to the account email when omitted
+    #[serde(default)]
+    pub user_email: Option<String>,
+    #[serde(default)]
+    pub notify_channels: Option<Vec<Uuid>>,
+    #[serde(default)]
+    pub check_interval_minutes: Option<i32>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// `always` when omitted
+    #[serde(default)]
+    pub renotify: Option<RenotifyPolicy>,
+    /// Prices outside these are treated as scraping errors
+    #[serde(default)]
+    pub min_price: Option<f64>,
+    #[serde(default)]
+    pub max_price: Option<f64>,
+    /// Fills in whatever of the above is missing from one of the user's templates
+    #[serde(default)]
+    pub template_id: Option<Uuid>,
+}
+
+// Full replacement of an alert; `version` is the one the client last read
+#[derive(Debug, Deserialize)]
+pub struct ReplaceAlertRequest {
+    #[serde(flatten)]
+    pub alert: CreateAlertRequest,
+    pub version: i32,
+}
+
+// New URL for the same product (`POST /alerts/:id/relink`)
+#[derive(Debug, Deserialize)]
+pub struct RelinkAlertRequest {
     pub url: String,
-    pub target_price: f64,
-    pub user_email: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -43,10 +287,122 @@ pub struct AlertResponse {
     pub last_price: Option<f64>,
     pub user_email: String,
     pub platform: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notify_channels: Option<Vec<Uuid>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_id: Option<String>,
+    pub mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_stock: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub offers: Vec<Offer>,
+    /// `last_price` after the best offer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance_to_target: Option<TargetDistance>,
+    pub version: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub check_interval_minutes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    pub renotify: RenotifyPolicy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_price: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_price: Option<f64>,
+    /// `product_removed` when the page is gone and checks have stopped, otherwise `active`
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_removed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub product_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>,
+}
+
+/// What the weekly summary email reports, for one user
+#[derive(Debug, Clone, Serialize)]
+pub struct WeeklySummary {
+    pub since: DateTime<Utc>,
+    pub biggest_drops: Vec<SummaryDrop>,
+    pub nearest_target: Vec<SummaryNearTarget>,
+    /// Alerts with no successful check since `since`
+    pub needs_attention: Vec<SummaryFailingAlert>,
+}
+
+impl WeeklySummary {
+    /// Rows per section
+    pub const MAX_ROWS: i64 = 5;
+
+    /// Nothing worth an email
+    pub fn is_empty(&self) -> bool {
+        self.biggest_drops.is_empty() && self.nearest_target.is_empty() && self.needs_attention.is_empty()
+    }
+}
+
+/// An alert whose price fell over the week
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SummaryDrop {
+    pub alert_id: Uuid,
+    pub url: String,
+    pub platform: String,
+    pub target_price: f64,
+    pub start_price: f64,
+    pub end_price: f64,
+    /// Times the target was met during the week
+    pub drop_events: i64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SummaryNearTarget {
+    pub alert_id: Uuid,
+    pub url: String,
+    pub platform: String,
+    pub last_price: f64,
+    pub target_price: f64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SummaryFailingAlert {
+    pub alert_id: Uuid,
+    pub url: String,
+    pub platform: String,
+    pub failures: i64,
+    /// `error_kind` of the latest attempt
+    pub last_error: Option<String>,
+}
+
+/// How far the last seen price is above the target (negative once it's below)
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct TargetDistance {
+    pub amount: f64,
+    pub percent: f64,
+}
+
+impl TargetDistance {
+    pub fn new(last_price: f64, target_price: f64) -> Option<Self> {
+        if target_price <= 0.0 {
+            return None;
+        }
+        let amount = last_price - target_price;
+        Some(TargetDistance {
+            amount,
+            percent: (amount / target_price * 1000.0).round() / 10.0,
+        })
+    }
 }
 
 impl From<PriceAlert> for AlertResponse {
     fn from(alert: PriceAlert) -> Self {
+        let offers = alert.offers.0;
+        let effective_price = alert.last_price.and_then(|price| effective_price(price, &offers));
+        // Back-in-stock alerts have no target to be close to
+        let distance_to_target = alert
+            .last_price
+            .filter(|_| alert.alert_mode == AlertMode::Price.as_str())
+            .and_then(|price| TargetDistance::new(price, alert.target_price));
+        
         AlertResponse {
             id: alert.id.map(|id| id.to_string()).unwrap_or_default(),
             url: alert.url,
@@ -54,16 +410,321 @@ impl From<PriceAlert> for AlertResponse {
             last_price: alert.last_price,
             user_email: alert.user_email,
             platform: alert.platform,
+            notify_channels: alert.notify_channels,
+            product_id: alert.product_id,
+            mode: alert.alert_mode,
+            in_stock: alert.in_stock,
+            offers,
+            effective_price,
+            distance_to_target,
+            version: alert.version,
+            check_interval_minutes: alert.check_interval_minutes,
+            expires_at: alert.expires_at,
+            renotify: alert.renotify.0,
+            min_price: alert.min_price,
+            max_price: alert.max_price,
+            status: if alert.product_removed_at.is_some() { "product_removed" } else { "active" }.to_string(),
+            product_removed_at: alert.product_removed_at,
+            product_name: alert.product_name,
+            image_url: alert.image_url,
         }
     }
 }
 
+// Reusable settings for creating similar alerts (`template_id` on `POST /alerts`)
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct AlertTemplate {
+    pub id: Uuid,
+    #[serde(skip_serializing)]
+    pub user_id: Uuid,
+    pub name: String,
+    /// Target as % below the price when the alert is created
+    pub discount_percent: Option<f64>,
+    pub notify_channels: Option<Vec<Uuid>>,
+    pub check_interval_minutes: Option<i32>,
+    /// Alerts made from the template expire this many days after creation
+    pub expires_after_days: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AlertTemplateRequest {
+    pub name: String,
+    #[serde(default)]
+    pub discount_percent: Option<f64>,
+    #[serde(default)]
+    pub notify_channels: Option<Vec<Uuid>>,
+    #[serde(default)]
+    pub check_interval_minutes: Option<i32>,
+    #[serde(default)]
+    pub expires_after_days: Option<i32>,
+}
+
+// One product card on a search or category results page
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ListingItem {
+    pub title: String,
+    pub url: String,
+    pub price: f64,
+    /// Where the seller is, for classified ads (OLX, Quikr)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+// Watches the top results of a search/category page for anything under target
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ListingAlert {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub platform: String,
+    pub url: String,
+    pub target_price: f64,
+    /// Only the first N results (in the page's own order) are considered
+    pub max_results: i32,
+    /// Every term must appear in the result title (case-insensitive)
+    pub include_terms: Vec<String>,
+    /// Results whose title contains any of these are ignored
+    pub exclude_terms: Vec<String>,
+    /// Result URLs already notified, so each match is only sent once
+    #[serde(skip_serializing)]
+    pub notified_urls: Vec<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+impl ListingAlert {
+    /// Results within `max_results` that pass the include/exclude filters, at any price
+    pub fn matching<'a>(&self, items: &'a [ListingItem]) -> Vec<&'a ListingItem> {
+        let contains = |title: &str, term: &String| title.contains(&term.to_lowercase());
+
+        items
+            .iter()
+            .take(self.max_results.max(0) as usize)
+            .filter(|item| {
+                let title = item.title.to_lowercase();
+                self.include_terms.iter().all(|term| contains(&title, term))
+                    && !self.exclude_terms.iter().any(|term| contains(&title, term))
+            })
+            .collect()
+    }
+
+    /// Results that pass the filters, are under target and haven't been notified yet
+    pub fn new_matches<'a>(&self, items: &'a [ListingItem]) -> Vec<&'a ListingItem> {
+        self.matching(items)
+            .into_iter()
+            .filter(|item| item.price <= self.target_price && !self.notified_urls.contains(&item.url))
+            .collect()
+    }
+}
+
+/// Lowest asking price among a listing alert's matching results at one check
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ListingPricePoint {
+    pub min_price: f64,
+    /// How many results matched the filters
+    pub results: i32,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateListingAlertRequest {
+    /// Search or category page URL, with the platform's own filters applied
+    pub url: String,
+    pub target_price: f64,
+    #[serde(default)]
+    pub max_results: Option<i32>,
+    #[serde(default)]
+    pub include_terms: Vec<String>,
+    #[serde(default)]
+    pub exclude_terms: Vec<String>,
+}
+
+// Searches every platform for a free-text query ("Nike Pegasus 40 size 9")
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct KeywordWatch {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub query: String,
+    pub target_price: f64,
+    /// Platforms to search; every platform with search support when empty
+    pub platforms: Vec<String>,
+    /// Result URLs already notified, so each match is only sent once
+    #[serde(skip_serializing)]
+    pub notified_urls: Vec<String>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_checked: Option<DateTime<Utc>>,
+}
+
+impl KeywordWatch {
+    /// Results that are the queried product, under target and not yet notified
+    pub fn new_matches<'a>(&self, items: &'a [ListingItem]) -> Vec<&'a ListingItem> {
+        items
+            .iter()
+            .filter(|item| {
+                item.price <= self.target_price
+                    && !self.notified_urls.contains(&item.url)
+                    && crate::matcher::title_matches(&self.query, &item.title)
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateKeywordWatchRequest {
+    pub query: String,
+    pub target_price: f64,
+    #[serde(default)]
+    pub platforms: Vec<String>,
+}
+
+/// One thing that happened to an alert, for `GET /alerts/:id/timeline`
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct TimelineEntry {
+    /// Id of the row it comes from (history point, drop event, annotation...)
+    pub id: Uuid,
+    pub at: DateTime<Utc>,
+    /// `price`, `drop`, `notification`, `paused`, `resumed` or `relinked`
+    pub kind: String,
+    /// What's known about it: the price seen, the drop's target, the note...
+    pub details: sqlx::types::Json<serde_json::Value>,
+}
+
+/// Position in an alert's history, newest first: the last point of a page. Encoded as an
+/// opaque string for `next_cursor`; `id` breaks ties between points checked in the same instant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryCursor {
+    pub checked_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl HistoryCursor {
+    pub fn after(point: &PriceHistory) -> Self {
+        HistoryCursor { checked_at: point.checked_at, id: point.id }
+    }
+
+    pub fn encode(&self) -> String {
+        BASE64.encode(format!("{}:{}", self.checked_at.timestamp_micros(), self.id))
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let decoded = String::from_utf8(BASE64.decode(cursor).ok()?).ok()?;
+        let (micros, id) = decoded.split_once(':')?;
+        Some(HistoryCursor {
+            checked_at: DateTime::from_timestamp_micros(micros.parse().ok()?)?,
+            id: Uuid::parse_str(id).ok()?,
+        })
+    }
+}
+
+/// One checked alert's new price, offers and snapshot, written with the rest of its batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct PriceUpdate {
+    pub alert_id: Uuid,
+    pub price: f64,
+    pub mrp: Option<f64>,
+    pub offers: Vec<Offer>,
+    /// Left as they were when the page didn't show them
+    pub product_name: Option<String>,
+    pub image_url: Option<String>,
+    pub checked_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct PriceHistory {
     pub id: Uuid,
     pub alert_id: Uuid,
+    /// Selling price
     pub price: f64,
+    /// List price shown alongside, if any
+    pub mrp: Option<f64>,
     pub checked_at: DateTime<Utc>,
+    /// Far off the recent median, so most likely a scraping glitch; left out of stats and alerts
+    pub suspect: bool,
+}
+
+/// What an annotation on a price history marks
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnotationKind {
+    Bought,
+    SaleEvent,
+    PriceError,
+    Note,
+    /// Added by the worker when a check meets the alert's target
+    TargetReached,
+    /// Added when the alert is moved to a new URL with `POST /alerts/:id/relink`
+    Relinked,
+    /// Added by the worker when it stops checking the alert because its product page is gone
+    Paused,
+    /// Added when a paused alert is moved to a new URL and checked again
+    Resumed,
+}
+
+impl AnnotationKind {
+    pub const ALL: [AnnotationKind; 8] = [
+        AnnotationKind::Bought,
+        AnnotationKind::SaleEvent,
+        AnnotationKind::PriceError,
+        AnnotationKind::Note,
+        AnnotationKind::TargetReached,
+        AnnotationKind::Relinked,
+        AnnotationKind::Paused,
+        AnnotationKind::Resumed,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnnotationKind::Bought => "bought",
+            AnnotationKind::SaleEvent => "sale_event",
+            AnnotationKind::PriceError => "price_error",
+            AnnotationKind::Note => "note",
+            AnnotationKind::TargetReached => "target_reached",
+            AnnotationKind::Relinked => "relinked",
+            AnnotationKind::Paused => "paused",
+            AnnotationKind::Resumed => "resumed",
+        }
+    }
+
+    /// Kinds only the server adds, never `POST /alerts/:id/annotations`
+    pub fn is_system(&self) -> bool {
+        matches!(self, AnnotationKind::TargetReached | AnnotationKind::Relinked | AnnotationKind::Paused | AnnotationKind::Resumed)
+    }
+}
+
+/// A marker on an alert's price history ("bought here", "sale event", "price error"),
+/// added by the user or by the worker (`source` = `system`)
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Annotation {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    pub user_id: Option<Uuid>,
+    /// The history point it's pinned to, if any
+    pub history_id: Option<Uuid>,
+    /// `AnnotationKind` in snake_case
+    pub kind: String,
+    pub note: Option<String>,
+    /// `user` or `system`
+    pub source: String,
+    /// Where it sits on the chart
+    pub annotated_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Body of `POST /alerts/:id/annotations`. Pinned to `history_id` when given,
+/// otherwise placed at `at` (default: now).
+#[derive(Debug, Deserialize)]
+pub struct CreateAnnotationRequest {
+    pub kind: AnnotationKind,
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub history_id: Option<Uuid>,
+    #[serde(default)]
+    pub at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
@@ -74,11 +735,664 @@ pub struct PriceStats {
     pub data_points: Option<i64>,
 }
 
+/// An alert marked as purchased. The first tracked price and the target are copied in
+/// when it's marked, so the savings report doesn't depend on history retention.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Purchase {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub price: f64,
+    pub purchased_at: DateTime<Utc>,
+    /// First price recorded for the alert, if it was ever checked
+    pub initial_price: Option<f64>,
+    pub target_price: f64,
+    pub created_at: DateTime<Utc>,
+    pub url: String,
+    pub platform: String,
+}
+
+impl Purchase {
+    pub fn saved_vs_initial(&self) -> Option<f64> {
+        self.initial_price.map(|initial| initial - self.price)
+    }
+
+    pub fn saved_vs_target(&self) -> f64 {
+        self.target_price - self.price
+    }
+}
+
+/// Body of `POST /alerts/:id/purchase`; the last checked price and now by default
+#[derive(Debug, Deserialize)]
+pub struct MarkPurchasedRequest {
+    #[serde(default)]
+    pub price: Option<f64>,
+    #[serde(default)]
+    pub purchased_at: Option<DateTime<Utc>>,
+}
+
+/// `GET /reports/savings`: what buying at the tracked prices saved. Savings are signed,
+/// so paying more than the first tracked price counts against the total.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SavingsReport {
+    pub purchases: usize,
+    pub total_paid: f64,
+    /// Over purchases whose alert had a first recorded price
+    pub saved_vs_initial: f64,
+    pub saved_vs_target: f64,
+}
+
+impl SavingsReport {
+    pub fn from_purchases(purchases: &[Purchase]) -> Self {
+        let round = |amount: f64| (amount * 100.0).round() / 100.0;
+        SavingsReport {
+            purchases: purchases.len(),
+            total_paid: round(purchases.iter().map(|p| p.price).sum()),
+            saved_vs_initial: round(purchases.iter().filter_map(Purchase::saved_vs_initial).sum()),
+            saved_vs_target: round(purchases.iter().map(Purchase::saved_vs_target).sum()),
+        }
+    }
+}
+
+/// Lowest recorded prices for an alert, read before the current check is saved
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, sqlx::FromRow)]
+pub struct PriceLows {
+    pub all_time_low: Option<f64>,
+    pub recent_low: Option<f64>,
+}
+
+/// How a drop compares with the price history, for the notification
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceBadge {
+    LowestEver,
+    #[serde(rename = "lowest_90_days")]
+    Lowest90Days,
+}
+
+impl PriceBadge {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PriceBadge::LowestEver => "Lowest price ever",
+            PriceBadge::Lowest90Days => "Lowest price in 90 days",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriceBadge::LowestEver => "lowest_ever",
+            PriceBadge::Lowest90Days => "lowest_90_days",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "lowest_ever" => Some(PriceBadge::LowestEver),
+            "lowest_90_days" => Some(PriceBadge::Lowest90Days),
+            _ => None,
+        }
+    }
+}
+
+/// One rung of an alert's price ladder: notified once, the first time a check finds
+/// the price at or below it. The alert's own target is the last rung.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct AlertThreshold {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    pub price: f64,
+    pub notified_at: Option<DateTime<Utc>>,
+    /// The price that set it off
+    pub notified_price: Option<f64>,
+}
+
+/// Body of `PUT /alerts/:id/thresholds`, replacing the whole ladder
+#[derive(Debug, Deserialize)]
+pub struct SetThresholdsRequest {
+    pub prices: Vec<f64>,
+}
+
+/// A check that met an alert's target, as recorded by the worker.
+/// Notifications are built from these rows rather than from the alert's current state.
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct PriceDropEvent {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    pub user_id: Option<Uuid>,
+    /// The `price_history` snapshot that triggered the drop
+    pub history_id: Option<Uuid>,
+    pub price: f64,
+    pub target_price: f64,
+    pub previous_price: Option<f64>,
+    /// `PriceBadge` in snake_case
+    pub badge: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PriceLows {
+    /// Window used for `recent_low`
+    pub const RECENT_DAYS: i64 = 90;
+
+    /// No badge without history: the first check can't claim a record
+    pub fn badge(&self, current_price: f64) -> Option<PriceBadge> {
+        if current_price <= self.all_time_low? {
+            Some(PriceBadge::LowestEver)
+        } else if self.recent_low.is_some_and(|low| current_price <= low) {
+            Some(PriceBadge::Lowest90Days)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether `price` is more than `factor` times above or below `median`, like ₹3 read off
+/// a ₹3,499 page. A factor of 1 or less turns the check off.
+pub fn is_price_anomaly(price: f64, median: f64, factor: f64) -> bool {
+    factor > 1.0 && median > 0.0 && (price > median * factor || price * factor < median)
+}
+
+/// A drop as listed to integrations: the event plus the product it was for
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct DropFeedItem {
+    pub id: Uuid,
+    pub alert_id: Uuid,
+    pub url: String,
+    pub platform: String,
+    pub price: f64,
+    pub target_price: f64,
+    pub previous_price: Option<f64>,
+    pub badge: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Credential for integrations, sent as `X-API-Key`. The key itself is only shown once.
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: Uuid,
+    #[serde(skip_serializing)]
+    pub user_id: Uuid,
+    pub name: String,
+    /// First characters of the key, to tell keys apart
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub key_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    /// `alerts:read` only when omitted
+    #[serde(default)]
+    pub scopes: Option<Vec<Scope>>,
+}
+
+/// `POST /auth/tokens`: a token limited to some scopes, for a dashboard or script
+#[derive(Debug, Deserialize)]
+pub struct CreateTokenRequest {
+    pub scopes: Vec<Scope>,
+    /// Lifetime; the usual access-token lifetime when omitted
+    pub ttl_minutes: Option<i64>,
+}
+
+/// Whole account as portable JSON: `GET /export` writes it, `POST /import` reads it back
+/// (on this instance or another). IDs only link entries within the file; imports get new ones.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountExport {
+    /// Always `ACCOUNT_EXPORT_FORMAT`
+    pub format: String,
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    /// Account settings; missing from version 1 files, which leave them as they are
+    #[serde(default)]
+    pub preferences: Option<ExportedPreferences>,
+    #[serde(default)]
+    pub channels: Vec<ExportedChannel>,
+    #[serde(default)]
+    pub templates: Vec<ExportedTemplate>,
+    #[serde(default)]
+    pub alerts: Vec<ExportedAlert>,
+    #[serde(default)]
+    pub products: Vec<ExportedProduct>,
+    #[serde(default)]
+    pub listing_alerts: Vec<ExportedListingAlert>,
+    #[serde(default)]
+    pub watches: Vec<ExportedWatch>,
+}
+
+pub const ACCOUNT_EXPORT_FORMAT: &str = "price_tracker.account";
+/// 2 added `preferences`, `templates` and the per-alert settings
+pub const ACCOUNT_EXPORT_VERSION: u32 = 2;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedPreferences {
+    pub timezone: String,
+    #[serde(default)]
+    pub default_discount_percent: Option<f64>,
+    #[serde(default)]
+    pub weekly_summary: bool,
+    #[serde(default)]
+    pub exclude_from_public: bool,
+    #[serde(default)]
+    pub notifications_paused: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub discount_percent: Option<f64>,
+    /// IDs from `channels`
+    #[serde(default)]
+    pub notify_channels: Option<Vec<Uuid>>,
+    #[serde(default)]
+    pub check_interval_minutes: Option<i32>,
+    #[serde(default)]
+    pub expires_after_days: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedChannel {
+    pub id: Uuid,
+    pub channel: String,
+    pub destination: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedAlert {
+    pub id: Uuid,
+    pub url: String,
+    pub platform: String,
+    #[serde(default)]
+    pub product_id: Option<String>,
+    pub alert_mode: String,
+    pub target_price: f64,
+    pub user_email: String,
+    #[serde(default)]
+    pub last_price: Option<f64>,
+    /// IDs from `channels`
+    #[serde(default)]
+    pub notify_channels: Option<Vec<Uuid>>,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub check_interval_minutes: Option<i32>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub renotify: RenotifyPolicy,
+    #[serde(default)]
+    pub min_price: Option<f64>,
+    #[serde(default)]
+    pub max_price: Option<f64>,
+    /// Price ladder above the target
+    #[serde(default)]
+    pub thresholds: Vec<f64>,
+    #[serde(default)]
+    pub notifications_paused: bool,
+    /// Every recorded price, oldest first
+    #[serde(default)]
+    pub history: Vec<ExportedPrice>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct ExportedPrice {
+    pub price: f64,
+    #[serde(default)]
+    pub mrp: Option<f64>,
+    pub checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedProduct {
+    pub name: String,
+    /// IDs from `alerts`
+    pub alert_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedListingAlert {
+    pub url: String,
+    pub platform: String,
+    pub target_price: f64,
+    pub max_results: i32,
+    #[serde(default)]
+    pub include_terms: Vec<String>,
+    #[serde(default)]
+    pub exclude_terms: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExportedWatch {
+    pub query: String,
+    pub target_price: f64,
+    #[serde(default)]
+    pub platforms: Vec<String>,
+}
+
+/// What `POST /import` added, and why anything was left out
+#[derive(Debug, Serialize, Default)]
+pub struct ImportSummary {
+    /// Whether the file's account settings were applied
+    pub preferences: bool,
+    pub channels: usize,
+    pub templates: usize,
+    pub alerts: usize,
+    pub price_points: usize,
+    pub products: usize,
+    pub listing_alerts: usize,
+    pub watches: usize,
+    pub skipped: Vec<String>,
+}
+
+/// Alerts for the same item on different platforms, grouped by the user
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Product {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProductRequest {
+    pub name: String,
+    pub alert_ids: Vec<Uuid>,
+}
+
+/// A platform's lowest price for a product on one day
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct DailyPlatformPrice {
+    pub platform: String,
+    pub day: DateTime<Utc>,
+    pub price: f64,
+}
+
+/// One day of a product's price across everyone tracking it, for the public chart.
+/// Only the day's spread; nothing identifies the alerts or users behind it.
+#[derive(Debug, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct PublicDailyPrice {
+    pub day: DateTime<Utc>,
+    pub low: f64,
+    pub high: f64,
+    pub average: f64,
+}
+
+/// Site-wide totals for the landing page (`GET /stats/public`), recomputed periodically.
+/// Alerts of users who opted out of public aggregates, and suspect prices, aren't counted.
+#[derive(Debug, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct PublicStats {
+    /// Distinct products (by platform product ID, else URL) with an active alert
+    pub products_tracked: i64,
+    /// Price drops detected in the 7 days before `updated_at`
+    pub drops_this_week: i64,
+    /// How far those drops were below the price before them on average, in rupees
+    pub average_saving: Option<f64>,
+    /// The same, in percent of the price before
+    pub average_saving_percent: Option<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PlatformSummary {
+    pub platform: String,
+    pub latest_price: f64,
+    pub lowest_price: f64,
+    pub average_price: f64,
+    /// Days with a price on this platform
+    pub days: usize,
+    /// Compared days on which this platform was strictly the cheapest
+    pub days_cheapest: usize,
+    /// Average gap to the next cheapest platform on those days
+    pub average_saving: Option<f64>,
+}
+
+/// Which platform has historically been cheaper for a product, and by how much.
+/// Only days with prices from at least two platforms count as compared.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PlatformComparison {
+    pub days_compared: usize,
+    pub cheapest_platform: Option<String>,
+    pub platforms: Vec<PlatformSummary>,
+}
+
+impl PlatformComparison {
+    /// `prices` must be ordered by day, as `Database::get_product_daily_prices` returns them
+    pub fn from_daily(prices: &[DailyPlatformPrice]) -> Self {
+        let mut by_day: Vec<(DateTime<Utc>, Vec<&DailyPlatformPrice>)> = Vec::new();
+        for price in prices {
+            match by_day.last_mut() {
+                Some((day, entries)) if *day == price.day => entries.push(price),
+                _ => by_day.push((price.day, vec![price])),
+            }
+        }
+
+        let mut days_cheapest: HashMap<&str, (usize, f64)> = HashMap::new();
+        let mut days_compared = 0;
+        for (_, entries) in &by_day {
+            if entries.len() < 2 {
+                continue;
+            }
+            days_compared += 1;
+
+            let mut sorted = entries.clone();
+            sorted.sort_by(|a, b| a.price.total_cmp(&b.price));
+            let saving = sorted[1].price - sorted[0].price;
+            // A tie isn't a win for anyone
+            if saving > 0.0 {
+                let entry = days_cheapest.entry(sorted[0].platform.as_str()).or_default();
+                entry.0 += 1;
+                entry.1 += saving;
+            }
+        }
+
+        let mut platforms: Vec<PlatformSummary> = Vec::new();
+        let mut names: Vec<&str> = prices.iter().map(|p| p.platform.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        for name in names {
+            let own: Vec<f64> = prices.iter().filter(|p| p.platform == name).map(|p| p.price).collect();
+            let (wins, total_saving) = days_cheapest.get(name).copied().unwrap_or_default();
+            platforms.push(PlatformSummary {
+                platform: name.to_string(),
+                latest_price: own[own.len() - 1],
+                lowest_price: own.iter().copied().fold(f64::INFINITY, f64::min),
+                average_price: round2(own.iter().sum::<f64>() / own.len() as f64),
+                days: own.len(),
+                days_cheapest: wins,
+                average_saving: (wins > 0).then(|| round2(total_saving / wins as f64)),
+            });
+        }
+
+        let cheapest_platform = platforms
+            .iter()
+            .filter(|p| p.days_cheapest > 0)
+            .max_by_key(|p| p.days_cheapest)
+            .map(|p| p.platform.clone());
+
+        PlatformComparison { days_compared, cheapest_platform, platforms }
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// Per-platform scrape health over a time window (admin dashboard)
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ScrapeStats {
+    pub platform: String,
+    pub attempts: i64,
+    pub successes: i64,
+    pub failures: i64,
+    pub avg_duration_ms: Option<f64>,
+    pub p95_duration_ms: Option<f64>,
+}
+
+// A platform whose checks are spread out because too many of them failed; see `throttle`
+#[derive(Debug, Serialize, Clone, PartialEq, sqlx::FromRow)]
+pub struct PlatformThrottle {
+    pub platform: String,
+    /// Its alerts are checked every `factor` rounds; 1 is every round
+    pub factor: i32,
+    pub adjusted_at: DateTime<Utc>,
+    /// Rounds before this leave the platform's alerts alone
+    pub next_check_at: Option<DateTime<Utc>>,
+}
+
+impl PlatformThrottle {
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_check_at.is_none_or(|at| now >= at)
+    }
+}
+
+// One change of a platform's throttle, with the numbers behind it (admin dashboard)
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ThrottleAdjustment {
+    pub platform: String,
+    pub from_factor: i32,
+    pub to_factor: i32,
+    /// Attempts and failures (missing pages aside) since the previous adjustment
+    pub attempts: i64,
+    pub failures: i64,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// How far an unfinished check run got: the last alert of its last completed chunk,
+// in the run's `created_at DESC, id DESC` order
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct CheckCheckpoint {
+    pub run_started_at: DateTime<Utc>,
+    pub after_created_at: DateTime<Utc>,
+    pub after_id: Uuid,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CheckCheckpoint {
+    /// Whether `alert` comes after the checkpoint, i.e. still has to be checked this run
+    pub fn is_ahead(&self, alert: &PriceAlert) -> bool {
+        (alert.created_at, alert.id.unwrap_or_default()) < (self.after_created_at, self.after_id)
+    }
+}
+
+// A notification waiting in `notification_outbox`, written together with the change
+// that caused it
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OutboxEntry {
+    pub id: Uuid,
+    pub event: sqlx::types::Json<crate::notifier_trait::NotificationEvent>,
+    /// The alert's `notify_channels` when it was written
+    pub channels: Option<Vec<Uuid>>,
+    /// Claims so far, the one being worked on included
+    pub attempts: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+// Per-user notification destination (email address, webhook URL, topic...)
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct NotificationChannel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub channel: String,
+    pub destination: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateChannelRequest {
+    pub channel: String,
+    pub destination: String,
+}
+
+// Per-platform scraping overrides, editable through the admin API
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct ScraperConfig {
+    pub platform: String,
+    /// CSS selectors tried (in order) before the scraper's built-in ones
+    pub selectors: Vec<String>,
+    /// Regexes whose first capture group holds the price text
+    pub price_patterns: Vec<String>,
+    /// Extra request headers, replacing the defaults with the same name
+    pub headers: sqlx::types::Json<HashMap<String, String>>,
+    /// Whole-request timeout for the platform's pages
+    pub timeout_secs: Option<i32>,
+    /// How long to wait for the platform's servers to accept a connection
+    pub connect_timeout_secs: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A platform sitemap the discovery job reads product links from, managed through
+/// `/admin/sitemaps`
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct SitemapSource {
+    pub id: Uuid,
+    pub platform: String,
+    /// A `<urlset>`, or a `<sitemapindex>` whose first few sitemaps are read; may be gzipped
+    pub url: String,
+    /// Product links kept from each read, highest sitemap `<priority>` first
+    pub max_products: i32,
+    pub created_at: DateTime<Utc>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    /// Product links kept by the last read
+    pub last_discovered: Option<i32>,
+    pub last_error: Option<String>,
+    /// Catalog products currently checked because of it
+    pub products: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSitemapSourceRequest {
+    pub url: String,
+    pub max_products: Option<i32>,
+}
+
+/// A product found through a sitemap and checked on a schedule, so alerts created on it
+/// start with history
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct CatalogProduct {
+    pub id: Uuid,
+    pub platform: String,
+    pub product_id: String,
+    pub url: String,
+    pub source_id: Option<Uuid>,
+    pub priority: f64,
+    pub active: bool,
+    pub discovered_at: DateTime<Utc>,
+    pub last_checked: Option<DateTime<Utc>>,
+    pub last_price: Option<f64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpsertScraperConfigRequest {
+    #[serde(default)]
+    pub selectors: Vec<String>,
+    #[serde(default)]
+    pub price_patterns: Vec<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    pub timeout_secs: Option<i32>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<i32>,
+}
+
 // Auth request/response models
 #[derive(Debug, Deserialize)]
 pub struct SignupRequest {
     pub email: String,
     pub password: String,
+    /// Terms version the user was shown; signing up accepts the live version, so a stale one is refused
+    #[serde(default)]
+    pub terms_version: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptTermsRequest {
+    pub version: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -98,5 +1412,359 @@ pub struct UserResponse {
     pub id: String,
     pub email: String,
     pub created_at: DateTime<Utc>,
+    pub plan: Plan,
+    pub limits: PlanLimits,
+    pub timezone: String,
+    pub notifications_paused: bool,
+    pub default_discount_percent: Option<f64>,
+    pub role: Role,
+    pub email_verified: bool,
+    pub exclude_from_public: bool,
+    pub terms_version: Option<String>,
+    pub terms_accepted_at: Option<DateTime<Utc>>,
+    /// The live terms changed since they were last accepted
+    pub terms_reaccept_required: bool,
+    pub weekly_summary: bool,
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        let plan = Plan::parse(&user.plan);
+        UserResponse {
+            id: user.id.to_string(),
+            email: user.email,
+            created_at: user.created_at,
+            plan,
+            limits: plan.limits(),
+            timezone: user.timezone,
+            notifications_paused: user.notifications_paused,
+            default_discount_percent: user.default_discount_percent,
+            role: Role::parse(&user.role),
+            email_verified: user.email_verified_at.is_some(),
+            exclude_from_public: user.exclude_from_public,
+            terms_reaccept_required: Terms::from_env().needs_acceptance(user.terms_version.as_deref()),
+            terms_version: user.terms_version,
+            terms_accepted_at: user.terms_accepted_at,
+            weekly_summary: user.weekly_summary,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetMaintenanceRequest {
+    pub enabled: bool,
+    /// Shown to clients whose writes are rejected; a generic notice when omitted
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPlanRequest {
+    pub plan: Plan,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRoleRequest {
+    pub role: Role,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTimezoneRequest {
+    /// IANA zone name, e.g. "Asia/Kolkata"
+    pub timezone: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetNotificationsPausedRequest {
+    pub paused: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPrivacyRequest {
+    pub exclude_from_public: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetWeeklySummaryRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetDefaultDiscountRequest {
+    /// e.g. 20 for "alert me at 20% off"; null turns the default off
+    pub percent: Option<f64>,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_cursor_round_trip() {
+        let cursor = HistoryCursor {
+            checked_at: DateTime::parse_from_rfc3339("2026-10-16T09:12:03.123456Z").unwrap().with_timezone(&Utc),
+            id: Uuid::new_v4(),
+        };
+        assert_eq!(HistoryCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(HistoryCursor::decode("not a cursor"), None);
+        assert_eq!(HistoryCursor::decode(&BASE64.encode("123:not-a-uuid")), None);
+    }
+
+    fn item(title: &str, price: f64) -> ListingItem {
+        ListingItem {
+            title: title.to_string(),
+            url: format!("https://www.myntra.com/{}", title.to_lowercase().replace(' ', "-")),
+            price,
+            location: None,
+        }
+    }
+
+    #[test]
+    fn test_listing_alert_new_matches() {
+        let mut alert = ListingAlert {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            user_email: "buyer@example.com".to_string(),
+            platform: "myntra".to_string(),
+            url: "https://www.myntra.com/formal-shirts?f=Color%3ABlack".to_string(),
+            target_price: 1500.0,
+            max_results: 3,
+            include_terms: vec!["Black".to_string()],
+            exclude_terms: vec!["slim".to_string()],
+            notified_urls: vec![],
+            is_active: true,
+            created_at: Utc::now(),
+            last_checked: None,
+        };
+        let items = vec![
+            item("Men Black Formal Shirt", 1299.0),
+            item("Men Black Slim Fit Shirt", 999.0),
+            item("Men Black Oxford Shirt", 1799.0),
+            item("Men Black Linen Formal Shirt", 899.0),
+        ];
+
+        // The fourth result is outside max_results
+        assert_eq!(alert.matching(&items), vec![&items[0], &items[2]]);
+        let matches = alert.new_matches(&items);
+        assert_eq!(matches, vec![&items[0]]);
+
+        alert.notified_urls.push(items[0].url.clone());
+        assert!(alert.new_matches(&items).is_empty());
+    }
+
+    #[test]
+    fn test_effective_price_uses_best_offer() {
+        let offer = |discount| Offer {
+            kind: "bank".to_string(),
+            description: "10% off on HDFC cards".to_string(),
+            code: None,
+            discount,
+        };
+
+        assert_eq!(effective_price(1999.0, &[offer(Some(150.0)), offer(None), offer(Some(300.0))]), Some(1699.0));
+        assert_eq!(effective_price(1999.0, &[offer(None)]), None);
+        assert_eq!(effective_price(100.0, &[offer(Some(300.0))]), Some(0.0));
+    }
+
+    #[test]
+    fn test_price_alert_is_restock() {
+        let mut alert = PriceAlert {
+            id: None,
+            url: "https://www.myntra.com/12345678".to_string(),
+            target_price: 0.0,
+            last_price: None,
+            user_email: "buyer@example.com".to_string(),
+            user_id: None,
+            platform: "myntra".to_string(),
+            created_at: Utc::now(),
+            last_checked: Utc::now(),
+            is_active: true,
+            notify_channels: None,
+            product_id: None,
+            alert_mode: AlertMode::BackInStock.as_str().to_string(),
+            in_stock: None,
+            offers: sqlx::types::Json(vec![]),
+            version: 1,
+            check_interval_minutes: None,
+            expires_at: None,
+            renotify: Default::default(),
+            min_price: None,
+            max_price: None,
+            product_removed_at: None,
+            product_name: None,
+            image_url: None,
+            notifications_paused: false,
+        };
+
+        // Unknown -> in stock is the first check, not a restock
+        assert!(!alert.is_restock(true));
+
+        alert.in_stock = Some(false);
+        assert!(!alert.is_restock(false));
+        assert!(alert.is_restock(true));
+
+        alert.in_stock = Some(true);
+        assert!(!alert.is_restock(true));
+
+        alert.min_price = Some(500.0);
+        alert.max_price = Some(5000.0);
+        assert!(alert.in_bounds(500.0) && alert.in_bounds(5000.0));
+        assert!(!alert.in_bounds(5.0) && !alert.in_bounds(50000.0));
+    }
+
+    #[test]
+    fn test_renotify_policy() {
+        let now = Utc::now();
+        let last = PriceDropEvent {
+            id: Uuid::new_v4(),
+            alert_id: Uuid::new_v4(),
+            user_id: None,
+            history_id: None,
+            price: 900.0,
+            target_price: 999.0,
+            previous_price: Some(1299.0),
+            badge: None,
+            created_at: now - chrono::Duration::days(3),
+        };
+        let notifies = |policy: RenotifyPolicy, previous: Option<f64>, price: f64| {
+            policy.should_notify(Some(&last), previous, 999.0, price, now)
+        };
+
+        assert!(notifies(RenotifyPolicy::Always, Some(900.0), 900.0));
+        assert!(!notifies(RenotifyPolicy::Never, Some(1299.0), 800.0));
+        assert!(!notifies(RenotifyPolicy::FurtherDrop { percent: 10.0 }, Some(900.0), 820.0));
+        assert!(notifies(RenotifyPolicy::FurtherDrop { percent: 10.0 }, Some(900.0), 800.0));
+        assert!(notifies(RenotifyPolicy::AfterDays { days: 3 }, Some(900.0), 900.0));
+        assert!(!notifies(RenotifyPolicy::AfterDays { days: 4 }, Some(900.0), 900.0));
+        assert!(!notifies(RenotifyPolicy::Rearm, Some(950.0), 900.0));
+        assert!(notifies(RenotifyPolicy::Rearm, Some(1099.0), 900.0));
+
+        // The first drop, or the first since the target changed, always notifies
+        assert!(RenotifyPolicy::Never.should_notify(None, None, 999.0, 900.0, now));
+        assert!(RenotifyPolicy::Never.should_notify(Some(&last), Some(900.0), 949.0, 900.0, now));
+
+        let policies = [
+            RenotifyPolicy::Always,
+            RenotifyPolicy::Never,
+            RenotifyPolicy::FurtherDrop { percent: 10.0 },
+            RenotifyPolicy::AfterDays { days: 3 },
+            RenotifyPolicy::Rearm,
+        ];
+        let tags: Vec<_> = policies.iter().map(|p| serde_json::to_value(p).unwrap()["policy"].as_str().unwrap().to_string()).collect();
+        assert_eq!(tags, RenotifyPolicy::NAMES);
+
+        let policy: RenotifyPolicy = serde_json::from_str(r#"{"policy": "further_drop", "percent": 5}"#).unwrap();
+        assert_eq!(policy, RenotifyPolicy::FurtherDrop { percent: 5.0 });
+        assert!(RenotifyPolicy::FurtherDrop { percent: 0.0 }.validate().is_err());
+        assert!(RenotifyPolicy::AfterDays { days: 0 }.validate().is_err());
+        assert!(RenotifyPolicy::Rearm.validate().is_ok());
+    }
+
+    #[test]
+    fn test_price_anomaly() {
+        assert!(is_price_anomaly(3.0, 3499.0, 5.0));
+        assert!(is_price_anomaly(34990.0, 3499.0, 5.0));
+        // A real sale, even a steep one, gets through
+        assert!(!is_price_anomaly(1299.0, 3499.0, 5.0));
+        assert!(!is_price_anomaly(3.0, 3499.0, 0.0));
+    }
+
+    #[test]
+    fn test_price_badge() {
+        let lows = PriceLows { all_time_low: Some(899.0), recent_low: Some(999.0) };
+        assert_eq!(lows.badge(850.0), Some(PriceBadge::LowestEver));
+        assert_eq!(lows.badge(899.0), Some(PriceBadge::LowestEver));
+        assert_eq!(lows.badge(950.0), Some(PriceBadge::Lowest90Days));
+        assert_eq!(lows.badge(1099.0), None);
+
+        // Nothing checked in the last 90 days
+        let stale = PriceLows { all_time_low: Some(899.0), recent_low: None };
+        assert_eq!(stale.badge(950.0), None);
+
+        assert_eq!(PriceLows::default().badge(500.0), None);
+
+        // Stored as text in price_drop_events, same spelling as the JSON
+        for badge in [PriceBadge::LowestEver, PriceBadge::Lowest90Days] {
+            assert_eq!(serde_json::to_value(badge).unwrap(), badge.as_str());
+            assert_eq!(PriceBadge::parse(badge.as_str()), Some(badge));
+        }
+    }
+
+    #[test]
+    fn test_platform_comparison() {
+        let day = |n: i64| DateTime::<Utc>::from_timestamp(1_700_000_000 + n * 86_400, 0).unwrap();
+        let price = |platform: &str, n: i64, price: f64| DailyPlatformPrice {
+            platform: platform.to_string(),
+            day: day(n),
+            price,
+        };
+        let prices = vec![
+            price("ajio", 0, 1000.0),
+            price("myntra", 0, 900.0),
+            price("ajio", 1, 950.0),
+            price("myntra", 1, 950.0),
+            price("ajio", 2, 800.0),
+            price("myntra", 2, 1000.0),
+            price("myntra", 3, 700.0),
+            price("ajio", 4, 1100.0),
+            price("myntra", 4, 1000.0),
+        ];
+
+        let comparison = PlatformComparison::from_daily(&prices);
+        // Day 3 only has Myntra; day 1 is a tie
+        assert_eq!(comparison.days_compared, 4);
+        assert_eq!(comparison.cheapest_platform.as_deref(), Some("myntra"));
+
+        let myntra = &comparison.platforms[1];
+        assert_eq!(myntra.platform, "myntra");
+        assert_eq!(myntra.days_cheapest, 2);
+        assert_eq!(myntra.average_saving, Some(100.0));
+        assert_eq!(myntra.lowest_price, 700.0);
+        assert_eq!(myntra.latest_price, 1000.0);
+        assert_eq!(myntra.days, 5);
+
+        let ajio = &comparison.platforms[0];
+        assert_eq!(ajio.days_cheapest, 1);
+        assert_eq!(ajio.average_saving, Some(200.0));
+        assert_eq!(ajio.average_price, 962.5);
+
+        let empty = PlatformComparison::from_daily(&[]);
+        assert_eq!(empty.days_compared, 0);
+        assert_eq!(empty.cheapest_platform, None);
+        assert!(empty.platforms.is_empty());
+    }
+
+    #[test]
+    fn test_target_distance() {
+        let distance = TargetDistance::new(1099.0, 999.0).unwrap();
+        assert_eq!(distance.amount, 100.0);
+        assert_eq!(distance.percent, 10.0);
+
+        let below = TargetDistance::new(899.0, 1000.0).unwrap();
+        assert_eq!(below.amount, -101.0);
+        assert_eq!(below.percent, -10.1);
+
+        assert_eq!(TargetDistance::new(899.0, 0.0), None);
+    }
+
+    #[test]
+    fn test_savings_report() {
+        let purchase = |price: f64, initial_price: Option<f64>, target_price: f64| Purchase {
+            id: Uuid::new_v4(),
+            alert_id: Uuid::new_v4(),
+            user_id: None,
+            price,
+            purchased_at: Utc::now(),
+            initial_price,
+            target_price,
+            created_at: Utc::now(),
+            url: "https://www.myntra.com/12345678".to_string(),
+            platform: "myntra".to_string(),
+        };
+
+        let report = SavingsReport::from_purchases(&[purchase(799.0, Some(1299.0), 999.0), purchase(1100.0, None, 1000.0)]);
+        assert_eq!(report, SavingsReport { purchases: 2, total_paid: 1899.0, saved_vs_initial: 500.0, saved_vs_target: 100.0 });
+        assert_eq!(SavingsReport::from_purchases(&[]).total_paid, 0.0);
+    }
+}
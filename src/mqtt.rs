@@ -0,0 +1,103 @@
+use rumqttc::{AsyncClient, MqttOptions, QoS, Transport};
+use std::time::Duration;
+use uuid::Uuid;
+use crate::config::MqttConfig;
+use crate::event_schema::Versioned;
+use crate::events::LiveEvent;
+use crate::notifier_trait::NotificationEvent;
+
+/// Publishes price checks and drop events to an MQTT broker (Home Assistant, Node-RED, ...).
+///
+/// Messages are the same versioned JSON as `GET /events`. Price topics are retained so a
+/// subscriber that connects later still gets each alert's latest price; drops are not.
+#[derive(Clone)]
+pub struct MqttPublisher {
+    client: AsyncClient,
+    price_topic: String,
+    drop_topic: String,
+}
+
+impl MqttPublisher {
+    /// Messages queued while the broker is unreachable before new ones are dropped
+    const CAPACITY: usize = 100;
+
+    /// Spawns the event loop that keeps the connection alive, reconnecting on errors
+    pub fn connect(config: &MqttConfig) -> Self {
+        let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+        options.set_keep_alive(Duration::from_secs(30));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+        if config.tls {
+            options.set_transport(Transport::tls_with_default_config());
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(options, Self::CAPACITY);
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = eventloop.poll().await {
+                    tracing::warn!("MQTT connection error, retrying: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+        });
+
+        MqttPublisher {
+            client,
+            price_topic: config.price_topic.clone(),
+            drop_topic: config.drop_topic.clone(),
+        }
+    }
+
+    /// Forward `event` if it's a price check or a price drop; other events aren't published
+    pub fn publish(&self, event: &LiveEvent) {
+        let (template, alert_id, platform, retain) = match event {
+            LiveEvent::PriceChecked(checked) => (&self.price_topic, checked.alert_id, &checked.platform, true),
+            LiveEvent::Notification(NotificationEvent::PriceDrop(drop)) => match drop.alert_id {
+                Some(alert_id) => (&self.drop_topic, alert_id, &drop.platform, false),
+                // Test notifications have no alert to publish under
+                None => return,
+            },
+            _ => return,
+        };
+
+        let payload = match serde_json::to_vec(&Versioned::new(event)) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::warn!("Failed to serialize MQTT payload: {}", e);
+                return;
+            }
+        };
+        let topic = render_topic(template, alert_id, platform);
+        // Never block the worker on the broker; a full queue drops the message
+        if let Err(e) = self.client.try_publish(&topic, QoS::AtLeastOnce, retain, payload) {
+            tracing::warn!("Failed to publish to MQTT topic {}: {}", topic, e);
+        }
+    }
+}
+
+/// Fill in `{alert_id}` and `{platform}` in a topic template
+fn render_topic(template: &str, alert_id: Uuid, platform: &str) -> String {
+    template
+        .replace("{alert_id}", &alert_id.to_string())
+        .replace("{platform}", platform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_topic() {
+        let alert_id = Uuid::new_v4();
+        assert_eq!(
+            render_topic("price_tracker/{alert_id}/price", alert_id, "myntra"),
+            format!("price_tracker/{}/price", alert_id)
+        );
+        assert_eq!(
+            render_topic("home/{platform}/{alert_id}", alert_id, "ajio"),
+            format!("home/ajio/{}", alert_id)
+        );
+        assert_eq!(render_topic("prices", alert_id, "ajio"), "prices");
+    }
+}
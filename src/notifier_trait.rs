@@ -0,0 +1,185 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::models::{ListingItem, Offer, PriceAlert, PriceBadge, PriceDropEvent};
+
+/// Something worth telling a user about
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationEvent {
+    PriceDrop(PriceDrop),
+    ListingMatch(ListingMatch),
+    KeywordMatch(KeywordMatch),
+    BackInStock(BackInStock),
+    ProductRemoved(ProductRemoved),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceDrop {
+    pub alert_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub user_email: String,
+    pub url: String,
+    pub platform: String,
+    pub current_price: f64,
+    pub target_price: f64,
+    pub previous_price: Option<f64>,
+    /// Coupons/bank offers seen on the page at the time of the drop
+    pub offers: Vec<Offer>,
+    /// Set when the price is the lowest recorded (ever or in the last 90 days)
+    pub badge: Option<PriceBadge>,
+    /// Name and picture from the product page, when the scraper found them
+    #[serde(default)]
+    pub product_name: Option<String>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+}
+
+impl PriceDrop {
+    /// Notification for a drop recorded in `price_drop_events`
+    pub fn recorded(alert: &PriceAlert, event: &PriceDropEvent, offers: Vec<Offer>) -> Self {
+        PriceDrop {
+            alert_id: Some(event.alert_id),
+            user_id: event.user_id,
+            user_email: alert.user_email.clone(),
+            url: alert.url.clone(),
+            platform: alert.platform.clone(),
+            current_price: event.price,
+            target_price: event.target_price,
+            previous_price: event.previous_price,
+            offers,
+            badge: event.badge.as_deref().and_then(PriceBadge::parse),
+            product_name: alert.product_name.clone(),
+            image_url: alert.image_url.clone(),
+        }
+    }
+}
+
+/// New results under target on a watched search/category page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingMatch {
+    pub listing_alert_id: Uuid,
+    pub user_id: Uuid,
+    pub user_email: String,
+    /// The watched search/category page
+    pub url: String,
+    pub platform: String,
+    pub target_price: f64,
+    pub items: Vec<ListingItem>,
+}
+
+/// New results under target for a keyword watch, one event per platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordMatch {
+    pub watch_id: Uuid,
+    pub user_id: Uuid,
+    pub user_email: String,
+    pub query: String,
+    pub platform: String,
+    /// The platform search page the results came from
+    pub search_url: String,
+    pub target_price: f64,
+    pub items: Vec<ListingItem>,
+}
+
+/// A `back_in_stock` alert's product can be bought again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackInStock {
+    pub alert_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub user_email: String,
+    pub url: String,
+    pub platform: String,
+    /// Price at restock, when the page shows one
+    pub current_price: Option<f64>,
+}
+
+/// An alert's page has been answering 404/410, so the product looks delisted and
+/// checks have stopped until the URL is updated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductRemoved {
+    pub alert_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub user_email: String,
+    pub url: String,
+    pub platform: String,
+    /// Price at the last successful check
+    pub last_price: Option<f64>,
+}
+
+impl NotificationEvent {
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            NotificationEvent::PriceDrop(drop) => drop.user_id,
+            NotificationEvent::ListingMatch(listing) => Some(listing.user_id),
+            NotificationEvent::KeywordMatch(keyword) => Some(keyword.user_id),
+            NotificationEvent::BackInStock(restock) => restock.user_id,
+            NotificationEvent::ProductRemoved(removed) => removed.user_id,
+        }
+    }
+
+    /// The price alert the event is about, if any
+    pub fn alert_id(&self) -> Option<Uuid> {
+        match self {
+            NotificationEvent::PriceDrop(drop) => drop.alert_id,
+            NotificationEvent::BackInStock(restock) => restock.alert_id,
+            NotificationEvent::ProductRemoved(removed) => removed.alert_id,
+            NotificationEvent::ListingMatch(_) | NotificationEvent::KeywordMatch(_) => None,
+        }
+    }
+
+    /// SSE event name, matching the serialized `type` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotificationEvent::PriceDrop(_) => "price_drop",
+            NotificationEvent::ListingMatch(_) => "listing_match",
+            NotificationEvent::KeywordMatch(_) => "keyword_match",
+            NotificationEvent::BackInStock(_) => "back_in_stock",
+            NotificationEvent::ProductRemoved(_) => "product_removed",
+        }
+    }
+
+    /// Address used when the user hasn't configured any channels
+    pub fn user_email(&self) -> &str {
+        match self {
+            NotificationEvent::PriceDrop(drop) => &drop.user_email,
+            NotificationEvent::ListingMatch(listing) => &listing.user_email,
+            NotificationEvent::KeywordMatch(keyword) => &keyword.user_email,
+            NotificationEvent::BackInStock(restock) => &restock.user_email,
+            NotificationEvent::ProductRemoved(removed) => &removed.user_email,
+        }
+    }
+}
+
+
+/// Trait for notification channels (email, webhooks, push services)
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Deliver an event to a channel-specific destination (address, webhook URL, topic).
+    /// Any times shown in the message are rendered in `timezone`, the recipient's;
+    /// chat apps stamp messages in the reader's own zone, so their notifiers ignore it.
+    async fn notify(&self, destination: &str, event: &NotificationEvent, timezone: Tz) -> Result<()>;
+    
+    /// Get the channel name
+    fn channel_name(&self) -> &'static str;
+    
+    /// Validate a destination before it is saved for a user
+    fn validate_destination(&self, destination: &str) -> Result<()>;
+    
+    /// Deliver notifications held back by the daily send budget. Sends them one by one
+    /// unless the channel can bundle them into a single message.
+    async fn notify_digest(&self, destination: &str, events: &[NotificationEvent], timezone: Tz) -> Result<()> {
+        for event in events {
+            self.notify(destination, event, timezone).await?;
+        }
+        Ok(())
+    }
+    
+    /// True when `notify` only hands the message to a queue, which then records
+    /// how delivery went itself
+    fn queued(&self) -> bool {
+        false
+    }
+}
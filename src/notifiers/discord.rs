@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use anyhow::{Result, anyhow};
+use chrono_tz::Tz;
+use reqwest::Client;
+use serde_json::{json, Value};
+use crate::models::ListingItem;
+use crate::notifier_trait::{NotificationEvent, Notifier};
+
+/// Posts rich embeds to a Discord incoming webhook
+pub struct DiscordNotifier {
+    client: Client,
+}
+
+impl DiscordNotifier {
+    pub fn new() -> Self {
+        DiscordNotifier { client: Client::new() }
+    }
+
+    fn payload(event: &NotificationEvent) -> Value {
+        match event {
+            NotificationEvent::PriceDrop(drop) => {
+                let mut fields = vec![
+                    json!({ "name": "Now", "value": format!("₹{:.0}", drop.current_price), "inline": true }),
+                    json!({ "name": "Target", "value": format!("₹{:.0}", drop.target_price), "inline": true }),
+                ];
+                if let Some(previous) = drop.previous_price {
+                    fields.insert(0, json!({ "name": "Was", "value": format!("~~₹{:.0}~~", previous), "inline": true }));
+                }
+
+                let headline = format!("Price drop on {}", drop.platform.to_uppercase());
+                let mut embed = json!({
+                    "title": match &drop.product_name {
+                        Some(name) => format!("🚨 {}", name),
+                        None => format!("🚨 {}", headline),
+                    },
+                    "url": drop.url,
+                    "description": match drop.badge {
+                        Some(badge) => format!("🏆 {} · Save ₹{:.0} against your target", badge.label(), drop.target_price - drop.current_price),
+                        None => format!("Save ₹{:.0} against your target", drop.target_price - drop.current_price),
+                    },
+                    "color": 0x10b981,
+                    "fields": fields,
+                    "footer": { "text": "Clothing Price Tracker" }
+                });
+                // With the product named in the title, the platform moves above it
+                if drop.product_name.is_some() {
+                    embed["author"] = json!({ "name": headline });
+                }
+                if let Some(image_url) = &drop.image_url {
+                    embed["thumbnail"] = json!({ "url": image_url });
+                }
+
+                json!({
+                    "username": "Price Tracker",
+                    "embeds": [embed]
+                })
+            }
+            NotificationEvent::ListingMatch(listing) => Self::results_payload(
+                format!("🔎 {} result(s) under ₹{:.0} on {}", listing.items.len(), listing.target_price, listing.platform.to_uppercase()),
+                &listing.url,
+                &listing.items,
+            ),
+            NotificationEvent::KeywordMatch(keyword) => Self::results_payload(
+                format!("🔎 \"{}\" under ₹{:.0} on {}", keyword.query, keyword.target_price, keyword.platform.to_uppercase()),
+                &keyword.search_url,
+                &keyword.items,
+            ),
+            NotificationEvent::BackInStock(restock) => json!({
+                "username": "Price Tracker",
+                "embeds": [{
+                    "title": format!("📦 Back in stock on {}", restock.platform.to_uppercase()),
+                    "url": restock.url,
+                    "description": match restock.current_price {
+                        Some(price) => format!("Available again at ₹{:.0}", price),
+                        None => "Available again".to_string(),
+                    },
+                    "color": 0xf59e0b,
+                    "footer": { "text": "Clothing Price Tracker" }
+                }]
+            }),
+            NotificationEvent::ProductRemoved(removed) => json!({
+                "username": "Price Tracker",
+                "embeds": [{
+                    "title": format!("🚫 Product removed from {}", removed.platform.to_uppercase()),
+                    "url": removed.url,
+                    "description": "The page is gone, so checks have stopped. Update the alert's link to keep tracking it.",
+                    "color": 0x6b7280,
+                    "footer": { "text": "Clothing Price Tracker" }
+                }]
+            }),
+        }
+    }
+
+    /// One embed linking to the results page, with a line per matching product
+    fn results_payload(title: String, url: &str, items: &[ListingItem]) -> Value {
+        let lines: Vec<String> = items
+            .iter()
+            .map(|item| format!("[{}]({}) — ₹{:.0}", item.title, item.url, item.price))
+            .collect();
+
+        json!({
+            "username": "Price Tracker",
+            "embeds": [{
+                "title": title,
+                "url": url,
+                "description": lines.join("\n"),
+                "color": 0x6366f1,
+                "footer": { "text": "Clothing Price Tracker" }
+            }]
+        })
+    }
+}
+
+impl Default for DiscordNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    async fn notify(&self, destination: &str, event: &NotificationEvent, _timezone: Tz) -> Result<()> {
+        self.client
+            .post(destination)
+            .json(&Self::payload(event))
+            .send()
+            .await?
+            .error_for_status()?;
+        
+        Ok(())
+    }
+    
+    fn channel_name(&self) -> &'static str {
+        "discord"
+    }
+    
+    fn validate_destination(&self, destination: &str) -> Result<()> {
+        if destination.starts_with("https://discord.com/api/webhooks/")
+            || destination.starts_with("https://discordapp.com/api/webhooks/")
+        {
+            Ok(())
+        } else {
+            Err(anyhow!("Discord destination must be an incoming webhook URL"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier_trait::PriceDrop;
+    use mockito::Server;
+
+    fn sample_event() -> NotificationEvent {
+        NotificationEvent::PriceDrop(PriceDrop {
+            alert_id: None,
+            user_id: None,
+            user_email: "buyer@example.com".to_string(),
+            url: "https://www.flipkart.com/product/p/abc123".to_string(),
+            platform: "flipkart".to_string(),
+            current_price: 1199.0,
+            target_price: 1299.0,
+            previous_price: Some(1499.0),
+            offers: vec![],
+            badge: None,
+            product_name: None,
+            image_url: None,
+        })
+    }
+
+    #[test]
+    fn test_discord_payload_embed() {
+        let payload = DiscordNotifier::payload(&sample_event());
+        let embed = &payload["embeds"][0];
+
+        assert_eq!(embed["title"], "🚨 Price drop on FLIPKART");
+        assert_eq!(embed["url"], "https://www.flipkart.com/product/p/abc123");
+        assert_eq!(embed["fields"][0]["value"], "~~₹1499~~");
+        assert_eq!(embed["fields"][1]["value"], "₹1199");
+    }
+
+    #[test]
+    fn test_discord_payload_names_product() {
+        let NotificationEvent::PriceDrop(drop) = sample_event() else { unreachable!() };
+        let event = NotificationEvent::PriceDrop(PriceDrop {
+            product_name: Some("Levi's 511 Slim Jeans".to_string()),
+            image_url: Some("https://rukminim1.flixcart.com/image/jeans.jpeg".to_string()),
+            ..drop
+        });
+        let payload = DiscordNotifier::payload(&event);
+        let embed = &payload["embeds"][0];
+
+        assert_eq!(embed["title"], "🚨 Levi's 511 Slim Jeans");
+        assert_eq!(embed["author"]["name"], "Price drop on FLIPKART");
+        assert_eq!(embed["thumbnail"]["url"], "https://rukminim1.flixcart.com/image/jeans.jpeg");
+        assert!(DiscordNotifier::payload(&sample_event())["embeds"][0].get("thumbnail").is_none());
+    }
+
+    #[test]
+    fn test_discord_validate_destination() {
+        let notifier = DiscordNotifier::new();
+
+        assert!(notifier.validate_destination("https://discord.com/api/webhooks/123/abc").is_ok());
+        assert!(notifier.validate_destination("https://example.com/hook").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_discord_notify_posts_embed() {
+        let mut server = Server::new_async().await;
+        let _m = server.mock("POST", "/webhook")
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::Regex("FLIPKART".to_string()))
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let notifier = DiscordNotifier::new();
+        let url = format!("{}/webhook", server.url());
+
+        assert!(notifier.notify(&url, &sample_event(), Tz::UTC).await.is_ok());
+    }
+}
@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use anyhow::{Result, anyhow};
+use chrono::Utc;
+use chrono_tz::Tz;
+use crate::email::{EmailQueue, EmailService};
+use crate::notifier_trait::{NotificationEvent, Notifier};
+use crate::timezone::format_local;
+use crate::unsubscribe::{self, UnsubscribeScope};
+
+/// Delivers events as HTML emails through the outbound queue
+pub struct EmailNotifier {
+    queue: EmailQueue,
+}
+
+impl EmailNotifier {
+    pub fn new(queue: EmailQueue) -> Self {
+        EmailNotifier { queue }
+    }
+}
+
+#[async_trait]
+impl Notifier for EmailNotifier {
+    async fn notify(&self, destination: &str, event: &NotificationEvent, timezone: Tz) -> Result<()> {
+        let checked_at = format_local(Utc::now(), timezone);
+        let email = match event {
+            NotificationEvent::PriceDrop(drop) => EmailService::price_drop_alert(destination, drop, &checked_at),
+            NotificationEvent::ListingMatch(listing) => EmailService::listing_match_alert(
+                destination,
+                &listing.url,
+                &listing.items,
+                listing.target_price,
+                &listing.platform,
+                &checked_at,
+            ),
+            NotificationEvent::KeywordMatch(keyword) => EmailService::keyword_match_alert(
+                destination,
+                &keyword.query,
+                &keyword.search_url,
+                &keyword.items,
+                keyword.target_price,
+                &keyword.platform,
+                &checked_at,
+            ),
+            NotificationEvent::BackInStock(restock) => EmailService::back_in_stock_alert(
+                destination,
+                &restock.url,
+                restock.current_price,
+                &restock.platform,
+                &checked_at,
+            ),
+            NotificationEvent::ProductRemoved(removed) => EmailService::product_removed_alert(
+                destination,
+                &removed.url,
+                removed.last_price,
+                &removed.platform,
+                &checked_at,
+            ),
+        };
+        
+        let email = email.with_unsubscribe(
+            event.alert_id().map(|id| unsubscribe::link(UnsubscribeScope::Alert(id))),
+            event.user_id().map(|id| unsubscribe::link(UnsubscribeScope::Account(id))),
+        );
+        
+        self.queue.enqueue(email).await
+    }
+    
+    async fn notify_digest(&self, destination: &str, events: &[NotificationEvent], timezone: Tz) -> Result<()> {
+        let email = EmailService::digest(destination, events, &format_local(Utc::now(), timezone));
+        let account = events.iter().find_map(NotificationEvent::user_id);
+        let email = email.with_unsubscribe(None, account.map(|id| unsubscribe::link(UnsubscribeScope::Account(id))));
+        
+        self.queue.enqueue(email).await
+    }
+    
+    fn channel_name(&self) -> &'static str {
+        "email"
+    }
+    
+    fn validate_destination(&self, destination: &str) -> Result<()> {
+        destination
+            .parse::<lettre::message::Mailbox>()
+            .map(|_| ())
+            .map_err(|_| anyhow!("Invalid email address"))
+    }
+    
+    fn queued(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,316 @@
+pub mod discord;
+pub mod email;
+pub mod ntfy;
+pub mod slack;
+pub mod telegram;
+
+use crate::db::Database;
+use crate::email::EmailQueue;
+use crate::events::{EventBus, LiveEvent};
+use crate::metrics::{classify_delivery_error, delivery_metrics, DeliveryOutcome};
+use crate::notifier_trait::{NotificationEvent, Notifier};
+use crate::timezone;
+use chrono_tz::Tz;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Emails one user may be sent per UTC day before the rest wait for the next day's digest
+/// (`EMAIL_DAILY_CAP_PER_USER`, 50 by default; 0 for no limit). Protects the SMTP
+/// quota everyone shares from a user tracking hundreds of volatile items.
+pub fn email_daily_cap() -> Option<i32> {
+    let cap = std::env::var("EMAIL_DAILY_CAP_PER_USER")
+        .ok()
+        .and_then(|v| v.trim().parse::<i32>().ok())
+        .unwrap_or(50);
+    (cap > 0).then_some(cap)
+}
+
+/// Registered notification channels, keyed by channel name
+#[derive(Clone, Default)]
+pub struct NotifierRegistry {
+    notifiers: HashMap<&'static str, Arc<dyn Notifier>>,
+    /// Every dispatched event is also published here for live subscribers
+    events: EventBus,
+}
+
+impl NotifierRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the registry from whatever channels are configured for this deployment
+    pub fn from_env(email_queue: Option<EmailQueue>) -> Self {
+        let mut registry = Self::new();
+
+        if let Some(queue) = email_queue {
+            registry.register(Arc::new(email::EmailNotifier::new(queue)));
+        }
+
+        // Webhook channels need no server-side credentials
+        registry.register(Arc::new(discord::DiscordNotifier::new()));
+        registry.register(Arc::new(slack::SlackNotifier::new()));
+        registry.register(Arc::new(ntfy::NtfyNotifier::from_env()));
+        
+        // Telegram needs a bot token; chats are linked through the bot itself
+        if let Some(telegram) = telegram::TelegramNotifier::from_env() {
+            registry.register(Arc::new(telegram));
+        }
+
+        registry
+    }
+
+    pub fn register(&mut self, notifier: Arc<dyn Notifier>) {
+        self.notifiers.insert(notifier.channel_name(), notifier);
+    }
+
+    pub fn set_event_bus(&mut self, events: EventBus) {
+        self.events = events;
+    }
+
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
+
+    pub fn get(&self, channel: &str) -> Option<Arc<dyn Notifier>> {
+        self.notifiers.get(channel).cloned()
+    }
+
+    pub fn channels(&self) -> Vec<&'static str> {
+        let mut channels: Vec<_> = self.notifiers.keys().copied().collect();
+        channels.sort_unstable();
+        channels
+    }
+
+    /// Send an event to every channel the user has enabled (or only the
+    /// `only` subset chosen on the alert), falling back to email at the
+    /// alert's address when none are configured. Messages show times in the
+    /// user's timezone (UTC for events without a user). Nothing is delivered
+    /// while the user has paused notifications, or paused them for the alert.
+    /// Returns the number of successful deliveries.
+    pub async fn dispatch(
+        &self,
+        db: &Database,
+        event: &NotificationEvent,
+        only: Option<&[Uuid]>,
+    ) -> anyhow::Result<usize> {
+        self.events.publish(&LiveEvent::Notification(event.clone())).await;
+
+        let (mut destinations, zone) = match event.user_id() {
+            Some(user_id) => {
+                let (timezone, paused) = db.get_notification_settings(user_id).await?.unwrap_or_default();
+                if paused {
+                    tracing::info!("Notifications are paused for user {} - skipping delivery", user_id);
+                    return Ok(0);
+                }
+                if let Some(alert_id) = event.alert_id()
+                    && db.alert_notifications_paused(alert_id).await?
+                {
+                    tracing::info!("Notifications are paused for alert {} - skipping delivery", alert_id);
+                    return Ok(0);
+                }
+                
+                let destinations = db
+                    .get_enabled_channels(user_id)
+                    .await?
+                    .into_iter()
+                    .filter(|c| only.is_none_or(|ids| ids.is_empty() || ids.contains(&c.id)))
+                    .map(|c| (c.channel, c.destination))
+                    .collect();
+                (destinations, timezone::user_zone(&timezone))
+            }
+            None => (Vec::new(), Tz::UTC),
+        };
+
+        if destinations.is_empty() {
+            destinations.push(("email".to_string(), event.user_email().to_string()));
+        }
+
+        // Emails past the user's daily budget wait for the digest
+        if let (Some(user_id), Some(cap), Some(_)) = (event.user_id(), email_daily_cap(), self.get("email")) {
+            let mut within_budget = Vec::with_capacity(destinations.len());
+            for (channel, destination) in destinations {
+                if channel == "email" && !db.take_email_budget(user_id, cap).await? {
+                    tracing::info!("📬 User {} reached today's email limit ({}); deferring to the digest", user_id, cap);
+                    db.defer_notification(user_id, &destination, event).await?;
+                    continue;
+                }
+                within_budget.push((channel, destination));
+            }
+            destinations = within_budget;
+        }
+
+        Ok(self.deliver(Some(db), &destinations, event, zone).await)
+    }
+
+    /// Send each user one digest of the emails deferred before today by the daily
+    /// budget. Digests don't count against the budget. Returns the number sent.
+    pub async fn send_digests(&self, db: &Database) -> anyhow::Result<usize> {
+        let Some(notifier) = self.get("email") else {
+            return Ok(0);
+        };
+
+        let mut sent = 0;
+        for (user_id, destination, events) in db.take_due_digests().await? {
+            let (timezone, paused) = db.get_notification_settings(user_id).await?.unwrap_or_default();
+            if paused {
+                continue;
+            }
+            let result = notifier.notify_digest(&destination, &events, timezone::user_zone(&timezone)).await;
+            match &result {
+                Ok(()) => {
+                    sent += 1;
+                    tracing::info!("📬 Digest of {} notification(s) sent to {}", events.len(), destination);
+                }
+                Err(e) => tracing::error!("Failed to send digest to {}: {}", destination, e),
+            }
+            record_outcome(Some(db), "email", notifier.as_ref(), &result).await;
+        }
+        Ok(sent)
+    }
+
+    async fn deliver(
+        &self,
+        db: Option<&Database>,
+        destinations: &[(String, String)],
+        event: &NotificationEvent,
+        zone: Tz,
+    ) -> usize {
+        let mut delivered = 0;
+
+        for (channel, destination) in destinations {
+            let Some(notifier) = self.get(channel) else {
+                tracing::warn!("Notification channel '{}' is not configured - skipping", channel);
+                continue;
+            };
+
+            let result = notifier.notify(destination, event, zone).await;
+            match &result {
+                Ok(()) => {
+                    delivered += 1;
+                    tracing::info!("🔔 Notification sent via {} to {}", channel, destination);
+                }
+                Err(e) => tracing::error!("Failed to notify via {}: {}", channel, e),
+            }
+            
+            record_outcome(db, channel, notifier.as_ref(), &result).await;
+        }
+
+        delivered
+    }
+}
+
+/// Record a send attempt, unless a queued channel will report the real outcome once
+/// the message has gone out
+async fn record_outcome(db: Option<&Database>, channel: &str, notifier: &dyn Notifier, result: &anyhow::Result<()>) {
+    if notifier.queued() && result.is_ok() {
+        return;
+    }
+    let (outcome, error_kind) = match result {
+        Ok(()) => (DeliveryOutcome::Sent, None),
+        Err(e) => (DeliveryOutcome::Failed, Some(classify_delivery_error(e))),
+    };
+    record_delivery(db, channel, outcome, error_kind).await;
+}
+
+/// Count a delivery attempt in the `/metrics` counters and, given a database,
+/// the `notification_deliveries` table
+pub async fn record_delivery(
+    db: Option<&Database>,
+    channel: &str,
+    outcome: DeliveryOutcome,
+    error_kind: Option<&'static str>,
+) {
+    delivery_metrics().record(channel, outcome, error_kind);
+    
+    if let Some(db) = db
+        && let Err(e) = db.record_notification_delivery(channel, outcome.as_str(), error_kind).await
+    {
+        tracing::error!("Failed to record notification delivery: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier_trait::PriceDrop;
+    use anyhow::{Result, anyhow};
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+
+    struct RecordingNotifier {
+        name: &'static str,
+        sent: Mutex<Vec<String>>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        async fn notify(&self, destination: &str, _event: &NotificationEvent, _timezone: Tz) -> Result<()> {
+            if self.fail {
+                return Err(anyhow!("boom"));
+            }
+            self.sent.lock().unwrap().push(destination.to_string());
+            Ok(())
+        }
+
+        fn channel_name(&self) -> &'static str {
+            self.name
+        }
+
+        fn validate_destination(&self, _destination: &str) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn sample_event() -> NotificationEvent {
+        NotificationEvent::PriceDrop(PriceDrop {
+            alert_id: None,
+            user_id: None,
+            user_email: "buyer@example.com".to_string(),
+            url: "https://www.myntra.com/shirts/12345".to_string(),
+            platform: "myntra".to_string(),
+            current_price: 799.0,
+            target_price: 999.0,
+            previous_price: Some(1099.0),
+            offers: vec![],
+            badge: None,
+            product_name: None,
+            image_url: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_deliver_routes_to_registered_channels() {
+        let recorder = Arc::new(RecordingNotifier { name: "email", sent: Mutex::new(vec![]), fail: false });
+        let mut registry = NotifierRegistry::new();
+        registry.register(recorder.clone());
+
+        let destinations = vec![
+            ("email".to_string(), "a@example.com".to_string()),
+            ("carrier_pigeon".to_string(), "roof".to_string()),
+        ];
+        let delivered = registry.deliver(None, &destinations, &sample_event(), Tz::UTC).await;
+
+        assert_eq!(delivered, 1);
+        assert_eq!(*recorder.sent.lock().unwrap(), vec!["a@example.com".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_deliver_counts_only_successes() {
+        let mut registry = NotifierRegistry::new();
+        registry.register(Arc::new(RecordingNotifier { name: "email", sent: Mutex::new(vec![]), fail: true }));
+
+        let destinations = vec![("email".to_string(), "a@example.com".to_string())];
+        assert_eq!(registry.deliver(None, &destinations, &sample_event(), Tz::UTC).await, 0);
+    }
+
+    #[test]
+    fn test_channels_are_sorted() {
+        let mut registry = NotifierRegistry::new();
+        registry.register(Arc::new(RecordingNotifier { name: "slack", sent: Mutex::new(vec![]), fail: false }));
+        registry.register(Arc::new(RecordingNotifier { name: "email", sent: Mutex::new(vec![]), fail: false }));
+
+        assert_eq!(registry.channels(), vec!["email", "slack"]);
+    }
+}
@@ -0,0 +1,206 @@
+use async_trait::async_trait;
+use anyhow::{Result, anyhow};
+use chrono_tz::Tz;
+use reqwest::Client;
+use crate::models::ListingItem;
+use crate::notifier_trait::{NotificationEvent, Notifier};
+
+/// Publishes to an ntfy topic (ntfy.sh or a self-hosted server)
+pub struct NtfyNotifier {
+    client: Client,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl NtfyNotifier {
+    pub fn new(base_url: &str, token: Option<String>) -> Self {
+        NtfyNotifier {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let base_url = std::env::var("NTFY_BASE_URL")
+            .unwrap_or_else(|_| "https://ntfy.sh".to_string());
+        Self::new(&base_url, std::env::var("NTFY_TOKEN").ok())
+    }
+
+    /// The topic a destination names: a bare topic, or a topic URL on the configured server.
+    /// URLs on other hosts are refused, since the server's token goes wherever we publish.
+    fn topic(&self, destination: &str) -> Result<String> {
+        let topic = if destination.starts_with("https://") || destination.starts_with("http://") {
+            let url = reqwest::Url::parse(destination).map_err(|_| anyhow!("Invalid ntfy topic URL"))?;
+            let base = reqwest::Url::parse(&self.base_url).map_err(|_| anyhow!("Invalid NTFY_BASE_URL"))?;
+            if url.origin() != base.origin() {
+                return Err(anyhow!("ntfy topic URLs must be on {}", self.base_url));
+            }
+            let path = destination
+                .strip_prefix(self.base_url.as_str())
+                .unwrap_or(url.path())
+                .split(['?', '#'])
+                .next()
+                .unwrap_or_default();
+            path.trim_matches('/').to_string()
+        } else {
+            destination.to_string()
+        };
+        
+        if Self::is_valid_topic(&topic) {
+            Ok(topic)
+        } else {
+            Err(anyhow!("ntfy topics may only contain letters, digits, '-' and '_' (max 64)"))
+        }
+    }
+
+    fn topic_url(&self, destination: &str) -> Result<String> {
+        Ok(format!("{}/{}", self.base_url, self.topic(destination)?))
+    }
+
+    fn is_valid_topic(topic: &str) -> bool {
+        !topic.is_empty()
+            && topic.len() <= 64
+            && topic.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    }
+}
+
+/// Title, message and click URL for a batch of results: the cheapest one is featured
+fn results_message(title: String, url: &str, items: &[ListingItem], target_price: f64) -> (String, String, String) {
+    let best = items.iter().min_by(|a, b| a.price.total_cmp(&b.price));
+    let message = match best {
+        Some(item) => format!("{} at ₹{:.0} (target ₹{:.0})", item.title, item.price, target_price),
+        None => format!("Results under ₹{:.0}", target_price),
+    };
+    
+    (title, message, best.map_or(url, |item| item.url.as_str()).to_string())
+}
+
+#[async_trait]
+impl Notifier for NtfyNotifier {
+    async fn notify(&self, destination: &str, event: &NotificationEvent, _timezone: Tz) -> Result<()> {
+        let (title, message, click) = match event {
+            NotificationEvent::PriceDrop(drop) => (
+                format!("Price drop on {}", drop.platform.to_uppercase()),
+                format!(
+                    "Now ₹{:.0} (target ₹{:.0}) - save ₹{:.0}{}",
+                    drop.current_price,
+                    drop.target_price,
+                    drop.target_price - drop.current_price,
+                    drop.badge.map(|badge| format!("\n{}", badge.label())).unwrap_or_default()
+                ),
+                drop.url.clone(),
+            ),
+            NotificationEvent::ListingMatch(listing) => results_message(
+                format!("{} new result(s) on {}", listing.items.len(), listing.platform.to_uppercase()),
+                &listing.url,
+                &listing.items,
+                listing.target_price,
+            ),
+            NotificationEvent::KeywordMatch(keyword) => results_message(
+                format!("\"{}\" on {}", keyword.query, keyword.platform.to_uppercase()),
+                &keyword.search_url,
+                &keyword.items,
+                keyword.target_price,
+            ),
+            NotificationEvent::BackInStock(restock) => (
+                format!("Back in stock on {}", restock.platform.to_uppercase()),
+                match restock.current_price {
+                    Some(price) => format!("Available again at ₹{:.0}", price),
+                    None => "Available again".to_string(),
+                },
+                restock.url.clone(),
+            ),
+            NotificationEvent::ProductRemoved(removed) => (
+                format!("Product removed from {}", removed.platform.to_uppercase()),
+                "The page is gone, so it's no longer being checked. Update the alert's link to keep tracking it.".to_string(),
+                removed.url.clone(),
+            ),
+        };
+        
+        let mut request = self.client
+            .post(self.topic_url(destination)?)
+            .header("Title", title)
+            .header("Tags", "moneybag")
+            .header("Priority", "high")
+            .header("Click", click)
+            .body(message);
+        
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        
+        request.send().await?.error_for_status()?;
+        
+        Ok(())
+    }
+    
+    fn channel_name(&self) -> &'static str {
+        "ntfy"
+    }
+    
+    fn validate_destination(&self, destination: &str) -> Result<()> {
+        self.topic(destination).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier_trait::PriceDrop;
+    use mockito::Server;
+
+    #[test]
+    fn test_ntfy_topic_url() {
+        let notifier = NtfyNotifier::new("https://ntfy.example.com/", None);
+
+        assert_eq!(notifier.topic_url("deals").unwrap(), "https://ntfy.example.com/deals");
+        assert_eq!(notifier.topic_url("https://ntfy.example.com/mine").unwrap(), "https://ntfy.example.com/mine");
+        assert!(notifier.topic_url("https://ntfy.sh/mine").is_err());
+    }
+
+    #[test]
+    fn test_ntfy_validate_destination() {
+        let notifier = NtfyNotifier::new("https://ntfy.sh", None);
+
+        assert!(notifier.validate_destination("price_drops-42").is_ok());
+        assert!(notifier.validate_destination("https://ntfy.sh/price_drops").is_ok());
+        assert!(notifier.validate_destination("bad topic").is_err());
+        assert!(notifier.validate_destination("https://ntfy.sh/").is_err());
+        // Only the configured server gets published to
+        assert!(notifier.validate_destination("https://attacker.example/price_drops").is_err());
+        assert!(notifier.validate_destination("http://ntfy.sh/price_drops").is_err());
+        assert!(notifier.validate_destination("https://ntfy.sh.attacker.example/price_drops").is_err());
+        assert!(notifier.validate_destination("https://ntfy.sh/a/price_drops").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ntfy_publish_headers() {
+        let mut server = Server::new_async().await;
+        let _m = server.mock("POST", "/deals")
+            .match_header("title", "Price drop on MYNTRA")
+            .match_header("click", "https://www.myntra.com/12345")
+            .match_header("authorization", "Bearer tk_secret")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let notifier = NtfyNotifier::new(&server.url(), Some("tk_secret".to_string()));
+        let event = NotificationEvent::PriceDrop(PriceDrop {
+            alert_id: None,
+            user_id: None,
+            user_email: "buyer@example.com".to_string(),
+            url: "https://www.myntra.com/12345".to_string(),
+            platform: "myntra".to_string(),
+            current_price: 799.0,
+            target_price: 999.0,
+            previous_price: None,
+            offers: vec![],
+            badge: None,
+            product_name: None,
+            image_url: None,
+        });
+
+        assert!(notifier.notify("deals", &event, Tz::UTC).await.is_ok());
+    }
+}
@@ -0,0 +1,241 @@
+use async_trait::async_trait;
+use anyhow::{Result, anyhow};
+use chrono_tz::Tz;
+use reqwest::Client;
+use serde_json::{json, Value};
+use crate::models::ListingItem;
+use crate::notifier_trait::{NotificationEvent, Notifier};
+
+/// Posts Block Kit messages to a Slack incoming webhook
+pub struct SlackNotifier {
+    client: Client,
+}
+
+impl SlackNotifier {
+    pub fn new() -> Self {
+        SlackNotifier { client: Client::new() }
+    }
+
+    fn payload(event: &NotificationEvent) -> Value {
+        match event {
+            NotificationEvent::PriceDrop(drop) => {
+                let mut headline = format!(
+                    "🚨 Price drop on {}: ₹{:.0} (target ₹{:.0})",
+                    drop.platform.to_uppercase(),
+                    drop.current_price,
+                    drop.target_price
+                );
+                if let Some(badge) = drop.badge {
+                    headline.push_str(&format!(" · 🏆 {}", badge.label()));
+                }
+                let headline = escape(&headline);
+                let was = drop
+                    .previous_price
+                    .map(|p| format!("~₹{:.0}~", p))
+                    .unwrap_or_else(|| "—".to_string());
+
+                let mut summary = json!({
+                    "type": "section",
+                    "text": {
+                        "type": "mrkdwn",
+                        "text": match &drop.product_name {
+                            Some(name) => format!("*{}*\n{}\n<{}|View product>", headline, escape(name), drop.url),
+                            None => format!("*{}*\n<{}|View product>", headline, drop.url),
+                        }
+                    }
+                });
+                if let Some(image_url) = &drop.image_url {
+                    summary["accessory"] = json!({
+                        "type": "image",
+                        "image_url": image_url,
+                        "alt_text": drop.product_name.as_deref().unwrap_or("Product image")
+                    });
+                }
+
+                json!({
+                    "text": headline,
+                    "blocks": [
+                        summary,
+                        {
+                            "type": "section",
+                            "fields": [
+                                { "type": "mrkdwn", "text": format!("*Was*\n{}", was) },
+                                { "type": "mrkdwn", "text": format!("*Now*\n₹{:.0}", drop.current_price) },
+                                { "type": "mrkdwn", "text": format!("*Target*\n₹{:.0}", drop.target_price) }
+                            ]
+                        }
+                    ]
+                })
+            }
+            NotificationEvent::ListingMatch(listing) => Self::results_payload(
+                &format!(
+                    "🔎 {} result(s) under ₹{:.0} on {}",
+                    listing.items.len(),
+                    listing.target_price,
+                    listing.platform.to_uppercase()
+                ),
+                &listing.url,
+                &listing.items,
+            ),
+            NotificationEvent::KeywordMatch(keyword) => Self::results_payload(
+                &format!(
+                    "🔎 \"{}\" under ₹{:.0} on {}",
+                    keyword.query,
+                    keyword.target_price,
+                    keyword.platform.to_uppercase()
+                ),
+                &keyword.search_url,
+                &keyword.items,
+            ),
+            NotificationEvent::BackInStock(restock) => {
+                let headline = match restock.current_price {
+                    Some(price) => format!("📦 Back in stock on {} at ₹{:.0}", restock.platform.to_uppercase(), price),
+                    None => format!("📦 Back in stock on {}", restock.platform.to_uppercase()),
+                };
+                let headline = escape(&headline);
+
+                json!({
+                    "text": headline,
+                    "blocks": [{
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": format!("*{}*\n<{}|View product>", headline, restock.url) }
+                    }]
+                })
+            }
+            NotificationEvent::ProductRemoved(removed) => {
+                let headline = escape(&format!("🚫 Product removed from {}", removed.platform.to_uppercase()));
+
+                json!({
+                    "text": headline,
+                    "blocks": [{
+                        "type": "section",
+                        "text": {
+                            "type": "mrkdwn",
+                            "text": format!("*{}*\n<{}|This page> is gone, so checks have stopped. Update the alert's link to keep tracking it.", headline, removed.url)
+                        }
+                    }]
+                })
+            }
+        }
+    }
+
+    /// Headline linking to the results page, then a bullet per matching product
+    fn results_payload(headline: &str, url: &str, items: &[ListingItem]) -> Value {
+        let headline = escape(headline);
+        let lines: Vec<String> = items
+            .iter()
+            .map(|item| format!("• <{}|{}> — ₹{:.0}", item.url, escape(&item.title), item.price))
+            .collect();
+
+        json!({
+            "text": headline,
+            "blocks": [
+                {
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": format!("*{}*\n<{}|View search>", headline, url) }
+                },
+                {
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": lines.join("\n") }
+                }
+            ]
+        })
+    }
+}
+
+/// Scraped and user-entered text is escaped so it can't add mentions (`<!channel>`)
+/// or links of its own to a mrkdwn message
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+impl Default for SlackNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    async fn notify(&self, destination: &str, event: &NotificationEvent, _timezone: Tz) -> Result<()> {
+        self.client
+            .post(destination)
+            .json(&Self::payload(event))
+            .send()
+            .await?
+            .error_for_status()?;
+        
+        Ok(())
+    }
+    
+    fn channel_name(&self) -> &'static str {
+        "slack"
+    }
+    
+    fn validate_destination(&self, destination: &str) -> Result<()> {
+        if destination.starts_with("https://hooks.slack.com/") {
+            Ok(())
+        } else {
+            Err(anyhow!("Slack destination must be an incoming webhook URL"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier_trait::PriceDrop;
+
+    #[test]
+    fn test_slack_payload_blocks() {
+        let event = NotificationEvent::PriceDrop(PriceDrop {
+            alert_id: None,
+            user_id: None,
+            user_email: "buyer@example.com".to_string(),
+            url: "https://www.ajio.com/p/123".to_string(),
+            platform: "ajio".to_string(),
+            current_price: 899.0,
+            target_price: 999.0,
+            previous_price: None,
+            offers: vec![],
+            badge: None,
+            product_name: None,
+            image_url: None,
+        });
+
+        let payload = SlackNotifier::payload(&event);
+
+        assert_eq!(payload["text"], "🚨 Price drop on AJIO: ₹899 (target ₹999)");
+        assert!(payload["blocks"][0]["text"]["text"].as_str().unwrap().contains("<https://www.ajio.com/p/123|View product>"));
+        assert_eq!(payload["blocks"][1]["fields"][0]["text"], "*Was*\n—");
+        assert!(payload["blocks"][0].get("accessory").is_none());
+
+        let NotificationEvent::PriceDrop(drop) = event else { unreachable!() };
+        let event = NotificationEvent::PriceDrop(PriceDrop {
+            product_name: Some("DNMX Slim Fit Shirt".to_string()),
+            image_url: Some("https://assets.ajio.com/shirt.jpg".to_string()),
+            ..drop
+        });
+        let payload = SlackNotifier::payload(&event);
+        assert!(payload["blocks"][0]["text"]["text"].as_str().unwrap().contains("\nDNMX Slim Fit Shirt\n"));
+        assert_eq!(payload["blocks"][0]["accessory"]["image_url"], "https://assets.ajio.com/shirt.jpg");
+        assert_eq!(payload["blocks"][0]["accessory"]["alt_text"], "DNMX Slim Fit Shirt");
+
+        let NotificationEvent::PriceDrop(drop) = event else { unreachable!() };
+        let event = NotificationEvent::PriceDrop(PriceDrop {
+            product_name: Some("Shirt <!channel> <https://evil.example|Click> & more".to_string()),
+            ..drop
+        });
+        let text = SlackNotifier::payload(&event)["blocks"][0]["text"]["text"].as_str().unwrap().to_string();
+        assert!(text.contains("\nShirt &lt;!channel&gt; &lt;https://evil.example|Click&gt; &amp; more\n"));
+        assert!(!text.contains("<!channel>"));
+    }
+
+    #[test]
+    fn test_slack_validate_destination() {
+        let notifier = SlackNotifier::new();
+
+        assert!(notifier.validate_destination("https://hooks.slack.com/services/T0/B0/xyz").is_ok());
+        assert!(notifier.validate_destination("http://hooks.slack.com/services/T0").is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use async_trait::async_trait;
+use anyhow::{Result, anyhow};
+use chrono_tz::Tz;
+use reqwest::Client;
+use serde_json::json;
+use crate::models::ListingItem;
+use crate::notifier_trait::{NotificationEvent, Notifier};
+
+/// Messages a linked Telegram chat through the bot; destinations are chat IDs
+pub struct TelegramNotifier {
+    client: Client,
+    api_base: String,
+    token: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(api_base: &str, token: &str) -> Self {
+        TelegramNotifier {
+            client: Client::new(),
+            api_base: api_base.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    /// None unless `TELEGRAM_BOT_TOKEN` is set
+    pub fn from_env() -> Option<Self> {
+        let token = std::env::var("TELEGRAM_BOT_TOKEN").ok().filter(|t| !t.is_empty())?;
+        let api_base = std::env::var("TELEGRAM_API_BASE")
+            .unwrap_or_else(|_| "https://api.telegram.org".to_string());
+        Some(Self::new(&api_base, &token))
+    }
+
+    fn text(event: &NotificationEvent) -> String {
+        match event {
+            NotificationEvent::PriceDrop(drop) => format!(
+                "🚨 Price drop on {}\nNow ₹{:.0} (target ₹{:.0}){}\n{}",
+                drop.platform.to_uppercase(),
+                drop.current_price,
+                drop.target_price,
+                drop.badge.map(|badge| format!("\n🏆 {}", badge.label())).unwrap_or_default(),
+                drop.url
+            ),
+            NotificationEvent::ListingMatch(listing) => Self::results_text(
+                format!("🔎 {} result(s) under ₹{:.0} on {}", listing.items.len(), listing.target_price, listing.platform.to_uppercase()),
+                &listing.items,
+            ),
+            NotificationEvent::KeywordMatch(keyword) => Self::results_text(
+                format!("🔎 \"{}\" under ₹{:.0} on {}", keyword.query, keyword.target_price, keyword.platform.to_uppercase()),
+                &keyword.items,
+            ),
+            NotificationEvent::BackInStock(restock) => format!(
+                "📦 Back in stock on {}{}\n{}",
+                restock.platform.to_uppercase(),
+                restock.current_price.map(|price| format!(" at ₹{:.0}", price)).unwrap_or_default(),
+                restock.url
+            ),
+            NotificationEvent::ProductRemoved(removed) => format!(
+                "🚫 Product removed from {}\nChecks have stopped; update the alert's link to keep tracking it.\n{}",
+                removed.platform.to_uppercase(),
+                removed.url
+            ),
+        }
+    }
+
+    fn results_text(title: String, items: &[ListingItem]) -> String {
+        let lines: Vec<String> = items
+            .iter()
+            .map(|item| format!("• {} — ₹{:.0}\n{}", item.title, item.price, item.url))
+            .collect();
+        format!("{}\n{}", title, lines.join("\n"))
+    }
+}
+
+#[async_trait]
+impl Notifier for TelegramNotifier {
+    async fn notify(&self, destination: &str, event: &NotificationEvent, _timezone: Tz) -> Result<()> {
+        self.client
+            .post(format!("{}/bot{}/sendMessage", self.api_base, self.token))
+            .json(&json!({ "chat_id": destination, "text": Self::text(event) }))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    fn channel_name(&self) -> &'static str {
+        "telegram"
+    }
+
+    fn validate_destination(&self, destination: &str) -> Result<()> {
+        destination
+            .parse::<i64>()
+            .map(|_| ())
+            .map_err(|_| anyhow!("Telegram destination must be a chat ID; link a chat by sending /link to the bot"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier_trait::PriceDrop;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_telegram_notify_sends_message() {
+        let mut server = Server::new_async().await;
+        let _m = server.mock("POST", "/bot123:abc/sendMessage")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::PartialJson(json!({ "chat_id": "424242" })),
+                mockito::Matcher::Regex("Now ₹1199 \\(target ₹1299\\)".to_string()),
+            ]))
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let notifier = TelegramNotifier::new(&server.url(), "123:abc");
+        let event = NotificationEvent::PriceDrop(PriceDrop {
+            alert_id: None,
+            user_id: None,
+            user_email: "buyer@example.com".to_string(),
+            url: "https://www.flipkart.com/product/p/abc123".to_string(),
+            platform: "flipkart".to_string(),
+            current_price: 1199.0,
+            target_price: 1299.0,
+            previous_price: None,
+            offers: vec![],
+            badge: None,
+            product_name: None,
+            image_url: None,
+        });
+
+        assert!(notifier.notify("424242", &event, Tz::UTC).await.is_ok());
+        assert!(notifier.validate_destination("-100123").is_ok());
+        assert!(notifier.validate_destination("@channel").is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use crate::db::Database;
+use crate::models::OutboxEntry;
+use crate::notifiers::NotifierRegistry;
+
+/// Entries sent per poll
+const BATCH: i64 = 50;
+
+/// Attempts before an entry is left unsent for good (it stays in the table, with its error)
+pub const MAX_ATTEMPTS: i32 = 8;
+
+/// First retry after a failed send; doubles with each attempt up to `MAX_RETRY_DELAY`
+const RETRY_DELAY: Duration = Duration::from_secs(60);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
+
+/// How often the dispatcher looks for entries nobody sent, unless `OUTBOX_POLL_SECS` says otherwise
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+fn poll_interval() -> Duration {
+    std::env::var("OUTBOX_POLL_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|secs: &u64| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_POLL_INTERVAL)
+}
+
+/// How long a claim keeps other processes off an entry: long enough for every channel's
+/// timeouts, short enough that a crash only delays the notification by this much
+pub fn claim() -> chrono::Duration {
+    chrono::Duration::minutes(5)
+}
+
+/// When an entry that failed on its `attempts`th try is tried again
+pub fn retry_at(attempts: i32, now: DateTime<Utc>) -> DateTime<Utc> {
+    let delay = RETRY_DELAY.saturating_mul(2u32.saturating_pow(attempts.max(1) as u32 - 1)).min(MAX_RETRY_DELAY);
+    now + chrono::Duration::from_std(delay).unwrap_or_default()
+}
+
+/// Send a claimed entry through the user's channels and mark it sent, returning how many
+/// channels delivered it. If marking fails it goes out again once the claim runs out:
+/// a duplicate beats a lost notification.
+pub async fn send(db: &Database, notifiers: &NotifierRegistry, entry: &OutboxEntry) -> usize {
+    match notifiers.dispatch(db, &entry.event, entry.channels.as_deref()).await {
+        Ok(delivered) => {
+            if let Err(e) = db.mark_outbox_sent(entry.id).await {
+                tracing::error!("Failed to mark notification {} as sent: {}", entry.id, e);
+            }
+            delivered
+        }
+        Err(e) => {
+            tracing::error!("Failed to dispatch notification {} (attempt {}): {}", entry.id, entry.attempts, e);
+            if entry.attempts >= MAX_ATTEMPTS {
+                tracing::warn!("Giving up on notification {} after {} attempts", entry.id, entry.attempts);
+            }
+            if let Err(e) = db.release_outbox(entry.id, retry_at(entry.attempts, Utc::now()), &e.to_string()).await {
+                tracing::error!("Failed to reschedule notification {}: {}", entry.id, e);
+            }
+            0
+        }
+    }
+}
+
+/// Send every entry that was never sent (its writer crashed, or the send failed) and
+/// whose claim has run out. Returns how many were sent.
+pub async fn send_due(db: &Database, notifiers: &NotifierRegistry) -> anyhow::Result<usize> {
+    let mut sent = 0;
+    loop {
+        let entries = db.claim_outbox(BATCH, MAX_ATTEMPTS, claim()).await?;
+        if entries.is_empty() {
+            return Ok(sent);
+        }
+        for entry in &entries {
+            send(db, notifiers, entry).await;
+            sent += 1;
+        }
+    }
+}
+
+/// Look for unsent notifications every `OUTBOX_POLL_SECS`, for as long as the process runs
+pub fn spawn(db: Database, notifiers: NotifierRegistry) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(poll_interval());
+        loop {
+            ticker.tick().await;
+            match send_due(&db, &notifiers).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("📤 Sent {} notification(s) left in the outbox", count),
+                Err(e) => tracing::error!("Failed to send outbox notifications: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_retry_at() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+        assert_eq!(retry_at(1, now), now + chrono::Duration::minutes(1));
+        assert_eq!(retry_at(3, now), now + chrono::Duration::minutes(4));
+        assert_eq!(retry_at(MAX_ATTEMPTS, now), now + chrono::Duration::hours(1));
+        assert_eq!(retry_at(40, now), now + chrono::Duration::hours(1));
+    }
+}
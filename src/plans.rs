@@ -0,0 +1,123 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use crate::models::PriceAlert;
+
+/// Account tier, stored as `users.plan`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Plan {
+    #[default]
+    Free,
+    Pro,
+}
+
+impl Plan {
+    pub const ALL: [Plan; 2] = [Plan::Free, Plan::Pro];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Plan::Free => "free",
+            Plan::Pro => "pro",
+        }
+    }
+
+    /// Unknown values (e.g. a plan since removed) fall back to free
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "pro" => Plan::Pro,
+            _ => Plan::Free,
+        }
+    }
+
+    /// This plan's limits, overridable with `PLAN_<PLAN>_*` env vars
+    pub fn limits(&self) -> PlanLimits {
+        let (max_alerts, min_check_minutes) = match self {
+            Plan::Free => (10, 60),
+            Plan::Pro => (200, 15),
+        };
+        let var = |name: &str| format!("PLAN_{}_{}", self.as_str().to_uppercase(), name);
+
+        PlanLimits {
+            max_alerts: env_or(&var("MAX_ALERTS"), max_alerts),
+            min_check_minutes: env_or(&var("MIN_CHECK_MINUTES"), min_check_minutes),
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlanLimits {
+    /// Active price/stock alerts the account may have at once
+    pub max_alerts: i64,
+    /// Shortest gap between two checks of the same alert; manual check runs skip alerts checked more recently
+    pub min_check_minutes: i64,
+}
+
+impl PlanLimits {
+    /// Whether `alert` may be scraped again at `now`: its own interval, but never more
+    /// often than the plan allows. Alerts never checked yet always are.
+    pub fn is_due(&self, alert: &PriceAlert, now: DateTime<Utc>) -> bool {
+        let never_checked = alert.last_price.is_none() && alert.in_stock.is_none();
+        let interval = alert
+            .check_interval_minutes
+            .map_or(self.min_check_minutes, |minutes| self.min_check_minutes.max(minutes.into()));
+        never_checked || now - alert.last_checked >= Duration::minutes(interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_limits_and_due_alerts() {
+        assert_eq!(Plan::parse("pro"), Plan::Pro);
+        assert_eq!(Plan::parse("enterprise"), Plan::Free);
+
+        let free = Plan::Free.limits();
+        assert!(free.max_alerts < Plan::Pro.limits().max_alerts);
+
+        let now = Utc::now();
+        let mut alert = PriceAlert {
+            id: None,
+            url: "https://www.myntra.com/12345678".to_string(),
+            target_price: 999.0,
+            last_price: None,
+            user_email: "test@example.com".to_string(),
+            user_id: None,
+            platform: "myntra".to_string(),
+            created_at: now,
+            last_checked: now,
+            is_active: true,
+            notify_channels: None,
+            product_id: None,
+            alert_mode: "price".to_string(),
+            in_stock: None,
+            offers: sqlx::types::Json(vec![]),
+            version: 1,
+            check_interval_minutes: None,
+            expires_at: None,
+            renotify: Default::default(),
+            min_price: None,
+            max_price: None,
+            product_removed_at: None,
+            product_name: None,
+            image_url: None,
+            notifications_paused: false,
+        };
+        assert!(free.is_due(&alert, now));
+
+        alert.last_price = Some(1299.0);
+        assert!(!free.is_due(&alert, now + Duration::minutes(30)));
+        assert!(free.is_due(&alert, now + Duration::minutes(60)));
+
+        // A longer per-alert interval wins; a shorter one can't beat the plan
+        alert.check_interval_minutes = Some(24 * 60);
+        assert!(!free.is_due(&alert, now + Duration::minutes(60)));
+        alert.check_interval_minutes = Some(5);
+        assert!(!free.is_due(&alert, now + Duration::minutes(30)));
+    }
+}
@@ -0,0 +1,156 @@
+use serde_json::Value;
+
+/// Amount from a displayed Indian price: "₹1,29,999.00", "Rs. 999", "MRP ₹2,499",
+/// "INR 1,299.50". Takes the first number in the text, so labels and currency markers
+/// before it are skipped and anything after it ("(48% off)") is ignored. Commas may be
+/// lakh-style or western; up to two decimal places are paise.
+///
+/// `None` unless the amount is positive.
+pub fn parse_price(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let amount: &str = {
+        let rest = &text[start..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+            .unwrap_or(rest.len());
+        // A sentence-ending "." or "," isn't part of the number
+        rest[..end].trim_end_matches([',', '.'])
+    };
+
+    let (whole, paise) = match amount.split_once('.') {
+        Some((whole, paise)) => (whole, Some(paise)),
+        None => (amount, None),
+    };
+
+    // Separators only between digits: "1,29,999" and "129,999" but not "1,,299"
+    if whole.split(',').any(|group| group.is_empty() || !group.bytes().all(|b| b.is_ascii_digit())) {
+        return None;
+    }
+    if let Some(paise) = paise
+        && (paise.is_empty() || paise.len() > 2 || !paise.bytes().all(|b| b.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let digits: String = whole.chars().filter(|c| *c != ',').collect();
+    let number = match paise {
+        Some(paise) => format!("{}.{}", digits, paise),
+        None => digits,
+    };
+    number.parse::<f64>().ok().filter(|price| price.is_finite() && *price > 0.0)
+}
+
+/// Price from embedded page JSON, where it may be a number or a display string
+pub fn json_price(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64().filter(|price| price.is_finite() && *price > 0.0),
+        Value::String(s) => parse_price(s),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_price_formats() {
+        assert_eq!(parse_price("₹1,29,999.00"), Some(129999.0));
+        assert_eq!(parse_price("Rs. 999"), Some(999.0));
+        assert_eq!(parse_price("Rs.999"), Some(999.0));
+        assert_eq!(parse_price("MRP ₹2,499"), Some(2499.0));
+        assert_eq!(parse_price("INR 1,299.50"), Some(1299.5));
+        assert_eq!(parse_price("₹ 999.5"), Some(999.5));
+        assert_eq!(parse_price("129,999"), Some(129999.0));
+        assert_eq!(parse_price(" ₹2,500 "), Some(2500.0));
+        assert_eq!(parse_price("₹1,299 (48% off)"), Some(1299.0));
+        assert_eq!(parse_price("Now only ₹799."), Some(799.0));
+
+        assert_eq!(parse_price(""), None);
+        assert_eq!(parse_price("Sold out"), None);
+        assert_eq!(parse_price("₹0"), None);
+        assert_eq!(parse_price("₹1,,299"), None);
+        assert_eq!(parse_price("₹12.345"), None);
+    }
+
+    #[test]
+    fn test_json_price() {
+        assert_eq!(json_price(&json!(1299)), Some(1299.0));
+        assert_eq!(json_price(&json!(899.5)), Some(899.5));
+        assert_eq!(json_price(&json!("₹1,299")), Some(1299.0));
+        assert_eq!(json_price(&json!("27500")), Some(27500.0));
+        assert_eq!(json_price(&json!(0)), None);
+        assert_eq!(json_price(&json!(null)), None);
+    }
+
+    /// "12345678" -> "1,23,45,678"
+    fn lakh_grouped(rupees: u64) -> String {
+        let digits = rupees.to_string();
+        if digits.len() <= 3 {
+            return digits;
+        }
+        let (head, last_three) = digits.split_at(digits.len() - 3);
+        let mut groups: Vec<String> = Vec::new();
+        let mut rest = head;
+        while rest.len() > 2 {
+            let (more, pair) = rest.split_at(rest.len() - 2);
+            groups.insert(0, pair.to_string());
+            rest = more;
+        }
+        groups.insert(0, rest.to_string());
+        format!("{},{}", groups.join(","), last_three)
+    }
+
+    /// "12345678" -> "12,345,678"
+    fn western_grouped(rupees: u64) -> String {
+        let digits = rupees.to_string();
+        let mut out = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(',');
+            }
+            out.push(digit);
+        }
+        out
+    }
+
+    fn prefix() -> impl Strategy<Value = &'static str> {
+        prop::sample::select(vec!["", "₹", "₹ ", "Rs. ", "Rs.", "Rs ", "INR ", "MRP ₹", "MRP: Rs. ", "Price: "])
+    }
+
+    proptest! {
+        #[test]
+        fn prop_grouped_prices_round_trip(
+            rupees in 1u64..10_000_000_000,
+            paise in proptest::option::of(0u64..100),
+            lakh in any::<bool>(),
+            prefix in prefix(),
+            suffix in prop::sample::select(vec!["", " ", " (48% off)", " onwards", "."]),
+        ) {
+            let grouped = if lakh { lakh_grouped(rupees) } else { western_grouped(rupees) };
+            let text = match paise {
+                Some(paise) => format!("{}{}.{:02}{}", prefix, grouped, paise, suffix),
+                None => format!("{}{}{}", prefix, grouped, suffix),
+            };
+            let expected = rupees as f64 + paise.unwrap_or(0) as f64 / 100.0;
+
+            let parsed = parse_price(&text);
+            prop_assert!(parsed.is_some(), "failed to parse {:?}", text);
+            prop_assert!((parsed.unwrap() - expected).abs() <= expected * 1e-12, "{:?} parsed as {:?}", text, parsed);
+        }
+
+        #[test]
+        fn prop_never_panics_and_only_positive(text in ".{0,40}") {
+            if let Some(price) = parse_price(&text) {
+                prop_assert!(price.is_finite() && price > 0.0);
+            }
+        }
+
+        #[test]
+        fn prop_no_digits_no_price(text in "[^0-9]{0,40}") {
+            prop_assert_eq!(parse_price(&text), None);
+        }
+    }
+}
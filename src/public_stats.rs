@@ -0,0 +1,33 @@
+use std::time::Duration;
+use crate::db::Database;
+
+/// How often the landing page totals are recomputed, unless `PUBLIC_STATS_REFRESH_SECS` says otherwise
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+fn refresh_interval() -> Duration {
+    std::env::var("PUBLIC_STATS_REFRESH_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|secs: &u64| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_REFRESH_INTERVAL)
+}
+
+/// Recompute the totals behind `GET /stats/public` every `PUBLIC_STATS_REFRESH_SECS`,
+/// for as long as the process runs
+pub fn spawn(db: Database) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(refresh_interval());
+        loop {
+            ticker.tick().await;
+            match db.refresh_public_stats().await {
+                Ok(stats) => tracing::debug!(
+                    "📈 Public stats: {} products tracked, {} drops this week",
+                    stats.products_tracked,
+                    stats.drops_this_week
+                ),
+                Err(e) => tracing::error!("Failed to refresh public stats: {}", e),
+            }
+        }
+    });
+}
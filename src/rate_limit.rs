@@ -0,0 +1,144 @@
+use axum::http::HeaderMap;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::redis_store::RedisStore;
+
+/// Fixed-window rate limiter keyed by an arbitrary string (IP, user ID...).
+/// Counts in memory unless given a Redis store, so every replica shares one budget.
+#[derive(Clone)]
+pub struct RateLimiter {
+    max_requests: u32,
+    window: Duration,
+    windows: Arc<Mutex<HashMap<String, (Instant, u32)>>>,
+    /// Redis store plus the counter namespace (e.g. "login")
+    shared: Option<(RedisStore, &'static str)>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: u32, window: Duration) -> Self {
+        RateLimiter {
+            max_requests,
+            window,
+            windows: Arc::new(Mutex::new(HashMap::new())),
+            shared: None,
+        }
+    }
+
+    /// Keep the counters in Redis under `ratelimit:<scope>:<key>`
+    pub fn with_redis(mut self, store: RedisStore, scope: &'static str) -> Self {
+        self.shared = Some((store, scope));
+        self
+    }
+
+    /// Record a hit for `key`. Returns `Err(retry_after)` once the window's budget is spent.
+    /// Falls back to this replica's own counts while Redis is unreachable.
+    pub async fn check(&self, key: &str) -> Result<(), Duration> {
+        if let Some((store, scope)) = &self.shared {
+            match store.hit(&format!("ratelimit:{}:{}", scope, key), self.window).await {
+                Ok((count, retry_after)) if count > u64::from(self.max_requests) => return Err(retry_after),
+                Ok(_) => return Ok(()),
+                Err(e) => tracing::warn!("Shared rate limit unavailable, counting locally: {}", e),
+            }
+        }
+
+        self.check_local(key)
+    }
+
+    fn check_local(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+
+        // Opportunistically drop expired windows so the map doesn't grow unbounded
+        if windows.len() > 10_000 {
+            windows.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+        }
+
+        let entry = windows.entry(key.to_string()).or_insert((now, 0));
+        if now.duration_since(entry.0) >= self.window {
+            *entry = (now, 0);
+        }
+
+        if entry.1 >= self.max_requests {
+            return Err(self.window - now.duration_since(entry.0));
+        }
+
+        entry.1 += 1;
+        Ok(())
+    }
+}
+
+/// Proxies in front of the app that append to `X-Forwarded-For` (`TRUSTED_PROXIES`, default 0)
+pub fn trusted_proxies() -> usize {
+    std::env::var("TRUSTED_PROXIES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// The client IP rate limits key on. Clients can put anything in `X-Forwarded-For`, so only
+/// the hops our own `trusted` proxies appended count: the address the outermost of them
+/// saw, `trusted` entries from the right. Without trusted proxies it's the socket address.
+pub fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trusted: usize) -> String {
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .collect();
+
+    let hop = match trusted {
+        0 => None,
+        trusted => forwarded.len().checked_sub(trusted).and_then(|i| forwarded.get(i)).or(forwarded.first()),
+    };
+    hop.map(|ip| ip.to_string())
+        .or_else(|| peer.map(|addr| addr.ip().to_string()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rate_limiter_blocks_after_budget() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+
+        assert!(limiter.check("1.2.3.4").await.is_ok());
+        assert!(limiter.check("1.2.3.4").await.is_ok());
+        let retry_after = limiter.check("1.2.3.4").await.unwrap_err();
+        assert!(retry_after <= Duration::from_secs(60));
+
+        // Other keys have their own budget
+        assert!(limiter.check("5.6.7.8").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_window_resets() {
+        let limiter = RateLimiter::new(1, Duration::from_millis(20));
+
+        assert!(limiter.check("k").await.is_ok());
+        assert!(limiter.check("k").await.is_err());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(limiter.check("k").await.is_ok());
+    }
+
+    #[test]
+    fn test_client_ip_trusts_only_proxy_hops() {
+        let mut headers = HeaderMap::new();
+        let peer: SocketAddr = "10.0.0.1:5000".parse().unwrap();
+
+        assert_eq!(client_ip(&headers, Some(peer), 0), "10.0.0.1");
+        assert_eq!(client_ip(&headers, None, 0), "unknown");
+
+        // Whatever the client wrote comes first; each proxy appends what it saw
+        headers.insert("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.0.0.2".parse().unwrap());
+        assert_eq!(client_ip(&headers, Some(peer), 0), "10.0.0.1");
+        assert_eq!(client_ip(&headers, Some(peer), 1), "10.0.0.2");
+        assert_eq!(client_ip(&headers, Some(peer), 2), "203.0.113.7");
+        assert_eq!(client_ip(&headers, Some(peer), 5), "198.51.100.1");
+    }
+}
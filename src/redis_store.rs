@@ -0,0 +1,137 @@
+use anyhow::Result;
+use redis::aio::{ConnectionManager, PubSub};
+use redis::{AsyncCommands, Client, Script};
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Duration;
+use crate::config::RedisConfig;
+
+/// Fixed-window counter: the first hit in a window starts its expiry
+const HIT_SCRIPT: &str = r#"
+local count = redis.call('INCR', KEYS[1])
+if count == 1 then
+    redis.call('PEXPIRE', KEYS[1], ARGV[1])
+end
+return {count, redis.call('PTTL', KEYS[1])}
+"#;
+
+/// State shared between replicas: the alert cache, rate limit counters,
+/// the notification event channel and the worker's per-round lease.
+/// Every key and channel is namespaced with `RedisConfig::key_prefix`.
+#[derive(Clone)]
+pub struct RedisStore {
+    client: Client,
+    conn: ConnectionManager,
+    prefix: String,
+}
+
+impl RedisStore {
+    /// Connects eagerly so a wrong `REDIS_URL` fails at startup rather than on first use;
+    /// the connection manager reconnects by itself afterwards
+    pub async fn connect(config: &RedisConfig) -> Result<Self> {
+        let client = Client::open(config.url.as_str())?;
+        let conn = client.get_connection_manager().await?;
+        Ok(RedisStore { client, conn, prefix: config.key_prefix.clone() })
+    }
+
+    pub fn key(&self, name: &str) -> String {
+        format!("{}:{}", self.prefix, name)
+    }
+
+    pub async fn get_json<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        let value: Option<String> = self.conn.clone().get(self.key(name)).await?;
+        Ok(match value {
+            Some(json) => Some(serde_json::from_str(&json)?),
+            None => None,
+        })
+    }
+
+    pub async fn set_json<T: Serialize>(&self, name: &str, value: &T, ttl: Duration) -> Result<()> {
+        let json = serde_json::to_string(value)?;
+        let _: () = self.conn.clone().pset_ex(self.key(name), json, millis(ttl)).await?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, name: &str) -> Result<()> {
+        let _: () = self.conn.clone().del(self.key(name)).await?;
+        Ok(())
+    }
+
+    /// Count a hit against `name` in a window of length `window`.
+    /// Returns the hits so far and the time left in the window.
+    pub async fn hit(&self, name: &str, window: Duration) -> Result<(u64, Duration)> {
+        let (count, ttl_ms): (u64, i64) = Script::new(HIT_SCRIPT)
+            .key(self.key(name))
+            .arg(millis(window))
+            .invoke_async(&mut self.conn.clone())
+            .await?;
+        Ok((count, Duration::from_millis(ttl_ms.max(0) as u64)))
+    }
+
+    /// Take `name` for `ttl` unless another replica holds it. The lease is never
+    /// released early: it expires on its own, so it also marks the work as done.
+    pub async fn claim(&self, name: &str, holder: &str, ttl: Duration) -> Result<bool> {
+        let reply: Option<String> = redis::cmd("SET")
+            .arg(self.key(name))
+            .arg(holder)
+            .arg("NX")
+            .arg("PX")
+            .arg(millis(ttl))
+            .query_async(&mut self.conn.clone())
+            .await?;
+        Ok(reply.is_some())
+    }
+
+    pub async fn publish(&self, channel: &str, payload: &str) -> Result<()> {
+        let _: () = self.conn.clone().publish(self.key(channel), payload).await?;
+        Ok(())
+    }
+
+    /// A dedicated connection subscribed to `channel` (pub/sub can't share the manager's)
+    pub async fn subscribe(&self, channel: &str) -> Result<PubSub> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(self.key(channel)).await?;
+        Ok(pubsub)
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    (duration.as_millis() as u64).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    /// Runs against `TEST_REDIS_URL` when set; there's no Redis in the default test setup
+    async fn test_store() -> Option<RedisStore> {
+        let url = std::env::var("TEST_REDIS_URL").ok()?;
+        let config = RedisConfig { url, key_prefix: format!("test_{}", Uuid::new_v4()) };
+        Some(RedisStore::connect(&config).await.expect("TEST_REDIS_URL is set but unreachable"))
+    }
+
+    #[tokio::test]
+    async fn test_store_round_trip() {
+        let Some(store) = test_store().await else { return };
+
+        store.set_json("alerts:user", &vec![1, 2, 3], Duration::from_secs(60)).await.unwrap();
+        assert_eq!(store.get_json::<Vec<i32>>("alerts:user").await.unwrap(), Some(vec![1, 2, 3]));
+        store.delete("alerts:user").await.unwrap();
+        assert_eq!(store.get_json::<Vec<i32>>("alerts:user").await.unwrap(), None);
+
+        let (count, _) = store.hit("ratelimit:login:1.2.3.4", Duration::from_secs(60)).await.unwrap();
+        assert_eq!(count, 1);
+        let (count, left) = store.hit("ratelimit:login:1.2.3.4", Duration::from_secs(60)).await.unwrap();
+        assert_eq!(count, 2);
+        assert!(left <= Duration::from_secs(60));
+
+        assert!(store.claim("lock:price_check", "a", Duration::from_secs(60)).await.unwrap());
+        assert!(!store.claim("lock:price_check", "b", Duration::from_secs(60)).await.unwrap());
+    }
+
+    #[test]
+    fn test_millis_never_zero() {
+        assert_eq!(millis(Duration::ZERO), 1);
+        assert_eq!(millis(Duration::from_secs(2)), 2000);
+    }
+}
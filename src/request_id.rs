@@ -0,0 +1,97 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderName},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Span;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Span for each request, carrying the request ID so every log line can be grepped by it
+pub fn make_span(request: &Request<Body>) -> Span {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("-");
+    
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        request_id = %request_id,
+    )
+}
+
+/// Append the request ID to plain-text error bodies so users can quote it in bug reports
+pub async fn annotate_errors(request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    
+    let response = next.run(request).await;
+    
+    let is_error = response.status().is_client_error() || response.status().is_server_error();
+    let is_text = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/plain"));
+    let Some(request_id) = request_id.filter(|_| is_error && is_text) else {
+        return response;
+    };
+    
+    let (mut parts, body) = response.into_parts();
+    let message = match axum::body::to_bytes(body, 64 * 1024).await {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).trim_end().to_string(),
+        Err(_) => String::new(),
+    };
+    
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(format!("{} (request id: {})", message, request_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, middleware, routing::get, Router};
+    use tower::ServiceExt;
+    use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+
+    fn app() -> Router {
+        Router::new()
+            .route("/ok", get(|| async { "fine" }))
+            .route("/fail", get(|| async { (StatusCode::BAD_REQUEST, "Invalid alert ID".to_string()) }))
+            .layer(middleware::from_fn(annotate_errors))
+            .layer(PropagateRequestIdLayer::new(REQUEST_ID_HEADER))
+            .layer(SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid))
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_client_request_id_is_propagated_into_errors() {
+        let request = Request::builder().uri("/fail").header("x-request-id", "abc-123").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers()["x-request-id"], "abc-123");
+        assert_eq!(body_text(response).await, "Invalid alert ID (request id: abc-123)");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_generated_and_success_bodies_untouched() {
+        let request = Request::builder().uri("/ok").body(Body::empty()).unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        let request_id = response.headers()["x-request-id"].to_str().unwrap().to_string();
+        assert!(uuid::Uuid::parse_str(&request_id).is_ok());
+        assert_eq!(body_text(response).await, "fine");
+    }
+}
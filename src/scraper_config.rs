@@ -0,0 +1,248 @@
+use crate::db::Database;
+use crate::models::{ScraperConfig, UpsertScraperConfigRequest};
+use crate::scraper_trait::PriceScraper;
+use crate::scrapers::backoff::PlatformBackoff;
+use crate::scrapers::demo::DemoScraper;
+use crate::scrapers::mock::MockScraper;
+use crate::scrapers::{create_configured_scraper, create_scraper, ScraperBackend, MARKETPLACES, PLATFORMS};
+use regex::Regex;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Client, RequestBuilder};
+use scraper::Selector;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// In-memory copy of the `scraper_configs` table plus any remote rules file,
+/// shared by the worker and the API. Local DB overrides win over remote rules.
+#[derive(Clone, Default)]
+pub struct ScraperConfigStore {
+    configs: Arc<RwLock<HashMap<String, ScraperConfig>>>,
+    remote: Arc<RwLock<HashMap<String, ScraperConfig>>>,
+    backend: ScraperBackend,
+    alert_deadline: Option<Duration>,
+    backoff: PlatformBackoff,
+}
+
+impl ScraperConfigStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_backend(mut self, backend: ScraperBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn with_alert_deadline(mut self, deadline: Duration) -> Self {
+        self.alert_deadline = Some(deadline);
+        self
+    }
+
+    /// Longest one alert's scrape may take, redirects and retries included, before the
+    /// worker gives up on it and moves on (`SCRAPER_ALERT_DEADLINE_SECS`, default 90)
+    pub fn alert_deadline(&self) -> Duration {
+        self.alert_deadline.unwrap_or_else(|| {
+            let secs = std::env::var("SCRAPER_ALERT_DEADLINE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(90);
+            Duration::from_secs(secs)
+        })
+    }
+
+    /// The scraper checks should use for `platform`, with its overrides applied
+    pub fn scraper(&self, platform: &str) -> Option<Arc<dyn PriceScraper>> {
+        if let ScraperBackend::Live = self.backend {
+            return create_configured_scraper(platform, self.get(platform));
+        }
+
+        let platform = PLATFORMS.iter().chain(MARKETPLACES).find(|known| **known == platform)?;
+        match &self.backend {
+            ScraperBackend::Mock(script) => Some(Arc::new(MockScraper::new(platform, script.clone()))),
+            _ => Some(Arc::new(DemoScraper::new(platform))),
+        }
+    }
+
+    /// Platforms that rate-limited us and are being left alone for a while
+    pub fn backoff(&self) -> &PlatformBackoff {
+        &self.backoff
+    }
+
+    /// Pause between scrapes so the real sites don't rate limit us; scripted backends don't wait
+    pub fn request_delay(&self) -> Duration {
+        match self.backend {
+            ScraperBackend::Live => Duration::from_secs(2),
+            _ => Duration::ZERO,
+        }
+    }
+
+    pub fn get(&self, platform: &str) -> Option<ScraperConfig> {
+        self.configs
+            .read()
+            .unwrap()
+            .get(platform)
+            .or(self.remote.read().unwrap().get(platform))
+            .cloned()
+    }
+
+    /// Swap in the platforms from a freshly verified remote rules file
+    pub fn set_remote(&self, rules: HashMap<String, ScraperConfig>) {
+        *self.remote.write().unwrap() = rules;
+    }
+
+    /// Replace the cached configs with the current table contents
+    pub async fn reload(&self, db: &Database) -> anyhow::Result<usize> {
+        let configs = db.get_scraper_configs().await?;
+        let count = configs.len();
+
+        *self.configs.write().unwrap() = configs
+            .into_iter()
+            .map(|config| (config.platform.clone(), config))
+            .collect();
+
+        Ok(count)
+    }
+
+    /// Periodically pick up edits made by other instances (or directly in the DB)
+    pub fn spawn_refresh(&self, db: Database, every: Duration) {
+        let store = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = store.reload(&db).await {
+                    tracing::error!("Failed to reload scraper configs: {}", e);
+                }
+            }
+        });
+    }
+}
+
+impl ScraperConfig {
+    /// A client with the platform's connect timeout, when it overrides the shared one
+    pub fn client(&self) -> Option<Client> {
+        let secs = self.connect_timeout_secs.filter(|secs| *secs > 0)?;
+        Some(crate::http_client::with_connect_timeout(Duration::from_secs(secs as u64)))
+    }
+
+    /// Apply configured headers and timeout to a scraper request
+    pub fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        let mut headers = HeaderMap::new();
+        for (name, value) in self.headers.iter() {
+            if let (Ok(name), Ok(value)) = (HeaderName::try_from(name.as_str()), HeaderValue::from_str(value)) {
+                headers.insert(name, value);
+            }
+        }
+
+        let request = request.headers(headers);
+        match self.timeout_secs {
+            Some(secs) if secs > 0 => request.timeout(Duration::from_secs(secs as u64)),
+            _ => request,
+        }
+    }
+}
+
+/// Reject configs that would break scraping for the platform
+pub fn validate_config(platform: &str, config: &UpsertScraperConfigRequest) -> Result<(), String> {
+    if create_scraper(platform).is_none() {
+        return Err(format!("Unknown platform '{}'", platform));
+    }
+
+    for selector in &config.selectors {
+        Selector::parse(selector).map_err(|_| format!("Invalid CSS selector '{}'", selector))?;
+    }
+
+    for pattern in &config.price_patterns {
+        let regex = Regex::new(pattern).map_err(|e| format!("Invalid pattern '{}': {}", pattern, e))?;
+        if regex.captures_len() < 2 {
+            return Err(format!("Pattern '{}' needs a capture group for the price", pattern));
+        }
+    }
+
+    for (name, value) in &config.headers {
+        HeaderName::try_from(name.as_str()).map_err(|_| format!("Invalid header name '{}'", name))?;
+        HeaderValue::from_str(value).map_err(|_| format!("Invalid value for header '{}'", name))?;
+    }
+
+    if let Some(secs) = config.timeout_secs
+        && !(1..=120).contains(&secs)
+    {
+        return Err("timeout_secs must be between 1 and 120".to_string());
+    }
+
+    if let Some(secs) = config.connect_timeout_secs
+        && !(1..=60).contains(&secs)
+    {
+        return Err("connect_timeout_secs must be between 1 and 60".to_string());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::scrapers::strategy::Extractor;
+
+    fn config(selectors: &[&str], price_patterns: &[&str]) -> ScraperConfig {
+        ScraperConfig {
+            platform: "flipkart".to_string(),
+            selectors: selectors.iter().map(|s| s.to_string()).collect(),
+            price_patterns: price_patterns.iter().map(|s| s.to_string()).collect(),
+            headers: sqlx::types::Json(HashMap::new()),
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_extract_price_with_selectors_then_patterns() {
+        let html = r#"<div class="new-price">₹1,299</div><script>{"sellingPrice":899}</script>"#;
+        let extract = |config: ScraperConfig| Extractor::new("Flipkart", Vec::new()).with_config(Some(&config)).extract(html).ok();
+
+        assert_eq!(extract(config(&[".missing", ".new-price"], &[])), Some(1299.0));
+        assert_eq!(extract(config(&[".missing"], &[r#""sellingPrice":(\d+)"#])), Some(899.0));
+        assert_eq!(extract(config(&[".missing"], &[])), None);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let mut request = UpsertScraperConfigRequest {
+            selectors: vec![".Nx9W0j".to_string()],
+            price_patterns: vec![r#""price":(\d+)"#.to_string()],
+            headers: HashMap::from([("Accept-Language".to_string(), "en-IN".to_string())]),
+            timeout_secs: Some(20),
+            connect_timeout_secs: Some(5),
+        };
+        assert!(validate_config("flipkart", &request).is_ok());
+        assert!(validate_config("amazon", &request).is_err());
+
+        request.connect_timeout_secs = Some(0);
+        assert!(validate_config("flipkart", &request).is_err());
+        request.connect_timeout_secs = None;
+
+        request.price_patterns = vec![r#""price":\d+"#.to_string()];
+        assert!(validate_config("flipkart", &request).is_err());
+
+        request.price_patterns.clear();
+        request.selectors = vec!["div[".to_string()];
+        assert!(validate_config("flipkart", &request).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_demo_backend_never_fetches() {
+        let url = "https://www.ajio.com/p/469581234";
+        let store = ScraperConfigStore::new().with_backend(ScraperBackend::Demo);
+        let scraper = store.scraper("ajio").unwrap();
+        assert_eq!(scraper.platform_name(), "ajio");
+        assert_eq!(
+            scraper.get_price(url).await.unwrap(),
+            crate::scrapers::demo::demo_price(url, crate::scrapers::demo::today())
+        );
+        assert!(store.scraper("amazon").is_none());
+    }
+}
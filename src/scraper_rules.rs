@@ -0,0 +1,189 @@
+use crate::models::{ScraperConfig, UpsertScraperConfigRequest};
+use crate::scraper_config::{validate_config, ScraperConfigStore};
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chrono::Utc;
+use reqwest::Client;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Published selector rules, shared by many self-hosted instances
+#[derive(Debug, Deserialize)]
+pub struct RulesFile {
+    /// Monotonic version; older files are ignored so a stale mirror can't roll fixes back
+    pub version: u64,
+    pub platforms: HashMap<String, UpsertScraperConfigRequest>,
+}
+
+/// Fetches a rules file and its detached Ed25519 signature from a URL.
+///
+/// The signature file holds the base64 signature of the exact rules file bytes;
+/// rules that don't verify against `SCRAPER_RULES_PUBLIC_KEY` are discarded.
+pub struct RemoteRules {
+    client: Client,
+    url: String,
+    signature_url: String,
+    public_key: Vec<u8>,
+}
+
+impl RemoteRules {
+    pub fn new(url: String, signature_url: String, public_key: Vec<u8>) -> Self {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        RemoteRules { client, url, signature_url, public_key }
+    }
+
+    /// Configured via SCRAPER_RULES_URL, SCRAPER_RULES_PUBLIC_KEY (base64 Ed25519 key)
+    /// and optionally SCRAPER_RULES_SIGNATURE_URL (defaults to `<url>.sig`)
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(url) = std::env::var("SCRAPER_RULES_URL") else {
+            return Ok(None);
+        };
+
+        let public_key = std::env::var("SCRAPER_RULES_PUBLIC_KEY")
+            .map_err(|_| anyhow!("SCRAPER_RULES_PUBLIC_KEY must be set alongside SCRAPER_RULES_URL"))?;
+        let public_key = BASE64.decode(public_key.trim())?;
+        let signature_url = std::env::var("SCRAPER_RULES_SIGNATURE_URL")
+            .unwrap_or_else(|_| format!("{}.sig", url));
+
+        Ok(Some(Self::new(url, signature_url, public_key)))
+    }
+
+    /// Download and verify the rules file
+    pub async fn fetch(&self) -> Result<RulesFile> {
+        let body = self.client.get(&self.url).send().await?.error_for_status()?.bytes().await?;
+        let signature = self.client.get(&self.signature_url).send().await?.error_for_status()?.text().await?;
+
+        let signature = BASE64.decode(signature.trim())?;
+        UnparsedPublicKey::new(&ED25519, &self.public_key)
+            .verify(&body, &signature)
+            .map_err(|_| anyhow!("Rules file signature does not verify"))?;
+
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Fetch on startup and every `every`, pushing valid platforms into the store
+    pub fn spawn(self, store: ScraperConfigStore, every: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(every);
+            let mut current_version = None;
+
+            loop {
+                ticker.tick().await;
+
+                let rules = match self.fetch().await {
+                    Ok(rules) => rules,
+                    Err(e) => {
+                        tracing::error!("Failed to fetch scraper rules from {}: {}", self.url, e);
+                        continue;
+                    }
+                };
+
+                if current_version.is_some_and(|version| rules.version <= version) {
+                    continue;
+                }
+
+                current_version = Some(rules.version);
+                let configs = to_configs(rules.platforms);
+                tracing::info!("🧩 Applied scraper rules v{} ({} platform(s))", rules.version, configs.len());
+                store.set_remote(configs);
+            }
+        });
+    }
+}
+
+/// Keep only platforms whose rules pass the same checks as the admin API
+fn to_configs(platforms: HashMap<String, UpsertScraperConfigRequest>) -> HashMap<String, ScraperConfig> {
+    platforms
+        .into_iter()
+        .filter_map(|(platform, rules)| {
+            if let Err(e) = validate_config(&platform, &rules) {
+                tracing::warn!("Skipping remote rules for {}: {}", platform, e);
+                return None;
+            }
+
+            let config = ScraperConfig {
+                platform: platform.clone(),
+                selectors: rules.selectors,
+                price_patterns: rules.price_patterns,
+                headers: sqlx::types::Json(rules.headers),
+                timeout_secs: rules.timeout_secs,
+                connect_timeout_secs: rules.connect_timeout_secs,
+                updated_at: Utc::now(),
+            };
+            Some((platform, config))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use ring::rand::SystemRandom;
+    use ring::signature::{Ed25519KeyPair, KeyPair};
+
+    const RULES: &str = r#"{
+        "version": 3,
+        "platforms": {
+            "flipkart": { "selectors": [".hZ3P6w"], "timeout_secs": 20 },
+            "amazon": { "selectors": [".a-price"] }
+        }
+    }"#;
+
+    fn key_pair() -> Ed25519KeyPair {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap()
+    }
+
+    async fn serve(server: &mut Server, signature: String) -> (mockito::Mock, mockito::Mock) {
+        let rules = server.mock("GET", "/rules.json").with_body(RULES).create_async().await;
+        let sig = server.mock("GET", "/rules.json.sig").with_body(signature).create_async().await;
+        (rules, sig)
+    }
+
+    #[tokio::test]
+    async fn test_fetch_verified_rules() {
+        let mut server = Server::new_async().await;
+        let keys = key_pair();
+        let _mocks = serve(&mut server, BASE64.encode(keys.sign(RULES.as_bytes()))).await;
+
+        let url = format!("{}/rules.json", server.url());
+        let remote = RemoteRules::new(url.clone(), format!("{}.sig", url), keys.public_key().as_ref().to_vec());
+        let rules = remote.fetch().await.unwrap();
+        assert_eq!(rules.version, 3);
+
+        // Unknown platforms are dropped rather than failing the whole file
+        let configs = to_configs(rules.platforms);
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs["flipkart"].selectors, vec![".hZ3P6w".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_rules_signed_by_another_key() {
+        let mut server = Server::new_async().await;
+        let _mocks = serve(&mut server, BASE64.encode(key_pair().sign(RULES.as_bytes()))).await;
+
+        let url = format!("{}/rules.json", server.url());
+        let trusted = key_pair().public_key().as_ref().to_vec();
+        let remote = RemoteRules::new(url.clone(), format!("{}.sig", url), trusted);
+
+        assert!(remote.fetch().await.is_err());
+    }
+
+    #[test]
+    fn test_store_serves_remote_rules() {
+        let store = ScraperConfigStore::new();
+        let platforms: HashMap<String, UpsertScraperConfigRequest> =
+            serde_json::from_str::<RulesFile>(RULES).unwrap().platforms;
+        store.set_remote(to_configs(platforms));
+
+        assert_eq!(store.get("flipkart").unwrap().timeout_secs, Some(20));
+        assert!(store.get("myntra").is_none());
+    }
+}
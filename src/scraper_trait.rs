@@ -1,5 +1,74 @@
 use async_trait::async_trait;
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use crate::models::{ListingItem, Offer};
+use serde::Serialize;
+
+/// Everything read from one fetch of a product page
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductSnapshot {
+    /// What the product sells for right now; alerts are evaluated against this
+    pub selling_price: f64,
+    /// List price before any discount, when the page shows one
+    pub mrp: Option<f64>,
+    /// How far `selling_price` is below `mrp`, in percent
+    pub discount_percent: Option<f64>,
+    pub offers: Vec<Offer>,
+    /// Product name and picture, for notifications
+    pub title: Option<String>,
+    pub image_url: Option<String>,
+}
+
+impl ProductSnapshot {
+    /// An MRP below the selling price is some other figure (e.g. a per-unit price), so it's dropped
+    pub fn new(selling_price: f64, mrp: Option<f64>, offers: Vec<Offer>) -> Self {
+        let mrp = mrp.filter(|mrp| *mrp >= selling_price);
+        ProductSnapshot {
+            selling_price,
+            mrp,
+            discount_percent: mrp.map(|mrp| ((mrp - selling_price) / mrp * 1000.0).round() / 10.0),
+            offers,
+            title: None,
+            image_url: None,
+        }
+    }
+
+    pub fn with_details(self, title: Option<String>, image_url: Option<String>) -> Self {
+        ProductSnapshot { title, image_url, ..self }
+    }
+}
+
+/// The site answered 404 or 410 for a page: the product (or search) is no longer listed
+#[derive(Debug)]
+pub struct PageGone {
+    pub status: u16,
+}
+
+impl std::fmt::Display for PageGone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Page returned {}; the product may have been removed", self.status)
+    }
+}
+
+impl std::error::Error for PageGone {}
+
+/// The site answered 429 (or 503 with a `Retry-After`): it wants us to slow down
+#[derive(Debug)]
+pub struct RateLimited {
+    pub status: u16,
+    /// How long it asked us to wait, when it said
+    pub retry_after: Option<std::time::Duration>,
+}
+
+impl std::fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.retry_after {
+            Some(wait) => write!(f, "Rate limited ({}); the site asked us to wait {}s", self.status, wait.as_secs()),
+            None => write!(f, "Rate limited ({})", self.status),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
 
 /// Trait for platform-specific price scrapers
 #[async_trait]
@@ -7,15 +76,54 @@ pub trait PriceScraper: Send + Sync {
     /// Extract the current price from a product URL
     async fn get_price(&self, url: &str) -> Result<f64>;
     
+    /// Selling price, MRP and any coupons/bank offers, from a single page fetch.
+    /// Scrapers that read neither MRP nor offers just wrap `get_price`.
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        Ok(ProductSnapshot::new(self.get_price(url).await?, None, Vec::new()))
+    }
+    
     /// Get the platform name
     fn platform_name(&self) -> &'static str;
     
     /// Validate if a URL belongs to this platform
     fn can_handle(&self, url: &str) -> bool;
+    
+    /// Build the product page URL for a platform product ID (style code, item ID...),
+    /// or None if the ID isn't in this platform's format
+    fn product_url(&self, _product_id: &str) -> Option<String> {
+        None
+    }
+    
+    /// Extract the platform product ID from a product URL
+    fn product_id(&self, _url: &str) -> Option<String> {
+        None
+    }
+    
+    /// Whether the product can currently be bought
+    async fn get_availability(&self, _url: &str) -> Result<bool> {
+        Err(anyhow!("{} availability is not supported", self.platform_name()))
+    }
+    
+    /// Whether the URL is a search or category page this scraper can list
+    fn is_listing_url(&self, _url: &str) -> bool {
+        false
+    }
+    
+    /// The platform's search results page for a free-text query,
+    /// or None if this scraper can't list search results
+    fn search_url(&self, _query: &str) -> Option<String> {
+        None
+    }
+    
+    /// Product cards on a search or category page, in the page's order
+    async fn get_listings(&self, _url: &str) -> Result<Vec<ListingItem>> {
+        Err(anyhow!("{} search pages are not supported", self.platform_name()))
+    }
 }
 
 /// Determine which scraper to use based on URL
 pub fn detect_platform(url: &str) -> Option<&'static str> {
+    let url = desktop_url(url);
     if url.contains("myntra.com") {
         Some("myntra")
     } else if url.contains("flipkart.com") {
@@ -24,7 +132,255 @@ pub fn detect_platform(url: &str) -> Option<&'static str> {
         Some("ajio")
     } else if url.contains("tatacliq.com") {
         Some("tata_cliq")
+    } else if url.contains("shoppersstop.com") {
+        Some("shoppers_stop")
+    } else if url.contains("lifestylestores.com") {
+        Some("lifestyle")
+    } else if url.contains("olx.in") {
+        Some("olx")
+    } else if url.contains("quikr.com") {
+        Some("quikr")
     } else {
         None
     }
 }
+
+/// Each platform's domain, for checking where a URL really points
+const PLATFORM_DOMAINS: &[(&str, &str)] = &[
+    ("myntra", "myntra.com"),
+    ("flipkart", "flipkart.com"),
+    ("ajio", "ajio.com"),
+    ("tata_cliq", "tatacliq.com"),
+    ("shoppers_stop", "shoppersstop.com"),
+    ("lifestyle", "lifestylestores.com"),
+    ("olx", "olx.in"),
+    ("quikr", "quikr.com"),
+];
+
+/// Whether `url` is served by `platform` itself: its host is the platform's domain or a
+/// subdomain of it. `detect_platform` only looks for the domain anywhere in the URL, so
+/// check this before fetching a link someone else supplied.
+pub fn is_platform_host(url: &str, platform: &str) -> bool {
+    let Some((_, domain)) = PLATFORM_DOMAINS.iter().find(|(name, _)| *name == platform) else {
+        return false;
+    };
+    let Ok(parsed) = reqwest::Url::parse(&desktop_url(url)) else {
+        return false;
+    };
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    matches!(parsed.scheme(), "http" | "https")
+        && (host == *domain || host.strip_suffix(domain).is_some_and(|prefix| prefix.ends_with('.')))
+}
+
+/// What kind of page a platform URL points at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PageKind {
+    /// A single product (or ad), which price alerts track
+    Product,
+    /// Search results or a category, which listing alerts track
+    Listing,
+    /// The site's front page
+    Home,
+    /// Anything else: brand stores, account pages, paths the scraper doesn't recognize
+    Other,
+}
+
+impl PageKind {
+    /// Classify `url` by `scraper`'s URL patterns, without fetching it
+    pub fn of(scraper: &dyn PriceScraper, url: &str) -> Self {
+        if scraper.product_id(url).is_some() {
+            PageKind::Product
+        } else if scraper.is_listing_url(url) {
+            PageKind::Listing
+        } else if reqwest::Url::parse(url).is_ok_and(|u| u.path().trim_end_matches('/').is_empty()) {
+            PageKind::Home
+        } else {
+            PageKind::Other
+        }
+    }
+}
+
+/// Each platform's domain and the desktop host its scraper parses
+const DESKTOP_HOSTS: &[(&str, &str)] = &[
+    ("myntra.com", "www.myntra.com"),
+    ("flipkart.com", "www.flipkart.com"),
+    ("ajio.com", "www.ajio.com"),
+    ("tatacliq.com", "www.tatacliq.com"),
+    ("shoppersstop.com", "www.shoppersstop.com"),
+    ("lifestylestores.com", "www.lifestylestores.com"),
+    ("olx.in", "www.olx.in"),
+    ("quikr.com", "www.quikr.com"),
+];
+
+/// Subdomains that serve the same pages as the desktop site (other subdomains,
+/// like Quikr's per-city ones, are left alone)
+const MIRROR_SUBDOMAINS: &[&str] = &["www", "m", "mobile", "amp"];
+
+/// Rewrite mobile (`m.`), bare-domain and AMP variants of a platform URL to its
+/// desktop page, which is what the scrapers parse. Google AMP cache links
+/// (`*.cdn.ampproject.org/c/s/...`) are unwrapped first. Other URLs are only trimmed.
+pub fn desktop_url(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url.trim()) else {
+        return url.trim().to_string();
+    };
+    
+    if parsed.host_str().is_some_and(|host| host.ends_with(".cdn.ampproject.org")) {
+        let wrapped = parsed.path()
+            .strip_prefix("/c/s/")
+            .or_else(|| parsed.path().strip_prefix("/v/s/"))
+            .or_else(|| parsed.path().strip_prefix("/c/"))
+            .or_else(|| parsed.path().strip_prefix("/v/"));
+        let Some(inner) = wrapped.and_then(|path| {
+            let query = parsed.query().map(|q| format!("?{}", q)).unwrap_or_default();
+            reqwest::Url::parse(&format!("https://{}{}", path, query)).ok()
+        }) else {
+            return url.trim().to_string();
+        };
+        parsed = inner;
+    }
+    
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    let Some((_, desktop)) = DESKTOP_HOSTS.iter().find(|(domain, _)| {
+        host == *domain
+            || host
+                .strip_suffix(domain)
+                .and_then(|prefix| prefix.strip_suffix('.'))
+                .is_some_and(|subdomain| MIRROR_SUBDOMAINS.contains(&subdomain))
+    }) else {
+        return parsed.to_string();
+    };
+    
+    let _ = parsed.set_scheme("https");
+    let _ = parsed.set_host(Some(desktop));
+    
+    // AMP pages live under /amp/..., end in /amp, or carry ?amp / ?amp=1
+    let path = parsed.path().to_string();
+    let path = path.strip_prefix("/amp/").map(|rest| format!("/{}", rest)).unwrap_or(path);
+    let path = path.strip_suffix("/amp").filter(|rest| !rest.is_empty()).map(str::to_string).unwrap_or(path);
+    parsed.set_path(&path);
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !key.eq_ignore_ascii_case("amp"))
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    
+    parsed.to_string()
+}
+
+/// Normalize a product URL so the same product matches across alerts:
+/// lowercase host without `www.`, no tracking params, fragment or trailing slash.
+/// Params that select the product (Flipkart's `pid`) are kept, sorted by name.
+pub fn canonical_product_url(url: &str) -> String {
+    let parsed = reqwest::Url::parse(&desktop_url(url))
+        .ok()
+        .filter(|parsed| matches!(parsed.scheme(), "http" | "https") && parsed.host_str().is_some());
+    let Some(parsed) = parsed else {
+        return url.trim().to_string();
+    };
+    let parsed = crate::url_resolver::strip_tracking(parsed);
+    
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    let path = parsed.path().trim_end_matches('/');
+    let base = format!("https://{}{}", host, path);
+    
+    let mut params: Vec<(String, String)> = parsed.query_pairs().into_owned().collect();
+    if params.is_empty() {
+        return base;
+    }
+    params.sort();
+    
+    let Ok(mut canonical) = reqwest::Url::parse(&base) else {
+        return base;
+    };
+    canonical.query_pairs_mut().extend_pairs(params);
+    canonical.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_product_url() {
+        let canonical = "https://myntra.com/shirts/roadster/12345/buy";
+        
+        assert_eq!(canonical_product_url("https://www.myntra.com/shirts/roadster/12345/buy"), canonical);
+        assert_eq!(canonical_product_url("http://WWW.Myntra.com/shirts/roadster/12345/buy/?utm_source=app#reviews"), canonical);
+        assert_eq!(canonical_product_url("not a url"), "not a url");
+        for odd in ["mailto:a%zz?x=1", "javascript:%zz?x=1", "foo:%?a=b", "file:///etc/passwd?x=1"] {
+            assert_eq!(canonical_product_url(odd), odd);
+        }
+        
+        let flipkart = "https://flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234";
+        assert_eq!(
+            canonical_product_url("https://m.flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?affid=abc&pid=TSHFZ6G8HXYZ1234&utm_medium=share"),
+            flipkart
+        );
+        assert_ne!(
+            canonical_product_url("https://www.flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ9999"),
+            flipkart
+        );
+    }
+
+    #[test]
+    fn test_desktop_url() {
+        let desktop = "https://www.flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234";
+        
+        assert_eq!(desktop_url("https://m.flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234"), desktop);
+        assert_eq!(desktop_url("http://flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234&amp=1"), desktop);
+        assert_eq!(
+            desktop_url("https://www-flipkart-com.cdn.ampproject.org/c/s/www.flipkart.com/amp/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234"),
+            desktop
+        );
+        assert_eq!(desktop_url("https://m.myntra.com/shirts/roadster/12345678/buy/amp"), "https://www.myntra.com/shirts/roadster/12345678/buy");
+        assert_eq!(detect_platform("https://www-ajio-com.cdn.ampproject.org/c/s/www.ajio.com/p/469581234"), Some("ajio"));
+        
+        // Quikr's city subdomains are separate pages; unknown hosts are untouched
+        assert_eq!(desktop_url("https://mumbai.quikr.com/shoes/z1"), "https://mumbai.quikr.com/shoes/z1");
+        assert_eq!(desktop_url("http://127.0.0.1:1234/p/1?amp=1"), "http://127.0.0.1:1234/p/1?amp=1");
+    }
+
+    #[test]
+    fn test_is_platform_host() {
+        assert!(is_platform_host("https://www.myntra.com/shirts/roadster/12345678/buy", "myntra"));
+        assert!(is_platform_host("https://mumbai.quikr.com/shoes/z1", "quikr"));
+        assert!(is_platform_host("https://www-ajio-com.cdn.ampproject.org/c/s/www.ajio.com/p/469581234", "ajio"));
+        
+        // The domain has to be the host, not just appear somewhere in the URL
+        assert!(!is_platform_host("http://10.0.0.5/?x=myntra.com", "myntra"));
+        assert!(!is_platform_host("https://myntra.com.evil.example/p/1", "myntra"));
+        assert!(!is_platform_host("https://notmyntra.com/p/1", "myntra"));
+        assert!(!is_platform_host("https://www.flipkart.com/p/itm1", "myntra"));
+    }
+
+    #[test]
+    fn test_page_kind() {
+        let kind = |url: &str| PageKind::of(crate::scrapers::create_scraper(detect_platform(url).unwrap()).unwrap().as_ref(), url);
+        
+        assert_eq!(kind("https://www.myntra.com/shirts/roadster/men-black-formal-shirt/12345678/buy"), PageKind::Product);
+        assert_eq!(kind("https://www.myntra.com/men/formal-shirts?f=Color%3ABlack"), PageKind::Listing);
+        assert_eq!(kind("https://www.myntra.com/"), PageKind::Home);
+        assert_eq!(kind("https://www.ajio.com/levis-slim-fit-jeans/p/469581234_blue"), PageKind::Product);
+        assert_eq!(kind("https://www.ajio.com/search/?text=levis"), PageKind::Listing);
+        assert_eq!(kind("https://www.ajio.com/help/faqs"), PageKind::Other);
+        // Flipkart can't list searches, but a search is still no product
+        assert_eq!(kind("https://www.flipkart.com/search?q=jeans"), PageKind::Other);
+        assert_eq!(kind("https://www.flipkart.com"), PageKind::Home);
+    }
+
+    #[test]
+    fn test_snapshot_discount() {
+        let snapshot = ProductSnapshot::new(1299.0, Some(1999.0), Vec::new());
+        assert_eq!((snapshot.mrp, snapshot.discount_percent), (Some(1999.0), Some(35.0)));
+
+        let snapshot = ProductSnapshot::new(1299.0, Some(199.0), Vec::new());
+        assert_eq!((snapshot.mrp, snapshot.discount_percent), (None, None));
+    }
+}
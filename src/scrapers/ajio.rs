@@ -3,20 +3,138 @@ use anyhow::{Result, anyhow};
 use reqwest::Client;
 use regex::Regex;
 use serde_json::Value;
-use crate::scraper_trait::PriceScraper;
+use std::sync::LazyLock;
+use crate::models::{ListingItem, Offer, ScraperConfig};
+use crate::price_parse::json_price;
+use crate::scraper_trait::{PriceScraper, ProductSnapshot};
+use crate::scrapers::{availability_from_html, offer_kind, check_response, product_details};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{Extractor, JsonLd, PreloadedState};
 
 pub struct AjioScraper {
     client: Client,
+    config: Option<ScraperConfig>,
 }
 
 impl AjioScraper {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36")
-            .build()
-            .expect("Failed to create HTTP client");
+        AjioScraper { client: crate::http_client::shared(), config: None }
+    }
+    
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
+    }
+    
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+            .header("Accept-Language", "en-US,en;q=0.5")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
         
-        AjioScraper { client }
+        read_html(check_response(request.send().await?)?).await
+    }
+    
+    /// Price from an already-fetched product page
+    fn extract_price(&self, url: &str, html: &str) -> Result<f64> {
+        extractor(is_luxe(url), self.config.as_ref()).extract(html)
+    }
+}
+
+/// AJIO Luxe lives on its own sub-domain with a separate (Next.js) storefront
+fn is_luxe(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|u| u.host_str() == Some("luxe.ajio.com"))
+}
+
+const INITIAL_STATE: &str = r#"window\.__INITIAL_STATE__\s*=\s*(\{.*?\});"#;
+
+const NEXT_DATA: &str = r#"(?s)<script id="__NEXT_DATA__"[^>]*>(.*?)</script>"#;
+
+/// Where the selling price is looked for, after any configured selectors. Luxe pages
+/// carry the product in `__NEXT_DATA__` instead of `__INITIAL_STATE__`.
+fn extractor(luxe: bool, config: Option<&ScraperConfig>) -> Extractor {
+    if luxe {
+        return Extractor::new("Ajio Luxe", vec![
+            Box::new(PreloadedState::new("__NEXT_DATA__", NEXT_DATA, "/props/pageProps/product/price/value")),
+            Box::new(PreloadedState::new("__NEXT_DATA__", NEXT_DATA, "/props/pageProps/product/offerPrice/value")),
+            Box::new(JsonLd),
+        ])
+        .with_config(config);
+    }
+    Extractor::new("Ajio", vec![
+        Box::new(PreloadedState::new("__INITIAL_STATE__", INITIAL_STATE, "/product/price/value")),
+        Box::new(PreloadedState::new("__INITIAL_STATE__", INITIAL_STATE, "/product/offerPrice")),
+    ])
+    .with_config(config)
+}
+
+/// MRP (`wasPriceData`) from `__INITIAL_STATE__`, or from `__NEXT_DATA__` on Luxe pages
+fn extract_mrp(url: &str, html: &str) -> Option<f64> {
+    static INITIAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(INITIAL_STATE).unwrap());
+    static NEXT: LazyLock<Regex> = LazyLock::new(|| Regex::new(NEXT_DATA).unwrap());
+
+    let (state, product_path): (&Regex, &[&str]) = if is_luxe(url) {
+        (&NEXT, &["props", "pageProps", "product"])
+    } else {
+        (&INITIAL, &["product"])
+    };
+    let data = serde_json::from_str::<Value>(&state.captures(html)?[1]).ok()?;
+    let product = product_path.iter().fold(&data, |value, key| &value[*key]);
+    json_price(&product["wasPriceData"]["value"])
+}
+
+/// Coupons and bank offers from `product.potentialPromotions` in `__INITIAL_STATE__`
+fn extract_offers(html: &str) -> Vec<Offer> {
+    static INITIAL: LazyLock<Regex> = LazyLock::new(|| Regex::new(INITIAL_STATE).unwrap());
+
+    let Some(data) = INITIAL
+        .captures(html)
+        .and_then(|c| serde_json::from_str::<Value>(&c[1]).ok())
+    else {
+        return Vec::new();
+    };
+
+    data["product"]["potentialPromotions"]
+        .as_array()
+        .map(|promotions| {
+            promotions
+                .iter()
+                .filter_map(|promotion| {
+                    let code = promotion["code"].as_str().map(str::to_string);
+                    let kind = match promotion["promotionType"].as_str() {
+                        Some(label) => offer_kind(label),
+                        None if code.is_some() => "coupon",
+                        None => "other",
+                    };
+                    Some(Offer {
+                        kind: kind.to_string(),
+                        description: promotion["description"].as_str()?.trim().to_string(),
+                        code,
+                        discount: promotion["maxSavingPrice"].as_f64().filter(|d| *d > 0.0),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Default for AjioScraper {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -25,40 +143,40 @@ impl PriceScraper for AjioScraper {
     async fn get_price(&self, url: &str) -> Result<f64> {
         tracing::info!("Scraping Ajio URL: {}", url);
         
-        let response = self.client
-            .get(url)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.5")
-            .send()
-            .await?;
+        let html = self.fetch_html(url).await?;
+        self.extract_price(url, &html)
+    }
+    
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        tracing::info!("Scraping Ajio URL: {}", url);
         
-        let html = response.text().await?;
+        let html = self.fetch_html(url).await?;
+        let (title, image_url) = product_details(&html);
+        Ok(ProductSnapshot::new(
+            self.extract_price(url, &html)?,
+            extract_mrp(url, &html),
+            extract_offers(&html),
+        )
+        .with_details(title, image_url))
+    }
+    
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        let html = self.fetch_html(url).await?;
         
-        // Look for window.__INITIAL_STATE__
-        let re = Regex::new(r#"window\.__INITIAL_STATE__\s*=\s*(\{.*?\});"#)?;
+        let re = Regex::new(INITIAL_STATE)?;
+        if let Some(captures) = re.captures(&html)
+            && let Ok(data) = serde_json::from_str::<Value>(&captures[1])
+            && let Some(status) = data["product"]["stock"]["stockLevelStatus"].as_str()
+        {
+            return Ok(status != "outOfStock");
+        }
         
-        if let Some(captures) = re.captures(&html) {
-            if let Some(json_str) = captures.get(1) {
-                let data: Value = serde_json::from_str(json_str.as_str())?;
-                
-                // Navigate JSON structure to find price
-                // Ajio typically stores price in: product.price.value or similar
-                if let Some(product) = data.get("product") {
-                    if let Some(price) = product["price"]["value"].as_f64() {
-                        tracing::info!("Found Ajio price: ₹{}", price);
-                        return Ok(price);
-                    }
-                    
-                    // Alternative path
-                    if let Some(price) = product["offerPrice"].as_f64() {
-                        tracing::info!("Found Ajio offer price: ₹{}", price);
-                        return Ok(price);
-                    }
-                }
-            }
+        if let Some(in_stock) = availability_from_html(&html, &["OUT OF STOCK", "Out of Stock"]) {
+            return Ok(in_stock);
         }
         
-        Err(anyhow!("Could not find price in Ajio HTML. Site structure may have changed."))
+        // No stock signal: a listed price means it can be bought
+        Ok(self.extract_price(url, &html).is_ok())
     }
     
     fn platform_name(&self) -> &'static str {
@@ -68,4 +186,145 @@ impl PriceScraper for AjioScraper {
     fn can_handle(&self, url: &str) -> bool {
         url.contains("ajio.com")
     }
+    
+    fn product_url(&self, product_id: &str) -> Option<String> {
+        // Product codes look like "469581234_blue" (or just the numeric part)
+        static PRODUCT_CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[0-9]{6,}(_[0-9a-z]+)?$").unwrap());
+        let id = product_id.trim().to_lowercase();
+        PRODUCT_CODE.is_match(&id).then(|| format!("https://www.ajio.com/p/{}", id))
+    }
+    
+    fn product_id(&self, url: &str) -> Option<String> {
+        static CODE_IN_URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"ajio\.com/(?:.*/)?p/([0-9]+(?:_[0-9a-z]+)?)").unwrap());
+        CODE_IN_URL.captures(url).map(|c| c[1].to_string())
+    }
+    
+    fn is_listing_url(&self, url: &str) -> bool {
+        // Search results live under /search/, categories under /<slug>/c/<code>
+        self.can_handle(url)
+            && !is_luxe(url)
+            && reqwest::Url::parse(url).is_ok_and(|u| u.path().starts_with("/search") || u.path().contains("/c/"))
+    }
+    
+    fn search_url(&self, query: &str) -> Option<String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        
+        let mut url = reqwest::Url::parse("https://www.ajio.com/search/").ok()?;
+        url.query_pairs_mut().append_pair("text", query);
+        Some(url.to_string())
+    }
+    
+    async fn get_listings(&self, url: &str) -> Result<Vec<ListingItem>> {
+        tracing::info!("Scraping Ajio listing: {}", url);
+        let html = self.fetch_html(url).await?;
+        
+        let re = Regex::new(INITIAL_STATE)?;
+        let data: Value = re
+            .captures(&html)
+            .and_then(|c| serde_json::from_str(&c[1]).ok())
+            .ok_or_else(|| anyhow!("Could not find search results in Ajio HTML. Site structure may have changed."))?;
+        
+        let base = reqwest::Url::parse(url)?;
+        let results = data["grid"]["results"].as_array().cloned().unwrap_or_default();
+        
+        Ok(results
+            .iter()
+            .filter_map(|product| {
+                Some(ListingItem {
+                    title: product["name"].as_str()?.to_string(),
+                    url: base.join(product["url"].as_str()?).ok()?.to_string(),
+                    price: json_price(&product["price"]["value"])?,
+                    location: None,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper_trait::detect_platform;
+
+    #[test]
+    fn test_ajio_luxe_detection() {
+        let url = "https://luxe.ajio.com/coach-leather-tote/p/469581234_black";
+        let scraper = AjioScraper::new();
+
+        assert!(is_luxe(url));
+        assert!(!is_luxe("https://www.ajio.com/levis-jeans/p/469581234_blue"));
+        assert!(scraper.can_handle(url));
+        assert_eq!(detect_platform(url), Some("ajio"));
+        assert_eq!(scraper.product_id(url).as_deref(), Some("469581234_black"));
+    }
+
+    #[test]
+    fn test_ajio_luxe_price_extraction() {
+        let next_data = r#"
+            <script id="__NEXT_DATA__" type="application/json">
+                {"props": {"pageProps": {"product": {"name": "Leather Tote", "price": {"value": 32990, "currency": "INR"}}}}}
+            </script>
+        "#;
+        let luxe = extractor(true, None);
+        assert_eq!(luxe.extract(next_data).unwrap(), 32990.0);
+
+        let json_ld = r#"<script type="application/ld+json">{"@type": "Product", "offers": {"price": "18500"}}</script>"#;
+        assert_eq!(luxe.extract(json_ld).unwrap(), 18500.0);
+
+        assert!(luxe.extract("<html></html>").is_err());
+        // Main-site state isn't read on Luxe pages
+        assert!(luxe.extract(r#"<script>window.__INITIAL_STATE__ = {"product": {"price": {"value": 999}}};</script>"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ajio_listings() {
+        let mut server = mockito::Server::new_async().await;
+        let mock_html = r#"<script>window.__INITIAL_STATE__ = {"grid": {"results": [{"name": "Levis Slim Fit Jeans", "url": "/levis-slim-fit-jeans/p/469581234_blue", "price": {"value": 1499}}, {"name": "Broken card"}]}};</script>"#;
+        let _m = server.mock("GET", "/search/")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(mock_html)
+            .create_async()
+            .await;
+        
+        let scraper = AjioScraper::new();
+        let items = scraper.get_listings(&format!("{}/search/?text=levis%20jeans", server.url())).await.unwrap();
+        
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].url, format!("{}/levis-slim-fit-jeans/p/469581234_blue", server.url()));
+        assert_eq!(items[0].price, 1499.0);
+        
+        assert!(scraper.is_listing_url("https://www.ajio.com/men-jeans/c/830216001"));
+        assert!(!scraper.is_listing_url("https://www.ajio.com/levis-slim-fit-jeans/p/469581234_blue"));
+        assert_eq!(
+            scraper.search_url("levis jeans").as_deref(),
+            Some("https://www.ajio.com/search/?text=levis+jeans")
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_ajio_product_offers() {
+        let mut server = mockito::Server::new_async().await;
+        let mock_html = r#"<script>window.__INITIAL_STATE__ = {"product": {"price": {"value": 2499}, "wasPriceData": {"value": 4999}, "potentialPromotions": [{"code": "EXTRA300", "description": "Get Extra Rs.300 Off on 1999 and Above", "maxSavingPrice": 300}, {"promotionType": "BANK", "description": "10% Instant Discount on ICICI Bank Cards"}, {"code": "NODESC"}]}};</script>"#;
+        let _m = server.mock("GET", "/p/469581234_blue")
+            .with_status(200)
+            .with_body(mock_html)
+            .create_async()
+            .await;
+        
+        let scraper = AjioScraper::new();
+        let snapshot = scraper.get_product(&format!("{}/p/469581234_blue", server.url())).await.unwrap();
+        
+        assert_eq!(snapshot.selling_price, 2499.0);
+        assert_eq!(snapshot.mrp, Some(4999.0));
+        assert_eq!(snapshot.offers.len(), 2);
+        assert_eq!(snapshot.offers[0].kind, "coupon");
+        assert_eq!(snapshot.offers[0].code.as_deref(), Some("EXTRA300"));
+        assert_eq!(snapshot.offers[0].discount, Some(300.0));
+        assert_eq!(snapshot.offers[1].kind, "bank");
+        assert_eq!(snapshot.offers[1].discount, None);
+    }
 }
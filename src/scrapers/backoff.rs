@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use crate::scraper_trait::RateLimited;
+
+/// How long a platform is left alone after a 429 that doesn't say, unless
+/// `RATE_LIMIT_BACKOFF_SECS` says otherwise. Doubles with each 429 in a row.
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+/// Longest any backoff lasts, so a bogus `Retry-After` can't stop a platform's checks for days
+const MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60);
+
+fn default_backoff() -> Duration {
+    std::env::var("RATE_LIMIT_BACKOFF_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|secs: &u64| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_BACKOFF)
+}
+
+/// A platform that rate-limited us, and until when it's left alone
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Backoff {
+    pub platform: String,
+    /// Status of the last rate-limited response (429, or 503 with `Retry-After`)
+    pub status: u16,
+    /// First 429 of this streak
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Rate-limited responses in a row; a success starts over
+    pub hits: u32,
+    /// Checks skipped instead of sent since the streak began
+    pub skipped: u64,
+}
+
+/// Platforms the worker is backing off from, shared with the API for the status endpoint.
+/// Kept per process, like the scrape metrics; the check lock means one replica does the scraping.
+#[derive(Clone, Default)]
+pub struct PlatformBackoff {
+    platforms: Arc<RwLock<HashMap<String, Backoff>>>,
+}
+
+impl PlatformBackoff {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Back off `platform` for as long as the site asked, or an ever longer default when it didn't
+    pub fn record(&self, platform: &str, limited: &RateLimited) -> Backoff {
+        let now = Utc::now();
+        let mut platforms = self.platforms.write().unwrap();
+        let previous = platforms.get(platform);
+        let hits = previous.map_or(1, |backoff| backoff.hits.saturating_add(1));
+        let wait = limited
+            .retry_after
+            .unwrap_or_else(|| default_backoff().saturating_mul(2u32.saturating_pow(hits - 1)))
+            .min(MAX_BACKOFF);
+
+        let backoff = Backoff {
+            platform: platform.to_string(),
+            status: limited.status,
+            since: previous.map_or(now, |backoff| backoff.since),
+            until: now + chrono::Duration::from_std(wait).unwrap_or_default(),
+            hits,
+            skipped: previous.map_or(0, |backoff| backoff.skipped),
+        };
+        platforms.insert(platform.to_string(), backoff.clone());
+        backoff
+    }
+
+    /// The backoff `platform` is under right now, counting the check that's being skipped for it
+    pub fn skip(&self, platform: &str) -> Option<Backoff> {
+        let mut platforms = self.platforms.write().unwrap();
+        let backoff = platforms.get_mut(platform).filter(|backoff| backoff.until > Utc::now())?;
+        backoff.skipped += 1;
+        Some(backoff.clone())
+    }
+
+    /// A page came back fine, so the platform's streak is over
+    pub fn clear(&self, platform: &str) {
+        if self.platforms.read().unwrap().contains_key(platform) {
+            self.platforms.write().unwrap().remove(platform);
+        }
+    }
+
+    /// Platforms still being backed off from, by platform name
+    pub fn active(&self) -> Vec<Backoff> {
+        let now = Utc::now();
+        let mut active: Vec<Backoff> = self
+            .platforms
+            .read()
+            .unwrap()
+            .values()
+            .filter(|backoff| backoff.until > now)
+            .cloned()
+            .collect();
+        active.sort_by(|a, b| a.platform.cmp(&b.platform));
+        active
+    }
+}
+
+/// `Retry-After` as a wait: either delay-seconds or an HTTP date
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?.with_timezone(&Utc);
+    Some((date - now).to_std().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 16, 7, 28, 0).unwrap();
+        assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Fri, 16 Oct 2026 07:30:00 GMT", now), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Fri, 16 Oct 2026 07:00:00 GMT", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_backoff_streak() {
+        let backoff = PlatformBackoff::new();
+        assert!(backoff.skip("flipkart").is_none());
+
+        let first = backoff.record("flipkart", &RateLimited { status: 429, retry_after: Some(Duration::from_secs(60)) });
+        assert_eq!(first.hits, 1);
+        assert!(first.until - first.since <= chrono::Duration::seconds(61));
+        assert_eq!(backoff.skip("flipkart").map(|b| b.skipped), Some(1));
+        assert!(backoff.skip("myntra").is_none());
+
+        // No Retry-After the second time: the default, doubled, capped
+        let second = backoff.record("flipkart", &RateLimited { status: 429, retry_after: None });
+        assert_eq!((second.hits, second.skipped, second.since), (2, 1, first.since));
+        assert!(second.until - Utc::now() > chrono::Duration::minutes(29));
+        let huge = backoff.record("flipkart", &RateLimited { status: 429, retry_after: Some(Duration::from_secs(7 * 24 * 3600)) });
+        assert!(huge.until - Utc::now() <= chrono::Duration::hours(6));
+
+        assert_eq!(backoff.active().len(), 1);
+        backoff.clear("flipkart");
+        assert!(backoff.active().is_empty());
+        assert!(backoff.skip("flipkart").is_none());
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{bail, Context, Result};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use regex::bytes::Regex;
+use std::io::Read;
+use std::sync::LazyLock;
+
+/// What scrapers ask for. reqwest is built without its decompression features, so
+/// bodies are unpacked here, where a mislabelled one can still be recognised.
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// Where the HTML spec looks for a `<meta charset>` before giving up
+const META_PRESCAN: usize = 1024;
+
+/// A fetched page as text: `Content-Encoding` undone, then decoded with the right charset
+pub(crate) async fn read_html(response: reqwest::Response) -> Result<String> {
+    let header = |name: reqwest::header::HeaderName| {
+        response.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+    };
+    let content_encoding = header(reqwest::header::CONTENT_ENCODING);
+    let content_type = header(reqwest::header::CONTENT_TYPE);
+
+    let bytes = response.bytes().await?;
+    let bytes = decompress(content_encoding.as_deref(), &bytes)?;
+    Ok(decode(content_type.as_deref(), &bytes))
+}
+
+/// Undo each `Content-Encoding` in reverse order of application. A gzip body without
+/// the header (some CDNs drop it) is recognised by its magic bytes.
+pub fn decompress(content_encoding: Option<&str>, bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut body = bytes.to_vec();
+    let codings = content_encoding.unwrap_or_default().split(',').map(|c| c.trim().to_ascii_lowercase()).filter(|c| !c.is_empty());
+    for coding in codings.collect::<Vec<_>>().into_iter().rev() {
+        body = match coding.as_str() {
+            "gzip" | "x-gzip" => read_all(flate2::read::MultiGzDecoder::new(body.as_slice())).context("Bad gzip body")?,
+            // Servers disagree on whether "deflate" means zlib-wrapped or raw
+            "deflate" => read_all(flate2::read::ZlibDecoder::new(body.as_slice()))
+                .or_else(|_| read_all(flate2::read::DeflateDecoder::new(body.as_slice())))
+                .context("Bad deflate body")?,
+            "br" => read_all(brotli_decompressor::Decompressor::new(body.as_slice(), 4096)).context("Bad brotli body")?,
+            "identity" => body,
+            other => bail!("Unsupported Content-Encoding '{}'", other),
+        };
+    }
+
+    if body.starts_with(&[0x1f, 0x8b])
+        && let Ok(unpacked) = read_all(flate2::read::MultiGzDecoder::new(body.as_slice()))
+    {
+        body = unpacked;
+    }
+    Ok(body)
+}
+
+fn read_all(mut reader: impl Read) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out)?;
+    Ok(out)
+}
+
+/// Decode with the charset from a byte-order mark, the `Content-Type` header, or the
+/// page's own `<meta>` tag, in that order; UTF-8 when none says, or Windows-1252 when
+/// the bytes aren't UTF-8. Latin-1 labels are often wrong, so a body that is valid
+/// UTF-8 with non-ASCII in it is read as UTF-8 anyway (otherwise ₹ turns into "â‚¹").
+pub fn decode(content_type: Option<&str>, bytes: &[u8]) -> String {
+    if let Some((encoding, bom)) = Encoding::for_bom(bytes) {
+        return encoding.decode_without_bom_handling(&bytes[bom..]).0.into_owned();
+    }
+
+    let declared = content_type.and_then(header_charset).or_else(|| meta_charset(bytes));
+    let utf8 = std::str::from_utf8(bytes).ok();
+    let encoding = match (declared, utf8) {
+        (Some(encoding), Some(text)) if encoding == WINDOWS_1252 && !text.is_ascii() => UTF_8,
+        (Some(encoding), _) => encoding,
+        (None, Some(_)) => UTF_8,
+        (None, None) => WINDOWS_1252,
+    };
+    encoding.decode_without_bom_handling(bytes).0.into_owned()
+}
+
+/// `charset=` from a `Content-Type` header
+fn header_charset(content_type: &str) -> Option<&'static Encoding> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim().eq_ignore_ascii_case("charset")
+            .then(|| Encoding::for_label(value.trim().trim_matches('"').as_bytes()))
+            .flatten()
+    })
+}
+
+/// `<meta charset="...">` or `<meta http-equiv="Content-Type" content="...; charset=...">`
+/// near the top of the page
+fn meta_charset(bytes: &[u8]) -> Option<&'static Encoding> {
+    static META: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(?i)<meta[^>]+charset\s*=\s*["']?([a-z0-9_:.\-]+)"#).unwrap()
+    });
+
+    let head = &bytes[..bytes.len().min(META_PRESCAN)];
+    let label = META.captures(head)?.get(1)?.as_bytes();
+    // A UTF-16 label in an ASCII-compatible page can't be right; the spec reads it as UTF-8
+    Encoding::for_label(label).map(Encoding::output_encoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GZIP_PAGE: &[u8] = include_bytes!("../../tests/fixtures/pages/flipkart_utf8.html.gz");
+    const BROTLI_PAGE: &[u8] = include_bytes!("../../tests/fixtures/pages/myntra_utf8.html.br");
+    const WINDOWS_1252_PAGE: &[u8] = include_bytes!("../../tests/fixtures/pages/tata_cliq_windows1252.html");
+
+    #[test]
+    fn test_decompress() {
+        let html = decode(None, &decompress(Some("gzip"), GZIP_PAGE).unwrap());
+        assert!(html.contains("₹2,999"));
+        assert!(html.contains("🎉 Festive offer"));
+
+        let html = decode(Some("text/html; charset=utf-8"), &decompress(Some("br"), BROTLI_PAGE).unwrap());
+        assert!(html.contains(r#""discounted": 1299"#));
+        assert!(html.contains("Men’s Shirt ✨"));
+
+        // gzip without the header, and unknown codings
+        assert_eq!(decompress(None, GZIP_PAGE).unwrap(), decompress(Some("gzip"), GZIP_PAGE).unwrap());
+        assert_eq!(decompress(Some("identity"), b"<p>plain</p>").unwrap(), b"<p>plain</p>");
+        assert!(decompress(Some("zstd"), b"").is_err());
+        assert!(decompress(Some("br"), b"not brotli at all").is_err());
+    }
+
+    #[test]
+    fn test_decode_charsets() {
+        // Declared only in <meta>; the header has no charset
+        let html = decode(Some("text/html"), WINDOWS_1252_PAGE);
+        assert!(html.contains("Rs. 1,299 – Café Crème Shirt"));
+        // Nothing declared, and not UTF-8
+        assert_eq!(decode(None, b"<p>Caf\xe9 \x96 Rs. 499</p>"), "<p>Café – Rs. 499</p>");
+
+        // A Latin-1 header on a UTF-8 page
+        let utf8 = "<div class=\"price\">₹1,299</div>".as_bytes();
+        assert_eq!(decode(Some("text/html; charset=ISO-8859-1"), utf8), "<div class=\"price\">₹1,299</div>");
+
+        let mut bom = vec![0xef, 0xbb, 0xbf];
+        bom.extend_from_slice(utf8);
+        assert_eq!(decode(Some("text/html; charset=windows-1252"), &bom), "<div class=\"price\">₹1,299</div>");
+
+        let shift_jis = b"<meta charset=\"shift_jis\"><p>\x83\x65\x83\x58\x83\x67</p>";
+        assert!(decode(None, shift_jis).contains("テスト"));
+    }
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use chrono::Utc;
+use crate::scraper_trait::{detect_platform, PriceScraper};
+
+/// Fake backend for demo mode: never touches the network, and gives every URL a price
+/// that drifts day by day along a fixed curve. The same curve produces the seeded
+/// history, so charts and live checks line up.
+pub struct DemoScraper {
+    platform: &'static str,
+}
+
+impl DemoScraper {
+    pub fn new(platform: &'static str) -> Self {
+        DemoScraper { platform }
+    }
+}
+
+#[async_trait]
+impl PriceScraper for DemoScraper {
+    async fn get_price(&self, url: &str) -> Result<f64> {
+        Ok(demo_price(url, today()))
+    }
+
+    fn platform_name(&self) -> &'static str {
+        self.platform
+    }
+
+    fn can_handle(&self, url: &str) -> bool {
+        detect_platform(url) == Some(self.platform)
+    }
+
+    /// Out of stock roughly one day in eleven
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        Ok((fnv1a(url) as i64 + today()) % 11 != 0)
+    }
+}
+
+/// Days since the Unix epoch, the unit the demo prices move in
+pub fn today() -> i64 {
+    Utc::now().timestamp().div_euclid(86_400)
+}
+
+/// Price of `url` on `day`: a per-URL base of ₹500-₹4,400 swinging ±15% over about a month,
+/// rounded to end in 9 like real listings
+pub fn demo_price(url: &str, day: i64) -> f64 {
+    let hash = fnv1a(url);
+    let base = 500.0 + (hash % 40) as f64 * 100.0;
+    let phase = (hash >> 8) % 31;
+    let swing = 0.15 * ((day + phase as i64) as f64 * std::f64::consts::TAU / 31.0).sin();
+    let price = (base * (1.0 + swing) / 10.0).round() * 10.0 - 1.0;
+    price.max(99.0)
+}
+
+/// Stable across builds and platforms, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_demo_prices_are_deterministic() {
+        let url = "https://www.myntra.com/12345678";
+        let scraper = DemoScraper::new("myntra");
+        assert_eq!(scraper.get_price(url).await.unwrap(), demo_price(url, today()));
+        assert!(scraper.can_handle(url));
+        assert!(!scraper.can_handle("https://www.ajio.com/p/469581234"));
+
+        let month: Vec<f64> = (0..31).map(|day| demo_price(url, 20_000 + day)).collect();
+        assert!(month.iter().all(|price| *price >= 99.0 && price % 10.0 == 9.0));
+        // The curve actually moves, so there is something to alert on
+        let low = month.iter().cloned().fold(f64::MAX, f64::min);
+        let high = month.iter().cloned().fold(f64::MIN, f64::max);
+        assert!(high > low);
+        assert_eq!(month, (0..31).map(|day| demo_price(url, 20_000 + day)).collect::<Vec<_>>());
+    }
+}
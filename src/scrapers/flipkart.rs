@@ -1,32 +1,118 @@
 use async_trait::async_trait;
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use reqwest::Client;
+use regex::Regex;
 use scraper::{Html, Selector};
-use crate::scraper_trait::PriceScraper;
+use std::sync::LazyLock;
+use crate::models::{Offer, ScraperConfig};
+use crate::price_parse::parse_price;
+use crate::scraper_trait::{PriceScraper, ProductSnapshot};
+use crate::scrapers::{availability_from_html, offer_kind, check_response, product_details};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{CssSelector, Extractor};
+use serde_json::Value;
 
 pub struct FlipkartScraper {
     client: Client,
+    config: Option<ScraperConfig>,
 }
 
 impl FlipkartScraper {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36")
-            .build()
-            .expect("Failed to create HTTP client");
-        
-        FlipkartScraper { client }
+        FlipkartScraper { client: crate::http_client::shared(), config: None }
+    }
+    
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
     }
     
-    fn parse_price(&self, price_str: &str) -> Result<f64> {
-        let cleaned = price_str
-            .replace('₹', "")
-            .replace(',', "")
-            .trim()
-            .to_string();
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+            .header("Accept-Language", "en-US,en;q=0.5")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
         
-        cleaned.parse::<f64>()
-            .map_err(|e| anyhow!("Failed to parse price '{}': {}", price_str, e))
+        read_html(check_response(request.send().await?)?).await
+    }
+    
+    /// Price from an already-fetched product page
+    fn extract_price(&self, html: &str) -> Result<f64> {
+        extractor(self.config.as_ref()).extract(html)
+    }
+}
+
+/// Where the selling price is looked for, after any configured selectors
+fn extractor(config: Option<&ScraperConfig>) -> Extractor {
+    // Flipkart changes its class names frequently
+    Extractor::new("Flipkart", vec![
+        Box::new(CssSelector::new(".Nx9W0j")), // Current price selector (2026 spec)
+        Box::new(CssSelector::new(".Nx9bqj")), // Alternative
+        Box::new(CssSelector::new("._30jeq3")), // Alternative
+        Box::new(CssSelector::new("._16Jk6d")), // Another alternative
+        Box::new(CssSelector::new(".CEmiEU")), // Older selector
+    ])
+    .with_config(config)
+}
+
+/// The struck-through MRP next to the selling price, if the product is discounted
+fn extract_mrp(html: &str) -> Option<f64> {
+    let document = Html::parse_document(html);
+    [".yRaY8j", "._3I9_wc"]
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| parse_price(&document.select(&selector).next()?.text().collect::<String>()))
+}
+
+/// Bank offers and coupons from `productPage.offers` in `__INITIAL_STATE__`
+fn extract_offers(html: &str) -> Vec<Offer> {
+    static INITIAL_STATE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"window\.__INITIAL_STATE__\s*=\s*(\{.*?\});"#).unwrap()
+    });
+
+    let Some(data) = INITIAL_STATE
+        .captures(html)
+        .and_then(|c| serde_json::from_str::<Value>(&c[1]).ok())
+    else {
+        return Vec::new();
+    };
+
+    data["productPage"]["offers"]
+        .as_array()
+        .map(|offers| {
+            offers
+                .iter()
+                .filter_map(|offer| {
+                    let description = offer["description"].as_str().or(offer["title"].as_str())?;
+                    Some(Offer {
+                        kind: offer_kind(offer["type"].as_str().unwrap_or_default()).to_string(),
+                        description: description.trim().to_string(),
+                        code: offer["code"].as_str().map(str::to_string),
+                        discount: offer["discountAmount"].as_f64().filter(|d| *d > 0.0),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Default for FlipkartScraper {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -35,38 +121,27 @@ impl PriceScraper for FlipkartScraper {
     async fn get_price(&self, url: &str) -> Result<f64> {
         tracing::info!("Scraping Flipkart URL: {}", url);
         
-        let response = self.client
-            .get(url)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.5")
-            .send()
-            .await?;
-        
-        let html = response.text().await?;
-        let document = Html::parse_document(&html);
-        
-        // Try multiple selectors as Flipkart changes them frequently
-        let selectors = vec![
-            ".Nx9W0j",  // Current price selector (2026 spec)
-            ".Nx9bqj",  // Alternative
-            "._30jeq3", // Alternative
-            "._16Jk6d", // Another alternative
-            ".CEmiEU",  // Older selector
-        ];
-        
-        for selector_str in selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(element) = document.select(&selector).next() {
-                    let price_text = element.text().collect::<String>();
-                    if let Ok(price) = self.parse_price(&price_text) {
-                        tracing::info!("Found Flipkart price: ₹{}", price);
-                        return Ok(price);
-                    }
-                }
-            }
+        let html = self.fetch_html(url).await?;
+        self.extract_price(&html)
+    }
+    
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        tracing::info!("Scraping Flipkart URL: {}", url);
+        
+        let html = self.fetch_html(url).await?;
+        let (title, image_url) = product_details(&html);
+        Ok(ProductSnapshot::new(self.extract_price(&html)?, extract_mrp(&html), extract_offers(&html)).with_details(title, image_url))
+    }
+    
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        let html = self.fetch_html(url).await?;
+        
+        if let Some(in_stock) = availability_from_html(&html, &["Sold Out", "Currently unavailable", "Coming Soon"]) {
+            return Ok(in_stock);
         }
         
-        Err(anyhow!("Could not find price in Flipkart HTML. Site structure may have changed."))
+        // No stock signal: a listed price means it can be bought
+        Ok(self.extract_price(&html).is_ok())
     }
     
     fn platform_name(&self) -> &'static str {
@@ -76,6 +151,18 @@ impl PriceScraper for FlipkartScraper {
     fn can_handle(&self, url: &str) -> bool {
         url.contains("flipkart.com")
     }
+    
+    fn product_url(&self, product_id: &str) -> Option<String> {
+        // Item IDs ("itm6a3d...") resolve regardless of the slug in front of /p/
+        static ITEM_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^itm[0-9a-z]{8,}$").unwrap());
+        let id = product_id.trim().to_lowercase();
+        ITEM_ID.is_match(&id).then(|| format!("https://www.flipkart.com/product/p/{}", id))
+    }
+    
+    fn product_id(&self, url: &str) -> Option<String> {
+        static ITEM_IN_URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"flipkart\.com/.*?/p/(itm[0-9a-z]+)").unwrap());
+        ITEM_IN_URL.captures(url).map(|c| c[1].to_string())
+    }
 }
 
 #[cfg(test)]
@@ -93,6 +180,21 @@ mod tests {
         assert!(!scraper.can_handle("https://www.ajio.com/product"));
     }
 
+    #[test]
+    fn test_flipkart_product_ids() {
+        let scraper = FlipkartScraper::new();
+        
+        assert_eq!(
+            scraper.product_url("itm6a3d8f7c9b2e1").as_deref(),
+            Some("https://www.flipkart.com/product/p/itm6a3d8f7c9b2e1")
+        );
+        assert_eq!(scraper.product_url("12345"), None);
+        assert_eq!(
+            scraper.product_id("https://www.flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234").as_deref(),
+            Some("itm6a3d8f7c9b2e1")
+        );
+    }
+
     #[tokio::test]
     async fn test_flipkart_platform_name() {
         let scraper = FlipkartScraper::new();
@@ -101,12 +203,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_parse_price() {
-        let scraper = FlipkartScraper::new();
-        
-        assert_eq!(scraper.parse_price("₹1,299").unwrap(), 1299.0);
-        assert_eq!(scraper.parse_price("₹999").unwrap(), 999.0);
-        assert_eq!(scraper.parse_price("1,999").unwrap(), 1999.0);
-        assert_eq!(scraper.parse_price(" ₹2,500 ").unwrap(), 2500.0);
+        assert_eq!(parse_price("₹1,299"), Some(1299.0));
+        assert_eq!(parse_price("₹999"), Some(999.0));
+        assert_eq!(parse_price("1,999"), Some(1999.0));
+        assert_eq!(parse_price(" ₹2,500 "), Some(2500.0));
     }
 
     #[tokio::test]
@@ -118,6 +218,7 @@ mod tests {
             <html>
             <body>
                 <div class="Nx9W0j">₹1,499</div>
+                <div class="yRaY8j">₹2,999</div>
             </body>
             </html>
         "#;
@@ -131,9 +232,10 @@ mod tests {
         
         let scraper = FlipkartScraper::new();
         let url = format!("{}/product/123", server.url());
-        let price = scraper.get_price(&url).await.unwrap();
+        let product = scraper.get_product(&url).await.unwrap();
         
-        assert_eq!(price, 1499.0);
+        assert_eq!(product.selling_price, 1499.0);
+        assert_eq!(product.mrp, Some(2999.0));
     }
 
     #[tokio::test]
@@ -163,6 +265,80 @@ mod tests {
         assert_eq!(price, 2999.0);
     }
 
+    #[tokio::test]
+    async fn test_flipkart_configured_selector() {
+        let mut server = Server::new_async().await;
+        
+        // Markup changed to a class the built-in list doesn't know yet
+        let mock_html = r#"
+            <!DOCTYPE html>
+            <html>
+            <body>
+                <div class="hZ3P6w">₹1,799</div>
+            </body>
+            </html>
+        "#;
+        
+        let _m = server.mock("GET", "/product/789")
+            .match_header("accept-language", "en-IN")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(mock_html)
+            .create_async()
+            .await;
+        
+        let config = ScraperConfig {
+            platform: "flipkart".to_string(),
+            selectors: vec![".hZ3P6w".to_string()],
+            price_patterns: vec![],
+            headers: sqlx::types::Json([("Accept-Language".to_string(), "en-IN".to_string())].into()),
+            timeout_secs: Some(10),
+            connect_timeout_secs: Some(5),
+            updated_at: chrono::Utc::now(),
+        };
+        let scraper = FlipkartScraper::new().with_config(Some(config));
+        let url = format!("{}/product/789", server.url());
+        let price = scraper.get_price(&url).await.unwrap();
+        
+        assert_eq!(price, 1799.0);
+    }
+
+    #[tokio::test]
+    async fn test_flipkart_gzip_page() {
+        let mut server = Server::new_async().await;
+        let _m = server.mock("GET", "/product/gz")
+            .match_header("accept-encoding", "gzip, deflate, br")
+            .with_status(200)
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_header("content-encoding", "gzip")
+            .with_body(include_bytes!("../../tests/fixtures/pages/flipkart_utf8.html.gz"))
+            .create_async()
+            .await;
+        
+        let scraper = FlipkartScraper::new();
+        let product = scraper.get_product(&format!("{}/product/gz", server.url())).await.unwrap();
+        
+        assert_eq!((product.selling_price, product.mrp), (2999.0, Some(3999.0)));
+        assert_eq!(product.offers[0].description, "🎉 Festive offer: 10% off on HDFC Bank cards");
+    }
+    
+    #[test]
+    fn test_flipkart_offers() {
+        let html = r#"
+            <div class="Nx9W0j">₹1,499</div>
+            <script>window.__INITIAL_STATE__ = {"productPage": {"offers": [{"type": "BANK_OFFER", "title": "Bank Offer", "description": "Flat ₹100 off on HDFC Bank Credit Card", "discountAmount": 100}, {"type": "COUPON", "description": "Extra ₹50 off", "code": "SAVE50", "discountAmount": 50}, {"type": "SPECIAL_PRICE"}]}};</script>
+        "#;
+        
+        let offers = extract_offers(html);
+        assert_eq!(offers.len(), 2);
+        assert_eq!(offers[0].kind, "bank");
+        assert_eq!(offers[0].discount, Some(100.0));
+        assert_eq!(offers[1].kind, "coupon");
+        assert_eq!(offers[1].code.as_deref(), Some("SAVE50"));
+        
+        assert!(extract_offers("<div>₹999</div>").is_empty());
+    }
+    
     #[tokio::test]
     async fn test_flipkart_price_not_found() {
         let mut server = Server::new_async().await;
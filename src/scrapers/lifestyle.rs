@@ -0,0 +1,221 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use reqwest::Client;
+use regex::Regex;
+use scraper::{Html, Selector};
+use std::sync::LazyLock;
+use crate::models::ScraperConfig;
+use crate::price_parse::{json_price, parse_price};
+use crate::scraper_trait::{PriceScraper, ProductSnapshot};
+use crate::scrapers::{availability_from_html, check_response, product_details};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{CssSelector, Extractor, JsonLd, MetaTag, PreloadedState};
+use serde_json::Value;
+
+/// Lifestyle (Landmark Group) product pages
+pub struct LifestyleScraper {
+    client: Client,
+    config: Option<ScraperConfig>,
+}
+
+impl LifestyleScraper {
+    pub fn new() -> Self {
+        LifestyleScraper { client: crate::http_client::shared(), config: None }
+    }
+    
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
+    }
+    
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+            .header("Accept-Language", "en-IN,en;q=0.9")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
+        
+        read_html(check_response(request.send().await?)?).await
+    }
+    
+    /// Price from an already-fetched product page
+    fn extract_price(&self, html: &str) -> Result<f64> {
+        extractor(self.config.as_ref()).extract(html)
+    }
+}
+
+const PRELOADED_STATE: &str = r#"window\.__PRELOADED_STATE__\s*=\s*(\{.*?\});"#;
+
+/// Where the selling price is looked for, after any configured selectors
+fn extractor(config: Option<&ScraperConfig>) -> Extractor {
+    Extractor::new("Lifestyle", vec![
+        Box::new(JsonLd),
+        Box::new(PreloadedState::new("__PRELOADED_STATE__", PRELOADED_STATE, "/product/details/price/value")),
+        // Server-rendered fallback markup
+        Box::new(CssSelector::new("#details-price .price-value")),
+        Box::new(CssSelector::new("[data-qa='product-price']")),
+        Box::new(CssSelector::new(".product-price .selling-price")),
+        Box::new(MetaTag("product:price:amount")),
+    ])
+    .with_config(config)
+}
+
+/// `(selling price, MRP)` from the product in `window.__PRELOADED_STATE__`,
+/// which the storefront's React app renders from
+fn preloaded_prices(html: &str) -> Option<(f64, Option<f64>)> {
+    static PRELOADED: LazyLock<Regex> = LazyLock::new(|| Regex::new(PRELOADED_STATE).unwrap());
+    
+    let data: Value = serde_json::from_str(&PRELOADED.captures(html)?[1]).ok()?;
+    let product = &data["product"]["details"];
+    let price = json_price(&product["price"]["value"])?;
+    let mrp = json_price(&product["wasPrice"]["value"]).filter(|mrp| *mrp > price);
+    Some((price, mrp))
+}
+
+/// The struck-through MRP next to the selling price, if the product is discounted
+fn extract_mrp(html: &str) -> Option<f64> {
+    if let Some((_, mrp)) = preloaded_prices(html) {
+        return mrp;
+    }
+    let document = Html::parse_document(html);
+    ["#details-price .was-price", "[data-qa='product-mrp']", ".product-price .mrp"]
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| parse_price(&document.select(&selector).next()?.text().collect::<String>()))
+}
+
+impl Default for LifestyleScraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PriceScraper for LifestyleScraper {
+    async fn get_price(&self, url: &str) -> Result<f64> {
+        tracing::info!("Scraping Lifestyle URL: {}", url);
+        
+        let html = self.fetch_html(url).await?;
+        self.extract_price(&html)
+    }
+    
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        tracing::info!("Scraping Lifestyle URL: {}", url);
+        
+        let html = self.fetch_html(url).await?;
+        let (title, image_url) = product_details(&html);
+        Ok(ProductSnapshot::new(self.extract_price(&html)?, extract_mrp(&html), Vec::new()).with_details(title, image_url))
+    }
+    
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        let html = self.fetch_html(url).await?;
+        
+        if let Some(in_stock) = availability_from_html(&html, &["Out of stock", "OUT OF STOCK", "Sold out"]) {
+            return Ok(in_stock);
+        }
+        
+        // No stock signal: a listed price means it can be bought
+        Ok(self.extract_price(&html).is_ok())
+    }
+    
+    fn platform_name(&self) -> &'static str {
+        "lifestyle"
+    }
+    
+    fn can_handle(&self, url: &str) -> bool {
+        url.contains("lifestylestores.com")
+    }
+    
+    fn product_url(&self, product_id: &str) -> Option<String> {
+        // Product codes are 13-digit numbers like "1000012345678"
+        let id = product_id.trim();
+        (id.len() >= 9 && id.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| format!("https://www.lifestylestores.com/in/en/p/{}", id))
+    }
+    
+    fn product_id(&self, url: &str) -> Option<String> {
+        // ".../p/1000012345678-Blue-Blue": the color suffix picks a variant of the same product
+        static CODE_IN_URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"lifestylestores\.com/.*?/p/(\d{9,})").unwrap());
+        CODE_IN_URL.captures(url).map(|c| c[1].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[test]
+    fn test_lifestyle_urls() {
+        let scraper = LifestyleScraper::new();
+        
+        assert!(scraper.can_handle("https://www.lifestylestores.com/in/en/SHOP-Lifestyle-Men-Shirt/p/1000012345678-White-WHITE"));
+        assert!(!scraper.can_handle("https://www.shoppersstop.com/p-206418475"));
+        assert_eq!(
+            scraper.product_id("https://www.lifestylestores.com/in/en/SHOP-Lifestyle-Men-Shirt/p/1000012345678-White-WHITE").as_deref(),
+            Some("1000012345678")
+        );
+        assert_eq!(
+            scraper.product_url("1000012345678").as_deref(),
+            Some("https://www.lifestylestores.com/in/en/p/1000012345678")
+        );
+        assert_eq!(scraper.product_url("12-ab"), None);
+    }
+
+    #[tokio::test]
+    async fn test_lifestyle_price_extraction() {
+        let mut server = Server::new_async().await;
+        
+        let preloaded = r#"
+            <div id="root"></div>
+            <script>window.__PRELOADED_STATE__ = {"product": {"details": {"name": "Men Solid Shirt", "price": {"value": 699}, "wasPrice": {"value": 1299}}}};</script>
+        "#;
+        let _preloaded = server.mock("GET", "/in/en/p/1000012345678-White-WHITE")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(preloaded)
+            .create_async()
+            .await;
+        
+        let markup = r#"<div id="details-price"><span class="price-value">₹ 1,049</span><span class="was-price">₹ 1,499</span></div>"#;
+        let _markup = server.mock("GET", "/in/en/p/1000087654321-Blue-BLUE")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(markup)
+            .create_async()
+            .await;
+        
+        let scraper = LifestyleScraper::new();
+        let product = scraper.get_product(&format!("{}/in/en/p/1000012345678-White-WHITE", server.url())).await.unwrap();
+        assert_eq!((product.selling_price, product.mrp), (699.0, Some(1299.0)));
+        
+        let product = scraper.get_product(&format!("{}/in/en/p/1000087654321-Blue-BLUE", server.url())).await.unwrap();
+        assert_eq!((product.selling_price, product.mrp), (1049.0, Some(1499.0)));
+    }
+    
+    #[tokio::test]
+    async fn test_lifestyle_price_not_found() {
+        let mut server = Server::new_async().await;
+        let _m = server.mock("GET", "/product/invalid")
+            .with_status(200)
+            .with_body("<p>No price here</p>")
+            .create_async()
+            .await;
+        
+        let scraper = LifestyleScraper::new();
+        assert!(scraper.get_price(&format!("{}/product/invalid", server.url())).await.is_err());
+    }
+}
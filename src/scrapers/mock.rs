@@ -0,0 +1,322 @@
+use async_trait::async_trait;
+use anyhow::{Context, Result, anyhow};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::models::{ListingItem, Offer};
+use crate::scraper_trait::{detect_platform, PageGone, PriceScraper, ProductSnapshot, RateLimited};
+
+/// What one read of a mocked page returns
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MockStep {
+    #[serde(default)]
+    pub price: Option<f64>,
+    /// List price shown next to `price`
+    #[serde(default)]
+    pub mrp: Option<f64>,
+    #[serde(default = "in_stock_default")]
+    pub in_stock: bool,
+    #[serde(default)]
+    pub offers: Vec<Offer>,
+    /// Search/category results, for listing URLs
+    #[serde(default)]
+    pub listings: Vec<ListingItem>,
+    /// Fail the read with this message instead
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Answer 404, like a product the site has taken down
+    #[serde(default)]
+    pub gone: bool,
+    /// Answer 429, like a site that's rate limiting us...
+    #[serde(default)]
+    pub rate_limited: bool,
+    /// ...with this `Retry-After`
+    #[serde(default)]
+    pub retry_after_secs: Option<u64>,
+    /// Respond this long after the request, like a slow site
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Product name and picture the page shows
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub image_url: Option<String>,
+}
+
+fn in_stock_default() -> bool {
+    true
+}
+
+impl MockStep {
+    pub fn price(price: f64) -> Self {
+        MockStep { price: Some(price), mrp: None, in_stock: true, offers: Vec::new(), listings: Vec::new(), error: None, gone: false, rate_limited: false, retry_after_secs: None, delay_ms: 0, title: None, image_url: None }
+    }
+
+    pub fn out_of_stock() -> Self {
+        MockStep { price: None, in_stock: false, ..Self::price(0.0) }
+    }
+
+    pub fn error(message: &str) -> Self {
+        MockStep { price: None, error: Some(message.to_string()), ..Self::price(0.0) }
+    }
+
+    pub fn gone() -> Self {
+        MockStep { price: None, gone: true, ..Self::price(0.0) }
+    }
+
+    pub fn rate_limited(retry_after: Option<Duration>) -> Self {
+        MockStep { price: None, rate_limited: true, retry_after_secs: retry_after.map(|wait| wait.as_secs()), ..Self::price(0.0) }
+    }
+
+    pub fn listings(items: Vec<ListingItem>) -> Self {
+        MockStep { price: None, listings: items, ..Self::price(0.0) }
+    }
+
+    pub fn with_mrp(self, mrp: f64) -> Self {
+        MockStep { mrp: Some(mrp), ..self }
+    }
+
+    pub fn with_details(self, title: &str, image_url: &str) -> Self {
+        MockStep { title: Some(title.to_string()), image_url: Some(image_url.to_string()), ..self }
+    }
+
+    pub fn delayed(self, delay: Duration) -> Self {
+        MockStep { delay_ms: delay.as_millis() as u64, ..self }
+    }
+
+    /// The usual story: `checks` reads at `stable`, `checks` at `dropped`, then back to `stable`
+    pub fn stable_drop_recover(stable: f64, dropped: f64, checks: usize) -> Vec<MockStep> {
+        let mut steps = vec![Self::price(stable); checks];
+        steps.extend(vec![Self::price(dropped); checks]);
+        steps.push(Self::price(stable));
+        steps
+    }
+}
+
+/// A step in a fixture file: a bare number is just a price
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FixtureStep {
+    Price(f64),
+    Step(MockStep),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FixtureEntry {
+    Sequence(Vec<FixtureStep>),
+    Single(FixtureStep),
+}
+
+impl From<FixtureStep> for MockStep {
+    fn from(step: FixtureStep) -> Self {
+        match step {
+            FixtureStep::Price(price) => MockStep::price(price),
+            FixtureStep::Step(step) => step,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Script {
+    steps: Vec<MockStep>,
+    /// Index of the next step to hand out
+    next: usize,
+    /// Step read by `get_availability`, which the following `get_price` reuses
+    held: Option<usize>,
+}
+
+/// Scripted pages for `MockScraper`, keyed by URL and shared by every platform's mock.
+///
+/// Each read moves a URL to its next step and the last step repeats forever, so a
+/// sequence plays out over consecutive checks. A restock check's availability and price
+/// reads count as one step.
+#[derive(Debug, Default)]
+pub struct MockScript {
+    pages: Mutex<HashMap<String, Script>>,
+}
+
+impl MockScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fixture JSON: an object mapping URLs to a step or a list of steps, where a step is
+    /// a price or `{"price", "in_stock", "offers", "listings", "error", "gone", "rate_limited",
+    /// "retry_after_secs", "delay_ms"}`
+    pub fn from_json(json: &str) -> Result<Self> {
+        let entries: HashMap<String, FixtureEntry> = serde_json::from_str(json)?;
+        let script = Self::new();
+        for (url, entry) in entries {
+            let steps = match entry {
+                FixtureEntry::Sequence(steps) => steps.into_iter().map(MockStep::from).collect(),
+                FixtureEntry::Single(step) => vec![step.into()],
+            };
+            script.set(&url, steps);
+        }
+        Ok(script)
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        let json = std::fs::read_to_string(path).with_context(|| format!("Failed to read mock fixture {}", path))?;
+        Self::from_json(&json).with_context(|| format!("Invalid mock fixture {}", path))
+    }
+
+    /// Replace the steps for `url` and start it over
+    pub fn set(&self, url: &str, steps: Vec<MockStep>) {
+        self.pages
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), Script { steps, ..Script::default() });
+    }
+
+    /// Builder form of `set`
+    pub fn with(self, url: &str, steps: Vec<MockStep>) -> Self {
+        self.set(url, steps);
+        self
+    }
+
+    /// How many steps of `url` have been read so far
+    pub fn reads(&self, url: &str) -> usize {
+        self.pages.lock().unwrap().get(url).map_or(0, |script| script.next)
+    }
+
+    fn read(&self, url: &str, hold: bool) -> Result<MockStep> {
+        let mut pages = self.pages.lock().unwrap();
+        let script = pages
+            .get_mut(url)
+            .filter(|script| !script.steps.is_empty())
+            .ok_or_else(|| anyhow!("No mock page scripted for {}", url))?;
+
+        let index = match script.held.take() {
+            Some(index) if !hold => index,
+            _ => {
+                let index = script.next.min(script.steps.len() - 1);
+                script.next += 1;
+                index
+            }
+        };
+        if hold {
+            script.held = Some(index);
+        }
+
+        Ok(script.steps[index].clone())
+    }
+
+    /// Read the next step, taking as long as it says to
+    async fn play(&self, url: &str, hold: bool) -> Result<MockStep> {
+        let step = self.read(url, hold)?;
+        if step.delay_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(step.delay_ms)).await;
+        }
+        if step.gone {
+            return Err(PageGone { status: 404 }.into());
+        }
+        if step.rate_limited {
+            return Err(RateLimited { status: 429, retry_after: step.retry_after_secs.map(Duration::from_secs) }.into());
+        }
+        match &step.error {
+            Some(message) => Err(anyhow!("{}", message)),
+            None => Ok(step),
+        }
+    }
+}
+
+/// `PriceScraper` that serves a `MockScript` instead of fetching anything,
+/// for integration tests and offline development (`SCRAPER_BACKEND=mock`)
+pub struct MockScraper {
+    platform: &'static str,
+    script: Arc<MockScript>,
+}
+
+impl MockScraper {
+    pub fn new(platform: &'static str, script: Arc<MockScript>) -> Self {
+        MockScraper { platform, script }
+    }
+}
+
+#[async_trait]
+impl PriceScraper for MockScraper {
+    async fn get_price(&self, url: &str) -> Result<f64> {
+        Ok(self.get_product(url).await?.selling_price)
+    }
+
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        let step = self.script.play(url, false).await?;
+        let price = step.price.ok_or_else(|| anyhow!("Could not find price on {}", url))?;
+        Ok(ProductSnapshot::new(price, step.mrp, step.offers).with_details(step.title, step.image_url))
+    }
+
+    fn platform_name(&self) -> &'static str {
+        self.platform
+    }
+
+    fn can_handle(&self, url: &str) -> bool {
+        detect_platform(url) == Some(self.platform)
+    }
+
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        Ok(self.script.play(url, true).await?.in_stock)
+    }
+
+    fn is_listing_url(&self, url: &str) -> bool {
+        self.can_handle(url)
+    }
+
+    async fn get_listings(&self, url: &str) -> Result<Vec<ListingItem>> {
+        Ok(self.script.play(url, false).await?.listings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://www.myntra.com/12345678";
+
+    #[tokio::test]
+    async fn test_scripted_sequence_then_repeats_last_step() {
+        let script = Arc::new(MockScript::new().with(URL, MockStep::stable_drop_recover(1299.0, 899.0, 2)));
+        let scraper = MockScraper::new("myntra", script.clone());
+
+        let mut prices = Vec::new();
+        for _ in 0..7 {
+            prices.push(scraper.get_price(URL).await.unwrap());
+        }
+        assert_eq!(prices, vec![1299.0, 1299.0, 899.0, 899.0, 1299.0, 1299.0, 1299.0]);
+        assert_eq!(script.reads(URL), 7);
+
+        assert!(scraper.get_price("https://www.myntra.com/999").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_availability_and_price_share_a_step() {
+        let script = Arc::new(MockScript::new().with(URL, vec![MockStep::out_of_stock(), MockStep::price(999.0)]));
+        let scraper = MockScraper::new("myntra", script.clone());
+
+        assert!(!scraper.get_availability(URL).await.unwrap());
+        assert!(scraper.get_availability(URL).await.unwrap());
+        assert_eq!(scraper.get_price(URL).await.unwrap(), 999.0);
+        assert_eq!(script.reads(URL), 2);
+    }
+
+    #[tokio::test]
+    async fn test_fixture_file() {
+        let script = Arc::new(MockScript::from_file("tests/fixtures/mock_pages.json").unwrap());
+        let scraper = MockScraper::new("myntra", script);
+
+        assert_eq!(scraper.get_price(URL).await.unwrap(), 1299.0);
+        assert_eq!(scraper.get_price(URL).await.unwrap(), 899.0);
+
+        let error = scraper.get_price("https://www.myntra.com/87654321").await.unwrap_err();
+        assert_eq!(crate::metrics::classify_error(&error), "price_not_found");
+
+        let product = scraper.get_product("https://www.myntra.com/55555555").await.unwrap();
+        assert_eq!((product.selling_price, product.mrp), (2499.0, Some(3999.0)));
+        assert_eq!(product.offers[0].kind, "bank");
+
+        let items = scraper.get_listings("https://www.myntra.com/men-jeans").await.unwrap();
+        assert_eq!(items.len(), 1);
+    }
+}
@@ -2,16 +2,324 @@ pub mod myntra;
 pub mod flipkart;
 pub mod ajio;
 pub mod tata_cliq;
+pub mod shoppers_stop;
+pub mod lifestyle;
+pub mod olx;
+pub mod quikr;
+pub mod demo;
+pub mod mock;
+pub mod body;
+pub mod backoff;
+pub mod strategy;
 
-use crate::scraper_trait::PriceScraper;
+use crate::models::{ListingItem, ScraperConfig};
+use crate::price_parse::json_price;
+use crate::scraper_trait::{PageGone, PriceScraper, RateLimited};
+use scraper::{Html, Selector};
+use serde_json::Value;
 use std::sync::Arc;
 
+/// Every platform with a scraper, in the order searches are run
+pub const PLATFORMS: &[&str] = &["myntra", "flipkart", "ajio", "tata_cliq", "shoppers_stop", "lifestyle"];
+
+/// Second-hand classifieds. Tracked through listing alerts on their search pages
+/// (or single ads); keyword watches don't search them, since used items would
+/// drown out new ones.
+pub const MARKETPLACES: &[&str] = &["olx", "quikr"];
+
+/// Each platform's display name and home page, whose favicon stands in for its logo
+const SITES: &[(&str, &str, &str)] = &[
+    ("myntra", "Myntra", "https://www.myntra.com"),
+    ("flipkart", "Flipkart", "https://www.flipkart.com"),
+    ("ajio", "AJIO", "https://www.ajio.com"),
+    ("tata_cliq", "Tata CLiQ", "https://www.tatacliq.com"),
+    ("shoppers_stop", "Shoppers Stop", "https://www.shoppersstop.com"),
+    ("lifestyle", "Lifestyle", "https://www.lifestylestores.com"),
+    ("olx", "OLX", "https://www.olx.in"),
+    ("quikr", "Quikr", "https://www.quikr.com"),
+];
+
+/// Display name and home page of a platform in `PLATFORMS` or `MARKETPLACES`
+pub fn platform_site(platform: &str) -> Option<(&'static str, &'static str)> {
+    SITES.iter().find(|(name, ..)| *name == platform).map(|&(_, display_name, home)| (display_name, home))
+}
+
 pub fn create_scraper(platform: &str) -> Option<Arc<dyn PriceScraper>> {
+    create_configured_scraper(platform, None)
+}
+
+/// Where the worker's scrapers get their prices
+#[derive(Debug, Clone, Default)]
+pub enum ScraperBackend {
+    /// The real product pages
+    #[default]
+    Live,
+    /// `demo::DemoScraper`'s made-up prices, for demo mode
+    Demo,
+    /// Scripted pages, for tests and offline development
+    Mock(Arc<mock::MockScript>),
+}
+
+impl ScraperBackend {
+    /// `SCRAPER_BACKEND`: `live` (default), `demo`, or `mock` with the pages
+    /// in the `MOCK_SCRAPER_FIXTURE` JSON file
+    pub fn from_env() -> anyhow::Result<Self> {
+        let backend = std::env::var("SCRAPER_BACKEND").unwrap_or_default();
+        match backend.trim().to_lowercase().as_str() {
+            "" | "live" => Ok(ScraperBackend::Live),
+            "demo" => Ok(ScraperBackend::Demo),
+            "mock" => {
+                let path = std::env::var("MOCK_SCRAPER_FIXTURE")
+                    .map_err(|_| anyhow::anyhow!("SCRAPER_BACKEND=mock needs MOCK_SCRAPER_FIXTURE"))?;
+                Ok(ScraperBackend::Mock(Arc::new(mock::MockScript::from_file(&path)?)))
+            }
+            other => Err(anyhow::anyhow!("Unknown SCRAPER_BACKEND '{}' (expected live, demo or mock)", other)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScraperBackend::Live => "live",
+            ScraperBackend::Demo => "demo",
+            ScraperBackend::Mock(_) => "mock",
+        }
+    }
+}
+
+/// Like `create_scraper`, with the platform's overrides from `scraper_configs` applied
+pub fn create_configured_scraper(platform: &str, config: Option<ScraperConfig>) -> Option<Arc<dyn PriceScraper>> {
     match platform {
-        "myntra" => Some(Arc::new(myntra::MyntraScraper::new())),
-        "flipkart" => Some(Arc::new(flipkart::FlipkartScraper::new())),
-        "ajio" => Some(Arc::new(ajio::AjioScraper::new())),
-        "tata_cliq" => Some(Arc::new(tata_cliq::TataCliqScraper::new())),
+        "myntra" => Some(Arc::new(myntra::MyntraScraper::new().with_config(config))),
+        "flipkart" => Some(Arc::new(flipkart::FlipkartScraper::new().with_config(config))),
+        "ajio" => Some(Arc::new(ajio::AjioScraper::new().with_config(config))),
+        "tata_cliq" => Some(Arc::new(tata_cliq::TataCliqScraper::new().with_config(config))),
+        "shoppers_stop" => Some(Arc::new(shoppers_stop::ShoppersStopScraper::new().with_config(config))),
+        "lifestyle" => Some(Arc::new(lifestyle::LifestyleScraper::new().with_config(config))),
+        "olx" => Some(Arc::new(olx::OlxScraper::new().with_config(config))),
+        "quikr" => Some(Arc::new(quikr::QuikrScraper::new().with_config(config))),
         _ => None,
     }
 }
+
+/// schema.org `Product` objects from the page's JSON-LD blocks
+fn json_ld_products(html: &str) -> Vec<Value> {
+    let document = Html::parse_document(html);
+    let Ok(selector) = Selector::parse(r#"script[type="application/ld+json"]"#) else {
+        return Vec::new();
+    };
+
+    document
+        .select(&selector)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.text().collect::<String>()).ok())
+        .flat_map(|data| match data {
+            Value::Array(items) => items,
+            Value::Object(ref object) if object.contains_key("@graph") => {
+                data["@graph"].as_array().cloned().unwrap_or_default()
+            }
+            other => vec![other],
+        })
+        .filter(|item| item["@type"] == "Product")
+        .collect()
+}
+
+/// The first offer of a product (`offers` may be a single object or a list)
+fn first_offer(product: &Value) -> &Value {
+    let offers = &product["offers"];
+    offers.as_array().and_then(|o| o.first()).unwrap_or(offers)
+}
+
+/// `offers.price` from a schema.org `Product` in the page's JSON-LD blocks
+pub(crate) fn json_ld_price(html: &str) -> Option<f64> {
+    json_ld_products(html)
+        .iter()
+        .find_map(|product| json_price(&first_offer(product)["price"]))
+}
+
+/// The product's name and picture: from its JSON-LD `Product` (whose `image` may be a URL,
+/// a list of them or an `ImageObject`), else the page's `og:title`/`og:image`
+pub(crate) fn product_details(html: &str) -> (Option<String>, Option<String>) {
+    let products = json_ld_products(html);
+    let text = |value: &Value| value.as_str().map(str::trim).filter(|text| !text.is_empty()).map(str::to_string);
+    let image = |value: &Value| match value {
+        Value::Array(images) => images.first().and_then(|image| text(image).or_else(|| text(&image["url"]))),
+        image => text(image).or_else(|| text(&image["url"])),
+    };
+
+    let document = Html::parse_document(html);
+    let meta = |property: &str| {
+        let selector = Selector::parse(&format!(r#"meta[property="{}"]"#, property)).ok()?;
+        document
+            .select(&selector)
+            .find_map(|meta| meta.value().attr("content"))
+            .map(str::trim)
+            .filter(|content| !content.is_empty())
+            .map(str::to_string)
+    };
+    (
+        products.iter().find_map(|product| text(&product["name"])).or_else(|| meta("og:title")),
+        products.iter().find_map(|product| image(&product["image"])).or_else(|| meta("og:image")),
+    )
+}
+
+/// Results from a schema.org `ItemList` in the page's JSON-LD blocks, whose
+/// `itemListElement`s are `Product`s (or `ListItem`s wrapping one)
+pub(crate) fn json_ld_item_list(html: &str, base: &reqwest::Url) -> Vec<ListingItem> {
+    let document = Html::parse_document(html);
+    let Ok(selector) = Selector::parse(r#"script[type="application/ld+json"]"#) else {
+        return Vec::new();
+    };
+
+    document
+        .select(&selector)
+        .filter_map(|script| serde_json::from_str::<Value>(&script.text().collect::<String>()).ok())
+        .filter(|data| data["@type"] == "ItemList")
+        .flat_map(|data| data["itemListElement"].as_array().cloned().unwrap_or_default())
+        .filter_map(|element| {
+            let product = if element["item"].is_object() { &element["item"] } else { &element };
+            Some(ListingItem {
+                title: product["name"].as_str()?.trim().to_string(),
+                url: base.join(product["url"].as_str().or(element["url"].as_str())?).ok()?.to_string(),
+                price: json_price(&first_offer(product)["price"])?,
+                location: first_offer(product)["availableAtOrFrom"]["address"]["addressLocality"]
+                    .as_str()
+                    .map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+/// The fetched page, or `PageGone` when the site says it no longer exists, or `RateLimited`
+/// when it's telling us to slow down. Other error statuses still go through, since block
+/// pages and the like are reported by parsing.
+pub(crate) fn check_response(response: reqwest::Response) -> anyhow::Result<reqwest::Response> {
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| backoff::parse_retry_after(value, chrono::Utc::now()));
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE => {
+            Err(PageGone { status: response.status().as_u16() }.into())
+        }
+        reqwest::StatusCode::TOO_MANY_REQUESTS => Err(RateLimited { status: 429, retry_after }.into()),
+        // Some CDNs rate-limit with a 503; only one that says when to come back counts
+        reqwest::StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => {
+            Err(RateLimited { status: 503, retry_after }.into())
+        }
+        _ => Ok(response),
+    }
+}
+
+/// Stock status from JSON-LD `offers.availability`, then from sold-out banners in the page
+pub(crate) fn availability_from_html(html: &str, sold_out_markers: &[&str]) -> Option<bool> {
+    let from_json_ld = json_ld_products(html).iter().find_map(|product| {
+        let availability = first_offer(product)["availability"].as_str()?;
+        if availability.ends_with("InStock") || availability.ends_with("LimitedAvailability") {
+            Some(true)
+        } else if availability.ends_with("OutOfStock") || availability.ends_with("SoldOut") || availability.ends_with("Discontinued") {
+            Some(false)
+        } else {
+            None
+        }
+    });
+
+    from_json_ld.or_else(|| sold_out_markers.iter().any(|marker| html.contains(marker)).then_some(false))
+}
+
+/// Normalizes a platform's offer type label ("BANK_OFFER", "Coupon", "PROMO_CODE"...) to an `Offer::kind`
+pub(crate) fn offer_kind(label: &str) -> &'static str {
+    let label = label.to_lowercase();
+    if label.contains("bank") || label.contains("card") {
+        "bank"
+    } else if label.contains("coupon") || label.contains("code") {
+        "coupon"
+    } else {
+        "other"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_ld_price() {
+        let html = r#"
+            <script type="application/ld+json">{"@type": "BreadcrumbList"}</script>
+            <script type="application/ld+json">
+                {"@type": "Product", "name": "Leather Tote", "offers": [{"@type": "Offer", "price": "24,990", "priceCurrency": "INR"}]}
+            </script>
+        "#;
+        assert_eq!(json_ld_price(html), Some(24990.0));
+
+        let graph = r#"<script type="application/ld+json">{"@graph": [{"@type": "Product", "offers": {"price": 12500}}]}</script>"#;
+        assert_eq!(json_ld_price(graph), Some(12500.0));
+
+        assert_eq!(json_ld_price("<p>no structured data</p>"), None);
+    }
+
+    #[test]
+    fn test_product_details() {
+        let html = r#"
+            <meta property="og:title" content="Roadster Men Black Tee">
+            <script type="application/ld+json">
+                {"@type": "Product", "name": "Leather Tote", "image": [{"@type": "ImageObject", "url": "https://cdn.example.com/tote.jpg"}]}
+            </script>
+        "#;
+        assert_eq!(
+            product_details(html),
+            (Some("Leather Tote".to_string()), Some("https://cdn.example.com/tote.jpg".to_string()))
+        );
+
+        let og = r#"<meta property="og:title" content=" Roadster Men Black Tee "><meta property="og:image" content="https://cdn.example.com/tee.jpg">"#;
+        assert_eq!(
+            product_details(og),
+            (Some("Roadster Men Black Tee".to_string()), Some("https://cdn.example.com/tee.jpg".to_string()))
+        );
+        assert_eq!(product_details("<p>₹999</p>"), (None, None));
+    }
+
+    #[test]
+    fn test_json_ld_item_list() {
+        let html = r#"<script type="application/ld+json">{"@type": "ItemList", "itemListElement": [
+            {"@type": "ListItem", "position": 1, "item": {"@type": "Product", "name": "Jordan 1 Mid", "url": "/ad/jordan-1-mid",
+                "offers": {"price": "5,800", "availableAtOrFrom": {"address": {"addressLocality": "Pune"}}}}},
+            {"@type": "Product", "name": "No price", "url": "/ad/no-price"}
+        ]}</script>"#;
+        let base = reqwest::Url::parse("https://www.quikr.com/shoes").unwrap();
+        let items = json_ld_item_list(html, &base);
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].url, "https://www.quikr.com/ad/jordan-1-mid");
+        assert_eq!((items[0].price, items[0].location.as_deref()), (5800.0, Some("Pune")));
+    }
+
+    #[test]
+    fn test_availability_from_html() {
+        let in_stock = r#"<script type="application/ld+json">{"@type": "Product", "offers": {"availability": "https://schema.org/InStock"}}</script>"#;
+        let sold_out = r#"<script type="application/ld+json">{"@type": "Product", "offers": {"availability": "http://schema.org/OutOfStock"}}</script>"#;
+
+        assert_eq!(availability_from_html(in_stock, &["Sold Out"]), Some(true));
+        assert_eq!(availability_from_html(sold_out, &[]), Some(false));
+        assert_eq!(availability_from_html("<div>This item is Sold Out</div>", &["Sold Out"]), Some(false));
+        assert_eq!(availability_from_html("<div>₹999</div>", &["Sold Out"]), None);
+    }
+
+    #[test]
+    fn test_every_platform_has_a_site() {
+        for platform in PLATFORMS.iter().chain(MARKETPLACES) {
+            assert!(platform_site(platform).is_some(), "{} has no display name", platform);
+        }
+        assert_eq!(platform_site("tata_cliq"), Some(("Tata CLiQ", "https://www.tatacliq.com")));
+        assert_eq!(platform_site("amazon"), None);
+    }
+
+    #[test]
+    fn test_offer_kind() {
+        assert_eq!(offer_kind("BANK_OFFER"), "bank");
+        assert_eq!(offer_kind("CreditCard"), "bank");
+        assert_eq!(offer_kind("Coupon"), "coupon");
+        assert_eq!(offer_kind("PROMO_CODE"), "coupon");
+        assert_eq!(offer_kind("SPECIAL_PRICE"), "other");
+    }
+}
@@ -3,20 +3,101 @@ use anyhow::{Result, anyhow};
 use reqwest::Client;
 use regex::Regex;
 use serde_json::Value;
-use crate::scraper_trait::PriceScraper;
+use std::sync::LazyLock;
+use crate::models::{ListingItem, ScraperConfig};
+use crate::price_parse::json_price;
+use crate::scraper_trait::{PriceScraper, ProductSnapshot};
+use crate::scrapers::{availability_from_html, check_response, product_details};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{ExtractionStrategy, Extractor, JsonLd, PreloadedState};
 
 pub struct MyntraScraper {
     client: Client,
+    config: Option<ScraperConfig>,
 }
 
 impl MyntraScraper {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36")
-            .build()
-            .expect("Failed to create HTTP client");
+        MyntraScraper { client: crate::http_client::shared(), config: None }
+    }
+    
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
+    }
+    
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+            .header("Accept-Language", "en-US,en;q=0.5")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
         
-        MyntraScraper { client }
+        read_html(check_response(request.send().await?)?).await
+    }
+    
+    /// Price from an already-fetched product page
+    fn extract_price(&self, url: &str, html: &str) -> Result<f64> {
+        extractor(is_luxe(url), self.config.as_ref()).extract(html)
+    }
+}
+
+/// `window.__myntra_preloaded_state__` (2026 spec)
+const PRELOADED_STATE: &str = r#"window\.__myntra_preloaded_state__\s*=\s*(\{[\s\S]*?\});"#;
+
+/// Older pages' `pdpData` object in a script tag
+const PDP_DATA: &str = r#"pdpData["\s:=]+(\{.*?\})\s*[,;]"#;
+
+/// Where the selling price is looked for, after any configured selectors. Only the
+/// discounted price is the selling price; MRP is reported separately by `extract_mrp`.
+fn extractor(luxe: bool, config: Option<&ScraperConfig>) -> Extractor {
+    let mut strategies: Vec<Box<dyn ExtractionStrategy>> = Vec::new();
+    // Luxe pages don't ship the preloaded state; the price is in the schema.org markup
+    if luxe {
+        strategies.push(Box::new(JsonLd));
+    }
+    strategies.push(Box::new(PreloadedState::new("__myntra_preloaded_state__", PRELOADED_STATE, "/pdpData/price/discounted")));
+    strategies.push(Box::new(PreloadedState::new("pdpData", PDP_DATA, "/price/discounted")));
+    Extractor::new(if luxe { "Myntra Luxe" } else { "Myntra" }, strategies).with_config(config)
+}
+
+/// MRP from the preloaded state or pdpData, if the page has one
+fn extract_mrp(html: &str) -> Option<f64> {
+    static PRELOADED: LazyLock<Regex> = LazyLock::new(|| Regex::new(PRELOADED_STATE).unwrap());
+    static PDP: LazyLock<Regex> = LazyLock::new(|| Regex::new(PDP_DATA).unwrap());
+
+    if let Some(captures) = PRELOADED.captures(html)
+        && let Ok(data) = serde_json::from_str::<Value>(&captures[1])
+        && let Some(mrp) = json_price(&data["pdpData"]["price"]["mrp"])
+    {
+        return Some(mrp);
+    }
+
+    let data = serde_json::from_str::<Value>(&PDP.captures(html)?[1]).ok()?;
+    json_price(&data["price"]["mrp"]).or_else(|| json_price(&data["mrp"]))
+}
+
+/// Myntra Luxe products are served under `/luxe/...` with a server-rendered page
+fn is_luxe(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|u| u.path().starts_with("/luxe/"))
+}
+
+impl Default for MyntraScraper {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -25,52 +106,40 @@ impl PriceScraper for MyntraScraper {
     async fn get_price(&self, url: &str) -> Result<f64> {
         tracing::info!("Scraping Myntra URL: {}", url);
         
-        let response = self.client
-            .get(url)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.5")
-            .send()
-            .await?;
-        
-        let html = response.text().await?;
-        
-        // Primary: Look for window.__myntra_preloaded_state__ (2026 spec)
-        let re_preloaded = Regex::new(r#"window\.__myntra_preloaded_state__\s*=\s*(\{[\s\S]*?\});"#)?;
-        if let Some(captures) = re_preloaded.captures(&html) {
-            if let Some(json_str) = captures.get(1) {
-                if let Ok(data) = serde_json::from_str::<Value>(json_str.as_str()) {
-                    // Navigate the preloaded state structure
-                    if let Some(price) = data["pdpData"]["price"]["discounted"].as_f64() {
-                        tracing::info!("Found Myntra price (preloaded_state): ₹{}", price);
-                        return Ok(price);
-                    }
-                    if let Some(price) = data["pdpData"]["price"]["mrp"].as_f64() {
-                        tracing::info!("Found Myntra MRP (preloaded_state): ₹{}", price);
-                        return Ok(price);
-                    }
-                }
+        let html = self.fetch_html(url).await?;
+        self.extract_price(url, &html)
+    }
+    
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        tracing::info!("Scraping Myntra URL: {}", url);
+        
+        let html = self.fetch_html(url).await?;
+        let (title, image_url) = product_details(&html);
+        Ok(ProductSnapshot::new(self.extract_price(url, &html)?, extract_mrp(&html), Vec::new()).with_details(title, image_url))
+    }
+    
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        let html = self.fetch_html(url).await?;
+        
+        // Sizes carry their own stock flags; the product is available if any size is
+        let re_preloaded = Regex::new(PRELOADED_STATE)?;
+        if let Some(captures) = re_preloaded.captures(&html)
+            && let Ok(data) = serde_json::from_str::<Value>(&captures[1])
+        {
+            if let Some(sizes) = data["pdpData"]["sizes"].as_array() {
+                return Ok(sizes.iter().any(|size| size["available"].as_bool() == Some(true)));
+            }
+            if let Some(out_of_stock) = data["pdpData"]["flags"]["outOfStock"].as_bool() {
+                return Ok(!out_of_stock);
             }
         }
         
-        // Fallback: Look for pdpData in script tags
-        let re = Regex::new(r#"pdpData["\s:]+(\{.*?\})\s*[,;]"#)?;
-        if let Some(captures) = re.captures(&html) {
-            if let Some(json_str) = captures.get(1) {
-                let data: Value = serde_json::from_str(json_str.as_str())?;
-                
-                if let Some(price) = data["price"]["discounted"].as_f64() {
-                    tracing::info!("Found Myntra price (pdpData): ₹{}", price);
-                    return Ok(price);
-                }
-                
-                if let Some(price) = data["mrp"].as_f64() {
-                    tracing::info!("Found Myntra MRP (pdpData): ₹{}", price);
-                    return Ok(price);
-                }
-            }
+        if let Some(in_stock) = availability_from_html(&html, &["OUT OF STOCK"]) {
+            return Ok(in_stock);
         }
         
-        Err(anyhow!("Could not find price in Myntra HTML. Site structure may have changed."))
+        // No stock signal: a listed price means it can be bought
+        Ok(self.extract_price(url, &html).is_ok())
     }
     
     fn platform_name(&self) -> &'static str {
@@ -80,6 +149,68 @@ impl PriceScraper for MyntraScraper {
     fn can_handle(&self, url: &str) -> bool {
         url.contains("myntra.com")
     }
+    
+    fn product_url(&self, product_id: &str) -> Option<String> {
+        // Style IDs are numeric; Myntra redirects /<style id> to the full product path
+        let id = product_id.trim();
+        (!id.is_empty() && id.len() <= 12 && id.chars().all(|c| c.is_ascii_digit()))
+            .then(|| format!("https://www.myntra.com/{}", id))
+    }
+    
+    fn product_id(&self, url: &str) -> Option<String> {
+        static STYLE_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"myntra\.com/(?:.*/)?(\d{5,12})(?:/buy)?/?(?:[?#]|$)").unwrap());
+        STYLE_ID.captures(url).map(|c| c[1].to_string())
+    }
+    
+    fn is_listing_url(&self, url: &str) -> bool {
+        // Search and category pages are any non-product path, e.g. /formal-shirts?f=...
+        self.can_handle(url)
+            && self.product_id(url).is_none()
+            && reqwest::Url::parse(url).is_ok_and(|u| u.path().len() > 1)
+    }
+    
+    fn search_url(&self, query: &str) -> Option<String> {
+        let slug = query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        if slug.is_empty() {
+            return None;
+        }
+        
+        let mut url = reqwest::Url::parse(&format!("https://www.myntra.com/{}", slug)).ok()?;
+        url.query_pairs_mut().append_pair("rawQuery", query.trim());
+        Some(url.to_string())
+    }
+    
+    async fn get_listings(&self, url: &str) -> Result<Vec<ListingItem>> {
+        tracing::info!("Scraping Myntra listing: {}", url);
+        let html = self.fetch_html(url).await?;
+        
+        let re_preloaded = Regex::new(PRELOADED_STATE)?;
+        let data: Value = re_preloaded
+            .captures(&html)
+            .and_then(|c| serde_json::from_str(&c[1]).ok())
+            .ok_or_else(|| anyhow!("Could not find search results in Myntra HTML. Site structure may have changed."))?;
+        
+        let base = reqwest::Url::parse(url)?;
+        let products = data["searchData"]["results"]["products"].as_array().cloned().unwrap_or_default();
+        
+        Ok(products
+            .iter()
+            .filter_map(|product| {
+                let path = product["landingPageUrl"].as_str()?;
+                Some(ListingItem {
+                    title: product["productName"].as_str()?.to_string(),
+                    url: base.join(&format!("/{}", path.trim_start_matches('/'))).ok()?.to_string(),
+                    price: json_price(&product["price"])?,
+                    location: None,
+                })
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -97,6 +228,56 @@ mod tests {
         assert!(!scraper.can_handle("https://www.ajio.com/product"));
     }
 
+    #[test]
+    fn test_myntra_product_ids() {
+        let scraper = MyntraScraper::new();
+        
+        assert_eq!(scraper.product_url("12345678").as_deref(), Some("https://www.myntra.com/12345678"));
+        assert_eq!(scraper.product_url("nike-shirt"), None);
+        assert_eq!(
+            scraper.product_id("https://www.myntra.com/shirts/nike/nike-men-blue-shirt/12345678/buy?src=app").as_deref(),
+            Some("12345678")
+        );
+        assert_eq!(scraper.product_id("https://www.myntra.com/12345678").as_deref(), Some("12345678"));
+        assert_eq!(
+            scraper.product_id("https://www.myntra.com/luxe/handbags/coach/coach-women-tote/23456789/buy").as_deref(),
+            Some("23456789")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_myntra_luxe_price_extraction() {
+        let mut server = Server::new_async().await;
+        
+        let mock_html = r#"
+            <!DOCTYPE html>
+            <html>
+            <head>
+                <script type="application/ld+json">
+                    {"@context": "https://schema.org", "@type": "Product", "name": "Coach Women Tote",
+                     "offers": {"@type": "Offer", "price": "27500", "priceCurrency": "INR"}}
+                </script>
+            </head>
+            <body></body>
+            </html>
+        "#;
+        
+        let _m = server.mock("GET", "/luxe/handbags/coach/coach-women-tote/23456789/buy")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(mock_html)
+            .create_async()
+            .await;
+        
+        let scraper = MyntraScraper::new();
+        let url = format!("{}/luxe/handbags/coach/coach-women-tote/23456789/buy", server.url());
+        assert!(is_luxe(&url));
+        assert!(!is_luxe("https://www.myntra.com/shirts/nike/12345678/buy"));
+        
+        let price = scraper.get_price(&url).await.unwrap();
+        assert_eq!(price, 27500.0);
+    }
+
     #[tokio::test]
     async fn test_myntra_platform_name() {
         let scraper = MyntraScraper::new();
@@ -135,9 +316,20 @@ mod tests {
         
         let scraper = MyntraScraper::new();
         let url = format!("{}/product/12345", server.url());
-        let price = scraper.get_price(&url).await.unwrap();
+        let product = scraper.get_product(&url).await.unwrap();
+        
+        assert_eq!(product.selling_price, 1299.0);
+        assert_eq!(product.mrp, Some(1999.0));
+        assert_eq!(product.discount_percent, Some(35.0));
+    }
+
+    #[test]
+    fn test_myntra_mrp_is_not_a_selling_price() {
+        let html = r#"<script>window.__myntra_preloaded_state__ = {"pdpData": {"price": {"mrp": 1999}}};</script>"#;
+        let scraper = MyntraScraper::new();
         
-        assert_eq!(price, 1299.0);
+        assert!(scraper.extract_price("https://www.myntra.com/12345678", html).is_err());
+        assert_eq!(extract_mrp(html), Some(1999.0));
     }
 
     #[tokio::test]
@@ -193,4 +385,62 @@ mod tests {
         
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_myntra_listings() {
+        let mut server = Server::new_async().await;
+        
+        let mock_html = r#"
+            <script>
+                window.__myntra_preloaded_state__ = {
+                    "searchData": {
+                        "results": {
+                            "products": [
+                                {"productName": "Men Black Formal Shirt", "landingPageUrl": "shirts/roadster/men-black-formal-shirt/12345678/buy", "price": 1299, "mrp": 2199},
+                                {"productName": "Men Black Slim Fit Shirt", "landingPageUrl": "shirts/hrx/men-black-slim-fit-shirt/23456789/buy", "price": 999}
+                            ]
+                        }
+                    }
+                };
+            </script>
+        "#;
+        
+        let _m = server.mock("GET", "/men/formal-shirts")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(mock_html)
+            .create_async()
+            .await;
+        
+        let scraper = MyntraScraper::new();
+        let items = scraper.get_listings(&format!("{}/men/formal-shirts?f=Color%3ABlack", server.url())).await.unwrap();
+        
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Men Black Formal Shirt");
+        assert_eq!(items[0].url, format!("{}/shirts/roadster/men-black-formal-shirt/12345678/buy", server.url()));
+        assert_eq!(items[1].price, 999.0);
+        
+        assert!(scraper.is_listing_url("https://www.myntra.com/men/formal-shirts?f=Color%3ABlack"));
+        assert!(!scraper.is_listing_url("https://www.myntra.com/shirts/roadster/men-black-formal-shirt/12345678/buy"));
+        
+        let search = scraper.search_url("Nike Pegasus 40").unwrap();
+        assert_eq!(search, "https://www.myntra.com/nike-pegasus-40?rawQuery=Nike+Pegasus+40");
+        assert!(scraper.is_listing_url(&search));
+    }
+
+    #[tokio::test]
+    async fn test_myntra_availability_from_sizes() {
+        let mut server = Server::new_async().await;
+        
+        let sold_out = r#"<script>window.__myntra_preloaded_state__ = {"pdpData": {"price": {"discounted": 1299}, "sizes": [{"label": "M", "available": false}, {"label": "L", "available": false}]}};</script>"#;
+        let restocked = r#"<script>window.__myntra_preloaded_state__ = {"pdpData": {"price": {"discounted": 1299}, "sizes": [{"label": "M", "available": false}, {"label": "L", "available": true}]}};</script>"#;
+        
+        let _sold_out = server.mock("GET", "/shirts/1").with_body(sold_out).create_async().await;
+        let _restocked = server.mock("GET", "/shirts/2").with_body(restocked).create_async().await;
+        
+        let scraper = MyntraScraper::new();
+        assert!(!scraper.get_availability(&format!("{}/shirts/1", server.url())).await.unwrap());
+        assert!(scraper.get_availability(&format!("{}/shirts/2", server.url())).await.unwrap());
+    }
 }
@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use scraper::{ElementRef, Html, Selector};
+use crate::models::{ListingItem, ScraperConfig};
+use crate::price_parse::parse_price;
+use crate::scraper_trait::PriceScraper;
+use crate::scrapers::{json_ld_item_list, check_response};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{CssSelector, Extractor, JsonLd};
+
+/// OLX India classifieds. Listing alerts on a search page track the asking
+/// prices of used items; a single ad page can be tracked like a product.
+pub struct OlxScraper {
+    client: Client,
+    config: Option<ScraperConfig>,
+}
+
+impl OlxScraper {
+    pub fn new() -> Self {
+        OlxScraper { client: crate::http_client::shared(), config: None }
+    }
+
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
+    }
+
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+            .header("Accept-Language", "en-IN,en;q=0.9")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
+
+        read_html(check_response(request.send().await?)?).await
+    }
+
+    /// Asking price on an ad page
+    fn extract_price(&self, html: &str) -> Result<f64> {
+        extractor(self.config.as_ref()).extract(html)
+    }
+}
+
+/// Where the asking price is looked for, after any configured selectors
+fn extractor(config: Option<&ScraperConfig>) -> Extractor {
+    Extractor::new("OLX", vec![
+        Box::new(JsonLd),
+        Box::new(CssSelector::new(r#"[data-aut-id="itemPrice"]"#)),
+    ])
+    .with_config(config)
+}
+
+/// Ad cards on a results page: `li[data-aut-id="itemBox"]` with title, price and location spans
+fn extract_listings(html: &str, base: &reqwest::Url) -> Vec<ListingItem> {
+    let document = Html::parse_document(html);
+    let selector = |css: &str| Selector::parse(css).expect("valid selector");
+    let (card, link, title, price, location) = (
+        selector(r#"li[data-aut-id="itemBox"]"#),
+        selector("a[href]"),
+        selector(r#"[data-aut-id="itemTitle"]"#),
+        selector(r#"[data-aut-id="itemPrice"]"#),
+        selector(r#"[data-aut-id="item-location"]"#),
+    );
+    let text = |card: &ElementRef, selector: &Selector| {
+        card.select(selector).next().map(|e| e.text().collect::<String>().trim().to_string())
+    };
+
+    let items: Vec<ListingItem> = document
+        .select(&card)
+        .filter_map(|card| {
+            Some(ListingItem {
+                title: text(&card, &title)?,
+                url: base.join(card.select(&link).next()?.value().attr("href")?).ok()?.to_string(),
+                price: parse_price(&text(&card, &price)?)?,
+                location: text(&card, &location).filter(|l| !l.is_empty()),
+            })
+        })
+        .collect();
+
+    if items.is_empty() { json_ld_item_list(html, base) } else { items }
+}
+
+/// "q-jordan-1" or "shoes_c1723"
+fn is_search_or_category(segment: &str) -> bool {
+    segment.starts_with("q-")
+        || segment
+            .rsplit_once("_c")
+            .is_some_and(|(_, id)| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+}
+
+impl Default for OlxScraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PriceScraper for OlxScraper {
+    async fn get_price(&self, url: &str) -> Result<f64> {
+        tracing::info!("Scraping OLX ad: {}", url);
+
+        let html = self.fetch_html(url).await?;
+        self.extract_price(&html)
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "olx"
+    }
+
+    fn can_handle(&self, url: &str) -> bool {
+        url.contains("olx.in")
+    }
+
+    fn product_id(&self, url: &str) -> Option<String> {
+        // Ad pages end in "-iid-1712345678"
+        let (_, id) = url.split('?').next()?.rsplit_once("-iid-")?;
+        let id = id.trim_end_matches('/');
+        id.chars().all(|c| c.is_ascii_digit()).then(|| id.to_string())
+    }
+
+    fn is_listing_url(&self, url: &str) -> bool {
+        // Searches are /items/q-<query> (optionally under a city, /mumbai_g4058997/q-...),
+        // categories /<slug>_c<id>
+        self.can_handle(url)
+            && self.product_id(url).is_none()
+            && reqwest::Url::parse(url).is_ok_and(|u| {
+                u.path().starts_with("/items")
+                    || u.path_segments().is_some_and(|mut segments| segments.any(is_search_or_category))
+            })
+    }
+
+    fn search_url(&self, query: &str) -> Option<String> {
+        let slug = query
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase();
+        (!slug.is_empty()).then(|| format!("https://www.olx.in/items/q-{}", slug))
+    }
+
+    async fn get_listings(&self, url: &str) -> Result<Vec<ListingItem>> {
+        tracing::info!("Scraping OLX listing: {}", url);
+        let html = self.fetch_html(url).await?;
+
+        let items = extract_listings(&html, &reqwest::Url::parse(url)?);
+        if items.is_empty() && !html.contains("itemBox") {
+            return Err(anyhow!("Could not find ads in OLX HTML. Site structure may have changed."));
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_olx_listings() {
+        let mut server = Server::new_async().await;
+        let mock_html = r#"<ul>
+            <li data-aut-id="itemBox"><a href="/item/nike-jordan-1-size-9-iid-1712345678">
+                <span data-aut-id="itemPrice">₹ 6,500</span>
+                <span data-aut-id="itemTitle">Nike Jordan 1 size 9</span>
+                <span data-aut-id="item-location">Andheri West, Mumbai</span>
+            </a></li>
+            <li data-aut-id="itemBox"><a href="/item/jordan-1-low-iid-1712349999">
+                <span data-aut-id="itemPrice">₹ 4,200</span>
+                <span data-aut-id="itemTitle">Jordan 1 Low, worn twice</span>
+            </a></li>
+            <li data-aut-id="itemBox"><span data-aut-id="itemTitle">Sponsored</span></li>
+        </ul>"#;
+        let _m = server.mock("GET", "/items/q-jordan-1")
+            .with_status(200)
+            .with_body(mock_html)
+            .create_async()
+            .await;
+
+        let scraper = OlxScraper::new();
+        let items = scraper.get_listings(&format!("{}/items/q-jordan-1", server.url())).await.unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].url, format!("{}/item/nike-jordan-1-size-9-iid-1712345678", server.url()));
+        assert_eq!(items[0].price, 6500.0);
+        assert_eq!(items[0].location.as_deref(), Some("Andheri West, Mumbai"));
+        assert_eq!(items[1].location, None);
+
+        assert!(scraper.is_listing_url("https://www.olx.in/items/q-jordan-1"));
+        assert!(scraper.is_listing_url("https://www.olx.in/mumbai_g4058997/q-jordan-1"));
+        assert!(scraper.is_listing_url("https://www.olx.in/shoes_c1723"));
+        assert!(!scraper.is_listing_url("https://www.olx.in/item/nike-jordan-1-size-9-iid-1712345678"));
+        assert_eq!(scraper.product_id("https://www.olx.in/item/nike-jordan-1-size-9-iid-1712345678").as_deref(), Some("1712345678"));
+        assert_eq!(scraper.search_url("Jordan 1 (size 9)").as_deref(), Some("https://www.olx.in/items/q-jordan-1-size-9"));
+    }
+}
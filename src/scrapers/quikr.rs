@@ -0,0 +1,193 @@
+use async_trait::async_trait;
+use anyhow::{Result, anyhow};
+use reqwest::Client;
+use scraper::{ElementRef, Html, Selector};
+use crate::models::{ListingItem, ScraperConfig};
+use crate::price_parse::parse_price;
+use crate::scraper_trait::PriceScraper;
+use crate::scrapers::{json_ld_item_list, check_response};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{CssSelector, Extractor, JsonLd};
+
+/// Quikr classifieds. Results pages carry a JSON-LD `ItemList`; the ad cards
+/// are read when it's missing.
+pub struct QuikrScraper {
+    client: Client,
+    config: Option<ScraperConfig>,
+}
+
+impl QuikrScraper {
+    pub fn new() -> Self {
+        QuikrScraper { client: crate::http_client::shared(), config: None }
+    }
+
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
+    }
+
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")
+            .header("Accept-Language", "en-IN,en;q=0.9")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
+
+        read_html(check_response(request.send().await?)?).await
+    }
+
+    /// Asking price on an ad page
+    fn extract_price(&self, html: &str) -> Result<f64> {
+        extractor(self.config.as_ref()).extract(html)
+    }
+}
+
+/// Where the asking price is looked for, after any configured selectors
+fn extractor(config: Option<&ScraperConfig>) -> Extractor {
+    Extractor::new("Quikr", vec![
+        Box::new(JsonLd),
+        Box::new(CssSelector::new(".ad-price")),
+        Box::new(CssSelector::new(".price")),
+        Box::new(CssSelector::new("[itemprop='price']")),
+    ])
+    .with_config(config)
+}
+
+/// Ad cards, for results pages without structured data
+fn extract_cards(html: &str, base: &reqwest::Url) -> Vec<ListingItem> {
+    let document = Html::parse_document(html);
+    let selector = |css: &str| Selector::parse(css).expect("valid selector");
+    let (card, link, title, price, location) = (
+        selector(".ad-card, .snb-tile, [data-testid='ad-card']"),
+        selector("a[href]"),
+        selector(".ad-title, .title, h3"),
+        selector(".ad-price, .price"),
+        selector(".ad-location, .location"),
+    );
+    let text = |card: &ElementRef, selector: &Selector| {
+        card.select(selector).next().map(|e| e.text().collect::<String>().trim().to_string())
+    };
+
+    document
+        .select(&card)
+        .filter_map(|card| {
+            Some(ListingItem {
+                title: text(&card, &title)?,
+                url: base.join(card.select(&link).next()?.value().attr("href")?).ok()?.to_string(),
+                price: parse_price(&text(&card, &price)?)?,
+                location: text(&card, &location).filter(|l| !l.is_empty()),
+            })
+        })
+        .collect()
+}
+
+impl Default for QuikrScraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PriceScraper for QuikrScraper {
+    async fn get_price(&self, url: &str) -> Result<f64> {
+        tracing::info!("Scraping Quikr ad: {}", url);
+
+        let html = self.fetch_html(url).await?;
+        self.extract_price(&html)
+    }
+
+    fn platform_name(&self) -> &'static str {
+        "quikr"
+    }
+
+    fn can_handle(&self, url: &str) -> bool {
+        url.contains("quikr.com")
+    }
+
+    fn product_id(&self, url: &str) -> Option<String> {
+        // Ad pages end in "W0QQAdIdZ380123456"
+        let (_, id) = url.split('?').next()?.rsplit_once("W0QQAdIdZ")?;
+        let id = id.trim_end_matches('/');
+        id.chars().all(|c| c.is_ascii_digit()).then(|| id.to_string())
+    }
+
+    fn is_listing_url(&self, url: &str) -> bool {
+        // Anything that isn't an ad: /<city>/<category>, /<city>/<query>/q-...
+        self.can_handle(url)
+            && self.product_id(url).is_none()
+            && reqwest::Url::parse(url).is_ok_and(|u| u.path().len() > 1)
+    }
+
+    async fn get_listings(&self, url: &str) -> Result<Vec<ListingItem>> {
+        tracing::info!("Scraping Quikr listing: {}", url);
+        let html = self.fetch_html(url).await?;
+        let base = reqwest::Url::parse(url)?;
+
+        let items = json_ld_item_list(&html, &base);
+        if !items.is_empty() {
+            return Ok(items);
+        }
+
+        let items = extract_cards(&html, &base);
+        if items.is_empty() {
+            return Err(anyhow!("Could not find ads in Quikr HTML. Site structure may have changed."));
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_quikr_listings() {
+        let mut server = Server::new_async().await;
+        let _json_ld = server.mock("GET", "/mumbai/shoes/z1")
+            .with_status(200)
+            .with_body(r#"<script type="application/ld+json">{"@type": "ItemList", "itemListElement": [
+                {"@type": "Product", "name": "Adidas Ultraboost UK 9", "url": "/mumbai/ultraboost/W0QQAdIdZ380123456", "offers": {"price": 3500}}
+            ]}</script>"#)
+            .create_async()
+            .await;
+        let _cards = server.mock("GET", "/pune/shoes/z1")
+            .with_status(200)
+            .with_body(r#"<div class="ad-card"><a href="/pune/jordan/W0QQAdIdZ380999999"><h3>Jordan 4 Retro</h3>
+                <span class="ad-price">Rs 9,000</span><span class="ad-location">Kothrud</span></a></div>"#)
+            .create_async()
+            .await;
+        let _changed = server.mock("GET", "/delhi/shoes/z1")
+            .with_status(200)
+            .with_body("<div>redesigned</div>")
+            .create_async()
+            .await;
+
+        let scraper = QuikrScraper::new();
+        let items = scraper.get_listings(&format!("{}/mumbai/shoes/z1", server.url())).await.unwrap();
+        assert_eq!(items[0].price, 3500.0);
+        assert_eq!(scraper.product_id(&items[0].url).as_deref(), Some("380123456"));
+
+        let items = scraper.get_listings(&format!("{}/pune/shoes/z1", server.url())).await.unwrap();
+        assert_eq!((items[0].title.as_str(), items[0].price), ("Jordan 4 Retro", 9000.0));
+        assert_eq!(items[0].location.as_deref(), Some("Kothrud"));
+
+        assert!(scraper.get_listings(&format!("{}/delhi/shoes/z1", server.url())).await.is_err());
+
+        assert!(scraper.is_listing_url("https://www.quikr.com/mumbai/shoes/z1"));
+        assert!(!scraper.is_listing_url("https://www.quikr.com/mumbai/ultraboost/W0QQAdIdZ380123456"));
+    }
+}
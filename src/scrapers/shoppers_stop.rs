@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use reqwest::Client;
+use regex::Regex;
+use scraper::{Html, Selector};
+use std::sync::LazyLock;
+use crate::models::ScraperConfig;
+use crate::price_parse::parse_price;
+use crate::scraper_trait::{PriceScraper, ProductSnapshot};
+use crate::scrapers::{availability_from_html, check_response, product_details};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{CssSelector, Extractor, JsonLd, MetaTag};
+
+pub struct ShoppersStopScraper {
+    client: Client,
+    config: Option<ScraperConfig>,
+}
+
+impl ShoppersStopScraper {
+    pub fn new() -> Self {
+        ShoppersStopScraper { client: crate::http_client::shared(), config: None }
+    }
+    
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
+    }
+    
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+            .header("Accept-Language", "en-IN,en;q=0.9")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
+        
+        read_html(check_response(request.send().await?)?).await
+    }
+    
+    /// Price from an already-fetched product page
+    fn extract_price(&self, html: &str) -> Result<f64> {
+        extractor(self.config.as_ref()).extract(html)
+    }
+}
+
+/// Where the selling price is looked for, after any configured selectors
+fn extractor(config: Option<&ScraperConfig>) -> Extractor {
+    // Product pages carry schema.org JSON-LD; the markup classes change with every sale theme
+    Extractor::new("Shoppers Stop", vec![
+        Box::new(JsonLd),
+        Box::new(CssSelector::new(".pdp-price .selling-price")),
+        Box::new(CssSelector::new(".product-price .final-price")),
+        Box::new(CssSelector::new("[data-testid='selling-price']")),
+        Box::new(CssSelector::new(".price_container .offer-price")),
+        Box::new(MetaTag("product:price:amount")),
+    ])
+    .with_config(config)
+}
+
+/// The struck-through MRP next to the selling price, if the product is discounted
+fn extract_mrp(html: &str) -> Option<f64> {
+    let document = Html::parse_document(html);
+    [".pdp-price .mrp-price", ".product-price .strike-price", "[data-testid='mrp-price']"]
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| parse_price(&document.select(&selector).next()?.text().collect::<String>()))
+}
+
+impl Default for ShoppersStopScraper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PriceScraper for ShoppersStopScraper {
+    async fn get_price(&self, url: &str) -> Result<f64> {
+        tracing::info!("Scraping Shoppers Stop URL: {}", url);
+        
+        let html = self.fetch_html(url).await?;
+        self.extract_price(&html)
+    }
+    
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        tracing::info!("Scraping Shoppers Stop URL: {}", url);
+        
+        let html = self.fetch_html(url).await?;
+        let (title, image_url) = product_details(&html);
+        Ok(ProductSnapshot::new(self.extract_price(&html)?, extract_mrp(&html), Vec::new()).with_details(title, image_url))
+    }
+    
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        let html = self.fetch_html(url).await?;
+        
+        if let Some(in_stock) = availability_from_html(&html, &["Out of Stock", "OUT OF STOCK", "Sold Out"]) {
+            return Ok(in_stock);
+        }
+        
+        // No stock signal: a listed price means it can be bought
+        Ok(self.extract_price(&html).is_ok())
+    }
+    
+    fn platform_name(&self) -> &'static str {
+        "shoppers_stop"
+    }
+    
+    fn can_handle(&self, url: &str) -> bool {
+        url.contains("shoppersstop.com")
+    }
+    
+    fn product_url(&self, product_id: &str) -> Option<String> {
+        // Product codes look like "206418475" or "A23VMDRS123_BLACK"; the slug is cosmetic
+        static PRODUCT_CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[0-9A-Z]{6,}(_[0-9A-Z]+)?$").unwrap());
+        let id = product_id.trim().to_uppercase();
+        PRODUCT_CODE.is_match(&id).then(|| format!("https://www.shoppersstop.com/p-{}", id))
+    }
+    
+    fn product_id(&self, url: &str) -> Option<String> {
+        static CODE_IN_URL: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)shoppersstop\.com/(?:.*/)?p-([0-9a-z]+(?:_[0-9a-z]+)?)").unwrap()
+        });
+        CODE_IN_URL.captures(url).map(|c| c[1].to_uppercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[test]
+    fn test_shoppers_stop_urls() {
+        let scraper = ShoppersStopScraper::new();
+        
+        assert!(scraper.can_handle("https://www.shoppersstop.com/levis-mens-slim-fit-jeans/p-206418475"));
+        assert!(!scraper.can_handle("https://www.lifestylestores.com/in/en/p/1000012345678"));
+        assert_eq!(
+            scraper.product_id("https://www.shoppersstop.com/vero-moda-womens-a-line-dress/p-a23vmdrs123_black/colorChange").as_deref(),
+            Some("A23VMDRS123_BLACK")
+        );
+        assert_eq!(
+            scraper.product_url("206418475").as_deref(),
+            Some("https://www.shoppersstop.com/p-206418475")
+        );
+        assert_eq!(scraper.product_url("abc"), None);
+        // "p-" inside the slug isn't the product code
+        assert_eq!(scraper.product_id("https://www.shoppersstop.com/top-wear/c-women"), None);
+    }
+
+    #[tokio::test]
+    async fn test_shoppers_stop_price_extraction() {
+        let mut server = Server::new_async().await;
+        
+        let json_ld = r#"
+            <script type="application/ld+json">
+                {"@type": "Product", "name": "Levis Slim Fit Jeans", "offers": {"price": "1,799", "availability": "https://schema.org/InStock"}}
+            </script>
+            <div class="pdp-price"><span class="selling-price">₹1,799</span><span class="mrp-price">₹2,999</span></div>
+        "#;
+        let _json_ld = server.mock("GET", "/levis-jeans/p-206418475")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(json_ld)
+            .create_async()
+            .await;
+        
+        // Clearance pages without structured data
+        let markup = r#"<div class="product-price"><span class="final-price">₹899</span></div><p>Sold Out</p>"#;
+        let _markup = server.mock("GET", "/clearance-tee/p-206400001")
+            .with_status(200)
+            .with_header("content-type", "text/html")
+            .with_body(markup)
+            .create_async()
+            .await;
+        
+        let scraper = ShoppersStopScraper::new();
+        let product = scraper.get_product(&format!("{}/levis-jeans/p-206418475", server.url())).await.unwrap();
+        assert_eq!(product.selling_price, 1799.0);
+        assert_eq!(product.mrp, Some(2999.0));
+        assert!(scraper.get_availability(&format!("{}/levis-jeans/p-206418475", server.url())).await.unwrap());
+        
+        let url = format!("{}/clearance-tee/p-206400001", server.url());
+        assert_eq!(scraper.get_price(&url).await.unwrap(), 899.0);
+        assert!(!scraper.get_availability(&url).await.unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_shoppers_stop_price_not_found() {
+        let mut server = Server::new_async().await;
+        let _m = server.mock("GET", "/product/invalid")
+            .with_status(200)
+            .with_body("<p>No price here</p>")
+            .create_async()
+            .await;
+        
+        let scraper = ShoppersStopScraper::new();
+        assert!(scraper.get_price(&format!("{}/product/invalid", server.url())).await.is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde_json::Value;
+use std::cell::OnceCell;
+use crate::explain;
+use crate::models::ScraperConfig;
+use crate::price_parse::{json_price, parse_price};
+use crate::scrapers::json_ld_price;
+
+/// A fetched product page, parsed at most once however many strategies look at it
+pub struct Page<'a> {
+    pub html: &'a str,
+    document: OnceCell<Html>,
+}
+
+impl<'a> Page<'a> {
+    pub fn new(html: &'a str) -> Self {
+        Page { html, document: OnceCell::new() }
+    }
+
+    pub fn document(&self) -> &Html {
+        self.document.get_or_init(|| Html::parse_document(self.html))
+    }
+}
+
+/// One way of reading the selling price off a product page. Scrapers list theirs in
+/// the order to try them; the same implementations serve every platform.
+pub trait ExtractionStrategy: Send + Sync {
+    /// Shown in logs and by `scrape --explain`
+    fn name(&self) -> String;
+
+    /// The price, or what was seen instead (regex didn't match, path missing...)
+    fn extract(&self, page: &Page) -> Result<f64, String>;
+}
+
+/// JSON a storefront embeds for its client app (`window.__INITIAL_STATE__ = {...};`),
+/// captured by `pattern` and read at a JSON `pointer`
+pub struct PreloadedState {
+    label: &'static str,
+    pattern: Regex,
+    pointer: &'static str,
+}
+
+impl PreloadedState {
+    /// `pattern`'s first capture group must be the JSON object
+    pub fn new(label: &'static str, pattern: &str, pointer: &'static str) -> Self {
+        PreloadedState { label, pattern: Regex::new(pattern).expect("invalid preloaded state pattern"), pointer }
+    }
+}
+
+impl ExtractionStrategy for PreloadedState {
+    fn name(&self) -> String {
+        format!("{} {}", self.label, self.pointer.trim_start_matches('/').replace('/', "."))
+    }
+
+    fn extract(&self, page: &Page) -> Result<f64, String> {
+        let captures = self.pattern.captures(page.html).ok_or("regex didn't match")?;
+        let json = captures.get(1).map_or("", |m| m.as_str());
+        let data: Value = serde_json::from_str(json).map_err(|e| format!("script found, invalid JSON: {}", e))?;
+        match data.pointer(self.pointer) {
+            Some(value) => json_price(value).ok_or_else(|| format!("path found, but no price in {}", value)),
+            None => Err("script found, path missing".to_string()),
+        }
+    }
+}
+
+/// `offers.price` of a schema.org `Product` in the page's JSON-LD blocks
+pub struct JsonLd;
+
+impl ExtractionStrategy for JsonLd {
+    fn name(&self) -> String {
+        "JSON-LD".to_string()
+    }
+
+    fn extract(&self, page: &Page) -> Result<f64, String> {
+        json_ld_price(page.html).ok_or_else(|| "no Product offer price".to_string())
+    }
+}
+
+/// Text of the first element matching a CSS selector that holds a price
+pub struct CssSelector(pub String);
+
+impl CssSelector {
+    pub fn new(selector: &str) -> Self {
+        CssSelector(selector.to_string())
+    }
+}
+
+impl ExtractionStrategy for CssSelector {
+    fn name(&self) -> String {
+        self.0.clone()
+    }
+
+    fn extract(&self, page: &Page) -> Result<f64, String> {
+        let selector = Selector::parse(&self.0).map_err(|_| "invalid selector".to_string())?;
+        let texts: Vec<String> = page.document().select(&selector).map(|element| element.text().collect()).collect();
+        if let Some(price) = texts.iter().find_map(|text| parse_price(text)) {
+            return Ok(price);
+        }
+        match texts.first() {
+            Some(text) => Err(format!("element found, but no price in {:?}", text.trim())),
+            None => Err("no matching element".to_string()),
+        }
+    }
+}
+
+/// `content` of a `<meta>` price tag, such as Open Graph's `product:price:amount`,
+/// matched by `property`, `name` or `itemprop`
+pub struct MetaTag(pub &'static str);
+
+impl ExtractionStrategy for MetaTag {
+    fn name(&self) -> String {
+        format!("meta {}", self.0)
+    }
+
+    fn extract(&self, page: &Page) -> Result<f64, String> {
+        let css = format!(r#"meta[property="{0}"], meta[name="{0}"], meta[itemprop="{0}"]"#, self.0);
+        let selector = Selector::parse(&css).map_err(|_| "invalid selector".to_string())?;
+        let content = page
+            .document()
+            .select(&selector)
+            .find_map(|meta| meta.value().attr("content"))
+            .ok_or("no tag with content")?;
+        parse_price(content).ok_or_else(|| format!("tag found, but no price in {:?}", content))
+    }
+}
+
+/// A regex from a `ScraperConfig` whose first capture group holds the price text
+pub struct Pattern(pub String);
+
+impl ExtractionStrategy for Pattern {
+    fn name(&self) -> String {
+        format!("pattern {}", self.0)
+    }
+
+    fn extract(&self, page: &Page) -> Result<f64, String> {
+        let regex = Regex::new(&self.0).map_err(|_| "invalid pattern".to_string())?;
+        let captures = regex.captures(page.html).ok_or("no match")?;
+        let captured = captures.get(1).map_or("", |m| m.as_str());
+        parse_price(captured).ok_or_else(|| format!("matched, but no price in {:?}", captured))
+    }
+}
+
+/// A scraper's strategies, tried in order until one finds a price
+pub struct Extractor {
+    /// "Myntra", "Ajio Luxe"... for logs and the error when nothing matched
+    label: &'static str,
+    strategies: Vec<Box<dyn ExtractionStrategy>>,
+}
+
+impl Extractor {
+    pub fn new(label: &'static str, strategies: Vec<Box<dyn ExtractionStrategy>>) -> Self {
+        Extractor { label, strategies }
+    }
+
+    /// Try the platform's configured selectors, then its configured patterns, ahead of the built-in strategies
+    pub fn with_config(mut self, config: Option<&ScraperConfig>) -> Self {
+        if let Some(config) = config {
+            let configured = config
+                .selectors
+                .iter()
+                .map(|selector| Box::new(CssSelector::new(selector)) as Box<dyn ExtractionStrategy>)
+                .chain(config.price_patterns.iter().map(|pattern| Box::new(Pattern(pattern.clone())) as Box<dyn ExtractionStrategy>));
+            self.strategies.splice(0..0, configured);
+        }
+        self
+    }
+
+    /// Names of the strategies, in the order they're tried
+    pub fn names(&self) -> Vec<String> {
+        self.strategies.iter().map(|strategy| strategy.name()).collect()
+    }
+
+    /// Price from the first strategy that finds one, noting each attempt for `scrape --explain`
+    pub fn extract(&self, html: &str) -> Result<f64> {
+        let page = Page::new(html);
+        for strategy in &self.strategies {
+            let name = strategy.name();
+            match strategy.extract(&page) {
+                Ok(price) => {
+                    explain::note(&name, true, format!("₹{}", price));
+                    tracing::info!("Found {} price via {}: ₹{}", self.label, name, price);
+                    return Ok(price);
+                }
+                Err(miss) => explain::note(&name, false, miss),
+            }
+        }
+        Err(anyhow!("Could not find price in {} HTML. Site structure may have changed.", self.label))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_strategies() {
+        let html = r#"
+            <head><meta property="product:price:amount" content="1499.00"></head>
+            <div class="price">Price on request</div><div class="price">₹1,299</div>
+            <script>window.__STATE__ = {"product": {"price": {"value": 1199}}};</script>
+        "#;
+        let page = Page::new(html);
+        let state = PreloadedState::new("__STATE__", r#"window\.__STATE__\s*=\s*(\{.*?\});"#, "/product/price/value");
+
+        assert_eq!(state.name(), "__STATE__ product.price.value");
+        assert_eq!(state.extract(&page), Ok(1199.0));
+        let missing = PreloadedState::new("__STATE__", r#"window\.__STATE__\s*=\s*(\{.*?\});"#, "/product/mrp");
+        assert_eq!(missing.extract(&page), Err("script found, path missing".to_string()));
+
+        assert_eq!(CssSelector::new(".price").extract(&page), Ok(1299.0));
+        assert_eq!(CssSelector::new(".mrp").extract(&page), Err("no matching element".to_string()));
+        assert_eq!(MetaTag("product:price:amount").extract(&page), Ok(1499.0));
+        assert!(JsonLd.extract(&page).is_err());
+    }
+
+    #[test]
+    fn test_extractor_order() {
+        let html = r#"<div class="new-price">₹1,299</div><script>{"sellingPrice":899}</script>"#;
+        let config = ScraperConfig {
+            platform: "flipkart".to_string(),
+            selectors: vec![".missing".to_string()],
+            price_patterns: vec![r#""sellingPrice":(\d+)"#.to_string()],
+            headers: sqlx::types::Json(HashMap::new()),
+            timeout_secs: None,
+            connect_timeout_secs: None,
+            updated_at: Utc::now(),
+        };
+        let built_in = || Extractor::new("Flipkart", vec![Box::new(CssSelector::new(".new-price"))]);
+
+        let extractor = built_in().with_config(Some(&config));
+        assert_eq!(extractor.names(), vec![".missing", r#"pattern "sellingPrice":(\d+)"#, ".new-price"]);
+        assert_eq!(extractor.extract(html).unwrap(), 899.0);
+        assert_eq!(built_in().with_config(None).extract(html).unwrap(), 1299.0);
+
+        let error = Extractor::new("Flipkart", Vec::new()).extract(html).unwrap_err();
+        assert_eq!(error.to_string(), "Could not find price in Flipkart HTML. Site structure may have changed.");
+    }
+}
@@ -1,32 +1,84 @@
 use async_trait::async_trait;
-use anyhow::{Result, anyhow};
+use anyhow::Result;
 use reqwest::Client;
+use regex::Regex;
 use scraper::{Html, Selector};
-use crate::scraper_trait::PriceScraper;
+use std::sync::LazyLock;
+use crate::models::ScraperConfig;
+use crate::price_parse::parse_price;
+use crate::scraper_trait::{PriceScraper, ProductSnapshot};
+use crate::scrapers::{availability_from_html, check_response, product_details};
+use crate::scrapers::body::{read_html, ACCEPT_ENCODING};
+use crate::scrapers::strategy::{CssSelector, Extractor, MetaTag};
 
 pub struct TataCliqScraper {
     client: Client,
+    config: Option<ScraperConfig>,
 }
 
 impl TataCliqScraper {
     pub fn new() -> Self {
-        let client = Client::builder()
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36")
-            .build()
-            .expect("Failed to create HTTP client");
-        
-        TataCliqScraper { client }
+        TataCliqScraper { client: crate::http_client::shared(), config: None }
+    }
+    
+    /// Use `client` instead of the shared one
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    
+    /// Try DB-configured selectors/patterns/headers ahead of the built-in ones
+    pub fn with_config(mut self, config: Option<ScraperConfig>) -> Self {
+        if let Some(client) = config.as_ref().and_then(ScraperConfig::client) {
+            self.client = client;
+        }
+        self.config = config;
+        self
     }
     
-    fn parse_price(&self, price_str: &str) -> Result<f64> {
-        let cleaned = price_str
-            .replace('₹', "")
-            .replace(',', "")
-            .trim()
-            .to_string();
+    async fn fetch_html(&self, url: &str) -> Result<String> {
+        let mut request = self.client
+            .get(url)
+            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
+            .header("Accept-Language", "en-US,en;q=0.5")
+            .header("Accept-Encoding", ACCEPT_ENCODING);
+        if let Some(config) = &self.config {
+            request = config.apply(request);
+        }
         
-        cleaned.parse::<f64>()
-            .map_err(|e| anyhow!("Failed to parse price '{}': {}", price_str, e))
+        read_html(check_response(request.send().await?)?).await
+    }
+    
+    /// Price from an already-fetched product page
+    fn extract_price(&self, html: &str) -> Result<f64> {
+        extractor(self.config.as_ref()).extract(html)
+    }
+}
+
+/// Where the selling price is looked for, after any configured selectors
+fn extractor(config: Option<&ScraperConfig>) -> Extractor {
+    Extractor::new("Tata Cliq", vec![
+        Box::new(CssSelector::new("div.ProductDescription__price")),
+        Box::new(CssSelector::new(".product-price")),
+        Box::new(CssSelector::new("[data-test='product-price']")),
+        Box::new(CssSelector::new(".price-tag")),
+        Box::new(MetaTag("product:price:amount")),
+    ])
+    .with_config(config)
+}
+
+/// The struck-through MRP next to the selling price, if the product is discounted
+fn extract_mrp(html: &str) -> Option<f64> {
+    let document = Html::parse_document(html);
+    ["div.ProductDescription__priceCancelled", ".product-mrp", "[data-test='product-mrp']"]
+        .iter()
+        .filter_map(|selector| Selector::parse(selector).ok())
+        .find_map(|selector| parse_price(&document.select(&selector).next()?.text().collect::<String>()))
+}
+
+impl Default for TataCliqScraper {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -35,37 +87,27 @@ impl PriceScraper for TataCliqScraper {
     async fn get_price(&self, url: &str) -> Result<f64> {
         tracing::info!("Scraping Tata Cliq URL: {}", url);
         
-        let response = self.client
-            .get(url)
-            .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-            .header("Accept-Language", "en-US,en;q=0.5")
-            .send()
-            .await?;
-        
-        let html = response.text().await?;
-        let document = Html::parse_document(&html);
+        let html = self.fetch_html(url).await?;
+        self.extract_price(&html)
+    }
+    
+    async fn get_product(&self, url: &str) -> Result<ProductSnapshot> {
+        tracing::info!("Scraping Tata Cliq URL: {}", url);
         
-        // Try multiple selectors
-        let selectors = vec![
-            "div.ProductDescription__price",
-            ".product-price",
-            "[data-test='product-price']",
-            ".price-tag",
-        ];
+        let html = self.fetch_html(url).await?;
+        let (title, image_url) = product_details(&html);
+        Ok(ProductSnapshot::new(self.extract_price(&html)?, extract_mrp(&html), Vec::new()).with_details(title, image_url))
+    }
+    
+    async fn get_availability(&self, url: &str) -> Result<bool> {
+        let html = self.fetch_html(url).await?;
         
-        for selector_str in selectors {
-            if let Ok(selector) = Selector::parse(selector_str) {
-                if let Some(element) = document.select(&selector).next() {
-                    let price_text = element.text().collect::<String>();
-                    if let Ok(price) = self.parse_price(&price_text) {
-                        tracing::info!("Found Tata Cliq price: ₹{}", price);
-                        return Ok(price);
-                    }
-                }
-            }
+        if let Some(in_stock) = availability_from_html(&html, &["Out of Stock", "OUT OF STOCK"]) {
+            return Ok(in_stock);
         }
         
-        Err(anyhow!("Could not find price in Tata Cliq HTML. Site structure may have changed."))
+        // No stock signal: a listed price means it can be bought
+        Ok(self.extract_price(&html).is_ok())
     }
     
     fn platform_name(&self) -> &'static str {
@@ -75,4 +117,16 @@ impl PriceScraper for TataCliqScraper {
     fn can_handle(&self, url: &str) -> bool {
         url.contains("tatacliq.com")
     }
+    
+    fn product_url(&self, product_id: &str) -> Option<String> {
+        // Listing IDs look like "MP000000012345678"
+        static LISTING_ID: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^mp\d{9,}$").unwrap());
+        let id = product_id.trim().to_lowercase();
+        LISTING_ID.is_match(&id).then(|| format!("https://www.tatacliq.com/p-{}", id))
+    }
+    
+    fn product_id(&self, url: &str) -> Option<String> {
+        static ID_IN_URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)tatacliq\.com/.*?p-(mp\d+)").unwrap());
+        ID_IN_URL.captures(url).map(|c| c[1].to_uppercase())
+    }
 }
@@ -0,0 +1,80 @@
+use super::{validate_key, Storage};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// Files under a local directory (fine for a VM with a persistent disk)
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+    
+    /// STORAGE_LOCAL_DIR, defaulting to `./data`
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("STORAGE_LOCAL_DIR").unwrap_or_else(|_| "data".to_string()))
+    }
+    
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, body: Vec<u8>, _content_type: &str) -> Result<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        
+        tokio::fs::write(path, body).await?;
+        Ok(())
+    }
+    
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(body) => Ok(Some(body)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+    
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+    
+    fn backend_name(&self) -> &'static str {
+        "local"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_round_trip() {
+        let root = std::env::temp_dir().join(format!("price-tracker-storage-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+        
+        storage.put("exports/alerts.csv", b"url,price\n".to_vec(), "text/csv").await.unwrap();
+        assert_eq!(storage.get("exports/alerts.csv").await.unwrap(), Some(b"url,price\n".to_vec()));
+        
+        storage.delete("exports/alerts.csv").await.unwrap();
+        assert_eq!(storage.get("exports/alerts.csv").await.unwrap(), None);
+        // Deleting a missing key is not an error
+        storage.delete("exports/alerts.csv").await.unwrap();
+        
+        assert!(storage.put("../escape.txt", vec![], "text/plain").await.is_err());
+        
+        tokio::fs::remove_dir_all(root).await.ok();
+    }
+}
@@ -0,0 +1,61 @@
+pub mod local;
+pub mod s3;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Blob storage for chart images, account exports and backups.
+/// Keys are `/`-separated relative paths like `exports/<user_id>.json`.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn put(&self, key: &str, body: Vec<u8>, content_type: &str) -> Result<()>;
+    
+    /// `Ok(None)` when the key doesn't exist
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>>;
+    
+    async fn delete(&self, key: &str) -> Result<()>;
+    
+    fn backend_name(&self) -> &'static str;
+}
+
+/// Pick the backend from STORAGE_BACKEND (`local`, the default, or `s3`)
+pub fn from_env() -> Result<Arc<dyn Storage>> {
+    let backend = std::env::var("STORAGE_BACKEND").unwrap_or_else(|_| "local".to_string());
+    
+    match backend.as_str() {
+        "local" => Ok(Arc::new(local::LocalStorage::from_env())),
+        "s3" => Ok(Arc::new(s3::S3Storage::from_env()?)),
+        other => Err(anyhow!("Unknown STORAGE_BACKEND '{}', expected 'local' or 's3'", other)),
+    }
+}
+
+/// Reject keys that could escape the storage root
+pub(crate) fn validate_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && !key.starts_with('/')
+        && !key.contains('\\')
+        && key.split('/').all(|part| !part.is_empty() && part != "." && part != "..");
+    
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid storage key '{}'", key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("snapshots/abc/1700000000.html").is_ok());
+        assert!(validate_key("exports/alerts.csv").is_ok());
+        
+        assert!(validate_key("").is_err());
+        assert!(validate_key("/etc/passwd").is_err());
+        assert!(validate_key("snapshots/../../etc/passwd").is_err());
+        assert!(validate_key("snapshots//double").is_err());
+    }
+}
@@ -0,0 +1,88 @@
+use chrono::{DateTime, Datelike, Duration, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use crate::db::Database;
+use crate::email::{EmailQueue, EmailService};
+use crate::timezone::{format_local, user_zone};
+use crate::unsubscribe::{self, UnsubscribeScope};
+
+/// Summaries go out on this day, in each user's own timezone...
+const SEND_WEEKDAY: Weekday = Weekday::Mon;
+
+/// ...from this local hour on
+const SEND_HOUR: u32 = 9;
+
+/// How often the job looks for users whose summary is due
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60 * 60);
+
+/// Whether a user in `zone` whose last summary went out at `last_sent` gets one now.
+/// Anything sent in the last six days counts as this week's, so a late run doesn't
+/// send twice and a missed Monday isn't made up on Tuesday.
+pub fn is_due(now: DateTime<Utc>, zone: Tz, last_sent: Option<DateTime<Utc>>) -> bool {
+    let local = now.with_timezone(&zone);
+    local.weekday() == SEND_WEEKDAY
+        && local.hour() >= SEND_HOUR
+        && last_sent.is_none_or(|sent| now - sent >= Duration::days(6))
+}
+
+/// Queue this week's summary for every opted-in user it's due for. Users with nothing
+/// to report are marked as done without an email. Returns how many were queued.
+pub async fn send_due(db: &Database, queue: &EmailQueue) -> anyhow::Result<usize> {
+    let now = Utc::now();
+    let mut queued = 0;
+    for user in db.get_weekly_summary_recipients().await? {
+        let zone = user_zone(&user.timezone);
+        if !is_due(now, zone, user.weekly_summary_sent_at) {
+            continue;
+        }
+        // Claimed before sending, so replicas running the same hour don't both send
+        if !db.claim_weekly_summary(user.id, now - Duration::days(6)).await? {
+            continue;
+        }
+
+        let summary = db.get_weekly_summary(user.id, now - Duration::days(7)).await?;
+        if summary.is_empty() {
+            tracing::debug!("Nothing to summarise for {} this week", user.email);
+            continue;
+        }
+        let email = EmailService::weekly_summary(&user.email, &summary, &format_local(now, zone))
+            .with_unsubscribe(Some(unsubscribe::link(UnsubscribeScope::WeeklySummary(user.id))), None);
+        queue.enqueue(email).await?;
+        queued += 1;
+    }
+    Ok(queued)
+}
+
+/// Check for due summaries every hour, for as long as the process runs
+pub fn spawn(db: Database, queue: EmailQueue) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match send_due(&db, &queue).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("📰 Queued {} weekly summary email(s)", count),
+                Err(e) => tracing::error!("Failed to send weekly summaries: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_is_due() {
+        let kolkata = Tz::Asia__Kolkata;
+        // Monday 09:30 in Kolkata, still Monday 04:00 in UTC
+        let monday = Utc.with_ymd_and_hms(2026, 10, 19, 4, 0, 0).unwrap();
+
+        assert!(is_due(monday, kolkata, None));
+        assert!(is_due(monday, kolkata, Some(monday - Duration::days(7))));
+        assert!(!is_due(monday, kolkata, Some(monday - Duration::hours(1))));
+        // Too early in UTC, and still Sunday in Los Angeles
+        assert!(!is_due(monday, Tz::UTC, None));
+        assert!(!is_due(monday, Tz::America__Los_Angeles, None));
+    }
+}
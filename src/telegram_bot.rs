@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::price_parse::parse_price;
+
+/// How long a code from `POST /telegram/link` can be redeemed with `/link`
+pub const LINK_CODE_TTL_MINUTES: i64 = 15;
+
+pub const HELP: &str = "Commands:
+/track <url> <price> - alert when the product drops to your price
+/list - your active alerts
+/price <n> - current price of alert n from /list
+/delete <n> - stop alert n from /list
+/unlink - disconnect this chat from your account";
+
+const TRACK_USAGE: &str = "Usage: /track <url> <price>, e.g. /track https://www.myntra.com/12345678 999";
+
+/// The parts of a Telegram webhook update the bot reads
+#[derive(Debug, Deserialize)]
+pub struct Update {
+    pub message: Option<Message>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Message {
+    pub chat: Chat,
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Chat {
+    pub id: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// `/start <code>` (from a t.me deep link) or `/link <code>`
+    Link(Option<String>),
+    Track { url: String, target_price: f64 },
+    List,
+    /// 1-based position in the `/list` output
+    Price(usize),
+    Delete(usize),
+    Unlink,
+    Help,
+    /// A known command with bad arguments; holds the usage to reply with
+    Invalid(&'static str),
+}
+
+impl Command {
+    pub fn parse(text: &str) -> Command {
+        let mut words = text.split_whitespace();
+        // "/list@PriceTrackerBot" in group chats
+        let name = words
+            .next()
+            .unwrap_or_default()
+            .split('@')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        let args: Vec<&str> = words.collect();
+
+        match name.as_str() {
+            "/start" | "/link" => Command::Link(args.first().map(|code| code.to_uppercase())),
+            "/track" => match args.as_slice() {
+                [url, price] => match parse_price(price) {
+                    Some(target_price) => Command::Track { url: url.to_string(), target_price },
+                    None => Command::Invalid(TRACK_USAGE),
+                },
+                _ => Command::Invalid(TRACK_USAGE),
+            },
+            "/list" => Command::List,
+            "/price" => match position(&args) {
+                Some(n) => Command::Price(n),
+                None => Command::Invalid("Usage: /price <n>, where n is the alert's number in /list"),
+            },
+            "/delete" => match position(&args) {
+                Some(n) => Command::Delete(n),
+                None => Command::Invalid("Usage: /delete <n>, where n is the alert's number in /list"),
+            },
+            "/unlink" => Command::Unlink,
+            _ => Command::Help,
+        }
+    }
+}
+
+fn position(args: &[&str]) -> Option<usize> {
+    match args {
+        [n] => n.parse().ok().filter(|n| *n > 0),
+        _ => None,
+    }
+}
+
+/// Short code users type into the bot; unambiguous characters only
+pub fn generate_link_code() -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+    uuid::Uuid::new_v4()
+        .as_bytes()
+        .iter()
+        .take(8)
+        .map(|b| ALPHABET[*b as usize % ALPHABET.len()] as char)
+        .collect()
+}
+
+/// Webhook response that makes Telegram send `text` to the chat, saving a Bot API call
+pub fn reply(chat_id: i64, text: &str) -> Value {
+    json!({
+        "method": "sendMessage",
+        "chat_id": chat_id,
+        "text": text,
+        "disable_web_page_preview": true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(Command::parse("/start ab12cd34"), Command::Link(Some("AB12CD34".to_string())));
+        assert_eq!(Command::parse("/link"), Command::Link(None));
+        assert_eq!(
+            Command::parse("/track https://www.myntra.com/12345678 ₹1,299"),
+            Command::Track { url: "https://www.myntra.com/12345678".to_string(), target_price: 1299.0 }
+        );
+        assert!(matches!(Command::parse("/track https://www.myntra.com/12345678"), Command::Invalid(_)));
+        assert_eq!(Command::parse("/list@PriceTrackerBot"), Command::List);
+        assert_eq!(Command::parse("/price 2"), Command::Price(2));
+        assert!(matches!(Command::parse("/delete 0"), Command::Invalid(_)));
+        assert_eq!(Command::parse("hello"), Command::Help);
+
+        let code = generate_link_code();
+        assert_eq!(code.len(), 8);
+        assert!(code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()));
+    }
+}
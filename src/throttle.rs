@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use crate::config::ThrottleConfig;
+use crate::db::Database;
+use crate::models::{PlatformThrottle, ThrottleAdjustment};
+
+/// Most rounds a platform's alerts wait between checks, however badly it's failing
+pub const MAX_FACTOR: i32 = 8;
+
+/// The factor after `failures` out of `attempts`: doubled (up to `MAX_FACTOR`) at or above
+/// the failure rate, halved back towards 1 at or below the recovery rate, and left alone in
+/// between or on too few attempts to tell
+pub fn next_factor(config: &ThrottleConfig, factor: i32, attempts: i64, failures: i64) -> i32 {
+    if attempts < config.min_attempts.max(1) {
+        return factor;
+    }
+    let rate = failures as f64 / attempts as f64;
+    if rate >= config.failure_rate {
+        (factor * 2).min(MAX_FACTOR)
+    } else if rate <= config.recovery_rate {
+        (factor / 2).max(1)
+    } else {
+        factor
+    }
+}
+
+/// When a platform checked in the round that started at `checked_at` is next checked, so
+/// that `factor - 1` rounds skip it. Half a round of slack absorbs the drift between rounds.
+pub fn next_check_at(checked_at: DateTime<Utc>, factor: i32, round: Duration) -> Option<DateTime<Utc>> {
+    let round = chrono::Duration::from_std(round).unwrap_or_default();
+    (factor > 1).then(|| checked_at + round * (factor - 1) + round / 2)
+}
+
+fn every(factor: i32) -> String {
+    match factor {
+        1 => "every round".to_string(),
+        factor => format!("every {} rounds", factor),
+    }
+}
+
+/// What the dashboard shows for a change from `from` to `to`
+pub fn reason(config: &ThrottleConfig, from: i32, to: i32, attempts: i64, failures: i64) -> String {
+    let percent = |rate: f64| (rate * 100.0).round();
+    let rate = failures as f64 / attempts.max(1) as f64;
+    let (trend, limit) = if to > from {
+        ("at or above", config.failure_rate)
+    } else {
+        ("at or below", config.recovery_rate)
+    };
+    format!(
+        "{} of {} checks failed ({}%, {} {}%): checking {} instead of {}",
+        failures,
+        attempts,
+        percent(rate),
+        trend,
+        percent(limit),
+        every(to),
+        every(from)
+    )
+}
+
+/// Judge every platform on its checks since its last adjustment, saving and logging each
+/// change, and return the throttles in force by platform. A platform that's slowed down
+/// sits out the round about to run; one that recovers doesn't wait any longer than it
+/// already had to.
+pub async fn adjust(
+    db: &Database,
+    config: &ThrottleConfig,
+    round: Duration,
+    now: DateTime<Utc>,
+) -> anyhow::Result<HashMap<String, PlatformThrottle>> {
+    let mut throttles: HashMap<String, PlatformThrottle> = db
+        .get_platform_throttles()
+        .await?
+        .into_iter()
+        .map(|throttle| (throttle.platform.clone(), throttle))
+        .collect();
+
+    let last_round = now - chrono::Duration::from_std(round).unwrap_or_default();
+    for (platform, attempts, failures) in db.get_platform_failure_counts(now - config.window).await? {
+        let current = throttles.get(&platform);
+        let from = current.map_or(1, |throttle| throttle.factor);
+        let to = next_factor(config, from, attempts, failures);
+        if to == from {
+            continue;
+        }
+
+        let mut next = next_check_at(last_round, to, round);
+        if to < from
+            && let Some(waiting) = current.and_then(|throttle| throttle.next_check_at)
+        {
+            next = next.map(|at| at.min(waiting));
+        }
+        let adjustment = ThrottleAdjustment {
+            platform: platform.clone(),
+            from_factor: from,
+            to_factor: to,
+            attempts,
+            failures,
+            reason: reason(config, from, to, attempts, failures),
+            created_at: now,
+        };
+        if to > from {
+            tracing::warn!("🐢 Slowing down {} checks: {}", platform, adjustment.reason);
+        } else {
+            tracing::info!("🐇 Speeding {} checks back up: {}", platform, adjustment.reason);
+        }
+        let throttle = db.save_throttle_adjustment(&adjustment, next).await?;
+        throttles.insert(platform, throttle);
+    }
+    Ok(throttles)
+}
+
+/// Schedule the next check of each throttled platform among `platforms`, whose alerts
+/// were checked in the round that started at `started`
+pub async fn mark_checked<'a>(
+    db: &Database,
+    throttles: &HashMap<String, PlatformThrottle>,
+    platforms: impl IntoIterator<Item = &'a str>,
+    started: DateTime<Utc>,
+    round: Duration,
+) -> anyhow::Result<()> {
+    for platform in platforms {
+        if let Some(throttle) = throttles.get(platform).filter(|throttle| throttle.factor > 1) {
+            db.set_throttle_next_check(platform, next_check_at(started, throttle.factor, round)).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn config() -> ThrottleConfig {
+        ThrottleConfig {
+            failure_rate: 0.5,
+            recovery_rate: 0.2,
+            min_attempts: 20,
+            window: chrono::Duration::hours(24),
+        }
+    }
+
+    #[test]
+    fn test_next_factor() {
+        let config = config();
+        assert_eq!(next_factor(&config, 1, 10, 10), 1);
+        assert_eq!(next_factor(&config, 1, 20, 10), 2);
+        assert_eq!(next_factor(&config, 8, 20, 20), MAX_FACTOR);
+        // Between the two rates nothing changes, either way
+        assert_eq!(next_factor(&config, 4, 20, 6), 4);
+        assert_eq!(next_factor(&config, 4, 20, 4), 2);
+        assert_eq!(next_factor(&config, 1, 20, 0), 1);
+    }
+
+    #[test]
+    fn test_next_check_at() {
+        let round = Duration::from_secs(6 * 60 * 60);
+        let started = Utc.with_ymd_and_hms(2026, 10, 16, 0, 0, 0).unwrap();
+        assert_eq!(next_check_at(started, 1, round), None);
+        assert_eq!(next_check_at(started, 2, round), Some(started + chrono::Duration::hours(9)));
+        assert_eq!(next_check_at(started, 4, round), Some(started + chrono::Duration::hours(21)));
+    }
+
+    #[test]
+    fn test_reason() {
+        let config = config();
+        assert_eq!(
+            reason(&config, 1, 2, 24, 15),
+            "15 of 24 checks failed (63%, at or above 50%): checking every 2 rounds instead of every round"
+        );
+        assert_eq!(
+            reason(&config, 2, 1, 20, 1),
+            "1 of 20 checks failed (5%, at or below 20%): checking every round instead of every 2 rounds"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+
+/// Zone for users who haven't picked one
+pub const DEFAULT_TIMEZONE: &str = "UTC";
+
+/// An IANA zone name such as "Asia/Kolkata", or None if it isn't one
+pub fn parse(name: &str) -> Option<Tz> {
+    name.trim().parse().ok()
+}
+
+/// A stored `users.timezone`, falling back to UTC if it no longer parses
+pub fn user_zone(name: &str) -> Tz {
+    parse(name).unwrap_or(Tz::UTC)
+}
+
+/// `at` as a user in `zone` would read it, e.g. "16 Oct 2026, 9:12 PM IST"
+pub fn format_local(at: DateTime<Utc>, zone: Tz) -> String {
+    at.with_timezone(&zone).format("%-d %b %Y, %-I:%M %p %Z").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_format_in_user_zone() {
+        let at = Utc.with_ymd_and_hms(2026, 10, 16, 15, 42, 0).unwrap();
+
+        assert_eq!(format_local(at, user_zone("Asia/Kolkata")), "16 Oct 2026, 9:12 PM IST");
+        assert_eq!(format_local(at, user_zone("America/New_York")), "16 Oct 2026, 11:42 AM EDT");
+        assert_eq!(format_local(at, user_zone("Mars/Olympus_Mons")), "16 Oct 2026, 3:42 PM UTC");
+        assert!(parse("Mars/Olympus_Mons").is_none());
+    }
+}
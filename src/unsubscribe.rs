@@ -0,0 +1,92 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64, Engine};
+use ring::hmac;
+use uuid::Uuid;
+
+use crate::email::app_base_url;
+
+/// What a one-click unsubscribe link switches off
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsubscribeScope {
+    /// Pause notifications about this price alert
+    Alert(Uuid),
+    /// Pause every notification for this user
+    Account(Uuid),
+    /// Stop this user's weekly summary email
+    WeeklySummary(Uuid),
+}
+
+impl UnsubscribeScope {
+    fn kind(&self) -> &'static str {
+        match self {
+            UnsubscribeScope::Alert(_) => "alert",
+            UnsubscribeScope::Account(_) => "account",
+            UnsubscribeScope::WeeklySummary(_) => "weekly_summary",
+        }
+    }
+
+    fn id(&self) -> Uuid {
+        match self {
+            UnsubscribeScope::Alert(id) | UnsubscribeScope::Account(id) | UnsubscribeScope::WeeklySummary(id) => *id,
+        }
+    }
+}
+
+/// `UNSUBSCRIBE_SECRET`, falling back to `JWT_SECRET`. Links don't expire,
+/// so rotating the secret is the only way to invalidate ones already sent.
+fn key() -> hmac::Key {
+    let secret = std::env::var("UNSUBSCRIBE_SECRET")
+        .or_else(|_| std::env::var("JWT_SECRET"))
+        .unwrap_or_else(|_| "dev_secret_key_change_in_production".to_string());
+    hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes())
+}
+
+/// `{kind}.{id}.{signature}`, safe to put in a query string as-is
+pub fn sign(scope: UnsubscribeScope) -> String {
+    let payload = format!("{}.{}", scope.kind(), scope.id());
+    let signature = hmac::sign(&key(), payload.as_bytes());
+    format!("{}.{}", payload, BASE64.encode(signature.as_ref()))
+}
+
+/// The scope a token was signed for, or None if it's malformed or tampered with
+pub fn verify(token: &str) -> Option<UnsubscribeScope> {
+    let (payload, signature) = token.trim().rsplit_once('.')?;
+    let signature = BASE64.decode(signature).ok()?;
+    hmac::verify(&key(), payload.as_bytes(), &signature).ok()?;
+
+    let (kind, id) = payload.split_once('.')?;
+    let id = Uuid::parse_str(id).ok()?;
+    match kind {
+        "alert" => Some(UnsubscribeScope::Alert(id)),
+        "account" => Some(UnsubscribeScope::Account(id)),
+        "weekly_summary" => Some(UnsubscribeScope::WeeklySummary(id)),
+        _ => None,
+    }
+}
+
+/// Link that applies `scope` without signing in
+pub fn link(scope: UnsubscribeScope) -> String {
+    format!("{}/unsubscribe?token={}", app_base_url(), sign(scope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsubscribe_token_round_trip() {
+        let id = Uuid::new_v4();
+        let alert = sign(UnsubscribeScope::Alert(id));
+        let account = sign(UnsubscribeScope::Account(id));
+
+        assert_eq!(verify(&alert), Some(UnsubscribeScope::Alert(id)));
+        assert_eq!(verify(&account), Some(UnsubscribeScope::Account(id)));
+        let summary = sign(UnsubscribeScope::WeeklySummary(id));
+        assert_eq!(verify(&summary), Some(UnsubscribeScope::WeeklySummary(id)));
+
+        // Reusing an alert signature for the whole account must fail
+        let forged = alert.replacen("alert.", "account.", 1);
+        assert_eq!(verify(&forged), None);
+        assert_eq!(verify(&format!("alert.{}.", Uuid::new_v4())), None);
+        assert_eq!(verify("not-a-token"), None);
+    }
+}
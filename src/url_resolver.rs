@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use reqwest::{redirect::Policy, Client, Url};
+use std::net::IpAddr;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use crate::scraper_trait::{detect_platform, is_platform_host};
+
+/// Hosts used by the shopping apps' "share" buttons that only redirect to the real product page
+const SHORTENER_HOSTS: &[&str] = &["myntr.it", "dl.flipkart.com", "fkrt.it", "fkrt.co", "ajio.page.link", "bit.ly", "amzn.to"];
+
+/// Most hops followed from a short link to the product page
+const MAX_REDIRECTS: usize = 10;
+
+/// Query parameters that only carry attribution and never affect which product is shown
+const TRACKING_PARAMS: &[&str] = &[
+    "affid", "affextparam1", "affextparam2", "cmpid", "fbclid", "gclid", "_appid",
+    "_branch_match_id", "_branch_referrer", "sharer", "share_source", "referrer",
+    "srno", "otracker", "ssid",
+];
+
+/// Turns whatever users paste (share-sheet text, short links, app deep links)
+/// into a plain product URL.
+pub struct UrlResolver {
+    client: Client,
+    shorteners: Vec<String>,
+}
+
+impl UrlResolver {
+    pub fn new() -> Self {
+        Self::with_shorteners(SHORTENER_HOSTS.iter().map(|h| h.to_string()).collect())
+    }
+
+    fn with_shorteners(shorteners: Vec<String>) -> Self {
+        let client = Client::builder()
+            .redirect(redirect_policy(shorteners.clone()))
+            .timeout(Duration::from_secs(10))
+            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/122.0.0.0 Safari/537.36")
+            .build()
+            .expect("Failed to create HTTP client");
+
+        UrlResolver { client, shorteners }
+    }
+
+    /// Treat `host` as a shortener too (used by tests against a local server)
+    pub fn with_shortener(mut self, host: &str) -> Self {
+        self.shorteners.push(host.to_string());
+        Self::with_shorteners(self.shorteners)
+    }
+
+    pub async fn resolve(&self, input: &str) -> Result<String> {
+        let raw = extract_url(input).ok_or_else(|| anyhow!("No link found in '{}'", input.trim()))?;
+        let mut url = Url::parse(raw)?;
+
+        // Flipkart app deep links mirror the website path under /dl/
+        if is_flipkart_deep_link(&url) {
+            let path = url.path()["/dl".len()..].to_string();
+            url.set_host(Some("www.flipkart.com"))?;
+            url.set_path(&path);
+        } else if self.is_short_link(&url) {
+            url = self.follow_redirects(url).await?;
+        }
+
+        // Mobile, AMP and bare-domain links all scrape as the desktop page
+        let url = Url::parse(&crate::scraper_trait::desktop_url(url.as_str()))?;
+        Ok(strip_tracking(url).to_string())
+    }
+
+    /// Whether resolving `input` means requesting a short link, rather than only rewriting it
+    pub fn needs_fetch(&self, input: &str) -> bool {
+        extract_url(input)
+            .and_then(|raw| Url::parse(raw).ok())
+            .is_some_and(|url| self.is_short_link(&url) && !is_flipkart_deep_link(&url))
+    }
+
+    fn is_short_link(&self, url: &Url) -> bool {
+        url.host_str().is_some_and(|host| self.shorteners.iter().any(|s| s == host))
+    }
+
+    async fn follow_redirects(&self, url: Url) -> Result<Url> {
+        // Some shorteners reject HEAD, so fall back to GET
+        let response = match self.client.head(url.clone()).send().await {
+            Ok(response) if response.status().is_success() || response.status().is_redirection() => response,
+            _ => self.client.get(url.clone()).send().await?,
+        };
+
+        let resolved = response.url().clone();
+        tracing::info!("🔗 Resolved {} -> {}", url, resolved);
+        Ok(resolved)
+    }
+}
+
+impl Default for UrlResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn is_flipkart_deep_link(url: &Url) -> bool {
+    url.host_str() == Some("dl.flipkart.com") && url.path().starts_with("/dl/")
+}
+
+/// Follow a short link only through other shorteners and on to the platforms' own
+/// hosts, so a link can't bounce the server into internal addresses or arbitrary sites.
+/// Hosts registered as shorteners are trusted as they are; any other IP address is refused.
+fn redirect_policy(shorteners: Vec<String>) -> Policy {
+    Policy::custom(move |attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error(anyhow!("Short link redirected more than {} times", MAX_REDIRECTS));
+        }
+        let url = attempt.url();
+        let host = url.host_str().unwrap_or_default().to_string();
+        if shorteners.contains(&host) {
+            return attempt.follow();
+        }
+        let internal = host
+            .trim_matches(['[', ']'])
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| is_internal(&ip));
+        let on_platform = detect_platform(url.as_str()).is_some_and(|platform| is_platform_host(url.as_str(), platform));
+        if internal || !on_platform {
+            let refused = anyhow!("Short link redirected to {}, which isn't a supported store", host);
+            return attempt.error(refused);
+        }
+        attempt.follow()
+    })
+}
+
+/// Loopback, private, link-local and other addresses that never belong to a public store
+fn is_internal(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast() || ip.is_documentation()
+        }
+        IpAddr::V6(ip) => {
+            let unique_local = (ip.segments()[0] & 0xfe00) == 0xfc00;
+            let link_local = (ip.segments()[0] & 0xffc0) == 0xfe80;
+            ip.is_loopback() || ip.is_unspecified() || unique_local || link_local
+                || ip.to_ipv4_mapped().is_some_and(|ip| is_internal(&IpAddr::V4(ip)))
+        }
+    }
+}
+
+/// First http(s) link in shared text like "Check out this shirt on Myntra! https://myntr.it/abc"
+fn extract_url(input: &str) -> Option<&str> {
+    static URL: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"https?://[^\s"'<>]+"#).unwrap());
+    URL.find(input).map(|m| m.as_str().trim_end_matches(['.', ',', ')', '!']))
+}
+
+/// Drop attribution query params and the fragment, keeping the ones that pick the product (e.g. `pid`)
+pub(crate) fn strip_tracking(mut url: Url) -> Url {
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| {
+            let key = key.to_lowercase();
+            !key.starts_with("utm_") && !TRACKING_PARAMS.contains(&key.as_str())
+        })
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+
+    if kept.is_empty() {
+        url.set_query(None);
+    } else {
+        url.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    url.set_fragment(None);
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[test]
+    fn test_extract_url_from_share_text() {
+        let shared = "Check out this Roadster shirt on Myntra! https://myntr.it/b9Xk2 .";
+        assert_eq!(extract_url(shared), Some("https://myntr.it/b9Xk2"));
+        assert_eq!(extract_url("no link here"), None);
+    }
+
+    #[tokio::test]
+    async fn test_flipkart_deep_link_and_tracking_params() {
+        let resolver = UrlResolver::new();
+        let resolved = resolver
+            .resolve("https://dl.flipkart.com/dl/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234&affid=abc&utm_source=app&cmpid=share")
+            .await
+            .unwrap();
+
+        assert_eq!(resolved, "https://www.flipkart.com/roadster-tshirt/p/itm6a3d8f7c9b2e1?pid=TSHFZ6G8HXYZ1234");
+
+        let resolved = resolver.resolve("https://m.myntra.com/shirts/roadster/12345678/buy?utm_source=share").await.unwrap();
+        assert_eq!(resolved, "https://www.myntra.com/shirts/roadster/12345678/buy");
+        
+        assert!(resolver.needs_fetch("Check out this shirt on Myntra! https://myntr.it/b9Xk2"));
+        assert!(!resolver.needs_fetch("https://dl.flipkart.com/dl/roadster-tshirt/p/itm6a3d8f7c9b2e1"));
+        assert!(!resolver.needs_fetch("https://www.myntra.com/shirts/roadster/12345678/buy"));
+    }
+
+    #[tokio::test]
+    async fn test_follows_short_link_redirects() {
+        let mut server = Server::new_async().await;
+        let product_url = format!("{}/shirts/roadster/12345678/buy", server.url());
+        let _short = server.mock("HEAD", "/b9Xk2")
+            .with_status(301)
+            .with_header("location", &format!("{}?utm_source=share", product_url))
+            .create_async()
+            .await;
+        let _product = server.mock("HEAD", "/shirts/roadster/12345678/buy")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let host = Url::parse(&server.url()).unwrap().host_str().unwrap().to_string();
+        let resolver = UrlResolver::new().with_shortener(&host);
+        let resolved = resolver.resolve(&format!("Look! {}/b9Xk2", server.url())).await.unwrap();
+
+        assert_eq!(resolved, product_url);
+    }
+
+    #[tokio::test]
+    async fn test_refuses_redirects_off_the_stores() {
+        let mut server = Server::new_async().await;
+        let _metadata = server.mock("HEAD", "/metadata")
+            .with_status(302)
+            .with_header("location", "http://169.254.169.254/latest/meta-data/")
+            .create_async()
+            .await;
+        let _elsewhere = server.mock("HEAD", "/elsewhere")
+            .with_status(302)
+            .with_header("location", "https://example.com/?ref=myntra.com")
+            .create_async()
+            .await;
+        let _fallback = server.mock("GET", mockito::Matcher::Any)
+            .with_status(302)
+            .with_header("location", "http://169.254.169.254/latest/meta-data/")
+            .create_async()
+            .await;
+
+        let host = Url::parse(&server.url()).unwrap().host_str().unwrap().to_string();
+        let resolver = UrlResolver::new().with_shortener(&host);
+        assert!(resolver.resolve(&format!("{}/metadata", server.url())).await.is_err());
+        assert!(resolver.resolve(&format!("{}/elsewhere", server.url())).await.is_err());
+    }
+
+    #[test]
+    fn test_is_internal() {
+        for ip in ["127.0.0.1", "10.0.0.5", "192.168.1.1", "169.254.169.254", "::1", "fd00::1", "::ffff:10.0.0.1"] {
+            assert!(is_internal(&ip.parse().unwrap()), "{}", ip);
+        }
+        assert!(!is_internal(&"23.185.0.1".parse().unwrap()));
+    }
+}
@@ -0,0 +1,191 @@
+use std::time::Duration;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde_json::Value;
+use crate::config::WaybackConfig;
+use crate::db::Database;
+use crate::models::PriceAlert;
+use crate::scraper_config::ScraperConfigStore;
+
+/// Alerts claimed per batch
+const BATCH: i64 = 5;
+
+/// How often alerts that were never backfilled are looked for
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// One archived capture of a page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub captured_at: DateTime<Utc>,
+    /// The CDX timestamp (`yyyyMMddhhmmss`) and URL that address the capture
+    timestamp: String,
+    original: String,
+}
+
+impl Snapshot {
+    /// The capture as originally served, without the archive's toolbar and link rewriting
+    pub fn url(&self, config: &WaybackConfig) -> String {
+        format!("{}/web/{}id_/{}", config.base_url, self.timestamp, self.original)
+    }
+}
+
+/// Captures in a CDX `output=json&fl=timestamp,original` response, whose first row is the header
+pub fn parse_cdx(body: &Value) -> Vec<Snapshot> {
+    body.as_array()
+        .into_iter()
+        .flatten()
+        .skip(1)
+        .filter_map(|row| {
+            let timestamp = row.get(0)?.as_str()?;
+            let original = row.get(1)?.as_str()?;
+            let captured_at = NaiveDateTime::parse_from_str(timestamp, "%Y%m%d%H%M%S").ok()?.and_utc();
+            Some(Snapshot { captured_at, timestamp: timestamp.to_string(), original: original.to_string() })
+        })
+        .collect()
+}
+
+/// Successful captures of `url` within the lookback, at most one per month, newest first
+pub async fn list_snapshots(config: &WaybackConfig, url: &str) -> Result<Vec<Snapshot>> {
+    let from = (Utc::now() - chrono::Duration::days(config.lookback_days)).format("%Y%m%d").to_string();
+    let body = crate::http_client::shared()
+        .get(format!("{}/cdx/search/cdx", config.base_url))
+        .query(&[
+            ("url", url),
+            ("output", "json"),
+            ("fl", "timestamp,original"),
+            ("filter", "statuscode:200"),
+            ("collapse", "timestamp:6"),
+            ("from", from.as_str()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+
+    // No captures comes back as an empty body rather than `[]`
+    if body.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut snapshots = parse_cdx(&serde_json::from_str(&body)?);
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.captured_at));
+    Ok(snapshots)
+}
+
+/// Read the alert's page from archived captures with its platform's scraper and add the
+/// prices to its history (only those older than its own checks, see
+/// `Database::save_backfilled_prices`). Returns the captures read and the prices saved.
+pub async fn backfill_alert(
+    db: &Database,
+    scraper_configs: &ScraperConfigStore,
+    config: &WaybackConfig,
+    alert: &PriceAlert,
+) -> Result<(usize, u64)> {
+    let alert_id = alert.id.ok_or_else(|| anyhow!("Alert has no ID"))?;
+    let scraper = scraper_configs
+        .scraper(&alert.platform)
+        .ok_or_else(|| anyhow!("No scraper for platform '{}'", alert.platform))?;
+
+    let snapshots = list_snapshots(config, &alert.url).await?;
+    let snapshots = &snapshots[..snapshots.len().min(config.max_snapshots)];
+    let mut points = Vec::new();
+    for snapshot in snapshots {
+        tokio::time::sleep(config.request_gap).await;
+        match scraper.get_product(&snapshot.url(config)).await {
+            Ok(product) if alert.in_bounds(product.selling_price) => {
+                points.push((snapshot.captured_at, product.selling_price, product.mrp));
+            }
+            Ok(product) => tracing::debug!(
+                "Ignoring archived price {} of {} from {}: outside the alert's range",
+                product.selling_price,
+                alert.url,
+                snapshot.captured_at
+            ),
+            // Older page layouts often don't parse; the other captures still count
+            Err(e) => tracing::debug!("No price in the {} capture of {}: {}", snapshot.captured_at, alert.url, e),
+        }
+    }
+
+    let saved = db.save_backfilled_prices(alert_id, &points).await?;
+    Ok((snapshots.len(), saved))
+}
+
+/// Backfill every alert that never was, a batch at a time. Alerts are claimed before they're
+/// read, so one interrupted by a restart is skipped rather than read twice. Returns how
+/// many alerts were backfilled.
+pub async fn backfill_due(db: &Database, scraper_configs: &ScraperConfigStore, config: &WaybackConfig) -> Result<usize> {
+    let mut done = 0;
+    loop {
+        let alerts = db.claim_backfills(BATCH).await?;
+        if alerts.is_empty() {
+            return Ok(done);
+        }
+        for alert in &alerts {
+            let Some(alert_id) = alert.id else { continue };
+            let outcome = backfill_alert(db, scraper_configs, config, alert).await;
+            match &outcome {
+                Ok((_, 0)) => {}
+                Ok((snapshots, saved)) => tracing::info!(
+                    "🕰️ Seeded {} with {} archived price(s) from {} capture(s)",
+                    alert.url,
+                    saved,
+                    snapshots
+                ),
+                Err(e) => tracing::warn!("Failed to backfill {} from the Wayback Machine: {}", alert.url, e),
+            }
+            let (snapshots, saved) = outcome.as_ref().map_or((0, 0), |&counts| counts);
+            let error = outcome.as_ref().err().map(|e| e.to_string());
+            db.finish_backfill(alert_id, snapshots, saved, error.as_deref()).await?;
+            done += 1;
+            tokio::time::sleep(config.request_gap).await;
+        }
+    }
+}
+
+/// Look for alerts to backfill every few minutes, for as long as the process runs
+pub fn spawn(db: Database, scraper_configs: ScraperConfigStore, config: WaybackConfig) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            match backfill_due(&db, &scraper_configs, &config).await {
+                Ok(0) => {}
+                Ok(count) => tracing::info!("🕰️ Backfilled {} alert(s) from the Wayback Machine", count),
+                Err(e) => tracing::error!("Failed to backfill alerts: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_cdx() {
+        let body = json!([
+            ["timestamp", "original"],
+            ["20260312084501", "https://www.myntra.com/12345678"],
+            ["not-a-time", "https://www.myntra.com/12345678"],
+            ["20260105190000"]
+        ]);
+        let snapshots = parse_cdx(&body);
+
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].captured_at, Utc.with_ymd_and_hms(2026, 3, 12, 8, 45, 1).unwrap());
+
+        let config = WaybackConfig {
+            base_url: "https://web.archive.org".to_string(),
+            max_snapshots: 12,
+            lookback_days: 365,
+            request_gap: Duration::ZERO,
+        };
+        assert_eq!(
+            snapshots[0].url(&config),
+            "https://web.archive.org/web/20260312084501id_/https://www.myntra.com/12345678"
+        );
+        assert!(parse_cdx(&json!([])).is_empty());
+    }
+}
@@ -1,116 +1,1036 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use chrono::Utc;
 use tokio::time::interval;
 use crate::db::Database;
-use crate::scrapers::create_scraper;
-use crate::email::EmailService;
+use crate::maintenance::Maintenance;
+use crate::metrics::{classify_error, scrape_metrics};
+use crate::scraper_config::ScraperConfigStore;
+use crate::scrapers::PLATFORMS;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use serde::Serialize;
+use tokio::sync::mpsc::UnboundedSender;
+use crate::models::{is_price_anomaly, AlertMode, AnnotationKind, ListingItem, Offer, PriceAlert, PriceBadge, PriceLows, PriceUpdate, RenotifyPolicy};
+use crate::notifier_trait::{BackInStock, KeywordMatch, ListingMatch, NotificationEvent, PriceDrop, ProductRemoved};
+use crate::scraper_trait::{desktop_url, PageGone, PriceScraper, ProductSnapshot, RateLimited};
+use crate::notifiers::NotifierRegistry;
+use crate::events::{AlertFailed, LiveEvent, PriceChecked};
+use crate::redis_store::RedisStore;
+use crate::outbox;
+use crate::config::ThrottleConfig;
+use crate::throttle;
 
-pub async fn start_price_monitor(db: Database) {
+/// Per-alert progress of a check run, streamed to whoever triggered it manually
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum CheckProgress {
+    Started { total: usize },
+    Checking { index: usize, total: usize, alert_id: Option<uuid::Uuid>, url: String },
+    Price { alert_id: Option<uuid::Uuid>, price: f64, target_price: f64, dropped: bool },
+    Stock { alert_id: Option<uuid::Uuid>, in_stock: bool },
+    Failed { alert_id: Option<uuid::Uuid>, reason: &'static str, error: String },
+    Finished { checked: usize, drops: usize },
+    Aborted { error: String },
+}
+
+impl CheckProgress {
+    /// SSE event name, matching the serialized `event` tag
+    pub fn name(&self) -> &'static str {
+        match self {
+            CheckProgress::Started { .. } => "started",
+            CheckProgress::Checking { .. } => "checking",
+            CheckProgress::Price { .. } => "price",
+            CheckProgress::Stock { .. } => "stock",
+            CheckProgress::Failed { .. } => "failed",
+            CheckProgress::Finished { .. } => "finished",
+            CheckProgress::Aborted { .. } => "aborted",
+        }
+    }
+}
+
+/// What checking a single alert found
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum AlertCheck {
+    /// `notified` is how many channels delivered the drop notification
+    Price { price: f64, mrp: Option<f64>, target_price: f64, dropped: bool, badge: Option<PriceBadge>, notified: usize },
+    Stock { in_stock: bool, restocked: bool, notified: usize },
+    Failed { reason: &'static str, error: String },
+}
+
+/// Where a check run reports progress; `None` for the scheduled runs
+pub type ProgressSender = UnboundedSender<CheckProgress>;
+
+fn report(progress: Option<&ProgressSender>, event: CheckProgress) {
+    // A closed receiver just means the client went away; the check carries on
+    if let Some(sender) = progress {
+        let _ = sender.send(event);
+    }
+}
+
+/// Check results held back so a run writes them in batches rather than with three
+/// statements per alert. Their `PriceChecked` events go out once they're stored.
+#[derive(Default)]
+struct PendingWrites {
+    updates: Vec<PriceUpdate>,
+    checked: Vec<PriceChecked>,
+}
+
+/// Results a run holds before writing them, unless `CHECK_WRITE_BATCH` says otherwise
+const DEFAULT_WRITE_BATCH: usize = 100;
+
+fn write_batch_size() -> usize {
+    std::env::var("CHECK_WRITE_BATCH")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|size: &usize| *size > 0)
+        .unwrap_or(DEFAULT_WRITE_BATCH)
+}
+
+/// Alerts a run checks between checkpoints, unless `CHECK_CHUNK_SIZE` says otherwise
+const DEFAULT_CHUNK_SIZE: usize = 200;
+
+/// Checkpoint row shared by scheduled and manual runs
+const CHECK_RUN: &str = "price_check";
+
+fn chunk_size() -> usize {
+    std::env::var("CHECK_CHUNK_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|size: &usize| *size > 0)
+        .unwrap_or(DEFAULT_CHUNK_SIZE)
+}
+
+/// How many times above or below the recent median a price has to be to count as a
+/// glitch, unless `PRICE_ANOMALY_FACTOR` says otherwise (0 turns the check off)
+const DEFAULT_ANOMALY_FACTOR: f64 = 5.0;
+
+/// Recent prices the median is taken over, and how many it needs before it's used
+const ANOMALY_WINDOW: i64 = 10;
+const ANOMALY_MIN_POINTS: i64 = 3;
+
+fn anomaly_factor() -> f64 {
+    std::env::var("PRICE_ANOMALY_FACTOR")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|factor: &f64| factor.is_finite())
+        .unwrap_or(DEFAULT_ANOMALY_FACTOR)
+}
+
+/// 404/410s in a row before an alert's product counts as removed, unless
+/// `PRODUCT_REMOVED_AFTER` says otherwise
+const DEFAULT_PRODUCT_REMOVED_AFTER: i32 = 3;
+
+fn product_removed_after() -> i32 {
+    std::env::var("PRODUCT_REMOVED_AFTER")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|checks: &i32| *checks > 0)
+        .unwrap_or(DEFAULT_PRODUCT_REMOVED_AFTER)
+}
+
+impl PendingWrites {
+    fn push(&mut self, update: PriceUpdate, checked: PriceChecked) {
+        self.updates.push(update);
+        self.checked.push(checked);
+    }
+
+    fn len(&self) -> usize {
+        self.updates.len()
+    }
+
+    async fn flush(&mut self, db: &Database, notifiers: &NotifierRegistry) -> anyhow::Result<()> {
+        let updates = std::mem::take(&mut self.updates);
+        let checked = std::mem::take(&mut self.checked);
+        db.apply_price_updates(&updates).await?;
+        if !updates.is_empty() {
+            tracing::debug!("💾 Saved {} price snapshot(s)", updates.len());
+        }
+        
+        for event in checked {
+            notifiers.events().publish(&LiveEvent::PriceChecked(event)).await;
+        }
+        Ok(())
+    }
+}
+
+/// How often the scheduled check runs
+const CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Lease each scheduled round takes in Redis so only one replica runs it
+const CHECK_LOCK: &str = "lock:price_check";
+
+/// Runs the scheduled checks. With `shared` set, replicas race for a lease lasting most of
+/// an interval each round, so the checks (and their notifications) happen once per round.
+/// A round that falls during maintenance waits for it to end instead of being skipped.
+pub async fn start_price_monitor(
+    db: Database,
+    notifiers: NotifierRegistry,
+    scraper_configs: ScraperConfigStore,
+    shared: Option<RedisStore>,
+    maintenance: Maintenance,
+) {
     tracing::info!("Starting background price monitoring worker (6-hour interval)");
     
-    let mut ticker = interval(Duration::from_secs(6 * 60 * 60)); // 6 hours
+    let mut ticker = interval(CHECK_INTERVAL);
+    let holder = uuid::Uuid::new_v4().to_string();
     
     loop {
         ticker.tick().await;
         
+        if maintenance.is_enabled() {
+            tracing::info!("🚧 Maintenance mode is on; price checks are paused");
+            maintenance.wait_until_off().await;
+            tracing::info!("Maintenance mode is off; resuming price checks");
+        }
+        
+        if let Some(store) = &shared {
+            match store.claim(CHECK_LOCK, &holder, CHECK_INTERVAL.mul_f64(0.9)).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    tracing::info!("Another replica is running this round's price check");
+                    continue;
+                }
+                // Better a duplicate round than none at all
+                Err(e) => tracing::warn!("Couldn't take the price check lock, running anyway: {}", e),
+            }
+        }
+        
         tracing::info!("Running scheduled price check...");
         
-        if let Err(e) = check_all_alerts(db.clone()).await {
+        if let Err(e) = check_all_alerts(db.clone(), &notifiers, &scraper_configs, None).await {
             tracing::error!("Error during price check: {}", e);
         }
     }
 }
 
-async fn check_all_alerts(db: Database) -> anyhow::Result<()> {
-    let alerts = db.get_all_active_alerts().await?;
+async fn check_all_alerts(
+    db: Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+    progress: Option<&ProgressSender>,
+) -> anyhow::Result<()> {
+    let expired = db.expire_alerts().await?;
+    if expired > 0 {
+        tracing::info!("Deactivated {} expired alert(s)", expired);
+    }
+    let mut alerts = due_alerts(&db, db.get_all_active_alerts().await?).await?;
     
-    let mut alerts_checked = 0;
+    // Platforms failing too often are checked every few rounds until they recover
+    let throttles = match ThrottleConfig::from_env() {
+        Some(config) => throttle::adjust(&db, &config, CHECK_INTERVAL, Utc::now())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::error!("Failed to adjust platform throttles: {}", e);
+                HashMap::new()
+            }),
+        None => HashMap::new(),
+    };
+    let now = Utc::now();
+    let before = alerts.len();
+    alerts.retain(|alert| throttles.get(&alert.platform).is_none_or(|throttle| throttle.is_due(now)));
+    if alerts.len() < before {
+        tracing::info!("🐢 Skipping {} alert(s) on throttled platforms this round", before - alerts.len());
+    }
+    
+    // Pick up after the last chunk an interrupted run finished, unless that run belongs
+    // to an earlier cycle. Alerts created since it started wait for the next one.
+    let checkpoint = db
+        .get_check_checkpoint(CHECK_RUN)
+        .await?
+        .filter(|checkpoint| Utc::now() - checkpoint.run_started_at < chrono::Duration::from_std(CHECK_INTERVAL).unwrap_or_default());
+    let run_started_at = match &checkpoint {
+        Some(checkpoint) => {
+            alerts.retain(|alert| checkpoint.is_ahead(alert));
+            tracing::info!(
+                "Resuming the price check started at {} ({} alert(s) left)",
+                checkpoint.run_started_at.to_rfc3339(),
+                alerts.len()
+            );
+            checkpoint.run_started_at
+        }
+        None => Utc::now(),
+    };
+    
+    let total = alerts.len();
+    report(progress, CheckProgress::Started { total });
+    
+    let started = AtomicUsize::new(0);
     let mut price_drops = 0;
+    for chunk in alerts.chunks(chunk_size()) {
+        // Each platform's alerts are checked in turn, and the platforms side by side
+        let mut by_platform: BTreeMap<&str, Vec<&PriceAlert>> = BTreeMap::new();
+        for alert in chunk {
+            by_platform.entry(alert.platform.as_str()).or_default().push(alert);
+        }
+        let groups = by_platform
+            .into_values()
+            .map(|alerts| check_platform(&db, notifiers, scraper_configs, alerts, total, &started, progress));
+        
+        // Every group gets to write what it checked before a failed one stops the run,
+        // which then resumes with this chunk
+        for drops in futures::future::join_all(groups).await {
+            price_drops += drops?;
+        }
+        if let Some(last) = chunk.last()
+            && let Some(id) = last.id
+        {
+            db.save_check_checkpoint(CHECK_RUN, run_started_at, last.created_at, id).await?;
+        }
+    }
+    db.clear_check_checkpoint(CHECK_RUN).await?;
+    let alerts_checked = started.into_inner();
+    
+    let platforms: BTreeSet<&str> = alerts.iter().map(|alert| alert.platform.as_str()).collect();
+    if let Err(e) = throttle::mark_checked(&db, &throttles, platforms, run_started_at, CHECK_INTERVAL).await {
+        tracing::error!("Failed to schedule throttled platforms: {}", e);
+    }
+    
+    tracing::info!(
+        "Price check complete. Checked: {}, Drops detected: {}",
+        alerts_checked,
+        price_drops
+    );
     
-    // Initialize email service (optional - only if credentials are set)
-    let email_service = EmailService::from_env().ok();
+    check_listing_alerts(&db, notifiers, scraper_configs).await?;
+    check_keyword_watches(&db, notifiers, scraper_configs).await?;
+    
+    // Yesterday's emails held back by the daily budget
+    match notifiers.send_digests(&db).await {
+        Ok(0) => {}
+        Ok(sent) => tracing::info!("📬 Sent {} email digest(s)", sent),
+        Err(e) => tracing::error!("Failed to send email digests: {}", e),
+    }
+    
+    report(progress, CheckProgress::Finished { checked: alerts_checked, drops: price_drops });
+    Ok(())
+}
+
+/// Check one platform's alerts in order, pausing between requests to it, and return how
+/// many dropped. Runs alongside the other platforms' groups, so a slow or blocking site
+/// only holds up its own alerts.
+async fn check_platform(
+    db: &Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+    alerts: Vec<&PriceAlert>,
+    total: usize,
+    started: &AtomicUsize,
+    progress: Option<&ProgressSender>,
+) -> anyhow::Result<usize> {
+    let mut price_drops = 0;
+    let mut writes = PendingWrites::default();
+    let batch_size = write_batch_size();
     
     for alert in alerts {
-        alerts_checked += 1;
+        report(progress, CheckProgress::Checking {
+            index: started.fetch_add(1, Ordering::Relaxed) + 1,
+            total,
+            alert_id: alert.id,
+            url: alert.url.clone(),
+        });
         
-        // Get the appropriate scraper
-        let scraper = match create_scraper(&alert.platform) {
-            Some(s) => s,
-            None => {
-                tracing::warn!("Unknown platform: {}", alert.platform);
-                continue;
-            }
-        };
+        let check = check_alert_into(db, notifiers, scraper_configs, alert, progress, &mut writes).await;
+        // Keep what's already been checked even if this run is about to stop
+        if writes.len() >= batch_size || check.is_err() {
+            writes.flush(db, notifiers).await?;
+        }
+        
+        match check? {
+            AlertCheck::Price { dropped: true, .. } => price_drops += 1,
+            // Nothing was fetched, so there's no request to space out
+            AlertCheck::Failed { reason: "unsupported_platform" | "backing_off", .. } => continue,
+            _ => {}
+        }
         
-        // Scrape current price
-        match scraper.get_price(&alert.url).await {
-            Ok(current_price) => {
+        // Small delay to avoid rate limiting
+        tokio::time::sleep(scraper_configs.request_delay()).await;
+    }
+    writes.flush(db, notifiers).await?;
+    Ok(price_drops)
+}
+
+/// Scrape one alert, record the result in its history and notify on a drop (or a restock
+/// for `back_in_stock` alerts). Used by every check run and by `POST /alerts/:id/check`.
+pub async fn check_alert(
+    db: &Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+    alert: &PriceAlert,
+    progress: Option<&ProgressSender>,
+) -> anyhow::Result<AlertCheck> {
+    let mut writes = PendingWrites::default();
+    let check = check_alert_into(db, notifiers, scraper_configs, alert, progress, &mut writes).await;
+    writes.flush(db, notifiers).await?;
+    check
+}
+
+/// `check_alert`, leaving the price update in `writes` unless it's a drop
+async fn check_alert_into(
+    db: &Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+    alert: &PriceAlert,
+    progress: Option<&ProgressSender>,
+    writes: &mut PendingWrites,
+) -> anyhow::Result<AlertCheck> {
+    // Get the appropriate scraper
+    let Some(scraper) = scraper_configs.scraper(&alert.platform) else {
+        tracing::warn!("Unknown platform: {}", alert.platform);
+        let error = format!("Unknown platform: {}", alert.platform);
+        report(progress, CheckProgress::Failed { alert_id: alert.id, reason: "unsupported_platform", error: error.clone() });
+        return Ok(AlertCheck::Failed { reason: "unsupported_platform", error });
+    };
+    
+    // The platform told us to slow down; its alerts wait until it's over rather than add to it
+    if let Some(backoff) = scraper_configs.backoff().skip(&alert.platform) {
+        let error = format!("{} is rate limiting us; backing off until {}", alert.platform, backoff.until.to_rfc3339());
+        tracing::debug!("Skipping {}: {}", alert.url, error);
+        report(progress, CheckProgress::Failed { alert_id: alert.id, reason: "backing_off", error: error.clone() });
+        return Ok(AlertCheck::Failed { reason: "backing_off", error });
+    }
+    
+    if alert.alert_mode == AlertMode::BackInStock.as_str() {
+        return check_stock(db, notifiers, alert, scraper.as_ref(), scraper_configs, progress).await;
+    }
+    
+    // Scrape current price, recording how long it took and how it went
+    let started = Instant::now();
+    let result = within(scraper_configs.alert_deadline(), &alert.url, scraper.get_product(&desktop_url(&alert.url))).await;
+    record_attempt(db, scraper_configs, alert.id, &alert.platform, started.elapsed(), &result).await;
+    
+    match result {
+        // Alerts are only ever evaluated against the selling price; MRP is just recorded
+        Ok(ProductSnapshot { selling_price: current_price, mrp, offers, title, image_url, .. }) => {
+            // Notifications show the name and picture the page has now
+            let alert = &PriceAlert {
+                product_name: title.or_else(|| alert.product_name.clone()),
+                image_url: image_url.or_else(|| alert.image_url.clone()),
+                ..alert.clone()
+            };
+            
+            // A price outside the range the user gave, or far off the recent ones, is most
+            // likely a selector grabbing the wrong number. It's kept in history, flagged,
+            // but doesn't move the alert.
+            if !alert.in_bounds(current_price) {
+                let bound = |bound: Option<f64>| bound.map(|price| format!("₹{}", price)).unwrap_or_else(|| "any".to_string());
+                let error = format!("₹{} is outside the alert's bounds ({} to {})", current_price, bound(alert.min_price), bound(alert.max_price));
+                return Ok(flag_suspect(db, alert, current_price, mrp, "out_of_bounds", error, progress).await);
+            }
+            if let Some(median) = suspect_against(db, alert, current_price).await {
+                let error = format!("₹{} is far from the recent median of ₹{}", current_price, median);
+                return Ok(flag_suspect(db, alert, current_price, mrp, "suspect_price", error, progress).await);
+            }
+            
+            tracing::info!(
+                "Alert {}: Current=₹{}, Target=₹{}, Last=₹{:?}",
+                alert.id.map(|id| id.to_string()).unwrap_or_default(),
+                current_price,
+                alert.target_price,
+                alert.last_price
+            );
+            report(progress, CheckProgress::Price {
+                alert_id: alert.id,
+                price: current_price,
+                target_price: alert.target_price,
+                dropped: current_price <= alert.target_price,
+            });
+            
+            // Check if price dropped below target, and whether the alert wants to hear about it again
+            let dropped = current_price <= alert.target_price;
+            let notify = dropped && renotify_allowed(db, alert, current_price).await;
+            if dropped && !notify {
                 tracing::info!(
-                    "Alert {}: Current=₹{}, Target=₹{}, Last=₹{:?}",
+                    "Alert {}: ₹{} is still at or below target; held back by its renotify policy",
                     alert.id.map(|id| id.to_string()).unwrap_or_default(),
+                    current_price
+                );
+            }
+            
+            let mut badge = None;
+            if notify {
+                tracing::warn!(
+                    "🚨 ALARM! Price drop detected for {}: ₹{} <= ₹{} (Target)",
+                    alert.user_email,
                     current_price,
-                    alert.target_price,
-                    alert.last_price
+                    alert.target_price
                 );
                 
-                // Check if price dropped below target
-                if current_price <= alert.target_price {
-                    tracing::warn!(
-                        "🚨 ALARM! Price drop detected for {}: ₹{} <= ₹{} (Target)",
-                        alert.user_email,
-                        current_price,
-                        alert.target_price
-                    );
-                    price_drops += 1;
-                    
-                    // Send email notification if service is configured
-                    if let Some(ref email_svc) = email_service {
-                        match email_svc.send_price_drop_alert(
-                            &alert.user_email,
-                            &alert.url,
-                            current_price,
-                            alert.target_price,
-                            &alert.platform
-                        ).await {
-                            Ok(_) => tracing::info!("📧 Email sent to {}", alert.user_email),
-                            Err(e) => tracing::error!("Failed to send email: {}", e),
-                        }
-                    } else {
-                        tracing::warn!("Email service not configured - skipping notification");
+                // Compare against history before this check is saved into it
+                if let Some(id) = alert.id {
+                    let since = Utc::now() - chrono::Duration::days(PriceLows::RECENT_DAYS);
+                    match db.get_price_lows(id, since).await {
+                        Ok(lows) => badge = lows.badge(current_price),
+                        Err(e) => tracing::error!("Failed to load price lows: {}", e),
                     }
                 }
+            }
+            
+            // Update alert with new price
+            let mut outboxed = None;
+            if let Some(id) = alert.id {
+                let checked_at = Utc::now();
+                let checked = PriceChecked {
+                    alert_id: id,
+                    user_id: alert.user_id,
+                    url: alert.url.clone(),
+                    platform: alert.platform.clone(),
+                    price: current_price,
+                    target_price: alert.target_price,
+                    checked_at,
+                };
                 
-                // Update alert with new price
-                if let Some(id) = alert.id {
-                    db.update_alert_price(id, current_price).await?;
+                if notify {
+                    // The drop, its snapshot and its notification are written together, so
+                    // a crash from here on can delay the notification but not lose it
+                    let (event, entry) = db.save_price_drop(alert, current_price, mrp, &offers, badge, outbox::claim()).await?;
+                    tracing::debug!("💾 Saved price snapshot: ₹{}", current_price);
                     
-                    // Save price snapshot to history for tracking trends
-                    if let Err(e) = db.save_price_snapshot(id, current_price).await {
-                        tracing::error!("Failed to save price history: {}", e);
-                    } else {
-                        tracing::debug!("💾 Saved price snapshot: ₹{}", current_price);
+                    let note = format!("₹{} reached the target of ₹{}", current_price, alert.target_price);
+                    if let Err(e) = db.create_annotation(event.alert_id, None, AnnotationKind::TargetReached, Some(&note), event.history_id, event.created_at).await {
+                        tracing::error!("Failed to annotate price drop: {}", e);
                     }
+                    outboxed = Some(entry);
+                    
+                    notifiers.events().publish(&LiveEvent::PriceChecked(checked)).await;
+                } else {
+                    let update = PriceUpdate {
+                        alert_id: id,
+                        price: current_price,
+                        mrp,
+                        offers: offers.clone(),
+                        product_name: alert.product_name.clone(),
+                        image_url: alert.image_url.clone(),
+                        checked_at,
+                    };
+                    writes.push(update, checked);
                 }
             }
+            
+            let mut delivered = 0;
+            
+            // Ladder rungs above the target ping once each. A check that reaches the target
+            // uses them up quietly, since the drop notification already covers them.
+            if let Some(id) = alert.id {
+                match db.take_reached_thresholds(id, current_price).await {
+                    Ok(rungs) if !dropped => {
+                        if let Some(rung) = rungs.into_iter().reduce(f64::min) {
+                            delivered += notify_threshold(db, notifiers, alert, current_price, rung, offers.clone()).await;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Failed to check price thresholds: {}", e),
+                }
+            }
+            
+            if notify {
+                // Notify through the user's configured channels
+                delivered = match &outboxed {
+                    Some(entry) => outbox::send(db, notifiers, entry).await,
+                    // Not a saved alert, so there's no drop to record
+                    None => {
+                        let event = NotificationEvent::PriceDrop(PriceDrop {
+                            alert_id: alert.id,
+                            user_id: alert.user_id,
+                            user_email: alert.user_email.clone(),
+                            url: alert.url.clone(),
+                            platform: alert.platform.clone(),
+                            current_price,
+                            target_price: alert.target_price,
+                            previous_price: alert.last_price,
+                            offers,
+                            badge,
+                            product_name: alert.product_name.clone(),
+                            image_url: alert.image_url.clone(),
+                        });
+                        notifiers.dispatch(db, &event, alert.notify_channels.as_deref()).await.unwrap_or_else(|e| {
+                            tracing::error!("Failed to dispatch notifications: {}", e);
+                            0
+                        })
+                    }
+                };
+                if delivered == 0 {
+                    tracing::warn!("No notification channel delivered the drop for {}", alert.user_email);
+                }
+            }
+            
+            Ok(AlertCheck::Price { price: current_price, mrp, target_price: alert.target_price, dropped, badge, notified: delivered })
+        }
+        Err(e) => {
+            tracing::error!("Failed to scrape {}: {}", alert.url, e);
+            report_failure(notifiers, progress, alert, &e).await;
+            count_page_gone(db, notifiers, alert, &e).await;
+            Ok(AlertCheck::Failed { reason: classify_error(&e), error: e.to_string() })
+        }
+    }
+}
+
+/// Save a price the check doesn't trust as suspect, and report the check as failed
+async fn flag_suspect(
+    db: &Database,
+    alert: &PriceAlert,
+    price: f64,
+    mrp: Option<f64>,
+    reason: &'static str,
+    error: String,
+    progress: Option<&ProgressSender>,
+) -> AlertCheck {
+    tracing::warn!("Alert {}: {}; saving it as suspect", alert.id.map(|id| id.to_string()).unwrap_or_default(), error);
+    let saved = match alert.id {
+        Some(id) => db.save_suspect_price(id, price, mrp).await.map(|_| ()),
+        None => Ok(()),
+    };
+    if let Err(e) = saved {
+        tracing::error!("Failed to save suspect price: {}", e);
+    }
+    report(progress, CheckProgress::Failed { alert_id: alert.id, reason, error: error.clone() });
+    AlertCheck::Failed { reason, error }
+}
+
+/// The recent median `price` is an anomaly against, if it is one
+async fn suspect_against(db: &Database, alert: &PriceAlert, price: f64) -> Option<f64> {
+    let factor = anomaly_factor();
+    let id = alert.id.filter(|_| factor > 1.0)?;
+    let median = match db.get_recent_median_price(id, ANOMALY_WINDOW, ANOMALY_MIN_POINTS).await {
+        Ok(median) => median?,
+        Err(e) => {
+            tracing::error!("Failed to load recent prices: {}", e);
+            return None;
+        }
+    };
+    is_price_anomaly(price, median, factor).then_some(median)
+}
+
+/// Send a ladder ping for `rung`, one of the thresholds above the alert's target.
+/// Returns how many channels delivered it.
+async fn notify_threshold(
+    db: &Database,
+    notifiers: &NotifierRegistry,
+    alert: &PriceAlert,
+    price: f64,
+    rung: f64,
+    offers: Vec<Offer>,
+) -> usize {
+    tracing::info!("🪜 Price ladder for {}: ₹{} <= ₹{}", alert.user_email, price, rung);
+    let event = NotificationEvent::PriceDrop(PriceDrop {
+        alert_id: alert.id,
+        user_id: alert.user_id,
+        user_email: alert.user_email.clone(),
+        url: alert.url.clone(),
+        platform: alert.platform.clone(),
+        current_price: price,
+        target_price: rung,
+        previous_price: alert.last_price,
+        offers,
+        badge: None,
+        product_name: alert.product_name.clone(),
+        image_url: alert.image_url.clone(),
+    });
+    match notifiers.dispatch(db, &event, alert.notify_channels.as_deref()).await {
+        Ok(0) => {
+            tracing::warn!("No notification channel delivered the ladder ping for {}", alert.user_email);
+            0
+        }
+        Ok(count) => count,
+        Err(e) => {
+            tracing::error!("Failed to dispatch notifications: {}", e);
+            0
+        }
+    }
+}
+
+/// Whether the alert's renotify policy lets a price at or below target through. Held-back
+/// drops are saved like any other price, but not recorded as drops or sent.
+async fn renotify_allowed(db: &Database, alert: &PriceAlert, price: f64) -> bool {
+    let policy = alert.renotify.0;
+    let Some(id) = alert.id.filter(|_| policy != RenotifyPolicy::Always) else {
+        return true;
+    };
+    let last_drop = match db.get_price_drops_for_alert(id, None, 1).await {
+        Ok(drops) => drops.into_iter().next(),
+        Err(e) => {
+            tracing::error!("Failed to load the last price drop: {}", e);
+            None
+        }
+    };
+    policy.should_notify(last_drop.as_ref(), alert.last_price, alert.target_price, price, Utc::now())
+}
+
+/// Drop alerts checked more recently than their owner's plan allows, so manual
+/// runs can't re-scrape a free account's alerts on every click
+async fn due_alerts(db: &Database, alerts: Vec<PriceAlert>) -> anyhow::Result<Vec<PriceAlert>> {
+    let plans = db.get_plans_of_alert_owners().await?;
+    let now = Utc::now();
+    let (due, waiting): (Vec<_>, Vec<_>) = alerts.into_iter().partition(|alert| {
+        let plan = alert.user_id.and_then(|id| plans.get(&id)).copied().unwrap_or_default();
+        plan.limits().is_due(alert, now)
+    });
+    if !waiting.is_empty() {
+        tracing::info!("Skipping {} alert(s) checked within their plan's minimum interval", waiting.len());
+    }
+    Ok(due)
+}
+
+/// Give up on a scrape that outlives `deadline`, so one hanging site can't stall the whole run
+async fn within<T>(
+    deadline: Duration,
+    url: &str,
+    scrape: impl std::future::Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    match tokio::time::timeout(deadline, scrape).await {
+        Ok(result) => result,
+        Err(elapsed) => Err(anyhow::Error::new(elapsed).context(format!("Timed out after {:?} scraping {}", deadline, url))),
+    }
+}
+
+/// Evaluate a `back_in_stock` alert: notify only on an out-of-stock -> in-stock transition
+async fn check_stock(
+    db: &Database,
+    notifiers: &NotifierRegistry,
+    alert: &PriceAlert,
+    scraper: &dyn PriceScraper,
+    scraper_configs: &ScraperConfigStore,
+    progress: Option<&ProgressSender>,
+) -> anyhow::Result<AlertCheck> {
+    let deadline = scraper_configs.alert_deadline();
+    let started = Instant::now();
+    let result = within(deadline, &alert.url, scraper.get_availability(&desktop_url(&alert.url))).await;
+    record_attempt(db, scraper_configs, alert.id, &alert.platform, started.elapsed(), &result).await;
+    
+    let in_stock = match result {
+        Ok(in_stock) => in_stock,
+        Err(e) => {
+            tracing::error!("Failed to check stock for {}: {}", alert.url, e);
+            report_failure(notifiers, progress, alert, &e).await;
+            count_page_gone(db, notifiers, alert, &e).await;
+            return Ok(AlertCheck::Failed { reason: classify_error(&e), error: e.to_string() });
+        }
+    };
+    report(progress, CheckProgress::Stock { alert_id: alert.id, in_stock });
+    
+    let restocked = alert.is_restock(in_stock);
+    let mut notified = 0;
+    if restocked {
+        tracing::warn!("📦 Back in stock for {}: {}", alert.user_email, alert.url);
+        
+        let event = NotificationEvent::BackInStock(BackInStock {
+            alert_id: alert.id,
+            user_id: alert.user_id,
+            user_email: alert.user_email.clone(),
+            url: alert.url.clone(),
+            platform: alert.platform.clone(),
+            current_price: within(deadline, &alert.url, scraper.get_price(&desktop_url(&alert.url))).await.ok(),
+        });
+        match notifiers.dispatch(db, &event, alert.notify_channels.as_deref()).await {
+            Ok(0) => tracing::warn!("No notification channel delivered the restock for {}", alert.user_email),
+            Ok(count) => notified = count,
+            Err(e) => tracing::error!("Failed to dispatch notifications: {}", e),
+        }
+    }
+    
+    if let Some(id) = alert.id {
+        db.update_alert_stock(id, in_stock).await?;
+    }
+    
+    Ok(AlertCheck::Stock { in_stock, restocked, notified })
+}
+
+/// Scrape each watched search/category page and notify about new results under target
+async fn check_listing_alerts(
+    db: &Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+) -> anyhow::Result<()> {
+    let alerts = db.get_all_active_listing_alerts().await?;
+    
+    for alert in alerts {
+        let Some(scraper) = scraper_configs.scraper(&alert.platform) else {
+            tracing::warn!("Unknown platform: {}", alert.platform);
+            continue;
+        };
+        if scraper_configs.backoff().skip(&alert.platform).is_some() {
+            continue;
+        }
+        
+        let started = Instant::now();
+        let result = within(scraper_configs.alert_deadline(), &alert.url, scraper.get_listings(&alert.url)).await;
+        record_attempt(db, scraper_configs, None, &alert.platform, started.elapsed(), &result).await;
+        
+        let items = match result {
+            Ok(items) => items,
             Err(e) => {
-                tracing::error!("Failed to scrape {}: {}", alert.url, e);
+                tracing::error!("Failed to scrape listing {}: {}", alert.url, e);
+                continue;
             }
+        };
+        
+        let matching = alert.matching(&items);
+        if let Some(min_price) = matching.iter().map(|item| item.price).reduce(f64::min) {
+            db.save_listing_price(alert.id, min_price, matching.len() as i32).await?;
         }
         
-        // Small delay to avoid rate limiting
-        tokio::time::sleep(Duration::from_secs(2)).await;
+        let matches: Vec<_> = alert.new_matches(&items).into_iter().cloned().collect();
+        let notified: Vec<String> = matches.iter().map(|item| item.url.clone()).collect();
+        
+        if !matches.is_empty() {
+            tracing::warn!(
+                "🚨 {} listing result(s) under ₹{} for {}",
+                matches.len(),
+                alert.target_price,
+                alert.user_email
+            );
+            
+            let event = NotificationEvent::ListingMatch(ListingMatch {
+                listing_alert_id: alert.id,
+                user_id: alert.user_id,
+                user_email: alert.user_email.clone(),
+                url: alert.url.clone(),
+                platform: alert.platform.clone(),
+                target_price: alert.target_price,
+                items: matches,
+            });
+            match notifiers.dispatch(db, &event, None).await {
+                Ok(0) => tracing::warn!("No notification channel delivered the listing match for {}", alert.user_email),
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to dispatch notifications: {}", e),
+            }
+        }
+        
+        db.mark_listing_alert_checked(alert.id, &notified).await?;
+        
+        tokio::time::sleep(scraper_configs.request_delay()).await;
     }
     
-    tracing::info!(
-        "Price check complete. Checked: {}, Drops detected: {}",
-        alerts_checked,
-        price_drops
-    );
+    Ok(())
+}
+
+/// Search each watch's platforms for its query and notify about new results under target
+async fn check_keyword_watches(
+    db: &Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+) -> anyhow::Result<()> {
+    let watches = db.get_all_active_keyword_watches().await?;
+    
+    // Popular queries are shared between users; search each page once per run
+    let mut searched: HashMap<String, Vec<ListingItem>> = HashMap::new();
+    
+    for watch in watches {
+        let platforms: Vec<&str> = if watch.platforms.is_empty() {
+            PLATFORMS.to_vec()
+        } else {
+            watch.platforms.iter().map(String::as_str).collect()
+        };
+        let mut notified = Vec::new();
+        
+        for platform in platforms {
+            let Some(scraper) = scraper_configs.scraper(platform) else {
+                continue;
+            };
+            let Some(search_url) = scraper.search_url(&watch.query) else {
+                continue;
+            };
+            
+            if !searched.contains_key(&search_url) {
+                if scraper_configs.backoff().skip(platform).is_some() {
+                    continue;
+                }
+                let started = Instant::now();
+                let result = within(scraper_configs.alert_deadline(), &search_url, scraper.get_listings(&search_url)).await;
+                record_attempt(db, scraper_configs, None, platform, started.elapsed(), &result).await;
+                tokio::time::sleep(scraper_configs.request_delay()).await;
+                
+                match result {
+                    Ok(items) => {
+                        searched.insert(search_url.clone(), items);
+                    }
+                    Err(e) => {
+                        tracing::error!("Failed to search {} for \"{}\": {}", platform, watch.query, e);
+                        continue;
+                    }
+                }
+            }
+            
+            let matches: Vec<ListingItem> = watch.new_matches(&searched[&search_url]).into_iter().cloned().collect();
+            if matches.is_empty() {
+                continue;
+            }
+            
+            tracing::warn!(
+                "🚨 \"{}\" found under ₹{} on {} for {}",
+                watch.query,
+                watch.target_price,
+                platform,
+                watch.user_email
+            );
+            notified.extend(matches.iter().map(|item| item.url.clone()));
+            
+            let event = NotificationEvent::KeywordMatch(KeywordMatch {
+                watch_id: watch.id,
+                user_id: watch.user_id,
+                user_email: watch.user_email.clone(),
+                query: watch.query.clone(),
+                platform: platform.to_string(),
+                search_url,
+                target_price: watch.target_price,
+                items: matches,
+            });
+            match notifiers.dispatch(db, &event, None).await {
+                Ok(0) => tracing::warn!("No notification channel delivered the keyword match for {}", watch.user_email),
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to dispatch notifications: {}", e),
+            }
+        }
+        
+        db.mark_keyword_watch_checked(watch.id, &notified).await?;
+    }
     
     Ok(())
 }
 
-/// Manual trigger for testing (can be exposed via API)
-pub async fn trigger_manual_check(db: Database) -> anyhow::Result<String> {
-    check_all_alerts(db).await?;
+/// Tell whoever triggered the run, and the user's live event stream, that a scrape failed
+async fn report_failure(
+    notifiers: &NotifierRegistry,
+    progress: Option<&ProgressSender>,
+    alert: &PriceAlert,
+    error: &anyhow::Error,
+) {
+    let reason = classify_error(error);
+    report(progress, CheckProgress::Failed {
+        alert_id: alert.id,
+        reason,
+        error: error.to_string(),
+    });
+    
+    if let Some(alert_id) = alert.id {
+        notifiers.events().publish(&LiveEvent::AlertFailed(AlertFailed {
+            alert_id,
+            user_id: alert.user_id,
+            url: alert.url.clone(),
+            platform: alert.platform.clone(),
+            reason: reason.to_string(),
+            error: error.to_string(),
+        })).await;
+    }
+}
+
+/// Count a 404/410 towards the alert's product being delisted. The check that reaches
+/// `product_removed_after()` in a row marks it removed, which stops its checks, and
+/// tells the owner; any successful read starts the count over.
+async fn count_page_gone(db: &Database, notifiers: &NotifierRegistry, alert: &PriceAlert, error: &anyhow::Error) {
+    let Some(id) = alert.id.filter(|_| error.is::<PageGone>()) else {
+        return;
+    };
+    let limit = product_removed_after();
+    match db.record_page_gone(id, limit).await {
+        Ok(false) => {}
+        Ok(true) => {
+            tracing::warn!("🚫 Product removed, no longer checking {}", alert.url);
+            let note = format!("The product page answered 404/410 on {} checks in a row", limit);
+            if let Err(e) = db.create_annotation(id, None, AnnotationKind::Paused, Some(&note), None, Utc::now()).await {
+                tracing::error!("Failed to annotate removed product: {}", e);
+            }
+            let event = NotificationEvent::ProductRemoved(ProductRemoved {
+                alert_id: alert.id,
+                user_id: alert.user_id,
+                user_email: alert.user_email.clone(),
+                url: alert.url.clone(),
+                platform: alert.platform.clone(),
+                last_price: alert.last_price,
+            });
+            if let Err(e) = notifiers.dispatch(db, &event, alert.notify_channels.as_deref()).await {
+                tracing::error!("Failed to dispatch notifications: {}", e);
+            }
+        }
+        Err(e) => tracing::error!("Failed to record missing page for {}: {}", alert.url, e),
+    }
+}
+
+/// Persist one scrape attempt and update the in-process metrics. A rate-limited
+/// response backs the whole platform off; any other answer ends its backoff.
+async fn record_attempt<T>(
+    db: &Database,
+    scraper_configs: &ScraperConfigStore,
+    alert_id: Option<uuid::Uuid>,
+    platform: &str,
+    elapsed: Duration,
+    result: &anyhow::Result<T>,
+) {
+    let (outcome, error_kind) = match result {
+        Ok(_) => ("success", None),
+        Err(e) => ("failure", Some(classify_error(e))),
+    };
+    
+    match result.as_ref().err().and_then(|e| e.downcast_ref::<RateLimited>()) {
+        Some(limited) => {
+            let backoff = scraper_configs.backoff().record(platform, limited);
+            tracing::warn!("⏳ {} rate limited us ({}); backing off until {}", platform, limited.status, backoff.until.to_rfc3339());
+        }
+        None => scraper_configs.backoff().clear(platform),
+    }
+    
+    scrape_metrics().record(platform, elapsed, outcome, error_kind);
+    
+    let duration_ms = elapsed.as_millis().min(i32::MAX as u128) as i32;
+    if let Err(e) = db.record_scrape_attempt(alert_id, platform, duration_ms, outcome, error_kind).await {
+        tracing::error!("Failed to record scrape attempt: {}", e);
+    }
+}
+
+/// Check one user's due alerts right away. Unlike the scheduled run it leaves the
+/// checkpoint, platform throttles and the site-wide listing/keyword/digest passes alone,
+/// so progress only ever covers that user's alerts.
+async fn check_user_alerts(
+    db: Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+    user_id: uuid::Uuid,
+    progress: Option<&ProgressSender>,
+) -> anyhow::Result<()> {
+    let alerts: Vec<PriceAlert> = db.get_alerts_by_user(user_id)
+        .await?
+        .into_iter()
+        .filter(|alert| alert.product_removed_at.is_none())
+        .collect();
+    let alerts = due_alerts(&db, alerts).await?;
+    
+    let total = alerts.len();
+    report(progress, CheckProgress::Started { total });
+    
+    let mut by_platform: BTreeMap<&str, Vec<&PriceAlert>> = BTreeMap::new();
+    for alert in &alerts {
+        by_platform.entry(alert.platform.as_str()).or_default().push(alert);
+    }
+    let started = AtomicUsize::new(0);
+    let groups = by_platform
+        .into_values()
+        .map(|alerts| check_platform(&db, notifiers, scraper_configs, alerts, total, &started, progress));
+    
+    let mut price_drops = 0;
+    for drops in futures::future::join_all(groups).await {
+        price_drops += drops?;
+    }
+    
+    report(progress, CheckProgress::Finished { checked: started.into_inner(), drops: price_drops });
+    Ok(())
+}
+
+/// Manual trigger behind the API, optionally reporting progress: every active alert,
+/// or only `user_id`'s when given
+pub async fn trigger_manual_check(
+    db: Database,
+    notifiers: &NotifierRegistry,
+    scraper_configs: &ScraperConfigStore,
+    user_id: Option<uuid::Uuid>,
+    progress: Option<&ProgressSender>,
+) -> anyhow::Result<String> {
+    let check = match user_id {
+        Some(user_id) => check_user_alerts(db, notifiers, scraper_configs, user_id, progress).await,
+        None => check_all_alerts(db, notifiers, scraper_configs, progress).await,
+    };
+    if let Err(e) = check {
+        report(progress, CheckProgress::Aborted { error: e.to_string() });
+        return Err(e);
+    }
     Ok("Price check completed".to_string())
 }
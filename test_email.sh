@@ -1,7 +1,7 @@
 #!/bin/bash
 
 # Test Email Notifications Script
-# Usage: ./test_email.sh your-email@example.com
+# Usage: TOKEN=<admin token> ./test_email.sh your-email@example.com
 
 EMAIL=${1:-"test@example.com"}
 
@@ -10,8 +10,9 @@ echo "================================"
 echo "Sending test email to: $EMAIL"
 echo
 
-# Test endpoint
-curl -X POST http://localhost:3000/email/test \
+# Any recipient needs an admin token; POST /email/test only mails your own address
+curl -X POST http://localhost:3000/admin/email/test \
+  -H "Authorization: Bearer $TOKEN" \
   -H "Content-Type: application/json" \
   -d "{\"email\": \"$EMAIL\"}" \
   | jq .